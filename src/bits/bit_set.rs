@@ -0,0 +1,253 @@
+//! This module implements a growable bit set, a sequence of bits packed into 64-bit words.
+//! It is the storage used by the succinct structures of the crate, which read its words directly.
+//!
+//! # Performance
+//! - O(1) for `get`, `set` and amortized O(1) for `push`
+//! - O(n / 64) for `count_ones`
+//! - 1 bit per element, plus at most 63 unused bits in the last word
+//!
+//! # Implementation Details
+//! - Bit `i` is bit `i % 64` (from the least significant one) of word `i / 64`.
+//! - The unused bits of the last word are always zero, so whole words can be counted without masking.
+//!
+//! # Usage
+//! ```
+//! use data_structures::bits::bit_set::BitSet;
+//!
+//! let mut bits: BitSet = [true, false, true].into_iter().collect();
+//! bits.push(true);
+//! bits.set(1, true).unwrap();
+//!
+//! assert_eq!(bits.len(), 4);
+//! assert_eq!(bits.count_ones(), 4);
+//! assert_eq!(bits.get(1), Some(true));
+//! assert_eq!(bits.get(4), None);
+//! ```
+//!
+use crate::error::DataStructureError;
+
+/// Struct representing a sequence of bits
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    /// Create a new empty bit set
+    ///
+    /// # Returns
+    /// A new BitSet instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    ///
+    /// let bits = BitSet::new();
+    /// assert!(bits.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        BitSet {
+            words: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Create a bit set with all its bits cleared
+    /// # Arguments
+    /// * `len`: The number of bits
+    /// # Returns
+    /// A new BitSet instance with `len` zero bits
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    ///
+    /// let bits = BitSet::with_len(100);
+    /// assert_eq!(bits.len(), 100);
+    /// assert_eq!(bits.count_ones(), 0);
+    /// ```
+    pub fn with_len(len: usize) -> Self {
+        BitSet {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    /// Get the number of bits
+    /// # Returns
+    /// The number of bits in the set, set or not
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    ///
+    /// let mut bits = BitSet::new();
+    /// bits.push(false);
+    /// assert_eq!(bits.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the bit set has no bits
+    /// # Returns
+    /// True if the length is zero
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    ///
+    /// assert!(BitSet::with_len(0).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Append a bit at the end
+    /// # Arguments
+    /// * `bit`: The value of the new bit
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    ///
+    /// let mut bits = BitSet::new();
+    /// bits.push(true);
+    /// assert_eq!(bits.get(0), Some(true));
+    /// ```
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        self.words[self.len / 64] |= u64::from(bit) << (self.len % 64);
+        self.len += 1;
+    }
+
+    /// Get a bit
+    /// # Arguments
+    /// * `index`: The position of the bit
+    /// # Returns
+    /// The value of the bit, or None if the index is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    ///
+    /// let bits: BitSet = [false, true].into_iter().collect();
+    /// assert_eq!(bits.get(1), Some(true));
+    /// assert_eq!(bits.get(2), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        Some(self.words[index / 64] >> (index % 64) & 1 == 1)
+    }
+
+    /// Set or clear a bit
+    /// # Arguments
+    /// * `index`: The position of the bit
+    /// * `bit`: The new value of the bit
+    /// # Returns
+    /// Ok, or an error if the index is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    /// use data_structures::error::DataStructureError;
+    ///
+    /// let mut bits = BitSet::with_len(3);
+    /// assert!(bits.set(2, true).is_ok());
+    /// assert_eq!(bits.set(3, true), Err(DataStructureError::IndexOutOfBounds { index: 3, len: 3 }));
+    /// assert_eq!(bits.get(2), Some(true));
+    /// ```
+    pub fn set(&mut self, index: usize, bit: bool) -> Result<(), DataStructureError> {
+        if index >= self.len {
+            return Err(DataStructureError::IndexOutOfBounds {
+                index,
+                len: self.len,
+            });
+        }
+
+        let mask = 1 << (index % 64);
+        if bit {
+            self.words[index / 64] |= mask;
+        } else {
+            self.words[index / 64] &= !mask;
+        }
+        Ok(())
+    }
+
+    /// Count the set bits
+    /// # Returns
+    /// The number of bits equal to one
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    ///
+    /// let bits: BitSet = [true, false, true, true].into_iter().collect();
+    /// assert_eq!(bits.count_ones(), 3);
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Get an iterator over the positions of the set bits
+    /// # Returns
+    /// A lazy iterator yielding the indexes of the ones, in increasing order
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    ///
+    /// let bits: BitSet = [false, true, false, true].into_iter().collect();
+    /// assert_eq!(bits.iter_ones().collect::<Vec<_>>(), vec![1, 3]);
+    /// ```
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, &word)| {
+            let mut rest = word;
+            std::iter::from_fn(move || {
+                if rest == 0 {
+                    return None;
+                }
+                let bit = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                Some(index * 64 + bit)
+            })
+        })
+    }
+
+    // The packed words, with the unused bits of the last one cleared
+    pub(crate) fn words(&self) -> &[u64] {
+        &self.words
+    }
+}
+
+impl FromIterator<bool> for BitSet {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = BitSet::new();
+        for bit in iter {
+            bits.push(bit);
+        }
+        bits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_set_across_words() {
+        let mut bits = BitSet::with_len(130);
+
+        for index in [0, 63, 64, 127, 129] {
+            bits.set(index, true).unwrap();
+        }
+        bits.set(63, false).unwrap();
+
+        assert_eq!(bits.words().len(), 3);
+        assert_eq!(bits.count_ones(), 4);
+        assert_eq!(bits.iter_ones().collect::<Vec<_>>(), vec![0, 64, 127, 129]);
+        assert_eq!(bits.get(63), Some(false));
+        assert_eq!(bits.get(130), None);
+    }
+}
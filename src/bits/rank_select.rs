@@ -0,0 +1,342 @@
+//! This module implements a rank/select dictionary over a bit set, the building block of succinct structures.
+//! `rank1(i)` counts the ones before position `i`, and `select1(k)` finds the position of the k-th one; `rank0` and
+//! `select0` do the same for the zeros. Both are answered from a small index built once over the frozen bits.
+//!
+//! # Performance
+//! - O(1) for `rank1` and `rank0`: one table lookup and at most 8 word popcounts
+//! - O(log n) for `select1` and `select0`: a binary search over the blocks, then at most 8 words to scan
+//! - The index takes 64 bits for every 512 bits of input, an eighth of the size of the bit set
+//!
+//! # Implementation Details
+//! - The bits are split in blocks of 512 (8 words), and the index stores the number of ones before each block.
+//! - Inside a word, the k-th one is found by clearing the k lowest ones and taking the trailing zeros.
+//! - The structure owns its bit set and exposes it read-only, since any change would invalidate the index.
+//!
+//! # Usage
+//! ```
+//! use data_structures::bits::bit_set::BitSet;
+//! use data_structures::bits::rank_select::RankSelect;
+//!
+//! let bits: BitSet = [true, false, true, true, false].into_iter().collect();
+//! let index = RankSelect::new(bits);
+//!
+//! assert_eq!(index.rank1(3), 2);
+//! assert_eq!(index.rank0(5), 2);
+//! assert_eq!(index.select1(2), Some(3));
+//! assert_eq!(index.select0(1), Some(4));
+//! assert_eq!(index.select1(3), None);
+//! ```
+//!
+use crate::bits::bit_set::BitSet;
+
+// Words per block of the rank index
+const BLOCK_WORDS: usize = 8;
+const BLOCK_BITS: usize = BLOCK_WORDS * 64;
+
+/// Struct representing a bit set with constant-time rank and logarithmic-time select
+///
+#[derive(Debug, Clone)]
+pub struct RankSelect {
+    bits: BitSet,
+    blocks: Vec<u64>, // ones before each block, plus the total at the end
+}
+
+// Position of the k-th one (from zero) in a word that has more than k ones
+fn select_in_word(mut word: u64, k: usize) -> usize {
+    for _ in 0..k {
+        word &= word - 1;
+    }
+    word.trailing_zeros() as usize
+}
+
+impl RankSelect {
+    /// Build the index over a bit set
+    ///
+    /// # Arguments
+    /// * `bits`: The bits to index, which can no longer be changed
+    ///
+    /// # Returns
+    /// A new RankSelect instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    /// use data_structures::bits::rank_select::RankSelect;
+    ///
+    /// let index = RankSelect::new(BitSet::with_len(10));
+    /// assert_eq!(index.len(), 10);
+    /// ```
+    pub fn new(bits: BitSet) -> Self {
+        let mut blocks = Vec::with_capacity(bits.words().len() / BLOCK_WORDS + 2);
+        let mut ones = 0;
+
+        for block in bits.words().chunks(BLOCK_WORDS) {
+            blocks.push(ones);
+            ones += block
+                .iter()
+                .map(|word| u64::from(word.count_ones()))
+                .sum::<u64>();
+        }
+        blocks.push(ones);
+
+        RankSelect { bits, blocks }
+    }
+
+    /// Get the indexed bits
+    /// # Returns
+    /// A reference to the bit set
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    /// use data_structures::bits::rank_select::RankSelect;
+    ///
+    /// let index = RankSelect::new([true].into_iter().collect());
+    /// assert_eq!(index.bits().get(0), Some(true));
+    /// ```
+    pub fn bits(&self) -> &BitSet {
+        &self.bits
+    }
+
+    /// Get the number of bits
+    /// # Returns
+    /// The length of the indexed bit set
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    /// use data_structures::bits::rank_select::RankSelect;
+    ///
+    /// let index = RankSelect::new([true, false].into_iter().collect());
+    /// assert_eq!(index.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Check if there are no bits
+    /// # Returns
+    /// True if the indexed bit set is empty
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    /// use data_structures::bits::rank_select::RankSelect;
+    ///
+    /// assert!(RankSelect::new(BitSet::new()).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Get the number of ones, in constant time
+    /// # Returns
+    /// The number of set bits
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    /// use data_structures::bits::rank_select::RankSelect;
+    ///
+    /// let index = RankSelect::new([true, false, true].into_iter().collect());
+    /// assert_eq!(index.count_ones(), 2);
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        *self.blocks.last().unwrap() as usize
+    }
+
+    /// Count the ones before a position
+    /// # Arguments
+    /// * `index`: The end of the counted range, exclusive. Positions past the end are clamped to the length.
+    /// # Returns
+    /// The number of ones in `[0, index)`
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    /// use data_structures::bits::rank_select::RankSelect;
+    ///
+    /// let index = RankSelect::new([true, true, false, true].into_iter().collect());
+    ///
+    /// assert_eq!(index.rank1(0), 0);
+    /// assert_eq!(index.rank1(2), 2);
+    /// assert_eq!(index.rank1(10), 3);
+    /// ```
+    pub fn rank1(&self, index: usize) -> usize {
+        let index = index.min(self.len());
+        let words = self.bits.words();
+        let block = index / BLOCK_BITS;
+
+        let mut rank = self.blocks[block] as usize;
+        for word in &words[block * BLOCK_WORDS..index / 64] {
+            rank += word.count_ones() as usize;
+        }
+
+        if !index.is_multiple_of(64) {
+            let mask = (1u64 << (index % 64)) - 1;
+            rank += (words[index / 64] & mask).count_ones() as usize;
+        }
+
+        rank
+    }
+
+    /// Count the zeros before a position
+    /// # Arguments
+    /// * `index`: The end of the counted range, exclusive. Positions past the end are clamped to the length.
+    /// # Returns
+    /// The number of zeros in `[0, index)`
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    /// use data_structures::bits::rank_select::RankSelect;
+    ///
+    /// let index = RankSelect::new([true, false, false, true].into_iter().collect());
+    /// assert_eq!(index.rank0(3), 2);
+    /// ```
+    pub fn rank0(&self, index: usize) -> usize {
+        let index = index.min(self.len());
+        index - self.rank1(index)
+    }
+
+    /// Find the position of a one by its rank
+    /// # Arguments
+    /// * `rank`: The number of ones before the wanted one, 0 for the first one
+    /// # Returns
+    /// The position of the one, or None if there are not enough ones
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    /// use data_structures::bits::rank_select::RankSelect;
+    ///
+    /// let index = RankSelect::new([false, true, false, true].into_iter().collect());
+    ///
+    /// assert_eq!(index.select1(0), Some(1));
+    /// assert_eq!(index.select1(1), Some(3));
+    /// assert_eq!(index.select1(2), None);
+    /// ```
+    pub fn select1(&self, rank: usize) -> Option<usize> {
+        if rank >= self.count_ones() {
+            return None;
+        }
+
+        let ones_before = |block: usize| self.blocks[block] as usize;
+        let block = self.last_block_before(|block| ones_before(block) <= rank);
+
+        self.select_from(block, rank - ones_before(block), |word| word)
+    }
+
+    /// Find the position of a zero by its rank
+    /// # Arguments
+    /// * `rank`: The number of zeros before the wanted one, 0 for the first zero
+    /// # Returns
+    /// The position of the zero, or None if there are not enough zeros
+    /// # Example
+    /// ```
+    /// use data_structures::bits::bit_set::BitSet;
+    /// use data_structures::bits::rank_select::RankSelect;
+    ///
+    /// let index = RankSelect::new([true, false, true, false].into_iter().collect());
+    ///
+    /// assert_eq!(index.select0(0), Some(1));
+    /// assert_eq!(index.select0(1), Some(3));
+    /// assert_eq!(index.select0(2), None);
+    /// ```
+    pub fn select0(&self, rank: usize) -> Option<usize> {
+        if rank >= self.len() - self.count_ones() {
+            return None;
+        }
+
+        let zeros_before = |block: usize| block * BLOCK_BITS - self.blocks[block] as usize;
+        let block = self.last_block_before(|block| zeros_before(block) <= rank);
+
+        self.select_from(block, rank - zeros_before(block), |word| !word)
+    }
+
+    // Binary search for the last block whose start satisfies a predicate, which holds for the first block
+    fn last_block_before(&self, starts_before: impl Fn(usize) -> bool) -> usize {
+        let (mut low, mut high) = (0, self.blocks.len() - 1);
+
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if starts_before(middle) {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        low
+    }
+
+    // Scan the words of a block for the one of the given rank, after mapping them (to look for zeros)
+    fn select_from(
+        &self,
+        block: usize,
+        mut rank: usize,
+        map: impl Fn(u64) -> u64,
+    ) -> Option<usize> {
+        let words = self.bits.words();
+
+        for (position, &word) in words.iter().enumerate().skip(block * BLOCK_WORDS) {
+            let word = map(word);
+            let ones = word.count_ones() as usize;
+
+            if rank < ones {
+                let index = position * 64 + select_in_word(word, rank);
+                return (index < self.len()).then_some(index);
+            }
+            rank -= ones;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_rank_select_against_scan() {
+        let mut rng = Lcg::new(11);
+        let bits: BitSet = (0..3000)
+            .map(|_| {
+                let seed = rng.next_u64();
+                (seed >> 33).is_multiple_of(3)
+            })
+            .collect();
+        let index = RankSelect::new(bits.clone());
+
+        let ones: Vec<_> = bits.iter_ones().collect();
+        let zeros: Vec<_> = (0..bits.len())
+            .filter(|&i| bits.get(i) == Some(false))
+            .collect();
+
+        for position in 0..=bits.len() {
+            let expected = ones.partition_point(|&one| one < position);
+            assert_eq!(index.rank1(position), expected);
+            assert_eq!(index.rank0(position), position - expected);
+        }
+
+        for (rank, &position) in ones.iter().enumerate() {
+            assert_eq!(index.select1(rank), Some(position));
+        }
+        for (rank, &position) in zeros.iter().enumerate() {
+            assert_eq!(index.select0(rank), Some(position));
+        }
+        assert_eq!(index.select1(ones.len()), None);
+        assert_eq!(index.select0(zeros.len()), None);
+    }
+
+    #[test]
+    fn test_rank_select_block_boundaries() {
+        // Exactly two full blocks of ones
+        let index = RankSelect::new((0..2 * BLOCK_BITS).map(|_| true).collect());
+
+        assert_eq!(index.rank1(BLOCK_BITS), BLOCK_BITS);
+        assert_eq!(index.rank1(2 * BLOCK_BITS), 2 * BLOCK_BITS);
+        assert_eq!(index.select1(BLOCK_BITS), Some(BLOCK_BITS));
+        assert_eq!(index.select0(0), None);
+
+        let empty = RankSelect::new(BitSet::new());
+        assert_eq!(empty.rank1(0), 0);
+        assert_eq!(empty.select1(0), None);
+        assert_eq!(empty.select0(0), None);
+    }
+}
@@ -0,0 +1,522 @@
+//! This module implements a least recently used cache on the `Vertex` primitive of the crate.
+//! The entries form a doubly linked list of vertexes from the most to the least recently used one, and a `HashMap`
+//! indexes the vertex of each key. A hit moves its vertex to the front and an insertion into a full cache evicts the
+//! vertex at the back, both without scanning or reordering anything else.
+//!
+//! # Performance
+//! - O(1) expected for `get`, `put` and `remove`
+//! - O(1) for `len`, `contains` and the statistics
+//!
+//! # Implementation Details
+//! - Each vertex holds its key and value, and is linked to the more recent entry through `Previous` and to the less
+//!   recent one through `Next`, like the vertexes of `LinkedList`.
+//! - Both pointers are strong. An evicted or removed vertex is cleared, dropping its pointers, and dropping the cache
+//!   clears the remaining ones, so no vertex outlives its entry.
+//! - An `EvictionListener` is called with every evicted entry, before `put` returns it. Removals aren't evictions.
+//! - Hits, misses and evictions are counted as `CacheStats`, like `SegmentedCache`.
+//!
+//! # Usage
+//! ```
+//! use data_structures::cache::lru_cache::LruCache;
+//!
+//! let mut cache = LruCache::new(2);
+//!
+//! cache.put("a", 1);
+//! cache.put("b", 2);
+//! assert_eq!(cache.get(&"a"), Some(1));
+//!
+//! // "b" is now the least recently used entry
+//! assert_eq!(cache.put("c", 3), Some(("b", 2)));
+//! assert!(!cache.contains(&"b"));
+//! ```
+//!
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use super::segmented_cache::CacheStats;
+use crate::linked_list::shared::{Ptr, Shared, ThreadSafe};
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+/// Trait of the callbacks told about evicted entries
+///
+/// Closures taking the key and the value implement it. With the `arc` feature, listeners must be `Send + Sync` so the
+/// cache holding them is too.
+///
+pub trait EvictionListener<K, V>: ThreadSafe {
+    /// Called with an entry evicted to make room for a new one
+    fn on_evict(&mut self, key: &K, value: &V);
+}
+
+impl<K, V, F: FnMut(&K, &V) + ThreadSafe> EvictionListener<K, V> for F {
+    fn on_evict(&mut self, key: &K, value: &V) {
+        self(key, value)
+    }
+}
+
+type Node<K, V> = Shared<Vertex<(K, V)>>;
+
+/// Struct representing a least recently used cache
+///
+pub struct LruCache<K, V> {
+    entries: HashMap<K, Node<K, V>>,
+    front: Option<Node<K, V>>, // most recently used
+    back: Option<Node<K, V>>,  // least recently used
+
+    capacity: usize,
+    stats: CacheStats,
+    listener: Option<Box<dyn EvictionListener<K, V>>>,
+}
+
+// Link two vertexes, `newer` coming before `older`
+fn link<K, V>(newer: &Node<K, V>, older: &Node<K, V>) {
+    newer
+        .borrow_mut()
+        .set_connection(PointerName::Next, Some(older));
+    older
+        .borrow_mut()
+        .set_connection(PointerName::Previous, Some(newer));
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Create a new empty cache
+    /// # Arguments
+    /// * `capacity`: The maximum number of entries, at least 1
+    /// # Returns
+    /// A new LruCache instance
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    ///
+    /// let cache: LruCache<u32, String> = LruCache::new(100);
+    /// assert_eq!(cache.capacity(), 100);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        LruCache {
+            entries: HashMap::new(),
+            front: None,
+            back: None,
+            capacity: capacity.max(1),
+            stats: CacheStats::default(),
+            listener: None,
+        }
+    }
+
+    /// Set the callback told about every evicted entry, replacing the previous one
+    /// # Arguments
+    /// * `listener`: The callback, such as a closure taking the key and the value
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let evicted = Arc::new(Mutex::new(Vec::new()));
+    /// let log = evicted.clone();
+    ///
+    /// let mut cache = LruCache::new(1);
+    /// cache.set_eviction_listener(move |key: &u32, _: &&str| log.lock().unwrap().push(*key));
+    /// cache.put(1, "one");
+    /// cache.put(2, "two");
+    ///
+    /// assert_eq!(*evicted.lock().unwrap(), vec![1]);
+    /// ```
+    pub fn set_eviction_listener(&mut self, listener: impl EvictionListener<K, V> + 'static) {
+        self.listener = Some(Box::new(listener));
+    }
+
+    /// Get the maximum number of entries
+    /// # Returns
+    /// The capacity of the cache
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    ///
+    /// let cache: LruCache<u32, u32> = LruCache::new(0);
+    /// assert_eq!(cache.capacity(), 1);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the number of entries
+    /// # Returns
+    /// The number of cached entries
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(4);
+    /// cache.put('a', 1);
+    /// assert_eq!(cache.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the cache is empty
+    /// # Returns
+    /// True if no entry is cached
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    ///
+    /// let cache: LruCache<u32, u32> = LruCache::new(4);
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the hit, miss and eviction counters
+    /// # Returns
+    /// The statistics since the creation of the cache or the last reset
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(1);
+    /// cache.put(1, 1);
+    /// cache.get(&1);
+    /// cache.get(&2);
+    /// cache.put(2, 2);
+    ///
+    /// let stats = cache.stats();
+    /// assert_eq!((stats.hits, stats.misses, stats.evictions), (1, 1, 1));
+    /// ```
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Reset the hit, miss and eviction counters
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    ///
+    /// let mut cache: LruCache<u32, u32> = LruCache::new(1);
+    /// cache.get(&1);
+    /// cache.reset_stats();
+    /// assert_eq!(cache.stats().misses, 0);
+    /// ```
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Check if a key is cached, without counting a hit or changing its recency
+    /// # Arguments
+    /// * `key`: The key to be checked
+    /// # Returns
+    /// True if the key is cached
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put("a", 1);
+    /// assert!(cache.contains(&"a"));
+    /// assert!(!cache.contains(&"b"));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Get a clone of the value of a key without changing its recency
+    /// # Arguments
+    /// * `key`: The key to be looked up
+    /// # Returns
+    /// The value, or None if the key isn't cached
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put(1, 'a');
+    /// cache.put(2, 'b');
+    ///
+    /// assert_eq!(cache.peek(&1), Some('a'));
+    /// assert_eq!(cache.put(3, 'c'), Some((1, 'a')));
+    /// ```
+    pub fn peek(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        let node = self.entries.get(key)?;
+        let value = node
+            .borrow()
+            .read_data()
+            .as_ref()
+            .map(|(_, value)| value.clone());
+        value
+    }
+
+    /// Get a clone of the value of a key, making it the most recently used entry
+    /// # Arguments
+    /// * `key`: The key to be looked up
+    /// # Returns
+    /// The value, or None if the key isn't cached
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put(1, 'a');
+    /// cache.put(2, 'b');
+    ///
+    /// assert_eq!(cache.get(&1), Some('a'));
+    /// assert_eq!(cache.put(3, 'c'), Some((2, 'b')));
+    /// ```
+    pub fn get(&mut self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        let Some(node) = self.entries.get(key).cloned() else {
+            self.stats.misses += 1;
+            return None;
+        };
+
+        self.stats.hits += 1;
+        self.touch(&node);
+        let value = node
+            .borrow()
+            .read_data()
+            .as_ref()
+            .map(|(_, value)| value.clone());
+        value
+    }
+
+    /// Insert or update an entry, making it the most recently used one
+    /// # Arguments
+    /// * `key`: The key of the entry
+    /// * `value`: The value of the entry
+    /// # Returns
+    /// The least recently used entry, evicted to make room for a new key, if the cache was full
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(1);
+    ///
+    /// assert_eq!(cache.put("a", 1), None);
+    /// assert_eq!(cache.put("a", 2), None);
+    /// assert_eq!(cache.put("b", 3), Some(("a", 2)));
+    /// ```
+    pub fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if let Some(node) = self.entries.get(&key).cloned() {
+            if let Some((_, old_value)) = node.borrow_mut().data_mut() {
+                *old_value = value;
+            }
+            self.touch(&node);
+            return None;
+        }
+
+        let evicted = if self.entries.len() >= self.capacity {
+            self.evict()
+        } else {
+            None
+        };
+
+        let node = Vertex::new((key.clone(), value));
+        self.push_front(&node);
+        self.entries.insert(key, node);
+        evicted
+    }
+
+    /// Remove an entry from the cache, without telling the eviction listener
+    /// # Arguments
+    /// * `key`: The key to be removed
+    /// # Returns
+    /// The value of the removed entry, or None if the key isn't cached
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put(1, 'x');
+    ///
+    /// assert_eq!(cache.remove(&1), Some('x'));
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let node = self.entries.remove(key)?;
+        self.unlink(&node);
+        let entry = node.borrow_mut().clear();
+        entry.map(|(_, value)| value)
+    }
+
+    // Remove the least recently used entry, telling the listener
+    fn evict(&mut self) -> Option<(K, V)> {
+        let node = self.back.clone()?;
+        self.unlink(&node);
+        // Clearing the vertex drops its pointers, so nothing keeps it alive once it leaves the index
+        let (key, value) = node.borrow_mut().clear()?;
+        self.entries.remove(&key);
+
+        self.stats.evictions += 1;
+        if let Some(listener) = self.listener.as_mut() {
+            listener.on_evict(&key, &value);
+        }
+        Some((key, value))
+    }
+
+    // Move a vertex of the list to the front
+    fn touch(&mut self, node: &Node<K, V>) {
+        if let Some(front) = &self.front {
+            if Ptr::ptr_eq(front, node) {
+                return;
+            }
+        }
+        self.unlink(node);
+        self.push_front(node);
+    }
+
+    // Link an unlinked vertex at the front
+    fn push_front(&mut self, node: &Node<K, V>) {
+        match self.front.take() {
+            Some(front) => link(node, &front),
+            None => self.back = Some(node.clone()),
+        }
+        self.front = Some(node.clone());
+    }
+
+    // Take a vertex out of the list, linking its neighbors together and dropping its pointers
+    fn unlink(&mut self, node: &Node<K, V>) {
+        let newer = node
+            .borrow_mut()
+            .set_connection(PointerName::Previous, None);
+        let older = node.borrow_mut().set_connection(PointerName::Next, None);
+
+        match &newer {
+            Some(newer) => {
+                newer
+                    .borrow_mut()
+                    .set_connection(PointerName::Next, older.as_ref());
+            }
+            None => self.front = older.clone(),
+        }
+        match &older {
+            Some(older) => {
+                older
+                    .borrow_mut()
+                    .set_connection(PointerName::Previous, newer.as_ref());
+            }
+            None => self.back = newer,
+        }
+    }
+}
+
+impl<K, V> Drop for LruCache<K, V> {
+    fn drop(&mut self) {
+        // Clear the vertexes one by one, the neighbors pointing to each other would keep them alive
+        self.back.take();
+        let mut current = self.front.take();
+        while let Some(node) = current {
+            current = node.borrow().get_pointer(PointerName::Next);
+            node.borrow_mut().clear();
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    /// Prints the entries from the most to the least recently used one
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        let mut current = self.front.clone();
+        while let Some(node) = current {
+            if let Some((key, value)) = node.borrow().read_data() {
+                map.entry(key, value);
+            }
+            current = node.borrow().get_pointer(PointerName::Next);
+        }
+        map.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_lru_cache_against_recency_list() {
+        let mut cache = LruCache::new(8);
+        // Keys from the most to the least recently used
+        let mut model: Vec<(u32, u32)> = Vec::new();
+        let mut rng = Lcg::new(37);
+
+        for step in 0..3_000u32 {
+            let seed = rng.next_u64();
+            let key = (seed >> 33) as u32 % 12;
+            match (seed >> 60) % 3 {
+                0 => {
+                    let position = model.iter().position(|&(k, _)| k == key);
+                    let expected = position.map(|position| model.remove(position));
+                    if let Some(entry) = expected {
+                        model.insert(0, entry);
+                    }
+                    assert_eq!(cache.get(&key), expected.map(|(_, value)| value));
+                }
+                1 => {
+                    let expected = model
+                        .iter()
+                        .position(|&(k, _)| k == key)
+                        .map(|position| model.remove(position).1);
+                    assert_eq!(cache.remove(&key), expected);
+                }
+                _ => {
+                    let evicted = match model.iter().position(|&(k, _)| k == key) {
+                        Some(position) => {
+                            model.remove(position);
+                            None
+                        }
+                        None if model.len() == 8 => model.pop(),
+                        None => None,
+                    };
+                    model.insert(0, (key, step));
+                    assert_eq!(cache.put(key, step), evicted);
+                }
+            }
+            assert_eq!(cache.len(), model.len());
+        }
+
+        let entries: Vec<String> = model
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect();
+        assert_eq!(
+            format!("{:?}", cache),
+            format!("{{{}}}", entries.join(", "))
+        );
+    }
+
+    #[test]
+    fn test_evicted_entries_are_released() {
+        let value = Rc::new(());
+        let evicted = Arc::new(AtomicUsize::new(0));
+        let count = evicted.clone();
+
+        let mut cache = LruCache::new(4);
+        cache.set_eviction_listener(move |_: &u32, _: &Rc<()>| {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+        for key in 0..100u32 {
+            drop(cache.put(key, value.clone()));
+            cache.get(&(key / 2));
+        }
+        assert_eq!(evicted.load(Ordering::Relaxed), 96);
+        assert_eq!(cache.stats().evictions, 96);
+
+        // Only the cached values are alive, and the back vertex is held by the index, its newer neighbor, the back
+        // pointer of the cache and this clone
+        assert_eq!(Rc::strong_count(&value), 1 + 4);
+        let back = cache.back.clone().unwrap();
+        assert_eq!(Ptr::strong_count(&back), 4);
+        drop(back);
+
+        cache.remove(&99);
+        assert_eq!(Rc::strong_count(&value), 1 + 3);
+        drop(cache);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+}
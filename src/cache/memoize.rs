@@ -0,0 +1,527 @@
+//! This module implements memoization on top of the caches of the crate.
+//! `memoize` wraps a function and a cache into a `Memoized` value: calling it with an argument already seen returns the
+//! cached result instead of running the function again. The cache decides which results are kept, so a memoized
+//! function uses bounded memory with `LruCache` or `SegmentedCache`, or remembers everything with a `HashMap`.
+//!
+//! # Performance
+//! - A hit costs a lookup in the cache and a clone of the result
+//! - A miss costs the function, an insertion in the cache and a clone of the result
+//!
+//! # Implementation Details
+//! - The cache is any type implementing `CachePolicy`, which is implemented for `LruCache`, `SegmentedCache` and
+//!   `HashMap`. The cache is keyed by the argument itself, so two arguments with the same hash never share a result.
+//! - Each entry keeps the time its computation took, its cost. `MemoStats` adds up the cost of every computation, the
+//!   cost saved by the hits and the cost of the results currently cached, which drops when an entry is evicted.
+//! - With a time to live, an entry older than it is recomputed on its next call and counted as an expiration.
+//!   Expired entries are not swept: they stay in the cache until they are called or evicted.
+//!
+//! # Usage
+//! ```
+//! use data_structures::cache::lru_cache::LruCache;
+//! use data_structures::cache::memoize::memoize;
+//!
+//! let mut collatz = memoize(
+//!     |start: &u64| {
+//!         let (mut n, mut steps) = (*start, 0u32);
+//!         while n != 1 {
+//!             n = if n % 2 == 0 { n / 2 } else { 3 * n + 1 };
+//!             steps += 1;
+//!         }
+//!         steps
+//!     },
+//!     LruCache::new(100),
+//! );
+//!
+//! assert_eq!(collatz.call(27), 111);
+//! assert_eq!(collatz.call(27), 111);
+//!
+//! let stats = collatz.stats();
+//! assert_eq!((stats.hits, stats.misses), (1, 1));
+//! assert_eq!(stats.saved_time, stats.compute_time);
+//! ```
+//!
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use super::lru_cache::LruCache;
+use super::segmented_cache::SegmentedCache;
+
+/// Operations a cache needs to hold the results of a `Memoized` function
+pub trait CachePolicy<K, V> {
+    /// Returns a clone of the value of a key, as a lookup that the policy may count as a use
+    fn get(&mut self, key: &K) -> Option<V>;
+    /// Inserts a key, returning the entry evicted to make room for it
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)>;
+    /// Removes a key, returning its value
+    fn remove(&mut self, key: &K) -> Option<V>;
+    /// Returns the number of keys
+    fn len(&self) -> usize;
+    /// Returns true if there are no keys
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> CachePolicy<K, V> for LruCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<V> {
+        LruCache::get(self, key)
+    }
+
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        LruCache::put(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        LruCache::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        LruCache::len(self)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> CachePolicy<K, V> for SegmentedCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<V> {
+        SegmentedCache::get(self, key).cloned()
+    }
+
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        SegmentedCache::put(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        SegmentedCache::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        SegmentedCache::len(self)
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> CachePolicy<K, V> for HashMap<K, V> {
+    fn get(&mut self, key: &K) -> Option<V> {
+        HashMap::get(self, key).cloned()
+    }
+
+    /// Never evicts, the map grows with every new key
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        HashMap::insert(self, key, value);
+        None
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        HashMap::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+}
+
+/// A result cached by a `Memoized` function, with the cost of its computation
+#[derive(Debug, Clone)]
+pub struct MemoEntry<R> {
+    value: R,
+    cost: Duration,
+    computed_at: Instant,
+}
+
+/// Call counters and cost accounting of a memoized function
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub expirations: u64,
+    /// Time spent running the function
+    pub compute_time: Duration,
+    /// Sum of the costs of the results returned from the cache
+    pub saved_time: Duration,
+    /// Sum of the costs of the results currently cached
+    pub cached_time: Duration,
+}
+
+impl MemoStats {
+    /// Get the ratio of calls answered from the cache
+    /// # Returns
+    /// The hit rate between 0.0 and 1.0, or 0.0 if there was no call
+    /// # Example
+    /// ```
+    /// use data_structures::cache::memoize::MemoStats;
+    ///
+    /// let stats = MemoStats { hits: 9, misses: 1, ..Default::default() };
+    /// assert_eq!(stats.hit_rate(), 0.9);
+    /// ```
+    pub fn hit_rate(&self) -> f64 {
+        let calls = self.hits + self.misses;
+        if calls == 0 {
+            0.0
+        } else {
+            self.hits as f64 / calls as f64
+        }
+    }
+}
+
+/// Struct representing a function whose results are cached
+///
+pub struct Memoized<A, R, F, C> {
+    function: F,
+    cache: C,
+    ttl: Option<Duration>,
+    stats: MemoStats,
+    _marker: std::marker::PhantomData<fn(A) -> R>,
+}
+
+/// Wrap a function so its results are cached
+/// # Arguments
+/// * `function`: The function to memoize, called with a reference to the argument
+/// * `cache`: The cache holding the results, empty or not, which decides which results are kept
+/// # Returns
+/// A new Memoized instance, without time to live
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use data_structures::cache::memoize::memoize;
+///
+/// let mut square = memoize(|x: &i64| x * x, HashMap::new());
+/// assert_eq!(square.call(12), 144);
+/// assert_eq!(square.len(), 1);
+/// ```
+pub fn memoize<A, R, F, C>(function: F, cache: C) -> Memoized<A, R, F, C>
+where
+    F: FnMut(&A) -> R,
+    C: CachePolicy<A, MemoEntry<R>>,
+{
+    Memoized {
+        function,
+        cache,
+        ttl: None,
+        stats: MemoStats::default(),
+        _marker: std::marker::PhantomData,
+    }
+}
+
+impl<A, R: Clone, F: FnMut(&A) -> R, C: CachePolicy<A, MemoEntry<R>>> Memoized<A, R, F, C> {
+    /// Get the result of the function for an argument, from the cache if it holds a fresh one
+    /// # Arguments
+    /// * `argument`: The argument of the function, kept as the key of the result on a miss
+    /// # Returns
+    /// The result of the function
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    /// use data_structures::cache::memoize::memoize;
+    ///
+    /// let mut calls = 0;
+    /// let mut length = memoize(
+    ///     |word: &String| {
+    ///         calls += 1;
+    ///         word.chars().count()
+    ///     },
+    ///     LruCache::new(10),
+    /// );
+    ///
+    /// assert_eq!(length.call("café".to_string()), 4);
+    /// assert_eq!(length.call("café".to_string()), 4);
+    /// drop(length);
+    /// assert_eq!(calls, 1);
+    /// ```
+    pub fn call(&mut self, argument: A) -> R {
+        if let Some(entry) = self.cache.get(&argument) {
+            if !self.is_expired(&entry) {
+                self.stats.hits += 1;
+                self.stats.saved_time += entry.cost;
+                return entry.value;
+            }
+            self.cache.remove(&argument);
+            self.stats.cached_time -= entry.cost;
+            self.stats.expirations += 1;
+        }
+
+        self.stats.misses += 1;
+        let start = Instant::now();
+        let value = (self.function)(&argument);
+        let computed_at = Instant::now();
+        let cost = computed_at - start;
+        self.stats.compute_time += cost;
+        self.stats.cached_time += cost;
+
+        let entry = MemoEntry {
+            value: value.clone(),
+            cost,
+            computed_at,
+        };
+        if let Some((_, evicted)) = self.cache.put(argument, entry) {
+            self.stats.evictions += 1;
+            self.stats.cached_time -= evicted.cost;
+        }
+        value
+    }
+
+    /// Forget the cached result of an argument, so the next call computes it again
+    /// # Arguments
+    /// * `argument`: The argument whose result is dropped
+    /// # Returns
+    /// True if a result was cached for the argument
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::cache::memoize::memoize;
+    ///
+    /// let mut double = memoize(|x: &u8| x * 2, HashMap::new());
+    /// double.call(4);
+    ///
+    /// assert!(double.invalidate(&4));
+    /// assert!(!double.invalidate(&4));
+    /// ```
+    pub fn invalidate(&mut self, argument: &A) -> bool {
+        match self.cache.remove(argument) {
+            Some(entry) => {
+                self.stats.cached_time -= entry.cost;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Turn the memoized function into a closure, for the APIs expecting one
+    /// # Returns
+    /// A closure calling `call`
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::cache::memoize::memoize;
+    ///
+    /// let square = memoize(|x: &u32| x * x, HashMap::new());
+    /// let squares: Vec<u32> = [1, 2, 1, 2].into_iter().map(square.into_fn()).collect();
+    /// assert_eq!(squares, vec![1, 4, 1, 4]);
+    /// ```
+    pub fn into_fn(mut self) -> impl FnMut(A) -> R {
+        move |argument| self.call(argument)
+    }
+
+    // Check if a cached result is older than the time to live
+    fn is_expired(&self, entry: &MemoEntry<R>) -> bool {
+        self.ttl
+            .is_some_and(|ttl| entry.computed_at.elapsed() >= ttl)
+    }
+}
+
+impl<A, R, F, C: CachePolicy<A, MemoEntry<R>>> Memoized<A, R, F, C> {
+    /// Get the number of cached results, expired ones included
+    /// # Returns
+    /// The number of entries in the cache
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    /// use data_structures::cache::memoize::memoize;
+    ///
+    /// let mut negate = memoize(|x: &i32| -x, LruCache::new(2));
+    /// for x in 0..5 {
+    ///     negate.call(x);
+    /// }
+    /// assert_eq!(negate.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Check if no result is cached
+    /// # Returns
+    /// True if the cache is empty
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::cache::memoize::memoize;
+    ///
+    /// let negate = memoize(|x: &i32| -x, HashMap::new());
+    /// assert!(negate.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Get the time to live of the cached results
+    /// # Returns
+    /// The time after which a result is computed again, or None if results don't expire
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::cache::memoize::memoize;
+    ///
+    /// let negate = memoize(|x: &i32| -x, HashMap::new());
+    /// assert_eq!(negate.ttl(), None);
+    /// ```
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Set the time to live of the cached results, applying to the results already cached too
+    /// # Arguments
+    /// * `ttl`: The time after which a result is computed again, or None for results that don't expire
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use std::time::Duration;
+    /// use data_structures::cache::memoize::memoize;
+    ///
+    /// let mut negate = memoize(|x: &i32| -x, HashMap::new());
+    /// negate.call(1);
+    ///
+    /// // Every result is stale at once
+    /// negate.set_ttl(Some(Duration::ZERO));
+    /// negate.call(1);
+    /// assert_eq!(negate.stats().expirations, 1);
+    /// ```
+    pub fn set_ttl(&mut self, ttl: Option<Duration>) {
+        self.ttl = ttl;
+    }
+
+    /// Get the call counters and the cost accounting
+    /// # Returns
+    /// A copy of the statistics
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    /// use data_structures::cache::memoize::memoize;
+    ///
+    /// let mut increment = memoize(|x: &u8| x + 1, SegmentedCache::new(8));
+    /// increment.call(1);
+    /// increment.call(1);
+    /// increment.call(2);
+    ///
+    /// let stats = increment.stats();
+    /// assert_eq!((stats.hits, stats.misses), (1, 2));
+    /// assert!(stats.cached_time <= stats.compute_time);
+    /// ```
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+
+    /// Reset the counters, keeping `cached_time` which describes the cached results
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::cache::memoize::memoize;
+    ///
+    /// let mut increment = memoize(|x: &u8| x + 1, HashMap::new());
+    /// increment.call(1);
+    /// increment.reset_stats();
+    /// assert_eq!(increment.stats().misses, 0);
+    /// ```
+    pub fn reset_stats(&mut self) {
+        self.stats = MemoStats {
+            cached_time: self.stats.cached_time,
+            ..MemoStats::default()
+        };
+    }
+
+    /// Get a reference to the cache holding the results
+    /// # Returns
+    /// A reference to the cache
+    /// # Example
+    /// ```
+    /// use data_structures::cache::lru_cache::LruCache;
+    /// use data_structures::cache::memoize::memoize;
+    ///
+    /// let mut negate = memoize(|x: &i32| -x, LruCache::new(4));
+    /// negate.call(1);
+    /// assert!(negate.cache().contains(&1));
+    /// ```
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+}
+
+impl<A, R, F, C: fmt::Debug> fmt::Debug for Memoized<A, R, F, C> {
+    /// Prints the cache and the statistics, not the function
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Memoized")
+            .field("cache", &self.cache)
+            .field("ttl", &self.ttl)
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_memoize_against_lru_model() {
+        let calls = Cell::new(0u64);
+        let capacity = 8;
+        let mut memoized = memoize(
+            |x: &u64| {
+                calls.set(calls.get() + 1);
+                (0..=*x).map(|i| i * i).sum::<u64>()
+            },
+            LruCache::new(capacity),
+        );
+
+        // Model: the cached arguments, from the least to the most recently used
+        let mut model: Vec<u64> = Vec::new();
+        let (mut hits, mut evictions) = (0, 0);
+        let mut rng = Lcg::new(5);
+
+        for _ in 0..5_000 {
+            let seed = rng.next_u64();
+            let x = (seed >> 33) % 20;
+
+            match model.iter().position(|&cached| cached == x) {
+                Some(index) => {
+                    model.remove(index);
+                    hits += 1;
+                }
+                None if model.len() == capacity => {
+                    model.remove(0);
+                    evictions += 1;
+                }
+                None => {}
+            }
+            model.push(x);
+
+            assert_eq!(memoized.call(x), x * (x + 1) * (2 * x + 1) / 6);
+            assert_eq!(memoized.len(), model.len());
+        }
+
+        let stats = memoized.stats();
+        assert_eq!(
+            (stats.hits, stats.evictions, stats.expirations),
+            (hits, evictions, 0)
+        );
+        assert_eq!(stats.misses, calls.get());
+        assert!(stats.cached_time <= stats.compute_time);
+
+        // Invalidating every cached result gives their whole cost back
+        for x in model {
+            assert!(memoized.invalidate(&x));
+        }
+        assert!(memoized.is_empty());
+        assert_eq!(memoized.stats().cached_time, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_memoize_ttl() {
+        let mut memoized = memoize(|x: &u32| x + 1, HashMap::new());
+        memoized.set_ttl(Some(Duration::from_secs(3600)));
+        for _ in 0..3 {
+            assert_eq!(memoized.call(1), 2);
+        }
+        assert_eq!(memoized.stats().hits, 2);
+
+        // A zero time to live recomputes every call, replacing the expired entry
+        memoized.set_ttl(Some(Duration::ZERO));
+        for _ in 0..3 {
+            assert_eq!(memoized.call(1), 2);
+        }
+        let stats = memoized.stats();
+        assert_eq!((stats.hits, stats.misses, stats.expirations), (2, 4, 3));
+        assert_eq!(memoized.len(), 1);
+    }
+}
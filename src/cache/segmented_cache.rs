@@ -0,0 +1,458 @@
+//! This module implements a segmented cache using the 2Q replacement algorithm.
+//! New entries go through a short probation FIFO (`A1in`); keys evicted from it are remembered in a ghost FIFO (`A1out`),
+//! and only keys requested again while remembered are promoted to the main LRU segment (`Am`). A long scan of one-time
+//! keys therefore only churns the probation segment and doesn't flush the frequently used entries.
+//!
+//! # Performance
+//! - O(log n) for `get`, `put` and `remove`
+//! - O(1) for the statistics
+//!
+//! # Implementation Details
+//! - Entries are stored in a `HashMap` together with the segment they belong to and their recency stamp.
+//! - Each segment orders its keys in a `BTreeMap` indexed by stamp, so the oldest key is the first one and a key can be
+//!   moved or removed without scanning the segment.
+//! - By default the probation segment holds up to 25% of the capacity and the ghost segment remembers up to 50% of the
+//!   capacity in keys (without values).
+//! - Hits, misses and evictions are counted, and `put` returns the evicted entry, if any.
+//!
+//! # Usage
+//! ```
+//! use data_structures::cache::segmented_cache::SegmentedCache;
+//!
+//! let mut cache = SegmentedCache::new(4);
+//!
+//! cache.put("a", 1);
+//! assert_eq!(cache.get(&"a"), Some(&1));
+//! assert_eq!(cache.get(&"b"), None);
+//!
+//! let stats = cache.stats();
+//! assert_eq!((stats.hits, stats.misses), (1, 1));
+//! ```
+//!
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+/// Segment of the cache holding a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment {
+    Probation, // A1in
+    Main,      // Am
+}
+
+/// Hit, miss and eviction counters of a cache
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Get the ratio of lookups that were hits
+    /// # Returns
+    /// The hit rate between 0.0 and 1.0, or 0.0 if there was no lookup
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::CacheStats;
+    ///
+    /// let stats = CacheStats { hits: 3, misses: 1, evictions: 0 };
+    /// assert_eq!(stats.hit_rate(), 0.75);
+    /// ```
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Struct representing a cache with the 2Q replacement policy
+///
+#[derive(Debug)]
+pub struct SegmentedCache<K, V> {
+    entries: HashMap<K, (V, Segment, u64)>,
+    probation: BTreeMap<u64, K>,
+    main: BTreeMap<u64, K>,
+    ghosts: BTreeMap<u64, K>,
+    ghost_stamps: HashMap<K, u64>,
+
+    capacity: usize,
+    probation_size: usize,
+    ghost_size: usize,
+
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> SegmentedCache<K, V> {
+    /// Create a new empty SegmentedCache with the default segment sizes
+    ///
+    /// # Arguments
+    /// * `capacity`: The maximum number of entries kept in the cache. A capacity of 0 is replaced by 1.
+    ///
+    /// # Returns
+    /// A new SegmentedCache instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    ///
+    /// let cache: SegmentedCache<u32, String> = SegmentedCache::new(100);
+    /// assert_eq!(cache.capacity(), 100);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self::with_segments(capacity, capacity / 4, capacity / 2)
+    }
+
+    /// Create a new empty SegmentedCache with custom segment sizes
+    ///
+    /// # Arguments
+    /// * `capacity`: The maximum number of entries kept in the cache. A capacity of 0 is replaced by 1.
+    /// * `probation_size`: The number of entries kept in the probation FIFO before they are evicted. A value of 0 is replaced by 1.
+    /// * `ghost_size`: The number of evicted keys remembered to detect a second request
+    ///
+    /// # Returns
+    /// A new SegmentedCache instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    ///
+    /// let cache: SegmentedCache<u32, u32> = SegmentedCache::with_segments(1000, 100, 2000);
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn with_segments(capacity: usize, probation_size: usize, ghost_size: usize) -> Self {
+        SegmentedCache {
+            entries: HashMap::new(),
+            probation: BTreeMap::new(),
+            main: BTreeMap::new(),
+            ghosts: BTreeMap::new(),
+            ghost_stamps: HashMap::new(),
+            capacity: capacity.max(1),
+            probation_size: probation_size.max(1),
+            ghost_size,
+            clock: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Get the maximum number of entries of the cache
+    /// # Returns
+    /// The capacity of the cache
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    ///
+    /// let cache: SegmentedCache<u8, u8> = SegmentedCache::new(8);
+    /// assert_eq!(cache.capacity(), 8);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the number of entries in the cache
+    /// # Returns
+    /// The number of cached entries, not counting the remembered ghost keys
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    ///
+    /// let mut cache = SegmentedCache::new(8);
+    /// cache.put(1, 'a');
+    /// assert_eq!(cache.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the cache is empty
+    /// # Returns
+    /// True if the cache has no entries, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    ///
+    /// let cache: SegmentedCache<u8, u8> = SegmentedCache::new(8);
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the hit, miss and eviction counters
+    /// # Returns
+    /// A copy of the statistics of the cache
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    ///
+    /// let mut cache = SegmentedCache::new(1);
+    /// cache.put(1, 1);
+    /// cache.put(2, 2);
+    /// assert_eq!(cache.stats().evictions, 1);
+    /// ```
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Reset the hit, miss and eviction counters
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    ///
+    /// let mut cache: SegmentedCache<u8, u8> = SegmentedCache::new(1);
+    /// cache.get(&1);
+    /// cache.reset_stats();
+    /// assert_eq!(cache.stats().misses, 0);
+    /// ```
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Check if a key is cached, without updating its recency or the statistics
+    /// # Arguments
+    /// * `key`: The key to be checked
+    /// # Returns
+    /// True if the key is cached, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    ///
+    /// let mut cache = SegmentedCache::new(2);
+    /// cache.put("key", ());
+    /// assert!(cache.contains(&"key"));
+    /// assert_eq!(cache.stats().hits, 0);
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Get the value of a key, counting a hit or a miss
+    /// Entries of the main segment become the most recently used ones. Entries of the probation segment keep their position,
+    /// so a burst of requests right after the insertion doesn't promote them.
+    /// # Arguments
+    /// * `key`: The key to be searched
+    /// # Returns
+    /// A reference to the value, or None if the key isn't cached
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    ///
+    /// let mut cache = SegmentedCache::new(2);
+    /// cache.put(1, "one");
+    ///
+    /// assert_eq!(cache.get(&1), Some(&"one"));
+    /// assert_eq!(cache.get(&2), None);
+    /// ```
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let segment = match self.entries.get(key) {
+            Some((_, segment, _)) => *segment,
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+
+        self.stats.hits += 1;
+        if segment == Segment::Main {
+            self.touch(key);
+        }
+
+        self.entries.get(key).map(|(value, _, _)| value)
+    }
+
+    /// Insert or update an entry
+    /// A new key enters the probation segment, unless it was recently evicted from it, in which case it goes to the main segment.
+    /// # Arguments
+    /// * `key`: The key of the entry
+    /// * `value`: The value of the entry
+    /// # Returns
+    /// The entry evicted to make room for the new one, if any
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    ///
+    /// let mut cache = SegmentedCache::new(1);
+    ///
+    /// assert_eq!(cache.put("a", 1), None);
+    /// assert_eq!(cache.put("a", 2), None);
+    /// assert_eq!(cache.put("b", 3), Some(("a", 2)));
+    /// ```
+    pub fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if let Some((old_value, segment, _)) = self.entries.get_mut(&key) {
+            *old_value = value;
+            if *segment == Segment::Main {
+                self.touch(&key);
+            }
+            return None;
+        }
+
+        let segment = match self.ghost_stamps.remove(&key) {
+            Some(stamp) => {
+                self.ghosts.remove(&stamp);
+                Segment::Main
+            }
+            None => Segment::Probation,
+        };
+
+        let evicted = if self.entries.len() >= self.capacity {
+            self.evict()
+        } else {
+            None
+        };
+
+        let stamp = self.tick();
+        self.segment_mut(segment).insert(stamp, key.clone());
+        self.entries.insert(key, (value, segment, stamp));
+
+        evicted
+    }
+
+    /// Remove an entry from the cache
+    /// # Arguments
+    /// * `key`: The key to be removed
+    /// # Returns
+    /// The value of the removed entry, or None if the key isn't cached
+    /// # Example
+    /// ```
+    /// use data_structures::cache::segmented_cache::SegmentedCache;
+    ///
+    /// let mut cache = SegmentedCache::new(2);
+    /// cache.put(1, 'x');
+    ///
+    /// assert_eq!(cache.remove(&1), Some('x'));
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (value, segment, stamp) = self.entries.remove(key)?;
+        self.segment_mut(segment).remove(&stamp);
+
+        Some(value)
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn segment_mut(&mut self, segment: Segment) -> &mut BTreeMap<u64, K> {
+        match segment {
+            Segment::Probation => &mut self.probation,
+            Segment::Main => &mut self.main,
+        }
+    }
+
+    // Make a main segment entry the most recently used one
+    fn touch(&mut self, key: &K) {
+        let stamp = self.tick();
+        let entry = self.entries.get_mut(key).unwrap();
+
+        let key = self.main.remove(&entry.2).unwrap();
+        entry.2 = stamp;
+        self.main.insert(stamp, key);
+    }
+
+    // Evict the oldest probation entry if the probation segment is over its size, otherwise the least recently used main entry
+    fn evict(&mut self) -> Option<(K, V)> {
+        let from_probation = self.probation.len() >= self.probation_size || self.main.is_empty();
+
+        let (_, key) = if from_probation {
+            self.probation.pop_first()?
+        } else {
+            self.main.pop_first()?
+        };
+        let (value, _, _) = self.entries.remove(&key).unwrap();
+        self.stats.evictions += 1;
+
+        // Keys evicted from probation are remembered, so a second request promotes them
+        if from_probation && self.ghost_size > 0 {
+            let stamp = self.tick();
+            self.ghosts.insert(stamp, key.clone());
+            self.ghost_stamps.insert(key.clone(), stamp);
+
+            if self.ghosts.len() > self.ghost_size {
+                let (_, oldest) = self.ghosts.pop_first().unwrap();
+                self.ghost_stamps.remove(&oldest);
+            }
+        }
+
+        Some((key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segmented_cache_promotion() {
+        let mut cache = SegmentedCache::with_segments(2, 1, 4);
+
+        cache.put(1, 1);
+        cache.put(2, 2);
+        assert_eq!(cache.put(3, 3), Some((1, 1))); // 1 becomes a ghost
+        assert!(!cache.contains(&1));
+
+        // Second request while remembered, promoted to the main segment
+        assert_eq!(cache.put(1, 10), Some((2, 2)));
+        cache.put(4, 4);
+        cache.put(5, 5);
+
+        // Probation entries were evicted, the promoted one is still cached
+        assert_eq!(cache.get(&1), Some(&10));
+        assert!(cache.contains(&5));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats().evictions, 4);
+    }
+
+    #[test]
+    fn test_segmented_cache_scan_resistance() {
+        let mut cache = SegmentedCache::new(100);
+
+        // A hot set requested twice, with other keys in between, gets promoted to the main segment
+        for key in 0..20 {
+            cache.put(key, key);
+        }
+        for key in 1_000..1_100 {
+            cache.put(key, key);
+        }
+        for key in 0..20 {
+            if cache.get(&key).is_none() {
+                cache.put(key, key);
+            }
+        }
+
+        // A long scan of one-time keys only churns the probation segment
+        for key in 10_000..20_000 {
+            cache.put(key, key);
+        }
+
+        cache.reset_stats();
+        for key in 0..20 {
+            assert_eq!(cache.get(&key), Some(&key));
+        }
+
+        assert_eq!(cache.stats().hit_rate(), 1.0);
+        assert_eq!(cache.len(), 100);
+    }
+
+    #[test]
+    fn test_segmented_cache_remove_and_update() {
+        let mut cache = SegmentedCache::new(3);
+
+        cache.put("a", 1);
+        cache.put("b", 2);
+        assert_eq!(cache.remove(&"a"), Some(1));
+        assert_eq!(cache.remove(&"a"), None);
+
+        cache.put("b", 20);
+        assert_eq!(cache.get(&"b"), Some(&20));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.stats().evictions, 0);
+    }
+}
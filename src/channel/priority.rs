@@ -0,0 +1,506 @@
+//! This module implements a multi-producer, single-consumer channel with several priority lanes.
+//! Each message is sent to a lane, lane 0 having the highest priority, and lanes keep their messages in FIFO order.
+//! The receiver picks the lane of the next message with the channel's `SchedulingPolicy`: strict priority always
+//! drains the highest lane first, while weighted fair scheduling shares the reads between the waiting lanes in
+//! proportion to their weights, so low severity messages still move when the high ones never stop.
+//!
+//! # Performance
+//! - O(1) for `send`
+//! - O(l) for `try_recv` and `recv`, where l is the number of lanes
+//!
+//! # Implementation Details
+//! - The lanes are bounded `VecDeque`s behind one `Mutex`, with a `Condvar` to wake up the blocked receiver.
+//! - Weighted fair scheduling is a smooth weighted round robin: on every read, each waiting lane earns its weight in
+//!   credit, and the lane with the most credit is read and pays the sum of the weights. Lanes are interleaved instead
+//!   of read in bursts, and an empty lane doesn't build up credit.
+//! - Senders can be cloned. Once every sender is dropped, the receiver reads the remaining messages and then gets None.
+//!   Once the receiver is dropped, sending fails with `Disconnected`.
+//!
+//! # Usage
+//! ```
+//! use data_structures::channel::priority::{PriorityChannel, SchedulingPolicy};
+//!
+//! const HIGH: usize = 0;
+//! const NORMAL: usize = 1;
+//! const LOW: usize = 2;
+//!
+//! let (sender, mut receiver) =
+//!     PriorityChannel::with_policy(3, 16, SchedulingPolicy::WeightedFair(vec![4, 2, 1]));
+//!
+//! let worker = sender.clone();
+//! std::thread::spawn(move || {
+//!     worker.send(LOW, "disk usage at 70%").unwrap();
+//!     worker.send(HIGH, "disk failure").unwrap();
+//! })
+//! .join()
+//! .unwrap();
+//! sender.send(NORMAL, "backup done").unwrap();
+//! drop(sender);
+//!
+//! assert_eq!(receiver.recv(), Some("disk failure"));
+//! assert_eq!(receiver.recv(), Some("backup done"));
+//! assert_eq!(receiver.recv(), Some("disk usage at 70%"));
+//! assert_eq!(receiver.recv(), None);
+//! ```
+//!
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+};
+
+use crate::error::DataStructureError;
+
+/// How the receiver chooses the lane of the next message
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchedulingPolicy {
+    /// Read the lane with the lowest index that has a message
+    Strict,
+    /// Share the reads between the lanes that have messages in proportion to their weights, one weight per lane
+    WeightedFair(Vec<u32>),
+}
+
+/// State shared by the senders and the receiver
+struct Inner<T> {
+    lanes: Vec<VecDeque<T>>,
+    capacity: usize,   // maximum number of messages of each lane
+    weights: Vec<u32>, // empty for strict priority
+    credits: Vec<i64>,
+
+    senders: usize,
+    receiver_alive: bool,
+}
+
+impl<T> Inner<T> {
+    // Pop the next message, choosing its lane with the scheduling policy
+    fn take_next(&mut self) -> Option<T> {
+        let lane = if self.weights.is_empty() {
+            self.lanes.iter().position(|lane| !lane.is_empty())?
+        } else {
+            let mut total = 0;
+            let mut chosen: Option<usize> = None;
+            for (lane, messages) in self.lanes.iter().enumerate() {
+                if messages.is_empty() {
+                    self.credits[lane] = 0;
+                    continue;
+                }
+                let weight = i64::from(self.weights[lane]);
+                self.credits[lane] += weight;
+                total += weight;
+                if chosen.is_none_or(|best| self.credits[lane] > self.credits[best]) {
+                    chosen = Some(lane);
+                }
+            }
+            let lane = chosen?;
+            self.credits[lane] -= total;
+            lane
+        };
+        self.lanes[lane].pop_front()
+    }
+}
+
+/// Struct representing the state shared by the two sides of a priority channel
+///
+/// The channel is created with `strict` or `with_policy`, which return its `Sender` and `Receiver`.
+///
+pub struct PriorityChannel<T> {
+    inner: Mutex<Inner<T>>,
+    available: Condvar,
+}
+
+/// Struct representing a sending side of a priority channel
+///
+pub struct Sender<T> {
+    channel: Arc<PriorityChannel<T>>,
+}
+
+/// Struct representing the receiving side of a priority channel
+///
+pub struct Receiver<T> {
+    channel: Arc<PriorityChannel<T>>,
+}
+
+impl<T> PriorityChannel<T> {
+    /// Create a new channel with strict priority between its lanes
+    ///
+    /// # Arguments
+    /// * `lanes`: The number of lanes, lane 0 having the highest priority. Must be greater than 0.
+    /// * `capacity`: The maximum number of messages waiting in each lane. Must be greater than 0.
+    ///
+    /// # Returns
+    /// The sender and the receiver of the channel
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::channel::priority::PriorityChannel;
+    ///
+    /// let (sender, mut receiver) = PriorityChannel::strict(2, 8);
+    /// sender.send(1, "low").unwrap();
+    /// sender.send(0, "high").unwrap();
+    ///
+    /// assert_eq!(receiver.try_recv(), Some("high"));
+    /// assert_eq!(receiver.try_recv(), Some("low"));
+    /// ```
+    pub fn strict(lanes: usize, capacity: usize) -> (Sender<T>, Receiver<T>) {
+        Self::with_policy(lanes, capacity, SchedulingPolicy::Strict)
+    }
+
+    /// Create a new channel with the given scheduling policy between its lanes
+    ///
+    /// # Arguments
+    /// * `lanes`: The number of lanes, lane 0 having the highest priority. Must be greater than 0.
+    /// * `capacity`: The maximum number of messages waiting in each lane. Must be greater than 0.
+    /// * `policy`: How the receiver chooses the lane of the next message. Weighted fair scheduling needs one weight
+    ///   greater than 0 per lane.
+    ///
+    /// # Returns
+    /// The sender and the receiver of the channel
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::channel::priority::{PriorityChannel, SchedulingPolicy};
+    ///
+    /// let (sender, mut receiver) =
+    ///     PriorityChannel::with_policy(2, 8, SchedulingPolicy::WeightedFair(vec![2, 1]));
+    /// for i in 0..3 {
+    ///     sender.send(0, ('h', i)).unwrap();
+    ///     sender.send(1, ('l', i)).unwrap();
+    /// }
+    ///
+    /// // Two high messages for each low one
+    /// let lanes: String = (0..6).map(|_| receiver.try_recv().unwrap().0).collect();
+    /// assert_eq!(lanes, "hlhhll");
+    /// ```
+    pub fn with_policy(
+        lanes: usize,
+        capacity: usize,
+        policy: SchedulingPolicy,
+    ) -> (Sender<T>, Receiver<T>) {
+        assert!(lanes > 0, "Number of lanes must be greater than 0");
+        assert!(capacity > 0, "Capacity must be greater than 0");
+        let weights = match policy {
+            SchedulingPolicy::Strict => Vec::new(),
+            SchedulingPolicy::WeightedFair(weights) => {
+                assert_eq!(weights.len(), lanes, "There must be one weight per lane");
+                assert!(
+                    weights.iter().all(|&weight| weight > 0),
+                    "Weights must be greater than 0"
+                );
+                weights
+            }
+        };
+
+        let channel = Arc::new(PriorityChannel {
+            inner: Mutex::new(Inner {
+                lanes: (0..lanes).map(|_| VecDeque::new()).collect(),
+                capacity,
+                weights,
+                credits: vec![0; lanes],
+                senders: 1,
+                receiver_alive: true,
+            }),
+            available: Condvar::new(),
+        });
+
+        (
+            Sender {
+                channel: channel.clone(),
+            },
+            Receiver { channel },
+        )
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner<T>> {
+        self.inner.lock().expect("PriorityChannel lock poisoned")
+    }
+}
+
+impl<T> Sender<T> {
+    /// Get the number of lanes of the channel
+    /// # Returns
+    /// The number of lanes
+    /// # Example
+    /// ```
+    /// use data_structures::channel::priority::PriorityChannel;
+    ///
+    /// let (sender, _receiver) = PriorityChannel::<u32>::strict(3, 8);
+    /// assert_eq!(sender.lanes(), 3);
+    /// ```
+    pub fn lanes(&self) -> usize {
+        self.channel.lock().lanes.len()
+    }
+
+    /// Get the maximum number of messages waiting in each lane
+    /// # Returns
+    /// The capacity of a lane
+    /// # Example
+    /// ```
+    /// use data_structures::channel::priority::PriorityChannel;
+    ///
+    /// let (sender, _receiver) = PriorityChannel::<u32>::strict(3, 8);
+    /// assert_eq!(sender.capacity(), 8);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.channel.lock().capacity
+    }
+
+    /// Send a message to a lane without blocking
+    ///
+    /// # Arguments
+    /// * `lane`: The lane of the message, 0 being the highest priority
+    /// * `value`: The message
+    ///
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the message was queued, Err(DataStructureError::IndexOutOfBounds) if the lane doesn't exist,
+    /// Err(DataStructureError::Full) if the lane is full, or Err(DataStructureError::Disconnected) if the receiver
+    /// was dropped
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::channel::priority::PriorityChannel;
+    /// use data_structures::error::DataStructureError;
+    ///
+    /// let (sender, receiver) = PriorityChannel::strict(2, 1);
+    /// assert_eq!(sender.send(0, 'a'), Ok(()));
+    /// assert_eq!(sender.send(0, 'b'), Err(DataStructureError::Full));
+    /// assert_eq!(sender.send(1, 'b'), Ok(()));
+    /// assert_eq!(sender.send(2, 'c'), Err(DataStructureError::IndexOutOfBounds { index: 2, len: 2 }));
+    ///
+    /// drop(receiver);
+    /// assert_eq!(sender.send(1, 'd'), Err(DataStructureError::Disconnected));
+    /// ```
+    pub fn send(&self, lane: usize, value: T) -> Result<(), DataStructureError> {
+        let mut inner = self.channel.lock();
+        if !inner.receiver_alive {
+            return Err(DataStructureError::Disconnected);
+        }
+        let len = inner.lanes.len();
+        if lane >= len {
+            return Err(DataStructureError::IndexOutOfBounds { index: lane, len });
+        }
+        if inner.lanes[lane].len() == inner.capacity {
+            return Err(DataStructureError::Full);
+        }
+
+        inner.lanes[lane].push_back(value);
+        drop(inner);
+        self.channel.available.notify_one();
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.channel.lock().senders += 1;
+        Sender {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut inner = self.channel.lock();
+        inner.senders -= 1;
+        if inner.senders == 0 {
+            drop(inner);
+            self.channel.available.notify_all();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Get the number of lanes of the channel
+    /// # Returns
+    /// The number of lanes
+    /// # Example
+    /// ```
+    /// use data_structures::channel::priority::PriorityChannel;
+    ///
+    /// let (_sender, receiver) = PriorityChannel::<u32>::strict(3, 8);
+    /// assert_eq!(receiver.lanes(), 3);
+    /// ```
+    pub fn lanes(&self) -> usize {
+        self.channel.lock().lanes.len()
+    }
+
+    /// Get the number of messages waiting, in every lane
+    /// # Returns
+    /// The number of messages waiting
+    /// # Example
+    /// ```
+    /// use data_structures::channel::priority::PriorityChannel;
+    ///
+    /// let (sender, mut receiver) = PriorityChannel::strict(2, 8);
+    /// sender.send(0, 1).unwrap();
+    /// sender.send(1, 2).unwrap();
+    ///
+    /// assert_eq!(receiver.len(), 2);
+    /// receiver.try_recv();
+    /// assert_eq!(receiver.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.channel.lock().lanes.iter().map(VecDeque::len).sum()
+    }
+
+    /// Check if no message is waiting
+    /// # Returns
+    /// true if every lane is empty
+    /// # Example
+    /// ```
+    /// use data_structures::channel::priority::PriorityChannel;
+    ///
+    /// let (sender, receiver) = PriorityChannel::strict(2, 8);
+    /// assert!(receiver.is_empty());
+    /// sender.send(1, ()).unwrap();
+    /// assert!(!receiver.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read the next message without blocking
+    /// # Returns
+    /// Option<T>
+    /// Some with the next message chosen by the scheduling policy, or None if there is no message waiting
+    /// # Example
+    /// ```
+    /// use data_structures::channel::priority::PriorityChannel;
+    ///
+    /// let (sender, mut receiver) = PriorityChannel::strict(2, 8);
+    /// assert_eq!(receiver.try_recv(), None);
+    ///
+    /// sender.send(1, "later").unwrap();
+    /// sender.send(0, "now").unwrap();
+    /// assert_eq!(receiver.try_recv(), Some("now"));
+    /// ```
+    pub fn try_recv(&mut self) -> Option<T> {
+        self.channel.lock().take_next()
+    }
+
+    /// Read the next message, blocking until one is sent
+    /// # Returns
+    /// Option<T>
+    /// Some with the next message chosen by the scheduling policy, or None once every sender is dropped and every
+    /// message was read
+    /// # Example
+    /// ```
+    /// use data_structures::channel::priority::PriorityChannel;
+    ///
+    /// let (sender, mut receiver) = PriorityChannel::strict(2, 8);
+    ///
+    /// let handle = std::thread::spawn(move || {
+    ///     let mut values = Vec::new();
+    ///     while let Some(value) = receiver.recv() {
+    ///         values.push(value);
+    ///     }
+    ///     values
+    /// });
+    ///
+    /// sender.send(1, 1).unwrap();
+    /// sender.send(1, 2).unwrap();
+    /// drop(sender);
+    /// assert_eq!(handle.join().unwrap(), vec![1, 2]);
+    /// ```
+    pub fn recv(&mut self) -> Option<T> {
+        let mut inner = self.channel.lock();
+        loop {
+            if let Some(value) = inner.take_next() {
+                return Some(value);
+            }
+            if inner.senders == 0 {
+                return None;
+            }
+            inner = self
+                .channel
+                .available
+                .wait(inner)
+                .expect("PriorityChannel lock poisoned");
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut inner = self.channel.lock();
+        inner.receiver_alive = false;
+        // Nobody can read the waiting messages anymore
+        inner.lanes.iter_mut().for_each(VecDeque::clear);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheduling_policies() {
+        // Strict priority drains the highest lane first, and each lane in FIFO order
+        let (sender, mut receiver) = PriorityChannel::strict(3, 8);
+        for i in 0..4 {
+            sender.send(2 - i % 3, i).unwrap();
+        }
+        let order: Vec<_> = std::iter::from_fn(|| receiver.try_recv()).collect();
+        assert_eq!(order, vec![2, 1, 0, 3]);
+
+        // With every lane busy, weighted fair scheduling reads each lane in proportion to its weight
+        let (sender, mut receiver) =
+            PriorityChannel::with_policy(3, 100, SchedulingPolicy::WeightedFair(vec![3, 2, 1]));
+        for i in 0..100 {
+            for lane in 0..3 {
+                sender.send(lane, (lane, i)).unwrap();
+            }
+        }
+        let mut counts = [0; 3];
+        let mut expected_next = [0; 3];
+        for read in 1..=60 {
+            let (lane, i) = receiver.try_recv().unwrap();
+            assert_eq!(i, expected_next[lane]);
+            expected_next[lane] += 1;
+            counts[lane] += 1;
+            if read % 6 == 0 {
+                assert_eq!(counts, [read / 2, read / 3, read / 6]);
+            }
+        }
+
+        // Once a lane is empty, the others share the reads
+        let (sender, mut receiver) =
+            PriorityChannel::with_policy(3, 8, SchedulingPolicy::WeightedFair(vec![3, 2, 1]));
+        for i in 0..4 {
+            sender.send(1, i).unwrap();
+            sender.send(2, i + 10).unwrap();
+        }
+        let order: Vec<_> = std::iter::from_fn(|| receiver.try_recv()).collect();
+        assert_eq!(order, vec![0, 10, 1, 2, 11, 3, 12, 13]);
+    }
+
+    #[test]
+    fn test_concurrent_producers() {
+        let (sender, mut receiver) =
+            PriorityChannel::with_policy(3, 4, SchedulingPolicy::WeightedFair(vec![4, 2, 1]));
+        let handles: Vec<_> = (0..3)
+            .map(|lane| {
+                let sender = sender.clone();
+                std::thread::spawn(move || {
+                    for i in 0..1000u64 {
+                        while sender.send(lane, (lane, i)).is_err() {
+                            std::thread::yield_now();
+                        }
+                    }
+                })
+            })
+            .collect();
+        drop(sender);
+
+        let mut next = [0u64; 3];
+        while let Some((lane, i)) = receiver.recv() {
+            assert_eq!(i, next[lane]);
+            next[lane] += 1;
+        }
+        assert_eq!(next, [1000; 3]);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+}
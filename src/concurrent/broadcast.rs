@@ -0,0 +1,491 @@
+//! This module implements a single-producer, multi-consumer broadcast queue.
+//! Every message sent is delivered to every receiver subscribed at that time. Messages live in a shared bounded ring and
+//! each receiver keeps its own cursor over it, so a fast receiver never waits for a slow one to read a message.
+//! When the ring is full because of a slow receiver, the queue either overwrites the oldest message, making the slow
+//! receiver skip it, or rejects the new one, depending on its `SlowConsumerPolicy`.
+//!
+//! # Performance
+//! - O(r) for `send` and `try_recv`, where r is the number of receivers, to find the slowest cursor
+//! - Each message is stored once and cloned for each receiver when it is read
+//!
+//! # Implementation Details
+//! - The ring is a `VecDeque` behind a `Mutex`, with a `Condvar` to wake up blocked receivers.
+//! - Messages are numbered. Each receiver stores the number of the next message it will read, and the ring drops the
+//!   messages every receiver has read.
+//! - A receiver reports its lag (messages waiting for it) and the number of messages it missed because they were
+//!   overwritten.
+//! - Dropping the `Broadcast` closes the queue: receivers read the remaining messages and then get None.
+//!
+//! # Usage
+//! ```
+//! use data_structures::concurrent::broadcast::Broadcast;
+//!
+//! let sender = Broadcast::new(16);
+//! let mut fast = sender.subscribe();
+//! let mut slow = sender.subscribe();
+//!
+//! sender.send("tick").unwrap();
+//! sender.send("tock").unwrap();
+//!
+//! assert_eq!(fast.try_recv(), Some("tick"));
+//! assert_eq!(fast.try_recv(), Some("tock"));
+//!
+//! assert_eq!(slow.lag(), 2);
+//! let handle = std::thread::spawn(move || slow.recv());
+//! assert_eq!(handle.join().unwrap(), Some("tick"));
+//! ```
+//!
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+};
+
+use crate::error::DataStructureError;
+
+/// What a full broadcast queue does with a new message
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlowConsumerPolicy {
+    /// Drop the oldest message. Receivers that hadn't read it skip it and count it as missed.
+    Overwrite,
+    /// Reject the new message until the slowest receiver catches up
+    Reject,
+}
+
+/// State shared by the sender and the receivers
+struct Inner<T> {
+    ring: VecDeque<T>,
+    first: u64, // number of the message at the front of the ring
+    capacity: usize,
+    policy: SlowConsumerPolicy,
+
+    cursors: HashMap<u64, u64>, // next message number of each receiver
+    next_receiver: u64,
+    closed: bool,
+}
+
+impl<T> Inner<T> {
+    // Number of the next message to be sent
+    fn end(&self) -> u64 {
+        self.first + self.ring.len() as u64
+    }
+
+    // Drop the messages every receiver has read
+    fn trim(&mut self) {
+        let slowest = self.cursors.values().copied().min().unwrap_or(self.end());
+        while self.first < slowest && !self.ring.is_empty() {
+            self.ring.pop_front();
+            self.first += 1;
+        }
+    }
+}
+
+impl<T: Clone> Inner<T> {
+    // Read the message at a receiver's cursor and advance it, skipping the overwritten messages
+    fn take_next(&mut self, id: u64, missed: &mut u64) -> Option<T> {
+        let mut cursor = self.cursors[&id];
+
+        // Skip the messages overwritten before this receiver read them
+        if cursor < self.first {
+            *missed += self.first - cursor;
+            cursor = self.first;
+        }
+        if cursor == self.end() {
+            self.cursors.insert(id, cursor);
+            return None;
+        }
+
+        let value = self.ring[(cursor - self.first) as usize].clone();
+        self.cursors.insert(id, cursor + 1);
+        self.trim();
+        Some(value)
+    }
+}
+
+struct Channel<T> {
+    inner: Mutex<Inner<T>>,
+    available: Condvar,
+}
+
+impl<T> Channel<T> {
+    fn lock(&self) -> MutexGuard<'_, Inner<T>> {
+        self.inner.lock().expect("Broadcast lock poisoned")
+    }
+}
+
+/// Struct representing the sending side of a broadcast queue
+///
+pub struct Broadcast<T> {
+    channel: Arc<Channel<T>>,
+}
+
+/// Struct representing a receiver of a broadcast queue, with its own cursor
+///
+pub struct Receiver<T> {
+    channel: Arc<Channel<T>>,
+    id: u64,
+    missed: u64,
+}
+
+impl<T: Clone> Broadcast<T> {
+    /// Create a new Broadcast queue that overwrites the oldest message when full
+    ///
+    /// # Arguments
+    /// * `capacity`: The maximum number of messages kept for the receivers. Must be greater than 0.
+    ///
+    /// # Returns
+    /// A new Broadcast instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::broadcast::Broadcast;
+    ///
+    /// let sender: Broadcast<u32> = Broadcast::new(8);
+    /// assert_eq!(sender.capacity(), 8);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        Self::with_policy(capacity, SlowConsumerPolicy::Overwrite)
+    }
+
+    /// Create a new Broadcast queue with the given policy for slow receivers
+    ///
+    /// # Arguments
+    /// * `capacity`: The maximum number of messages kept for the receivers. Must be greater than 0.
+    /// * `policy`: What to do when the ring is full
+    ///
+    /// # Returns
+    /// A new Broadcast instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::broadcast::{Broadcast, SlowConsumerPolicy};
+    /// use data_structures::error::DataStructureError;
+    ///
+    /// let sender = Broadcast::with_policy(1, SlowConsumerPolicy::Reject);
+    /// let _receiver = sender.subscribe();
+    ///
+    /// assert_eq!(sender.send(1), Ok(()));
+    /// assert_eq!(sender.send(2), Err(DataStructureError::Full));
+    /// ```
+    pub fn with_policy(capacity: usize, policy: SlowConsumerPolicy) -> Self {
+        assert!(capacity > 0, "Capacity must be greater than 0");
+
+        Broadcast {
+            channel: Arc::new(Channel {
+                inner: Mutex::new(Inner {
+                    ring: VecDeque::with_capacity(capacity),
+                    first: 0,
+                    capacity,
+                    policy,
+                    cursors: HashMap::new(),
+                    next_receiver: 0,
+                    closed: false,
+                }),
+                available: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Get the maximum number of messages kept for the receivers
+    /// # Returns
+    /// The capacity of the ring
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::broadcast::Broadcast;
+    ///
+    /// let sender: Broadcast<u32> = Broadcast::new(4);
+    /// assert_eq!(sender.capacity(), 4);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.channel.lock().capacity
+    }
+
+    /// Get the number of subscribed receivers
+    /// # Returns
+    /// The number of live receivers
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::broadcast::Broadcast;
+    ///
+    /// let sender: Broadcast<u32> = Broadcast::new(4);
+    /// let receiver = sender.subscribe();
+    /// assert_eq!(sender.receiver_count(), 1);
+    ///
+    /// drop(receiver);
+    /// assert_eq!(sender.receiver_count(), 0);
+    /// ```
+    pub fn receiver_count(&self) -> usize {
+        self.channel.lock().cursors.len()
+    }
+
+    /// Subscribe a new receiver, which gets every message sent from now on
+    /// # Returns
+    /// A new Receiver
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::broadcast::Broadcast;
+    ///
+    /// let sender = Broadcast::new(4);
+    /// sender.send(1).unwrap();
+    ///
+    /// let mut receiver = sender.subscribe();
+    /// sender.send(2).unwrap();
+    /// assert_eq!(receiver.try_recv(), Some(2));
+    /// ```
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut inner = self.channel.lock();
+        let id = inner.next_receiver;
+        inner.next_receiver += 1;
+        let end = inner.end();
+        inner.cursors.insert(id, end);
+
+        Receiver {
+            channel: self.channel.clone(),
+            id,
+            missed: 0,
+        }
+    }
+
+    /// Send a message to every subscribed receiver
+    ///
+    /// # Arguments
+    /// * `value`: The message
+    ///
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the message was queued, Err if the ring is full and the policy is `Reject`
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::broadcast::Broadcast;
+    ///
+    /// let sender = Broadcast::new(1);
+    /// let mut receiver = sender.subscribe();
+    ///
+    /// sender.send(1).unwrap();
+    /// sender.send(2).unwrap(); // overwrites 1
+    ///
+    /// assert_eq!(receiver.try_recv(), Some(2));
+    /// assert_eq!(receiver.missed(), 1);
+    /// ```
+    pub fn send(&self, value: T) -> Result<(), DataStructureError> {
+        let mut inner = self.channel.lock();
+
+        // Messages sent without receivers are dropped right away
+        if inner.cursors.is_empty() {
+            inner.first += 1;
+            return Ok(());
+        }
+
+        if inner.ring.len() == inner.capacity {
+            match inner.policy {
+                SlowConsumerPolicy::Reject => return Err(DataStructureError::Full),
+                SlowConsumerPolicy::Overwrite => {
+                    inner.ring.pop_front();
+                    inner.first += 1;
+                }
+            }
+        }
+
+        inner.ring.push_back(value);
+        drop(inner);
+        self.channel.available.notify_all();
+        Ok(())
+    }
+}
+
+impl<T> Drop for Broadcast<T> {
+    fn drop(&mut self) {
+        self.channel.lock().closed = true;
+        self.channel.available.notify_all();
+    }
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Get the number of messages waiting for this receiver
+    /// # Returns
+    /// The lag of the receiver behind the sender
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::broadcast::Broadcast;
+    ///
+    /// let sender = Broadcast::new(4);
+    /// let mut receiver = sender.subscribe();
+    /// sender.send(1).unwrap();
+    /// sender.send(2).unwrap();
+    ///
+    /// assert_eq!(receiver.lag(), 2);
+    /// receiver.try_recv();
+    /// assert_eq!(receiver.lag(), 1);
+    /// ```
+    pub fn lag(&self) -> usize {
+        let inner = self.channel.lock();
+        let cursor = inner.cursors[&self.id].max(inner.first);
+        (inner.end() - cursor) as usize
+    }
+
+    /// Get the number of messages this receiver missed because they were overwritten before it read them
+    /// # Returns
+    /// The total number of missed messages
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::broadcast::Broadcast;
+    ///
+    /// let sender = Broadcast::new(2);
+    /// let mut receiver = sender.subscribe();
+    /// for i in 0..5 {
+    ///     sender.send(i).unwrap();
+    /// }
+    ///
+    /// assert_eq!(receiver.try_recv(), Some(3));
+    /// assert_eq!(receiver.missed(), 3);
+    /// ```
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    /// Read the next message without blocking
+    /// # Returns
+    /// Option<T>
+    /// Some with a clone of the next message, or None if there is no message waiting
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::broadcast::Broadcast;
+    ///
+    /// let sender = Broadcast::new(4);
+    /// let mut receiver = sender.subscribe();
+    ///
+    /// assert_eq!(receiver.try_recv(), None);
+    /// sender.send('a').unwrap();
+    /// assert_eq!(receiver.try_recv(), Some('a'));
+    /// ```
+    pub fn try_recv(&mut self) -> Option<T> {
+        let mut inner = self.channel.lock();
+        inner.take_next(self.id, &mut self.missed)
+    }
+
+    /// Read the next message, blocking until one is sent
+    /// # Returns
+    /// Option<T>
+    /// Some with a clone of the next message, or None once the sender is dropped and every message was read
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::broadcast::Broadcast;
+    ///
+    /// let sender = Broadcast::new(4);
+    /// let mut receiver = sender.subscribe();
+    ///
+    /// let handle = std::thread::spawn(move || {
+    ///     let mut values = Vec::new();
+    ///     while let Some(value) = receiver.recv() {
+    ///         values.push(value);
+    ///     }
+    ///     values
+    /// });
+    ///
+    /// sender.send(1).unwrap();
+    /// sender.send(2).unwrap();
+    /// drop(sender);
+    /// assert_eq!(handle.join().unwrap(), vec![1, 2]);
+    /// ```
+    pub fn recv(&mut self) -> Option<T> {
+        let mut inner = self.channel.lock();
+        loop {
+            if let Some(value) = inner.take_next(self.id, &mut self.missed) {
+                return Some(value);
+            }
+            if inner.closed {
+                return None;
+            }
+            inner = self
+                .channel
+                .available
+                .wait(inner)
+                .expect("Broadcast lock poisoned");
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut inner = self.channel.lock();
+        inner.cursors.remove(&self.id);
+        inner.trim();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_independent_cursors() {
+        let sender = Broadcast::new(3);
+        let mut a = sender.subscribe();
+        let mut b = sender.subscribe();
+
+        for i in 0..3 {
+            sender.send(i).unwrap();
+        }
+        assert_eq!(a.try_recv(), Some(0));
+        assert_eq!(a.try_recv(), Some(1));
+
+        // b is the slowest, so the message 3 overwrites the message 0 that only b hadn't read
+        sender.send(3).unwrap();
+        assert_eq!(a.lag(), 2);
+        assert_eq!(b.lag(), 3);
+        assert_eq!(b.try_recv(), Some(1));
+        assert_eq!(b.missed(), 1);
+        assert_eq!(a.missed(), 0);
+
+        // Dropping the slowest receiver frees the messages only it was waiting for
+        drop(b);
+        assert_eq!(a.try_recv(), Some(2));
+        assert_eq!(a.try_recv(), Some(3));
+        assert_eq!(a.try_recv(), None);
+    }
+
+    #[test]
+    fn test_reject_policy() {
+        let sender = Broadcast::with_policy(2, SlowConsumerPolicy::Reject);
+        let mut receiver = sender.subscribe();
+
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+        assert_eq!(sender.send(3), Err(DataStructureError::Full));
+
+        assert_eq!(receiver.try_recv(), Some(1));
+        sender.send(3).unwrap();
+        assert_eq!(receiver.missed(), 0);
+        assert_eq!(receiver.try_recv(), Some(2));
+        assert_eq!(receiver.try_recv(), Some(3));
+    }
+
+    #[test]
+    fn test_concurrent_consumers() {
+        let sender = Broadcast::with_policy(8, SlowConsumerPolicy::Reject);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let mut receiver = sender.subscribe();
+                std::thread::spawn(move || {
+                    let mut sum = 0u64;
+                    while let Some(value) = receiver.recv() {
+                        sum += value;
+                    }
+                    sum
+                })
+            })
+            .collect();
+
+        for i in 1..=1000u64 {
+            while sender.send(i).is_err() {
+                std::thread::yield_now();
+            }
+        }
+        drop(sender);
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 500_500);
+        }
+    }
+}
@@ -0,0 +1,482 @@
+//! This module implements a least recently used cache shared by many threads, without a global lock.
+//! The keys are spread over shards by hash, each shard behind its own mutex with its own recency order, so threads
+//! working on different shards never wait for each other. The capacity is global: when an insertion overflows it, a few
+//! shards are sampled and the least recently used entry among their oldest ones is evicted, which approximates a global
+//! LRU order.
+//!
+//! # Performance
+//! - O(log n) for `get`, `put` and `remove`, with n the number of entries of a shard, locking that shard only
+//! - An eviction locks the sampled shards one after the other, never two at a time
+//!
+//! # Implementation Details
+//! - Each shard keeps its entries in a `HashMap` with their recency stamp, and orders its keys in a `BTreeMap` indexed by
+//!   stamp, like the segments of `SegmentedCache`.
+//! - Stamps come from one atomic clock, so the stamps of different shards can be compared when choosing the victim.
+//! - The victim is the oldest entry among the shard that overflowed and three other shards taken in turn.
+//! - Hits, misses and evictions are kept in `StripedCounter`s and reported as `CacheStats`.
+//!
+//! # Usage
+//! ```
+//! use data_structures::concurrent::lru::ConcurrentLru;
+//! use std::sync::Arc;
+//! use std::thread;
+//!
+//! let cache = Arc::new(ConcurrentLru::new(1000));
+//!
+//! let workers: Vec<_> = (0..4u64)
+//!     .map(|worker| {
+//!         let cache = cache.clone();
+//!         thread::spawn(move || {
+//!             for key in 0..500 {
+//!                 if cache.get(&key).is_none() {
+//!                     cache.put(key, key * worker);
+//!                 }
+//!             }
+//!         })
+//!     })
+//!     .collect();
+//! for worker in workers {
+//!     worker.join().unwrap();
+//! }
+//!
+//! assert_eq!(cache.len(), 500);
+//! let stats = cache.stats();
+//! assert_eq!(stats.hits + stats.misses, 2000);
+//! ```
+//!
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+
+use super::striped_counter::StripedCounter;
+use crate::cache::segmented_cache::CacheStats;
+
+// Number of shards looked at to choose a victim, including the one that overflowed
+const SAMPLES: usize = 4;
+
+/// Entries of one shard and their recency order
+struct Shard<K, V> {
+    entries: HashMap<K, (V, u64)>,
+    order: BTreeMap<u64, K>,
+}
+
+impl<K: Hash + Eq + Clone, V> Shard<K, V> {
+    fn oldest(&self) -> Option<u64> {
+        self.order.keys().next().copied()
+    }
+
+    fn evict_oldest(&mut self) -> Option<(K, V)> {
+        let (_, key) = self.order.pop_first()?;
+        let (value, _) = self.entries.remove(&key).unwrap();
+        Some((key, value))
+    }
+}
+
+/// Struct representing a least recently used cache split into independently locked shards
+///
+pub struct ConcurrentLru<K, V> {
+    shards: Box<[Mutex<Shard<K, V>>]>,
+    hasher: RandomState,
+    capacity: usize,
+
+    len: AtomicUsize,
+    clock: AtomicU64,
+    next_sample: AtomicUsize,
+
+    hits: StripedCounter,
+    misses: StripedCounter,
+    evictions: StripedCounter,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> ConcurrentLru<K, V> {
+    /// Create a new empty cache, with four shards per available core
+    ///
+    /// # Arguments
+    /// * `capacity`: The maximum number of entries kept in the cache. A capacity of 0 is replaced by 1.
+    ///
+    /// # Returns
+    /// A new ConcurrentLru instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// let cache: ConcurrentLru<u32, String> = ConcurrentLru::new(100);
+    /// assert_eq!(cache.capacity(), 100);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+        Self::with_shards(capacity, 4 * cores)
+    }
+
+    /// Create a new empty cache with the given number of shards
+    ///
+    /// # Arguments
+    /// * `capacity`: The maximum number of entries kept in the cache. A capacity of 0 is replaced by 1.
+    /// * `shards`: The number of independently locked shards. A value of 0 is replaced by 1.
+    ///
+    /// # Returns
+    /// A new ConcurrentLru instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// let cache: ConcurrentLru<u32, u32> = ConcurrentLru::with_shards(1000, 16);
+    /// assert_eq!(cache.shards(), 16);
+    /// ```
+    pub fn with_shards(capacity: usize, shards: usize) -> Self {
+        let shards = (0..shards.max(1))
+            .map(|_| {
+                Mutex::new(Shard {
+                    entries: HashMap::new(),
+                    order: BTreeMap::new(),
+                })
+            })
+            .collect();
+
+        ConcurrentLru {
+            shards,
+            hasher: RandomState::new(),
+            capacity: capacity.max(1),
+            len: AtomicUsize::new(0),
+            clock: AtomicU64::new(0),
+            next_sample: AtomicUsize::new(0),
+            hits: StripedCounter::new(),
+            misses: StripedCounter::new(),
+            evictions: StripedCounter::new(),
+        }
+    }
+
+    /// Get the maximum number of entries of the cache
+    /// # Returns
+    /// The capacity of the cache
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// let cache: ConcurrentLru<u8, u8> = ConcurrentLru::new(8);
+    /// assert_eq!(cache.capacity(), 8);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the number of shards
+    /// # Returns
+    /// The number of independently locked shards
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// let cache: ConcurrentLru<u8, u8> = ConcurrentLru::with_shards(8, 2);
+    /// assert_eq!(cache.shards(), 2);
+    /// ```
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Get the number of entries in the cache
+    /// While other threads insert, the count can exceed the capacity for the time of their evictions.
+    /// # Returns
+    /// The number of cached entries
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// let cache = ConcurrentLru::new(8);
+    /// cache.put(1, 'a');
+    /// assert_eq!(cache.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Check if the cache is empty
+    /// # Returns
+    /// True if the cache has no entries, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// let cache: ConcurrentLru<u8, u8> = ConcurrentLru::new(8);
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the hit, miss and eviction counters
+    /// # Returns
+    /// The statistics of the cache, added up over every thread
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// let cache = ConcurrentLru::new(4);
+    /// cache.put("a", 1);
+    /// cache.get(&"a");
+    /// cache.get(&"b");
+    ///
+    /// assert_eq!(cache.stats().hit_rate(), 0.5);
+    /// ```
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.sum(),
+            misses: self.misses.sum(),
+            evictions: self.evictions.sum(),
+        }
+    }
+
+    /// Reset the hit, miss and eviction counters
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// let cache: ConcurrentLru<u8, u8> = ConcurrentLru::new(1);
+    /// cache.get(&1);
+    /// cache.reset_stats();
+    /// assert_eq!(cache.stats().misses, 0);
+    /// ```
+    pub fn reset_stats(&self) {
+        self.hits.reset();
+        self.misses.reset();
+        self.evictions.reset();
+    }
+
+    /// Check if a key is cached, without updating its recency or the statistics
+    /// # Arguments
+    /// * `key`: The key to be checked
+    /// # Returns
+    /// True if the key is cached, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// let cache = ConcurrentLru::new(2);
+    /// cache.put("key", ());
+    /// assert!(cache.contains(&"key"));
+    /// assert_eq!(cache.stats().hits, 0);
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.shard(key).entries.contains_key(key)
+    }
+
+    /// Get a clone of the value of a key, making it the most recently used entry and counting a hit or a miss
+    /// # Arguments
+    /// * `key`: The key to be searched
+    /// # Returns
+    /// A clone of the value, or None if the key isn't cached
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// let cache = ConcurrentLru::new(2);
+    /// cache.put(1, "one");
+    ///
+    /// assert_eq!(cache.get(&1), Some("one"));
+    /// assert_eq!(cache.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut shard = self.shard(key);
+        let stamp = self.tick();
+
+        let Some((value, old_stamp)) = shard.entries.get_mut(key) else {
+            drop(shard);
+            self.misses.increment();
+            return None;
+        };
+        let value = value.clone();
+        let old_stamp = std::mem::replace(old_stamp, stamp);
+
+        let key = shard.order.remove(&old_stamp).unwrap();
+        shard.order.insert(stamp, key);
+        drop(shard);
+
+        self.hits.increment();
+        Some(value)
+    }
+
+    /// Insert or update an entry, making it the most recently used one
+    /// # Arguments
+    /// * `key`: The key of the entry
+    /// * `value`: The value of the entry
+    /// # Returns
+    /// The entry evicted to make room for the new one, if any
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// // A single shard makes the eviction order exact
+    /// let cache = ConcurrentLru::with_shards(2, 1);
+    ///
+    /// assert_eq!(cache.put("a", 1), None);
+    /// assert_eq!(cache.put("b", 2), None);
+    /// cache.get(&"a");
+    /// assert_eq!(cache.put("c", 3), Some(("b", 2)));
+    /// ```
+    pub fn put(&self, key: K, value: V) -> Option<(K, V)> {
+        let index = self.shard_index(&key);
+        let mut shard = self.lock(index);
+        let stamp = self.tick();
+
+        if let Some((old_value, old_stamp)) = shard.entries.get_mut(&key) {
+            *old_value = value;
+            let old_stamp = std::mem::replace(old_stamp, stamp);
+            shard.order.remove(&old_stamp);
+            shard.order.insert(stamp, key);
+            return None;
+        }
+
+        shard.order.insert(stamp, key.clone());
+        shard.entries.insert(key, (value, stamp));
+        // Counted before the shard is unlocked, so an entry can't be evicted before it is counted
+        let len = self.len.fetch_add(1, Ordering::Relaxed);
+        drop(shard);
+
+        if len < self.capacity {
+            return None;
+        }
+        self.evict(index)
+    }
+
+    /// Remove an entry from the cache
+    /// # Arguments
+    /// * `key`: The key to be removed
+    /// # Returns
+    /// The value of the removed entry, or None if the key isn't cached
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::lru::ConcurrentLru;
+    ///
+    /// let cache = ConcurrentLru::new(2);
+    /// cache.put(1, 'x');
+    ///
+    /// assert_eq!(cache.remove(&1), Some('x'));
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn remove(&self, key: &K) -> Option<V> {
+        let mut shard = self.shard(key);
+        let (value, stamp) = shard.entries.remove(key)?;
+        shard.order.remove(&stamp);
+        drop(shard);
+
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        Some(value)
+    }
+
+    // Evict the oldest entry among the overflowing shard and a few others, holding one lock at a time
+    fn evict(&self, overflowing: usize) -> Option<(K, V)> {
+        loop {
+            let first = self.next_sample.fetch_add(SAMPLES - 1, Ordering::Relaxed);
+            let samples = std::iter::once(overflowing)
+                .chain((first..first + SAMPLES - 1).map(|index| index % self.shards.len()));
+
+            let victim = samples
+                .filter_map(|index| self.lock(index).oldest().map(|stamp| (stamp, index)))
+                .min();
+
+            // Without a victim in the samples, fall back to the first non empty shard
+            let victim = victim.map(|(_, index)| index).or_else(|| {
+                (0..self.shards.len()).find(|&index| !self.lock(index).order.is_empty())
+            });
+
+            // Another thread may have emptied the shard since it was sampled
+            match victim.and_then(|index| self.lock(index).evict_oldest()) {
+                Some(entry) => {
+                    self.len.fetch_sub(1, Ordering::Relaxed);
+                    self.evictions.increment();
+                    return Some(entry);
+                }
+                None if self.len() <= self.capacity => return None,
+                None => continue,
+            }
+        }
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn shard_index(&self, key: &K) -> usize {
+        (self.hasher.hash_one(key) % self.shards.len() as u64) as usize
+    }
+
+    fn shard(&self, key: &K) -> MutexGuard<'_, Shard<K, V>> {
+        self.lock(self.shard_index(key))
+    }
+
+    fn lock(&self, index: usize) -> MutexGuard<'_, Shard<K, V>> {
+        self.shards[index]
+            .lock()
+            .expect("Cache shard lock poisoned")
+    }
+}
+
+impl<K, V> fmt::Debug for ConcurrentLru<K, V> {
+    /// Prints the size and the shape of the cache, without locking the shards
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConcurrentLru")
+            .field("len", &self.len.load(Ordering::Relaxed))
+            .field("capacity", &self.capacity)
+            .field("shards", &self.shards.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_single_shard_is_exact_lru() {
+        let cache = ConcurrentLru::with_shards(3, 1);
+        for key in 0..3 {
+            cache.put(key, key);
+        }
+        cache.get(&0);
+
+        assert_eq!(cache.put(3, 3), Some((1, 1)));
+        assert_eq!(cache.put(4, 4), Some((2, 2)));
+        assert_eq!(cache.put(5, 5), Some((0, 0)));
+        assert_eq!(cache.stats().evictions, 3);
+    }
+
+    #[test]
+    fn test_concurrent_capacity() {
+        let cache = Arc::new(ConcurrentLru::with_shards(100, 8));
+
+        let threads: Vec<_> = (0..8u64)
+            .map(|thread| {
+                let cache = cache.clone();
+                thread::spawn(move || {
+                    for i in 0..2_000 {
+                        let key = (thread * 7_919 + i * 31) % 500;
+                        if cache.get(&key).is_none() {
+                            cache.put(key, key);
+                        }
+                        if i % 10 == 0 {
+                            cache.remove(&((key + 1) % 500));
+                        }
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // Once the writers are done, the count is exact and within the capacity
+        let cached = (0..500).filter(|key| cache.contains(key)).count();
+        assert_eq!(cached, cache.len());
+        assert!(cache.len() <= 100);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits + stats.misses, 16_000);
+    }
+}
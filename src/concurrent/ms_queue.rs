@@ -0,0 +1,434 @@
+//! This module implements the Michael-Scott queue: an unbounded FIFO queue that any number of threads can push to and
+//! pop from without locks. A thread that stalls in the middle of an operation never blocks the others, which help it
+//! finish instead of waiting for it.
+//!
+//! # Performance
+//! - O(1) for `push` and `try_pop` without contention. Under contention a compare-and-swap may fail and be retried, but
+//!   some thread always makes progress.
+//!
+//! # Implementation Details
+//! - The elements are a singly linked list of nodes, from the head to the tail. The head is a sentinel node whose value
+//!   was already popped, so the queue is never without a node and pushes and pops work on different ends.
+//! - `push` links the new node after the last one with a compare-and-swap, then swings the tail to it. A thread that
+//!   finds the tail lagging behind swings it itself before going on.
+//! - `try_pop` swings the head to the next node and moves the value out of it; that node becomes the new sentinel.
+//! - Popped sentinels may still be read by other threads, so they are freed through the epoch reclaimer of the
+//!   `reclaim` module. Each thread registers in the queue's collector and passes its handle to the operations.
+//!
+//! # Usage
+//! ```
+//! use data_structures::concurrent::ms_queue::MsQueue;
+//! use std::sync::Arc;
+//! use std::thread;
+//!
+//! let queue = Arc::new(MsQueue::new());
+//!
+//! let producers: Vec<_> = (0..4)
+//!     .map(|producer| {
+//!         let queue = queue.clone();
+//!         thread::spawn(move || {
+//!             let handle = queue.register();
+//!             for i in 0..100 {
+//!                 queue.push(producer * 100 + i, &handle);
+//!             }
+//!         })
+//!     })
+//!     .collect();
+//! for producer in producers {
+//!     producer.join().unwrap();
+//! }
+//!
+//! let handle = queue.register();
+//! let mut popped = Vec::new();
+//! while let Some(value) = queue.try_pop(&handle) {
+//!     popped.push(value);
+//! }
+//! popped.sort();
+//! assert_eq!(popped, (0..400).collect::<Vec<_>>());
+//! ```
+//!
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::reclaim::epoch::{Collector, LocalHandle};
+
+/// A node of the queue. The value is uninitialized in the sentinel, and dropping a node never drops its value.
+struct Node<T> {
+    value: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn boxed(value: MaybeUninit<T>) -> *mut Node<T> {
+        Box::into_raw(Box::new(Node {
+            value,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+/// Struct representing an unbounded lock-free multi-producer multi-consumer queue
+///
+pub struct MsQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    collector: Collector,
+    values: PhantomData<T>,
+}
+
+// SAFETY: the values are moved between threads but never shared, so the queue is safe to share when they can be sent
+unsafe impl<T: Send> Sync for MsQueue<T> {}
+
+impl<T: Send + 'static> MsQueue<T> {
+    /// Create a new empty queue with its own collector
+    ///
+    /// # Returns
+    /// A new MsQueue instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::ms_queue::MsQueue;
+    ///
+    /// let queue: MsQueue<u32> = MsQueue::new();
+    /// assert!(queue.is_empty(&queue.register()));
+    /// ```
+    pub fn new() -> Self {
+        Self::with_collector(Collector::new())
+    }
+
+    /// Create a new empty queue freeing its nodes through a given collector
+    /// Sharing a collector between the structures used by the same threads lets them register once for all of them.
+    ///
+    /// # Arguments
+    /// * `collector`: The collector the popped nodes are deferred to
+    ///
+    /// # Returns
+    /// A new MsQueue instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::ms_queue::MsQueue;
+    /// use data_structures::reclaim::epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let requests = MsQueue::with_collector(collector.clone());
+    /// let responses = MsQueue::with_collector(collector.clone());
+    ///
+    /// let handle = collector.register();
+    /// requests.push("ping", &handle);
+    /// responses.push("pong", &handle);
+    /// assert_eq!(requests.try_pop(&handle), Some("ping"));
+    /// ```
+    pub fn with_collector(collector: Collector) -> Self {
+        let sentinel = Node::boxed(MaybeUninit::uninit());
+
+        MsQueue {
+            head: AtomicPtr::new(sentinel),
+            tail: AtomicPtr::new(sentinel),
+            collector,
+            values: PhantomData,
+        }
+    }
+
+    /// Get the collector the popped nodes are deferred to
+    /// # Returns
+    /// The collector of the queue
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::ms_queue::MsQueue;
+    ///
+    /// let queue: MsQueue<u32> = MsQueue::new();
+    /// let handle = queue.collector().register();
+    /// assert!(queue.is_empty(&handle));
+    /// ```
+    pub fn collector(&self) -> &Collector {
+        &self.collector
+    }
+
+    /// Register the current thread in the collector of the queue
+    /// # Returns
+    /// The handle to pass to the operations of the queue from this thread
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::ms_queue::MsQueue;
+    ///
+    /// let queue = MsQueue::new();
+    /// let handle = queue.register();
+    /// queue.push(1, &handle);
+    /// ```
+    pub fn register(&self) -> LocalHandle {
+        self.collector.register()
+    }
+
+    /// Add an element at the back of the queue
+    /// # Arguments
+    /// * `value`: The element to add
+    /// * `handle`: The handle of the current thread in the collector of the queue
+    /// # Panics
+    /// If the handle was registered in another collector
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::ms_queue::MsQueue;
+    ///
+    /// let queue = MsQueue::new();
+    /// let handle = queue.register();
+    ///
+    /// queue.push('a', &handle);
+    /// queue.push('b', &handle);
+    /// assert_eq!(queue.try_pop(&handle), Some('a'));
+    /// ```
+    pub fn push(&self, value: T, handle: &LocalHandle) {
+        self.check_handle(handle);
+        let node = Node::boxed(MaybeUninit::new(value));
+        let _guard = handle.pin();
+
+        loop {
+            let tail = self.tail.load(Ordering::Acquire);
+            // SAFETY: the thread is pinned, so the tail can't be freed before the guard is dropped
+            let next = unsafe { &(*tail).next };
+            let after_tail = next.load(Ordering::Acquire);
+
+            if !after_tail.is_null() {
+                // The tail is lagging behind, help the pushing thread move it
+                let _ = self.tail.compare_exchange(
+                    tail,
+                    after_tail,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                );
+                continue;
+            }
+
+            if next
+                .compare_exchange(ptr::null_mut(), node, Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                // If this fails another thread already moved the tail to the new node
+                let _ =
+                    self.tail
+                        .compare_exchange(tail, node, Ordering::Release, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+
+    /// Remove the element at the front of the queue, if any
+    /// # Arguments
+    /// * `handle`: The handle of the current thread in the collector of the queue
+    /// # Returns
+    /// The oldest element, or None if the queue was empty
+    /// # Panics
+    /// If the handle was registered in another collector
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::ms_queue::MsQueue;
+    ///
+    /// let queue = MsQueue::new();
+    /// let handle = queue.register();
+    ///
+    /// assert_eq!(queue.try_pop(&handle), None);
+    /// queue.push(1, &handle);
+    /// assert_eq!(queue.try_pop(&handle), Some(1));
+    /// ```
+    pub fn try_pop(&self, handle: &LocalHandle) -> Option<T> {
+        self.check_handle(handle);
+        let guard = handle.pin();
+
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            // SAFETY: the thread is pinned, so the head can't be freed before the guard is dropped
+            let next = unsafe { (*head).next.load(Ordering::Acquire) };
+            if next.is_null() {
+                return None;
+            }
+
+            let tail = self.tail.load(Ordering::Acquire);
+            if head == tail {
+                // The head must not pass the tail, help the pushing thread move it first
+                let _ =
+                    self.tail
+                        .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+                continue;
+            }
+
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                // SAFETY: winning the exchange makes this thread the only one to read the value of the new sentinel,
+                // which was initialized by its push
+                let value = unsafe { (*next).value.assume_init_read() };
+                // SAFETY: the old sentinel is unlinked, and only the thread winning the exchange defers it
+                unsafe { guard.defer_destroy(head) };
+                return Some(value);
+            }
+        }
+    }
+
+    /// Check if the queue is empty
+    /// Other threads may push or pop right after the check, so the result is only a snapshot.
+    /// # Arguments
+    /// * `handle`: The handle of the current thread in the collector of the queue
+    /// # Returns
+    /// True if the queue had no element when checked
+    /// # Panics
+    /// If the handle was registered in another collector
+    /// # Example
+    /// ```
+    /// use data_structures::concurrent::ms_queue::MsQueue;
+    ///
+    /// let queue = MsQueue::new();
+    /// let handle = queue.register();
+    ///
+    /// queue.push(1, &handle);
+    /// assert!(!queue.is_empty(&handle));
+    /// ```
+    pub fn is_empty(&self, handle: &LocalHandle) -> bool {
+        self.check_handle(handle);
+        let _guard = handle.pin();
+
+        let head = self.head.load(Ordering::Acquire);
+        // SAFETY: the thread is pinned, so the head can't be freed before the guard is dropped
+        unsafe { (*head).next.load(Ordering::Acquire).is_null() }
+    }
+
+    // Pins of a handle from another collector wouldn't delay the destruction of the nodes
+    fn check_handle(&self, handle: &LocalHandle) {
+        assert!(
+            handle.is_registered_in(&self.collector),
+            "Handle registered in another collector"
+        );
+    }
+}
+
+impl<T: Send + 'static> Default for MsQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for MsQueue<T> {
+    fn drop(&mut self) {
+        // No other thread can access the queue anymore, so the nodes are freed directly
+        let sentinel = *self.head.get_mut();
+        // SAFETY: every node of the list was allocated by Node::boxed and is owned by the queue
+        let mut current = unsafe { Box::from_raw(sentinel) }.next.into_inner();
+        while !current.is_null() {
+            // SAFETY: as above, and the nodes after the sentinel hold an initialized value
+            let mut node = unsafe { Box::from_raw(current) };
+            unsafe { node.value.assume_init_drop() };
+            current = node.next.into_inner();
+        }
+    }
+}
+
+impl<T> fmt::Debug for MsQueue<T> {
+    /// Prints the collector only, reading the elements would need a handle
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MsQueue")
+            .field("collector", &self.collector)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_concurrent_producers_and_consumers() {
+        const PRODUCERS: usize = 4;
+        const PER_PRODUCER: usize = 20_000;
+        let queue = Arc::new(MsQueue::new());
+        let consumed = Arc::new(AtomicUsize::new(0));
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|producer| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    let handle = queue.register();
+                    for i in 0..PER_PRODUCER {
+                        queue.push((producer, i), &handle);
+                    }
+                })
+            })
+            .collect();
+
+        let consumers: Vec<_> = (0..4)
+            .map(|_| {
+                let queue = queue.clone();
+                let consumed = consumed.clone();
+                thread::spawn(move || {
+                    let handle = queue.register();
+                    let mut popped = Vec::new();
+                    while consumed.load(Ordering::SeqCst) < PRODUCERS * PER_PRODUCER {
+                        if let Some(value) = queue.try_pop(&handle) {
+                            consumed.fetch_add(1, Ordering::SeqCst);
+                            popped.push(value);
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let mut seen = vec![Vec::new(); PRODUCERS];
+        for consumer in consumers {
+            let mut last = [None; PRODUCERS];
+            for (producer, i) in consumer.join().unwrap() {
+                // Each consumer sees the elements of a producer in the order they were pushed
+                assert!(last[producer] < Some(i));
+                last[producer] = Some(i);
+                seen[producer].push(i);
+            }
+        }
+
+        // Every element was popped exactly once
+        for mut values in seen {
+            values.sort();
+            assert_eq!(values, (0..PER_PRODUCER).collect::<Vec<_>>());
+        }
+        assert!(queue.is_empty(&queue.register()));
+    }
+
+    #[test]
+    fn test_elements_dropped_once() {
+        let tracker = Arc::new(());
+        let queue = MsQueue::new();
+        let handle = queue.register();
+
+        for _ in 0..100 {
+            queue.push(tracker.clone(), &handle);
+        }
+        for _ in 0..40 {
+            drop(queue.try_pop(&handle));
+        }
+        assert_eq!(Arc::strong_count(&tracker), 61);
+
+        // The popped sentinels are freed without dropping their moved-out values again
+        handle.pin().flush();
+        handle.pin().flush();
+        assert_eq!(queue.collector().pending(), 0);
+        assert_eq!(Arc::strong_count(&tracker), 61);
+
+        drop(handle);
+        drop(queue);
+        assert_eq!(Arc::strong_count(&tracker), 1);
+
+        let other = Collector::new().register();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            MsQueue::<u8>::new().push(1, &other)
+        }));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! This module implements a copy-on-write wrapper for any cloneable container.
+//! Cloning the wrapper only shares the container, so snapshots handed to readers are cheap. The first mutation made
+//! through a wrapper whose container is shared clones it, and the writer goes on with its own copy while the readers
+//! keep the snapshot they were given.
+//!
+//! # Performance
+//! - O(1) for `clone`, `snapshot` and reads through `Deref`
+//! - O(1) for `make_mut` when the container isn't shared, a full clone of the container otherwise
+//!
+//! # Implementation Details
+//! - The container is kept behind a `Ptr`, so it is an `Rc` by default and an `Arc` with the `arc` feature, which lets
+//!   snapshots be sent to other threads.
+//! - `make_mut` has the semantics of `Rc::make_mut`: it clones only if another wrapper or snapshot still points to
+//!   the container.
+//!
+//! # Usage
+//! ```
+//! use data_structures::cow::cow_container::CowContainer;
+//!
+//! let mut writer = CowContainer::new(vec![1, 2, 3]);
+//! let reader = writer.clone();
+//!
+//! writer.make_mut().push(4);
+//!
+//! assert_eq!(*reader, vec![1, 2, 3]);
+//! assert_eq!(*writer, vec![1, 2, 3, 4]);
+//! ```
+//!
+use std::fmt;
+use std::ops::Deref;
+
+use crate::linked_list::shared::Ptr;
+
+/// Struct representing a container shared between its clones until one of them changes it
+///
+pub struct CowContainer<C> {
+    inner: Ptr<C>,
+}
+
+impl<C: Clone> CowContainer<C> {
+    /// Wrap a container
+    ///
+    /// # Arguments
+    /// * `container`: The container to share
+    ///
+    /// # Returns
+    /// A new CowContainer instance, the only owner of the container
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::cow::cow_container::CowContainer;
+    ///
+    /// let wrapper = CowContainer::new(String::from("text"));
+    /// assert!(!wrapper.is_shared());
+    /// ```
+    pub fn new(container: C) -> Self {
+        CowContainer {
+            inner: Ptr::new(container),
+        }
+    }
+
+    /// Get a mutable reference to the container, cloning it first if it is shared
+    /// # Returns
+    /// A mutable reference to a container owned by this wrapper only
+    /// # Example
+    /// ```
+    /// use data_structures::cow::cow_container::CowContainer;
+    ///
+    /// let mut wrapper = CowContainer::new(vec![1]);
+    /// let snapshot = wrapper.snapshot();
+    ///
+    /// wrapper.make_mut()[0] = 10;
+    /// assert_eq!(*snapshot, vec![1]);
+    /// assert_eq!(*wrapper, vec![10]);
+    /// ```
+    pub fn make_mut(&mut self) -> &mut C {
+        Ptr::make_mut(&mut self.inner)
+    }
+
+    /// Get a read-only snapshot of the container, unaffected by later mutations of this wrapper
+    /// # Returns
+    /// A shared pointer to the current container
+    /// # Example
+    /// ```
+    /// use data_structures::cow::cow_container::CowContainer;
+    ///
+    /// let wrapper = CowContainer::new(vec!['a', 'b']);
+    /// let snapshot = wrapper.snapshot();
+    ///
+    /// assert_eq!(snapshot.len(), 2);
+    /// assert!(wrapper.is_shared());
+    /// ```
+    pub fn snapshot(&self) -> Ptr<C> {
+        self.inner.clone()
+    }
+
+    /// Check if the container is shared with other wrappers or snapshots
+    /// # Returns
+    /// True if the next `make_mut` will clone the container
+    /// # Example
+    /// ```
+    /// use data_structures::cow::cow_container::CowContainer;
+    ///
+    /// let mut wrapper = CowContainer::new(vec![0u8; 4]);
+    /// let other = wrapper.clone();
+    /// assert!(wrapper.is_shared());
+    ///
+    /// wrapper.make_mut();
+    /// assert!(!wrapper.is_shared());
+    /// assert!(!other.is_shared());
+    /// ```
+    pub fn is_shared(&self) -> bool {
+        Ptr::strong_count(&self.inner) > 1
+    }
+
+    /// Unwrap the container, cloning it if it is shared
+    /// # Returns
+    /// The container
+    /// # Example
+    /// ```
+    /// use data_structures::cow::cow_container::CowContainer;
+    ///
+    /// let wrapper = CowContainer::new(vec![1, 2]);
+    /// assert_eq!(wrapper.into_inner(), vec![1, 2]);
+    /// ```
+    pub fn into_inner(self) -> C {
+        Ptr::unwrap_or_clone(self.inner)
+    }
+}
+
+impl<C> Clone for CowContainer<C> {
+    /// Shares the container, without cloning it
+    fn clone(&self) -> Self {
+        CowContainer {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C> Deref for CowContainer<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.inner
+    }
+}
+
+impl<C: Clone + Default> Default for CowContainer<C> {
+    fn default() -> Self {
+        Self::new(C::default())
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for CowContainer<C> {
+    /// Prints the container
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range::segment_tree::SegmentTree;
+
+    #[test]
+    fn test_snapshots_keep_their_state() {
+        let tree = SegmentTree::new(vec![1, 2, 3, 4], 0, |a: &i32, b: &i32| a + b);
+        let mut writer = CowContainer::new(tree);
+        let readers: Vec<_> = (0..3).map(|_| writer.snapshot()).collect();
+
+        writer.make_mut().set(0, 10).unwrap();
+        let copy = writer.snapshot();
+        writer.make_mut().set(1, 20).unwrap();
+
+        // The readers see the original, the copy sees the first write only
+        for reader in &readers {
+            assert_eq!(reader.query(0..4), Ok(10));
+        }
+        assert_eq!(copy.query(0..4), Ok(19));
+        assert_eq!(writer.query(0..4), Ok(37));
+        assert!(!writer.is_shared());
+    }
+}
@@ -0,0 +1,246 @@
+//! This module defines the error type shared by every fallible operation of the crate.
+//! Each failure is a variant of `DataStructureError`, carrying the values that caused it when they are useful to the
+//! caller, so errors can be matched on instead of compared as strings.
+//!
+//! # Implementation Details
+//! - The messages printed by `Display` start with a capital letter and have no trailing period, like the rest of
+//!   the crate.
+//! - With the `std` feature, the enum implements `std::error::Error`, so it converts into `Box<dyn Error>` and
+//!   `io::Error` with `?`.
+//! - Decoding failures of the `persist` module share a few variants; the less common ones carry a static reason.
+//! - The enum is `#[non_exhaustive]`, so matches outside the crate need a wildcard arm and new variants can be added.
+//!
+//! # Usage
+//! ```
+//! use data_structures::error::DataStructureError;
+//! use data_structures::linked_list::fifo::FIFO;
+//!
+//! let mut fifo = FIFO::new(2);
+//! fifo.push(1).unwrap();
+//! fifo.push(2).unwrap();
+//!
+//! match fifo.push(3) {
+//!     Err(DataStructureError::Full) => {}
+//!     other => panic!("unexpected result: {:?}", other),
+//! }
+//!
+//! let error = fifo.set_max_size(1).unwrap_err();
+//! assert_eq!(error, DataStructureError::CapacityBelowLen { len: 2, requested: 1 });
+//! assert_eq!(error.to_string(), "New max size 1 is less than current size 2");
+//! ```
+//!
+use core::fmt;
+
+/// Error returned by the fallible operations of the data structures
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DataStructureError {
+    /// The structure is at its maximum size and the overflow policy kept every element
+    Full,
+    /// The requested maximum size is smaller than the number of elements held
+    CapacityBelowLen { len: usize, requested: usize },
+    /// A single element needs more bytes than the whole budget
+    ElementTooLarge { size: usize, budget: usize },
+    /// The elements held leave no room in the byte budget for a new one
+    BudgetExceeded,
+    /// A custom overflow policy chose a position past the end of the queue
+    InvalidPosition { position: usize, len: usize },
+    /// The memory for a new node couldn't be allocated
+    AllocationFailed,
+    /// The index is not smaller than the length
+    IndexOutOfBounds { index: usize, len: usize },
+    /// The range is reversed or ends past the length
+    RangeOutOfBounds {
+        start: usize,
+        end: usize,
+        len: usize,
+    },
+    /// The operation has no result for an empty range
+    EmptyRange,
+    /// The rank is not smaller than the number of elements in the range
+    RankOutOfRange { rank: usize, len: usize },
+    /// The version hasn't been created yet
+    UnknownVersion(usize),
+    /// The version was discarded to bound the history
+    PrunedVersion(usize),
+    /// The tree already has a root
+    RootOccupied,
+    /// The node already has a child on this side
+    ChildOccupied,
+    /// The root of a tree can't be detached from it
+    DetachedRoot,
+    /// The node is not part of this structure
+    ForeignNode,
+    /// The vertex is not smaller than the number of vertices
+    VertexOutOfBounds { vertex: usize, len: usize },
+    /// The vertex was removed from the graph, or is hidden by the view
+    RemovedVertex(usize),
+    /// A vertex already exists at the index
+    OccupiedVertex(usize),
+    /// The vertices are already in the same tree, or already have an edge between them
+    AlreadyConnected,
+    /// There is no edge between the vertices
+    MissingEdge,
+    /// The source and the sink of a flow are the same vertex
+    SourceIsSink,
+    /// The keys given as sorted are not strictly increasing
+    UnsortedKeys,
+    /// The same key is given more than once
+    DuplicateKeys,
+    /// The event is scheduled before the last event popped
+    EventInPast { at: u64, now: u64 },
+    /// The expression has no token
+    EmptyExpression,
+    /// An operator doesn't have its two operands
+    MissingOperand,
+    /// Operands are left over once every operator has been applied
+    MissingOperator,
+    /// A token is neither an operator nor a number
+    InvalidToken,
+    /// The right operand of a division is zero
+    DivisionByZero,
+    /// The input ended in the middle of a value
+    UnexpectedEnd,
+    /// The bytes don't match their checksum
+    ChecksumMismatch,
+    /// The input doesn't start with the snapshot header
+    NotASnapshot,
+    /// The snapshot was written by a newer version of the format
+    UnsupportedVersion(u64),
+    /// Bytes are left over after the value
+    TrailingBytes,
+    /// The bytes don't encode a valid value, with the reason
+    InvalidEncoding(&'static str),
+    /// A line of a text format can't be parsed, with its number counted from 1 and the reason
+    InvalidLine { line: usize, reason: &'static str },
+    /// The other side of the channel was dropped
+    Disconnected,
+}
+
+impl fmt::Display for DataStructureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataStructureError::Full => write!(f, "Queue is full"),
+            DataStructureError::CapacityBelowLen { len, requested } => {
+                write!(
+                    f,
+                    "New max size {} is less than current size {}",
+                    requested, len
+                )
+            }
+            DataStructureError::ElementTooLarge { size, budget } => {
+                write!(
+                    f,
+                    "Element of {} bytes is larger than the byte budget of {}",
+                    size, budget
+                )
+            }
+            DataStructureError::BudgetExceeded => write!(f, "Byte budget exceeded"),
+            DataStructureError::InvalidPosition { position, len } => {
+                write!(
+                    f,
+                    "Overflow policy chose position {} in a queue of {}",
+                    position, len
+                )
+            }
+            DataStructureError::AllocationFailed => write!(f, "Memory allocation failed"),
+            DataStructureError::IndexOutOfBounds { index, len } => {
+                write!(f, "Index {} out of bounds for length {}", index, len)
+            }
+            DataStructureError::RangeOutOfBounds { start, end, len } => {
+                write!(
+                    f,
+                    "Range {}..{} out of bounds for length {}",
+                    start, end, len
+                )
+            }
+            DataStructureError::EmptyRange => write!(f, "Range is empty"),
+            DataStructureError::RankOutOfRange { rank, len } => {
+                write!(f, "Rank {} out of range for {} elements", rank, len)
+            }
+            DataStructureError::UnknownVersion(version) => {
+                write!(f, "Version {} doesn't exist", version)
+            }
+            DataStructureError::PrunedVersion(version) => {
+                write!(f, "Version {} was pruned", version)
+            }
+            DataStructureError::RootOccupied => write!(f, "Tree already has a root"),
+            DataStructureError::ChildOccupied => write!(f, "Node already has this child"),
+            DataStructureError::DetachedRoot => write!(f, "The root can't be detached"),
+            DataStructureError::ForeignNode => write!(f, "Node doesn't belong to the structure"),
+            DataStructureError::VertexOutOfBounds { vertex, len } => {
+                write!(f, "Vertex {} out of range for {} vertices", vertex, len)
+            }
+            DataStructureError::RemovedVertex(vertex) => write!(f, "Vertex {} was removed", vertex),
+            DataStructureError::OccupiedVertex(vertex) => {
+                write!(f, "Vertex {} already exists", vertex)
+            }
+            DataStructureError::AlreadyConnected => write!(f, "Vertices are already connected"),
+            DataStructureError::MissingEdge => write!(f, "No edge between the vertices"),
+            DataStructureError::SourceIsSink => write!(f, "Source and sink are the same vertex"),
+            DataStructureError::UnsortedKeys => write!(f, "Keys are not strictly increasing"),
+            DataStructureError::DuplicateKeys => write!(f, "Keys are not distinct"),
+            DataStructureError::EventInPast { at, now } => {
+                write!(
+                    f,
+                    "Event is scheduled at {}, before the current time {}",
+                    at, now
+                )
+            }
+            DataStructureError::EmptyExpression => write!(f, "Empty expression"),
+            DataStructureError::MissingOperand => write!(f, "Missing operand"),
+            DataStructureError::MissingOperator => write!(f, "Missing operator"),
+            DataStructureError::InvalidToken => write!(f, "Invalid token"),
+            DataStructureError::DivisionByZero => write!(f, "Division by zero"),
+            DataStructureError::UnexpectedEnd => write!(f, "Unexpected end of input"),
+            DataStructureError::ChecksumMismatch => write!(f, "Checksum mismatch"),
+            DataStructureError::NotASnapshot => write!(f, "Not a snapshot"),
+            DataStructureError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported format version {}", version)
+            }
+            DataStructureError::TrailingBytes => write!(f, "Trailing bytes after the value"),
+            DataStructureError::InvalidEncoding(reason) => {
+                write!(f, "Invalid encoding: {}", reason)
+            }
+            DataStructureError::InvalidLine { line, reason } => {
+                write!(f, "Invalid line {}: {}", line, reason)
+            }
+            DataStructureError::Disconnected => write!(f, "Channel is disconnected"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DataStructureError {}
+
+#[cfg(feature = "fallible-alloc")]
+impl From<crate::storage::node_storage::AllocError> for DataStructureError {
+    fn from(_: crate::storage::node_storage::AllocError) -> Self {
+        DataStructureError::AllocationFailed
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::error::Error;
+    use std::io;
+
+    #[test]
+    fn test_error_conversions() {
+        let error = DataStructureError::CapacityBelowLen {
+            len: 3,
+            requested: 2,
+        };
+        assert_eq!(
+            error.to_string(),
+            "New max size 2 is less than current size 3"
+        );
+
+        // Boxed and wrapped in an io::Error, the message is kept
+        let boxed: Box<dyn Error + Send + Sync> = error.into();
+        let wrapped = io::Error::new(io::ErrorKind::InvalidData, boxed);
+        assert_eq!(wrapped.to_string(), error.to_string());
+    }
+}
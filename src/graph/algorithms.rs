@@ -0,0 +1,717 @@
+//! This module implements algorithms over a whole `Graph`, or over any `GraphView` of one such as a `FilteredView`,
+//! on top of the read-only view of vertices and weighted edges.
+//! `kruskal` and `prim` find a minimum spanning forest, the lightest set of edges keeping every connected part of the
+//! graph connected, as network design needs. `astar` finds a shortest path guided by a `Heuristic` computed from the
+//! values of the vertices, such as the coordinates of the cells of a map. `max_flow` pushes as much flow as the
+//! capacities of the edges allow from a source to a sink, and finds a minimum cut separating them, the base of
+//! matching and assignment problems.
+//!
+//! # Performance
+//! - O(E log E) for `kruskal`, dominated by sorting the edges
+//! - O(E log E) for `prim`, each stored edge entering the heap once from each of its vertices
+//! - O((V + E) log V) for `astar` in the worst case, and much less with a heuristic close to the real weights
+//! - O(V² E) for `max_flow`, and O(E √V) on unit capacity graphs such as bipartite matchings
+//!
+//! # Implementation Details
+//! - Spanning trees ignore the direction of the edges: a directed edge connects its two vertices like an undirected
+//!   one, and an undirected edge, stored both ways, is taken at most once.
+//! - On a graph that isn't connected, both algorithms return a spanning forest, one tree per connected part.
+//! - `kruskal` sorts the edges by weight and keeps the ones that join two trees of a `DisjointSet`.
+//! - `prim` grows each tree from its lowest vertex, taking the lightest edge leaving the tree from a `BinaryHeap`.
+//! - Ties are broken by the vertices of the edges, so both results are reproducible. Edges are reported in the
+//!   direction they are stored, `from` being the lower vertex for an undirected edge.
+//! - `astar` runs the search behind `Graph::shortest_path_astar`, estimating the weight left from each vertex by giving
+//!   its value and the value of the goal to the heuristic. Closures taking both values are heuristics, and `Dijkstra`
+//!   estimates zero.
+//! - `max_flow` is Dinic's algorithm on a residual copy of the edges, each paired with a reverse edge of no capacity.
+//!   Each phase levels the vertices by a breadth first search from the source, then saturates the paths that go one
+//!   level down at each step with an iterative depth first search. The vertices still reachable from the source once
+//!   the sink isn't are the source side of a minimum cut.
+//!
+//! # Usage
+//! ```
+//! use data_structures::graph::algorithms::{kruskal, prim};
+//! use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+//!
+//! let mut network = Graph::weighted();
+//! for office in ["a", "b", "c", "d"] {
+//!     network.add_vertex(office);
+//! }
+//! for (from, to, cost) in [(0, 1, 7), (0, 2, 5), (1, 2, 8), (1, 3, 9), (2, 3, 15)] {
+//!     network.add_weighted_edge(from, to, cost, EdgeKind::Undirected).unwrap();
+//! }
+//!
+//! let (cost, cables) = kruskal(&network);
+//! assert_eq!(cost, 21);
+//! assert_eq!(cables, vec![(0, 2, 5), (0, 1, 7), (1, 3, 9)]);
+//! assert_eq!(prim(&network).0, 21);
+//! ```
+//!
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::VecDeque;
+use std::ops::{Add, Sub};
+
+use crate::error::DataStructureError;
+use crate::graph::union_find::DisjointSet;
+use crate::graph::vertex_graph::NodeId;
+use crate::graph::view::{check_vertex, GraphView};
+
+/// Trait for the estimates guiding `astar`, computed from the values of the vertices
+/// An estimate must be admissible, never more than the weight of the lightest path to the goal, and consistent, never
+/// decreasing by more than the weight of an edge along a path, like a straight line distance on a map. Otherwise
+/// `astar` may return a longer path.
+pub trait Heuristic<T, W> {
+    /// Estimate the weight of the lightest path from a vertex to the goal
+    /// # Arguments
+    /// * `from`: The value of the vertex
+    /// * `goal`: The value of the goal vertex
+    /// # Returns
+    /// The estimated weight, zero when `from` is the goal
+    fn estimate(&self, from: &T, goal: &T) -> W;
+}
+
+impl<T, W, F: Fn(&T, &T) -> W> Heuristic<T, W> for F {
+    fn estimate(&self, from: &T, goal: &T) -> W {
+        self(from, goal)
+    }
+}
+
+/// The heuristic estimating every weight at zero, with which `astar` behaves like Dijkstra's algorithm
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dijkstra;
+
+impl<T, W: Default> Heuristic<T, W> for Dijkstra {
+    fn estimate(&self, _: &T, _: &T) -> W {
+        W::default()
+    }
+}
+
+/// Find a minimum spanning forest with Kruskal's algorithm
+/// # Arguments
+/// * `graph`: The graph, whose edges are taken as undirected
+/// # Returns
+/// The total weight of the forest and its edges as `(from, to, weight)`, in the order they were taken, lightest first
+/// # Example
+/// ```
+/// use data_structures::graph::algorithms::kruskal;
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// let mut graph = Graph::weighted();
+/// for value in 0..4 {
+///     graph.add_vertex(value);
+/// }
+/// graph.add_weighted_edge(0, 1, 3, EdgeKind::Undirected).unwrap();
+/// graph.add_weighted_edge(1, 2, 1, EdgeKind::Directed).unwrap();
+/// graph.add_weighted_edge(2, 0, 2, EdgeKind::Directed).unwrap();
+///
+/// // Vertex 3 is a tree of its own
+/// assert_eq!(kruskal(&graph), (3, vec![(1, 2, 1), (2, 0, 2)]));
+/// ```
+pub fn kruskal<G, W>(graph: &G) -> (W, Vec<(usize, usize, W)>)
+where
+    G: GraphView<Weight = W>,
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    let mut edges: Vec<(usize, usize, W)> = graph
+        .vertices()
+        .flat_map(|from| {
+            graph
+                .weighted_neighbors(from)
+                .into_iter()
+                .map(move |(to, weight)| (from, to, weight))
+        })
+        .collect();
+    edges.sort_unstable_by_key(|&(from, to, weight)| (weight, from, to));
+
+    let mut forest: DisjointSet<usize> = graph.vertices().collect();
+    let mut total = W::default();
+    let mut tree = Vec::new();
+    for (from, to, weight) in edges {
+        if forest.union(&from, &to) == Some(true) {
+            total = total + weight;
+            tree.push((from, to, weight));
+        }
+    }
+    (total, tree)
+}
+
+/// Find a minimum spanning forest with Prim's algorithm
+/// # Arguments
+/// * `graph`: The graph, whose edges are taken as undirected
+/// # Returns
+/// The total weight of the forest and its edges as `(from, to, weight)`, tree by tree in the order they were taken
+/// # Example
+/// ```
+/// use data_structures::graph::algorithms::prim;
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// let mut graph = Graph::weighted();
+/// for value in 0..4 {
+///     graph.add_vertex(value);
+/// }
+/// graph.add_weighted_edge(0, 1, 3, EdgeKind::Undirected).unwrap();
+/// graph.add_weighted_edge(1, 2, 1, EdgeKind::Directed).unwrap();
+/// graph.add_weighted_edge(2, 0, 2, EdgeKind::Directed).unwrap();
+///
+/// // Grown from vertex 0: the edge to 2 is the lightest leaving it, then the one from 1 to 2
+/// assert_eq!(prim(&graph), (3, vec![(2, 0, 2), (1, 2, 1)]));
+/// ```
+pub fn prim<G, W>(graph: &G) -> (W, Vec<(usize, usize, W)>)
+where
+    G: GraphView<Weight = W>,
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    // Every edge is reachable from both of its vertices, with the direction it is stored in
+    let mut incident: Vec<Vec<(usize, usize, W)>> = vec![Vec::new(); graph.vertex_bound()];
+    for from in graph.vertices() {
+        for (to, weight) in graph.weighted_neighbors(from) {
+            incident[from].push((from, to, weight));
+            incident[to].push((from, to, weight));
+        }
+    }
+
+    let mut in_tree = vec![false; graph.vertex_bound()];
+    let mut total = W::default();
+    let mut tree = Vec::new();
+    let mut open = BinaryHeap::new();
+    for root in graph.vertices() {
+        if in_tree[root] {
+            continue;
+        }
+        in_tree[root] = true;
+        open.extend(
+            incident[root]
+                .iter()
+                .map(|&edge| Reverse((edge.2, edge.0, edge.1))),
+        );
+
+        while let Some(Reverse((weight, from, to))) = open.pop() {
+            // The edge leaves the tree by whichever end isn't in it yet
+            let next = if in_tree[from] { to } else { from };
+            if in_tree[next] {
+                continue;
+            }
+            in_tree[next] = true;
+            total = total + weight;
+            tree.push((from, to, weight));
+            open.extend(
+                incident[next]
+                    .iter()
+                    .map(|&edge| Reverse((edge.2, edge.0, edge.1))),
+            );
+        }
+    }
+    (total, tree)
+}
+
+/// Struct representing a maximum flow between two vertices, with a minimum cut separating them
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxFlow<W> {
+    /// The total flow leaving the source, which is also the capacity of the minimum cut
+    pub value: W,
+    /// The flow through every edge as `(from, to, flow)`, in increasing order of the vertices
+    pub flows: Vec<(usize, usize, W)>,
+    /// The vertices on the side of the source of the minimum cut, in increasing order
+    pub source_side: Vec<usize>,
+    /// The edges going from the source side to the other side, saturated by the flow, as `(from, to)`
+    pub cut: Vec<(usize, usize)>,
+}
+
+// Edge of the residual graph, with the index of the edge going back
+struct Residual<W> {
+    to: usize,
+    capacity: W,
+    reverse: usize,
+}
+
+/// Find a maximum flow from a vertex to another, with Dinic's algorithm
+/// The weight of each edge is its capacity, and must not be negative. An undirected edge can carry flow either way.
+/// # Arguments
+/// * `graph`: The graph
+/// * `source`: The index of the vertex the flow leaves from
+/// * `sink`: The index of the vertex the flow goes to
+/// # Returns
+/// The flow and a minimum cut, Err(DataStructureError::VertexOutOfBounds) if a vertex doesn't exist, or
+/// Err(DataStructureError::SourceIsSink) if both vertices are the same
+/// # Example
+/// ```
+/// use data_structures::graph::algorithms::max_flow;
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// // Two workers, three tasks: each worker can do one task, among the ones they know
+/// let mut graph = Graph::weighted();
+/// let source = graph.add_vertex("source");
+/// let workers = [graph.add_vertex("ana"), graph.add_vertex("bo")];
+/// let tasks = [graph.add_vertex("cook"), graph.add_vertex("drive"), graph.add_vertex("paint")];
+/// let sink = graph.add_vertex("sink");
+/// for worker in workers {
+///     graph.add_weighted_edge(source, worker, 1, EdgeKind::Directed).unwrap();
+/// }
+/// for task in tasks {
+///     graph.add_weighted_edge(task, sink, 1, EdgeKind::Directed).unwrap();
+/// }
+/// graph.add_weighted_edge(workers[0], tasks[0], 1, EdgeKind::Directed).unwrap();
+/// graph.add_weighted_edge(workers[0], tasks[1], 1, EdgeKind::Directed).unwrap();
+/// graph.add_weighted_edge(workers[1], tasks[0], 1, EdgeKind::Directed).unwrap();
+///
+/// let flow = max_flow(&graph, source, sink).unwrap();
+/// assert_eq!(flow.value, 2);
+/// let assignments: Vec<(usize, usize)> = flow
+///     .flows
+///     .iter()
+///     .filter(|&&(from, to, flow)| workers.contains(&from) && tasks.contains(&to) && flow == 1)
+///     .map(|&(from, to, _)| (from, to))
+///     .collect();
+/// assert_eq!(assignments, vec![(workers[0], tasks[1]), (workers[1], tasks[0])]);
+/// assert_eq!(flow.cut.len(), 2);
+/// ```
+pub fn max_flow<G, W>(
+    graph: &G,
+    source: usize,
+    sink: usize,
+) -> Result<MaxFlow<W>, DataStructureError>
+where
+    G: GraphView<Weight = W>,
+    W: Copy + Ord + Add<Output = W> + Sub<Output = W> + Default,
+{
+    check_vertex(graph, source)?;
+    check_vertex(graph, sink)?;
+    if source == sink {
+        return Err(DataStructureError::SourceIsSink);
+    }
+
+    // Every edge of the graph gets a residual edge, followed by its reverse
+    let zero = W::default();
+    let mut edges = Vec::new();
+    let mut residuals: Vec<Residual<W>> = Vec::new();
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); graph.vertex_bound()];
+    for from in graph.vertices() {
+        for (to, capacity) in graph.weighted_neighbors(from) {
+            let index = residuals.len();
+            edges.push((from, to, capacity, index));
+            residuals.push(Residual {
+                to,
+                capacity,
+                reverse: index + 1,
+            });
+            residuals.push(Residual {
+                to: from,
+                capacity: zero,
+                reverse: index,
+            });
+            outgoing[from].push(index);
+            outgoing[to].push(index + 1);
+        }
+    }
+
+    let mut value = zero;
+    let mut levels: Vec<Option<usize>> = vec![None; graph.vertex_bound()];
+    loop {
+        // Level the vertices by their distance from the source over the edges with capacity left
+        levels.iter_mut().for_each(|level| *level = None);
+        levels[source] = Some(0);
+        let mut queue = VecDeque::from([source]);
+        while let Some(vertex) = queue.pop_front() {
+            for &index in &outgoing[vertex] {
+                let edge = &residuals[index];
+                if edge.capacity > zero && levels[edge.to].is_none() {
+                    levels[edge.to] = levels[vertex].map(|level| level + 1);
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+        if levels[sink].is_none() {
+            break;
+        }
+
+        // Saturate the paths going one level down at each step. Edges leading to dead ends are skipped for the rest
+        // of the phase by moving past them in `next`.
+        let mut next = vec![0; graph.vertex_bound()];
+        let mut path: Vec<usize> = Vec::new();
+        let mut vertex = source;
+        loop {
+            if vertex == sink {
+                let pushed = path
+                    .iter()
+                    .map(|&index| residuals[index].capacity)
+                    .min()
+                    .unwrap();
+                for &index in &path {
+                    residuals[index].capacity = residuals[index].capacity - pushed;
+                    let reverse = residuals[index].reverse;
+                    residuals[reverse].capacity = residuals[reverse].capacity + pushed;
+                }
+                value = value + pushed;
+                path.clear();
+                vertex = source;
+                continue;
+            }
+
+            let step = outgoing[vertex][next[vertex]..].iter().position(|&index| {
+                let edge = &residuals[index];
+                edge.capacity > zero && levels[edge.to] == levels[vertex].map(|level| level + 1)
+            });
+            match step {
+                Some(offset) => {
+                    next[vertex] += offset;
+                    let index = outgoing[vertex][next[vertex]];
+                    path.push(index);
+                    vertex = residuals[index].to;
+                }
+                None => {
+                    // A dead end: go back and skip the edge that led here
+                    next[vertex] = outgoing[vertex].len();
+                    let Some(index) = path.pop() else {
+                        break;
+                    };
+                    vertex = residuals[residuals[index].reverse].to;
+                    next[vertex] += 1;
+                }
+            }
+        }
+    }
+
+    let source_side: Vec<usize> = graph
+        .vertices()
+        .filter(|&vertex| levels[vertex].is_some())
+        .collect();
+    let cut = edges
+        .iter()
+        .filter(|&&(from, to, _, _)| levels[from].is_some() && levels[to].is_none())
+        .map(|&(from, to, _, _)| (from, to))
+        .collect();
+    let flows = edges
+        .into_iter()
+        .map(|(from, to, capacity, index)| (from, to, capacity - residuals[index].capacity))
+        .collect();
+    Ok(MaxFlow {
+        value,
+        flows,
+        source_side,
+        cut,
+    })
+}
+
+/// Find a path of minimal total weight between two vertices, with A* guided by a heuristic
+/// The weights must not be negative.
+/// # Arguments
+/// * `graph`: The graph
+/// * `start`: The index of the start vertex
+/// * `goal`: The index of the goal vertex
+/// * `heuristic`: The estimate of the weight left to the goal, from the values of a vertex and of the goal
+/// # Returns
+/// The total weight and the vertices of a shortest path, both ends included, None if the goal can't be reached, or
+/// Err(DataStructureError::VertexOutOfBounds) if a vertex doesn't exist
+/// # Example
+/// ```
+/// use data_structures::graph::algorithms::{astar, Dijkstra};
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// // A 3x3 grid whose center is a wall, each cell holding its coordinates
+/// let mut grid = Graph::weighted();
+/// for y in 0..3i32 {
+///     for x in 0..3i32 {
+///         grid.add_vertex((x, y));
+///     }
+/// }
+/// for cell in (0..9).filter(|&cell| cell != 4) {
+///     if cell % 3 < 2 && cell + 1 != 4 {
+///         grid.add_weighted_edge(cell, cell + 1, 1, EdgeKind::Undirected).unwrap();
+///     }
+///     if cell < 6 && cell + 3 != 4 {
+///         grid.add_weighted_edge(cell, cell + 3, 1, EdgeKind::Undirected).unwrap();
+///     }
+/// }
+///
+/// let manhattan = |from: &(i32, i32), goal: &(i32, i32)| (from.0.abs_diff(goal.0) + from.1.abs_diff(goal.1));
+/// let (weight, path) = astar(&grid, 1, 7, manhattan).unwrap().unwrap();
+/// assert_eq!(weight, 4);
+/// assert_eq!(path, vec![1, 0, 3, 6, 7]);
+///
+/// assert_eq!(astar(&grid, 1, 7, Dijkstra).unwrap().unwrap().0, 4);
+/// assert_eq!(astar(&grid, 1, 4, Dijkstra).unwrap(), None);
+/// ```
+pub fn astar<G, W, H>(
+    graph: &G,
+    start: usize,
+    goal: usize,
+    heuristic: H,
+) -> Result<Option<(W, Vec<usize>)>, DataStructureError>
+where
+    G: GraphView<Weight = W>,
+    W: Copy + Ord + Add<Output = W> + Default,
+    H: Heuristic<G::Value, W>,
+{
+    // The vertices are checked before the heuristic is first called, and only vertices of the view are estimated
+    best_first(graph, start, goal, |vertex| {
+        graph
+            .with_value(goal, |goal| {
+                graph.with_value(vertex, |from| heuristic.estimate(from, goal))
+            })
+            .flatten()
+            .unwrap()
+    })
+}
+
+// A* search from a vertex to another, given the estimate of the weight left from each vertex
+pub(crate) fn best_first<G, W>(
+    graph: &G,
+    from: NodeId,
+    to: NodeId,
+    mut heuristic: impl FnMut(NodeId) -> W,
+) -> Result<Option<(W, Vec<NodeId>)>, DataStructureError>
+where
+    G: GraphView<Weight = W>,
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    check_vertex(graph, from)?;
+    check_vertex(graph, to)?;
+
+    let mut best: Vec<Option<W>> = vec![None; graph.vertex_bound()];
+    let mut previous = vec![None; graph.vertex_bound()];
+    let mut open = BinaryHeap::new();
+    best[from] = Some(W::default());
+    open.push(Reverse((heuristic(from), W::default(), from)));
+
+    while let Some(Reverse((_, weight, vertex))) = open.pop() {
+        if best[vertex].is_some_and(|best| weight > best) {
+            continue; // A shorter way to this vertex was found after this entry was pushed
+        }
+        if vertex == to {
+            let mut path = vec![to];
+            while let Some(before) = previous[*path.last().unwrap()] {
+                path.push(before);
+            }
+            path.reverse();
+            return Ok(Some((weight, path)));
+        }
+
+        for (neighbor, edge) in graph.weighted_neighbors(vertex) {
+            let through = weight + edge;
+            if best[neighbor].is_none_or(|best| through < best) {
+                best[neighbor] = Some(through);
+                previous[neighbor] = Some(vertex);
+                open.push(Reverse((through + heuristic(neighbor), through, neighbor)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::vertex_graph::{EdgeKind, Graph};
+    use crate::test_util::Lcg;
+
+    type Edge = (usize, usize, u64);
+
+    // Random graph with edges of both kinds, and the list of its edges taken as undirected
+    fn random_graph(rng: &mut Lcg, size: usize, edges: usize) -> (Graph<usize, u64>, Vec<Edge>) {
+        let mut graph = Graph::weighted();
+        for value in 0..size {
+            graph.add_vertex(value);
+        }
+        let mut list = Vec::new();
+        for _ in 0..edges {
+            let seed = rng.next_u64();
+            let from = (seed >> 33) as usize % size;
+            let to = (seed >> 45) as usize % size;
+            let weight = (seed >> 20) % 10;
+            let kind = if (seed >> 60).is_multiple_of(2) {
+                EdgeKind::Undirected
+            } else {
+                EdgeKind::Directed
+            };
+            if graph.add_weighted_edge(from, to, weight, kind).is_ok() {
+                list.push((from, to, weight));
+            }
+        }
+        (graph, list)
+    }
+
+    // Connected parts of the vertices joined by some edges
+    fn components(size: usize, edges: &[Edge]) -> Vec<Vec<usize>> {
+        let mut sets: DisjointSet<usize> = (0..size).collect();
+        for &(from, to, _) in edges {
+            sets.union(&from, &to);
+        }
+        let mut groups = sets.groups();
+        for group in &mut groups {
+            group.sort_unstable();
+        }
+        groups.sort();
+        groups
+    }
+
+    #[test]
+    fn test_spanning_forests_against_brute_force() {
+        let mut rng = Lcg::new(41);
+        for _ in 0..60 {
+            let (graph, edges) = random_graph(&mut rng, 6, 9);
+            let expected_components = components(graph.len(), &edges);
+
+            // The lightest subset of the edges that keeps the same connected parts
+            let lightest = (0..1u32 << edges.len())
+                .filter_map(|mask| {
+                    let subset: Vec<_> = (0..edges.len())
+                        .filter(|&bit| mask & (1 << bit) != 0)
+                        .map(|bit| edges[bit])
+                        .collect();
+                    (components(graph.len(), &subset) == expected_components)
+                        .then(|| subset.iter().map(|edge| edge.2).sum::<u64>())
+                })
+                .min()
+                .unwrap();
+
+            for (total, forest) in [kruskal(&graph), prim(&graph)] {
+                assert_eq!(total, lightest);
+                assert_eq!(forest.iter().map(|edge| edge.2).sum::<u64>(), total);
+                assert_eq!(forest.len(), graph.len() - expected_components.len());
+                assert_eq!(components(graph.len(), &forest), expected_components);
+                assert!(forest
+                    .iter()
+                    .all(|&(from, to, weight)| graph.edge_weight(from, to) == Some(weight)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_graph_algorithms_agree() {
+        let mut rng = Lcg::new(43);
+        let (graph, edges) = random_graph(&mut rng, 300, 1500);
+        let (kruskal_total, kruskal_forest) = kruskal(&graph);
+        let (prim_total, prim_forest) = prim(&graph);
+
+        assert_eq!(kruskal_total, prim_total);
+        assert_eq!(kruskal_forest.len(), prim_forest.len());
+        assert_eq!(
+            components(graph.len(), &prim_forest),
+            components(graph.len(), &edges)
+        );
+        assert_eq!(kruskal(&Graph::<u8, u32>::weighted()), (0, Vec::new()));
+    }
+
+    #[test]
+    fn test_astar_on_grid_with_walls() {
+        let width = 12;
+        let mut grid: Graph<(u32, u32), u32> = Graph::weighted();
+        let mut rng = Lcg::new(47);
+        let walls: Vec<bool> = (0..width * width)
+            .map(|_| (rng.next_u64() >> 60) < 4)
+            .collect();
+        for cell in 0..width * width {
+            grid.add_vertex(((cell % width) as u32, (cell / width) as u32));
+        }
+        for cell in (0..width * width).filter(|&cell| !walls[cell]) {
+            if cell % width < width - 1 && !walls[cell + 1] {
+                let weight = 1 + (rng.next_u64() >> 62) as u32;
+                grid.add_weighted_edge(cell, cell + 1, weight, EdgeKind::Undirected)
+                    .unwrap();
+            }
+            if cell + width < width * width && !walls[cell + width] {
+                let weight = 1 + (rng.next_u64() >> 62) as u32;
+                grid.add_weighted_edge(cell, cell + width, weight, EdgeKind::Undirected)
+                    .unwrap();
+            }
+        }
+
+        // Every edge weighs at least 1, so the Manhattan distance never overestimates
+        let manhattan = |from: &(u32, u32), goal: &(u32, u32)| {
+            from.0.abs_diff(goal.0) + from.1.abs_diff(goal.1)
+        };
+        for _ in 0..100 {
+            let seed = rng.next_u64();
+            let start = (seed >> 20) as usize % (width * width);
+            let goal = (seed >> 40) as usize % (width * width);
+
+            let expected = grid.shortest_path(start, goal).unwrap();
+            let found = astar(&grid, start, goal, manhattan).unwrap();
+            assert_eq!(
+                found.as_ref().map(|(weight, _)| *weight),
+                expected.as_ref().map(|(weight, _)| *weight)
+            );
+            if let Some((weight, path)) = found {
+                assert_eq!((path[0], *path.last().unwrap()), (start, goal));
+                let total: u32 = path
+                    .windows(2)
+                    .map(|step| grid.edge_weight(step[0], step[1]).unwrap())
+                    .sum();
+                assert_eq!(total, weight);
+            }
+            assert_eq!(astar(&grid, start, goal, Dijkstra).unwrap(), expected);
+        }
+
+        let len = width * width;
+        assert_eq!(
+            astar(&grid, 0, len, Dijkstra),
+            Err(DataStructureError::VertexOutOfBounds { vertex: len, len })
+        );
+    }
+
+    #[test]
+    fn test_max_flow_against_brute_force_cuts() {
+        let mut rng = Lcg::new(53);
+        for round in 0..80 {
+            let size = if round < 60 { 7 } else { 40 };
+            let (graph, _) = random_graph(&mut rng, size, size * 3);
+            let (source, sink) = (0, size - 1);
+            let flow = max_flow(&graph, source, sink).unwrap();
+
+            // Capacities and conservation hold, and the value leaves the source
+            let mut balance = vec![0i64; size];
+            for &(from, to, amount) in &flow.flows {
+                assert!(amount <= graph.edge_weight(from, to).unwrap());
+                balance[from] -= amount as i64;
+                balance[to] += amount as i64;
+            }
+            for (vertex, &net) in balance.iter().enumerate() {
+                if vertex != source && vertex != sink {
+                    assert_eq!(net, 0);
+                }
+            }
+            assert_eq!(balance[sink], flow.value as i64);
+
+            // The cut is saturated and as heavy as the flow
+            assert!(flow.source_side.contains(&source));
+            assert!(!flow.source_side.contains(&sink));
+            let capacity: u64 = flow
+                .cut
+                .iter()
+                .map(|&(from, to)| graph.edge_weight(from, to).unwrap())
+                .sum();
+            assert_eq!(capacity, flow.value);
+
+            // On small graphs, no cut is lighter
+            if size <= 7 {
+                let lightest = (0..1u32 << size)
+                    .filter(|side| side & 1 != 0 && side & (1 << sink) == 0)
+                    .map(|side| {
+                        flow.flows
+                            .iter()
+                            .filter(|&&(from, to, _)| {
+                                side & (1 << from) != 0 && side & (1 << to) == 0
+                            })
+                            .map(|&(from, to, _)| graph.edge_weight(from, to).unwrap())
+                            .sum::<u64>()
+                    })
+                    .min()
+                    .unwrap();
+                assert_eq!(flow.value, lightest);
+            }
+        }
+
+        let (graph, _) = random_graph(&mut rng, 3, 3);
+        assert_eq!(
+            max_flow(&graph, 1, 1),
+            Err(DataStructureError::SourceIsSink)
+        );
+        assert_eq!(
+            max_flow(&graph, 0, 3),
+            Err(DataStructureError::VertexOutOfBounds { vertex: 3, len: 3 })
+        );
+    }
+}
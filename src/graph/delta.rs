@@ -0,0 +1,571 @@
+//! This module compares two versions of a graph and carries the changes from one to the other. `diff` lists the
+//! vertices and edges added, removed or changed between two graphs or views as a `GraphDelta`, and `apply_delta`
+//! replays a delta on a graph, so copies of a topology such as a service mesh are kept in sync by shipping only their
+//! differences instead of hand-written set algebra.
+//!
+//! # Performance
+//! - O(V + E log E) for `diff`, counting the vertices and edges of both versions
+//! - O(V + D) for `apply_delta`, D being the number of changes, plus O(V) for each vertex removed
+//!
+//! # Implementation Details
+//! - Vertices are matched by index, which stays valid across removals, and edges by their ends, as the serial of an
+//!   `EdgeId` only means something in the graph that gave it. A vertex whose value changed is relabeled, and an edge
+//!   whose weight changed is reweighted and keeps its id, rather than removed and added again.
+//! - A delta lists every edge missing from the second version, the edges of removed vertices included, so it
+//!   describes the change on its own.
+//! - `apply_delta` checks the whole delta against the graph before changing it, so a delta made from another version
+//!   is rejected and leaves the graph as it was.
+//! - Changes are applied as edges removed, vertices removed, relabeled and added, then edges added and reweighted.
+//! - The delta records the `vertex_bound` of the second version, and the graph is raised to it. Added vertices keep
+//!   their index, which must be at or past the bound of the graph and below the one of the delta, so an index that was
+//!   removed is never given to a vertex again and both versions give the same ids to the vertices they add next.
+//!
+//! # Usage
+//! ```
+//! use data_structures::graph::delta::{apply_delta, diff};
+//! use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+//!
+//! let mut mesh = Graph::weighted();
+//! let gateway = mesh.add_vertex("gateway");
+//! let users = mesh.add_vertex("users");
+//! let billing = mesh.add_vertex("billing");
+//! mesh.add_weighted_edge(gateway, users, 10, EdgeKind::Directed).unwrap();
+//! mesh.add_weighted_edge(gateway, billing, 10, EdgeKind::Directed).unwrap();
+//!
+//! // The next version of the mesh, edited on a copy
+//! let mut next = mesh.subgraph(|_, _| true, |_, _| true);
+//! next.remove_vertex(billing).unwrap();
+//! let payments = next.add_vertex("payments");
+//! next.add_weighted_edge(gateway, payments, 20, EdgeKind::Directed).unwrap();
+//! next.set_value(users, "accounts").unwrap();
+//!
+//! let delta = diff(&mesh, &next);
+//! assert_eq!(delta.removed_nodes, vec![billing]);
+//! assert_eq!(delta.added_nodes, vec![(payments, "payments")]);
+//! assert_eq!(delta.relabeled_nodes, vec![(users, "accounts")]);
+//! assert_eq!(delta.removed_edges, vec![(gateway, billing)]);
+//!
+//! apply_delta(&mut mesh, delta).unwrap();
+//! assert!(diff(&mesh, &next).is_empty());
+//! ```
+//!
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use crate::error::DataStructureError;
+use crate::graph::vertex_graph::{EdgeKind, Graph, NodeId};
+use crate::graph::view::{check_vertex, GraphView};
+
+/// Struct holding the changes turning a version of a graph into another
+/// The vertices are listed in increasing order, and the edges, given by their source and target, ordered by source
+/// and then by target.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphDelta<T, W> {
+    /// Vertices only in the second version, with their values
+    pub added_nodes: Vec<(NodeId, T)>,
+    /// Vertices only in the first version
+    pub removed_nodes: Vec<NodeId>,
+    /// Vertices in both versions whose value changed, with the new value
+    pub relabeled_nodes: Vec<(NodeId, T)>,
+    /// Edges only in the second version, with their weights
+    pub added_edges: Vec<(NodeId, NodeId, W)>,
+    /// Edges only in the first version, the edges of removed vertices included
+    pub removed_edges: Vec<(NodeId, NodeId)>,
+    /// Edges in both versions whose weight changed, with the new weight
+    pub reweighted_edges: Vec<(NodeId, NodeId, W)>,
+    /// The `vertex_bound` of the second version, above the index of every vertex added
+    pub vertex_bound: usize,
+}
+
+impl<T, W> GraphDelta<T, W> {
+    /// Check if the delta changes no vertex and no edge
+    /// # Returns
+    /// True if both versions compared are the same, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::graph::delta::diff;
+    /// use data_structures::graph::vertex_graph::Graph;
+    ///
+    /// let mut graph = Graph::new();
+    /// graph.add_vertex(1);
+    ///
+    /// assert!(diff(&graph, &graph).is_empty());
+    /// assert!(!diff(&graph, &Graph::new()).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.relabeled_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.reweighted_edges.is_empty()
+    }
+}
+
+/// Compare two versions of a graph, matching their vertices by index
+/// # Arguments
+/// * `before`: The first version, a graph or a view of one
+/// * `after`: The second version, a graph or a view of one
+/// # Returns
+/// The changes turning `before` into `after`
+/// # Example
+/// ```
+/// use data_structures::graph::delta::diff;
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// let mut before = Graph::weighted();
+/// let a = before.add_vertex('a');
+/// let b = before.add_vertex('b');
+/// before.add_weighted_edge(a, b, 1, EdgeKind::Undirected).unwrap();
+///
+/// let mut after = before.subgraph(|_, _| true, |_, _| true);
+/// after.retain_edges(|edge, _| edge.from == a);
+/// after.remove_edge(a, b, EdgeKind::Directed).unwrap();
+/// after.add_weighted_edge(a, b, 2, EdgeKind::Directed).unwrap();
+///
+/// let delta = diff(&before, &after);
+/// assert_eq!(delta.removed_edges, vec![(b, a)]);
+/// assert_eq!(delta.reweighted_edges, vec![(a, b, 2)]);
+/// assert!(delta.added_nodes.is_empty() && delta.relabeled_nodes.is_empty());
+/// ```
+pub fn diff<A, B, T, W>(before: &A, after: &B) -> GraphDelta<T, W>
+where
+    A: GraphView<Value = T, Weight = W>,
+    B: GraphView<Value = T, Weight = W>,
+    T: Clone + PartialEq,
+    W: Clone + PartialEq,
+{
+    let mut delta = GraphDelta {
+        added_nodes: Vec::new(),
+        removed_nodes: Vec::new(),
+        relabeled_nodes: Vec::new(),
+        added_edges: Vec::new(),
+        removed_edges: Vec::new(),
+        reweighted_edges: Vec::new(),
+        vertex_bound: after.vertex_bound(),
+    };
+
+    for vertex in 0..before.vertex_bound().max(after.vertex_bound()) {
+        match (
+            before.contains_vertex(vertex),
+            after.contains_vertex(vertex),
+        ) {
+            (false, false) => continue,
+            (true, false) => delta.removed_nodes.push(vertex),
+            (false, true) => {
+                let value = after.with_value(vertex, T::clone).unwrap();
+                delta.added_nodes.push((vertex, value));
+            }
+            (true, true) => {
+                // Only values that changed are cloned
+                let changed = after.with_value(vertex, |new| {
+                    let changed = before.with_value(vertex, |old| old != new).unwrap();
+                    changed.then(|| new.clone())
+                });
+                if let Some(value) = changed.flatten() {
+                    delta.relabeled_nodes.push((vertex, value));
+                }
+            }
+        }
+
+        // Both lists are sorted by neighbor, so they are merged
+        let mut old = before.weighted_neighbors(vertex).into_iter().peekable();
+        let mut new = after.weighted_neighbors(vertex).into_iter().peekable();
+        loop {
+            let order = match (old.peek(), new.peek()) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((old_to, _)), Some((new_to, _))) => old_to.cmp(new_to),
+            };
+            let from = vertex;
+            match order {
+                Ordering::Less => {
+                    let (to, _) = old.next().unwrap();
+                    delta.removed_edges.push((from, to));
+                }
+                Ordering::Greater => {
+                    let (to, weight) = new.next().unwrap();
+                    delta.added_edges.push((from, to, weight));
+                }
+                Ordering::Equal => {
+                    let (to, old_weight) = old.next().unwrap();
+                    let (_, new_weight) = new.next().unwrap();
+                    if old_weight != new_weight {
+                        delta.reweighted_edges.push((from, to, new_weight));
+                    }
+                }
+            }
+        }
+    }
+    delta
+}
+
+/// Apply the changes of a delta to a graph
+/// The delta must have been made from a version of the graph with the same vertices and edges, such as the graph
+/// itself. It is checked before anything changes, so a failed call leaves the graph as it was.
+/// # Arguments
+/// * `graph`: The graph to change
+/// * `delta`: The changes, usually returned by `diff` with the graph as the first version
+/// # Returns
+/// Ok if the delta was applied, or the error of the first change that doesn't fit the graph:
+/// Err(DataStructureError::VertexOutOfBounds) or Err(DataStructureError::RemovedVertex) for a missing vertex,
+/// Err(DataStructureError::OccupiedVertex) for a vertex added where one exists, Err(DataStructureError::RemovedVertex)
+/// for a vertex added at a removed index, Err(DataStructureError::VertexOutOfBounds) for one added at or past the
+/// `vertex_bound` of the delta, Err(DataStructureError::MissingEdge) for a missing edge,
+/// Err(DataStructureError::AlreadyConnected) for an edge added where one exists, or
+/// Err(DataStructureError::AllocationFailed) if the slots up to the bound can't be allocated
+/// # Example
+/// ```
+/// use data_structures::error::DataStructureError;
+/// use data_structures::graph::delta::{apply_delta, diff};
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// let mut graph = Graph::new();
+/// let a = graph.add_vertex("a");
+/// let b = graph.add_vertex("b");
+///
+/// let mut target = graph.subgraph(|_, _| true, |_, _| true);
+/// target.add_edge(a, b, EdgeKind::Undirected).unwrap();
+/// let delta = diff(&graph, &target);
+///
+/// assert_eq!(apply_delta(&mut graph, delta.clone()), Ok(()));
+/// assert!(graph.has_edge(b, a));
+/// assert_eq!(apply_delta(&mut graph, delta), Err(DataStructureError::AlreadyConnected));
+/// ```
+pub fn apply_delta<T, W: Clone>(
+    graph: &mut Graph<T, W>,
+    delta: GraphDelta<T, W>,
+) -> Result<(), DataStructureError> {
+    check_delta(graph, &delta)?;
+    graph.raise_vertex_bound(delta.vertex_bound)?;
+
+    for (from, to) in delta.removed_edges {
+        graph.remove_edge(from, to, EdgeKind::Directed).unwrap();
+    }
+    for vertex in delta.removed_nodes {
+        graph.remove_vertex(vertex).unwrap();
+    }
+    for (vertex, value) in delta.relabeled_nodes {
+        graph.set_value(vertex, value).unwrap();
+    }
+    for (vertex, value) in delta.added_nodes {
+        graph.insert_vertex_at(vertex, value);
+    }
+    for (from, to, weight) in delta.added_edges {
+        graph
+            .add_weighted_edge(from, to, weight, EdgeKind::Directed)
+            .unwrap();
+    }
+    for (from, to, weight) in delta.reweighted_edges {
+        graph.set_edge_weight(from, to, weight);
+    }
+    Ok(())
+}
+
+// Check that every change of a delta can be applied to the graph, in the order `apply_delta` applies them
+fn check_delta<T, W: Clone>(
+    graph: &Graph<T, W>,
+    delta: &GraphDelta<T, W>,
+) -> Result<(), DataStructureError> {
+    let check_edge = |&(from, to): &(NodeId, NodeId)| {
+        check_vertex(graph, from)?;
+        check_vertex(graph, to)?;
+        match graph.has_edge(from, to) {
+            true => Ok(()),
+            false => Err(DataStructureError::MissingEdge),
+        }
+    };
+
+    let mut removed_edges = HashSet::new();
+    for edge in &delta.removed_edges {
+        check_edge(edge)?;
+        if !removed_edges.insert(*edge) {
+            return Err(DataStructureError::MissingEdge);
+        }
+    }
+
+    let mut removed = HashSet::new();
+    for &vertex in &delta.removed_nodes {
+        check_vertex(graph, vertex)?;
+        if !removed.insert(vertex) {
+            return Err(DataStructureError::RemovedVertex(vertex));
+        }
+    }
+    // Error for a vertex that won't exist once the vertices are removed
+    let missing = |vertex: NodeId| match check_vertex(graph, vertex) {
+        Ok(()) if !removed.contains(&vertex) => None,
+        Ok(()) => Some(DataStructureError::RemovedVertex(vertex)),
+        Err(error) => Some(error),
+    };
+
+    for &(vertex, _) in &delta.relabeled_nodes {
+        if let Some(error) = missing(vertex) {
+            return Err(error);
+        }
+    }
+
+    // Added vertices take indexes that neither version used before
+    let mut added = HashSet::new();
+    for &(vertex, _) in &delta.added_nodes {
+        if graph.contains_vertex(vertex) || !added.insert(vertex) {
+            return Err(DataStructureError::OccupiedVertex(vertex));
+        }
+        if vertex < graph.vertex_bound() {
+            return Err(DataStructureError::RemovedVertex(vertex));
+        }
+        if vertex >= delta.vertex_bound {
+            return Err(DataStructureError::VertexOutOfBounds {
+                vertex,
+                len: delta.vertex_bound,
+            });
+        }
+    }
+
+    let mut added_edges = HashSet::new();
+    for &(from, to, _) in &delta.added_edges {
+        for vertex in [from, to] {
+            if let Some(error) = missing(vertex).filter(|_| !added.contains(&vertex)) {
+                return Err(error);
+            }
+        }
+        let kept = graph.has_edge(from, to) && !removed_edges.contains(&(from, to));
+        if kept || !added_edges.insert((from, to)) {
+            return Err(DataStructureError::AlreadyConnected);
+        }
+    }
+
+    let mut reweighted = HashSet::new();
+    for &(from, to, _) in &delta.reweighted_edges {
+        let edge = (from, to);
+        check_edge(&edge)?;
+        for vertex in [from, to] {
+            if let Some(error) = missing(vertex) {
+                return Err(error);
+            }
+        }
+        if removed_edges.contains(&edge) || added_edges.contains(&edge) || !reweighted.insert(edge)
+        {
+            return Err(DataStructureError::MissingEdge);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::vertex_graph::EdgeId;
+    use crate::graph::view::FilteredView;
+    use crate::test_util::Lcg;
+
+    // Random changes to a graph of values below 5: vertices added, removed and relabeled, edges added and removed
+    fn mutate(graph: &mut Graph<u64, u64>, rng: &mut Lcg, changes: usize) {
+        for _ in 0..changes {
+            let seed = rng.next_u64();
+            let bound = graph.vertex_bound();
+            let from = (seed >> 33) as usize % bound;
+            let to = (seed >> 45) as usize % bound;
+            match (seed >> 10) % 8 {
+                0 => {
+                    graph.add_vertex(seed % 5);
+                }
+                1 => {
+                    let _ = graph.remove_vertex(from);
+                }
+                2 => {
+                    let _ = graph.set_value(from, seed % 5);
+                }
+                3 => {
+                    let _ = graph.remove_edge(from, to, EdgeKind::Directed);
+                }
+                _ => {
+                    let _ = graph.remove_edge(from, to, EdgeKind::Directed);
+                    let _ = graph.add_weighted_edge(from, to, seed % 3, EdgeKind::Directed);
+                }
+            }
+        }
+    }
+
+    // Vertices with their values and edges by their ends with their weights, to compare graphs
+    type Snapshot = (Vec<(NodeId, u64)>, Vec<(NodeId, NodeId, u64)>);
+
+    fn snapshot(graph: &Graph<u64, u64>) -> Snapshot {
+        let vertices = graph
+            .vertices()
+            .map(|vertex| (vertex, graph.value(vertex).unwrap()))
+            .collect();
+        let edges = graph
+            .edges()
+            .map(|edge| {
+                let weight = graph.edge_weight(edge.from, edge.to).unwrap();
+                (edge.from, edge.to, weight)
+            })
+            .collect();
+        (vertices, edges)
+    }
+
+    #[test]
+    fn test_diff_and_apply_against_snapshots() {
+        let mut rng = Lcg::new(37);
+        let mut rejected = 0;
+        let mut restored = 0;
+        for round in 0..30 {
+            let mut before = Graph::weighted();
+            for _ in 0..20 {
+                before.add_vertex(rng.next_u64() % 5);
+            }
+            mutate(&mut before, &mut rng, 120);
+            let original = snapshot(&before);
+            let mut after = before.subgraph(|_, _| true, |_, _| true);
+            mutate(&mut after, &mut rng, round * 4);
+
+            let delta = diff(&before, &after);
+            assert_eq!(delta.is_empty(), snapshot(&before) == snapshot(&after));
+            assert!(diff(&after, &after).is_empty());
+
+            // Every change listed is real, and together they account for both snapshots
+            let (old_vertices, old_edges) = snapshot(&before);
+            let (new_vertices, new_edges) = snapshot(&after);
+            for &(vertex, value) in &delta.relabeled_nodes {
+                assert_ne!(before.value(vertex), Some(value));
+                assert_eq!(after.value(vertex), Some(value));
+            }
+            for &(from, to, weight) in &delta.reweighted_edges {
+                assert_ne!(before.edge_weight(from, to), Some(weight));
+                assert_eq!(after.edge_weight(from, to), Some(weight));
+            }
+            assert_eq!(
+                old_vertices.len() + delta.added_nodes.len() - delta.removed_nodes.len(),
+                new_vertices.len()
+            );
+            assert_eq!(
+                old_edges.len() + delta.added_edges.len() - delta.removed_edges.len(),
+                new_edges.len()
+            );
+
+            // A delta applied to the wrong version is rejected without changing the graph
+            let mut other = after.subgraph(|_, _| true, |_, _| true);
+            if apply_delta(&mut other, delta.clone()).is_err() {
+                assert_eq!(snapshot(&other), snapshot(&after));
+                rejected += 1;
+            }
+
+            let inverse = diff(&after, &before);
+            let reweighted: Vec<EdgeId> = delta
+                .reweighted_edges
+                .iter()
+                .map(|&(from, to, _)| before.edge_id(from, to).unwrap())
+                .collect();
+            apply_delta(&mut before, delta).unwrap();
+            assert_eq!(snapshot(&before), snapshot(&after));
+            assert!(reweighted.iter().all(|&edge| before.contains_edge(edge)));
+            assert_eq!(before.vertex_bound(), after.vertex_bound());
+            assert!(diff(&before, &after).is_empty());
+
+            // New vertices are added after the indexes of both versions
+            let fresh = before.add_vertex(0);
+            assert!(fresh >= after.vertex_bound());
+            assert_eq!(before.remove_vertex(fresh), Ok(0));
+
+            // Going back only works if no removed vertex has to come back at its index
+            let state = snapshot(&before);
+            match inverse.added_nodes.first() {
+                None => {
+                    apply_delta(&mut before, inverse).unwrap();
+                    assert_eq!(snapshot(&before), original);
+                    restored += 1;
+                }
+                Some(&(vertex, _)) => {
+                    assert_eq!(
+                        apply_delta(&mut before, inverse),
+                        Err(DataStructureError::RemovedVertex(vertex))
+                    );
+                    assert_eq!(snapshot(&before), state);
+                }
+            }
+        }
+        assert!(rejected > 20);
+        assert!(restored > 0);
+    }
+
+    #[test]
+    fn test_added_vertices_take_fresh_indexes() {
+        let mut graph = Graph::weighted();
+        for value in 0..4u64 {
+            graph.add_vertex(value);
+        }
+        graph
+            .add_weighted_edge(0, 1, 1, EdgeKind::Directed)
+            .unwrap();
+        graph.remove_vertex(2).unwrap();
+
+        let mut next = graph.subgraph(|_, _| true, |_, _| true);
+        let added = next.add_vertex(9);
+        next.add_weighted_edge(added, 0, 2, EdgeKind::Directed)
+            .unwrap();
+        let gone = next.add_vertex(8);
+        next.remove_vertex(gone).unwrap();
+        let delta = diff(&graph, &next);
+        assert_eq!(delta.vertex_bound, 6);
+
+        // A removed index, an index past the bound of the delta, or one that can't be allocated is rejected
+        let forged = |vertex: NodeId, vertex_bound: usize| {
+            let mut forged = delta.clone();
+            forged.added_nodes[0].0 = vertex;
+            forged.added_edges.clear();
+            forged.vertex_bound = vertex_bound;
+            forged
+        };
+        let before = snapshot(&graph);
+        let mut target = graph.subgraph(|_, _| true, |_, _| true);
+        assert_eq!(
+            apply_delta(&mut target, forged(2, 6)),
+            Err(DataStructureError::RemovedVertex(2))
+        );
+        assert_eq!(
+            apply_delta(&mut target, forged(6, 6)),
+            Err(DataStructureError::VertexOutOfBounds { vertex: 6, len: 6 })
+        );
+        assert_eq!(
+            apply_delta(&mut target, forged(usize::MAX, usize::MAX)),
+            Err(DataStructureError::VertexOutOfBounds {
+                vertex: usize::MAX,
+                len: usize::MAX
+            })
+        );
+        assert_eq!(
+            apply_delta(&mut target, forged(usize::MAX - 1, usize::MAX)),
+            Err(DataStructureError::AllocationFailed)
+        );
+        assert_eq!(snapshot(&target), before);
+        assert_eq!(target.vertex_bound(), 4);
+
+        // The graph takes the bound of the other version, so both add their next vertex at the same index
+        apply_delta(&mut target, delta).unwrap();
+        assert_eq!(snapshot(&target), snapshot(&next));
+        assert_eq!(target.vertex_bound(), 6);
+        assert_eq!(target.add_vertex(7), next.add_vertex(7));
+    }
+
+    #[test]
+    fn test_diff_against_view() {
+        let mut rng = Lcg::new(41);
+        let mut graph = Graph::weighted();
+        for _ in 0..30 {
+            graph.add_vertex(rng.next_u64() % 5);
+        }
+        mutate(&mut graph, &mut rng, 200);
+
+        let view = FilteredView::new(&graph, |_, &value| value > 0, |_, &weight| weight != 1);
+        let delta = diff(&graph, &view);
+        assert!(delta.added_nodes.is_empty() && delta.added_edges.is_empty());
+        assert!(delta.relabeled_nodes.is_empty() && delta.reweighted_edges.is_empty());
+
+        let mut pruned = graph.subgraph(|_, _| true, |_, _| true);
+        apply_delta(&mut pruned, delta).unwrap();
+        let expected = graph.subgraph(|_, &value| value > 0, |_, &weight| weight != 1);
+        assert_eq!(snapshot(&pruned), snapshot(&expected));
+    }
+}
@@ -0,0 +1,499 @@
+//! This module implements dynamic connectivity on a forest with Euler tour trees.
+//! Edges can be linked and cut at any time, as long as the graph stays a forest, and connectivity questions are answered
+//! without walking the graph: each tree of the forest is kept as its Euler tour in a balanced binary tree, so two
+//! vertices are connected when they are in the same balanced tree.
+//!
+//! # Performance
+//! - O(log n) expected for `link`, `cut`, `connected` and `component_size`
+//! - O(n + m) memory: one node per vertex and two per edge
+//!
+//! # Implementation Details
+//! - The Euler tour of a tree holds one node per vertex and one per direction of every edge. Tours are stored in
+//!   treaps keyed by position (implicit treaps), whose nodes keep their parent so the root and the position of a node
+//!   can be found from the node.
+//! - `link(u, v)` rotates the tours of u and v to start at them, and joins them as `tour(u) uv tour(v) vu`.
+//! - `cut(u, v)` splits the tour around the nodes of `uv` and `vu`: the part between them is the tour of one side, and
+//!   the parts around them form the tour of the other.
+//! - Treap nodes live in a `FreeListVec`, so the slots of cut edges are reused. Priorities come from a xorshift
+//!   generator with a fixed seed, so runs are reproducible.
+//!
+//! # Usage
+//! ```
+//! use data_structures::error::DataStructureError;
+//! use data_structures::graph::dynamic_connectivity::DynamicConnectivity;
+//!
+//! let mut forest = DynamicConnectivity::new(4);
+//!
+//! forest.link(0, 1).unwrap();
+//! forest.link(1, 2).unwrap();
+//! assert!(forest.connected(0, 2));
+//! assert!(!forest.connected(0, 3));
+//!
+//! forest.cut(0, 1).unwrap();
+//! assert!(!forest.connected(0, 2));
+//! assert!(forest.connected(1, 2));
+//! assert_eq!(forest.link(1, 2), Err(DataStructureError::AlreadyConnected));
+//! ```
+//!
+use std::collections::HashMap;
+
+use crate::error::DataStructureError;
+use crate::storage::free_list_vec::FreeListVec;
+
+// Node of an implicit treap, holding a vertex or a directed edge of a tour
+#[derive(Debug, Clone)]
+struct TourNode {
+    left: Option<usize>,
+    right: Option<usize>,
+    parent: Option<usize>,
+    priority: u64,
+    size: usize,     // nodes in the subtree
+    vertices: usize, // vertex nodes in the subtree
+    is_vertex: bool,
+}
+
+/// Struct representing a forest whose edges can be added and removed, answering connectivity queries
+///
+#[derive(Debug, Clone)]
+pub struct DynamicConnectivity {
+    nodes: FreeListVec<TourNode>,
+    vertices: Vec<usize>,                  // tour node of each vertex
+    edges: HashMap<(usize, usize), usize>, // tour node of each direction of each edge
+    seed: u64,
+}
+
+impl DynamicConnectivity {
+    /// Create a forest of isolated vertices
+    ///
+    /// # Arguments
+    /// * `vertices`: The number of vertices, numbered from 0
+    ///
+    /// # Returns
+    /// A new DynamicConnectivity instance without edges
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::graph::dynamic_connectivity::DynamicConnectivity;
+    ///
+    /// let forest = DynamicConnectivity::new(3);
+    /// assert_eq!(forest.vertex_count(), 3);
+    /// assert!(!forest.connected(0, 1));
+    /// ```
+    pub fn new(vertices: usize) -> Self {
+        let mut forest = DynamicConnectivity {
+            nodes: FreeListVec::with_capacity(vertices),
+            vertices: Vec::with_capacity(vertices),
+            edges: HashMap::new(),
+            seed: 0x9E37_79B9_7F4A_7C15,
+        };
+
+        for _ in 0..vertices {
+            forest.add_vertex();
+        }
+        forest
+    }
+
+    /// Get the number of vertices
+    /// # Returns
+    /// The number of vertices, connected or not
+    /// # Example
+    /// ```
+    /// use data_structures::graph::dynamic_connectivity::DynamicConnectivity;
+    ///
+    /// assert_eq!(DynamicConnectivity::new(5).vertex_count(), 5);
+    /// ```
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Get the number of edges
+    /// # Returns
+    /// The number of linked pairs of vertices
+    /// # Example
+    /// ```
+    /// use data_structures::graph::dynamic_connectivity::DynamicConnectivity;
+    ///
+    /// let mut forest = DynamicConnectivity::new(3);
+    /// forest.link(0, 1).unwrap();
+    /// assert_eq!(forest.edge_count(), 1);
+    /// ```
+    pub fn edge_count(&self) -> usize {
+        self.edges.len() / 2
+    }
+
+    /// Add an isolated vertex
+    /// # Returns
+    /// The number of the new vertex
+    /// # Example
+    /// ```
+    /// use data_structures::graph::dynamic_connectivity::DynamicConnectivity;
+    ///
+    /// let mut forest = DynamicConnectivity::new(2);
+    /// assert_eq!(forest.add_vertex(), 2);
+    /// assert_eq!(forest.component_size(2), 1);
+    /// ```
+    pub fn add_vertex(&mut self) -> usize {
+        let node = self.new_node(true);
+        self.vertices.push(node);
+        self.vertices.len() - 1
+    }
+
+    /// Check if there is an edge between two vertices
+    /// # Arguments
+    /// * `u`, `v`: The vertices
+    /// # Returns
+    /// True if the vertices are linked directly
+    /// # Example
+    /// ```
+    /// use data_structures::graph::dynamic_connectivity::DynamicConnectivity;
+    ///
+    /// let mut forest = DynamicConnectivity::new(3);
+    /// forest.link(0, 1).unwrap();
+    /// forest.link(1, 2).unwrap();
+    ///
+    /// assert!(forest.has_edge(1, 0));
+    /// assert!(!forest.has_edge(0, 2));
+    /// ```
+    pub fn has_edge(&self, u: usize, v: usize) -> bool {
+        self.edges.contains_key(&(u, v))
+    }
+
+    /// Check if two vertices are in the same tree
+    /// # Arguments
+    /// * `u`, `v`: The vertices
+    /// # Returns
+    /// True if there is a path between the vertices, false if they aren't connected or don't exist
+    /// # Example
+    /// ```
+    /// use data_structures::graph::dynamic_connectivity::DynamicConnectivity;
+    ///
+    /// let mut forest = DynamicConnectivity::new(3);
+    /// forest.link(0, 2).unwrap();
+    ///
+    /// assert!(forest.connected(2, 0));
+    /// assert!(!forest.connected(0, 1));
+    /// assert!(!forest.connected(0, 10));
+    /// ```
+    pub fn connected(&self, u: usize, v: usize) -> bool {
+        match (self.vertices.get(u), self.vertices.get(v)) {
+            (Some(&u), Some(&v)) => self.root(u) == self.root(v),
+            _ => false,
+        }
+    }
+
+    /// Count the vertices of the tree holding a vertex
+    /// # Arguments
+    /// * `v`: The vertex
+    /// # Returns
+    /// The number of vertices connected to `v`, itself included, or 0 if the vertex doesn't exist
+    /// # Example
+    /// ```
+    /// use data_structures::graph::dynamic_connectivity::DynamicConnectivity;
+    ///
+    /// let mut forest = DynamicConnectivity::new(4);
+    /// forest.link(0, 1).unwrap();
+    /// forest.link(0, 2).unwrap();
+    ///
+    /// assert_eq!(forest.component_size(2), 3);
+    /// assert_eq!(forest.component_size(3), 1);
+    /// ```
+    pub fn component_size(&self, v: usize) -> usize {
+        self.vertices
+            .get(v)
+            .map_or(0, |&node| self.node(self.root(node)).vertices)
+    }
+
+    /// Add an edge between two vertices of different trees
+    /// # Arguments
+    /// * `u`, `v`: The vertices to link
+    /// # Returns
+    /// Ok, or an error if a vertex doesn't exist or the vertices are already connected, since the edge would close a cycle
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::graph::dynamic_connectivity::DynamicConnectivity;
+    ///
+    /// let mut forest = DynamicConnectivity::new(3);
+    ///
+    /// assert!(forest.link(0, 1).is_ok());
+    /// assert_eq!(forest.link(1, 0), Err(DataStructureError::AlreadyConnected));
+    /// assert_eq!(forest.link(0, 3), Err(DataStructureError::VertexOutOfBounds { vertex: 3, len: 3 }));
+    /// ```
+    pub fn link(&mut self, u: usize, v: usize) -> Result<(), DataStructureError> {
+        let len = self.vertices.len();
+        if let Some(vertex) = [u, v].into_iter().find(|&vertex| vertex >= len) {
+            return Err(DataStructureError::VertexOutOfBounds { vertex, len });
+        }
+        if self.connected(u, v) {
+            return Err(DataStructureError::AlreadyConnected);
+        }
+
+        let tour_u = self.reroot(self.vertices[u]);
+        let tour_v = self.reroot(self.vertices[v]);
+
+        let forward = self.new_node(false);
+        let backward = self.new_node(false);
+        self.edges.insert((u, v), forward);
+        self.edges.insert((v, u), backward);
+
+        let tour = self.merge(Some(tour_u), Some(forward));
+        let tour = self.merge(tour, Some(tour_v));
+        self.merge(tour, Some(backward));
+
+        Ok(())
+    }
+
+    /// Remove the edge between two vertices
+    /// # Arguments
+    /// * `u`, `v`: The linked vertices
+    /// # Returns
+    /// Ok, or an error if there is no edge between the vertices
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::graph::dynamic_connectivity::DynamicConnectivity;
+    ///
+    /// let mut forest = DynamicConnectivity::new(3);
+    /// forest.link(0, 1).unwrap();
+    ///
+    /// assert!(forest.cut(1, 0).is_ok());
+    /// assert!(!forest.connected(0, 1));
+    /// assert_eq!(forest.cut(0, 1), Err(DataStructureError::MissingEdge));
+    /// ```
+    pub fn cut(&mut self, u: usize, v: usize) -> Result<(), DataStructureError> {
+        let (Some(&forward), Some(&backward)) = (self.edges.get(&(u, v)), self.edges.get(&(v, u)))
+        else {
+            return Err(DataStructureError::MissingEdge);
+        };
+
+        // Order the two edge nodes by position: tour = before, first, between, second, after
+        let (first, second) = if self.position(forward) < self.position(backward) {
+            (forward, backward)
+        } else {
+            (backward, forward)
+        };
+
+        let root = self.root(first);
+        let (rest, after) = self.split(Some(root), self.position(second) + 1);
+        let (rest, _) = self.split(rest, self.size(rest) - 1);
+        let (before, rest) = self.split(rest, self.position(first));
+        let (_, between) = self.split(rest, 1);
+
+        // The part between the edges is one side, the parts around them make the other
+        self.detach(between);
+        let other = self.merge(before, after);
+        self.detach(other);
+
+        self.edges.remove(&(u, v));
+        self.edges.remove(&(v, u));
+        self.nodes.remove(forward);
+        self.nodes.remove(backward);
+
+        Ok(())
+    }
+
+    fn node(&self, index: usize) -> &TourNode {
+        self.nodes.get(index).unwrap()
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut TourNode {
+        self.nodes.get_mut(index).unwrap()
+    }
+
+    fn new_node(&mut self, is_vertex: bool) -> usize {
+        // xorshift64
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        self.nodes.insert(TourNode {
+            left: None,
+            right: None,
+            parent: None,
+            priority: self.seed,
+            size: 1,
+            vertices: usize::from(is_vertex),
+            is_vertex,
+        })
+    }
+
+    fn size(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |node| self.node(node).size)
+    }
+
+    fn detach(&mut self, node: Option<usize>) {
+        if let Some(node) = node {
+            self.node_mut(node).parent = None;
+        }
+    }
+
+    // Recompute the counters of a node from its children, and point the children to it
+    fn update(&mut self, node: usize) {
+        let TourNode {
+            left,
+            right,
+            is_vertex,
+            ..
+        } = *self.node(node);
+
+        let mut size = 1;
+        let mut vertices = usize::from(is_vertex);
+        for child in [left, right].into_iter().flatten() {
+            self.node_mut(child).parent = Some(node);
+            size += self.node(child).size;
+            vertices += self.node(child).vertices;
+        }
+
+        let node = self.node_mut(node);
+        node.size = size;
+        node.vertices = vertices;
+    }
+
+    fn root(&self, mut node: usize) -> usize {
+        while let Some(parent) = self.node(node).parent {
+            node = parent;
+        }
+        node
+    }
+
+    // Position of a node in its tour
+    fn position(&self, mut node: usize) -> usize {
+        let mut position = self.size(self.node(node).left);
+
+        while let Some(parent) = self.node(node).parent {
+            if self.node(parent).right == Some(node) {
+                position += self.size(self.node(parent).left) + 1;
+            }
+            node = parent;
+        }
+
+        position
+    }
+
+    // Split a treap into its first `count` nodes and the rest
+    fn split(&mut self, node: Option<usize>, count: usize) -> (Option<usize>, Option<usize>) {
+        let Some(node) = node else {
+            return (None, None);
+        };
+
+        let left = self.node(node).left;
+        let left_size = self.size(left);
+
+        let (first, second) = if count <= left_size {
+            let (first, rest) = self.split(left, count);
+            self.node_mut(node).left = rest;
+            (first, Some(node))
+        } else {
+            let right = self.node(node).right;
+            let (rest, second) = self.split(right, count - left_size - 1);
+            self.node_mut(node).right = rest;
+            (Some(node), second)
+        };
+
+        self.update(node);
+        self.detach(first);
+        self.detach(second);
+        (first, second)
+    }
+
+    // Concatenate two treaps
+    fn merge(&mut self, first: Option<usize>, second: Option<usize>) -> Option<usize> {
+        let (first, second) = match (first, second) {
+            (None, other) | (other, None) => return other,
+            (Some(first), Some(second)) => (first, second),
+        };
+
+        if self.node(first).priority > self.node(second).priority {
+            let right = self.merge(self.node(first).right, Some(second));
+            self.node_mut(first).right = right;
+            self.update(first);
+            Some(first)
+        } else {
+            let left = self.merge(Some(first), self.node(second).left);
+            self.node_mut(second).left = left;
+            self.update(second);
+            Some(second)
+        }
+    }
+
+    // Rotate the tour holding a vertex node so it starts at the node, returning the root of the tour
+    fn reroot(&mut self, vertex: usize) -> usize {
+        let root = self.root(vertex);
+        let (before, rest) = self.split(Some(root), self.position(vertex));
+
+        let root = self.merge(rest, before).unwrap();
+        self.detach(Some(root));
+        root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    // Component labels by walking the edges
+    fn components(vertices: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+        let mut labels: Vec<usize> = (0..vertices).collect();
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+            for &(u, v) in edges {
+                let label = labels[u].min(labels[v]);
+                if labels[u] != label || labels[v] != label {
+                    labels[u] = label;
+                    labels[v] = label;
+                    changed = true;
+                }
+            }
+        }
+
+        labels
+    }
+
+    #[test]
+    fn test_dynamic_connectivity_against_walk() {
+        let vertices = 40;
+        let mut forest = DynamicConnectivity::new(vertices);
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        let mut rng = Lcg::new(5);
+        let mut next = || (rng.next_u64() >> 33) as usize;
+
+        for _ in 0..2000 {
+            let labels = components(vertices, &edges);
+            let (u, v) = (next() % vertices, next() % vertices);
+
+            if next() % 3 == 0 && !edges.is_empty() {
+                let (u, v) = edges.swap_remove(next() % edges.len());
+                assert!(forest.cut(v, u).is_ok());
+            } else if labels[u] == labels[v] {
+                assert_eq!(forest.link(u, v), Err(DataStructureError::AlreadyConnected));
+            } else {
+                assert!(forest.link(u, v).is_ok());
+                edges.push((u, v));
+            }
+
+            let labels = components(vertices, &edges);
+            for _ in 0..10 {
+                let (a, b) = (next() % vertices, next() % vertices);
+                assert_eq!(forest.connected(a, b), labels[a] == labels[b]);
+
+                let size = labels.iter().filter(|&&label| label == labels[a]).count();
+                assert_eq!(forest.component_size(a), size);
+            }
+            assert_eq!(forest.edge_count(), edges.len());
+        }
+
+        // Only the vertex nodes are left once every edge is cut
+        for (u, v) in edges.drain(..) {
+            forest.cut(u, v).unwrap();
+        }
+        assert_eq!(forest.nodes.len(), vertices);
+
+        // A new vertex takes the slot of a cut edge node
+        let extra = forest.add_vertex();
+        forest.link(extra, 0).unwrap();
+        assert_eq!(forest.component_size(0), 2);
+    }
+}
@@ -0,0 +1,1410 @@
+//! This module reads and writes a `Graph` in the text formats of other graph tools: edge lists, adjacency lists,
+//! Graphviz DOT and, with the `graphml` feature, GraphML. Vertices are written as their values, so a graph round-trips
+//! with tools like networkx and Gephi, which identify vertices by name.
+//!
+//! # Performance
+//! - O(V + E) to write, apart from sorting the neighbors of each vertex
+//! - O(V + E) expected to read, the labels of the vertices being hashed
+//!
+//! # Implementation Details
+//! - Vertex values are written with `Display` and read with `FromStr`. Each label names one vertex, so labels must be
+//!   distinct; in edge and adjacency lists they must not contain whitespace or `#` either. DOT quotes them.
+//! - Edge weights go through the `TextWeight` trait: `()` has no text, so unweighted graphs are written without
+//!   weights, and the numbers are written and read as themselves.
+//! - Edge lists hold one `from to [weight]` line per edge, as networkx `write_edgelist`. Adjacency lists hold one line
+//!   per vertex, its label followed by the labels of its neighbors, as networkx `write_adjlist`; they keep isolated
+//!   vertices but no weights.
+//! - Every stored edge is written, so the undirected edges appear both ways and a written graph is read back with
+//!   `EdgeKind::Directed`. Files listing undirected edges once are read with `EdgeKind::Undirected`.
+//! - An edge read again once present is ignored, keeping the first weight.
+//! - The DOT reader understands node and edge statements with their attributes, `graph` files with `--` edges read as
+//!   undirected and `digraph` files with `->` edges. Attribute statements are skipped, subgraphs and ports aren't
+//!   supported. The weight is the `weight` attribute of an edge.
+//! - GraphML documents are written with one `node` per vertex, its label as id, and one `edge` per stored edge, the
+//!   weight in the `weight` key. The reader takes the first key named `weight` for edges and its default, follows the
+//!   `directed` attribute of edges over the `edgedefault` of the graph, and skips other data. It reads a single
+//!   graph; nested graphs, hyperedges and ports are rejected. XML is parsed by hand, with no dependency.
+//! - Parse errors are `DataStructureError::InvalidLine`, with the number of the line and the reason.
+//!
+//! # Usage
+//! ```
+//! use data_structures::graph::io::{from_dot, from_edge_list, to_dot, to_edge_list};
+//! use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+//!
+//! let roads: Graph<String, u32> = from_edge_list(
+//!     "# city city km\nlisbon porto 313\nlisbon faro 278\n",
+//!     EdgeKind::Undirected,
+//! )
+//! .unwrap();
+//! assert_eq!(roads.len(), 3);
+//! assert_eq!(roads.edge_weight(1, 0), Some(313));
+//!
+//! // Written back, each undirected edge appears both ways
+//! assert_eq!(to_edge_list(&roads).lines().count(), 4);
+//!
+//! let dot = to_dot(&roads);
+//! assert!(dot.contains("\"lisbon\" -> \"porto\" [weight=\"313\"];"));
+//! let copy: Graph<String, u32> = from_dot(&dot).unwrap();
+//! assert_eq!(to_edge_list(&copy), to_edge_list(&roads));
+//! ```
+//!
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+
+use crate::error::DataStructureError;
+use crate::graph::vertex_graph::{EdgeKind, Graph};
+use crate::graph::view::GraphView;
+
+/// Trait for the edge weights that can be written as text and read back
+///
+pub trait TextWeight: Sized {
+    /// Write the weight
+    /// # Returns
+    /// The text of the weight, or None if edges carrying it are written without a weight
+    fn to_text(&self) -> Option<String>;
+
+    /// Read a weight
+    /// # Arguments
+    /// * `text`: The text of the weight, None if the edge has no weight
+    /// # Returns
+    /// The weight, or None if the text isn't a valid weight or a weight is needed and missing
+    fn from_text(text: Option<&str>) -> Option<Self>;
+}
+
+impl TextWeight for () {
+    fn to_text(&self) -> Option<String> {
+        None
+    }
+
+    fn from_text(_: Option<&str>) -> Option<Self> {
+        Some(())
+    }
+}
+
+macro_rules! text_weight_number {
+    ($($ty:ty),*) => {$(
+        impl TextWeight for $ty {
+            fn to_text(&self) -> Option<String> {
+                Some(self.to_string())
+            }
+
+            fn from_text(text: Option<&str>) -> Option<Self> {
+                text?.parse().ok()
+            }
+        }
+    )*};
+}
+
+text_weight_number!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+/// Write a graph as an edge list
+/// # Arguments
+/// * `graph`: The graph
+/// # Returns
+/// One `from to [weight]` line per edge, in increasing order of the vertices
+/// # Example
+/// ```
+/// use data_structures::graph::io::to_edge_list;
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// let mut graph = Graph::weighted();
+/// let a = graph.add_vertex("a");
+/// let b = graph.add_vertex("b");
+/// graph.add_weighted_edge(a, b, 1.5, EdgeKind::Directed).unwrap();
+///
+/// assert_eq!(to_edge_list(&graph), "a b 1.5\n");
+/// ```
+pub fn to_edge_list<G>(graph: &G) -> String
+where
+    G: GraphView,
+    G::Value: Display,
+    G::Weight: TextWeight,
+{
+    let labels = labels(graph);
+    let mut text = String::new();
+    for from in graph.vertices() {
+        for (to, weight) in graph.weighted_neighbors(from) {
+            match weight.to_text() {
+                Some(weight) => writeln!(text, "{} {} {}", labels[from], labels[to], weight),
+                None => writeln!(text, "{} {}", labels[from], labels[to]),
+            }
+            .unwrap();
+        }
+    }
+    text
+}
+
+/// Read a graph from an edge list
+/// Blank lines are skipped, and `#` starts a comment running to the end of the line.
+/// # Arguments
+/// * `text`: The edge list, one `from to [weight]` line per edge
+/// * `kind`: The kind of the edges read
+/// # Returns
+/// The graph, its vertices numbered in the order they first appear, or Err(DataStructureError::InvalidLine) if a line
+/// doesn't have two or three fields, or a label or weight can't be parsed
+/// # Example
+/// ```
+/// use data_structures::graph::io::from_edge_list;
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// let graph: Graph<u32> = from_edge_list("1 2\n2 3 # a comment\n", EdgeKind::Directed).unwrap();
+/// assert_eq!(graph.value(2), Some(3));
+/// assert!(graph.has_edge(1, 2));
+/// assert!(!graph.has_edge(2, 1));
+///
+/// let error = from_edge_list::<u32, u32>("1 2 heavy\n", EdgeKind::Directed).unwrap_err();
+/// assert_eq!(error.to_string(), "Invalid line 1: invalid weight");
+/// ```
+pub fn from_edge_list<T: FromStr, W: TextWeight + Clone>(
+    text: &str,
+    kind: EdgeKind,
+) -> Result<Graph<T, W>, DataStructureError> {
+    let mut builder = Builder::new();
+    for (number, line) in text.lines().enumerate() {
+        let line_number = number + 1;
+        let fields: Vec<&str> = line
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        let (from, to, weight) = match fields[..] {
+            [] => continue,
+            [from, to] => (from, to, None),
+            [from, to, weight] => (from, to, Some(weight)),
+            _ => return Err(invalid(line_number, "expected two vertices and a weight")),
+        };
+
+        let from = builder.vertex(from, line_number)?;
+        let to = builder.vertex(to, line_number)?;
+        let weight = W::from_text(weight).ok_or(invalid(line_number, "invalid weight"))?;
+        builder.edge(from, to, weight, kind);
+    }
+    Ok(builder.graph)
+}
+
+/// Write a graph as an adjacency list, without the weights
+/// # Arguments
+/// * `graph`: The graph
+/// # Returns
+/// One line per vertex, in index order: its label followed by the labels of its neighbors
+/// # Example
+/// ```
+/// use data_structures::graph::io::to_adjacency_list;
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// let mut graph = Graph::new();
+/// let a = graph.add_vertex('a');
+/// let b = graph.add_vertex('b');
+/// let c = graph.add_vertex('c');
+/// graph.add_edge(a, b, EdgeKind::Directed).unwrap();
+/// graph.add_edge(a, c, EdgeKind::Directed).unwrap();
+///
+/// assert_eq!(to_adjacency_list(&graph), "a b c\nb\nc\n");
+/// ```
+pub fn to_adjacency_list<G>(graph: &G) -> String
+where
+    G: GraphView,
+    G::Value: Display,
+{
+    let labels = labels(graph);
+    let mut text = String::new();
+    for vertex in graph.vertices() {
+        text.push_str(&labels[vertex]);
+        for (neighbor, _) in graph.weighted_neighbors(vertex) {
+            text.push(' ');
+            text.push_str(&labels[neighbor]);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Read a graph from an adjacency list, its edges getting the default weight
+/// Blank lines are skipped, and `#` starts a comment running to the end of the line.
+/// # Arguments
+/// * `text`: The adjacency list, one line per vertex with its label followed by the labels of its neighbors
+/// * `kind`: The kind of the edges read
+/// # Returns
+/// The graph, its vertices numbered in the order they first appear, or Err(DataStructureError::InvalidLine) if a label
+/// can't be parsed
+/// # Example
+/// ```
+/// use data_structures::graph::io::from_adjacency_list;
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// let graph: Graph<String> = from_adjacency_list("a b c\nb c\nd\n", EdgeKind::Undirected).unwrap();
+/// assert_eq!(graph.len(), 4);
+/// assert_eq!(graph.neighbors(2).unwrap().collect::<Vec<_>>(), vec![0, 1]);
+/// assert_eq!(graph.neighbors(3).unwrap().count(), 0);
+/// ```
+pub fn from_adjacency_list<T: FromStr, W: Default + Clone>(
+    text: &str,
+    kind: EdgeKind,
+) -> Result<Graph<T, W>, DataStructureError> {
+    let mut builder = Builder::new();
+    for (number, line) in text.lines().enumerate() {
+        let line_number = number + 1;
+        let mut labels = line
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .split_whitespace();
+        let Some(label) = labels.next() else {
+            continue;
+        };
+
+        let from = builder.vertex(label, line_number)?;
+        for label in labels {
+            let to = builder.vertex(label, line_number)?;
+            builder.edge(from, to, W::default(), kind);
+        }
+    }
+    Ok(builder.graph)
+}
+
+/// Write a graph in the Graphviz DOT language
+/// # Arguments
+/// * `graph`: The graph
+/// # Returns
+/// A `digraph` declaring every vertex by its quoted label, then every edge with its weight as the `weight` attribute
+/// # Example
+/// ```
+/// use data_structures::graph::io::to_dot;
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// let mut graph = Graph::new();
+/// let a = graph.add_vertex("say \"hi\"");
+/// let b = graph.add_vertex("b");
+/// graph.add_edge(a, b, EdgeKind::Directed).unwrap();
+///
+/// assert_eq!(to_dot(&graph), "digraph {\n    \"say \\\"hi\\\"\";\n    \"b\";\n    \"say \\\"hi\\\"\" -> \"b\";\n}\n");
+/// ```
+pub fn to_dot<G>(graph: &G) -> String
+where
+    G: GraphView,
+    G::Value: Display,
+    G::Weight: TextWeight,
+{
+    let labels: Vec<String> = labels(graph)
+        .iter()
+        .map(|label| label.replace('\\', "\\\\").replace('"', "\\\""))
+        .collect();
+    let mut dot = String::from("digraph {\n");
+    for vertex in graph.vertices() {
+        writeln!(dot, "    \"{}\";", labels[vertex]).unwrap();
+    }
+    for from in graph.vertices() {
+        for (to, weight) in graph.weighted_neighbors(from) {
+            match weight.to_text() {
+                Some(weight) => writeln!(
+                    dot,
+                    "    \"{}\" -> \"{}\" [weight=\"{}\"];",
+                    labels[from], labels[to], weight
+                ),
+                None => writeln!(dot, "    \"{}\" -> \"{}\";", labels[from], labels[to]),
+            }
+            .unwrap();
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Read a graph written in the Graphviz DOT language
+/// The vertices are the node IDs, parsed as values. Edges of a `graph` are undirected and those of a `digraph`
+/// directed, their weight being their `weight` attribute.
+/// # Arguments
+/// * `text`: The DOT source
+/// # Returns
+/// The graph, its vertices numbered in the order they first appear, or Err(DataStructureError::InvalidLine) if the
+/// source isn't valid DOT, uses subgraphs or ports, or a label or weight can't be parsed
+/// # Example
+/// ```
+/// use data_structures::graph::io::from_dot;
+/// use data_structures::graph::vertex_graph::Graph;
+///
+/// let dot = r#"
+///     graph network {
+///         node [shape=box];  // attribute statements are skipped
+///         a -- b [weight=2];
+///         b -- c -- "d e" [weight=5, color=red];
+///     }
+/// "#;
+/// let graph: Graph<String, u32> = from_dot(dot).unwrap();
+/// assert_eq!(graph.len(), 4);
+/// assert_eq!(graph.edge_weight(3, 2), Some(5));
+///
+/// // Numeric weights can't be missing
+/// assert!(from_dot::<String, u32>("digraph { a -> b }").is_err());
+/// ```
+pub fn from_dot<T: FromStr, W: TextWeight + Clone>(
+    text: &str,
+) -> Result<Graph<T, W>, DataStructureError> {
+    let mut parser = DotParser {
+        tokens: tokenize(text)?,
+        position: 0,
+        last_line: text.lines().count().max(1),
+    };
+    let mut builder = Builder::new();
+
+    if parser.keyword("strict") {
+        parser.position += 1;
+    }
+    let kind = if parser.keyword("digraph") {
+        EdgeKind::Directed
+    } else if parser.keyword("graph") {
+        EdgeKind::Undirected
+    } else {
+        return Err(parser.error("expected graph or digraph"));
+    };
+    parser.position += 1;
+    if let Some((Token::Id(_, _), _)) = parser.peek() {
+        parser.position += 1;
+    }
+    parser.expect(Token::Symbol('{'))?;
+
+    loop {
+        let (token, line) = parser.next()?;
+        let id = match token {
+            Token::Symbol('}') => break,
+            Token::Symbol(';') => continue,
+            Token::Id(id, quoted) => {
+                let keyword =
+                    !quoted && ["graph", "node", "edge"].contains(&id.to_lowercase().as_str());
+                if !quoted && id.eq_ignore_ascii_case("subgraph") {
+                    return Err(invalid(line, "subgraphs are not supported"));
+                }
+                if keyword {
+                    parser.attributes()?;
+                    continue;
+                }
+                id
+            }
+            Token::Symbol('{') => return Err(invalid(line, "subgraphs are not supported")),
+            _ => return Err(invalid(line, "expected a statement")),
+        };
+
+        // A graph attribute assignment
+        if parser
+            .peek()
+            .is_some_and(|(token, _)| *token == Token::Symbol('='))
+        {
+            parser.position += 1;
+            parser.id()?;
+            continue;
+        }
+        if parser
+            .peek()
+            .is_some_and(|(token, _)| *token == Token::Symbol(':'))
+        {
+            return Err(invalid(line, "ports are not supported"));
+        }
+
+        // A node statement, or a chain of edges
+        let mut chain = vec![(builder.vertex(&id, line)?, line)];
+        while let Some((token @ (Token::Arrow | Token::Line), line)) = parser.peek().cloned() {
+            let expected = if kind == EdgeKind::Directed {
+                Token::Arrow
+            } else {
+                Token::Line
+            };
+            if token != expected {
+                return Err(invalid(line, "edge operator doesn't match the graph"));
+            }
+            parser.position += 1;
+            let (id, line) = parser.id()?;
+            chain.push((builder.vertex(&id, line)?, line));
+        }
+
+        let attributes = parser.attributes()?;
+        let weight = attributes
+            .iter()
+            .find(|(name, _)| name == "weight")
+            .map(|(_, value)| value.as_str());
+        for pair in chain.windows(2) {
+            let ((from, _), (to, line)) = (pair[0], pair[1]);
+            let weight = W::from_text(weight).ok_or(invalid(line, "invalid weight"))?;
+            builder.edge(from, to, weight, kind);
+        }
+    }
+
+    if let Some((_, line)) = parser.peek() {
+        return Err(invalid(*line, "content after the graph"));
+    }
+    Ok(builder.graph)
+}
+
+/// Write a graph in GraphML
+/// The vertices are `node` elements whose id is their label. The weights are `data` elements of the `weight` key,
+/// typed `long`, `double` or `string` by the weights written.
+/// # Arguments
+/// * `graph`: The graph
+/// # Returns
+/// The GraphML document, a directed graph listing the vertices and then the edges in increasing order
+/// # Example
+/// ```
+/// use data_structures::graph::io::to_graphml;
+/// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+///
+/// let mut graph = Graph::weighted();
+/// let a = graph.add_vertex("a");
+/// let b = graph.add_vertex("b & c");
+/// graph.add_weighted_edge(a, b, 2, EdgeKind::Directed).unwrap();
+///
+/// let graphml = to_graphml(&graph);
+/// assert!(graphml.contains("<key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"long\"/>"));
+/// assert!(graphml.contains("<node id=\"b &amp; c\"/>"));
+/// assert!(graphml.contains("<edge source=\"a\" target=\"b &amp; c\">\n      <data key=\"weight\">2</data>"));
+/// ```
+#[cfg(feature = "graphml")]
+pub fn to_graphml<G>(graph: &G) -> String
+where
+    G: GraphView,
+    G::Value: Display,
+    G::Weight: TextWeight,
+{
+    let labels: Vec<String> = labels(graph)
+        .iter()
+        .map(|label| escape_xml(label))
+        .collect();
+    let mut edges = Vec::new();
+    for from in graph.vertices() {
+        for (to, weight) in graph.weighted_neighbors(from) {
+            edges.push((from, to, weight.to_text()));
+        }
+    }
+    // The narrowest type that reads every weight back
+    let weights = || edges.iter().filter_map(|(_, _, weight)| weight.as_deref());
+    let weight_type = if weights().all(|weight| weight.parse::<i64>().is_ok()) {
+        "long"
+    } else if weights().all(|weight| weight.parse::<f64>().is_ok()) {
+        "double"
+    } else {
+        "string"
+    };
+
+    let mut graphml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    graphml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    if weights().next().is_some() {
+        writeln!(
+            graphml,
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"{}\"/>",
+            weight_type
+        )
+        .unwrap();
+    }
+    graphml.push_str("  <graph edgedefault=\"directed\">\n");
+    for vertex in graph.vertices() {
+        writeln!(graphml, "    <node id=\"{}\"/>", labels[vertex]).unwrap();
+    }
+    for (from, to, weight) in &edges {
+        let (from, to) = (&labels[*from], &labels[*to]);
+        match weight {
+            Some(weight) => writeln!(
+                graphml,
+                "    <edge source=\"{}\" target=\"{}\">\n      <data key=\"weight\">{}</data>\n    </edge>",
+                from,
+                to,
+                escape_xml(weight)
+            ),
+            None => writeln!(graphml, "    <edge source=\"{}\" target=\"{}\"/>", from, to),
+        }
+        .unwrap();
+    }
+    graphml.push_str("  </graph>\n</graphml>\n");
+    graphml
+}
+
+/// Read a graph written in GraphML
+/// The vertices are the ids of the `node` elements, parsed as values. Edges follow the `edgedefault` of the graph or
+/// their own `directed` attribute, and their weight is their data for the edge key named `weight`, or its default.
+/// # Arguments
+/// * `text`: The GraphML document
+/// # Returns
+/// The graph, its vertices numbered in the order they first appear, or Err(DataStructureError::InvalidLine) if the
+/// document isn't well-formed GraphML, has several or nested graphs, hyperedges or ports, or a label or weight can't
+/// be parsed
+/// # Example
+/// ```
+/// use data_structures::graph::io::from_graphml;
+/// use data_structures::graph::vertex_graph::Graph;
+///
+/// let graphml = r#"<?xml version="1.0" encoding="UTF-8"?>
+/// <graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+///   <key id="d0" for="edge" attr.name="weight" attr.type="double">
+///     <default>1.0</default>
+///   </key>
+///   <graph id="G" edgedefault="undirected">
+///     <node id="n0"/>
+///     <node id="n1"><desc>skipped</desc></node>
+///     <edge source="n0" target="n1"><data key="d0">2.5</data></edge>
+///     <edge source="n1" target="n2" directed="true"/>
+///   </graph>
+/// </graphml>"#;
+/// let graph: Graph<String, f64> = from_graphml(graphml).unwrap();
+/// assert_eq!(graph.len(), 3);
+/// assert_eq!(graph.edge_weight(1, 0), Some(2.5));
+/// assert_eq!(graph.edge_weight(1, 2), Some(1.0));
+/// assert!(!graph.has_edge(2, 1));
+/// ```
+#[cfg(feature = "graphml")]
+pub fn from_graphml<T: FromStr, W: TextWeight + Clone>(
+    text: &str,
+) -> Result<Graph<T, W>, DataStructureError> {
+    let mut reader = GraphmlReader {
+        builder: Builder::new(),
+        open: Vec::new(),
+        weight_key: None,
+        weight_default: None,
+        in_weight_key: false,
+        kind: None,
+        edge: None,
+        captured: None,
+    };
+    for (event, line) in xml_events(text)? {
+        match event {
+            Xml::Start {
+                name,
+                attributes,
+                empty,
+            } => {
+                reader.start(name, &attributes, line)?;
+                if empty {
+                    reader.end(line)?;
+                }
+            }
+            Xml::Text(text) => {
+                if let Some(captured) = reader.captured.as_mut() {
+                    captured.push_str(&text);
+                }
+            }
+            Xml::End(name) => {
+                if reader.open.last() != Some(&name) {
+                    return Err(invalid(line, "mismatched end tag"));
+                }
+                reader.end(line)?;
+            }
+        }
+    }
+
+    if !reader.open.is_empty() || reader.kind.is_none() {
+        let last_line = text.lines().count().max(1);
+        return Err(invalid(last_line, "unexpected end of the document"));
+    }
+    Ok(reader.builder.graph)
+}
+
+// Error for a line that can't be parsed
+fn invalid(line: usize, reason: &'static str) -> DataStructureError {
+    DataStructureError::InvalidLine { line, reason }
+}
+
+// Labels of the vertices by index, empty for the removed ones
+fn labels<G: GraphView>(graph: &G) -> Vec<String>
+where
+    G::Value: Display,
+{
+    let mut labels = vec![String::new(); graph.vertex_bound()];
+    for vertex in graph.vertices() {
+        labels[vertex] = graph.with_value(vertex, |value| value.to_string()).unwrap();
+    }
+    labels
+}
+
+/// Graph being read, with the vertex of each label
+struct Builder<T, W> {
+    graph: Graph<T, W>,
+    vertices: HashMap<String, usize>,
+}
+
+impl<T: FromStr, W: Clone> Builder<T, W> {
+    fn new() -> Self {
+        Builder {
+            graph: Graph::weighted(),
+            vertices: HashMap::new(),
+        }
+    }
+
+    // Vertex of a label, added on its first appearance
+    fn vertex(&mut self, label: &str, line: usize) -> Result<usize, DataStructureError> {
+        if let Some(&vertex) = self.vertices.get(label) {
+            return Ok(vertex);
+        }
+        let value = label
+            .parse()
+            .map_err(|_| invalid(line, "invalid vertex label"))?;
+        let vertex = self.graph.add_vertex(value);
+        self.vertices.insert(label.to_string(), vertex);
+        Ok(vertex)
+    }
+
+    // Add an edge, unless it is already present
+    fn edge(&mut self, from: usize, to: usize, weight: W, kind: EdgeKind) {
+        // Both vertices exist, so the only possible error is an edge already present
+        let _ = self.graph.add_weighted_edge(from, to, weight, kind);
+    }
+}
+
+/// Token of the DOT language
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Id(String, bool), // identifier, number or string, and whether it was quoted
+    Arrow,            // ->
+    Line,             // --
+    Symbol(char),
+}
+
+// Split a DOT source into tokens with their line numbers, dropping the comments
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, DataStructureError> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut characters = text.chars().peekable();
+    let mut line_start = true;
+
+    while let Some(character) = characters.next() {
+        match character {
+            '\n' => {
+                line += 1;
+                line_start = true;
+                continue;
+            }
+            _ if character.is_whitespace() => continue,
+            // Preprocessor output lines and line comments
+            '#' if line_start => while characters.next_if(|&next| next != '\n').is_some() {},
+            '/' if characters.next_if_eq(&'/').is_some() => {
+                while characters.next_if(|&next| next != '\n').is_some() {}
+            }
+            '/' if characters.next_if_eq(&'*').is_some() => {
+                let start = line;
+                let mut previous = ' ';
+                loop {
+                    match characters.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(next) => {
+                            line += usize::from(next == '\n');
+                            previous = next;
+                        }
+                        None => return Err(invalid(start, "unterminated comment")),
+                    }
+                }
+            }
+            '"' => {
+                let start = line;
+                let mut id = String::new();
+                loop {
+                    match characters.next() {
+                        Some('"') => break,
+                        Some('\\')
+                            if characters
+                                .peek()
+                                .is_some_and(|&next| next == '"' || next == '\\') =>
+                        {
+                            id.push(characters.next().unwrap());
+                        }
+                        Some(next) => {
+                            line += usize::from(next == '\n');
+                            id.push(next);
+                        }
+                        None => return Err(invalid(start, "unterminated string")),
+                    }
+                }
+                tokens.push((Token::Id(id, true), start));
+            }
+            '-' if characters.next_if_eq(&'>').is_some() => tokens.push((Token::Arrow, line)),
+            '-' if characters.next_if_eq(&'-').is_some() => tokens.push((Token::Line, line)),
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' | ':' => {
+                tokens.push((Token::Symbol(character), line))
+            }
+            // Numerals
+            '-' | '.' | '0'..='9' => {
+                let mut id = character.to_string();
+                while let Some(next) =
+                    characters.next_if(|next| next.is_ascii_digit() || *next == '.')
+                {
+                    id.push(next);
+                }
+                tokens.push((Token::Id(id, false), line));
+            }
+            _ if character.is_alphabetic() || character == '_' || !character.is_ascii() => {
+                let mut id = character.to_string();
+                while let Some(next) = characters
+                    .next_if(|next| next.is_alphanumeric() || *next == '_' || !next.is_ascii())
+                {
+                    id.push(next);
+                }
+                tokens.push((Token::Id(id, false), line));
+            }
+            _ => return Err(invalid(line, "unexpected character")),
+        }
+        line_start = false;
+    }
+    Ok(tokens)
+}
+
+/// Cursor over the tokens of a DOT source
+struct DotParser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    last_line: usize, // line reported when the source ends too early
+}
+
+impl DotParser {
+    fn peek(&self) -> Option<&(Token, usize)> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<(Token, usize), DataStructureError> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or(invalid(self.last_line, "unexpected end of the graph"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    // Error at the current token
+    fn error(&self, reason: &'static str) -> DataStructureError {
+        invalid(
+            self.peek().map_or(self.last_line, |(_, line)| *line),
+            reason,
+        )
+    }
+
+    // Check if the current token is an unquoted keyword, in any case
+    fn keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some((Token::Id(id, false), _)) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), DataStructureError> {
+        match self.next()? {
+            (token, _) if token == expected => Ok(()),
+            (_, line) => Err(invalid(line, "unexpected token")),
+        }
+    }
+
+    fn id(&mut self) -> Result<(String, usize), DataStructureError> {
+        match self.next()? {
+            (Token::Id(id, _), line) => Ok((id, line)),
+            (_, line) => Err(invalid(line, "expected an ID")),
+        }
+    }
+
+    // Attribute lists following a statement, as name and value pairs
+    fn attributes(&mut self) -> Result<Vec<(String, String)>, DataStructureError> {
+        let mut attributes = Vec::new();
+        while self
+            .peek()
+            .is_some_and(|(token, _)| *token == Token::Symbol('['))
+        {
+            self.position += 1;
+            loop {
+                match self.next()? {
+                    (Token::Symbol(']'), _) => break,
+                    (Token::Symbol(',' | ';'), _) => continue,
+                    (Token::Id(name, _), _) => {
+                        self.expect(Token::Symbol('='))?;
+                        let (value, _) = self.id()?;
+                        attributes.push((name, value));
+                    }
+                    (_, line) => return Err(invalid(line, "expected an attribute")),
+                }
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+// Escape the characters with a meaning in XML text and attribute values
+#[cfg(feature = "graphml")]
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+// Replace the entity and character references of XML text
+#[cfg(feature = "graphml")]
+fn unescape_xml(text: &str, line: usize) -> Result<String, DataStructureError> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or(invalid(line, "unterminated reference"))?;
+        let reference = &rest[start + 1..start + end];
+        let character = match reference {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match reference.strip_prefix('#') {
+                Some(code) => match code.strip_prefix('x') {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => code.parse().ok(),
+                }
+                .and_then(char::from_u32),
+                None => None,
+            },
+        };
+        unescaped.push(character.ok_or(invalid(line, "unknown reference"))?);
+        rest = &rest[start + end + 1..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+/// Event of an XML document
+#[cfg(feature = "graphml")]
+#[derive(Debug, Clone, PartialEq)]
+enum Xml {
+    Start {
+        name: String,
+        attributes: Vec<(String, String)>,
+        empty: bool, // written as <name/>, with no end tag
+    },
+    End(String),
+    Text(String),
+}
+
+// Split an XML document into events with their line numbers, dropping the declaration, comments and doctype
+#[cfg(feature = "graphml")]
+fn xml_events(text: &str) -> Result<Vec<(Xml, usize)>, DataStructureError> {
+    let mut events = Vec::new();
+    let mut line = 1;
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let start = line;
+        if !rest.starts_with('<') {
+            let length = rest.find('<').unwrap_or(rest.len());
+            let (text, tail) = rest.split_at(length);
+            line += text.matches('\n').count();
+            rest = tail;
+            events.push((Xml::Text(unescape_xml(text, start)?), start));
+        } else if rest.starts_with("<?") {
+            take_markup(&mut rest, &mut line, "?>", "unterminated declaration")?;
+        } else if rest.starts_with("<!--") {
+            take_markup(&mut rest, &mut line, "-->", "unterminated comment")?;
+        } else if let Some(tail) = rest.strip_prefix("<![CDATA[") {
+            rest = tail;
+            let text = take_markup(&mut rest, &mut line, "]]>", "unterminated CDATA section")?;
+            events.push((Xml::Text(text.to_string()), start));
+        } else if rest.starts_with("<!") {
+            take_markup(&mut rest, &mut line, ">", "unterminated declaration")?;
+        } else if let Some(tail) = rest.strip_prefix("</") {
+            rest = tail;
+            let name = take_markup(&mut rest, &mut line, ">", "unterminated tag")?;
+            events.push((Xml::End(name.trim().to_string()), start));
+        } else {
+            rest = &rest[1..];
+            let tag = take_markup(&mut rest, &mut line, ">", "unterminated tag")?;
+            let (tag, empty) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            let (name, attributes) = parse_tag(tag, start)?;
+            events.push((
+                Xml::Start {
+                    name,
+                    attributes,
+                    empty,
+                },
+                start,
+            ));
+        }
+    }
+    Ok(events)
+}
+
+// Take the input up to the end of a markup, counting its lines, and return what comes before the end
+#[cfg(feature = "graphml")]
+fn take_markup<'a>(
+    rest: &mut &'a str,
+    line: &mut usize,
+    end: &str,
+    reason: &'static str,
+) -> Result<&'a str, DataStructureError> {
+    let length = rest.find(end).ok_or(invalid(*line, reason))?;
+    let (taken, tail) = rest.split_at(length + end.len());
+    *line += taken.matches('\n').count();
+    *rest = tail;
+    Ok(&taken[..length])
+}
+
+// Name and attributes of a start tag, given without its angle brackets
+#[cfg(feature = "graphml")]
+fn parse_tag(
+    tag: &str,
+    line: usize,
+) -> Result<(String, Vec<(String, String)>), DataStructureError> {
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let (name, mut rest) = tag.split_at(name_end);
+    if name.is_empty() {
+        return Err(invalid(line, "expected an element name"));
+    }
+
+    let mut attributes = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        let (key, value) = rest
+            .split_once('=')
+            .ok_or(invalid(line, "expected an attribute"))?;
+        let value = value.trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|quote| *quote == '"' || *quote == '\'')
+            .ok_or(invalid(line, "expected a quoted value"))?;
+        let (value, tail) = value[1..]
+            .split_once(quote)
+            .ok_or(invalid(line, "unterminated value"))?;
+        attributes.push((key.trim().to_string(), unescape_xml(value, line)?));
+        rest = tail;
+    }
+    Ok((name.to_string(), attributes))
+}
+
+/// GraphML document being read
+#[cfg(feature = "graphml")]
+struct GraphmlReader<T, W> {
+    builder: Builder<T, W>,
+    // Names of the elements not closed yet
+    open: Vec<String>,
+    // Id of the key holding the weights of the edges, its default, and whether it is the key element open
+    weight_key: Option<String>,
+    weight_default: Option<String>,
+    in_weight_key: bool,
+    // Default kind of the edges, set by the graph element
+    kind: Option<EdgeKind>,
+    // Edge being read, with its weight once read
+    edge: Option<(usize, usize, EdgeKind, Option<String>)>,
+    // Text of the weight or of the default weight being read
+    captured: Option<String>,
+}
+
+#[cfg(feature = "graphml")]
+impl<T: FromStr, W: TextWeight + Clone> GraphmlReader<T, W> {
+    // Open an element
+    fn start(
+        &mut self,
+        name: String,
+        attributes: &[(String, String)],
+        line: usize,
+    ) -> Result<(), DataStructureError> {
+        let attribute = |name: &str| {
+            attributes
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        let required = |name: &str| attribute(name).ok_or(invalid(line, "missing attribute"));
+
+        match (self.open.last().map(String::as_str), name.as_str()) {
+            (None, "graphml") => {}
+            (None, _) => return Err(invalid(line, "expected a graphml element")),
+            (Some("graphml"), "key") => {
+                let edges = matches!(attribute("for"), Some("edge" | "all"));
+                self.in_weight_key = edges && attribute("attr.name") == Some("weight");
+                if self.in_weight_key {
+                    self.weight_key = Some(required("id")?.to_string());
+                    self.weight_default = None;
+                }
+            }
+            (Some("key"), "default") if self.in_weight_key => {
+                self.captured = Some(String::new());
+            }
+            (Some("graphml"), "graph") => {
+                if self.kind.is_some() {
+                    return Err(invalid(line, "several graphs are not supported"));
+                }
+                self.kind = Some(match required("edgedefault")? {
+                    "directed" => EdgeKind::Directed,
+                    "undirected" => EdgeKind::Undirected,
+                    _ => return Err(invalid(line, "invalid edgedefault")),
+                });
+            }
+            (Some("graph"), "node") => {
+                self.builder.vertex(required("id")?, line)?;
+            }
+            (Some("graph"), "edge") => {
+                let from = self.builder.vertex(required("source")?, line)?;
+                let to = self.builder.vertex(required("target")?, line)?;
+                let kind = match attribute("directed") {
+                    None => self.kind.unwrap(),
+                    Some("true") => EdgeKind::Directed,
+                    Some("false") => EdgeKind::Undirected,
+                    Some(_) => return Err(invalid(line, "invalid directed attribute")),
+                };
+                self.edge = Some((from, to, kind, None));
+            }
+            (Some("edge"), "data")
+                if self.edge.is_some()
+                    && self.weight_key.is_some()
+                    && attribute("key") == self.weight_key.as_deref() =>
+            {
+                self.captured = Some(String::new());
+            }
+            (Some("node" | "edge"), "graph") => {
+                return Err(invalid(line, "nested graphs are not supported"))
+            }
+            (Some("graph"), "hyperedge") => {
+                return Err(invalid(line, "hyperedges are not supported"))
+            }
+            (Some("node"), "port") => return Err(invalid(line, "ports are not supported")),
+            // Descriptions, the data of the vertices and the elements of extensions are skipped
+            _ => {}
+        }
+        self.open.push(name);
+        Ok(())
+    }
+
+    // Close the innermost open element
+    fn end(&mut self, line: usize) -> Result<(), DataStructureError> {
+        let name = self.open.pop().unwrap();
+        match (self.open.last().map(String::as_str), name.as_str()) {
+            (Some("key"), "default") => {
+                if let Some(text) = self.captured.take() {
+                    self.weight_default = Some(text);
+                }
+            }
+            (Some("edge"), "data") => {
+                if let Some(text) = self.captured.take() {
+                    self.edge.as_mut().unwrap().3 = Some(text);
+                }
+            }
+            (_, "key") => self.in_weight_key = false,
+            (Some("graph"), "edge") => {
+                let (from, to, kind, weight) = self.edge.take().unwrap();
+                let weight = weight.or_else(|| self.weight_default.clone());
+                let weight = W::from_text(weight.as_deref().map(str::trim))
+                    .ok_or(invalid(line, "invalid weight"))?;
+                self.builder.edge(from, to, weight, kind);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    // Random graph over labels that need quoting in DOT, with edges of both kinds
+    fn random_graph(rng: &mut Lcg, size: usize) -> Graph<String, i32> {
+        let mut graph = Graph::weighted();
+        for vertex in 0..size {
+            graph.add_vertex(format!("v{}\\\"é{}", vertex, vertex * 7));
+        }
+        for _ in 0..size * 2 {
+            let seed = rng.next_u64();
+            let kind = if (seed >> 60).is_multiple_of(2) {
+                EdgeKind::Undirected
+            } else {
+                EdgeKind::Directed
+            };
+            let weight = (seed >> 20) as i32 % 100 - 50;
+            let _ = graph.add_weighted_edge(
+                (seed >> 33) as usize % size,
+                (seed >> 45) as usize % size,
+                weight,
+                kind,
+            );
+        }
+        graph
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let mut rng = Lcg::new(59);
+        for size in [1, 5, 30] {
+            let graph = random_graph(&mut rng, size);
+            let edges = to_edge_list(&graph);
+
+            // DOT keeps every vertex and weight
+            let copy: Graph<String, i32> = from_dot(&to_dot(&graph)).unwrap();
+            assert_eq!(format!("{:?}", copy), format!("{:?}", graph));
+            assert_eq!(to_edge_list(&copy), edges);
+
+            // Labels with quotes can't go through the whitespace separated formats, plain ones can
+            let plain: Graph<String, i32> =
+                from_dot(&to_dot(&graph).replace("\\\\\\\"", "")).unwrap();
+            // Vertices are numbered by first appearance, so only the set of lines is kept
+            let read: Graph<String, i32> =
+                from_edge_list(&to_edge_list(&plain), EdgeKind::Directed).unwrap();
+            let sorted_lines = |text: String| {
+                let mut lines: Vec<String> = text.lines().map(String::from).collect();
+                lines.sort();
+                lines
+            };
+            assert_eq!(
+                sorted_lines(to_edge_list(&read)),
+                sorted_lines(to_edge_list(&plain))
+            );
+
+            // Adjacency lists keep isolated vertices but not the weights
+            let adjacency: Graph<String> =
+                from_adjacency_list(&to_adjacency_list(&plain), EdgeKind::Directed).unwrap();
+            assert_eq!(adjacency.len(), plain.len());
+            let unweighted: String = to_edge_list(&plain)
+                .lines()
+                .map(|line| line.rsplit_once(' ').unwrap().0.to_string() + "\n")
+                .collect();
+            assert_eq!(
+                sorted_lines(to_edge_list(&adjacency)),
+                sorted_lines(unweighted)
+            );
+        }
+
+        // Removed vertices leave no trace in the text, and the others keep their order
+        let mut graph = random_graph(&mut rng, 12);
+        graph.retain_nodes(|vertex, _| vertex % 3 != 1);
+        let copy: Graph<String, i32> = from_dot(&to_dot(&graph)).unwrap();
+        assert_eq!(copy.len(), 8);
+        assert_eq!(to_edge_list(&copy), to_edge_list(&graph));
+    }
+
+    #[test]
+    fn test_dot_syntax() {
+        let dot = "/* header */\n# 1 \"generated\"\nstrict DiGraph \"g\" {\n  rankdir = LR\n  edge [color=blue]; a\n  a -> b -> c [weight=-1.5];\n  \"multi\nline\" -> -.5 [label=\"x\", weight=2]\n}\n";
+        let graph: Graph<String, f64> = from_dot(dot).unwrap();
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph.value(3), Some("multi\nline".to_string()));
+        assert_eq!(graph.value(4), Some("-.5".to_string()));
+        assert_eq!(graph.edge_weight(1, 2), Some(-1.5));
+        assert_eq!(graph.edge_weight(3, 4), Some(2.0));
+
+        // Unweighted graphs ignore the weights
+        let unweighted: Graph<String> = from_dot("graph { a -- b [weight=3] }").unwrap();
+        assert!(unweighted.has_edge(1, 0));
+
+        let errors = [
+            (
+                "graph { a -> b }",
+                1,
+                "edge operator doesn't match the graph",
+            ),
+            (
+                "digraph {\n a -> b [weight=1]\n",
+                2,
+                "unexpected end of the graph",
+            ),
+            (
+                "digraph { subgraph { a } }",
+                1,
+                "subgraphs are not supported",
+            ),
+            ("digraph { a:n -> b }", 1, "ports are not supported"),
+            ("digraph {\n a -> b [weight=x] }", 2, "invalid weight"),
+            ("digraph { a -> b [weight] }", 1, "unexpected token"),
+            ("tree { }", 1, "expected graph or digraph"),
+            ("digraph { } }", 1, "content after the graph"),
+            ("digraph {\n \"a }", 2, "unterminated string"),
+        ];
+        for (dot, line, reason) in errors {
+            assert_eq!(
+                from_dot::<String, u32>(dot).err(),
+                Some(DataStructureError::InvalidLine { line, reason }),
+                "{}",
+                dot
+            );
+        }
+        assert_eq!(
+            from_dot::<u32, u32>("digraph { 1 -> x }").err(),
+            Some(invalid(1, "invalid vertex label"))
+        );
+    }
+
+    #[test]
+    fn test_list_syntax() {
+        let text = "\n# comment\na b 1\nb a 2\na b 3\n";
+        let directed: Graph<String, u8> = from_edge_list(text, EdgeKind::Directed).unwrap();
+        assert_eq!(directed.edge_weight(0, 1), Some(1));
+        assert_eq!(directed.edge_weight(1, 0), Some(2));
+
+        // Listed again the other way, an undirected edge is already present
+        let undirected: Graph<String, u8> = from_edge_list(text, EdgeKind::Undirected).unwrap();
+        assert_eq!(undirected.edge_weight(1, 0), Some(1));
+
+        assert_eq!(
+            from_edge_list::<String, u8>("a b\n", EdgeKind::Directed).err(),
+            Some(invalid(1, "invalid weight"))
+        );
+        assert_eq!(
+            from_edge_list::<String, ()>("a\n", EdgeKind::Directed).err(),
+            Some(invalid(1, "expected two vertices and a weight"))
+        );
+        assert_eq!(
+            from_adjacency_list::<u8, ()>("1 2\n300\n", EdgeKind::Directed).err(),
+            Some(invalid(2, "invalid vertex label"))
+        );
+    }
+
+    #[cfg(feature = "graphml")]
+    #[test]
+    fn test_graphml_round_trips() {
+        let mut rng = Lcg::new(61);
+        for size in [1, 5, 30] {
+            // Labels with quotes and backslashes are escaped
+            let graph = random_graph(&mut rng, size);
+            let copy: Graph<String, i32> = from_graphml(&to_graphml(&graph)).unwrap();
+            assert_eq!(format!("{:?}", copy), format!("{:?}", graph));
+            assert_eq!(to_edge_list(&copy), to_edge_list(&graph));
+            assert_eq!(to_graphml(&copy), to_graphml(&graph));
+
+            // Unweighted graphs have no key, float weights are doubles
+            let mut unweighted: Graph<String> = Graph::new();
+            for vertex in graph.vertices() {
+                unweighted.add_vertex(graph.value(vertex).unwrap());
+            }
+            for edge in graph.edges() {
+                unweighted
+                    .add_edge(edge.from, edge.to, EdgeKind::Directed)
+                    .unwrap();
+            }
+            let text = to_graphml(&unweighted);
+            assert!(!text.contains("<key"));
+            let read: Graph<String> = from_graphml(&text).unwrap();
+            assert_eq!(format!("{:?}", read), format!("{:?}", unweighted));
+
+            let mut floats: Graph<String, f64> = Graph::weighted();
+            for vertex in graph.vertices() {
+                floats.add_vertex(graph.value(vertex).unwrap());
+            }
+            for edge in graph.edges() {
+                let weight = graph.edge_weight(edge.from, edge.to).unwrap();
+                floats
+                    .add_weighted_edge(edge.from, edge.to, weight as f64 / 4.0, EdgeKind::Directed)
+                    .unwrap();
+            }
+            let text = to_graphml(&floats);
+            let read: Graph<String, f64> = from_graphml(&text).unwrap();
+            assert_eq!(to_edge_list(&read), to_edge_list(&floats));
+        }
+
+        // Removed vertices leave no trace, and the others keep their order
+        let mut graph = random_graph(&mut rng, 12);
+        graph.retain_nodes(|vertex, _| vertex % 3 != 1);
+        let copy: Graph<String, i32> = from_graphml(&to_graphml(&graph)).unwrap();
+        assert_eq!(copy.len(), 8);
+        assert_eq!(to_edge_list(&copy), to_edge_list(&graph));
+    }
+
+    #[cfg(feature = "graphml")]
+    #[test]
+    fn test_graphml_syntax() {
+        let graphml = r#"<?xml version='1.0' encoding='utf-8'?>
+<!DOCTYPE graphml>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <!-- keys of other attributes are skipped -->
+  <key id="d1" for="node" attr.name="color" attr.type="string"><default>red</default></key>
+  <key id="d0" for="edge" attr.name="weight" attr.type="double">
+    <default>0.5</default>
+  </key>
+  <graph edgedefault="undirected">
+    <node id="a &amp; b"><data key="d1">blue</data></node>
+    <node id='&#99;'/>
+    <edge source="a &amp; b" target="c"><data key="d0"> 2 </data></edge>
+    <edge source="c" target="&lt;d&gt;" directed="true"/>
+    <edge source="e" target="c"><desc>no weight</desc><data key="d0"><![CDATA[-1.5]]></data></edge>
+  </graph>
+</graphml>
+"#;
+        let graph: Graph<String, f64> = from_graphml(graphml).unwrap();
+        assert_eq!(graph.len(), 4);
+        assert_eq!(graph.value(0), Some("a & b".to_string()));
+        assert_eq!(graph.value(2), Some("<d>".to_string()));
+        assert_eq!(graph.edge_weight(1, 0), Some(2.0));
+        assert_eq!(graph.edge_weight(1, 2), Some(0.5));
+        assert_eq!(graph.edge_weight(2, 1), None);
+        assert_eq!(graph.edge_weight(1, 3), Some(-1.5));
+
+        // Without a default, numeric weights can't be missing
+        let unweighted = graphml.replace("<default>0.5</default>", "");
+        assert_eq!(
+            from_graphml::<String, f64>(&unweighted).err(),
+            Some(invalid(13, "invalid weight"))
+        );
+        assert!(from_graphml::<String, ()>(&unweighted).is_ok());
+
+        let errors = [
+            ("<graph edgedefault=\"directed\"/>", 1, "expected a graphml element"),
+            ("<graphml>\n<graph/>\n</graphml>", 2, "missing attribute"),
+            (
+                "<graphml><graph edgedefault=\"directed\"></graphml>",
+                1,
+                "mismatched end tag",
+            ),
+            (
+                "<graphml>\n<graph edgedefault=\"directed\">\n<node id=\"a\"/>\n",
+                3,
+                "unexpected end of the document",
+            ),
+            (
+                "<graphml><graph edgedefault=\"directed\"/><graph edgedefault=\"directed\"/></graphml>",
+                1,
+                "several graphs are not supported",
+            ),
+            (
+                "<graphml><graph edgedefault=\"directed\"><node id=\"a\"><graph edgedefault=\"directed\"/></node></graph></graphml>",
+                1,
+                "nested graphs are not supported",
+            ),
+            (
+                "<graphml><graph edgedefault=\"directed\"><hyperedge/></graph></graphml>",
+                1,
+                "hyperedges are not supported",
+            ),
+            (
+                "<graphml><graph edgedefault=\"directed\"><node id=\"a\"><port name=\"p\"/></node></graph></graphml>",
+                1,
+                "ports are not supported",
+            ),
+            (
+                "<graphml><graph edgedefault=\"mixed\"/></graphml>",
+                1,
+                "invalid edgedefault",
+            ),
+            (
+                "<graphml><graph edgedefault=\"directed\"><node id=\"&nbsp;\"/></graph></graphml>",
+                1,
+                "unknown reference",
+            ),
+            ("<graphml>\n<!-- open", 2, "unterminated comment"),
+            ("<graphml><graph edgedefault=directed/></graphml>", 1, "expected a quoted value"),
+            ("<graphml\n", 1, "unterminated tag"),
+        ];
+        for (graphml, line, reason) in errors {
+            assert_eq!(
+                from_graphml::<String, u32>(graphml).err(),
+                Some(DataStructureError::InvalidLine { line, reason }),
+                "{}",
+                graphml
+            );
+        }
+        assert_eq!(
+            from_graphml::<u32, u32>(
+                "<graphml><graph edgedefault=\"directed\"><node id=\"x\"/></graph></graphml>"
+            )
+            .err(),
+            Some(invalid(1, "invalid vertex label"))
+        );
+    }
+}
@@ -0,0 +1,412 @@
+//! This module implements a disjoint-set forest (union-find), which keeps elements partitioned into sets that can only
+//! be merged. Each set is a tree whose root represents it: `find` climbs to the root and `union` hangs one root under
+//! the other. It is the building block of Kruskal's minimum spanning tree, of connected components over a stream of
+//! edges, and of any grouping that only ever merges, such as the vertices of a `Graph` identified by their index.
+//!
+//! # Performance
+//! - O(α(n)) amortized for `find`, `union` and `same_set`, α being the inverse Ackermann function, below 5 in practice
+//! - O(1) expected for `make_set`
+//!
+//! # Implementation Details
+//! - The elements are numbered in insertion order through a `HashMap`, and the forest is stored as parallel `Vec`s of
+//!   parents, ranks and set sizes indexed by those numbers.
+//! - `union` hangs the root of lower rank under the other one, so trees stay O(log n) high even without compression.
+//! - `find` points every node it climbs through directly at the root, which is why it takes `&mut self`.
+//!
+//! # Usage
+//! ```
+//! use data_structures::graph::union_find::DisjointSet;
+//!
+//! // Kruskal's algorithm: take the lightest edges that join two different trees
+//! let mut edges = vec![(7, "a", "b"), (5, "a", "c"), (8, "b", "c"), (9, "b", "d"), (15, "c", "d")];
+//! edges.sort();
+//!
+//! let mut forest: DisjointSet<&str> = ["a", "b", "c", "d"].into_iter().collect();
+//! let tree: Vec<_> = edges
+//!     .into_iter()
+//!     .filter(|&(_, from, to)| forest.union(&from, &to) == Some(true))
+//!     .collect();
+//!
+//! assert_eq!(tree, vec![(5, "a", "c"), (7, "a", "b"), (9, "b", "d")]);
+//! assert_eq!(forest.set_count(), 1);
+//! ```
+//!
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/// Struct representing a partition of elements into disjoint sets
+///
+#[derive(Clone)]
+pub struct DisjointSet<T> {
+    elements: Vec<T>,
+    indices: HashMap<T, usize>,
+
+    parents: Vec<usize>, // a root is its own parent
+    ranks: Vec<u8>,      // upper bound of the height of the tree below a root
+    sizes: Vec<usize>,   // number of elements of the set, kept at its root
+
+    sets: usize,
+}
+
+impl<T: Hash + Eq + Clone> DisjointSet<T> {
+    /// Create a new empty partition
+    /// # Returns
+    /// A new DisjointSet instance
+    /// # Example
+    /// ```
+    /// use data_structures::graph::union_find::DisjointSet;
+    ///
+    /// let sets: DisjointSet<usize> = DisjointSet::new();
+    /// assert!(sets.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        DisjointSet {
+            elements: Vec::new(),
+            indices: HashMap::new(),
+            parents: Vec::new(),
+            ranks: Vec::new(),
+            sizes: Vec::new(),
+            sets: 0,
+        }
+    }
+
+    /// Get the number of elements
+    /// # Returns
+    /// The number of elements in every set
+    /// # Example
+    /// ```
+    /// use data_structures::graph::union_find::DisjointSet;
+    ///
+    /// let sets: DisjointSet<u32> = (0..10).collect();
+    /// assert_eq!(sets.len(), 10);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Check if the partition is empty
+    /// # Returns
+    /// True if there are no elements
+    /// # Example
+    /// ```
+    /// use data_structures::graph::union_find::DisjointSet;
+    ///
+    /// let mut sets = DisjointSet::new();
+    /// sets.make_set('a');
+    /// assert!(!sets.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Get the number of disjoint sets
+    /// # Returns
+    /// The number of sets, which goes down by one with every merging union
+    /// # Example
+    /// ```
+    /// use data_structures::graph::union_find::DisjointSet;
+    ///
+    /// let mut sets: DisjointSet<u32> = (0..10).collect();
+    /// sets.union(&1, &2);
+    /// assert_eq!(sets.set_count(), 9);
+    /// ```
+    pub fn set_count(&self) -> usize {
+        self.sets
+    }
+
+    /// Add an element in a set of its own
+    /// # Arguments
+    /// * `value`: The element to add
+    /// # Returns
+    /// True if the element was added, false if it was already known, in which case its set is unchanged
+    /// # Example
+    /// ```
+    /// use data_structures::graph::union_find::DisjointSet;
+    ///
+    /// let mut sets = DisjointSet::new();
+    /// assert!(sets.make_set("x"));
+    /// assert!(!sets.make_set("x"));
+    /// ```
+    pub fn make_set(&mut self, value: T) -> bool {
+        if self.indices.contains_key(&value) {
+            return false;
+        }
+
+        let index = self.elements.len();
+        self.indices.insert(value.clone(), index);
+        self.elements.push(value);
+        self.parents.push(index);
+        self.ranks.push(0);
+        self.sizes.push(1);
+        self.sets += 1;
+        true
+    }
+
+    /// Find the representative of the set of an element, compressing the path to it
+    /// # Arguments
+    /// * `value`: The element to look up
+    /// # Returns
+    /// A reference to the representative, the same for every element of the set, or None if the element is unknown
+    /// # Example
+    /// ```
+    /// use data_structures::graph::union_find::DisjointSet;
+    ///
+    /// let mut sets: DisjointSet<u32> = (0..4).collect();
+    /// sets.union(&0, &1);
+    ///
+    /// let representative = *sets.find(&0).unwrap();
+    /// assert_eq!(sets.find(&1), Some(&representative));
+    /// assert_eq!(sets.find(&2), Some(&2));
+    /// assert_eq!(sets.find(&9), None);
+    /// ```
+    pub fn find(&mut self, value: &T) -> Option<&T> {
+        let index = *self.indices.get(value)?;
+        let root = self.root(index);
+        Some(&self.elements[root])
+    }
+
+    /// Merge the sets of two elements
+    /// # Arguments
+    /// * `a`: An element of the first set
+    /// * `b`: An element of the second set
+    /// # Returns
+    /// Some(true) if two sets were merged, Some(false) if the elements were already in the same set, None if either
+    /// element is unknown
+    /// # Example
+    /// ```
+    /// use data_structures::graph::union_find::DisjointSet;
+    ///
+    /// let mut sets: DisjointSet<char> = "abc".chars().collect();
+    /// assert_eq!(sets.union(&'a', &'b'), Some(true));
+    /// assert_eq!(sets.union(&'b', &'a'), Some(false));
+    /// assert_eq!(sets.union(&'a', &'z'), None);
+    /// ```
+    pub fn union(&mut self, a: &T, b: &T) -> Option<bool> {
+        let a = *self.indices.get(a)?;
+        let b = *self.indices.get(b)?;
+        let (a, b) = (self.root(a), self.root(b));
+        if a == b {
+            return Some(false);
+        }
+
+        // The shorter tree goes under the taller one, equal heights make the new root one level taller
+        let (root, child) = if self.ranks[a] < self.ranks[b] {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        if self.ranks[root] == self.ranks[child] {
+            self.ranks[root] += 1;
+        }
+        self.parents[child] = root;
+        self.sizes[root] += self.sizes[child];
+        self.sets -= 1;
+        Some(true)
+    }
+
+    /// Check if two elements are in the same set
+    /// # Arguments
+    /// * `a`: The first element
+    /// * `b`: The second element
+    /// # Returns
+    /// True if both elements are known and in the same set
+    /// # Example
+    /// ```
+    /// use data_structures::graph::union_find::DisjointSet;
+    ///
+    /// let mut sets: DisjointSet<u32> = (0..5).collect();
+    /// sets.union(&0, &1);
+    /// sets.union(&1, &2);
+    ///
+    /// assert!(sets.same_set(&0, &2));
+    /// assert!(!sets.same_set(&0, &3));
+    /// ```
+    pub fn same_set(&mut self, a: &T, b: &T) -> bool {
+        match (self.indices.get(a), self.indices.get(b)) {
+            (Some(&a), Some(&b)) => self.root(a) == self.root(b),
+            _ => false,
+        }
+    }
+
+    /// Get the number of elements in the set of an element
+    /// # Arguments
+    /// * `value`: The element to look up
+    /// # Returns
+    /// The size of its set, or None if the element is unknown
+    /// # Example
+    /// ```
+    /// use data_structures::graph::union_find::DisjointSet;
+    ///
+    /// let mut sets: DisjointSet<u32> = (0..5).collect();
+    /// sets.union(&3, &4);
+    /// assert_eq!(sets.set_size(&4), Some(2));
+    /// assert_eq!(sets.set_size(&0), Some(1));
+    /// ```
+    pub fn set_size(&mut self, value: &T) -> Option<usize> {
+        let index = *self.indices.get(value)?;
+        let root = self.root(index);
+        Some(self.sizes[root])
+    }
+
+    /// Get the elements grouped by set
+    /// # Returns
+    /// One `Vec` per set, the sets ordered by their first element and each set in insertion order
+    /// # Example
+    /// ```
+    /// use data_structures::graph::union_find::DisjointSet;
+    ///
+    /// let mut sets: DisjointSet<u32> = (0..5).collect();
+    /// sets.union(&0, &3);
+    /// sets.union(&4, &1);
+    /// assert_eq!(sets.groups(), vec![vec![0, 3], vec![1, 4], vec![2]]);
+    /// ```
+    pub fn groups(&mut self) -> Vec<Vec<T>> {
+        let mut groups: Vec<Vec<T>> = Vec::new();
+        let mut group_of_root: HashMap<usize, usize> = HashMap::new();
+        for index in 0..self.elements.len() {
+            let root = self.root(index);
+            let group = *group_of_root.entry(root).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push(self.elements[index].clone());
+        }
+        groups
+    }
+
+    // Find the root of an element, pointing every node of the path at it
+    fn root(&mut self, index: usize) -> usize {
+        let mut root = index;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        let mut current = index;
+        while current != root {
+            current = std::mem::replace(&mut self.parents[current], root);
+        }
+        root
+    }
+}
+
+impl<T: Hash + Eq + Clone> Default for DisjointSet<T> {
+    /// Creates an empty partition
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq + Clone> FromIterator<T> for DisjointSet<T> {
+    /// Builds a partition with every element in a set of its own
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut sets = DisjointSet::new();
+        sets.extend(iter);
+        sets
+    }
+}
+
+impl<T: Hash + Eq + Clone> Extend<T> for DisjointSet<T> {
+    /// Adds every new element in a set of its own
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.make_set(value);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DisjointSet<T> {
+    /// Prints the number of elements and sets, the forest itself changes with every lookup
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DisjointSet")
+            .field("len", &self.elements.len())
+            .field("sets", &self.sets)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::vertex_graph::{EdgeKind, Graph};
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_disjoint_set_against_labels() {
+        // Naive partition: every element carries the label of its set, relabelled on each merge
+        let mut sets: DisjointSet<u32> = (0..300).collect();
+        let mut labels: Vec<u32> = (0..300).collect();
+        let mut rng = Lcg::new(43);
+
+        for _ in 0..2_000 {
+            let seed = rng.next_u64();
+            let a = (seed >> 33) as u32 % 300;
+            let b = (seed >> 13) as u32 % 300;
+
+            let (label_a, label_b) = (labels[a as usize], labels[b as usize]);
+            if seed >> 63 == 0 {
+                labels
+                    .iter_mut()
+                    .filter(|label| **label == label_b)
+                    .for_each(|label| *label = label_a);
+                assert_eq!(sets.union(&a, &b), Some(label_a != label_b));
+            } else {
+                assert_eq!(sets.same_set(&a, &b), label_a == label_b);
+            }
+
+            let size = labels
+                .iter()
+                .filter(|&&label| label == labels[a as usize])
+                .count();
+            assert_eq!(sets.set_size(&a), Some(size));
+        }
+
+        let mut distinct = labels.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(sets.set_count(), distinct.len());
+        assert_eq!(sets.groups().len(), distinct.len());
+
+        // Union by rank bounds the rank by log2 of the set size
+        for index in 0..sets.len() {
+            if sets.parents[index] == index {
+                assert!(1usize << sets.ranks[index] <= sets.sizes[index]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kruskal_over_graph_vertices() {
+        // A ring of 6 vertices with a chord: the spanning tree drops the heaviest edge of each cycle
+        let mut graph: Graph<char, u32> = Graph::weighted();
+        let vertices: Vec<usize> = "abcdef".chars().map(|c| graph.add_vertex(c)).collect();
+        let mut edges = vec![];
+        for (index, weight) in [4, 1, 3, 9, 2, 8].into_iter().enumerate() {
+            edges.push((weight, vertices[index], vertices[(index + 1) % 6]));
+        }
+        edges.push((5, vertices[0], vertices[3]));
+        for &(weight, from, to) in &edges {
+            graph
+                .add_weighted_edge(from, to, weight, EdgeKind::Undirected)
+                .unwrap();
+        }
+
+        edges.sort();
+        let mut forest: DisjointSet<usize> = vertices.iter().copied().collect();
+        let mut tree = Graph::new();
+        for &vertex in &vertices {
+            tree.add_vertex(graph.value(vertex).unwrap());
+        }
+        let mut total = 0;
+        for (weight, from, to) in edges {
+            if forest.union(&from, &to) == Some(true) {
+                tree.add_edge(from, to, EdgeKind::Undirected).unwrap();
+                total += weight;
+            }
+        }
+
+        assert_eq!(total, 1 + 2 + 3 + 4 + 8);
+        assert_eq!(forest.set_count(), 1);
+        assert_eq!(tree.bfs(vertices[0]).unwrap().count(), 6);
+    }
+}
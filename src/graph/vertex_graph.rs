@@ -0,0 +1,1621 @@
+//! This module implements a graph whose vertices are `Vertex` nodes linked by their connections.
+//! Vertices are added with a value and identified by their index, edges can be directed or undirected and carry a
+//! weight, and the graph can be walked breadth first or depth first from any vertex. On weighted graphs, shortest
+//! paths are found with Dijkstra's algorithm, or with A* given a heuristic.
+//! Vertices and edges can be removed one by one or filtered with `retain_nodes` and `retain_edges`. The index of a
+//! removed vertex is never given to a new one and every edge added gets a new serial, so `NodeId` and `EdgeId` values
+//! stay valid across removals, never name a newer vertex or edge, and the graph can be changed while walking the ids
+//! listed by `vertices` and `edges`. `subgraph` copies the vertices and
+//! edges accepted by two filters into a new graph, and the graph is a `GraphView` for the algorithms of the crate.
+//!
+//! # Performance
+//! - O(1) for `add_vertex`, and for `add_edge`, `remove_edge` and `has_edge`, apart from formatting the edge name
+//! - O(V) for `remove_vertex`, which drops the edges pointing to the vertex, and O(V + E) for `retain_nodes`
+//! - O(V + E log E) for `retain_edges` and `subgraph`, which sort the edges of each vertex
+//! - O(d log d) for `neighbors` of a vertex of degree d, which are sorted
+//! - O(V + E log E) for a full traversal
+//! - O((V + E) log V) for `shortest_path`, and usually much less for `shortest_path_astar` with a good heuristic
+//!
+//! # Implementation Details
+//! - Each vertex holds a `Custom` connection named after the index of every vertex it has an edge to, as the trees of
+//!   the crate name their children. An undirected edge is a connection in each direction.
+//! - Neighbors are listed from the connection names and sorted, so traversals visit them in increasing index order
+//!   and are reproducible.
+//! - Edges are strong pointers and can form cycles, so the graph clears every vertex when dropped.
+//! - A removed vertex leaves an empty slot behind. Arrays indexed by vertex are sized by `vertex_bound`, which
+//!   counts the slots, while `len` counts the vertices left. Operations given a removed vertex fail with
+//!   `RemovedVertex` where a vertex past the bound gives `VertexOutOfBounds`.
+//! - Slots are never reused, so the slots and the arrays sized by `vertex_bound` grow with every vertex ever added.
+//!   A graph with a lot of churn is compacted by copying it into a new one with fresh indexes.
+//! - The serial of each edge is kept in a map by its ends, and the serials come from a counter that only grows, so an
+//!   edge removed and added again between the same vertices gets a new `EdgeId`.
+//! - Weights are stored by the vertexes with their connections. The weight type defaults to `()` for unweighted
+//!   graphs; shortest paths need weights that can be added and ordered, with `Default` as zero, such as the integers.
+//!
+//! # Usage
+//! ```
+//! use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+//!
+//! let mut graph = Graph::new();
+//! let a = graph.add_vertex("a");
+//! let b = graph.add_vertex("b");
+//! let c = graph.add_vertex("c");
+//! let d = graph.add_vertex("d");
+//!
+//! graph.add_edge(a, b, EdgeKind::Undirected).unwrap();
+//! graph.add_edge(a, c, EdgeKind::Directed).unwrap();
+//! graph.add_edge(b, d, EdgeKind::Directed).unwrap();
+//!
+//! assert_eq!(graph.bfs(a).unwrap().collect::<Vec<_>>(), vec![a, b, c, d]);
+//! assert_eq!(graph.dfs(a).unwrap().collect::<Vec<_>>(), vec![a, b, d, c]);
+//! assert_eq!(graph.dfs(c).unwrap().count(), 1);
+//!
+//! let mut roads: Graph<&str, u32> = Graph::weighted();
+//! let lisbon = roads.add_vertex("Lisbon");
+//! let coimbra = roads.add_vertex("Coimbra");
+//! let porto = roads.add_vertex("Porto");
+//! roads.add_weighted_edge(lisbon, porto, 320, EdgeKind::Undirected).unwrap();
+//! roads.add_weighted_edge(lisbon, coimbra, 205, EdgeKind::Undirected).unwrap();
+//! roads.add_weighted_edge(coimbra, porto, 110, EdgeKind::Undirected).unwrap();
+//!
+//! assert_eq!(roads.shortest_path(porto, lisbon).unwrap(), Some((315, vec![porto, coimbra, lisbon])));
+//!
+//! // Ids listed before a change are still valid after it
+//! for city in roads.vertices() {
+//!     if roads.neighbors(city).unwrap().count() == 1 {
+//!         roads.remove_vertex(city).unwrap();
+//!     }
+//! }
+//! roads.retain_edges(|_, &distance| distance < 300);
+//! assert_eq!(roads.vertices().collect::<Vec<_>>(), vec![lisbon, coimbra, porto]);
+//! assert_eq!(roads.shortest_path(porto, lisbon).unwrap(), Some((315, vec![porto, coimbra, lisbon])));
+//! ```
+//!
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::ops::Add;
+
+use crate::error::DataStructureError;
+use crate::graph::algorithms::best_first;
+use crate::graph::view::GraphView;
+use crate::linked_list::shared::Shared;
+use crate::linked_list::vertex::{PointerName, Vertex};
+use crate::visualize::topology::Topology;
+
+// Name of the pointer holding the edge to the vertex at the given index
+fn edge_pointer(to: usize) -> PointerName {
+    PointerName::Custom(format!("edge{}", to))
+}
+
+// Index of the vertex an edge pointer leads to, None for the other pointers
+fn edge_target(name: &PointerName) -> Option<usize> {
+    match name {
+        PointerName::Custom(name) => name.strip_prefix("edge")?.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Index of a vertex, never reused once the vertex is removed
+pub type NodeId = usize;
+
+/// Identifier of the edge from a vertex to another, valid until the edge or one of its vertices is removed
+/// An undirected edge is made of the two directed edges with swapped ends. The serial tells apart the edges added
+/// one after another between the same vertices, so the id of a removed edge never becomes valid again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeId {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub serial: u64,
+}
+
+/// Whether an edge goes one way or both ways
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    Directed,
+    Undirected,
+}
+
+/// Struct representing a graph of vertexes identified by their index, with edges weighted by `W`
+///
+pub struct Graph<T, W = ()> {
+    vertices: Vec<Option<Shared<Vertex<T, W>>>>,
+    len: usize,
+    serials: HashMap<(NodeId, NodeId), u64>,
+    next_serial: u64,
+}
+
+/// Iterator over the vertices reachable from a start vertex, in breadth first order
+///
+pub struct Bfs<'a, T, W = ()> {
+    graph: &'a Graph<T, W>,
+    queue: VecDeque<usize>,
+    visited: Vec<bool>,
+}
+
+/// Iterator over the vertices reachable from a start vertex, in depth first preorder
+///
+pub struct Dfs<'a, T, W = ()> {
+    graph: &'a Graph<T, W>,
+    stack: Vec<usize>,
+    visited: Vec<bool>,
+}
+
+impl<T> Graph<T> {
+    /// Create a new graph without vertices, whose edges carry no weight
+    ///
+    /// # Returns
+    /// A new Graph instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::Graph;
+    ///
+    /// let graph: Graph<u32> = Graph::new();
+    /// assert!(graph.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::weighted()
+    }
+}
+
+impl<T, W> Graph<T, W> {
+    /// Create a new graph without vertices, whose edges carry a weight of type `W`
+    ///
+    /// # Returns
+    /// A new Graph instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::Graph;
+    ///
+    /// let graph: Graph<&str, f64> = Graph::weighted();
+    /// assert!(graph.is_empty());
+    /// ```
+    pub fn weighted() -> Self {
+        Graph {
+            vertices: Vec::new(),
+            len: 0,
+            serials: HashMap::new(),
+            next_serial: 0,
+        }
+    }
+
+    /// Get the number of vertices
+    /// # Returns
+    /// The number of vertices in the graph
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::Graph;
+    ///
+    /// let mut graph = Graph::new();
+    /// graph.add_vertex(1);
+    /// assert_eq!(graph.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the graph has no vertices
+    /// # Returns
+    /// True if the graph is empty, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::Graph;
+    ///
+    /// let mut graph = Graph::new();
+    /// assert!(graph.is_empty());
+    ///
+    /// graph.add_vertex(1);
+    /// assert!(!graph.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of vertex indexes given so far, removed vertices included
+    /// # Returns
+    /// One more than the largest index of a vertex, the size of an array indexed by vertex
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::Graph;
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex(1);
+    /// graph.add_vertex(2);
+    /// graph.remove_vertex(a).unwrap();
+    ///
+    /// assert_eq!(graph.len(), 1);
+    /// assert_eq!(graph.vertex_bound(), 2);
+    /// ```
+    pub fn vertex_bound(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Check if a vertex exists
+    /// # Arguments
+    /// * `vertex`: The index of the vertex
+    /// # Returns
+    /// True if the vertex was added and not removed, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::Graph;
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex(1);
+    /// assert!(graph.contains_vertex(a));
+    ///
+    /// graph.remove_vertex(a).unwrap();
+    /// assert!(!graph.contains_vertex(a));
+    /// ```
+    pub fn contains_vertex(&self, vertex: NodeId) -> bool {
+        self.get_node(vertex).is_some()
+    }
+
+    /// Add a vertex without edges
+    /// # Arguments
+    /// * `value`: The value held by the vertex
+    /// # Returns
+    /// The index of the new vertex, which is the number of vertices added before it, removed ones included
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::Graph;
+    ///
+    /// let mut graph = Graph::new();
+    /// assert_eq!(graph.add_vertex("a"), 0);
+    /// assert_eq!(graph.add_vertex("b"), 1);
+    ///
+    /// graph.remove_vertex(1).unwrap();
+    /// assert_eq!(graph.add_vertex("c"), 2);
+    /// ```
+    pub fn add_vertex(&mut self, value: T) -> NodeId {
+        self.vertices.push(Some(Vertex::new_weighted(value)));
+        self.len += 1;
+        self.vertices.len() - 1
+    }
+
+    /// Remove a vertex with the edges from and to it
+    /// The index of the vertex is not given to any later vertex.
+    /// # Arguments
+    /// * `vertex`: The index of the vertex
+    /// # Returns
+    /// The value of the vertex, Err(DataStructureError::VertexOutOfBounds) if the vertex was never added, or
+    /// Err(DataStructureError::RemovedVertex) if it was already removed
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex("a");
+    /// let b = graph.add_vertex("b");
+    /// graph.add_edge(a, b, EdgeKind::Undirected).unwrap();
+    ///
+    /// assert_eq!(graph.remove_vertex(b), Ok("b"));
+    /// assert_eq!(graph.neighbors(a).unwrap().count(), 0);
+    /// assert_eq!(graph.remove_vertex(b), Err(DataStructureError::RemovedVertex(b)));
+    /// ```
+    pub fn remove_vertex(&mut self, vertex: NodeId) -> Result<T, DataStructureError> {
+        self.check_vertex(vertex)?;
+
+        for to in self.sorted_neighbors(vertex) {
+            self.serials.remove(&(vertex, to));
+        }
+        let node = self.vertices[vertex].take().unwrap();
+        self.len -= 1;
+        let incoming = edge_pointer(vertex);
+        for (from, other) in self.vertices.iter().enumerate() {
+            let Some(other) = other else {
+                continue;
+            };
+            if other.borrow_mut().remove_connection(&incoming).is_some() {
+                self.serials.remove(&(from, vertex));
+            }
+        }
+        let value = node.borrow_mut().clear();
+        Ok(value.unwrap())
+    }
+
+    /// List the vertices of the graph
+    /// The ids are collected first, so the graph can be changed while iterating over them.
+    /// # Returns
+    /// An iterator over the indexes of the vertices in increasing order
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::Graph;
+    ///
+    /// let mut graph = Graph::new();
+    /// for value in 0..5 {
+    ///     graph.add_vertex(value);
+    /// }
+    /// for vertex in graph.vertices() {
+    ///     if vertex % 2 == 1 {
+    ///         graph.remove_vertex(vertex).unwrap();
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(graph.vertices().collect::<Vec<_>>(), vec![0, 2, 4]);
+    /// ```
+    pub fn vertices(&self) -> impl Iterator<Item = NodeId> {
+        let vertices: Vec<NodeId> = (0..self.vertex_bound())
+            .filter(|&vertex| self.contains_vertex(vertex))
+            .collect();
+        vertices.into_iter()
+    }
+
+    /// List the edges of the graph, an undirected edge giving one id for each direction
+    /// The ids are collected first, so the graph can be changed while iterating over them.
+    /// # Returns
+    /// An iterator over the ids of the edges, ordered by source and then by target
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeId, EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex(1);
+    /// let b = graph.add_vertex(2);
+    /// graph.add_edge(a, b, EdgeKind::Undirected).unwrap();
+    ///
+    /// for edge in graph.edges() {
+    ///     graph.remove_edge(edge.from, edge.to, EdgeKind::Directed).unwrap();
+    /// }
+    /// assert_eq!(graph.edges().count(), 0);
+    ///
+    /// graph.add_edge(b, a, EdgeKind::Directed).unwrap();
+    /// assert_eq!(graph.edges().collect::<Vec<_>>(), vec![EdgeId { from: b, to: a, serial: 2 }]);
+    /// ```
+    pub fn edges(&self) -> impl Iterator<Item = EdgeId> {
+        let edges: Vec<EdgeId> = self
+            .vertices()
+            .flat_map(|from| {
+                self.sorted_neighbors(from)
+                    .into_iter()
+                    .map(move |to| self.edge_id(from, to).unwrap())
+            })
+            .collect();
+        edges.into_iter()
+    }
+
+    /// Keep only the vertices accepted by a predicate, removing the others with their edges
+    /// # Arguments
+    /// * `keep`: Called once with the index and the value of every vertex, returns true to keep it
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::new();
+    /// for value in 0..6 {
+    ///     graph.add_vertex(value * 10);
+    /// }
+    /// for vertex in 0..5 {
+    ///     graph.add_edge(vertex, vertex + 1, EdgeKind::Directed).unwrap();
+    /// }
+    ///
+    /// graph.retain_nodes(|_, &value| value != 30);
+    /// assert_eq!(graph.len(), 5);
+    /// assert_eq!(graph.bfs(0).unwrap().collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// assert_eq!(graph.bfs(4).unwrap().collect::<Vec<_>>(), vec![4, 5]);
+    /// ```
+    pub fn retain_nodes(&mut self, mut keep: impl FnMut(NodeId, &T) -> bool) {
+        let removed: Vec<NodeId> = self
+            .vertices()
+            .filter(|&vertex| {
+                let node = self.node(vertex).borrow();
+                !keep(vertex, node.read_data().as_ref().unwrap())
+            })
+            .collect();
+        if removed.is_empty() {
+            return;
+        }
+        for &vertex in &removed {
+            let node = self.vertices[vertex].take().unwrap();
+            node.borrow_mut().clear();
+        }
+        self.len -= removed.len();
+        let vertices = &self.vertices;
+        self.serials
+            .retain(|&(from, to), _| vertices[from].is_some() && vertices[to].is_some());
+
+        // Drop the edges left pointing to the removed vertices
+        for node in self.vertices.iter().flatten() {
+            let mut node = node.borrow_mut();
+            let dangling: Vec<PointerName> = node
+                .connection_names()
+                .filter(|name| edge_target(name).is_some_and(|to| self.vertices[to].is_none()))
+                .cloned()
+                .collect();
+            for name in &dangling {
+                node.remove_connection(name);
+            }
+        }
+    }
+
+    /// Keep only the edges accepted by a predicate, removing the others
+    /// An undirected edge is seen as its two directions, each kept or removed on its own.
+    /// # Arguments
+    /// * `keep`: Called once with the id and the weight of every edge, returns true to keep it
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::weighted();
+    /// let a = graph.add_vertex('a');
+    /// let b = graph.add_vertex('b');
+    /// let c = graph.add_vertex('c');
+    /// graph.add_weighted_edge(a, b, 5, EdgeKind::Undirected).unwrap();
+    /// graph.add_weighted_edge(b, c, 1, EdgeKind::Undirected).unwrap();
+    ///
+    /// graph.retain_edges(|edge, &weight| weight < 5 && edge.from < edge.to);
+    /// assert_eq!(graph.edges().count(), 1);
+    /// assert!(graph.has_edge(b, c));
+    /// ```
+    pub fn retain_edges(&mut self, mut keep: impl FnMut(EdgeId, &W) -> bool) {
+        for from in 0..self.vertex_bound() {
+            // Borrows only the vertices, so the serials of the removed edges can be dropped
+            let Some(Some(node)) = self.vertices.get(from) else {
+                continue;
+            };
+            let mut node = node.borrow_mut();
+            let mut removed: Vec<(NodeId, PointerName)> = node
+                .connection_names()
+                .filter_map(|name| Some((edge_target(name)?, name.clone())))
+                .collect();
+            // Sorted so the predicate sees the edges in the order `edges` lists them
+            removed.sort_unstable_by_key(|(to, _)| *to);
+            removed.retain(|(to, name)| {
+                let weight = node.edge_weight(name).unwrap();
+                let serial = self.serials[&(from, *to)];
+                !keep(
+                    EdgeId {
+                        from,
+                        to: *to,
+                        serial,
+                    },
+                    weight,
+                )
+            });
+            for (to, name) in &removed {
+                node.remove_connection(name);
+                self.serials.remove(&(from, *to));
+            }
+        }
+    }
+
+    /// Copy the vertices and edges accepted by two filters into a new graph
+    /// The vertices keep their indexes and the edges their ids, the vertices left out being removed vertices of the
+    /// new graph. For a view that doesn't copy anything, see `graph::view::FilteredView`.
+    /// # Arguments
+    /// * `node_filter`: Called once with the index and the value of every vertex, returns true to copy it
+    /// * `edge_filter`: Called once with the id and the weight of every edge between copied vertices, returns true to
+    ///   copy it
+    /// # Returns
+    /// The new graph
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::weighted();
+    /// for value in 0..4 {
+    ///     graph.add_vertex(value);
+    /// }
+    /// graph.add_weighted_edge(0, 2, 1, EdgeKind::Undirected).unwrap();
+    /// graph.add_weighted_edge(2, 3, 2, EdgeKind::Directed).unwrap();
+    /// graph.add_weighted_edge(1, 2, 3, EdgeKind::Directed).unwrap();
+    ///
+    /// let even = graph.subgraph(|_, &value| value % 2 == 0, |edge, _| edge.from < edge.to);
+    /// assert_eq!(even.vertices().collect::<Vec<_>>(), vec![0, 2]);
+    /// assert!(even.has_edge(0, 2) && !even.has_edge(2, 0));
+    /// assert_eq!(even.value(3), None);
+    /// ```
+    pub fn subgraph(
+        &self,
+        mut node_filter: impl FnMut(NodeId, &T) -> bool,
+        mut edge_filter: impl FnMut(EdgeId, &W) -> bool,
+    ) -> Self
+    where
+        T: Clone,
+        W: Clone,
+    {
+        let mut subgraph = Graph::weighted();
+        subgraph.vertices = (0..self.vertex_bound())
+            .map(|vertex| {
+                let value = self.with_value(vertex, |value| {
+                    node_filter(vertex, value).then(|| value.clone())
+                });
+                value.flatten().map(Vertex::new_weighted)
+            })
+            .collect();
+        subgraph.len = subgraph.vertices.iter().flatten().count();
+        subgraph.next_serial = self.next_serial;
+
+        for from in subgraph.vertices() {
+            for (to, weight) in self.weighted_neighbors(from) {
+                let edge = self.edge_id(from, to).unwrap();
+                if subgraph.contains_vertex(to) && edge_filter(edge, &weight) {
+                    subgraph.link(from, to, weight, edge.serial);
+                }
+            }
+        }
+        subgraph
+    }
+
+    /// Get a clone of the value of a vertex
+    /// # Arguments
+    /// * `vertex`: The index of the vertex
+    /// # Returns
+    /// The value of the vertex, or None if there is no vertex at that index
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::Graph;
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex(String::from("a"));
+    ///
+    /// assert_eq!(graph.value(a), Some(String::from("a")));
+    /// assert_eq!(graph.value(1), None);
+    /// ```
+    pub fn value(&self, vertex: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        let node = self.get_node(vertex)?;
+        let value = node.borrow().read_data().clone();
+        value
+    }
+
+    /// Replace the value of a vertex
+    /// # Arguments
+    /// * `vertex`: The index of the vertex
+    /// * `value`: The new value
+    /// # Returns
+    /// The old value, Err(DataStructureError::VertexOutOfBounds) if the vertex was never added, or
+    /// Err(DataStructureError::RemovedVertex) if it was removed
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::Graph;
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex("draft");
+    ///
+    /// assert_eq!(graph.set_value(a, "final"), Ok("draft"));
+    /// assert_eq!(graph.value(a), Some("final"));
+    /// ```
+    pub fn set_value(&mut self, vertex: NodeId, value: T) -> Result<T, DataStructureError> {
+        self.check_vertex(vertex)?;
+        let old = self.node(vertex).borrow_mut().set_data(value);
+        Ok(old.unwrap())
+    }
+
+    /// Add an edge between two vertices, with the default weight
+    /// # Arguments
+    /// * `from`: The index of the source vertex
+    /// * `to`: The index of the target vertex
+    /// * `kind`: Directed for an edge from `from` to `to` only, Undirected for an edge both ways
+    /// # Returns
+    /// Ok if the edge was added, Err(DataStructureError::VertexOutOfBounds) if a vertex doesn't exist, or
+    /// Err(DataStructureError::AlreadyConnected) if one of the directions to add already exists
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex(1);
+    /// let b = graph.add_vertex(2);
+    ///
+    /// assert_eq!(graph.add_edge(a, b, EdgeKind::Directed), Ok(()));
+    /// assert_eq!(graph.add_edge(a, b, EdgeKind::Undirected), Err(DataStructureError::AlreadyConnected));
+    /// assert_eq!(graph.add_edge(b, a, EdgeKind::Directed), Ok(()));
+    /// ```
+    pub fn add_edge(
+        &mut self,
+        from: usize,
+        to: usize,
+        kind: EdgeKind,
+    ) -> Result<(), DataStructureError>
+    where
+        W: Default + Clone,
+    {
+        self.add_weighted_edge(from, to, W::default(), kind)
+    }
+
+    /// Add an edge between two vertices, carrying a weight
+    /// # Arguments
+    /// * `from`: The index of the source vertex
+    /// * `to`: The index of the target vertex
+    /// * `weight`: The weight of the edge, the same in both directions for an undirected edge
+    /// * `kind`: Directed for an edge from `from` to `to` only, Undirected for an edge both ways
+    /// # Returns
+    /// Ok if the edge was added, Err(DataStructureError::VertexOutOfBounds) if a vertex doesn't exist, or
+    /// Err(DataStructureError::AlreadyConnected) if one of the directions to add already exists
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::weighted();
+    /// let a = graph.add_vertex('a');
+    /// let b = graph.add_vertex('b');
+    ///
+    /// graph.add_weighted_edge(a, b, 2.5, EdgeKind::Undirected).unwrap();
+    /// assert_eq!(graph.edge_weight(b, a), Some(2.5));
+    /// ```
+    pub fn add_weighted_edge(
+        &mut self,
+        from: usize,
+        to: usize,
+        weight: W,
+        kind: EdgeKind,
+    ) -> Result<(), DataStructureError>
+    where
+        W: Clone,
+    {
+        self.check_vertex(from)?;
+        self.check_vertex(to)?;
+
+        if self.has_edge(from, to) || (kind == EdgeKind::Undirected && self.has_edge(to, from)) {
+            return Err(DataStructureError::AlreadyConnected);
+        }
+
+        if kind == EdgeKind::Undirected {
+            let serial = self.new_serial();
+            self.link(to, from, weight.clone(), serial);
+        }
+        let serial = self.new_serial();
+        self.link(from, to, weight, serial);
+        Ok(())
+    }
+
+    /// Get the weight of an edge
+    /// # Arguments
+    /// * `from`: The index of the source vertex
+    /// * `to`: The index of the target vertex
+    /// # Returns
+    /// A clone of the weight, or None if there is no edge from `from` to `to`
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::weighted();
+    /// let a = graph.add_vertex(1);
+    /// let b = graph.add_vertex(2);
+    /// graph.add_weighted_edge(a, b, "bridge", EdgeKind::Directed).unwrap();
+    ///
+    /// assert_eq!(graph.edge_weight(a, b), Some("bridge"));
+    /// assert_eq!(graph.edge_weight(b, a), None);
+    /// ```
+    pub fn edge_weight(&self, from: usize, to: usize) -> Option<W>
+    where
+        W: Clone,
+    {
+        let node = self.get_node(from)?.borrow();
+        node.edge_weight(&edge_pointer(to)).cloned()
+    }
+
+    /// Remove an edge between two vertices
+    /// # Arguments
+    /// * `from`: The index of the source vertex
+    /// * `to`: The index of the target vertex
+    /// * `kind`: Directed to remove the edge from `from` to `to` only, Undirected to remove both directions
+    /// # Returns
+    /// Ok if the edge was removed, Err(DataStructureError::VertexOutOfBounds) if a vertex doesn't exist, or
+    /// Err(DataStructureError::MissingEdge) if one of the directions to remove doesn't exist
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex(1);
+    /// let b = graph.add_vertex(2);
+    /// graph.add_edge(a, b, EdgeKind::Undirected).unwrap();
+    ///
+    /// assert_eq!(graph.remove_edge(a, b, EdgeKind::Directed), Ok(()));
+    /// assert!(graph.has_edge(b, a));
+    /// assert_eq!(graph.remove_edge(a, b, EdgeKind::Undirected), Err(DataStructureError::MissingEdge));
+    /// ```
+    pub fn remove_edge(
+        &mut self,
+        from: usize,
+        to: usize,
+        kind: EdgeKind,
+    ) -> Result<(), DataStructureError> {
+        self.check_vertex(from)?;
+        self.check_vertex(to)?;
+
+        if !self.has_edge(from, to) || (kind == EdgeKind::Undirected && !self.has_edge(to, from)) {
+            return Err(DataStructureError::MissingEdge);
+        }
+
+        self.node(from)
+            .borrow_mut()
+            .remove_connection(&edge_pointer(to));
+        self.serials.remove(&(from, to));
+        if kind == EdgeKind::Undirected {
+            self.node(to)
+                .borrow_mut()
+                .remove_connection(&edge_pointer(from));
+            self.serials.remove(&(to, from));
+        }
+        Ok(())
+    }
+
+    /// Check if there is an edge from a vertex to another
+    /// # Arguments
+    /// * `from`: The index of the source vertex
+    /// * `to`: The index of the target vertex
+    /// # Returns
+    /// True if the edge exists, false otherwise or if a vertex doesn't exist
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex(1);
+    /// let b = graph.add_vertex(2);
+    /// graph.add_edge(a, b, EdgeKind::Directed).unwrap();
+    ///
+    /// assert!(graph.has_edge(a, b));
+    /// assert!(!graph.has_edge(b, a));
+    /// ```
+    pub fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.get_node(from)
+            .is_some_and(|node| node.borrow().get_pointer(edge_pointer(to)).is_some())
+    }
+
+    /// Get the id of the edge from a vertex to another
+    /// # Arguments
+    /// * `from`: The index of the source vertex
+    /// * `to`: The index of the target vertex
+    /// # Returns
+    /// The id of the edge, or None if there is no edge from `from` to `to`
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex(1);
+    /// let b = graph.add_vertex(2);
+    /// graph.add_edge(a, b, EdgeKind::Directed).unwrap();
+    ///
+    /// let edge = graph.edge_id(a, b).unwrap();
+    /// assert_eq!((edge.from, edge.to), (a, b));
+    /// assert_eq!(graph.edge_id(b, a), None);
+    /// ```
+    pub fn edge_id(&self, from: NodeId, to: NodeId) -> Option<EdgeId> {
+        let serial = *self.serials.get(&(from, to))?;
+        Some(EdgeId { from, to, serial })
+    }
+
+    /// Check if the edge an id was given for is still in the graph
+    /// # Arguments
+    /// * `edge`: The id of the edge
+    /// # Returns
+    /// True if the edge exists, false if it was removed, even when a newer edge joins the same vertices
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex(1);
+    /// let b = graph.add_vertex(2);
+    /// graph.add_edge(a, b, EdgeKind::Directed).unwrap();
+    /// let old = graph.edge_id(a, b).unwrap();
+    /// assert!(graph.contains_edge(old));
+    ///
+    /// graph.remove_edge(a, b, EdgeKind::Directed).unwrap();
+    /// graph.add_edge(a, b, EdgeKind::Directed).unwrap();
+    /// assert!(!graph.contains_edge(old));
+    /// assert!(graph.contains_edge(graph.edge_id(a, b).unwrap()));
+    /// ```
+    pub fn contains_edge(&self, edge: EdgeId) -> bool {
+        self.edge_id(edge.from, edge.to) == Some(edge)
+    }
+
+    /// List the vertices a vertex has an edge to
+    /// # Arguments
+    /// * `vertex`: The index of the vertex
+    /// # Returns
+    /// An iterator over the indexes of the neighbors in increasing order, or
+    /// Err(DataStructureError::VertexOutOfBounds) if the vertex doesn't exist
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_vertex(1);
+    /// let b = graph.add_vertex(2);
+    /// let c = graph.add_vertex(3);
+    /// graph.add_edge(a, c, EdgeKind::Directed).unwrap();
+    /// graph.add_edge(a, b, EdgeKind::Undirected).unwrap();
+    ///
+    /// assert_eq!(graph.neighbors(a).unwrap().collect::<Vec<_>>(), vec![b, c]);
+    /// assert_eq!(graph.neighbors(b).unwrap().collect::<Vec<_>>(), vec![a]);
+    /// ```
+    pub fn neighbors(
+        &self,
+        vertex: usize,
+    ) -> Result<impl Iterator<Item = usize>, DataStructureError> {
+        self.check_vertex(vertex)?;
+        Ok(self.sorted_neighbors(vertex).into_iter())
+    }
+
+    /// Walk the vertices reachable from a vertex in breadth first order, the closest ones first
+    /// # Arguments
+    /// * `start`: The index of the vertex to start from, which is visited first
+    /// # Returns
+    /// An iterator over the indexes of the reachable vertices, or Err(DataStructureError::VertexOutOfBounds) if the
+    /// start vertex doesn't exist
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::new();
+    /// for value in 0..4 {
+    ///     graph.add_vertex(value);
+    /// }
+    /// graph.add_edge(0, 3, EdgeKind::Undirected).unwrap();
+    /// graph.add_edge(3, 1, EdgeKind::Undirected).unwrap();
+    /// graph.add_edge(0, 2, EdgeKind::Undirected).unwrap();
+    ///
+    /// assert_eq!(graph.bfs(0).unwrap().collect::<Vec<_>>(), vec![0, 2, 3, 1]);
+    /// ```
+    pub fn bfs(&self, start: usize) -> Result<Bfs<'_, T, W>, DataStructureError> {
+        self.check_vertex(start)?;
+
+        let mut visited = vec![false; self.vertex_bound()];
+        visited[start] = true;
+        Ok(Bfs {
+            graph: self,
+            queue: VecDeque::from([start]),
+            visited,
+        })
+    }
+
+    /// Walk the vertices reachable from a vertex in depth first preorder, following the lowest neighbors first
+    /// # Arguments
+    /// * `start`: The index of the vertex to start from, which is visited first
+    /// # Returns
+    /// An iterator over the indexes of the reachable vertices, or Err(DataStructureError::VertexOutOfBounds) if the
+    /// start vertex doesn't exist
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::new();
+    /// for value in 0..4 {
+    ///     graph.add_vertex(value);
+    /// }
+    /// graph.add_edge(0, 3, EdgeKind::Undirected).unwrap();
+    /// graph.add_edge(3, 1, EdgeKind::Undirected).unwrap();
+    /// graph.add_edge(0, 2, EdgeKind::Undirected).unwrap();
+    ///
+    /// assert_eq!(graph.dfs(0).unwrap().collect::<Vec<_>>(), vec![0, 2, 3, 1]);
+    /// assert_eq!(graph.dfs(1).unwrap().collect::<Vec<_>>(), vec![1, 3, 0, 2]);
+    /// ```
+    pub fn dfs(&self, start: usize) -> Result<Dfs<'_, T, W>, DataStructureError> {
+        self.check_vertex(start)?;
+
+        Ok(Dfs {
+            graph: self,
+            stack: vec![start],
+            visited: vec![false; self.vertex_bound()],
+        })
+    }
+
+    /// Find a path of minimal total weight between two vertices, with Dijkstra's algorithm
+    /// The weights must not be negative.
+    /// # Arguments
+    /// * `from`: The index of the start vertex
+    /// * `to`: The index of the target vertex
+    /// # Returns
+    /// The total weight and the vertices of a shortest path, both ends included, None if `to` can't be reached, or
+    /// Err(DataStructureError::VertexOutOfBounds) if a vertex doesn't exist
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::weighted();
+    /// for value in 0..4 {
+    ///     graph.add_vertex(value);
+    /// }
+    /// graph.add_weighted_edge(0, 1, 4, EdgeKind::Directed).unwrap();
+    /// graph.add_weighted_edge(0, 2, 1, EdgeKind::Directed).unwrap();
+    /// graph.add_weighted_edge(2, 1, 2, EdgeKind::Directed).unwrap();
+    ///
+    /// assert_eq!(graph.shortest_path(0, 1).unwrap(), Some((3, vec![0, 2, 1])));
+    /// assert_eq!(graph.shortest_path(0, 3).unwrap(), None);
+    /// ```
+    pub fn shortest_path(
+        &self,
+        from: usize,
+        to: usize,
+    ) -> Result<Option<(W, Vec<usize>)>, DataStructureError>
+    where
+        W: Copy + Ord + Add<Output = W> + Default,
+    {
+        self.shortest_path_astar(from, to, |_| W::default())
+    }
+
+    /// Find a path of minimal total weight between two vertices, with A* guided by a heuristic
+    /// The weights must not be negative, and the heuristic must never overestimate the remaining weight to `to` nor
+    /// decrease by more than the weight of an edge along it, like a straight line distance on a map.
+    /// # Arguments
+    /// * `from`: The index of the start vertex
+    /// * `to`: The index of the target vertex
+    /// * `heuristic`: The estimated weight from a vertex to `to`
+    /// # Returns
+    /// The total weight and the vertices of a shortest path, both ends included, None if `to` can't be reached, or
+    /// Err(DataStructureError::VertexOutOfBounds) if a vertex doesn't exist
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// // A 3x3 grid where each vertex holds its coordinates
+    /// let mut grid = Graph::weighted();
+    /// for y in 0..3i32 {
+    ///     for x in 0..3i32 {
+    ///         grid.add_vertex((x, y));
+    ///     }
+    /// }
+    /// for cell in 0..9 {
+    ///     if cell % 3 < 2 {
+    ///         grid.add_weighted_edge(cell, cell + 1, 1, EdgeKind::Undirected).unwrap();
+    ///     }
+    ///     if cell < 6 {
+    ///         grid.add_weighted_edge(cell, cell + 3, 1, EdgeKind::Undirected).unwrap();
+    ///     }
+    /// }
+    ///
+    /// // The Manhattan distance to the corner never overestimates
+    /// let (weight, path) = grid
+    ///     .shortest_path_astar(0, 8, |cell| {
+    ///         let (x, y) = grid.value(cell).unwrap();
+    ///         (2 - x + 2 - y) as u32
+    ///     })
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(weight, 4);
+    /// assert_eq!(path.len(), 5);
+    /// ```
+    pub fn shortest_path_astar(
+        &self,
+        from: usize,
+        to: usize,
+        heuristic: impl FnMut(usize) -> W,
+    ) -> Result<Option<(W, Vec<usize>)>, DataStructureError>
+    where
+        W: Copy + Ord + Add<Output = W> + Default,
+    {
+        best_first(self, from, to, heuristic)
+    }
+
+    // Check that a vertex exists
+    fn check_vertex(&self, vertex: usize) -> Result<(), DataStructureError> {
+        match self.vertices.get(vertex) {
+            None => Err(DataStructureError::VertexOutOfBounds {
+                vertex,
+                len: self.vertex_bound(),
+            }),
+            Some(None) => Err(DataStructureError::RemovedVertex(vertex)),
+            Some(Some(_)) => Ok(()),
+        }
+    }
+
+    // Raise the bound of the indexes to at least `bound` with empty slots, so none below it is given to a new vertex
+    pub(crate) fn raise_vertex_bound(&mut self, bound: usize) -> Result<(), DataStructureError> {
+        let additional = bound.saturating_sub(self.vertices.len());
+        self.vertices
+            .try_reserve(additional)
+            .map_err(|_| DataStructureError::AllocationFailed)?;
+        self.vertices
+            .resize_with(self.vertices.len() + additional, || None);
+        Ok(())
+    }
+
+    // Put a new vertex at an empty slot that was never used, made by `raise_vertex_bound`
+    pub(crate) fn insert_vertex_at(&mut self, vertex: NodeId, value: T) {
+        debug_assert!(self.vertices[vertex].is_none());
+        self.vertices[vertex] = Some(Vertex::new_weighted(value));
+        self.len += 1;
+    }
+
+    // Replace the weight of an existing edge, which keeps its id
+    pub(crate) fn set_edge_weight(&mut self, from: NodeId, to: NodeId, weight: W) {
+        let serial = self.serials[&(from, to)];
+        self.link(from, to, weight, serial);
+    }
+
+    // The vertex at an index, None if it was removed or never added
+    fn get_node(&self, vertex: usize) -> Option<&Shared<Vertex<T, W>>> {
+        self.vertices.get(vertex)?.as_ref()
+    }
+
+    // The vertex at the index of an existing vertex
+    fn node(&self, vertex: usize) -> &Shared<Vertex<T, W>> {
+        self.vertices[vertex].as_ref().unwrap()
+    }
+
+    // Serial for a new edge, never given before
+    fn new_serial(&mut self) -> u64 {
+        self.next_serial += 1;
+        self.next_serial - 1
+    }
+
+    // Point a vertex to another one, with the serial of the edge
+    fn link(&mut self, from: usize, to: usize, weight: W, serial: u64) {
+        let target = self.node(to).clone();
+        self.node(from)
+            .borrow_mut()
+            .set_edge(edge_pointer(to), &target, weight);
+        self.serials.insert((from, to), serial);
+    }
+
+    // Indexes of the neighbors of an existing vertex, in increasing order
+    fn sorted_neighbors(&self, vertex: usize) -> Vec<usize> {
+        let mut neighbors: Vec<usize> = self
+            .node(vertex)
+            .borrow()
+            .connection_names()
+            .filter_map(edge_target)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors
+    }
+
+    /// Take a snapshot of the vertices and edges of the graph, for debugging
+    /// # Returns
+    /// The topology of the graph, its nodes numbered in the order of the vertices, ready to be rendered as DOT or Mermaid
+    /// # Example
+    /// ```
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::weighted();
+    /// let a = graph.add_vertex("a");
+    /// let b = graph.add_vertex("b");
+    /// graph.add_weighted_edge(a, b, 3, EdgeKind::Directed).unwrap();
+    ///
+    /// assert!(graph.topology().to_dot().contains("n0 -> n1 [label=\"edge1: 3\"];"));
+    /// ```
+    pub fn topology(&self) -> Topology
+    where
+        T: fmt::Debug,
+        W: fmt::Debug,
+    {
+        Topology::from_vertices(self.vertices.iter().flatten())
+    }
+}
+
+impl<T, W: Clone> GraphView for Graph<T, W> {
+    type Value = T;
+    type Weight = W;
+
+    fn vertex_bound(&self) -> usize {
+        Graph::vertex_bound(self)
+    }
+
+    fn contains_vertex(&self, vertex: NodeId) -> bool {
+        Graph::contains_vertex(self, vertex)
+    }
+
+    fn with_value<R>(&self, vertex: NodeId, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let node = self.get_node(vertex)?.borrow();
+        node.read_data().as_ref().map(f)
+    }
+
+    fn weighted_edges(&self, vertex: NodeId) -> Vec<(EdgeId, W)> {
+        let neighbors = self.weighted_neighbors(vertex).into_iter();
+        neighbors
+            .map(|(to, weight)| (self.edge_id(vertex, to).unwrap(), weight))
+            .collect()
+    }
+
+    fn edge_id(&self, from: NodeId, to: NodeId) -> Option<EdgeId> {
+        Graph::edge_id(self, from, to)
+    }
+
+    fn weighted_neighbors(&self, vertex: NodeId) -> Vec<(NodeId, W)> {
+        let Some(node) = self.get_node(vertex) else {
+            return Vec::new();
+        };
+        let node = node.borrow();
+        let mut neighbors: Vec<(NodeId, W)> = node
+            .connection_names()
+            .filter_map(|name| Some((edge_target(name)?, node.edge_weight(name)?.clone())))
+            .collect();
+        neighbors.sort_unstable_by_key(|(neighbor, _)| *neighbor);
+        neighbors
+    }
+
+    fn len(&self) -> usize {
+        Graph::len(self)
+    }
+
+    fn vertices(&self) -> impl Iterator<Item = NodeId> {
+        Graph::vertices(self)
+    }
+
+    fn edges(&self) -> impl Iterator<Item = EdgeId> {
+        Graph::edges(self)
+    }
+}
+
+impl<T, W> Default for Graph<T, W> {
+    fn default() -> Self {
+        Self::weighted()
+    }
+}
+
+impl<T, W> Drop for Graph<T, W> {
+    fn drop(&mut self) {
+        // Edges may form cycles, clearing the vertexes drops them
+        for vertex in self.vertices.iter().flatten() {
+            vertex.borrow_mut().clear();
+        }
+    }
+}
+
+impl<T, W> Iterator for Bfs<'_, T, W> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let vertex = self.queue.pop_front()?;
+        for neighbor in self.graph.sorted_neighbors(vertex) {
+            if !self.visited[neighbor] {
+                self.visited[neighbor] = true;
+                self.queue.push_back(neighbor);
+            }
+        }
+        Some(vertex)
+    }
+}
+
+impl<T, W> Iterator for Dfs<'_, T, W> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            let vertex = self.stack.pop()?;
+            if self.visited[vertex] {
+                continue;
+            }
+            self.visited[vertex] = true;
+
+            // Pushed in reverse so the lowest neighbor is visited first
+            let neighbors = self.graph.sorted_neighbors(vertex);
+            self.stack.extend(
+                neighbors
+                    .into_iter()
+                    .rev()
+                    .filter(|neighbor| !self.visited[*neighbor]),
+            );
+            return Some(vertex);
+        }
+    }
+}
+
+impl<T: fmt::Debug, W> fmt::Debug for Graph<T, W> {
+    /// Prints each vertex with its value and the indexes of its neighbors
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        for index in self.vertices() {
+            let node = self.node(index).borrow();
+            map.entry(
+                &index,
+                &(
+                    node.read_data().as_ref().unwrap(),
+                    self.sorted_neighbors(index),
+                ),
+            );
+        }
+        map.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::HashSet;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_traversals_against_adjacency_matrix() {
+        let size = 30;
+        let mut graph = Graph::new();
+        let mut matrix = vec![vec![false; size]; size];
+        for value in 0..size {
+            graph.add_vertex(value);
+        }
+
+        let mut rng = Lcg::new(5);
+        for _ in 0..400 {
+            let seed = rng.next_u64();
+            let from = (seed >> 33) as usize % size;
+            let to = (seed >> 45) as usize % size;
+            let kind = if (seed >> 20).is_multiple_of(3) {
+                EdgeKind::Undirected
+            } else {
+                EdgeKind::Directed
+            };
+            let undirected = kind == EdgeKind::Undirected;
+
+            if (seed >> 25).is_multiple_of(4) {
+                let expected = if matrix[from][to] && (!undirected || matrix[to][from]) {
+                    matrix[from][to] = false;
+                    if undirected {
+                        matrix[to][from] = false;
+                    }
+                    Ok(())
+                } else {
+                    Err(DataStructureError::MissingEdge)
+                };
+                assert_eq!(graph.remove_edge(from, to, kind), expected);
+            } else {
+                let expected = if matrix[from][to] || (undirected && matrix[to][from]) {
+                    Err(DataStructureError::AlreadyConnected)
+                } else {
+                    matrix[from][to] = true;
+                    if undirected {
+                        matrix[to][from] = true;
+                    }
+                    Ok(())
+                };
+                assert_eq!(graph.add_edge(from, to, kind), expected);
+            }
+        }
+
+        for start in 0..size {
+            let expected: Vec<usize> = (0..size).filter(|&to| matrix[start][to]).collect();
+            assert_eq!(
+                graph.neighbors(start).unwrap().collect::<Vec<_>>(),
+                expected
+            );
+
+            // Breadth first: visited by increasing distance, each reachable vertex once
+            let mut distance = vec![usize::MAX; size];
+            distance[start] = 0;
+            let mut queue = VecDeque::from([start]);
+            while let Some(vertex) = queue.pop_front() {
+                for to in 0..size {
+                    if matrix[vertex][to] && distance[to] == usize::MAX {
+                        distance[to] = distance[vertex] + 1;
+                        queue.push_back(to);
+                    }
+                }
+            }
+            let bfs: Vec<usize> = graph.bfs(start).unwrap().collect();
+            assert!(bfs.windows(2).all(|w| distance[w[0]] <= distance[w[1]]));
+            let mut reached: Vec<usize> =
+                (0..size).filter(|&v| distance[v] != usize::MAX).collect();
+
+            // Depth first: every vertex after the first is a neighbor of a vertex on the current path
+            let dfs: Vec<usize> = graph.dfs(start).unwrap().collect();
+            let mut path = vec![dfs[0]];
+            for &vertex in &dfs[1..] {
+                while !matrix[*path.last().unwrap()][vertex] {
+                    path.pop();
+                }
+                path.push(vertex);
+            }
+
+            let mut bfs_sorted = bfs.clone();
+            let mut dfs_sorted = dfs.clone();
+            bfs_sorted.sort_unstable();
+            dfs_sorted.sort_unstable();
+            reached.sort_unstable();
+            assert_eq!(bfs_sorted, reached);
+            assert_eq!(dfs_sorted, reached);
+        }
+
+        assert_eq!(
+            graph.bfs(size).err(),
+            Some(DataStructureError::VertexOutOfBounds {
+                vertex: size,
+                len: size
+            })
+        );
+    }
+
+    #[test]
+    fn test_shortest_paths_against_floyd_warshall() {
+        let size = 25;
+        let mut graph = Graph::weighted();
+        let mut matrix: Vec<Vec<Option<u64>>> = vec![vec![None; size]; size];
+        for value in 0..size {
+            graph.add_vertex(value);
+        }
+
+        let mut rng = Lcg::new(11);
+        for _ in 0..120 {
+            let seed = rng.next_u64();
+            let from = (seed >> 33) as usize % size;
+            let to = (seed >> 45) as usize % size;
+            let weight = (seed >> 20) % 50;
+            if graph
+                .add_weighted_edge(from, to, weight, EdgeKind::Directed)
+                .is_ok()
+            {
+                matrix[from][to] = Some(weight);
+            }
+        }
+
+        let mut distances = matrix.clone();
+        for (vertex, row) in distances.iter_mut().enumerate() {
+            row[vertex] = Some(0);
+        }
+        for middle in 0..size {
+            for from in 0..size {
+                for to in 0..size {
+                    if let (Some(first), Some(second)) =
+                        (distances[from][middle], distances[middle][to])
+                    {
+                        if distances[from][to].is_none_or(|known| first + second < known) {
+                            distances[from][to] = Some(first + second);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (from, row) in distances.iter().enumerate() {
+            for (to, distance) in row.iter().enumerate() {
+                let dijkstra = graph.shortest_path(from, to).unwrap();
+                let astar = graph.shortest_path_astar(from, to, |_| 0).unwrap();
+                assert_eq!(dijkstra, astar);
+                assert_eq!(dijkstra.as_ref().map(|(weight, _)| *weight), *distance);
+                if let Some((weight, path)) = dijkstra {
+                    assert_eq!((path[0], *path.last().unwrap()), (from, to));
+                    let total: u64 = path
+                        .windows(2)
+                        .map(|edge| matrix[edge[0]][edge[1]].unwrap())
+                        .sum();
+                    assert_eq!(total, weight);
+                }
+            }
+        }
+        assert_eq!(
+            graph.shortest_path(0, size),
+            Err(DataStructureError::VertexOutOfBounds {
+                vertex: size,
+                len: size
+            })
+        );
+    }
+
+    // Ends of the edges of the adjacency matrix, ordered like `Graph::edges`
+    fn model_edges(matrix: &[Vec<Option<u64>>]) -> Vec<(NodeId, NodeId)> {
+        let mut edges = Vec::new();
+        for (from, row) in matrix.iter().enumerate() {
+            for (to, weight) in row.iter().enumerate() {
+                if weight.is_some() {
+                    edges.push((from, to));
+                }
+            }
+        }
+        edges
+    }
+
+    // Ends of the edges of the graph
+    fn ends(graph: &Graph<usize, u64>) -> Vec<(NodeId, NodeId)> {
+        graph.edges().map(|edge| (edge.from, edge.to)).collect()
+    }
+
+    // Drop a vertex and its edges from the model
+    fn remove_from_model(alive: &mut [bool], matrix: &mut [Vec<Option<u64>>], vertex: usize) {
+        alive[vertex] = false;
+        matrix[vertex].iter_mut().for_each(|weight| *weight = None);
+        matrix.iter_mut().for_each(|row| row[vertex] = None);
+    }
+
+    #[test]
+    fn test_stable_ids_against_model() {
+        let mut graph = Graph::weighted();
+        let mut alive: Vec<bool> = Vec::new();
+        let mut matrix: Vec<Vec<Option<u64>>> = Vec::new();
+        let mut rng = Lcg::new(23);
+        let mut ids: Vec<EdgeId> = Vec::new();
+        let mut gone: HashSet<EdgeId> = HashSet::new();
+
+        for round in 0..800 {
+            let seed = rng.next_u64();
+            let bound = alive.len() + 1;
+            let from = (seed >> 33) as usize % bound;
+            let to = (seed >> 45) as usize % bound;
+            let weight = (seed >> 20) % 20;
+            let check = |vertex: usize| match alive.get(vertex) {
+                Some(true) => Ok(()),
+                Some(false) => Err(DataStructureError::RemovedVertex(vertex)),
+                None => Err(DataStructureError::VertexOutOfBounds {
+                    vertex,
+                    len: alive.len(),
+                }),
+            };
+
+            match (seed >> 10) % 16 {
+                0..=3 => {
+                    assert_eq!(graph.add_vertex(alive.len()), alive.len());
+                    alive.push(true);
+                    matrix.iter_mut().for_each(|row| row.push(None));
+                    matrix.push(vec![None; alive.len()]);
+                }
+                4 => {
+                    let expected = check(from).map(|_| from);
+                    if expected.is_ok() {
+                        remove_from_model(&mut alive, &mut matrix, from);
+                    }
+                    assert_eq!(graph.remove_vertex(from), expected);
+                }
+                5 => {
+                    let modulo = (seed >> 50) as usize % 6 + 3;
+                    graph.retain_nodes(|vertex, &value| {
+                        assert_eq!(vertex, value);
+                        value % modulo != round % modulo
+                    });
+                    for vertex in 0..alive.len() {
+                        if alive[vertex] && vertex % modulo == round % modulo {
+                            remove_from_model(&mut alive, &mut matrix, vertex);
+                        }
+                    }
+                }
+                6 => {
+                    let expected = model_edges(&matrix);
+                    let mut seen = Vec::new();
+                    graph.retain_edges(|edge, &weight| {
+                        seen.push((edge.from, edge.to));
+                        weight % 4 != 0
+                    });
+                    assert_eq!(seen, expected);
+                    for weight in matrix.iter_mut().flatten() {
+                        if weight.is_some_and(|weight| weight % 4 == 0) {
+                            *weight = None;
+                        }
+                    }
+                }
+                7..=12 => {
+                    let expected =
+                        check(from)
+                            .and(check(to))
+                            .and_then(|_| match matrix[from][to] {
+                                Some(_) => Err(DataStructureError::AlreadyConnected),
+                                None => Ok(()),
+                            });
+                    if expected.is_ok() {
+                        matrix[from][to] = Some(weight);
+                    }
+                    assert_eq!(
+                        graph.add_weighted_edge(from, to, weight, EdgeKind::Directed),
+                        expected
+                    );
+                }
+                _ => {
+                    let expected =
+                        check(from)
+                            .and(check(to))
+                            .and_then(|_| match matrix[from][to] {
+                                Some(_) => Ok(()),
+                                None => Err(DataStructureError::MissingEdge),
+                            });
+                    if expected.is_ok() {
+                        matrix[from][to] = None;
+                    }
+                    assert_eq!(graph.remove_edge(from, to, EdgeKind::Directed), expected);
+                }
+            }
+
+            let live: Vec<usize> = (0..alive.len()).filter(|&vertex| alive[vertex]).collect();
+            assert_eq!(graph.len(), live.len());
+            assert_eq!(graph.vertex_bound(), alive.len());
+            assert_eq!(graph.vertices().collect::<Vec<_>>(), live);
+            assert_eq!(ends(&graph), model_edges(&matrix));
+            for edge in graph.edges() {
+                assert_eq!(
+                    graph.edge_weight(edge.from, edge.to),
+                    matrix[edge.from][edge.to]
+                );
+            }
+
+            // The id of a removed edge never comes back, even for a new edge between the same vertices
+            let current: Vec<EdgeId> = graph.edges().collect();
+            gone.extend(ids.iter().filter(|edge| !graph.contains_edge(**edge)));
+            assert!(current.iter().all(|edge| !gone.contains(edge)));
+            assert!(gone.iter().all(|edge| !graph.contains_edge(*edge)));
+            ids = current;
+
+            if round % 50 == 0 {
+                for &start in &live {
+                    assert!(graph.bfs(start).unwrap().all(|vertex| alive[vertex]));
+                    assert!(graph.dfs(start).unwrap().all(|vertex| alive[vertex]));
+                }
+                let (kruskal_total, kruskal_forest) = crate::graph::algorithms::kruskal(&graph);
+                let (prim_total, prim_forest) = crate::graph::algorithms::prim(&graph);
+                assert_eq!(kruskal_total, prim_total);
+                assert_eq!(kruskal_forest.len(), prim_forest.len());
+                assert!(kruskal_forest
+                    .iter()
+                    .all(|&(from, to, _)| alive[from] && alive[to]));
+            }
+        }
+        assert!(alive.iter().any(|alive| !alive));
+        assert!(graph.len() > 10);
+    }
+
+    #[test]
+    fn test_edge_ids_are_not_reused() {
+        let mut graph = Graph::weighted();
+        for value in 0..4 {
+            graph.add_vertex(value);
+        }
+        let mut gone = Vec::new();
+        let mut reused = 0;
+        let mut rng = Lcg::new(29);
+        for _ in 0..400 {
+            let seed = rng.next_u64();
+            let vertices: Vec<NodeId> = graph.vertices().collect();
+            let from = vertices[(seed >> 33) as usize % vertices.len()];
+            let to = vertices[(seed >> 45) as usize % vertices.len()];
+            let before: Vec<EdgeId> = graph.edges().collect();
+            match (seed >> 10) % 12 {
+                0 => {
+                    graph.retain_edges(|edge, _| edge.from != from);
+                }
+                1 => {
+                    // The vertex goes with its edges, and comes back as a new vertex
+                    graph.remove_vertex(from).unwrap();
+                    let vertex = graph.add_vertex(0);
+                    if to != from {
+                        graph
+                            .add_weighted_edge(vertex, to, 1, EdgeKind::Undirected)
+                            .unwrap();
+                    }
+                }
+                2 | 3 => {
+                    let _ = graph.remove_edge(from, to, EdgeKind::Undirected);
+                }
+                _ => {
+                    let _ = graph.remove_edge(from, to, EdgeKind::Directed);
+                    let _ = graph.add_weighted_edge(from, to, seed % 5, EdgeKind::Directed);
+                }
+            }
+            gone.extend(
+                before
+                    .into_iter()
+                    .filter(|edge| !graph.contains_edge(*edge)),
+            );
+
+            let edges: Vec<EdgeId> = graph.edges().collect();
+            let serials: HashSet<u64> = edges.iter().map(|edge| edge.serial).collect();
+            assert_eq!(serials.len(), edges.len());
+            for &edge in &edges {
+                assert_eq!(graph.edge_id(edge.from, edge.to), Some(edge));
+                assert!(!gone.contains(&edge));
+                if gone
+                    .iter()
+                    .any(|old| (old.from, old.to) == (edge.from, edge.to))
+                {
+                    reused += 1;
+                }
+            }
+        }
+        // Removed edges were added again between the same vertices many times, with new ids
+        assert!(reused > 50);
+    }
+
+    #[test]
+    fn test_cycles_are_dropped() {
+        let shared = Rc::new(());
+        let mut graph = Graph::new();
+        let a = graph.add_vertex(shared.clone());
+        let b = graph.add_vertex(shared.clone());
+        graph.add_edge(a, b, EdgeKind::Undirected).unwrap();
+        graph.add_edge(a, a, EdgeKind::Directed).unwrap();
+
+        assert_eq!(format!("{:?}", graph), "{0: ((), [0, 1]), 1: ((), [0])}");
+
+        let c = graph.add_vertex(shared.clone());
+        graph.add_edge(c, a, EdgeKind::Undirected).unwrap();
+        assert_eq!(graph.remove_vertex(c).map(drop), Ok(()));
+        assert_eq!(Rc::strong_count(&shared), 3);
+        assert_eq!(format!("{:?}", graph), "{0: ((), [0, 1]), 1: ((), [0])}");
+        drop(graph);
+        assert_eq!(Rc::strong_count(&shared), 1);
+    }
+}
@@ -0,0 +1,309 @@
+//! This module defines `GraphView`, the read-only view of the vertices and weighted edges of a graph that the
+//! algorithms of the crate run on, and `FilteredView`, which hides the vertices and edges rejected by two filters
+//! without copying anything. An algorithm can then run on a slice of a large graph, such as its active vertices or
+//! its cheap edges, and views can be stacked to combine filters. `Graph::subgraph` copies the same slice into a new
+//! graph when it must outlive the original or be changed.
+//!
+//! # Performance
+//! - O(1) plus the cost of the node filter for `contains_vertex` and `with_value` on a `FilteredView`
+//! - O(d log d) for `weighted_edges` and `weighted_neighbors` of a vertex of degree d, plus one call of each filter
+//!   per neighbor, and the same for `edge_id`, which looks through the edges of the source
+//! - O(V) for `len` and to start `vertices` on a `FilteredView`, which count and list the vertices accepted
+//!
+//! # Implementation Details
+//! - Views are identified by the indexes and edge ids of the underlying graph, so ids found on a view are valid on
+//!   the graph and arrays indexed by vertex are sized by `vertex_bound`, the one of the graph.
+//! - An edge of a `FilteredView` is visible when both of its vertices are and the edge filter accepts it. The filters
+//!   are `Fn` closures, called again every time a vertex or an edge is looked at, so they must give the same answer
+//!   each time.
+//! - A vertex hidden by a view is reported like a removed one, with `DataStructureError::RemovedVertex`.
+//!
+//! # Usage
+//! ```
+//! use data_structures::graph::algorithms::kruskal;
+//! use data_structures::graph::view::{FilteredView, GraphView};
+//! use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+//!
+//! // Servers with whether they are active, linked by the latency between them
+//! let mut mesh = Graph::weighted();
+//! let a = mesh.add_vertex(("a", true));
+//! let b = mesh.add_vertex(("b", false));
+//! let c = mesh.add_vertex(("c", true));
+//! mesh.add_weighted_edge(a, b, 1, EdgeKind::Undirected).unwrap();
+//! mesh.add_weighted_edge(b, c, 1, EdgeKind::Undirected).unwrap();
+//! mesh.add_weighted_edge(a, c, 5, EdgeKind::Undirected).unwrap();
+//!
+//! let active = FilteredView::new(&mesh, |_, &(_, active)| active, |_, _| true);
+//! assert_eq!(active.vertices().collect::<Vec<_>>(), vec![a, c]);
+//! assert_eq!(kruskal(&active), (5, vec![(a, c, 5)]));
+//! assert_eq!(kruskal(&mesh).0, 2);
+//!
+//! // The same slice, copied into a graph that keeps the indexes
+//! let copy = mesh.subgraph(|_, &(_, active)| active, |_, _| true);
+//! assert_eq!(copy.vertices().collect::<Vec<_>>(), vec![a, c]);
+//! assert_eq!(copy.edge_weight(c, a), Some(5));
+//! ```
+//!
+use crate::error::DataStructureError;
+use crate::graph::vertex_graph::{EdgeId, NodeId};
+
+/// Read-only view of the vertices and weighted edges of a graph, implemented by `Graph` and `FilteredView`
+///
+pub trait GraphView {
+    /// Type of the values of the vertices
+    type Value;
+    /// Type of the weights of the edges
+    type Weight: Clone;
+
+    /// One more than the largest index of a vertex, the size of an array indexed by vertex
+    fn vertex_bound(&self) -> usize;
+
+    /// Check if a vertex is in the view
+    fn contains_vertex(&self, vertex: NodeId) -> bool;
+
+    /// Call `f` with a reference to the value of a vertex, or return None if the vertex isn't in the view
+    fn with_value<R>(&self, vertex: NodeId, f: impl FnOnce(&Self::Value) -> R) -> Option<R>;
+
+    /// Edges from a vertex with their weights, in increasing order of target, none if the vertex isn't in the view
+    fn weighted_edges(&self, vertex: NodeId) -> Vec<(EdgeId, Self::Weight)>;
+
+    /// Neighbors of a vertex with the weights of the edges to them in increasing order, none if the vertex isn't in
+    /// the view
+    fn weighted_neighbors(&self, vertex: NodeId) -> Vec<(NodeId, Self::Weight)> {
+        self.weighted_edges(vertex)
+            .into_iter()
+            .map(|(edge, weight)| (edge.to, weight))
+            .collect()
+    }
+
+    /// Id of the edge from a vertex to another, None if the view has no such edge
+    fn edge_id(&self, from: NodeId, to: NodeId) -> Option<EdgeId> {
+        let mut edges = self.weighted_edges(from).into_iter();
+        edges.find(|(edge, _)| edge.to == to).map(|(edge, _)| edge)
+    }
+
+    /// Number of vertices in the view
+    fn len(&self) -> usize {
+        self.vertices().count()
+    }
+
+    /// Check if the view has no vertices
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Indexes of the vertices in the view, in increasing order
+    fn vertices(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.vertex_bound()).filter(|&vertex| self.contains_vertex(vertex))
+    }
+
+    /// Ids of the edges in the view, ordered by source and then by target
+    fn edges(&self) -> impl Iterator<Item = EdgeId> {
+        self.vertices().flat_map(|vertex| {
+            self.weighted_edges(vertex)
+                .into_iter()
+                .map(|(edge, _)| edge)
+        })
+    }
+}
+
+/// View of the vertices and edges of a graph accepted by two filters, made without copying the graph
+///
+pub struct FilteredView<'a, G, N, E> {
+    graph: &'a G,
+    node_filter: N,
+    edge_filter: E,
+}
+
+impl<'a, G, N, E> FilteredView<'a, G, N, E>
+where
+    G: GraphView,
+    N: Fn(NodeId, &G::Value) -> bool,
+    E: Fn(EdgeId, &G::Weight) -> bool,
+{
+    /// Create a view of the vertices and edges of a graph accepted by two filters
+    /// An edge is in the view when both of its vertices are and the edge filter accepts it.
+    /// # Arguments
+    /// * `graph`: The graph or view to filter
+    /// * `node_filter`: Called with the index and the value of a vertex, returns true to show it
+    /// * `edge_filter`: Called with the id and the weight of an edge between shown vertices, returns true to show it
+    /// # Returns
+    /// A new FilteredView instance
+    /// # Example
+    /// ```
+    /// use data_structures::graph::view::{FilteredView, GraphView};
+    /// use data_structures::graph::vertex_graph::{EdgeKind, Graph};
+    ///
+    /// let mut graph = Graph::weighted();
+    /// for value in 0..4 {
+    ///     graph.add_vertex(value);
+    /// }
+    /// graph.add_weighted_edge(0, 1, 10, EdgeKind::Directed).unwrap();
+    /// graph.add_weighted_edge(0, 2, 20, EdgeKind::Directed).unwrap();
+    /// graph.add_weighted_edge(2, 3, 30, EdgeKind::Directed).unwrap();
+    ///
+    /// let view = FilteredView::new(&graph, |_, &value| value != 1, |_, &weight| weight < 30);
+    /// assert_eq!(view.len(), 3);
+    /// assert_eq!(view.weighted_neighbors(0), vec![(2, 20)]);
+    /// assert_eq!(view.with_value(1, |value| *value), None);
+    ///
+    /// // Views can be filtered again
+    /// let narrower = FilteredView::new(&view, |vertex, _| vertex != 3, |_, _| true);
+    /// assert_eq!(narrower.vertices().collect::<Vec<_>>(), vec![0, 2]);
+    /// ```
+    pub fn new(graph: &'a G, node_filter: N, edge_filter: E) -> Self {
+        FilteredView {
+            graph,
+            node_filter,
+            edge_filter,
+        }
+    }
+}
+
+impl<G, N, E> GraphView for FilteredView<'_, G, N, E>
+where
+    G: GraphView,
+    N: Fn(NodeId, &G::Value) -> bool,
+    E: Fn(EdgeId, &G::Weight) -> bool,
+{
+    type Value = G::Value;
+    type Weight = G::Weight;
+
+    fn vertex_bound(&self) -> usize {
+        self.graph.vertex_bound()
+    }
+
+    fn contains_vertex(&self, vertex: NodeId) -> bool {
+        self.graph
+            .with_value(vertex, |value| (self.node_filter)(vertex, value))
+            .unwrap_or(false)
+    }
+
+    fn with_value<R>(&self, vertex: NodeId, f: impl FnOnce(&G::Value) -> R) -> Option<R> {
+        if !self.contains_vertex(vertex) {
+            return None;
+        }
+        self.graph.with_value(vertex, f)
+    }
+
+    fn weighted_edges(&self, vertex: NodeId) -> Vec<(EdgeId, G::Weight)> {
+        if !self.contains_vertex(vertex) {
+            return Vec::new();
+        }
+        let mut edges = self.graph.weighted_edges(vertex);
+        edges.retain(|(edge, weight)| {
+            self.contains_vertex(edge.to) && (self.edge_filter)(*edge, weight)
+        });
+        edges
+    }
+}
+
+// Check that a vertex is in a view
+pub(crate) fn check_vertex<G: GraphView>(
+    graph: &G,
+    vertex: NodeId,
+) -> Result<(), DataStructureError> {
+    if vertex >= graph.vertex_bound() {
+        return Err(DataStructureError::VertexOutOfBounds {
+            vertex,
+            len: graph.vertex_bound(),
+        });
+    }
+    if !graph.contains_vertex(vertex) {
+        return Err(DataStructureError::RemovedVertex(vertex));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::algorithms::{astar, kruskal, max_flow, prim, Dijkstra};
+    use crate::graph::io::to_edge_list;
+    use crate::graph::vertex_graph::{EdgeKind, Graph};
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_views_match_subgraphs() {
+        let size = 40;
+        let mut rng = Lcg::new(31);
+        let mut graph = Graph::weighted();
+        for _ in 0..size {
+            graph.add_vertex(rng.next_u64() % 4);
+        }
+        for _ in 0..200 {
+            let seed = rng.next_u64();
+            let from = (seed >> 33) as usize % size;
+            let to = (seed >> 45) as usize % size;
+            let _ = graph.add_weighted_edge(from, to, (seed >> 20) % 30 + 1, EdgeKind::Directed);
+        }
+        graph.remove_vertex(7).unwrap();
+
+        for round in 0..4u64 {
+            let node_filter = |vertex: usize, value: &u64| *value != round && vertex % 5 != 1;
+            let edge_filter = |edge: EdgeId, weight: &u64| {
+                (edge.from + edge.to) as u64 % 4 != round || *weight > 20
+            };
+            let view = FilteredView::new(&graph, node_filter, edge_filter);
+            let copy = graph.subgraph(node_filter, edge_filter);
+
+            assert_eq!(view.vertex_bound(), copy.vertex_bound());
+            assert_eq!(view.len(), copy.len());
+            assert_eq!(
+                view.vertices().collect::<Vec<_>>(),
+                copy.vertices().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                view.edges().collect::<Vec<_>>(),
+                copy.edges().collect::<Vec<_>>()
+            );
+            for vertex in 0..size {
+                assert_eq!(view.with_value(vertex, |value| *value), copy.value(vertex));
+                assert_eq!(
+                    view.weighted_neighbors(vertex),
+                    GraphView::weighted_neighbors(&copy, vertex)
+                );
+            }
+            assert_eq!(to_edge_list(&view), to_edge_list(&copy));
+
+            // Every vertex and edge shown passes the filters and the graph has it
+            for edge in view.edges() {
+                let weight = graph.edge_weight(edge.from, edge.to).unwrap();
+                assert!(edge_filter(edge, &weight));
+                assert!(node_filter(edge.from, &graph.value(edge.from).unwrap()));
+                assert!(node_filter(edge.to, &graph.value(edge.to).unwrap()));
+            }
+
+            assert_eq!(kruskal(&view), kruskal(&copy));
+            assert_eq!(prim(&view), prim(&copy));
+            let vertices: Vec<usize> = view.vertices().collect();
+            for pair in vertices.windows(2) {
+                assert_eq!(
+                    astar(&view, pair[0], pair[1], Dijkstra),
+                    copy.shortest_path(pair[0], pair[1])
+                );
+                let (from_view, from_copy) = (
+                    max_flow(&view, pair[0], pair[1]).unwrap(),
+                    max_flow(&copy, pair[0], pair[1]).unwrap(),
+                );
+                assert_eq!(from_view.value, from_copy.value);
+                assert_eq!(from_view.cut, from_copy.cut);
+            }
+
+            let hidden = (0..size)
+                .find(|&vertex| graph.contains_vertex(vertex) && !view.contains_vertex(vertex));
+            let hidden = hidden.unwrap();
+            assert_eq!(
+                astar(&view, hidden, vertices[0], Dijkstra),
+                Err(DataStructureError::RemovedVertex(hidden))
+            );
+            assert_eq!(
+                max_flow(&view, vertices[0], size).err(),
+                Some(DataStructureError::VertexOutOfBounds {
+                    vertex: size,
+                    len: size
+                })
+            );
+        }
+    }
+}
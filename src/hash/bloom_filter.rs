@@ -0,0 +1,279 @@
+//! This module implements a Bloom filter, a compact set that answers "maybe present" or "surely absent".
+//! Each element sets a few bits chosen by hashing it, and a lookup checks those bits: if one is clear the element was
+//! never inserted, if all are set it probably was. The filter never gives a false negative, and its false positive
+//! rate is chosen when it is sized, which makes it a cheap guard in front of expensive lookups.
+//!
+//! # Performance
+//! - O(k) for `insert` and `contains`, k being the number of hashes, from a single hash of the element
+//! - About 9.6 bits per element for a 1% false positive rate, 4.8 more bits for every tenfold decrease
+//!
+//! # Implementation Details
+//! - The bits are packed in a `Vec<u64>`. The k bit positions come from one 64-bit hash by double hashing,
+//!   `h1 + i * h2`, which is as good as k independent hashes for a Bloom filter.
+//! - The hash comes from a `RandomState`, like the shard hash of `concurrent::lru`, so each filter hashes differently.
+//! - `with_rate` uses the optimal sizes, `m = -n ln p / ln² 2` bits and `k = m / n ln 2` hashes.
+//! - Elements can't be removed, since a bit may be shared by several elements. `clear` resets the whole filter.
+//!
+//! # Usage
+//! ```
+//! use data_structures::hash::bloom_filter::BloomFilter;
+//!
+//! let mut seen = BloomFilter::with_rate(1000, 0.01);
+//! seen.insert("https://example.com/a");
+//! seen.insert("https://example.com/b");
+//!
+//! assert!(seen.contains("https://example.com/a"));
+//! assert!(!seen.contains("https://example.com/c"));
+//! ```
+//!
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+/// Struct representing a Bloom filter over elements of type `T`
+///
+pub struct BloomFilter<T: ?Sized> {
+    bits: Vec<u64>,
+    bit_count: usize,
+    hash_count: u32,
+    hasher: RandomState,
+    insertions: usize,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> BloomFilter<T> {
+    /// Create an empty filter with a given size
+    /// # Arguments
+    /// * `bit_count`: The number of bits, at least 1
+    /// * `hash_count`: The number of bits set per element, at least 1
+    /// # Returns
+    /// A new BloomFilter instance
+    /// # Example
+    /// ```
+    /// use data_structures::hash::bloom_filter::BloomFilter;
+    ///
+    /// let filter: BloomFilter<u32> = BloomFilter::new(1024, 7);
+    /// assert_eq!(filter.bit_count(), 1024);
+    /// assert_eq!(filter.hash_count(), 7);
+    /// ```
+    pub fn new(bit_count: usize, hash_count: u32) -> Self {
+        let bit_count = bit_count.max(1);
+        BloomFilter {
+            bits: vec![0; bit_count.div_ceil(64)],
+            bit_count,
+            hash_count: hash_count.max(1),
+            hasher: RandomState::new(),
+            insertions: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create an empty filter sized for a number of elements and a false positive rate
+    /// # Arguments
+    /// * `expected_items`: The number of elements the filter is sized for
+    /// * `false_positive_rate`: The rate of false positives once those elements are inserted, between 0 and 1
+    /// # Returns
+    /// A new BloomFilter instance with the optimal number of bits and hashes
+    /// # Example
+    /// ```
+    /// use data_structures::hash::bloom_filter::BloomFilter;
+    ///
+    /// let filter: BloomFilter<u64> = BloomFilter::with_rate(1000, 0.01);
+    /// assert_eq!(filter.bit_count(), 9586);
+    /// assert_eq!(filter.hash_count(), 7);
+    /// ```
+    pub fn with_rate(expected_items: usize, false_positive_rate: f64) -> Self {
+        let (bit_count, hash_count) = optimal_size(expected_items, false_positive_rate);
+        Self::new(bit_count, hash_count)
+    }
+
+    /// Get the number of bits
+    /// # Returns
+    /// The size of the filter in bits
+    /// # Example
+    /// ```
+    /// use data_structures::hash::bloom_filter::BloomFilter;
+    ///
+    /// let filter: BloomFilter<str> = BloomFilter::new(100, 3);
+    /// assert_eq!(filter.bit_count(), 100);
+    /// ```
+    pub fn bit_count(&self) -> usize {
+        self.bit_count
+    }
+
+    /// Get the number of hashes
+    /// # Returns
+    /// The number of bits set per element
+    /// # Example
+    /// ```
+    /// use data_structures::hash::bloom_filter::BloomFilter;
+    ///
+    /// let filter: BloomFilter<str> = BloomFilter::new(100, 3);
+    /// assert_eq!(filter.hash_count(), 3);
+    /// ```
+    pub fn hash_count(&self) -> u32 {
+        self.hash_count
+    }
+
+    /// Get the number of insertions since the filter was created or cleared
+    /// # Returns
+    /// The number of calls to `insert`, repeated elements counted each time
+    /// # Example
+    /// ```
+    /// use data_structures::hash::bloom_filter::BloomFilter;
+    ///
+    /// let mut filter = BloomFilter::with_rate(10, 0.1);
+    /// filter.insert(&1);
+    /// filter.insert(&2);
+    /// assert_eq!(filter.insertions(), 2);
+    /// ```
+    pub fn insertions(&self) -> usize {
+        self.insertions
+    }
+
+    /// Add an element
+    /// # Arguments
+    /// * `value`: The element to add
+    /// # Example
+    /// ```
+    /// use data_structures::hash::bloom_filter::BloomFilter;
+    ///
+    /// let mut filter = BloomFilter::with_rate(10, 0.1);
+    /// filter.insert("key");
+    /// assert!(filter.contains("key"));
+    /// ```
+    pub fn insert(&mut self, value: &T) {
+        for bit in self.positions(value) {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+        self.insertions += 1;
+    }
+
+    /// Check if an element may have been added
+    /// # Arguments
+    /// * `value`: The element to check
+    /// # Returns
+    /// False if the element was surely never added, true if it probably was
+    /// # Example
+    /// ```
+    /// use data_structures::hash::bloom_filter::BloomFilter;
+    ///
+    /// let mut filter = BloomFilter::with_rate(100, 0.001);
+    /// filter.insert(&42);
+    ///
+    /// assert!(filter.contains(&42));
+    /// assert!(!filter.contains(&7));
+    /// ```
+    pub fn contains(&self, value: &T) -> bool {
+        self.positions(value)
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Remove every element
+    /// # Example
+    /// ```
+    /// use data_structures::hash::bloom_filter::BloomFilter;
+    ///
+    /// let mut filter = BloomFilter::with_rate(10, 0.1);
+    /// filter.insert(&1);
+    /// filter.clear();
+    /// assert!(!filter.contains(&1));
+    /// ```
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+        self.insertions = 0;
+    }
+
+    /// Estimate the false positive rate from the bits set so far
+    /// # Returns
+    /// The probability that an element never added is reported as present, the fraction of set bits to the power k
+    /// # Example
+    /// ```
+    /// use data_structures::hash::bloom_filter::BloomFilter;
+    ///
+    /// let mut filter = BloomFilter::with_rate(1000, 0.01);
+    /// assert_eq!(filter.estimated_false_positive_rate(), 0.0);
+    ///
+    /// for i in 0..1000 {
+    ///     filter.insert(&i);
+    /// }
+    /// assert!(filter.estimated_false_positive_rate() < 0.02);
+    /// ```
+    pub fn estimated_false_positive_rate(&self) -> f64 {
+        let set: u32 = self.bits.iter().map(|word| word.count_ones()).sum();
+        (set as f64 / self.bit_count as f64).powi(self.hash_count as i32)
+    }
+
+    // Bit positions of an element, by double hashing of a single hash
+    fn positions(&self, value: &T) -> impl Iterator<Item = usize> {
+        let hash = self.hasher.hash_one(value);
+        let h1 = hash as u32 as u64;
+        let h2 = (hash >> 32) | 1; // odd, so the positions don't collapse onto one another
+        let bit_count = self.bit_count as u64;
+        (0..self.hash_count as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bit_count) as usize)
+    }
+}
+
+impl<T: ?Sized> Clone for BloomFilter<T> {
+    /// Copies the bits and the hasher, so the copy answers like the original
+    fn clone(&self) -> Self {
+        BloomFilter {
+            bits: self.bits.clone(),
+            bit_count: self.bit_count,
+            hash_count: self.hash_count,
+            hasher: self.hasher.clone(),
+            insertions: self.insertions,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for BloomFilter<T> {
+    /// Prints the size of the filter and the number of insertions, not the bits
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BloomFilter")
+            .field("bit_count", &self.bit_count)
+            .field("hash_count", &self.hash_count)
+            .field("insertions", &self.insertions)
+            .finish()
+    }
+}
+
+// Optimal number of bits and hashes for a number of elements and a false positive rate
+fn optimal_size(expected_items: usize, false_positive_rate: f64) -> (usize, u32) {
+    let items = expected_items.max(1) as f64;
+    let rate = false_positive_rate.clamp(f64::MIN_POSITIVE, 0.5);
+    let ln2 = std::f64::consts::LN_2;
+
+    let bit_count = (-items * rate.ln() / (ln2 * ln2)).ceil() as usize;
+    let hash_count = (bit_count as f64 / items * ln2).round() as u32;
+    (bit_count, hash_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom_filter_rates() {
+        for rate in [0.1, 0.01, 0.001] {
+            let mut filter = BloomFilter::with_rate(10_000, rate);
+            for i in 0..10_000u64 {
+                filter.insert(&(i * 2));
+            }
+
+            // No false negatives
+            assert!((0..10_000u64).all(|i| filter.contains(&(i * 2))));
+
+            // False positives close to the target, odd numbers were never inserted
+            let false_positives = (0..100_000u64)
+                .filter(|i| filter.contains(&(i * 2 + 1)))
+                .count();
+            let observed = false_positives as f64 / 100_000.0;
+            assert!(observed < rate * 1.5, "{} observed for {}", observed, rate);
+            assert!((filter.estimated_false_positive_rate() - rate).abs() < rate * 0.5);
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! This module implements a fixed capacity hash set with cuckoo hashing, for membership checks whose cost mustn't vary.
+//! Each element can only live in one of two buckets, chosen by two independent hashes, or in a small stash. A lookup
+//! reads those two buckets and the stash and nothing else, so it is O(1) in the worst case, where a probing `HashSet`
+//! may have to walk a long cluster. An insertion into two full buckets kicks an element out to its other bucket, which
+//! may kick another one, until a free slot is found.
+//!
+//! # Performance
+//! - O(1) worst case for `contains` and `remove`: 2 buckets of `BUCKET_SIZE` slots and the `STASH_SIZE` stash
+//! - O(1) expected for `insert`, at most `MAX_KICKS` displacements
+//! - The memory is allocated once, for the capacity given at creation
+//!
+//! # Implementation Details
+//! - The slots are one `Vec` of `Option<T>`, split into buckets of `BUCKET_SIZE`. There are enough buckets to keep the
+//!   table at most 80% full, where bucketed cuckoo hashing almost never needs the stash.
+//! - The two hashes come from two `RandomState`s, like the shard hash of `concurrent::lru`.
+//! - An insertion that runs out of kicks parks the homeless element in the stash. If the stash is full too, the kicks
+//!   are undone in reverse order and the insertion fails with `DataStructureError::Full`, leaving the set unchanged.
+//! - A removal moves stashed elements back to a bucket when one of theirs has room.
+//!
+//! # Usage
+//! ```
+//! use data_structures::hash::cuckoo_set::CuckooSet;
+//!
+//! let mut blocked = CuckooSet::with_capacity(1000);
+//! blocked.insert("10.0.0.7").unwrap();
+//! blocked.insert("10.0.0.9").unwrap();
+//!
+//! assert!(blocked.contains(&"10.0.0.7"));
+//! assert!(!blocked.contains(&"10.0.0.8"));
+//! assert_eq!(blocked.remove(&"10.0.0.9"), Some("10.0.0.9"));
+//! ```
+//!
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+use crate::error::DataStructureError;
+
+/// The number of slots of a bucket
+pub const BUCKET_SIZE: usize = 4;
+
+/// The number of elements the stash can hold
+pub const STASH_SIZE: usize = 8;
+
+/// The maximum number of elements moved by one insertion
+pub const MAX_KICKS: usize = 500;
+
+/// Struct representing a fixed capacity set with cuckoo hashing
+///
+pub struct CuckooSet<T> {
+    slots: Vec<Option<T>>, // buckets of BUCKET_SIZE slots
+    stash: Vec<T>,         // at most STASH_SIZE elements
+    hashers: [RandomState; 2],
+    capacity: usize,
+    len: usize,
+    seed: u64, // state of the generator choosing the element to kick
+}
+
+impl<T: Hash + Eq> CuckooSet<T> {
+    /// Create a new empty set, allocating all of its memory
+    /// # Arguments
+    /// * `capacity`: The maximum number of elements, at least 1
+    /// # Returns
+    /// A new CuckooSet instance
+    /// # Example
+    /// ```
+    /// use data_structures::hash::cuckoo_set::CuckooSet;
+    ///
+    /// let set: CuckooSet<u64> = CuckooSet::with_capacity(100);
+    /// assert_eq!(set.capacity(), 100);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        // At most 80% of the slots are used
+        let buckets = (capacity * 5 / 4).div_ceil(BUCKET_SIZE).max(2);
+
+        CuckooSet {
+            slots: (0..buckets * BUCKET_SIZE).map(|_| None).collect(),
+            stash: Vec::with_capacity(STASH_SIZE),
+            hashers: [RandomState::new(), RandomState::new()],
+            capacity,
+            len: 0,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    /// Get the maximum number of elements
+    /// # Returns
+    /// The capacity given at creation
+    /// # Example
+    /// ```
+    /// use data_structures::hash::cuckoo_set::CuckooSet;
+    ///
+    /// let set: CuckooSet<u64> = CuckooSet::with_capacity(0);
+    /// assert_eq!(set.capacity(), 1);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the number of elements
+    /// # Returns
+    /// The number of elements in the set
+    /// # Example
+    /// ```
+    /// use data_structures::hash::cuckoo_set::CuckooSet;
+    ///
+    /// let mut set = CuckooSet::with_capacity(10);
+    /// set.insert('a').unwrap();
+    /// set.insert('a').unwrap();
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the set is empty
+    /// # Returns
+    /// True if the set has no elements
+    /// # Example
+    /// ```
+    /// use data_structures::hash::cuckoo_set::CuckooSet;
+    ///
+    /// let set: CuckooSet<u8> = CuckooSet::with_capacity(10);
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of elements parked in the stash
+    /// # Returns
+    /// The number of elements that found no room in their buckets, at most `STASH_SIZE`
+    /// # Example
+    /// ```
+    /// use data_structures::hash::cuckoo_set::CuckooSet;
+    ///
+    /// let set: CuckooSet<u8> = (0..=255).collect();
+    /// assert!(set.stash_len() <= 8);
+    /// ```
+    pub fn stash_len(&self) -> usize {
+        self.stash.len()
+    }
+
+    /// Check if an element is in the set, reading at most two buckets and the stash
+    /// # Arguments
+    /// * `value`: The element to look for
+    /// # Returns
+    /// True if the element is in the set
+    /// # Example
+    /// ```
+    /// use data_structures::hash::cuckoo_set::CuckooSet;
+    ///
+    /// let set: CuckooSet<u32> = (0..100).collect();
+    /// assert!(set.contains(&42));
+    /// assert!(!set.contains(&100));
+    /// ```
+    pub fn contains(&self, value: &T) -> bool {
+        self.find(value).is_some() || self.stash.contains(value)
+    }
+
+    /// Insert an element
+    /// # Arguments
+    /// * `value`: The element to be inserted
+    /// # Returns
+    /// Result<bool, DataStructureError>
+    /// Ok(true) if the element was inserted, Ok(false) if it was already in the set, Err(DataStructureError::Full) if
+    /// the set is at its capacity or no room was found for it, in which case the set is unchanged
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::hash::cuckoo_set::CuckooSet;
+    ///
+    /// let mut set = CuckooSet::with_capacity(2);
+    /// assert_eq!(set.insert(1), Ok(true));
+    /// assert_eq!(set.insert(1), Ok(false));
+    /// assert_eq!(set.insert(2), Ok(true));
+    /// assert_eq!(set.insert(3), Err(DataStructureError::Full));
+    /// ```
+    pub fn insert(&mut self, value: T) -> Result<bool, DataStructureError> {
+        if self.contains(&value) {
+            return Ok(false);
+        }
+        if self.len == self.capacity {
+            return Err(DataStructureError::Full);
+        }
+
+        let mut homeless = value;
+        let mut bucket = self.buckets(&homeless)[0];
+        let mut kicks: Vec<usize> = Vec::new();
+
+        loop {
+            let [first, second] = self.buckets(&homeless);
+            if let Some(slot) = self.free_slot(first).or_else(|| self.free_slot(second)) {
+                self.slots[slot] = Some(homeless);
+                break;
+            }
+            if kicks.len() == MAX_KICKS {
+                if self.stash.len() < STASH_SIZE {
+                    self.stash.push(homeless);
+                    break;
+                }
+                // Put every kicked element back where it was, which leaves the new element homeless again
+                for slot in kicks.into_iter().rev() {
+                    homeless = self.slots[slot].replace(homeless).unwrap();
+                }
+                return Err(DataStructureError::Full);
+            }
+
+            // Swap with a random element of the bucket, which then tries its other bucket
+            let slot = bucket * BUCKET_SIZE + self.random() % BUCKET_SIZE;
+            homeless = self.slots[slot].replace(homeless).unwrap();
+            kicks.push(slot);
+
+            let [first, second] = self.buckets(&homeless);
+            bucket = if first == bucket { second } else { first };
+        }
+
+        self.len += 1;
+        Ok(true)
+    }
+
+    /// Remove an element from the set
+    /// # Arguments
+    /// * `value`: The element to remove
+    /// # Returns
+    /// The removed element, or None if it wasn't in the set
+    /// # Example
+    /// ```
+    /// use data_structures::hash::cuckoo_set::CuckooSet;
+    ///
+    /// let mut set: CuckooSet<&str> = ["a", "b"].into_iter().collect();
+    /// assert_eq!(set.remove(&"a"), Some("a"));
+    /// assert_eq!(set.remove(&"a"), None);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let removed = match self.find(value) {
+            Some(slot) => self.slots[slot].take(),
+            None => {
+                let index = self.stash.iter().position(|other| other == value)?;
+                Some(self.stash.swap_remove(index))
+            }
+        };
+        self.len -= 1;
+
+        // A freed slot may be one of the buckets of a stashed element
+        let mut index = 0;
+        while index < self.stash.len() {
+            let [first, second] = self.buckets(&self.stash[index]);
+            match self.free_slot(first).or_else(|| self.free_slot(second)) {
+                Some(slot) => self.slots[slot] = Some(self.stash.swap_remove(index)),
+                None => index += 1,
+            }
+        }
+        removed
+    }
+
+    /// Remove every element, keeping the memory
+    /// # Example
+    /// ```
+    /// use data_structures::hash::cuckoo_set::CuckooSet;
+    ///
+    /// let mut set: CuckooSet<u32> = (0..10).collect();
+    /// set.clear();
+    /// assert!(set.is_empty());
+    /// assert_eq!(set.capacity(), 10);
+    /// ```
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.stash.clear();
+        self.len = 0;
+    }
+
+    /// Get an iterator over the elements, in no particular order
+    /// # Returns
+    /// An iterator of references to the elements
+    /// # Example
+    /// ```
+    /// use data_structures::hash::cuckoo_set::CuckooSet;
+    ///
+    /// let set: CuckooSet<u32> = (1..=4).collect();
+    /// assert_eq!(set.iter().sum::<u32>(), 10);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.slots.iter().flatten().chain(self.stash.iter())
+    }
+
+    // Get the two buckets an element can live in
+    fn buckets(&self, value: &T) -> [usize; 2] {
+        let buckets = (self.slots.len() / BUCKET_SIZE) as u64;
+        self.hashers
+            .each_ref()
+            .map(|hasher| (hasher.hash_one(value) % buckets) as usize)
+    }
+
+    // Find the slot holding an element in its buckets
+    fn find(&self, value: &T) -> Option<usize> {
+        self.buckets(value).into_iter().find_map(|bucket| {
+            (bucket * BUCKET_SIZE..(bucket + 1) * BUCKET_SIZE)
+                .find(|&slot| self.slots[slot].as_ref() == Some(value))
+        })
+    }
+
+    fn free_slot(&self, bucket: usize) -> Option<usize> {
+        (bucket * BUCKET_SIZE..(bucket + 1) * BUCKET_SIZE).find(|&slot| self.slots[slot].is_none())
+    }
+
+    fn random(&mut self) -> usize {
+        self.seed = self
+            .seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.seed >> 33) as usize
+    }
+}
+
+impl<T: Hash + Eq> FromIterator<T> for CuckooSet<T> {
+    /// Builds a set with the number of elements as its capacity
+    /// # Panics
+    /// If an element finds no room, which is very unlikely below the capacity
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values: Vec<T> = iter.into_iter().collect();
+        let mut set = CuckooSet::with_capacity(values.len());
+        for value in values {
+            set.insert(value).expect("No room for an element");
+        }
+        set
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CuckooSet<T> {
+    /// Prints the elements as a set
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.slots.iter().flatten().chain(self.stash.iter()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_cuckoo_set_against_hash_set() {
+        let mut set = CuckooSet::with_capacity(1_000);
+        let mut reference = HashSet::new();
+        let mut rng = Lcg::new(41);
+
+        for _ in 0..20_000 {
+            let seed = rng.next_u64();
+            let value = (seed >> 33) % 2_000;
+
+            match seed >> 62 {
+                0 => assert_eq!(set.remove(&value), reference.take(&value)),
+                1 => assert_eq!(set.contains(&value), reference.contains(&value)),
+                _ if reference.len() == 1_000 && !reference.contains(&value) => {
+                    assert_eq!(set.insert(value), Err(DataStructureError::Full));
+                }
+                _ => assert_eq!(set.insert(value), Ok(reference.insert(value))),
+            }
+            assert_eq!(set.len(), reference.len());
+            assert!(set.stash_len() <= STASH_SIZE);
+        }
+
+        assert_eq!(
+            set.iter().collect::<HashSet<_>>(),
+            reference.iter().collect()
+        );
+    }
+
+    #[test]
+    fn test_failed_insertion_leaves_the_set_unchanged() {
+        // Elements hashing alike all compete for the same two buckets, then the stash
+        #[derive(Debug, PartialEq, Eq)]
+        struct Collide(u32);
+        impl Hash for Collide {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                0.hash(state);
+            }
+        }
+
+        let mut set = CuckooSet::with_capacity(100);
+        let mut room = 0;
+        while set.insert(Collide(room)) == Ok(true) {
+            room += 1;
+        }
+        assert!(room as usize >= BUCKET_SIZE + STASH_SIZE);
+        assert_eq!(set.stash_len(), STASH_SIZE);
+
+        assert_eq!(set.len(), room as usize);
+        assert!((0..room).all(|value| set.contains(&Collide(value))));
+        assert!(!set.contains(&Collide(room)));
+
+        // Removing a bucketed element lets a stashed one move back
+        let bucketed = (0..room)
+            .find(|&value| set.find(&Collide(value)).is_some())
+            .unwrap();
+        set.remove(&Collide(bucketed));
+        assert_eq!(set.stash_len(), STASH_SIZE - 1);
+        assert_eq!(set.insert(Collide(room)), Ok(true));
+    }
+
+    #[test]
+    fn test_cuckoo_set_fills_to_capacity() {
+        // Every capacity up to a few thousand elements can be filled, keeping lookups within the two buckets and stash
+        for capacity in [1, 7, 64, 1_000, 5_000] {
+            let mut set = CuckooSet::with_capacity(capacity);
+            for value in 0..capacity {
+                assert_eq!(set.insert(value), Ok(true));
+            }
+            assert_eq!(set.insert(capacity), Err(DataStructureError::Full));
+            assert!((0..capacity).all(|value| set.contains(&value)));
+            assert!(set.slots.len() <= (capacity * 5 / 4 + 2 * BUCKET_SIZE).max(8));
+        }
+    }
+}
@@ -0,0 +1,475 @@
+//! This module implements a map wrapper that puts a Bloom filter in front of another map.
+//! Every key inserted through the wrapper is added to the filter, so a lookup for a key that was never inserted is
+//! rejected by the filter without reaching the map. This pays off when the map is expensive to query, being large,
+//! remote or on disk, and most lookups are misses, as in caches of negative results or deduplication.
+//!
+//! # Performance
+//! - O(k) on top of the map for `get`, `contains_key` and `insert`, k being the number of hashes of the filter
+//! - A miss reaches the map only with the false positive rate of the filter
+//! - O(n) for `rebuild`
+//!
+//! # Implementation Details
+//! - The wrapped map is any type implementing `BackingMap`, which is implemented for `HashMap` and `BTreeMap`.
+//! - Removed keys can't be taken out of the filter, so they keep answering "maybe" and count as false positives until
+//!   `rebuild` creates a new filter from the keys left in the map, possibly with another size or rate.
+//! - `FilterStats` counts the hits, the misses rejected by the filter and the false positives that reached the map.
+//!
+//! # Usage
+//! ```
+//! use std::collections::HashMap;
+//! use data_structures::hash::filtered_map::FilteredMap;
+//!
+//! let mut users = FilteredMap::new(HashMap::new(), 1000, 0.01);
+//! users.insert("alice", 1);
+//! users.insert("bob", 2);
+//!
+//! assert_eq!(users.get(&"alice"), Some(&1));
+//! assert_eq!(users.get(&"mallory"), None);
+//!
+//! let stats = users.stats();
+//! assert_eq!(stats.hits, 1);
+//! assert_eq!(stats.filtered + stats.false_positives, 1);
+//! ```
+//!
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use super::bloom_filter::BloomFilter;
+
+/// Operations a map needs to be wrapped by a `FilteredMap`
+pub trait BackingMap<K, V> {
+    /// Returns the value of a key
+    fn get(&self, key: &K) -> Option<&V>;
+    /// Inserts a key, returning its previous value
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+    /// Removes a key, returning its value
+    fn remove(&mut self, key: &K) -> Option<V>;
+    /// Returns the number of keys
+    fn len(&self) -> usize;
+    /// Returns true if there are no keys
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Returns an iterator over the keys, used to rebuild the filter
+    fn keys(&self) -> Box<dyn Iterator<Item = &K> + '_>;
+}
+
+impl<K: Hash + Eq, V> BackingMap<K, V> for HashMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        HashMap::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn keys(&self) -> Box<dyn Iterator<Item = &K> + '_> {
+        Box::new(HashMap::keys(self))
+    }
+}
+
+impl<K: Ord, V> BackingMap<K, V> for BTreeMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        BTreeMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        BTreeMap::remove(self, key)
+    }
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn keys(&self) -> Box<dyn Iterator<Item = &K> + '_> {
+        Box::new(BTreeMap::keys(self))
+    }
+}
+
+/// Lookup counters of a filtered map
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FilterStats {
+    pub hits: u64,
+    pub filtered: u64,
+    pub false_positives: u64,
+}
+
+impl FilterStats {
+    /// Get the ratio of misses that the filter let through to the map
+    /// # Returns
+    /// The observed false positive rate between 0.0 and 1.0, or 0.0 if there was no miss
+    /// # Example
+    /// ```
+    /// use data_structures::hash::filtered_map::FilterStats;
+    ///
+    /// let stats = FilterStats { hits: 10, filtered: 99, false_positives: 1 };
+    /// assert_eq!(stats.false_positive_rate(), 0.01);
+    /// ```
+    pub fn false_positive_rate(&self) -> f64 {
+        let misses = self.filtered + self.false_positives;
+        if misses == 0 {
+            0.0
+        } else {
+            self.false_positives as f64 / misses as f64
+        }
+    }
+}
+
+/// Struct representing a map guarded by a Bloom filter of its keys
+///
+#[derive(Debug)]
+pub struct FilteredMap<K, V, M> {
+    map: M,
+    filter: BloomFilter<K>,
+    stats: FilterStats,
+    _marker: std::marker::PhantomData<fn() -> V>,
+}
+
+impl<K: Hash, V, M: BackingMap<K, V>> FilteredMap<K, V, M> {
+    /// Wrap a map, sizing the filter for a number of keys and a false positive rate
+    /// # Arguments
+    /// * `map`: The map to be wrapped, whose keys are added to the filter
+    /// * `expected_items`: The number of keys the filter is sized for
+    /// * `false_positive_rate`: The rate of misses reaching the map once those keys are inserted, between 0 and 1
+    /// # Returns
+    /// A new FilteredMap instance
+    /// # Example
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let existing = BTreeMap::from([(1, "one"), (2, "two")]);
+    /// let map = FilteredMap::new(existing, 100, 0.01);
+    /// assert!(map.contains_key(&2));
+    /// ```
+    pub fn new(map: M, expected_items: usize, false_positive_rate: f64) -> Self {
+        let mut filter = BloomFilter::with_rate(expected_items, false_positive_rate);
+        for key in map.keys() {
+            filter.insert(key);
+        }
+        FilteredMap {
+            map,
+            filter,
+            stats: FilterStats::default(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Get the number of keys
+    /// # Returns
+    /// The number of keys of the wrapped map
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let mut map = FilteredMap::new(HashMap::new(), 10, 0.1);
+    /// map.insert('a', 1);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Check if the map is empty
+    /// # Returns
+    /// True if the wrapped map has no keys
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let map: FilteredMap<u32, u32, _> = FilteredMap::new(HashMap::new(), 10, 0.1);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Get the wrapped map
+    /// # Returns
+    /// A reference to the map, for the lookups that don't need the filter
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let mut map = FilteredMap::new(HashMap::new(), 10, 0.1);
+    /// map.insert("k", 1);
+    /// assert_eq!(map.inner().get("k"), Some(&1));
+    /// ```
+    pub fn inner(&self) -> &M {
+        &self.map
+    }
+
+    /// Get the Bloom filter
+    /// # Returns
+    /// A reference to the filter, to check its size and estimated false positive rate
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let map: FilteredMap<u64, (), _> = FilteredMap::new(HashMap::new(), 1000, 0.01);
+    /// assert_eq!(map.filter().hash_count(), 7);
+    /// ```
+    pub fn filter(&self) -> &BloomFilter<K> {
+        &self.filter
+    }
+
+    /// Get the lookup counters
+    /// # Returns
+    /// The hits, filtered misses and false positives since the creation or the last reset
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let mut map = FilteredMap::new(HashMap::new(), 10, 0.1);
+    /// map.insert(1, 'a');
+    /// map.get(&1);
+    /// assert_eq!(map.stats().hits, 1);
+    /// ```
+    pub fn stats(&self) -> FilterStats {
+        self.stats
+    }
+
+    /// Reset the lookup counters
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::{FilteredMap, FilterStats};
+    ///
+    /// let mut map = FilteredMap::new(HashMap::new(), 10, 0.1);
+    /// map.insert(1, 'a');
+    /// map.get(&1);
+    /// map.reset_stats();
+    /// assert_eq!(map.stats(), FilterStats::default());
+    /// ```
+    pub fn reset_stats(&mut self) {
+        self.stats = FilterStats::default();
+    }
+
+    /// Get the value of a key, asking the map only if the filter doesn't rule the key out
+    /// # Arguments
+    /// * `key`: The key to be looked up
+    /// # Returns
+    /// A reference to the value, or None if the key isn't in the map
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let mut map = FilteredMap::new(HashMap::new(), 100, 0.001);
+    /// map.insert("red", 0xff0000);
+    ///
+    /// assert_eq!(map.get(&"red"), Some(&0xff0000));
+    /// assert_eq!(map.get(&"blue"), None);
+    /// assert_eq!(map.stats().filtered, 1);
+    /// ```
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if !self.filter.contains(key) {
+            self.stats.filtered += 1;
+            return None;
+        }
+
+        let value = self.map.get(key);
+        if value.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.false_positives += 1;
+        }
+        value
+    }
+
+    /// Check if a key is in the map, without counting the lookup
+    /// # Arguments
+    /// * `key`: The key to be checked
+    /// # Returns
+    /// True if the key is in the map
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let mut map = FilteredMap::new(HashMap::new(), 100, 0.01);
+    /// map.insert(5, ());
+    ///
+    /// assert!(map.contains_key(&5));
+    /// assert!(!map.contains_key(&6));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.filter.contains(key) && self.map.get(key).is_some()
+    }
+
+    /// Insert a key in the map and the filter
+    /// # Arguments
+    /// * `key`: The key
+    /// * `value`: Its value
+    /// # Returns
+    /// The previous value of the key, if it was present
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let mut map = FilteredMap::new(HashMap::new(), 10, 0.1);
+    /// assert_eq!(map.insert("k", 1), None);
+    /// assert_eq!(map.insert("k", 2), Some(1));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.filter.insert(&key);
+        self.map.insert(key, value)
+    }
+
+    /// Remove a key from the map. The filter keeps its bits until the next `rebuild`
+    /// # Arguments
+    /// * `key`: The key to be removed
+    /// # Returns
+    /// The value of the key, or None if it wasn't present
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let mut map = FilteredMap::new(HashMap::new(), 10, 0.1);
+    /// map.insert("k", 1);
+    ///
+    /// assert_eq!(map.remove(&"k"), Some(1));
+    /// assert_eq!(map.get(&"k"), None);
+    /// assert_eq!(map.stats().false_positives, 1); // the filter still knew the key
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.filter.contains(key) {
+            return None;
+        }
+        self.map.remove(key)
+    }
+
+    /// Replace the filter by a new one holding only the keys of the map
+    /// # Arguments
+    /// * `expected_items`: The number of keys the new filter is sized for
+    /// * `false_positive_rate`: The target false positive rate of the new filter, between 0 and 1
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let mut map = FilteredMap::new(HashMap::new(), 10, 0.1);
+    /// for i in 0..100 {
+    ///     map.insert(i, ());
+    /// }
+    /// assert!(map.filter().estimated_false_positive_rate() > 0.1);
+    ///
+    /// map.rebuild(1000, 0.01);
+    /// assert!(map.filter().estimated_false_positive_rate() < 0.01);
+    /// assert!(map.contains_key(&99));
+    /// ```
+    pub fn rebuild(&mut self, expected_items: usize, false_positive_rate: f64) {
+        let mut filter = BloomFilter::with_rate(expected_items, false_positive_rate);
+        for key in self.map.keys() {
+            filter.insert(key);
+        }
+        self.filter = filter;
+    }
+
+    /// Unwrap the map
+    /// # Returns
+    /// The wrapped map, the filter being dropped
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use data_structures::hash::filtered_map::FilteredMap;
+    ///
+    /// let mut map = FilteredMap::new(HashMap::new(), 10, 0.1);
+    /// map.insert(1, 2);
+    /// assert_eq!(map.into_inner(), HashMap::from([(1, 2)]));
+    /// ```
+    pub fn into_inner(self) -> M {
+        self.map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Map counting the lookups that reach it, standing in for an expensive store
+    struct CountingMap {
+        map: HashMap<u64, u64>,
+        lookups: std::cell::Cell<u64>,
+    }
+
+    impl BackingMap<u64, u64> for CountingMap {
+        fn get(&self, key: &u64) -> Option<&u64> {
+            self.lookups.set(self.lookups.get() + 1);
+            self.map.get(key)
+        }
+
+        fn insert(&mut self, key: u64, value: u64) -> Option<u64> {
+            self.map.insert(key, value)
+        }
+
+        fn remove(&mut self, key: &u64) -> Option<u64> {
+            self.map.remove(key)
+        }
+
+        fn len(&self) -> usize {
+            self.map.len()
+        }
+
+        fn keys(&self) -> Box<dyn Iterator<Item = &u64> + '_> {
+            Box::new(self.map.keys())
+        }
+    }
+
+    #[test]
+    fn test_filtered_map_skips_the_map_on_misses() {
+        let backing = CountingMap {
+            map: HashMap::new(),
+            lookups: std::cell::Cell::new(0),
+        };
+        let mut map = FilteredMap::new(backing, 10_000, 0.01);
+        for key in 0..10_000 {
+            map.insert(key * 2, key);
+        }
+
+        for key in 0..20_000 {
+            assert_eq!(map.get(&key).copied(), (key % 2 == 0).then_some(key / 2));
+        }
+
+        let stats = map.stats();
+        assert_eq!(stats.hits, 10_000);
+        assert_eq!(stats.filtered + stats.false_positives, 10_000);
+        assert!(stats.false_positive_rate() < 0.02);
+        assert_eq!(
+            map.inner().lookups.get(),
+            stats.hits + stats.false_positives
+        );
+
+        // Removed keys reach the map until the filter is rebuilt
+        for key in 0..5_000 {
+            map.remove(&(key * 2));
+        }
+        map.reset_stats();
+        (0..5_000).for_each(|key| assert_eq!(map.get(&(key * 2)), None));
+        assert_eq!(map.stats().false_positives, 5_000);
+
+        map.rebuild(10_000, 0.01);
+        map.reset_stats();
+        (0..5_000).for_each(|key| assert_eq!(map.get(&(key * 2)), None));
+        assert!(map.stats().false_positive_rate() < 0.02);
+    }
+}
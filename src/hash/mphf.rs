@@ -0,0 +1,343 @@
+//! This module implements a minimal perfect hash function (MPHF) over a static set of keys, with the BDZ algorithm.
+//! Built once from n distinct keys, it maps each of them to its own index in `0..n` without storing the keys, in less
+//! than 3 bits per key. It is meant for read-only dictionaries shipped with an application: the keys index a plain
+//! `Vec`, and `static_map::perfect_hash_map::StaticMap` does exactly that.
+//!
+//! # Performance
+//! - O(n) expected to build the function, O(n log n) with the duplicate check
+//! - O(1) for `index`: one hash of the key, three 2-bit reads and a rank over at most 4 words
+//! - About 2.8 bits per key: 2 bits for each of the 1.23n vertices and a 64-bit rank every 256 vertices
+//!
+//! # Implementation Details
+//! - Each key is an edge joining three vertices, one in each third of a hypergraph of 1.23n vertices. Edges are peeled
+//!   off vertices of degree one, tracked with the XOR of their incident edges, and a failed peeling is retried with
+//!   another seed. Above 1.22n vertices, a random 3-hypergraph peels completely with high probability.
+//! - Walking the peeling order backwards, each edge gives its free vertex a 2-bit value `g` so that the sum of the
+//!   three values modulo 3 picks that vertex. Vertices no edge picked keep the value 3, which counts as 0 in the sum.
+//! - The index of a key is the rank of its vertex among the picked ones, so the indexes are exactly `0..n`.
+//! - Keys are hashed once with `DefaultHasher::new()`, whose keys are fixed, and the seed only remixes that hash.
+//!   Two keys with the same hash could never be separated, so equal hashes are reported as
+//!   `DataStructureError::DuplicateKeys`.
+//!
+//! # Usage
+//! ```
+//! use data_structures::hash::mphf::Mphf;
+//!
+//! let colors = ["red", "green", "blue", "cyan", "magenta", "yellow"];
+//! let mphf = Mphf::new(&colors).unwrap();
+//!
+//! let mut indexes: Vec<usize> = colors.iter().map(|color| mphf.index(color).unwrap()).collect();
+//! indexes.sort();
+//! assert_eq!(indexes, vec![0, 1, 2, 3, 4, 5]);
+//! ```
+//!
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::error::DataStructureError;
+
+/// The number of vertices of the hypergraph per key
+const VERTICES_PER_KEY: f64 = 1.23;
+
+/// The number of vertices covered by each entry of the rank directory
+const RANK_BLOCK: usize = 256;
+
+/// The value of the vertices picked by no key
+const UNUSED: u64 = 3;
+
+/// Struct representing a minimal perfect hash function over a static set of keys
+///
+#[derive(Debug, Clone)]
+pub struct Mphf {
+    values: Vec<u64>, // 2-bit values of the vertices, 32 per word
+    ranks: Vec<u64>,  // number of picked vertices before each block of RANK_BLOCK vertices
+    part: usize,      // number of vertices in each third of the hypergraph
+    seed: u64,
+    len: usize,
+}
+
+impl Mphf {
+    /// Build the function over a set of keys
+    /// # Arguments
+    /// * `keys`: The distinct keys to be indexed
+    /// # Returns
+    /// Ok with the function, or Err(DataStructureError::DuplicateKeys) if two keys have the same hash, which in
+    /// practice means the same key was given twice
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::hash::mphf::Mphf;
+    ///
+    /// let mphf = Mphf::new(&[10, 20, 30]).unwrap();
+    /// assert_eq!(mphf.len(), 3);
+    ///
+    /// assert_eq!(Mphf::new(&[1, 2, 1]).unwrap_err(), DataStructureError::DuplicateKeys);
+    /// ```
+    pub fn new<'a, K, I>(keys: I) -> Result<Self, DataStructureError>
+    where
+        K: Hash + ?Sized + 'a,
+        I: IntoIterator<Item = &'a K>,
+    {
+        let hashes: Vec<u64> = keys.into_iter().map(key_hash).collect();
+
+        let mut sorted = hashes.clone();
+        sorted.sort_unstable();
+        if sorted.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(DataStructureError::DuplicateKeys);
+        }
+        drop(sorted);
+
+        // At least 2 vertices per third, so that two keys can always end up on different edges
+        let len = hashes.len();
+        let part = ((len as f64 * VERTICES_PER_KEY / 3.0).ceil() as usize).max(2);
+        let mut seed = 0;
+        loop {
+            let edges: Vec<[usize; 3]> = hashes
+                .iter()
+                .map(|&hash| vertices(hash, seed, part))
+                .collect();
+            if let Some(order) = peel(&edges, 3 * part) {
+                return Ok(Self::assign(&edges, &order, part, seed));
+            }
+            seed += 1;
+        }
+    }
+
+    /// Get the number of keys
+    /// # Returns
+    /// The number of keys the function was built over
+    /// # Example
+    /// ```
+    /// use data_structures::hash::mphf::Mphf;
+    ///
+    /// let mphf = Mphf::new(&["a", "b"]).unwrap();
+    /// assert_eq!(mphf.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the function was built over no key
+    /// # Returns
+    /// True if there are no keys
+    /// # Example
+    /// ```
+    /// use data_structures::hash::mphf::Mphf;
+    ///
+    /// let mphf = Mphf::new(Vec::<&u32>::new()).unwrap();
+    /// assert!(mphf.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the index of a key
+    /// # Arguments
+    /// * `key`: The key to be looked up
+    /// # Returns
+    /// Its index below `len` if the key is one of the set. Other keys get None when the function can tell, and an
+    /// arbitrary index otherwise, so the caller must check the key stored at that index
+    /// # Example
+    /// ```
+    /// use data_structures::hash::mphf::Mphf;
+    ///
+    /// let keys = ["GET", "PUT", "POST", "DELETE"];
+    /// let mphf = Mphf::new(&keys).unwrap();
+    ///
+    /// let mut table = vec![""; keys.len()];
+    /// for key in keys {
+    ///     table[mphf.index(&key).unwrap()] = key;
+    /// }
+    /// assert_eq!(table[mphf.index(&"POST").unwrap()], "POST");
+    /// ```
+    pub fn index<K: Hash + ?Sized>(&self, key: &K) -> Option<usize> {
+        let edge = vertices(key_hash(key), self.seed, self.part);
+        let sum: u64 = edge.iter().map(|&vertex| self.value(vertex) % 3).sum();
+        let vertex = edge[(sum % 3) as usize];
+        if self.value(vertex) == UNUSED {
+            return None;
+        }
+        Some(self.rank(vertex))
+    }
+
+    /// Get the memory used per key
+    /// # Returns
+    /// The number of bits of the vertex values and the rank directory, divided by the number of keys
+    /// # Example
+    /// ```
+    /// use data_structures::hash::mphf::Mphf;
+    ///
+    /// let keys: Vec<u64> = (0..10_000).collect();
+    /// let mphf = Mphf::new(&keys).unwrap();
+    /// assert!(mphf.bits_per_key() < 3.0);
+    /// ```
+    pub fn bits_per_key(&self) -> f64 {
+        let bits = (self.values.len() + self.ranks.len()) * u64::BITS as usize;
+        bits as f64 / self.len.max(1) as f64
+    }
+
+    // Give each peeled edge's free vertex the value picking it, and build the rank directory
+    fn assign(edges: &[[usize; 3]], order: &[(usize, usize)], part: usize, seed: u64) -> Self {
+        let vertex_count = 3 * part;
+        let mut mphf = Mphf {
+            values: vec![u64::MAX; vertex_count.div_ceil(32)],
+            ranks: Vec::new(),
+            part,
+            seed,
+            len: edges.len(),
+        };
+
+        for &(edge, free) in order.iter().rev() {
+            let position = edges[edge]
+                .iter()
+                .position(|&vertex| vertex == free)
+                .unwrap() as u64;
+            let others: u64 = edges[edge]
+                .iter()
+                .filter(|&&vertex| vertex != free)
+                .map(|&vertex| mphf.value(vertex) % 3)
+                .sum();
+            mphf.set_value(free, (position + 6 - others) % 3);
+        }
+
+        let mut picked = 0;
+        for block in mphf.values.chunks(RANK_BLOCK / 32) {
+            mphf.ranks.push(picked);
+            picked += block.iter().map(|&word| used_in(word, 32)).sum::<u64>();
+        }
+        mphf
+    }
+
+    // Read the 2-bit value of a vertex
+    fn value(&self, vertex: usize) -> u64 {
+        (self.values[vertex / 32] >> (2 * (vertex % 32))) & 3
+    }
+
+    // Write the 2-bit value of a vertex
+    fn set_value(&mut self, vertex: usize, value: u64) {
+        let shift = 2 * (vertex % 32);
+        let word = &mut self.values[vertex / 32];
+        *word = (*word & !(3 << shift)) | (value << shift);
+    }
+
+    // Count the picked vertices before a vertex
+    fn rank(&self, vertex: usize) -> usize {
+        let first_word = vertex / RANK_BLOCK * (RANK_BLOCK / 32);
+        let last_word = vertex / 32;
+        let full: u64 = self.values[first_word..last_word]
+            .iter()
+            .map(|&word| used_in(word, 32))
+            .sum();
+        let partial = used_in(self.values[last_word], vertex % 32);
+        (self.ranks[vertex / RANK_BLOCK] + full + partial) as usize
+    }
+}
+
+// Count the values other than UNUSED among the first `count` values of a word
+fn used_in(word: u64, count: usize) -> u64 {
+    let word = if count == 32 {
+        word
+    } else {
+        word & ((1 << (2 * count)) - 1)
+    };
+    let unused = (word & (word >> 1) & 0x5555_5555_5555_5555).count_ones() as u64;
+    count as u64 - unused
+}
+
+// Hash a key with fixed keys, so the same key always has the same hash
+fn key_hash<K: Hash + ?Sized>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Compute the three vertices of a key, one in each third of the hypergraph
+fn vertices(hash: u64, seed: u64, part: usize) -> [usize; 3] {
+    let mut vertices = [0; 3];
+    for (third, vertex) in vertices.iter_mut().enumerate() {
+        let mixed = mix(hash ^ seed.wrapping_mul(0xD6E8_FEB8_6659_FD93) ^ third as u64);
+        *vertex = third * part + ((mixed as u128 * part as u128) >> 64) as usize;
+    }
+    vertices
+}
+
+// Scramble the bits of a hash, the finalizer of SplitMix64
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+// Peel the hypergraph, returning the edges in peeling order with their free vertex, or None if a core remains
+fn peel(edges: &[[usize; 3]], vertex_count: usize) -> Option<Vec<(usize, usize)>> {
+    let mut degrees = vec![0u32; vertex_count];
+    let mut incident = vec![0usize; vertex_count]; // XOR of the incident edges not yet peeled
+    for (edge, vertices) in edges.iter().enumerate() {
+        for &vertex in vertices {
+            degrees[vertex] += 1;
+            incident[vertex] ^= edge;
+        }
+    }
+
+    let mut stack: Vec<usize> = (0..vertex_count).filter(|&v| degrees[v] == 1).collect();
+    let mut order = Vec::with_capacity(edges.len());
+    while let Some(free) = stack.pop() {
+        if degrees[free] != 1 {
+            continue;
+        }
+        let edge = incident[free];
+        order.push((edge, free));
+        for &vertex in &edges[edge] {
+            degrees[vertex] -= 1;
+            incident[vertex] ^= edge;
+            if degrees[vertex] == 1 {
+                stack.push(vertex);
+            }
+        }
+    }
+
+    (order.len() == edges.len()).then_some(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mphf_is_minimal_and_perfect() {
+        for len in (0..200).chain([1_000, 50_000]) {
+            let keys: Vec<String> = (0..len).map(|i| format!("key-{}", i * 7919)).collect();
+            let mphf = Mphf::new(&keys).unwrap();
+
+            let mut seen = vec![false; len];
+            for key in &keys {
+                let index = mphf.index(key).unwrap();
+                assert!(!seen[index], "index {} given twice for {} keys", index, len);
+                seen[index] = true;
+            }
+            assert!(seen.iter().all(|&taken| taken));
+
+            // Keys outside the set never get an index past the end
+            for other in 0..100 {
+                let key = format!("other-{}", other);
+                assert!(mphf.index(&key).is_none_or(|index| index < len));
+            }
+        }
+    }
+
+    #[test]
+    fn test_mphf_size_and_duplicates() {
+        let keys: Vec<u64> = (0..100_000).map(|i| i * i).collect();
+        let mphf = Mphf::new(&keys).unwrap();
+        let bits = mphf.bits_per_key();
+        assert!((2.0..3.0).contains(&bits), "{} bits per key", bits);
+
+        let mut duplicated = keys.clone();
+        duplicated.push(49);
+        assert_eq!(
+            Mphf::new(&duplicated).unwrap_err(),
+            DataStructureError::DuplicateKeys
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! This module implements a bucket queue, a priority queue for small integer priorities.
+//! Each priority from 0 to K - 1 has its own FIFO, and the lowest priority is served first. Elements of equal priority
+//! come out in the order they were pushed, which a binary heap doesn't guarantee, and pushing or popping never compares
+//! elements.
+//!
+//! # Performance
+//! - O(1) for `push`
+//! - O(1) amortized for `pop` when the popped priorities never decrease, as in Dijkstra's algorithm with small weights,
+//!   and O(K) in the worst case
+//!
+//! # Implementation Details
+//! - The buckets are `FIFO`s in a `Vec` indexed by priority.
+//! - The queue keeps the lowest priority that may be non-empty. Pushing a lower priority moves it down, and popping
+//!   scans up from it to the first non-empty bucket.
+//!
+//! # Usage
+//! ```
+//! use data_structures::heap::bucket_queue::BucketQueue;
+//!
+//! let mut tasks = BucketQueue::new(3);
+//!
+//! tasks.push(2, "cleanup").unwrap();
+//! tasks.push(0, "page on-call").unwrap();
+//! tasks.push(1, "retry job a").unwrap();
+//! tasks.push(1, "retry job b").unwrap();
+//!
+//! assert_eq!(tasks.pop(), Some((0, "page on-call")));
+//! assert_eq!(tasks.pop(), Some((1, "retry job a")));
+//! assert_eq!(tasks.pop(), Some((1, "retry job b")));
+//! assert_eq!(tasks.pop(), Some((2, "cleanup")));
+//! assert_eq!(tasks.pop(), None);
+//! ```
+//!
+use std::fmt;
+
+use crate::error::DataStructureError;
+use crate::linked_list::fifo::FIFO;
+
+/// Struct representing a priority queue over a small range of integer priorities, lowest first
+///
+pub struct BucketQueue<T> {
+    buckets: Vec<FIFO<T>>,
+    lowest: usize, // no bucket below this priority holds an element
+    len: usize,
+}
+
+impl<T> BucketQueue<T> {
+    /// Create a new empty BucketQueue
+    /// # Arguments
+    /// * `priorities`: The number of priorities K. Elements are pushed with a priority from 0 to K - 1.
+    /// # Returns
+    /// A new BucketQueue instance
+    /// # Example
+    /// ```
+    /// use data_structures::heap::bucket_queue::BucketQueue;
+    ///
+    /// let queue: BucketQueue<u32> = BucketQueue::new(8);
+    /// assert_eq!(queue.priorities(), 8);
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn new(priorities: usize) -> Self {
+        BucketQueue {
+            buckets: (0..priorities).map(|_| FIFO::new(0)).collect(),
+            lowest: priorities,
+            len: 0,
+        }
+    }
+
+    /// Get the number of priorities
+    /// # Returns
+    /// The number of buckets K
+    /// # Example
+    /// ```
+    /// use data_structures::heap::bucket_queue::BucketQueue;
+    ///
+    /// let queue: BucketQueue<u32> = BucketQueue::new(4);
+    /// assert_eq!(queue.priorities(), 4);
+    /// ```
+    pub fn priorities(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Get the number of elements in the queue
+    /// # Returns
+    /// The number of elements across all priorities
+    /// # Example
+    /// ```
+    /// use data_structures::heap::bucket_queue::BucketQueue;
+    ///
+    /// let mut queue = BucketQueue::new(4);
+    /// queue.push(0, 'a').unwrap();
+    /// queue.push(3, 'b').unwrap();
+    /// assert_eq!(queue.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the queue is empty
+    /// # Returns
+    /// True if no element is queued
+    /// # Example
+    /// ```
+    /// use data_structures::heap::bucket_queue::BucketQueue;
+    ///
+    /// let queue: BucketQueue<u32> = BucketQueue::new(4);
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of elements queued with a priority
+    /// # Arguments
+    /// * `priority`: The priority
+    /// # Returns
+    /// The number of elements of that priority, 0 if it is out of range
+    /// # Example
+    /// ```
+    /// use data_structures::heap::bucket_queue::BucketQueue;
+    ///
+    /// let mut queue = BucketQueue::new(4);
+    /// queue.push(2, 'a').unwrap();
+    /// queue.push(2, 'b').unwrap();
+    /// assert_eq!(queue.len_at(2), 2);
+    /// assert_eq!(queue.len_at(9), 0);
+    /// ```
+    pub fn len_at(&self, priority: usize) -> usize {
+        self.buckets.get(priority).map_or(0, FIFO::len)
+    }
+
+    /// Add an element after the other elements of the same priority
+    /// # Arguments
+    /// * `priority`: The priority of the element, lower is served first
+    /// * `value`: The element
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the element was added, Err(DataStructureError::IndexOutOfBounds) if the priority is not smaller than the
+    /// number of priorities
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::heap::bucket_queue::BucketQueue;
+    ///
+    /// let mut queue = BucketQueue::new(2);
+    /// assert_eq!(queue.push(1, "b"), Ok(()));
+    /// assert_eq!(queue.push(2, "c"), Err(DataStructureError::IndexOutOfBounds { index: 2, len: 2 }));
+    /// ```
+    pub fn push(&mut self, priority: usize, value: T) -> Result<(), DataStructureError> {
+        let len = self.buckets.len();
+        let bucket =
+            self.buckets
+                .get_mut(priority)
+                .ok_or(DataStructureError::IndexOutOfBounds {
+                    index: priority,
+                    len,
+                })?;
+        bucket.push(value)?;
+
+        self.len += 1;
+        self.lowest = self.lowest.min(priority);
+        Ok(())
+    }
+
+    /// Get the lowest priority holding an element
+    /// # Returns
+    /// The priority of the next element `pop` returns, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::heap::bucket_queue::BucketQueue;
+    ///
+    /// let mut queue = BucketQueue::new(4);
+    /// assert_eq!(queue.peek_priority(), None);
+    ///
+    /// queue.push(3, 'a').unwrap();
+    /// queue.push(1, 'b').unwrap();
+    /// assert_eq!(queue.peek_priority(), Some(1));
+    /// ```
+    pub fn peek_priority(&self) -> Option<usize> {
+        (self.lowest..self.buckets.len()).find(|&priority| !self.buckets[priority].is_empty())
+    }
+
+    /// Remove the oldest element of the lowest priority
+    /// # Returns
+    /// The priority and the element, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::heap::bucket_queue::BucketQueue;
+    ///
+    /// let mut queue = BucketQueue::new(4);
+    /// queue.push(2, 'a').unwrap();
+    /// queue.push(0, 'b').unwrap();
+    ///
+    /// assert_eq!(queue.pop(), Some((0, 'b')));
+    /// assert_eq!(queue.pop(), Some((2, 'a')));
+    /// assert_eq!(queue.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<(usize, T)> {
+        let priority = self.peek_priority()?;
+        // The empty buckets below are skipped once, and not scanned again until a lower priority is pushed
+        self.lowest = priority;
+        let value = self.buckets[priority].pop()?;
+        self.len -= 1;
+        Some((priority, value))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BucketQueue<T> {
+    /// Prints the non-empty buckets with their priority, lowest first
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.buckets
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, bucket)| !bucket.is_empty()),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_bucket_queue_against_sorted_map() {
+        let mut queue = BucketQueue::new(10);
+        let mut model: BTreeMap<(usize, u32), u32> = BTreeMap::new();
+        let mut rng = Lcg::new(31);
+
+        for step in 0..3_000u32 {
+            let seed = rng.next_u64();
+            if (seed >> 62) == 0 {
+                let expected = model
+                    .pop_first()
+                    .map(|((priority, _), value)| (priority, value));
+                assert_eq!(queue.pop(), expected);
+            } else {
+                // Keyed by push order within a priority, so the model is FIFO too
+                let priority = (seed >> 33) as usize % 10;
+                queue.push(priority, step).unwrap();
+                model.insert((priority, step), step);
+            }
+            assert_eq!(queue.len(), model.len());
+            assert_eq!(
+                queue.peek_priority(),
+                model.keys().next().map(|&(priority, _)| priority)
+            );
+        }
+    }
+
+    #[test]
+    fn test_dijkstra_small_weights() {
+        // Grid of 20 x 20 cells with weights 1 to 5, shortest path from the top left corner
+        let size = 20;
+        let weight = |cell: usize| (cell * 7 + cell / size * 3) % 5 + 1;
+        let neighbors = |cell: usize| {
+            let (row, column) = (cell / size, cell % size);
+            let mut out = Vec::new();
+            if row > 0 {
+                out.push(cell - size);
+            }
+            if row + 1 < size {
+                out.push(cell + size);
+            }
+            if column > 0 {
+                out.push(cell - 1);
+            }
+            if column + 1 < size {
+                out.push(cell + 1);
+            }
+            out
+        };
+
+        // Distances are bounded by 5 per step over at most size * size steps
+        let mut queue = BucketQueue::new(5 * size * size);
+        let mut distances = vec![usize::MAX; size * size];
+        distances[0] = 0;
+        queue.push(0, 0).unwrap();
+        while let Some((distance, cell)) = queue.pop() {
+            if distance > distances[cell] {
+                continue;
+            }
+            for next in neighbors(cell) {
+                let candidate = distance + weight(next);
+                if candidate < distances[next] {
+                    distances[next] = candidate;
+                    queue.push(candidate, next).unwrap();
+                }
+            }
+        }
+
+        // Bellman-Ford relaxation reaches the same distances
+        let mut expected = vec![usize::MAX; size * size];
+        expected[0] = 0;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for cell in 0..size * size {
+                if expected[cell] == usize::MAX {
+                    continue;
+                }
+                for next in neighbors(cell) {
+                    if expected[cell] + weight(next) < expected[next] {
+                        expected[next] = expected[cell] + weight(next);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        assert_eq!(distances, expected);
+    }
+}
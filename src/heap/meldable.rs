@@ -0,0 +1,40 @@
+//! This module defines the interface shared by the meldable heaps, the heaps that can merge with another heap of the
+//! same kind faster than by pushing its elements one by one. Code written against the trait can switch between the
+//! implementations, for instance to compare them in a benchmark.
+//!
+//! # Usage
+//! ```
+//! use data_structures::heap::meldable::MeldableHeap;
+//! use data_structures::heap::skew_heap::SkewHeap;
+//!
+//! fn heap_sort<H: MeldableHeap<u32>>(mut left: H, mut right: H) -> Vec<u32> {
+//!     left.meld(right);
+//!     std::iter::from_fn(|| left.pop()).collect()
+//! }
+//!
+//! let left: SkewHeap<u32> = [3, 1, 4].into_iter().collect();
+//! let right: SkewHeap<u32> = [1, 5, 9].into_iter().collect();
+//!
+//! assert_eq!(heap_sort(left, right), vec![9, 5, 4, 3, 1, 1]);
+//! ```
+//!
+
+/// A max-heap that can absorb another heap of the same type
+pub trait MeldableHeap<T: Ord> {
+    /// Get the number of elements in the heap
+    fn len(&self) -> usize;
+
+    /// Check if the heap is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add an element to the heap
+    fn push(&mut self, value: T);
+
+    /// Remove and return the greatest element, or None if the heap is empty
+    fn pop(&mut self) -> Option<T>;
+
+    /// Move every element of `other` into this heap
+    fn meld(&mut self, other: Self);
+}
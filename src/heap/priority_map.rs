@@ -0,0 +1,437 @@
+//! This module implements a priority map, a keyed priority queue.
+//! Entries are addressed by key like in a hash map, and served by priority like in a heap: the priority of any entry can be
+//! changed in place, and the entry with the highest priority is always available at the top.
+//!
+//! # Performance
+//! - O(1) for `get`, `priority` and `peek_highest`
+//! - O(log n) for `insert`, `change_priority`, `remove` and `pop_highest`
+//!
+//! # Implementation Details
+//! - The entries are stored in a binary max-heap laid out in a `Vec`.
+//! - A `HashMap` keeps the position of each key in the heap, and is updated on every swap, so an entry can be found and
+//!   moved up or down without scanning the heap.
+//! - Entries with equal priorities are served in no particular order.
+//!
+//! # Usage
+//! ```
+//! use data_structures::heap::priority_map::PriorityMap;
+//!
+//! let mut jobs = PriorityMap::new();
+//!
+//! jobs.insert("backup", 1, "nightly backup");
+//! jobs.insert("deploy", 5, "production deploy");
+//! jobs.insert("report", 3, "weekly report");
+//!
+//! jobs.change_priority(&"backup", 10);
+//!
+//! assert_eq!(jobs.pop_highest(), Some(("backup", 10, "nightly backup")));
+//! assert_eq!(jobs.pop_highest(), Some(("deploy", 5, "production deploy")));
+//! assert_eq!(jobs.get(&"report"), Some(&"weekly report"));
+//! ```
+//!
+#[cfg(feature = "fallible-alloc")]
+use std::collections::TryReserveError;
+use std::{collections::HashMap, hash::Hash};
+
+/// Struct representing a map of keyed entries ordered by priority
+///
+#[derive(Debug, Clone)]
+pub struct PriorityMap<K, P, V> {
+    heap: Vec<(K, P, V)>,
+    positions: HashMap<K, usize>,
+}
+
+impl<K: Hash + Eq + Clone, P: Ord, V> PriorityMap<K, P, V> {
+    /// Create a new empty PriorityMap
+    ///
+    /// # Returns
+    /// A new PriorityMap instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let map: PriorityMap<u32, u8, String> = PriorityMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        PriorityMap {
+            heap: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Create a new empty PriorityMap with room for at least `capacity` entries before reallocating
+    ///
+    /// # Arguments
+    /// * `capacity`: The number of entries to allocate room for
+    ///
+    /// # Returns
+    /// A new PriorityMap instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let map: PriorityMap<u32, u8, String> = PriorityMap::with_capacity(64);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        PriorityMap {
+            heap: Vec::with_capacity(capacity),
+            positions: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Reserve room for at least `additional` more entries, reporting an allocation failure instead of aborting
+    ///
+    /// # Arguments
+    /// * `additional`: The number of entries to make room for
+    ///
+    /// # Returns
+    /// Result<(), TryReserveError>
+    /// Ok if the next `additional` insertions won't allocate, Err if the memory couldn't be allocated
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let mut map: PriorityMap<u32, u8, ()> = PriorityMap::new();
+    /// assert!(map.try_reserve(16).is_ok());
+    /// assert!(map.try_reserve(usize::MAX).is_err());
+    /// ```
+    #[cfg(feature = "fallible-alloc")]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.heap.try_reserve(additional)?;
+        self.positions.try_reserve(additional)
+    }
+
+    /// Get the number of entries in the map
+    /// # Returns
+    /// The number of entries
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let mut map = PriorityMap::new();
+    /// map.insert(1, 1, ());
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Check if the map is empty
+    /// # Returns
+    /// True if the map has no entries, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let map: PriorityMap<u8, u8, u8> = PriorityMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Check if a key is in the map
+    /// # Arguments
+    /// * `key`: The key to be checked
+    /// # Returns
+    /// True if the key is in the map, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let mut map = PriorityMap::new();
+    /// map.insert("a", 1, ());
+    /// assert!(map.contains_key(&"a"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    /// Insert an entry, or replace the priority and value of an existing key
+    /// # Arguments
+    /// * `key`: The key of the entry
+    /// * `priority`: The priority of the entry
+    /// * `value`: The value of the entry
+    /// # Returns
+    /// The previous priority and value of the key, or None if the key is new
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let mut map = PriorityMap::new();
+    ///
+    /// assert_eq!(map.insert("a", 1, 'x'), None);
+    /// assert_eq!(map.insert("a", 2, 'y'), Some((1, 'x')));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn insert(&mut self, key: K, priority: P, value: V) -> Option<(P, V)> {
+        if let Some(&position) = self.positions.get(&key) {
+            let (_, old_priority, old_value) =
+                std::mem::replace(&mut self.heap[position], (key, priority, value));
+            self.restore(position);
+            return Some((old_priority, old_value));
+        }
+
+        self.heap.push((key.clone(), priority, value));
+        self.positions.insert(key, self.heap.len() - 1);
+        self.sift_up(self.heap.len() - 1);
+
+        None
+    }
+
+    /// Get the value of a key
+    /// # Arguments
+    /// * `key`: The key to be searched
+    /// # Returns
+    /// A reference to the value, or None if the key isn't in the map
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let mut map = PriorityMap::new();
+    /// map.insert(7, 0, "seven");
+    /// assert_eq!(map.get(&7), Some(&"seven"));
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.positions
+            .get(key)
+            .map(|&position| &self.heap[position].2)
+    }
+
+    /// Get the priority of a key
+    /// # Arguments
+    /// * `key`: The key to be searched
+    /// # Returns
+    /// A reference to the priority, or None if the key isn't in the map
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let mut map = PriorityMap::new();
+    /// map.insert(7, 3, ());
+    /// assert_eq!(map.priority(&7), Some(&3));
+    /// ```
+    pub fn priority(&self, key: &K) -> Option<&P> {
+        self.positions
+            .get(key)
+            .map(|&position| &self.heap[position].1)
+    }
+
+    /// Change the priority of a key, keeping its value
+    /// # Arguments
+    /// * `key`: The key of the entry
+    /// * `priority`: The new priority
+    /// # Returns
+    /// The previous priority, or None if the key isn't in the map
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let mut map = PriorityMap::new();
+    /// map.insert('a', 1, ());
+    /// map.insert('b', 2, ());
+    ///
+    /// assert_eq!(map.change_priority(&'a', 3), Some(1));
+    /// assert_eq!(map.change_priority(&'c', 3), None);
+    /// assert_eq!(map.peek_highest().map(|(k, _, _)| *k), Some('a'));
+    /// ```
+    pub fn change_priority(&mut self, key: &K, priority: P) -> Option<P> {
+        let position = *self.positions.get(key)?;
+        let old_priority = std::mem::replace(&mut self.heap[position].1, priority);
+        self.restore(position);
+
+        Some(old_priority)
+    }
+
+    /// Get the entry with the highest priority without removing it
+    /// # Returns
+    /// References to the key, priority and value of the top entry, or None if the map is empty
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let mut map = PriorityMap::new();
+    /// map.insert(1, 10, 'a');
+    /// map.insert(2, 20, 'b');
+    /// assert_eq!(map.peek_highest(), Some((&2, &20, &'b')));
+    /// ```
+    pub fn peek_highest(&self) -> Option<(&K, &P, &V)> {
+        self.heap
+            .first()
+            .map(|(key, priority, value)| (key, priority, value))
+    }
+
+    /// Remove and return the entry with the highest priority
+    /// # Returns
+    /// The key, priority and value of the top entry, or None if the map is empty
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let mut map = PriorityMap::new();
+    /// map.insert(1, 10, 'a');
+    /// map.insert(2, 20, 'b');
+    ///
+    /// assert_eq!(map.pop_highest(), Some((2, 20, 'b')));
+    /// assert_eq!(map.pop_highest(), Some((1, 10, 'a')));
+    /// assert_eq!(map.pop_highest(), None);
+    /// ```
+    pub fn pop_highest(&mut self) -> Option<(K, P, V)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        Some(self.remove_at(0))
+    }
+
+    /// Remove an entry by key
+    /// # Arguments
+    /// * `key`: The key of the entry
+    /// # Returns
+    /// The priority and value of the removed entry, or None if the key isn't in the map
+    /// # Example
+    /// ```
+    /// use data_structures::heap::priority_map::PriorityMap;
+    ///
+    /// let mut map = PriorityMap::new();
+    /// map.insert("a", 1, 'x');
+    ///
+    /// assert_eq!(map.remove(&"a"), Some((1, 'x')));
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<(P, V)> {
+        let position = *self.positions.get(key)?;
+        let (_, priority, value) = self.remove_at(position);
+
+        Some((priority, value))
+    }
+
+    fn remove_at(&mut self, position: usize) -> (K, P, V) {
+        let last = self.heap.len() - 1;
+        self.swap(position, last);
+
+        let entry = self.heap.pop().unwrap();
+        self.positions.remove(&entry.0);
+
+        if position < self.heap.len() {
+            self.restore(position);
+        }
+
+        entry
+    }
+
+    // Move an entry up or down until the heap property holds again
+    fn restore(&mut self, position: usize) {
+        let position = self.sift_up(position);
+        self.sift_down(position);
+    }
+
+    fn sift_up(&mut self, mut position: usize) -> usize {
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            if self.heap[position].1 <= self.heap[parent].1 {
+                break;
+            }
+            self.swap(position, parent);
+            position = parent;
+        }
+
+        position
+    }
+
+    fn sift_down(&mut self, mut position: usize) {
+        loop {
+            let left = 2 * position + 1;
+            let right = left + 1;
+            let mut largest = position;
+
+            if left < self.heap.len() && self.heap[left].1 > self.heap[largest].1 {
+                largest = left;
+            }
+            if right < self.heap.len() && self.heap[right].1 > self.heap[largest].1 {
+                largest = right;
+            }
+            if largest == position {
+                return;
+            }
+
+            self.swap(position, largest);
+            position = largest;
+        }
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        *self.positions.get_mut(&self.heap[a].0).unwrap() = a;
+        *self.positions.get_mut(&self.heap[b].0).unwrap() = b;
+    }
+}
+
+impl<K: Hash + Eq + Clone, P: Ord, V> Default for PriorityMap<K, P, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_priority_map_order() {
+        let mut map = PriorityMap::new();
+        let mut rng = Lcg::new(11);
+
+        for key in 0..1_000u32 {
+            let seed = rng.next_u64();
+            map.insert(key, (seed >> 40) as u32, key * 2);
+        }
+
+        // Change and remove some entries before draining
+        for key in (0..1_000).step_by(3) {
+            map.change_priority(&key, key * 7);
+        }
+        for key in (0..1_000).step_by(5) {
+            assert_eq!(map.remove(&key).map(|(_, value)| value), Some(key * 2));
+        }
+
+        let mut previous = u32::MAX;
+        let mut count = 0;
+        while let Some((key, priority, value)) = map.pop_highest() {
+            assert!(priority <= previous);
+            assert_eq!(value, key * 2);
+            if key % 3 == 0 {
+                assert_eq!(priority, key * 7);
+            }
+            previous = priority;
+            count += 1;
+        }
+
+        assert_eq!(count, 800);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_priority_map_scheduler() {
+        let mut tasks = PriorityMap::new();
+
+        tasks.insert("idle", 0, ());
+        tasks.insert("io", 5, ());
+        tasks.insert("render", 8, ());
+
+        // The io task gets boosted, then the render task gets lowered
+        assert_eq!(tasks.change_priority(&"io", 9), Some(5));
+        assert_eq!(tasks.change_priority(&"render", 1), Some(8));
+        assert_eq!(tasks.priority(&"render"), Some(&1));
+
+        let order: Vec<&str> =
+            std::iter::from_fn(|| tasks.pop_highest().map(|(k, _, _)| k)).collect();
+        assert_eq!(order, vec!["io", "render", "idle"]);
+    }
+}
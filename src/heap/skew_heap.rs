@@ -0,0 +1,335 @@
+//! This module implements a skew heap, a self-adjusting max-heap stored as a binary tree.
+//! Two skew heaps are melded by merging their right spines and swapping the children of every node on the merged path,
+//! without any rank or balance bookkeeping. Pushing and popping are melds with a single node and with the two subtrees of
+//! the root, which makes it the simplest of the meldable heaps.
+//!
+//! # Performance
+//! - O(log n) amortized for `push`, `pop` and `meld`, a single operation can take O(n)
+//! - O(1) for `peek`, `len` and `is_empty`
+//!
+//! # Implementation Details
+//! - Nodes are `Vertex` instances handled through `Shared<Vertex<T>>` pointers, using the `Left` and `Right` pointers
+//!   for the children.
+//! - The merge runs top-down in a loop instead of recursing, so long right spines don't overflow the stack. Dropping
+//!   the heap is iterative for the same reason.
+//! - Equal elements are popped in no particular order.
+//!
+//! # Usage
+//! ```
+//! use data_structures::heap::skew_heap::SkewHeap;
+//!
+//! let mut heap = SkewHeap::new();
+//! heap.push(3);
+//! heap.push(8);
+//!
+//! let mut other = SkewHeap::new();
+//! other.push(5);
+//! heap.meld(other);
+//!
+//! assert_eq!(heap.peek(), Some(8));
+//! assert_eq!(heap.pop(), Some(8));
+//! assert_eq!(heap.pop(), Some(5));
+//! assert_eq!(heap.len(), 1);
+//! ```
+//!
+use std::cmp::Ordering;
+use std::fmt;
+use std::mem;
+
+use crate::heap::meldable::MeldableHeap;
+use crate::linked_list::shared::Shared;
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+/// Struct representing a skew max-heap
+///
+pub struct SkewHeap<T> {
+    root: Option<Shared<Vertex<T>>>,
+    size: usize,
+}
+
+fn compare<T: Ord>(a: &Shared<Vertex<T>>, b: &Shared<Vertex<T>>) -> Ordering {
+    a.borrow().read_data().cmp(b.borrow().read_data())
+}
+
+// Merge two trees, returning the root of the result
+fn merge<T: Ord>(
+    a: Option<Shared<Vertex<T>>>,
+    b: Option<Shared<Vertex<T>>>,
+) -> Option<Shared<Vertex<T>>> {
+    let (a, b) = match (a, b) {
+        (None, other) | (other, None) => return other,
+        (Some(a), Some(b)) => (a, b),
+    };
+
+    // The node with the greater element goes on the merged path, the other tree is merged below it
+    let order = |a: Shared<Vertex<T>>, b: Shared<Vertex<T>>| match compare(&a, &b) {
+        Ordering::Less => (b, a),
+        _ => (a, b),
+    };
+
+    let (root, mut other) = order(a, b);
+    let mut tail = root.clone();
+
+    loop {
+        // Swap the children: the old left goes right, and the merge of the old right continues on the left
+        let next = tail.borrow().get_pointer(PointerName::Right);
+        let left = tail.borrow().get_pointer(PointerName::Left);
+        tail.borrow_mut()
+            .set_connection(PointerName::Right, left.as_ref());
+
+        match next {
+            None => {
+                tail.borrow_mut()
+                    .set_connection(PointerName::Left, Some(&other));
+                return Some(root);
+            }
+            Some(next) => {
+                let (greater, lesser) = order(next, other);
+                tail.borrow_mut()
+                    .set_connection(PointerName::Left, Some(&greater));
+                tail = greater;
+                other = lesser;
+            }
+        }
+    }
+}
+
+impl<T: Ord> SkewHeap<T> {
+    /// Create a new empty heap
+    ///
+    /// # Returns
+    /// A new SkewHeap instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::heap::skew_heap::SkewHeap;
+    ///
+    /// let heap: SkewHeap<i32> = SkewHeap::new();
+    /// assert!(heap.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        SkewHeap {
+            root: None,
+            size: 0,
+        }
+    }
+
+    /// Get the number of elements in the heap
+    /// # Returns
+    /// The number of elements
+    /// # Example
+    /// ```
+    /// use data_structures::heap::skew_heap::SkewHeap;
+    ///
+    /// let heap: SkewHeap<i32> = [1, 1, 2].into_iter().collect();
+    /// assert_eq!(heap.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the heap is empty
+    /// # Returns
+    /// True if the heap has no elements
+    /// # Example
+    /// ```
+    /// use data_structures::heap::skew_heap::SkewHeap;
+    ///
+    /// let mut heap = SkewHeap::default();
+    /// heap.push('a');
+    /// assert!(!heap.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Get the greatest element without removing it
+    /// # Returns
+    /// A clone of the greatest element, or None if the heap is empty
+    /// # Example
+    /// ```
+    /// use data_structures::heap::skew_heap::SkewHeap;
+    ///
+    /// let heap: SkewHeap<&str> = ["pear", "apple", "plum"].into_iter().collect();
+    /// assert_eq!(heap.peek(), Some("plum"));
+    /// ```
+    pub fn peek(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.root
+            .as_ref()
+            .and_then(|root| root.borrow().read_data().clone())
+    }
+
+    /// Call a function with a reference to the greatest element, without cloning it
+    /// # Arguments
+    /// * `f`: The function reading the element
+    /// # Returns
+    /// The result of `f`, or None if the heap is empty
+    /// # Example
+    /// ```
+    /// use data_structures::heap::skew_heap::SkewHeap;
+    ///
+    /// let heap: SkewHeap<String> = ["pear", "plum"].into_iter().map(String::from).collect();
+    /// assert_eq!(heap.peek_with(|fruit| fruit.len()), Some(4));
+    /// ```
+    pub fn peek_with<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let root = self.root.as_ref()?;
+        let node = root.borrow();
+        node.read_data().as_ref().map(f)
+    }
+
+    /// Add an element to the heap
+    /// # Arguments
+    /// * `value`: The element to add
+    /// # Example
+    /// ```
+    /// use data_structures::heap::skew_heap::SkewHeap;
+    ///
+    /// let mut heap = SkewHeap::new();
+    /// heap.push(2);
+    /// heap.push(7);
+    /// assert_eq!(heap.peek(), Some(7));
+    /// ```
+    pub fn push(&mut self, value: T) {
+        self.root = merge(self.root.take(), Some(Vertex::new(value)));
+        self.size += 1;
+    }
+
+    /// Remove the greatest element from the heap
+    /// # Returns
+    /// The greatest element, or None if the heap is empty
+    /// # Example
+    /// ```
+    /// use data_structures::heap::skew_heap::SkewHeap;
+    ///
+    /// let mut heap: SkewHeap<u8> = [4, 9, 1].into_iter().collect();
+    /// assert_eq!(heap.pop(), Some(9));
+    /// assert_eq!(heap.pop(), Some(4));
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.root.take()?;
+
+        let left = root.borrow().get_pointer(PointerName::Left);
+        let right = root.borrow().get_pointer(PointerName::Right);
+        self.root = merge(left, right);
+        self.size -= 1;
+
+        let value = root.borrow_mut().clear();
+        value
+    }
+
+    /// Move every element of another heap into this one
+    /// # Arguments
+    /// * `other`: The heap to absorb
+    /// # Example
+    /// ```
+    /// use data_structures::heap::skew_heap::SkewHeap;
+    ///
+    /// let mut heap: SkewHeap<i32> = [1, 6].into_iter().collect();
+    /// heap.meld([3, 10].into_iter().collect());
+    ///
+    /// assert_eq!(heap.len(), 4);
+    /// assert_eq!(heap.peek(), Some(10));
+    /// ```
+    pub fn meld(&mut self, mut other: SkewHeap<T>) {
+        self.root = merge(self.root.take(), other.root.take());
+        self.size += mem::take(&mut other.size);
+    }
+}
+
+impl<T: Ord> MeldableHeap<T> for SkewHeap<T> {
+    fn len(&self) -> usize {
+        SkewHeap::len(self)
+    }
+
+    fn push(&mut self, value: T) {
+        SkewHeap::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        SkewHeap::pop(self)
+    }
+
+    fn meld(&mut self, other: Self) {
+        SkewHeap::meld(self, other)
+    }
+}
+
+impl<T: Ord> Default for SkewHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SkewHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heap = SkewHeap::new();
+        for value in iter {
+            heap.push(value);
+        }
+        heap
+    }
+}
+
+impl<T> Drop for SkewHeap<T> {
+    fn drop(&mut self) {
+        // Unlink the nodes iteratively, so dropping a deep tree doesn't recurse through every vertex
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            stack.extend(node.borrow().get_pointer(PointerName::Left));
+            stack.extend(node.borrow().get_pointer(PointerName::Right));
+            node.borrow_mut().clear();
+        }
+    }
+}
+
+impl<T> fmt::Debug for SkewHeap<T> {
+    /// Prints the size only, the shape of a skew heap depends on the history of its operations
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SkewHeap").field("len", &self.size).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_skew_heap_sorts() {
+        let mut heap = SkewHeap::new();
+        let mut reference = Vec::new();
+        let mut rng = Lcg::new(7);
+
+        for _ in 0..1000 {
+            let seed = rng.next_u64();
+            let value = (seed >> 40) % 100;
+            heap.push(value);
+            reference.push(value);
+        }
+
+        reference.sort_unstable_by(|a, b| b.cmp(a));
+        let popped: Vec<_> = std::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(popped, reference);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_skew_heap_meld() {
+        let mut evens: SkewHeap<u32> = (0..50).map(|n| n * 2).collect();
+        let odds: SkewHeap<u32> = (0..50).map(|n| n * 2 + 1).collect();
+
+        MeldableHeap::meld(&mut evens, odds);
+        assert_eq!(evens.len(), 100);
+        assert!((0..100).rev().eq(std::iter::from_fn(|| evens.pop())));
+
+        // Ascending pushes build a long left spine, which must not overflow the stack on drop
+        let deep: SkewHeap<u32> = (0..100_000).collect();
+        assert_eq!(deep.peek(), Some(99_999));
+    }
+}
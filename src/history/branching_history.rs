@@ -0,0 +1,415 @@
+//! This module implements a navigation history that keeps every branch, like the history of a browser tab that would
+//! remember the pages left behind when a new link is followed after going back.
+//! States form a tree: `push_state` adds a new branch under the current state instead of discarding the forward states,
+//! `back` goes to the parent state and `forward` goes to a child. The branches of the current state can be listed and
+//! followed by index.
+//!
+//! # Performance
+//! - O(1) for `push_state` and `back`
+//! - O(c) for `forward`, `branches` and `forward_to`, where c is the number of branches of the current state
+//! - O(d) for `path` and `depth`, where d is the depth of the current state
+//!
+//! # Implementation Details
+//! - The states are the nodes of a `NaryTree`, the first state being its root. The history keeps a pointer to the node
+//!   of the current state.
+//! - The nodes left by `back` are stacked, so `forward` walks the same path again. Without such a node, it takes the
+//!   newest branch. Pushing a state or following another branch forgets the stacked nodes.
+//!
+//! # Usage
+//! ```
+//! use data_structures::history::branching_history::BranchingHistory;
+//!
+//! let mut tab = BranchingHistory::new("home");
+//! tab.push_state("news");
+//! tab.push_state("article");
+//!
+//! assert_eq!(tab.back(), Some("news"));
+//! assert_eq!(tab.back(), Some("home"));
+//! tab.push_state("weather");
+//!
+//! // The news branch is still there
+//! tab.back();
+//! assert_eq!(tab.branches(), vec!["news", "weather"]);
+//! assert_eq!(tab.forward_to(0), Some("news"));
+//! assert_eq!(tab.forward(), Some("article"));
+//! ```
+//!
+use std::fmt;
+
+use crate::linked_list::shared::{Ptr, Shared};
+use crate::linked_list::vertex::Vertex;
+use crate::tree::nary_tree::NaryTree;
+
+/// Struct representing a navigation history whose states form a tree of branches
+///
+pub struct BranchingHistory<T> {
+    tree: NaryTree<T>,
+    current: Shared<Vertex<T>>,
+    forward: Vec<Shared<Vertex<T>>>, // nodes left by `back`, the next one to go forward to at the end
+}
+
+impl<T> BranchingHistory<T> {
+    /// Create a new history holding a single state
+    /// # Arguments
+    /// * `initial`: The first state, root of every branch
+    /// # Returns
+    /// A new BranchingHistory instance
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let history = BranchingHistory::new(0);
+    /// assert_eq!(history.len(), 1);
+    /// assert!(!history.can_back());
+    /// ```
+    pub fn new(initial: T) -> Self {
+        let tree = NaryTree::new(initial);
+        let current = tree.root();
+        BranchingHistory {
+            tree,
+            current,
+            forward: Vec::new(),
+        }
+    }
+
+    /// Get the number of states in every branch
+    /// # Returns
+    /// The number of states, including the first one
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new(0);
+    /// history.push_state(1);
+    /// history.back();
+    /// history.push_state(2);
+    /// assert_eq!(history.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Check if the history only holds its first state
+    /// # Returns
+    /// True if no state was pushed
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new(0);
+    /// assert!(history.is_empty());
+    ///
+    /// history.push_state(1);
+    /// assert!(!history.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.tree.size() == 1
+    }
+
+    /// Get the number of states `back` can go through before reaching the first one
+    /// # Returns
+    /// The depth of the current state, 0 for the first state
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new('a');
+    /// history.push_state('b');
+    /// history.push_state('c');
+    /// assert_eq!(history.depth(), 2);
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.tree.depth(&self.current)
+    }
+
+    /// Make a new state current, as a new branch of the current state
+    /// The other branches are kept and can still be reached with `forward_to`.
+    /// # Arguments
+    /// * `state`: The new current state
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new(0);
+    /// history.push_state(1);
+    /// history.back();
+    /// history.push_state(2);
+    ///
+    /// history.back();
+    /// assert_eq!(history.branch_count(), 2);
+    /// ```
+    pub fn push_state(&mut self, state: T) {
+        self.current = self.tree.add_child(&self.current, state);
+        self.forward.clear();
+    }
+
+    /// Check if there is a state to go back to
+    /// # Returns
+    /// True if the current state isn't the first one
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new(0);
+    /// history.push_state(1);
+    /// assert!(history.can_back());
+    /// ```
+    pub fn can_back(&self) -> bool {
+        self.tree.parent(&self.current).is_some()
+    }
+
+    /// Check if there is a state to go forward to
+    /// # Returns
+    /// True if the current state has at least one branch
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new(0);
+    /// history.push_state(1);
+    /// history.back();
+    /// assert!(history.can_forward());
+    /// ```
+    pub fn can_forward(&self) -> bool {
+        self.branch_count() > 0
+    }
+
+    /// Get the number of branches of the current state
+    /// # Returns
+    /// The number of states pushed right after the current one
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let history = BranchingHistory::new(0);
+    /// assert_eq!(history.branch_count(), 0);
+    /// ```
+    pub fn branch_count(&self) -> usize {
+        self.tree.child_count(&self.current)
+    }
+}
+
+impl<T: Clone> BranchingHistory<T> {
+    /// Get a clone of the current state
+    /// # Returns
+    /// The current state
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new("home");
+    /// history.push_state("about");
+    /// assert_eq!(history.current(), "about");
+    /// ```
+    pub fn current(&self) -> T {
+        self.current.borrow().read_data().clone().unwrap()
+    }
+
+    /// Go back to the state the current one was pushed from
+    /// # Returns
+    /// A clone of the new current state, or None if the current state is the first one
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new(1);
+    /// history.push_state(2);
+    ///
+    /// assert_eq!(history.back(), Some(1));
+    /// assert_eq!(history.back(), None);
+    /// ```
+    pub fn back(&mut self) -> Option<T> {
+        let parent = self.tree.parent(&self.current)?;
+        let left = std::mem::replace(&mut self.current, parent);
+        self.forward.push(left);
+        Some(self.current())
+    }
+
+    /// Go forward to the state `back` came from, or to the newest branch if `back` didn't lead here
+    /// # Returns
+    /// A clone of the new current state, or None if the current state has no branch
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new(0);
+    /// history.push_state(1);
+    /// history.back();
+    /// history.push_state(2);
+    /// history.back();
+    ///
+    /// assert_eq!(history.forward(), Some(2));
+    /// assert_eq!(history.forward(), None);
+    /// ```
+    pub fn forward(&mut self) -> Option<T> {
+        let next = match self.forward.pop() {
+            Some(node) => node,
+            None => self.tree.children(&self.current).last()?,
+        };
+        self.current = next;
+        Some(self.current())
+    }
+
+    /// Go forward to a given branch of the current state
+    /// # Arguments
+    /// * `branch`: The index of the branch, in the order of `branches`
+    /// # Returns
+    /// A clone of the new current state, or None if there is no such branch
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new("draft");
+    /// history.push_state("short");
+    /// history.back();
+    /// history.push_state("long");
+    /// history.back();
+    ///
+    /// assert_eq!(history.forward_to(0), Some("short"));
+    /// assert_eq!(history.forward_to(0), None);
+    /// ```
+    pub fn forward_to(&mut self, branch: usize) -> Option<T> {
+        let next = self.tree.children(&self.current).nth(branch)?;
+
+        // Leaving the path `back` came along forgets it
+        match self.forward.last() {
+            Some(node) if Ptr::ptr_eq(node, &next) => {
+                self.forward.pop();
+            }
+            _ => self.forward.clear(),
+        }
+        self.current = next;
+        Some(self.current())
+    }
+
+    /// List the branches of the current state
+    /// # Returns
+    /// Clones of the states pushed right after the current one, from the oldest to the newest
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new('a');
+    /// history.push_state('b');
+    /// history.back();
+    /// history.push_state('c');
+    /// history.back();
+    ///
+    /// assert_eq!(history.branches(), vec!['b', 'c']);
+    /// ```
+    pub fn branches(&self) -> Vec<T> {
+        self.tree
+            .children(&self.current)
+            .map(|node| node.borrow().read_data().clone().unwrap())
+            .collect()
+    }
+
+    /// List the states from the first one to the current one
+    /// # Returns
+    /// Clones of the states `back` would go through, in reverse order, ending with the current state
+    /// # Example
+    /// ```
+    /// use data_structures::history::branching_history::BranchingHistory;
+    ///
+    /// let mut history = BranchingHistory::new("home");
+    /// history.push_state("docs");
+    /// history.push_state("install");
+    ///
+    /// assert_eq!(history.path(), vec!["home", "docs", "install"]);
+    /// ```
+    pub fn path(&self) -> Vec<T> {
+        let mut path = vec![self.current()];
+        let mut node = self.tree.parent(&self.current);
+        while let Some(parent) = node {
+            path.push(parent.borrow().read_data().clone().unwrap());
+            node = self.tree.parent(&parent);
+        }
+        path.reverse();
+        path
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BranchingHistory<T> {
+    /// Prints the current state and the size of the history, not the tree
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BranchingHistory")
+            .field("current", self.current.borrow().read_data())
+            .field("len", &self.tree.size())
+            .field("depth", &self.depth())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_branching_history_against_parent_links() {
+        // Model: the parent of each state, states being numbered in push order
+        let mut history = BranchingHistory::new(0usize);
+        let mut parents: Vec<Option<usize>> = vec![None];
+        let (mut current, mut forward): (usize, Vec<usize>) = (0, vec![]);
+        let children = |parents: &[Option<usize>], node: usize| -> Vec<usize> {
+            (0..parents.len())
+                .filter(|&state| parents[state] == Some(node))
+                .collect()
+        };
+        let mut rng = Lcg::new(11);
+
+        for _ in 0..2_000 {
+            let seed = rng.next_u64();
+            match (seed >> 33) % 4 {
+                0 => {
+                    let expected = parents[current].inspect(|&parent| {
+                        forward.push(current);
+                        current = parent;
+                    });
+                    assert_eq!(history.back(), expected);
+                }
+                1 => {
+                    let next = forward
+                        .pop()
+                        .or_else(|| children(&parents, current).last().copied());
+                    if let Some(next) = next {
+                        current = next;
+                    }
+                    assert_eq!(history.forward(), next);
+                }
+                2 => {
+                    let branches = children(&parents, current);
+                    let branch = (seed >> 40) as usize % (branches.len() + 1);
+                    let next = branches.get(branch).copied();
+                    if let Some(next) = next {
+                        if forward.last() == Some(&next) {
+                            forward.pop();
+                        } else {
+                            forward.clear();
+                        }
+                        current = next;
+                    }
+                    assert_eq!(history.forward_to(branch), next);
+                }
+                _ => {
+                    parents.push(Some(current));
+                    current = parents.len() - 1;
+                    forward.clear();
+                    history.push_state(current);
+                }
+            }
+
+            assert_eq!(history.current(), current);
+            assert_eq!(history.len(), parents.len());
+            assert_eq!(history.branches(), children(&parents, current));
+
+            let mut path = vec![current];
+            while let Some(parent) = parents[*path.last().unwrap()] {
+                path.push(parent);
+            }
+            path.reverse();
+            assert_eq!(history.depth(), path.len() - 1);
+            assert_eq!(history.path(), path);
+        }
+    }
+}
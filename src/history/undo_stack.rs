@@ -0,0 +1,347 @@
+//! This module implements an undo/redo history of states, for editors and tools.
+//! The history holds the current state, the past states that `undo` goes back to and the undone states that `redo`
+//! brings back. Pushing a new state discards the redo branch, and the past is bounded: once the depth is reached, each
+//! new state evicts the oldest one. A dirty flag tells if the current state differs from the last saved one.
+//!
+//! # Performance
+//! - O(1) for `push_state`, `undo`, `redo`, `is_dirty` and `mark_saved`
+//!
+//! # Implementation Details
+//! - The past and the undone states are two `Deque`s, so they live in the vertexes of a `CircularQueue`. The past is
+//!   pushed at the back and evicted from the front when it reaches the depth.
+//! - Every state gets a new version number when it is pushed. The dirty flag compares the version of the current
+//!   state with the version saved by `mark_saved`, so undoing back to the saved state makes the history clean again,
+//!   and a discarded saved state leaves it dirty for good since its version never comes back.
+//!
+//! # Usage
+//! ```
+//! use data_structures::history::undo_stack::UndoStack;
+//!
+//! let mut text = UndoStack::new(String::new(), 100);
+//! text.push_state("Hello".to_string());
+//! text.push_state("Hello, world".to_string());
+//! text.mark_saved();
+//!
+//! assert_eq!(text.undo().unwrap(), "Hello");
+//! assert!(text.is_dirty());
+//!
+//! assert_eq!(text.redo().unwrap(), "Hello, world");
+//! assert!(!text.is_dirty());
+//! ```
+//!
+use std::fmt;
+
+use crate::linked_list::deque::Deque;
+
+/// Struct representing a bounded undo/redo history of states
+///
+pub struct UndoStack<T> {
+    past: Deque<(u64, T)>,   // oldest at the front, most recent at the back
+    undone: Deque<(u64, T)>, // most recently undone at the back
+    current: (u64, T),
+    saved: u64, // version of the saved state
+    next_version: u64,
+}
+
+impl<T> UndoStack<T> {
+    /// Create a new history holding a single state
+    /// # Arguments
+    /// * `initial`: The current state, considered saved
+    /// * `depth`: The maximum number of states `undo` can go back, 0 for no limit
+    /// # Returns
+    /// A new UndoStack instance
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let history = UndoStack::new(0, 10);
+    /// assert_eq!(history.current(), &0);
+    /// assert!(!history.can_undo());
+    /// ```
+    pub fn new(initial: T, depth: usize) -> Self {
+        UndoStack {
+            past: Deque::new(depth),
+            undone: Deque::new(0),
+            current: (0, initial),
+            saved: 0,
+            next_version: 1,
+        }
+    }
+
+    /// Get the maximum number of states `undo` can go back
+    /// # Returns
+    /// The depth of the history, 0 for no limit
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let history = UndoStack::new('a', 3);
+    /// assert_eq!(history.depth(), 3);
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.past.max_size()
+    }
+
+    /// Get the current state
+    /// # Returns
+    /// A reference to the current state
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let mut history = UndoStack::new(1, 10);
+    /// history.push_state(2);
+    /// assert_eq!(history.current(), &2);
+    /// ```
+    pub fn current(&self) -> &T {
+        &self.current.1
+    }
+
+    /// Make a new state current, the previous one becoming undoable
+    /// The undone states are discarded. If the past is at the depth of the history, its oldest state is evicted.
+    /// # Arguments
+    /// * `state`: The new current state
+    /// # Returns
+    /// The evicted oldest state, if any
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let mut history = UndoStack::new(0, 2);
+    /// assert_eq!(history.push_state(1), None);
+    /// assert_eq!(history.push_state(2), None);
+    /// assert_eq!(history.push_state(3), Some(0));
+    /// assert_eq!(history.undo_len(), 2);
+    /// ```
+    pub fn push_state(&mut self, state: T) -> Option<T> {
+        self.undone.clear();
+
+        let previous = std::mem::replace(&mut self.current, (self.next_version, state));
+        self.next_version += 1;
+
+        let evicted = if self.past.is_full() {
+            self.past.pop_front()
+        } else {
+            None
+        };
+        self.past.push_back(previous).unwrap();
+        evicted.map(|(_, state)| state)
+    }
+
+    /// Go back to the previous state
+    /// # Returns
+    /// A reference to the new current state, or None if there is nothing to undo
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let mut history = UndoStack::new("draft", 10);
+    /// history.push_state("final");
+    ///
+    /// assert_eq!(history.undo(), Some(&"draft"));
+    /// assert_eq!(history.undo(), None);
+    /// ```
+    pub fn undo(&mut self) -> Option<&T> {
+        let previous = self.past.pop_back()?;
+        let current = std::mem::replace(&mut self.current, previous);
+        self.undone.push_back(current).unwrap();
+        Some(&self.current.1)
+    }
+
+    /// Go forward to the last undone state
+    /// # Returns
+    /// A reference to the new current state, or None if there is nothing to redo
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let mut history = UndoStack::new(1, 10);
+    /// history.push_state(2);
+    /// history.undo();
+    ///
+    /// assert_eq!(history.redo(), Some(&2));
+    /// assert_eq!(history.redo(), None);
+    /// ```
+    pub fn redo(&mut self) -> Option<&T> {
+        let next = self.undone.pop_back()?;
+        let current = std::mem::replace(&mut self.current, next);
+
+        // Undone states came from the past, so there is room for the state going back there
+        self.past.push_back(current).unwrap();
+        Some(&self.current.1)
+    }
+
+    /// Check if there is a state to go back to
+    /// # Returns
+    /// True if `undo` would change the current state
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let mut history = UndoStack::new(1, 10);
+    /// history.push_state(2);
+    /// assert!(history.can_undo());
+    /// ```
+    pub fn can_undo(&self) -> bool {
+        !self.past.is_empty()
+    }
+
+    /// Check if there is an undone state to go forward to
+    /// # Returns
+    /// True if `redo` would change the current state
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let mut history = UndoStack::new(1, 10);
+    /// history.push_state(2);
+    /// history.undo();
+    /// assert!(history.can_redo());
+    /// ```
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Get the number of states `undo` can go back
+    /// # Returns
+    /// The number of past states
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let mut history = UndoStack::new(0, 10);
+    /// history.push_state(1);
+    /// history.push_state(2);
+    /// assert_eq!(history.undo_len(), 2);
+    /// ```
+    pub fn undo_len(&self) -> usize {
+        self.past.len()
+    }
+
+    /// Get the number of states `redo` can go forward
+    /// # Returns
+    /// The number of undone states
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let mut history = UndoStack::new(0, 10);
+    /// history.push_state(1);
+    /// history.undo();
+    /// assert_eq!(history.redo_len(), 1);
+    /// ```
+    pub fn redo_len(&self) -> usize {
+        self.undone.len()
+    }
+
+    /// Check if the current state differs from the saved one
+    /// # Returns
+    /// True if the current state isn't the state current at the last `mark_saved`, or at the creation of the history
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let mut history = UndoStack::new(0, 10);
+    /// assert!(!history.is_dirty());
+    ///
+    /// history.push_state(1);
+    /// assert!(history.is_dirty());
+    ///
+    /// history.undo();
+    /// assert!(!history.is_dirty());
+    /// ```
+    pub fn is_dirty(&self) -> bool {
+        self.saved != self.current.0
+    }
+
+    /// Record the current state as saved
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let mut history = UndoStack::new(0, 10);
+    /// history.push_state(1);
+    /// history.mark_saved();
+    /// assert!(!history.is_dirty());
+    /// ```
+    pub fn mark_saved(&mut self) {
+        self.saved = self.current.0;
+    }
+
+    /// Discard every past and undone state, keeping the current one
+    /// # Example
+    /// ```
+    /// use data_structures::history::undo_stack::UndoStack;
+    ///
+    /// let mut history = UndoStack::new(0, 10);
+    /// history.push_state(1);
+    /// history.clear_history();
+    ///
+    /// assert!(!history.can_undo());
+    /// assert_eq!(history.current(), &1);
+    /// ```
+    pub fn clear_history(&mut self) {
+        self.past.clear();
+        self.undone.clear();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for UndoStack<T> {
+    /// Prints the current state and the size of both sides of the history
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UndoStack")
+            .field("current", &self.current.1)
+            .field("undo_len", &self.past.len())
+            .field("redo_len", &self.undone.len())
+            .field("dirty", &self.is_dirty())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_undo_stack_against_vectors() {
+        let depth = 5;
+        let mut history = UndoStack::new(0u32, depth);
+        let (mut past, mut current, mut undone): (Vec<u32>, u32, Vec<u32>) = (vec![], 0, vec![]);
+        let mut saved = 0u32; // states are all distinct, so the state itself identifies it
+        let mut rng = Lcg::new(7);
+
+        for state in 1..3_000u32 {
+            let seed = rng.next_u64();
+            match (seed >> 33) % 4 {
+                0 => {
+                    let expected = past.pop().inspect(|&previous| {
+                        undone.push(std::mem::replace(&mut current, previous));
+                    });
+                    assert_eq!(history.undo().copied(), expected);
+                }
+                1 => {
+                    let expected = undone.pop().inspect(|&next| {
+                        past.push(std::mem::replace(&mut current, next));
+                    });
+                    assert_eq!(history.redo().copied(), expected);
+                }
+                2 => {
+                    history.mark_saved();
+                    saved = current;
+                }
+                _ => {
+                    past.push(std::mem::replace(&mut current, state));
+                    let evicted = (past.len() > depth).then(|| past.remove(0));
+                    undone.clear();
+                    assert_eq!(history.push_state(state), evicted);
+                }
+            }
+
+            assert_eq!(history.current(), &current);
+            assert_eq!(history.undo_len(), past.len());
+            assert_eq!(history.redo_len(), undone.len());
+            assert_eq!(history.is_dirty(), saved != current);
+        }
+    }
+}
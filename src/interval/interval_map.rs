@@ -0,0 +1,334 @@
+//! This module implements a map from half-open ranges of keys to values.
+//! Ranges never overlap: inserting a range overwrites the parts of the existing ranges it covers, splitting them if needed,
+//! and adjacent ranges holding equal values are coalesced into a single range.
+//!
+//! # Performance
+//! - O(log n) for `get`
+//! - O(k log n) for `insert` and `remove`, where k is the number of existing ranges overlapped
+//! - O(log n + k) for `overlaps`, where k is the number of returned ranges
+//!
+//! # Implementation Details
+//! - The ranges are stored in a `BTreeMap` indexed by their start, holding the end and the value of each range.
+//! - Because ranges don't overlap, the range containing a point is always the last one starting at or before it.
+//! - Empty ranges (`start >= end`) are ignored.
+//!
+//! # Usage
+//! ```
+//! use data_structures::interval::interval_map::IntervalMap;
+//!
+//! let mut permissions = IntervalMap::new();
+//!
+//! permissions.insert(0..100, "r");
+//! permissions.insert(40..60, "rw");
+//! permissions.insert(60..80, "r");
+//!
+//! assert_eq!(permissions.get(&50), Some(&"rw"));
+//! assert_eq!(permissions.get(&70), Some(&"r"));
+//! assert_eq!(permissions.get(&100), None);
+//!
+//! // 60..80 was merged with the remaining 80..100 range
+//! assert_eq!(
+//!     permissions.iter().collect::<Vec<_>>(),
+//!     vec![(0..40, &"r"), (40..60, &"rw"), (60..100, &"r")]
+//! );
+//! ```
+//!
+use std::{collections::BTreeMap, ops::Range};
+
+/// Struct representing a map from non-overlapping half-open ranges to values
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalMap<K, V> {
+    ranges: BTreeMap<K, (K, V)>, // start -> (end, value)
+}
+
+impl<K: Ord + Clone, V: Eq + Clone> IntervalMap<K, V> {
+    /// Create a new empty IntervalMap
+    ///
+    /// # Returns
+    /// A new IntervalMap instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_map::IntervalMap;
+    ///
+    /// let map: IntervalMap<u32, bool> = IntervalMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        IntervalMap {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    /// Get the number of ranges in the map
+    /// # Returns
+    /// The number of stored (coalesced) ranges
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..10, 'a');
+    /// map.insert(10..20, 'a');
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Check if the map is empty
+    /// # Returns
+    /// True if the map has no ranges, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_map::IntervalMap;
+    ///
+    /// let map: IntervalMap<i32, ()> = IntervalMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Get the value of the range containing a point
+    /// # Arguments
+    /// * `point`: The key to be searched
+    /// # Returns
+    /// A reference to the value, or None if no range contains the point
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(10..20, "mapped");
+    ///
+    /// assert_eq!(map.get(&10), Some(&"mapped"));
+    /// assert_eq!(map.get(&20), None);
+    /// ```
+    pub fn get(&self, point: &K) -> Option<&V> {
+        self.ranges
+            .range(..=point.clone())
+            .next_back()
+            .filter(|(_, (end, _))| point < end)
+            .map(|(_, (_, value))| value)
+    }
+
+    /// Map a range of keys to a value
+    /// The parts of existing ranges covered by the new range are overwritten, and the new range is merged with the adjacent
+    /// ranges holding an equal value.
+    /// # Arguments
+    /// * `range`: The half-open range of keys
+    /// * `value`: The value of the range
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    ///
+    /// map.insert(0..10, 1);
+    /// map.insert(5..15, 2);
+    ///
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), vec![(0..5, &1), (5..15, &2)]);
+    /// ```
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        if range.start >= range.end {
+            return;
+        }
+
+        self.carve(&range);
+
+        let mut start = range.start;
+        let mut end = range.end;
+
+        // Merge with the previous range if it ends where this one starts with the same value
+        let previous = self
+            .ranges
+            .range(..start.clone())
+            .next_back()
+            .filter(|(_, (prev_end, prev_value))| *prev_end == start && *prev_value == value)
+            .map(|(prev_start, _)| prev_start.clone());
+        if let Some(previous) = previous {
+            self.ranges.remove(&previous);
+            start = previous;
+        }
+
+        // Merge with the next range if it starts where this one ends with the same value
+        if matches!(self.ranges.get(&end), Some((_, next_value)) if *next_value == value) {
+            let (next_end, _) = self.ranges.remove(&end).unwrap();
+            end = next_end;
+        }
+
+        self.ranges.insert(start, (end, value));
+    }
+
+    /// Remove the mapping of a range of keys
+    /// Existing ranges partially covered by the removed range are shrunk or split.
+    /// # Arguments
+    /// * `range`: The half-open range of keys to be unmapped
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    ///
+    /// map.insert(0..10, 'x');
+    /// map.remove(3..5);
+    ///
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), vec![(0..3, &'x'), (5..10, &'x')]);
+    /// ```
+    pub fn remove(&mut self, range: Range<K>) {
+        if range.start < range.end {
+            self.carve(&range);
+        }
+    }
+
+    // Remove every key of the range from the existing ranges
+    fn carve(&mut self, range: &Range<K>) {
+        let mut overlapping: Vec<K> = self
+            .ranges
+            .range(..range.start.clone())
+            .next_back()
+            .filter(|(_, (end, _))| *end > range.start)
+            .map(|(start, _)| start.clone())
+            .into_iter()
+            .collect();
+        overlapping.extend(
+            self.ranges
+                .range(range.start.clone()..range.end.clone())
+                .map(|(start, _)| start.clone()),
+        );
+
+        for start in overlapping {
+            let (end, value) = self.ranges.remove(&start).unwrap();
+
+            if start < range.start {
+                self.ranges
+                    .insert(start, (range.start.clone(), value.clone()));
+            }
+            if end > range.end {
+                self.ranges.insert(range.end.clone(), (end, value));
+            }
+        }
+    }
+
+    /// Get an iterator over the ranges overlapping a range of keys
+    /// # Arguments
+    /// * `range`: The half-open range of keys
+    /// # Returns
+    /// An iterator of (range, value) tuples ordered by start. The returned ranges aren't clipped to the given range.
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..10, 'a');
+    /// map.insert(20..30, 'b');
+    /// map.insert(40..50, 'c');
+    ///
+    /// let found: Vec<_> = map.overlaps(5..25).collect();
+    /// assert_eq!(found, vec![(0..10, &'a'), (20..30, &'b')]);
+    /// ```
+    pub fn overlaps(&self, range: Range<K>) -> impl Iterator<Item = (Range<K>, &V)> + '_ {
+        let first = if range.start < range.end {
+            self.ranges
+                .range(..range.start.clone())
+                .next_back()
+                .filter(|(_, (end, _))| *end > range.start)
+        } else {
+            None
+        };
+
+        let rest = if range.start < range.end {
+            Some(self.ranges.range(range.start.clone()..range.end.clone()))
+        } else {
+            None
+        };
+
+        first
+            .into_iter()
+            .chain(rest.into_iter().flatten())
+            .map(|(start, (end, value))| (start.clone()..end.clone(), value))
+    }
+
+    /// Get an iterator over all the ranges of the map
+    /// # Returns
+    /// An iterator of (range, value) tuples ordered by start
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(5..6, true);
+    /// map.insert(0..1, false);
+    ///
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), vec![(0..1, &false), (5..6, &true)]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (Range<K>, &V)> + '_ {
+        self.ranges
+            .iter()
+            .map(|(start, (end, value))| (start.clone()..end.clone(), value))
+    }
+}
+
+impl<K: Ord + Clone, V: Eq + Clone> Default for IntervalMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_interval_map_against_array() {
+        // Compare with a plain array holding one value per key
+        let mut map = IntervalMap::new();
+        let mut reference: Vec<Option<u8>> = vec![None; 100];
+        let mut rng = Lcg::new(3);
+
+        for step in 0..500 {
+            let seed = rng.next_u64();
+            let start = (seed >> 33) as usize % 100;
+            let end = start + ((seed >> 20) as usize % (100 - start)) + 1;
+            let value = (seed >> 50) as u8 % 3;
+
+            if step % 4 == 0 {
+                map.remove(start..end);
+                reference[start..end].fill(None);
+            } else {
+                map.insert(start..end, value);
+                reference[start..end].fill(Some(value));
+            }
+
+            for (point, expected) in reference.iter().enumerate() {
+                assert_eq!(map.get(&point), expected.as_ref());
+            }
+
+            // Ranges are disjoint, ordered and coalesced
+            let ranges: Vec<_> = map.iter().collect();
+            for pair in ranges.windows(2) {
+                assert!(pair[0].0.end <= pair[1].0.start);
+                assert!(pair[0].0.end < pair[1].0.start || pair[0].1 != pair[1].1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_interval_map_overlaps() {
+        let mut map = IntervalMap::new();
+        map.insert(0..10, 'a');
+        map.insert(10..20, 'b');
+        map.insert(30..40, 'c');
+
+        assert_eq!(
+            map.overlaps(10..11).collect::<Vec<_>>(),
+            vec![(10..20, &'b')]
+        );
+        assert_eq!(map.overlaps(20..30).count(), 0);
+        assert_eq!(map.overlaps(5..5).count(), 0);
+        assert_eq!(map.overlaps(0..100).count(), 3);
+    }
+}
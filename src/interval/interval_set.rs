@@ -0,0 +1,257 @@
+//! This module implements a set of keys stored as disjoint half-open ranges.
+//! Inserted ranges are merged with the ranges they overlap or touch, and removed ranges split the ranges they cut,
+//! so the set always holds the minimum number of ranges.
+//!
+//! # Performance
+//! - O(log n) for `contains`
+//! - O(k log n) for `insert` and `remove`, where k is the number of existing ranges overlapped
+//!
+//! # Implementation Details
+//! - The set is a lightweight wrapper around an `IntervalMap<K, ()>`, which already splits and coalesces ranges.
+//! - `gaps` walks the ranges overlapping a window and yields the uncovered parts of it, which is the query needed for free-space tracking.
+//!
+//! # Usage
+//! ```
+//! use data_structures::interval::interval_set::IntervalSet;
+//!
+//! let mut used = IntervalSet::new();
+//!
+//! used.insert(0..10);
+//! used.insert(20..30);
+//! used.insert(10..15);
+//!
+//! assert!(used.contains(&12));
+//! assert_eq!(used.iter().collect::<Vec<_>>(), vec![0..15, 20..30]);
+//! assert_eq!(used.gaps(0..40).collect::<Vec<_>>(), vec![15..20, 30..40]);
+//! ```
+//!
+use std::ops::Range;
+
+use super::interval_map::IntervalMap;
+
+/// Struct representing a set of keys as disjoint half-open ranges
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<K> {
+    ranges: IntervalMap<K, ()>,
+}
+
+impl<K: Ord + Clone> IntervalSet<K> {
+    /// Create a new empty IntervalSet
+    ///
+    /// # Returns
+    /// A new IntervalSet instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_set::IntervalSet;
+    ///
+    /// let set: IntervalSet<u64> = IntervalSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        IntervalSet {
+            ranges: IntervalMap::new(),
+        }
+    }
+
+    /// Get the number of disjoint ranges in the set
+    /// # Returns
+    /// The number of ranges
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_set::IntervalSet;
+    ///
+    /// let mut set = IntervalSet::new();
+    /// set.insert(0..5);
+    /// set.insert(3..8);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Check if the set is empty
+    /// # Returns
+    /// True if the set has no ranges, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_set::IntervalSet;
+    ///
+    /// let set: IntervalSet<i32> = IntervalSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Check if a point belongs to a range of the set
+    /// # Arguments
+    /// * `point`: The key to be searched
+    /// # Returns
+    /// True if a range contains the point, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_set::IntervalSet;
+    ///
+    /// let mut set = IntervalSet::new();
+    /// set.insert(1..3);
+    ///
+    /// assert!(set.contains(&2));
+    /// assert!(!set.contains(&3));
+    /// ```
+    pub fn contains(&self, point: &K) -> bool {
+        self.ranges.get(point).is_some()
+    }
+
+    /// Add a range to the set, merging it with the ranges it overlaps or touches
+    /// # Arguments
+    /// * `range`: The half-open range to be added
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_set::IntervalSet;
+    ///
+    /// let mut set = IntervalSet::new();
+    /// set.insert(0..2);
+    /// set.insert(4..6);
+    /// set.insert(1..5);
+    ///
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![0..6]);
+    /// ```
+    pub fn insert(&mut self, range: Range<K>) {
+        self.ranges.insert(range, ());
+    }
+
+    /// Remove a range from the set, splitting the ranges it cuts
+    /// # Arguments
+    /// * `range`: The half-open range to be removed
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_set::IntervalSet;
+    ///
+    /// let mut set = IntervalSet::new();
+    /// set.insert(0..10);
+    /// set.remove(4..6);
+    ///
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![0..4, 6..10]);
+    /// ```
+    pub fn remove(&mut self, range: Range<K>) {
+        self.ranges.remove(range);
+    }
+
+    /// Get an iterator over the ranges of the set
+    /// # Returns
+    /// An iterator of disjoint ranges, ordered by start
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_set::IntervalSet;
+    ///
+    /// let mut set = IntervalSet::new();
+    /// set.insert(5..6);
+    /// set.insert(1..2);
+    ///
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![1..2, 5..6]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = Range<K>> + '_ {
+        self.ranges.iter().map(|(range, _)| range)
+    }
+
+    /// Get an iterator over the gaps of the set inside a window
+    /// # Arguments
+    /// * `within`: The half-open window where the gaps are searched
+    /// # Returns
+    /// An iterator of the maximal ranges of the window not covered by the set, ordered by start
+    /// # Example
+    /// ```
+    /// use data_structures::interval::interval_set::IntervalSet;
+    ///
+    /// let mut set = IntervalSet::new();
+    /// set.insert(2..4);
+    ///
+    /// assert_eq!(set.gaps(0..10).collect::<Vec<_>>(), vec![0..2, 4..10]);
+    /// assert_eq!(set.gaps(2..4).count(), 0);
+    /// ```
+    pub fn gaps(&self, within: Range<K>) -> impl Iterator<Item = Range<K>> + '_ {
+        let mut cursor = within.start.clone();
+        let end = within.end.clone();
+
+        let covered: Vec<Range<K>> = self
+            .ranges
+            .overlaps(within)
+            .map(|(range, _)| range)
+            .collect();
+        let mut gaps = Vec::new();
+
+        for range in covered {
+            if cursor < range.start {
+                gaps.push(cursor.clone()..range.start.clone());
+            }
+            if range.end > cursor {
+                cursor = range.end;
+            }
+        }
+        if cursor < end {
+            gaps.push(cursor..end);
+        }
+
+        gaps.into_iter()
+    }
+}
+
+impl<K: Ord + Clone> Default for IntervalSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_set_free_space() {
+        // Allocate blocks in a 1000 bytes region and look for free space
+        let mut used = IntervalSet::new();
+
+        used.insert(0..100);
+        used.insert(200..300);
+        used.insert(300..350);
+        used.insert(900..1_000);
+
+        assert_eq!(used.len(), 3);
+        assert_eq!(
+            used.gaps(0..1_000).collect::<Vec<_>>(),
+            vec![100..200, 350..900]
+        );
+
+        used.remove(250..260);
+        assert_eq!(
+            used.iter().collect::<Vec<_>>(),
+            vec![0..100, 200..250, 260..350, 900..1_000]
+        );
+        assert!(!used.contains(&255));
+        assert!(used.contains(&260));
+
+        // Gaps are clipped to the window
+        assert_eq!(used.gaps(50..220).collect::<Vec<_>>(), vec![100..200]);
+        assert_eq!(used.gaps(240..270).collect::<Vec<_>>(), vec![250..260]);
+    }
+
+    #[test]
+    fn test_interval_set_merge_everything() {
+        let mut set = IntervalSet::new();
+
+        for i in (0..100).step_by(2) {
+            set.insert(i..i + 1);
+        }
+        assert_eq!(set.len(), 50);
+
+        for i in (1..100).step_by(2) {
+            set.insert(i..i + 1);
+        }
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0..100]);
+
+        set.remove(0..100);
+        assert!(set.is_empty());
+    }
+}
@@ -0,0 +1,484 @@
+//! This module implements a byte queue stored as a chain of reference counted chunks, for parsers consuming framed data.
+//! Bytes are appended as whole chunks and consumed from the front. Consuming or splitting off a prefix only moves the
+//! bounds of the chunks involved, so a frame can be cut out of the stream and handed to another stage without copying,
+//! and bytes are only copied when a caller asks for a contiguous slice.
+//!
+//! # Performance
+//! - O(1) for `push`, `len` and `chunk`
+//! - O(k) for `advance` and `split_to`, with k the number of chunks crossed
+//! - O(n) for `copy_to_slice` and `to_vec` of n bytes
+//!
+//! # Implementation Details
+//! - Each chunk is a range of an `Rc<[u8]>`. Splitting a chunk in two clones the `Rc`, so both halves share the bytes.
+//! - The chunks are kept in a `VecDeque` in stream order. A chunk is dropped once all its bytes are consumed; its memory
+//!   is freed when no other queue shares it.
+//! - Cloning the queue clones the chunk references, not the bytes.
+//!
+//! # Usage
+//! ```
+//! use data_structures::io::chunked_bytes::ChunkedBytes;
+//!
+//! // Two frames of "length byte + payload", received across three reads
+//! let mut stream = ChunkedBytes::new();
+//! stream.push(&b"\x03ab"[..]);
+//! stream.push(&b"c\x02"[..]);
+//! stream.push(&b"de"[..]);
+//!
+//! let mut frames = Vec::new();
+//! while let Some(len) = stream.get(0) {
+//!     if stream.len() < 1 + len as usize {
+//!         break;
+//!     }
+//!     stream.advance(1).unwrap();
+//!     frames.push(stream.split_to(len as usize).unwrap());
+//! }
+//!
+//! assert_eq!(frames[0].to_vec(), b"abc");
+//! assert_eq!(frames[1].to_vec(), b"de");
+//! assert!(stream.is_empty());
+//! ```
+//!
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::error::DataStructureError;
+
+/// A range of shared bytes
+#[derive(Clone)]
+struct Chunk {
+    data: Rc<[u8]>,
+    start: usize,
+    end: usize,
+}
+
+impl Chunk {
+    fn bytes(&self) -> &[u8] {
+        &self.data[self.start..self.end]
+    }
+
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+/// Struct representing a queue of bytes stored as a chain of shared chunks
+///
+#[derive(Clone, Default)]
+pub struct ChunkedBytes {
+    chunks: VecDeque<Chunk>,
+    len: usize,
+}
+
+impl ChunkedBytes {
+    /// Create a new empty ChunkedBytes
+    /// # Returns
+    /// A new ChunkedBytes instance
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let bytes = ChunkedBytes::new();
+    /// assert!(bytes.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        ChunkedBytes {
+            chunks: VecDeque::new(),
+            len: 0,
+        }
+    }
+
+    /// Get the number of bytes in the queue
+    /// # Returns
+    /// The total length of the chunks
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let mut bytes = ChunkedBytes::new();
+    /// bytes.push(vec![1, 2, 3]);
+    /// bytes.push(vec![4]);
+    /// assert_eq!(bytes.len(), 4);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the queue holds no byte
+    /// # Returns
+    /// True if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let mut bytes = ChunkedBytes::new();
+    /// bytes.push(Vec::new());
+    /// assert!(bytes.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of chunks the bytes are spread over
+    /// # Returns
+    /// The number of non-empty chunks
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let mut bytes = ChunkedBytes::new();
+    /// bytes.push(vec![1, 2]);
+    /// bytes.push(vec![3]);
+    /// assert_eq!(bytes.chunk_count(), 2);
+    /// ```
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Append a chunk at the end of the queue. An `Rc<[u8]>` is shared without copying its bytes.
+    /// # Arguments
+    /// * `chunk`: The bytes to append
+    /// # Example
+    /// ```
+    /// use std::rc::Rc;
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let shared: Rc<[u8]> = Rc::from(&b"payload"[..]);
+    /// let mut bytes = ChunkedBytes::new();
+    /// bytes.push(shared.clone());
+    /// bytes.push(&b"!"[..]);
+    ///
+    /// assert_eq!(bytes.to_vec(), b"payload!");
+    /// assert_eq!(Rc::strong_count(&shared), 2);
+    /// ```
+    pub fn push(&mut self, chunk: impl Into<Rc<[u8]>>) {
+        let data = chunk.into();
+        if data.is_empty() {
+            return;
+        }
+        self.len += data.len();
+        self.chunks.push_back(Chunk {
+            end: data.len(),
+            start: 0,
+            data,
+        });
+    }
+
+    /// Append the chunks of another queue, sharing their bytes
+    /// # Arguments
+    /// * `other`: The queue to append, left empty
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let mut head = ChunkedBytes::new();
+    /// head.push(&b"ab"[..]);
+    /// let mut tail = ChunkedBytes::new();
+    /// tail.push(&b"cd"[..]);
+    ///
+    /// head.append(&mut tail);
+    /// assert_eq!(head.to_vec(), b"abcd");
+    /// assert!(tail.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut ChunkedBytes) {
+        self.len += other.len;
+        self.chunks.append(&mut other.chunks);
+        other.len = 0;
+    }
+
+    /// Get the byte at a position from the front
+    /// # Arguments
+    /// * `index`: The position of the byte
+    /// # Returns
+    /// The byte, or None if `index` is not smaller than the length
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let mut bytes = ChunkedBytes::new();
+    /// bytes.push(vec![1, 2]);
+    /// bytes.push(vec![3]);
+    ///
+    /// assert_eq!(bytes.get(2), Some(3));
+    /// assert_eq!(bytes.get(3), None);
+    /// ```
+    pub fn get(&self, mut index: usize) -> Option<u8> {
+        for chunk in &self.chunks {
+            if index < chunk.len() {
+                return Some(chunk.bytes()[index]);
+            }
+            index -= chunk.len();
+        }
+        None
+    }
+
+    /// Get the contiguous bytes at the front of the queue, the rest of the first chunk
+    /// # Returns
+    /// The bytes of the first chunk, or an empty slice if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let mut bytes = ChunkedBytes::new();
+    /// bytes.push(&b"head"[..]);
+    /// bytes.push(&b"tail"[..]);
+    /// bytes.advance(1).unwrap();
+    ///
+    /// assert_eq!(bytes.chunk(), b"ead");
+    /// ```
+    pub fn chunk(&self) -> &[u8] {
+        self.chunks.front().map_or(&[], Chunk::bytes)
+    }
+
+    /// Iterate over the chunks of the queue
+    /// # Returns
+    /// An iterator over the contiguous slices, from the front to the back
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let mut bytes = ChunkedBytes::new();
+    /// bytes.push(&b"ab"[..]);
+    /// bytes.push(&b"c"[..]);
+    ///
+    /// assert_eq!(bytes.chunks().collect::<Vec<_>>(), vec![&b"ab"[..], &b"c"[..]]);
+    /// ```
+    pub fn chunks(&self) -> impl DoubleEndedIterator<Item = &[u8]> + '_ {
+        self.chunks.iter().map(Chunk::bytes)
+    }
+
+    /// Discard bytes from the front of the queue
+    /// # Arguments
+    /// * `count`: The number of bytes to discard
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the bytes were discarded, Err(DataStructureError::RangeOutOfBounds) if the queue holds fewer bytes,
+    /// in which case nothing is discarded
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let mut bytes = ChunkedBytes::new();
+    /// bytes.push(&b"ab"[..]);
+    /// bytes.push(&b"cd"[..]);
+    ///
+    /// bytes.advance(3).unwrap();
+    /// assert_eq!(bytes.to_vec(), b"d");
+    /// assert!(bytes.advance(2).is_err());
+    /// ```
+    pub fn advance(&mut self, count: usize) -> Result<(), DataStructureError> {
+        self.check_prefix(count)?;
+        self.len -= count;
+
+        let mut remaining = count;
+        while remaining > 0 {
+            let front = self.chunks.front_mut().unwrap();
+            if remaining < front.len() {
+                front.start += remaining;
+                break;
+            }
+            remaining -= front.len();
+            self.chunks.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Split off the bytes at the front of the queue, sharing them with the returned queue
+    /// # Arguments
+    /// * `count`: The number of bytes to split off
+    /// # Returns
+    /// Result<ChunkedBytes, DataStructureError>
+    /// Ok with the first `count` bytes, Err(DataStructureError::RangeOutOfBounds) if the queue holds fewer bytes, in
+    /// which case the queue is unchanged
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let mut bytes = ChunkedBytes::new();
+    /// bytes.push(&b"header"[..]);
+    /// bytes.push(&b"body"[..]);
+    ///
+    /// let header = bytes.split_to(6).unwrap();
+    /// assert_eq!(header.to_vec(), b"header");
+    /// assert_eq!(bytes.to_vec(), b"body");
+    /// ```
+    pub fn split_to(&mut self, count: usize) -> Result<ChunkedBytes, DataStructureError> {
+        self.check_prefix(count)?;
+        self.len -= count;
+
+        let mut prefix = ChunkedBytes::new();
+        prefix.len = count;
+        let mut remaining = count;
+        while remaining > 0 {
+            let front = self.chunks.front_mut().unwrap();
+            if remaining < front.len() {
+                // The chunk straddles the split: both queues keep a range of its bytes
+                let mut head = front.clone();
+                head.end = head.start + remaining;
+                front.start += remaining;
+                prefix.chunks.push_back(head);
+                break;
+            }
+            remaining -= front.len();
+            prefix.chunks.push_back(self.chunks.pop_front().unwrap());
+        }
+        Ok(prefix)
+    }
+
+    /// Copy bytes from the front of the queue into a slice and discard them
+    /// # Arguments
+    /// * `destination`: The slice to fill, its length is the number of bytes copied
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the slice was filled, Err(DataStructureError::RangeOutOfBounds) if the queue holds fewer bytes, in which
+    /// case nothing is copied or discarded
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let mut bytes = ChunkedBytes::new();
+    /// bytes.push(vec![0x12, 0x34]);
+    /// bytes.push(vec![0x56, 0x78, 0xff]);
+    ///
+    /// let mut word = [0; 4];
+    /// bytes.copy_to_slice(&mut word).unwrap();
+    /// assert_eq!(u32::from_be_bytes(word), 0x12345678);
+    /// assert_eq!(bytes.len(), 1);
+    /// ```
+    pub fn copy_to_slice(&mut self, destination: &mut [u8]) -> Result<(), DataStructureError> {
+        self.check_prefix(destination.len())?;
+
+        let mut copied = 0;
+        for chunk in &self.chunks {
+            if copied == destination.len() {
+                break;
+            }
+            let count = chunk.len().min(destination.len() - copied);
+            destination[copied..copied + count].copy_from_slice(&chunk.bytes()[..count]);
+            copied += count;
+        }
+        self.advance(copied)
+    }
+
+    /// Copy the bytes of the queue into a vector, keeping them in the queue
+    /// # Returns
+    /// A vector of the bytes, from the front to the back
+    /// # Example
+    /// ```
+    /// use data_structures::io::chunked_bytes::ChunkedBytes;
+    ///
+    /// let mut bytes = ChunkedBytes::new();
+    /// bytes.push(&b"ab"[..]);
+    /// bytes.push(&b"c"[..]);
+    ///
+    /// assert_eq!(bytes.to_vec(), b"abc");
+    /// assert_eq!(bytes.len(), 3);
+    /// ```
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.len);
+        for chunk in self.chunks() {
+            out.extend_from_slice(chunk);
+        }
+        out
+    }
+
+    // Check that the queue holds at least `count` bytes
+    fn check_prefix(&self, count: usize) -> Result<(), DataStructureError> {
+        if count > self.len {
+            return Err(DataStructureError::RangeOutOfBounds {
+                start: 0,
+                end: count,
+                len: self.len,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<u8>> for ChunkedBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        let mut queue = ChunkedBytes::new();
+        queue.push(bytes);
+        queue
+    }
+}
+
+impl fmt::Debug for ChunkedBytes {
+    /// Prints the length and the number of chunks, not the bytes
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkedBytes")
+            .field("len", &self.len)
+            .field("chunks", &self.chunks.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_chunked_bytes_against_vec() {
+        let mut bytes = ChunkedBytes::new();
+        let mut model: Vec<u8> = Vec::new();
+        let mut rng = Lcg::new(11);
+        let mut next = 0u8;
+
+        for _ in 0..2_000 {
+            let seed = rng.next_u64();
+            let count = (seed >> 40) as usize % 12;
+            match (seed >> 33) % 4 {
+                0 => {
+                    let chunk: Vec<u8> = (0..count).map(|i| next.wrapping_add(i as u8)).collect();
+                    next = next.wrapping_add(count as u8);
+                    model.extend_from_slice(&chunk);
+                    bytes.push(chunk);
+                }
+                1 => {
+                    let result = bytes.advance(count);
+                    assert_eq!(result.is_ok(), count <= model.len());
+                    if result.is_ok() {
+                        model.drain(..count);
+                    }
+                }
+                2 => match bytes.split_to(count) {
+                    Ok(prefix) => {
+                        assert_eq!(prefix.len(), count);
+                        assert_eq!(prefix.to_vec(), model.drain(..count).collect::<Vec<_>>());
+                    }
+                    Err(_) => assert!(count > model.len()),
+                },
+                _ => {
+                    let mut out = vec![0; count];
+                    match bytes.copy_to_slice(&mut out) {
+                        Ok(()) => assert_eq!(out, model.drain(..count).collect::<Vec<_>>()),
+                        Err(_) => assert!(count > model.len()),
+                    }
+                }
+            }
+
+            assert_eq!(bytes.len(), model.len());
+            assert_eq!(bytes.to_vec(), model);
+            assert_eq!(bytes.get(0), model.first().copied());
+            assert!(bytes.chunks().all(|chunk| !chunk.is_empty()));
+        }
+    }
+
+    #[test]
+    fn test_split_shares_chunks() {
+        let data: Rc<[u8]> = Rc::from(&b"0123456789"[..]);
+        let mut bytes = ChunkedBytes::new();
+        bytes.push(data.clone());
+
+        let head = bytes.split_to(4).unwrap();
+        assert_eq!(Rc::strong_count(&data), 3);
+        assert_eq!(head.chunk(), b"0123");
+        assert_eq!(bytes.chunk(), b"456789");
+
+        drop(head);
+        bytes.advance(6).unwrap();
+        assert_eq!(Rc::strong_count(&data), 1);
+        assert_eq!(format!("{:?}", bytes), "ChunkedBytes { len: 0, chunks: 0 }");
+    }
+}
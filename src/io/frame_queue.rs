@@ -0,0 +1,264 @@
+//! This module implements a queue of length-prefixed frames over a `ChunkedBytes` stream.
+//! Frames are written whole, a header holding their length followed by their bytes, and read back whole: bytes received
+//! from a stream can be pushed as they arrive, and `pop_frame` only returns a frame once all its bytes are in, so message
+//! boundaries survive however the stream was cut.
+//!
+//! # Performance
+//! - O(1) for `push_bytes`, O(n) for `push_frame` of n bytes, which copies the frame once
+//! - O(n) for `pop_frame` of n bytes, O(k) for `pop_frame_bytes` with k the number of chunks crossed
+//!
+//! # Implementation Details
+//! - The header is the length of the frame as a 4 byte big endian integer, the common framing of stream protocols.
+//! - `push_frame` writes the header and the frame as a single chunk, so a frame is never half written.
+//! - A partial frame stays in the queue until its last byte arrives. `pop_frame_bytes` returns the frame as chunks
+//!   shared with the stream, without copying.
+//!
+//! # Usage
+//! ```
+//! use data_structures::io::frame_queue::FrameQueue;
+//!
+//! let mut sender = FrameQueue::new();
+//! sender.push_frame(b"hello").unwrap();
+//! sender.push_frame(b"world").unwrap();
+//! let wire = sender.take_bytes().to_vec();
+//!
+//! // The receiver gets the stream in arbitrary pieces
+//! let mut receiver = FrameQueue::new();
+//! receiver.push_bytes(&wire[..7]);
+//! assert_eq!(receiver.pop_frame(), None);
+//!
+//! receiver.push_bytes(&wire[7..]);
+//! assert_eq!(receiver.pop_frame(), Some(b"hello".to_vec()));
+//! assert_eq!(receiver.pop_frame(), Some(b"world".to_vec()));
+//! assert_eq!(receiver.pop_frame(), None);
+//! ```
+//!
+use std::rc::Rc;
+
+use super::chunked_bytes::ChunkedBytes;
+use crate::error::DataStructureError;
+
+/// Number of bytes of a frame header
+pub const HEADER_LEN: usize = 4;
+
+/// Struct representing a queue of length-prefixed frames
+///
+#[derive(Debug, Clone, Default)]
+pub struct FrameQueue {
+    bytes: ChunkedBytes,
+}
+
+impl FrameQueue {
+    /// Create a new empty FrameQueue
+    /// # Returns
+    /// A new FrameQueue instance
+    /// # Example
+    /// ```
+    /// use data_structures::io::frame_queue::FrameQueue;
+    ///
+    /// let frames = FrameQueue::new();
+    /// assert!(frames.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        FrameQueue {
+            bytes: ChunkedBytes::new(),
+        }
+    }
+
+    /// Get the number of bytes buffered, headers and partial frames included
+    /// # Returns
+    /// The length of the underlying stream
+    /// # Example
+    /// ```
+    /// use data_structures::io::frame_queue::FrameQueue;
+    ///
+    /// let mut frames = FrameQueue::new();
+    /// frames.push_frame(b"abc").unwrap();
+    /// assert_eq!(frames.len(), 7);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Check if no byte is buffered
+    /// # Returns
+    /// True if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::io::frame_queue::FrameQueue;
+    ///
+    /// let mut frames = FrameQueue::new();
+    /// frames.push_bytes(&[0][..]);
+    /// assert!(!frames.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Append a whole frame, its header and its bytes
+    /// # Arguments
+    /// * `frame`: The bytes of the frame
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the frame was added, Err(DataStructureError::ElementTooLarge) if its length doesn't fit in the header
+    /// # Example
+    /// ```
+    /// use data_structures::io::frame_queue::FrameQueue;
+    ///
+    /// let mut frames = FrameQueue::new();
+    /// frames.push_frame(b"ping").unwrap();
+    /// assert_eq!(frames.take_bytes().to_vec(), b"\0\0\0\x04ping");
+    /// ```
+    pub fn push_frame(&mut self, frame: &[u8]) -> Result<(), DataStructureError> {
+        let length =
+            u32::try_from(frame.len()).map_err(|_| DataStructureError::ElementTooLarge {
+                size: frame.len(),
+                budget: u32::MAX as usize,
+            })?;
+
+        let mut chunk = Vec::with_capacity(HEADER_LEN + frame.len());
+        chunk.extend_from_slice(&length.to_be_bytes());
+        chunk.extend_from_slice(frame);
+        self.bytes.push(chunk);
+        Ok(())
+    }
+
+    /// Append bytes received from a stream, which may end in the middle of a frame
+    /// # Arguments
+    /// * `bytes`: The bytes received. An `Rc<[u8]>` is shared without copying.
+    /// # Example
+    /// ```
+    /// use data_structures::io::frame_queue::FrameQueue;
+    ///
+    /// let mut frames = FrameQueue::new();
+    /// frames.push_bytes(&b"\0\0\0\x02h"[..]);
+    /// assert_eq!(frames.pop_frame(), None);
+    ///
+    /// frames.push_bytes(&b"i"[..]);
+    /// assert_eq!(frames.pop_frame(), Some(b"hi".to_vec()));
+    /// ```
+    pub fn push_bytes(&mut self, bytes: impl Into<Rc<[u8]>>) {
+        self.bytes.push(bytes);
+    }
+
+    /// Get the length of the first frame, once its header is complete
+    /// # Returns
+    /// The length announced by the first header, or None if fewer than `HEADER_LEN` bytes are buffered
+    /// # Example
+    /// ```
+    /// use data_structures::io::frame_queue::FrameQueue;
+    ///
+    /// let mut frames = FrameQueue::new();
+    /// frames.push_bytes(&b"\0\0\x01\0"[..]);
+    /// assert_eq!(frames.peek_frame_len(), Some(256));
+    /// ```
+    pub fn peek_frame_len(&self) -> Option<usize> {
+        let mut header = [0; HEADER_LEN];
+        for (index, byte) in header.iter_mut().enumerate() {
+            *byte = self.bytes.get(index)?;
+        }
+        Some(u32::from_be_bytes(header) as usize)
+    }
+
+    /// Remove the first frame if all its bytes are buffered, sharing its chunks with the stream
+    /// # Returns
+    /// The bytes of the frame without its header, or None if no frame is complete
+    /// # Example
+    /// ```
+    /// use data_structures::io::frame_queue::FrameQueue;
+    ///
+    /// let mut frames = FrameQueue::new();
+    /// frames.push_frame(b"body").unwrap();
+    ///
+    /// let frame = frames.pop_frame_bytes().unwrap();
+    /// assert_eq!(frame.to_vec(), b"body");
+    /// assert!(frames.is_empty());
+    /// ```
+    pub fn pop_frame_bytes(&mut self) -> Option<ChunkedBytes> {
+        let length = self.peek_frame_len()?;
+        if self.bytes.len() < HEADER_LEN + length {
+            return None;
+        }
+        self.bytes.advance(HEADER_LEN).ok()?;
+        self.bytes.split_to(length).ok()
+    }
+
+    /// Remove the first frame if all its bytes are buffered
+    /// # Returns
+    /// A copy of the bytes of the frame without its header, or None if no frame is complete
+    /// # Example
+    /// ```
+    /// use data_structures::io::frame_queue::FrameQueue;
+    ///
+    /// let mut frames = FrameQueue::new();
+    /// frames.push_frame(b"").unwrap();
+    /// frames.push_frame(b"x").unwrap();
+    ///
+    /// assert_eq!(frames.pop_frame(), Some(Vec::new()));
+    /// assert_eq!(frames.pop_frame(), Some(b"x".to_vec()));
+    /// assert_eq!(frames.pop_frame(), None);
+    /// ```
+    pub fn pop_frame(&mut self) -> Option<Vec<u8>> {
+        self.pop_frame_bytes().map(|frame| frame.to_vec())
+    }
+
+    /// Take every buffered byte, headers and partial frames included, to write them to a stream
+    /// # Returns
+    /// The buffered bytes, leaving the queue empty
+    /// # Example
+    /// ```
+    /// use data_structures::io::frame_queue::FrameQueue;
+    ///
+    /// let mut frames = FrameQueue::new();
+    /// frames.push_frame(b"a").unwrap();
+    ///
+    /// let wire = frames.take_bytes();
+    /// assert_eq!(wire.len(), 5);
+    /// assert!(frames.is_empty());
+    /// ```
+    pub fn take_bytes(&mut self) -> ChunkedBytes {
+        std::mem::take(&mut self.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_frames_survive_any_cut() {
+        let frames: Vec<Vec<u8>> = (0..50u8)
+            .map(|i| (0..(i as usize * 7 % 23) as u8).map(|j| i ^ j).collect())
+            .collect();
+
+        let mut sender = FrameQueue::new();
+        for frame in &frames {
+            sender.push_frame(frame).unwrap();
+        }
+        let wire = sender.take_bytes().to_vec();
+
+        // Deliver the stream in pieces of varying sizes, popping whatever frames are complete
+        let mut receiver = FrameQueue::new();
+        let mut received = Vec::new();
+        let mut rng = Lcg::new(17);
+        let mut offset = 0;
+        while offset < wire.len() {
+            let seed = rng.next_u64();
+            let end = (offset + 1 + (seed >> 33) as usize % 9).min(wire.len());
+            receiver.push_bytes(&wire[offset..end]);
+            offset = end;
+
+            while let Some(frame) = receiver.pop_frame() {
+                received.push(frame);
+            }
+            // What is left never holds a complete frame
+            if let Some(length) = receiver.peek_frame_len() {
+                assert!(receiver.len() < HEADER_LEN + length);
+            }
+        }
+
+        assert_eq!(received, frames);
+        assert!(receiver.is_empty());
+    }
+}
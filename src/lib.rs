@@ -1,6 +1,217 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// The test harness always links std, so the unit tests can use its macros even without the feature
+#[cfg(all(test, not(feature = "std")))]
+#[macro_use]
+extern crate std;
+
 // Declare o módulo linked_list
 pub mod linked_list {
+    pub mod byte_bounded_fifo;
     pub mod circular_queue;
+    pub mod deque;
     pub mod fifo;
+    pub mod lifo;
+    pub mod list;
+    pub mod observer;
+    pub mod overflow;
+    pub mod shared;
+    pub mod skip_list;
     pub mod vertex;
 }
+
+pub mod error;
+
+#[cfg(test)]
+mod test_util;
+
+#[cfg(feature = "std")]
+pub mod time {
+    pub mod calendar_queue;
+}
+
+#[cfg(feature = "std")]
+pub mod tree {
+    pub mod avl_tree;
+    pub mod binary_tree;
+    pub mod bit_trie;
+    pub mod bk_tree;
+    pub mod expr_tree;
+    pub mod hld_index;
+    pub mod lca_index;
+    pub mod llrb_tree;
+    pub mod louds_tree;
+    pub mod nary_tree;
+    pub mod trie;
+    pub mod wavl_tree;
+}
+
+#[cfg(feature = "std")]
+pub mod text {
+    pub mod aho_corasick;
+    pub mod bytes_trie_map;
+}
+
+#[cfg(feature = "std")]
+pub mod interval {
+    pub mod interval_map;
+    pub mod interval_set;
+}
+
+#[cfg(feature = "std")]
+pub mod persistent {
+    pub mod segment_tree;
+    pub mod versioned_map;
+}
+
+#[cfg(feature = "std")]
+pub mod range {
+    pub mod disjoint_sparse_table;
+    pub mod lazy_segment_tree;
+    pub mod merge_sort_tree;
+    pub mod segment_tree;
+    pub mod sqrt_blocks;
+}
+
+#[cfg(feature = "std")]
+pub mod cache {
+    pub mod lru_cache;
+    pub mod memoize;
+    pub mod segmented_cache;
+}
+
+#[cfg(feature = "std")]
+pub mod history {
+    pub mod branching_history;
+    pub mod undo_stack;
+}
+
+#[cfg(feature = "std")]
+pub mod hash {
+    pub mod bloom_filter;
+    pub mod cuckoo_set;
+    pub mod filtered_map;
+    pub mod mphf;
+}
+
+#[cfg(feature = "std")]
+pub mod heap {
+    pub mod bucket_queue;
+    pub mod meldable;
+    pub mod priority_map;
+    pub mod skew_heap;
+}
+
+#[cfg(feature = "std")]
+pub mod schedule {
+    pub mod deadline_queue;
+}
+
+#[cfg(feature = "std")]
+pub mod rate {
+    pub mod paced_queue;
+}
+
+#[cfg(feature = "std")]
+pub mod pool {
+    pub mod buffer_pool;
+}
+
+#[cfg(feature = "std")]
+pub mod io {
+    pub mod chunked_bytes;
+    pub mod frame_queue;
+}
+
+#[cfg(feature = "std")]
+pub mod lsm {
+    pub mod mem_table;
+    pub mod skip_list;
+    pub mod sorted_run;
+    pub mod store;
+}
+
+#[cfg(feature = "std")]
+pub mod ring {
+    pub mod ring_buffer;
+}
+
+#[cfg(feature = "std")]
+pub mod sort {
+    pub mod bucket;
+    pub mod external;
+    pub mod radix;
+}
+
+#[cfg(feature = "std")]
+pub mod static_map {
+    pub mod perfect_hash_map;
+    pub mod sorted_slice_map;
+}
+
+pub mod storage {
+    pub mod arena;
+    pub mod free_list_vec;
+    pub mod heap_size;
+    pub mod node_storage;
+}
+
+#[cfg(feature = "std")]
+pub mod persist {
+    pub mod disk_fifo;
+    pub mod format;
+    pub mod wal;
+}
+
+#[cfg(feature = "std")]
+pub mod bits {
+    pub mod bit_set;
+    pub mod rank_select;
+}
+
+#[cfg(feature = "std")]
+pub mod graph {
+    pub mod algorithms;
+    pub mod delta;
+    pub mod dynamic_connectivity;
+    pub mod io;
+    pub mod union_find;
+    pub mod vertex_graph;
+    pub mod view;
+}
+
+#[cfg(feature = "std")]
+pub mod visualize {
+    pub mod topology;
+}
+
+#[cfg(feature = "std")]
+pub mod cow {
+    pub mod cow_container;
+}
+
+#[cfg(feature = "std")]
+pub mod channel {
+    pub mod priority;
+}
+
+#[cfg(feature = "std")]
+pub mod concurrent {
+    pub mod broadcast;
+    pub mod lru;
+    pub mod ms_queue;
+    pub mod striped_counter;
+}
+
+#[cfg(feature = "arc")]
+pub mod sync {
+    pub mod concurrent_circular_queue;
+    pub mod concurrent_fifo;
+}
+
+#[cfg(feature = "std")]
+pub mod reclaim {
+    pub mod epoch;
+}
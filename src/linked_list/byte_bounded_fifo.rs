@@ -0,0 +1,284 @@
+//! This module implements a FIFO bounded by the total size of its elements in bytes, in addition to their count.
+//! Elements report their size through the `HeapSize` trait. When a push would exceed the budget, the FIFO either rejects
+//! the new element or evicts the oldest ones until it fits, depending on its configuration.
+//!
+//! # Performance
+//! - O(1) for `pop`
+//! - O(1) for `push` when nothing is evicted, plus O(1) per evicted element
+//!
+//! # Implementation Details
+//! - The FIFO is a wrapper over `FIFO` that keeps a running total of `byte_size` of its elements.
+//! - The size of an element is measured when it is pushed and again when it leaves, so elements must not change their
+//!   heap size while they are queued.
+//! - An element larger than the whole budget is always rejected, since evicting everything wouldn't make room for it.
+//!
+//! # Usage
+//! ```
+//! use data_structures::error::DataStructureError;
+//! use data_structures::linked_list::byte_bounded_fifo::ByteBoundedFifo;
+//!
+//! // Room for two 8-byte elements
+//! let mut fifo = ByteBoundedFifo::new(0, 16);
+//!
+//! fifo.push(1u64).unwrap();
+//! fifo.push(2u64).unwrap();
+//! assert_eq!(fifo.push(3u64), Err(DataStructureError::BudgetExceeded));
+//!
+//! // In eviction mode the oldest elements make room for the new one
+//! fifo.set_evict_oldest(true);
+//! assert_eq!(fifo.push(3u64), Ok(vec![1]));
+//! assert_eq!(fifo.pop(), Some(2));
+//! ```
+//!
+use super::fifo::FIFO;
+use crate::error::DataStructureError;
+use crate::storage::heap_size::{byte_size, HeapSize};
+use alloc::vec::Vec;
+
+/// Struct representing a FIFO bounded by the total byte size of its elements
+///
+#[derive(Debug)]
+pub struct ByteBoundedFifo<T> {
+    fifo: FIFO<T>,
+    bytes: usize,
+    max_bytes: usize,
+    evict_oldest: bool,
+}
+
+impl<T: HeapSize> ByteBoundedFifo<T> {
+    /// Create a new ByteBoundedFifo that rejects the elements exceeding its bounds
+    ///
+    /// # Arguments
+    /// * `max_size`: The maximum number of elements. If 0, there is no limit on the count.
+    /// * `max_bytes`: The maximum total byte size of the elements, as measured by `byte_size`
+    ///
+    /// # Returns
+    /// A new ByteBoundedFifo instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::byte_bounded_fifo::ByteBoundedFifo;
+    ///
+    /// let fifo: ByteBoundedFifo<String> = ByteBoundedFifo::new(100, 4096);
+    /// assert_eq!(fifo.max_bytes(), 4096);
+    /// assert_eq!(fifo.max_size(), 100);
+    /// ```
+    pub fn new(max_size: usize, max_bytes: usize) -> Self {
+        ByteBoundedFifo {
+            fifo: FIFO::new(max_size),
+            bytes: 0,
+            max_bytes,
+            evict_oldest: false,
+        }
+    }
+
+    /// Get the number of elements in the queue
+    /// # Returns
+    /// The number of elements
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::byte_bounded_fifo::ByteBoundedFifo;
+    ///
+    /// let mut fifo = ByteBoundedFifo::new(0, 64);
+    /// fifo.push(1u8).unwrap();
+    /// assert_eq!(fifo.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.fifo.len()
+    }
+
+    /// Check if the queue is empty
+    /// # Returns
+    /// True if the queue is empty, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::byte_bounded_fifo::ByteBoundedFifo;
+    ///
+    /// let fifo: ByteBoundedFifo<u8> = ByteBoundedFifo::new(0, 64);
+    /// assert!(fifo.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.fifo.is_empty()
+    }
+
+    /// Get the maximum number of elements
+    /// # Returns
+    /// The maximum number of elements, 0 meaning no limit
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::byte_bounded_fifo::ByteBoundedFifo;
+    ///
+    /// let fifo: ByteBoundedFifo<u8> = ByteBoundedFifo::new(3, 64);
+    /// assert_eq!(fifo.max_size(), 3);
+    /// ```
+    pub fn max_size(&self) -> usize {
+        self.fifo.max_size()
+    }
+
+    /// Get the total byte size of the elements in the queue
+    /// # Returns
+    /// The bytes currently charged against the budget
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::byte_bounded_fifo::ByteBoundedFifo;
+    ///
+    /// let mut fifo = ByteBoundedFifo::new(0, 64);
+    /// fifo.push(String::with_capacity(10)).unwrap();
+    /// assert_eq!(fifo.bytes(), 10 + std::mem::size_of::<String>());
+    /// ```
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Get the byte budget
+    /// # Returns
+    /// The maximum total byte size of the elements
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::byte_bounded_fifo::ByteBoundedFifo;
+    ///
+    /// let fifo: ByteBoundedFifo<u8> = ByteBoundedFifo::new(0, 64);
+    /// assert_eq!(fifo.max_bytes(), 64);
+    /// ```
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Check if the oldest elements are evicted to make room for new ones
+    /// # Returns
+    /// True in eviction mode, false if pushes exceeding the bounds are rejected
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::byte_bounded_fifo::ByteBoundedFifo;
+    ///
+    /// let fifo: ByteBoundedFifo<u8> = ByteBoundedFifo::new(0, 64);
+    /// assert!(!fifo.evicts_oldest());
+    /// ```
+    pub fn evicts_oldest(&self) -> bool {
+        self.evict_oldest
+    }
+
+    /// Choose between rejecting the pushes that exceed the bounds and evicting the oldest elements to make room
+    /// # Arguments
+    /// * `evict_oldest`: True to evict, false to reject
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::byte_bounded_fifo::ByteBoundedFifo;
+    ///
+    /// let mut fifo = ByteBoundedFifo::new(1, 64);
+    /// fifo.set_evict_oldest(true);
+    ///
+    /// fifo.push(1u8).unwrap();
+    /// assert_eq!(fifo.push(2u8), Ok(vec![1]));
+    /// ```
+    pub fn set_evict_oldest(&mut self, evict_oldest: bool) {
+        self.evict_oldest = evict_oldest;
+    }
+
+    /// Push a new element to the queue
+    ///
+    /// # Arguments
+    /// * `value`: The element to be pushed
+    ///
+    /// # Returns
+    /// Result<Vec<T>, DataStructureError>
+    /// Ok with the elements evicted to make room, oldest first, or Err if the element doesn't fit.
+    /// An element that doesn't fit is dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::byte_bounded_fifo::ByteBoundedFifo;
+    ///
+    /// let mut fifo = ByteBoundedFifo::new(2, 64);
+    ///
+    /// assert_eq!(fifo.push(1u8), Ok(vec![]));
+    /// assert_eq!(fifo.push(2u8), Ok(vec![]));
+    /// assert_eq!(fifo.push(3u8), Err(DataStructureError::Full));
+    ///
+    /// let mut fifo = ByteBoundedFifo::new(0, 4);
+    /// assert!(matches!(fifo.push(0u64), Err(DataStructureError::ElementTooLarge { budget: 4, .. })));
+    /// ```
+    pub fn push(&mut self, value: T) -> Result<Vec<T>, DataStructureError> {
+        let size = byte_size(&value);
+        if size > self.max_bytes {
+            return Err(DataStructureError::ElementTooLarge {
+                size,
+                budget: self.max_bytes,
+            });
+        }
+
+        let mut evicted = Vec::new();
+        while self.bytes + size > self.max_bytes || self.fifo.is_full() {
+            if !self.evict_oldest {
+                return Err(if self.fifo.is_full() {
+                    DataStructureError::Full
+                } else {
+                    DataStructureError::BudgetExceeded
+                });
+            }
+            evicted.push(self.pop().unwrap());
+        }
+
+        self.fifo.push(value)?;
+        self.bytes += size;
+        Ok(evicted)
+    }
+
+    /// Pop the oldest element of the queue
+    /// # Returns
+    /// Option<T>
+    /// Some(T) if the queue is not empty, None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::byte_bounded_fifo::ByteBoundedFifo;
+    ///
+    /// let mut fifo = ByteBoundedFifo::new(0, 64);
+    /// fifo.push(1u8).unwrap();
+    ///
+    /// assert_eq!(fifo.pop(), Some(1));
+    /// assert_eq!(fifo.bytes(), 0);
+    /// assert_eq!(fifo.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let value = self.fifo.pop()?;
+        self.bytes -= byte_size(&value);
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn test_byte_budget_with_variable_sizes() {
+        let overhead = std::mem::size_of::<String>();
+        let message = |capacity: usize| String::with_capacity(capacity);
+
+        let mut fifo = ByteBoundedFifo::new(0, 3 * overhead + 100);
+        fifo.push(message(50)).unwrap();
+        fifo.push(message(40)).unwrap();
+        assert_eq!(
+            fifo.push(message(20)),
+            Err(DataStructureError::BudgetExceeded)
+        );
+        assert_eq!(fifo.len(), 2);
+
+        // Evicting the 50-byte message is enough for a 20-byte one
+        fifo.set_evict_oldest(true);
+        let evicted = fifo.push(message(20)).unwrap();
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].capacity(), 50);
+        assert_eq!(fifo.bytes(), 2 * overhead + 60);
+
+        // A large message evicts everything before it
+        let evicted = fifo.push(message(110)).unwrap();
+        assert_eq!(evicted.len(), 2);
+        assert_eq!(fifo.len(), 1);
+
+        while fifo.pop().is_some() {}
+        assert_eq!(fifo.bytes(), 0);
+    }
+}
@@ -1,10 +1,13 @@
 //! This module implements a circular queue using linked list vertexes. The queue allows adding and removing elements from both ends, maintaining a maximum size.
 //! It uses a linked list of vertexes to store the elements, where each vertex can point to its neighboring vertex.
-//! This implementation doesn't allow to read elements from the queue, only adding and removing them.
+//! The elements can be read without removing them, as clones: `peek_cursor` and `peek` read the cursor and its neighbors,
+//! and the iterators walk the ring from the cursor.
 //!
 //! # Performance
 //! - O(1) for both insert and remove operations
 //! - O(1) for checking if the queue is full or empty
+//! - O(1) for peeking at the cursor or its neighbors, and for each step of an iteration
+//! - O(1) for `append`, which splices two rings, and O(n) for `split_off`, which walks to the cut
 //!  
 //! # Implementation Details
 //! - The queue is implemented using a doubly linked list where each node (vertex) contains a value and pointers to the next and previous nodes.
@@ -33,10 +36,27 @@
 //! assert!(queue.is_empty());
 //! ```
 //!
-use std::{cell::RefCell, rc::Rc};
+use alloc::{boxed::Box, vec::Vec};
+use core::{cmp::Ordering, fmt};
 
+use super::observer::QueueObserver;
+use super::overflow::{OverflowPolicy, QueueView, Reject};
+use super::shared::Shared;
 use super::vertex::{PointerName, Vertex};
-
+use crate::error::DataStructureError;
+#[cfg(feature = "std")]
+use crate::persist::format::{read_length, write_varint, Persist};
+use crate::storage::node_storage::{NodeStorage, RcStorage};
+#[cfg(feature = "std")]
+use crate::visualize::topology::Topology;
+#[cfg(feature = "serde")]
+use serde::{
+    de::Error as _,
+    ser::{SerializeSeq, SerializeStruct},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     Left,
     Right,
@@ -50,6 +70,15 @@ impl From<Direction> for PointerName {
         }
     }
 }
+
+impl Direction {
+    fn opposite(self) -> Direction {
+        match self {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
 /// Struct representing a circular queue using linked list vertexes
 /// This queue allows adding and removing elements from both ends.
 /// The queue maintains a maximum size, and will return an error if an attempt is made to add an element when the queue is full.
@@ -57,13 +86,18 @@ impl From<Direction> for PointerName {
 /// The queue uses a linked list of vertexes to store the elements, where each vertex can point to its neighboring vertexes.
 /// The queue supports operations to add elements to either end and remove elements from either end.
 /// The queue also provides methods to check if it is full or empty, and to get the number of elements in the queue.
+/// The nodes live in a `NodeStorage` backend, reference counted vertexes by default.
+/// What happens when inserting in a full queue is decided by its `OverflowPolicy`, which rejects the insertion by default.
+/// An optional `QueueObserver` is told about the insertions, removals and overflows.
 ///
-#[derive(Debug)]
-pub struct CircularQueue<T> {
-    cursor: Option<Rc<RefCell<Vertex<T>>>>,
+pub struct CircularQueue<T, S: NodeStorage<T> = RcStorage> {
+    storage: S,
+    cursor: Option<S::Handle>,
 
     size: usize,
     max_size: usize,
+    policy: Box<dyn OverflowPolicy<T>>,
+    observer: Option<Box<dyn QueueObserver<T>>>,
 }
 
 impl<T> CircularQueue<T> {
@@ -85,12 +119,213 @@ impl<T> CircularQueue<T> {
     ///
     pub fn new(max_size: usize) -> Self {
         CircularQueue {
+            storage: RcStorage,
+            cursor: None,
+            size: 0,
+            max_size,
+            policy: Box::new(Reject),
+            observer: None,
+        }
+    }
+
+    /// Move all the elements of another queue to the left of the cursor, in O(1) by splicing the two rings
+    /// The elements of `other` keep their order from its cursor, so they come after the elements of this queue in the
+    /// order of `iter`. The overflow policy is not applied.
+    /// # Arguments
+    /// * `other`: The queue to empty into this one
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the elements were moved, Err(DataStructureError::Full) if they don't fit, in which case both queues are
+    /// unchanged
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::from(vec![1, 2]);
+    /// let mut other = CircularQueue::from(vec![3, 4]);
+    ///
+    /// queue.append(&mut other).unwrap();
+    /// assert_eq!(queue.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    /// assert!(other.is_empty());
+    ///
+    /// let mut small = CircularQueue::new(1);
+    /// assert_eq!(small.append(&mut queue), Err(DataStructureError::Full));
+    /// ```
+    pub fn append(&mut self, other: &mut CircularQueue<T>) -> Result<(), DataStructureError> {
+        if self.max_size != 0 && self.size + other.size > self.max_size {
+            return Err(DataStructureError::Full);
+        }
+        let Some(other_front) = other.cursor.take() else {
+            return Ok(());
+        };
+        let other_size = core::mem::take(&mut other.size);
+        let Some(front) = self.cursor.clone() else {
+            self.cursor = Some(other_front);
+            self.size = other_size;
+            return Ok(());
+        };
+
+        // The back of each ring is the left neighbor of its cursor, or the cursor itself in a single node ring
+        let back = self
+            .storage
+            .link(&front, Direction::Left)
+            .unwrap_or_else(|| front.clone());
+        let other_back = self
+            .storage
+            .link(&other_front, Direction::Left)
+            .unwrap_or_else(|| other_front.clone());
+
+        // back -> other_front ... other_back -> front
+        self.storage
+            .set_link(&back, Direction::Right, Some(&other_front));
+        self.storage
+            .set_link(&other_front, Direction::Left, Some(&back));
+        self.storage
+            .set_link(&other_back, Direction::Right, Some(&front));
+        self.storage
+            .set_link(&front, Direction::Left, Some(&other_back));
+
+        self.size += other_size;
+        Ok(())
+    }
+
+    /// Cut the ring in two, keeping the first `at` elements from the cursor and returning the others
+    /// The returned queue has the same maximum size, its cursor on the element at position `at`. The nodes are
+    /// relinked, not copied: finding the cut is O(n), the cut itself O(1).
+    /// # Arguments
+    /// * `at`: The number of elements kept, counted from the cursor to the right
+    /// # Returns
+    /// Result<CircularQueue<T>, DataStructureError>
+    /// Ok with the elements from position `at` on, Err(DataStructureError::IndexOutOfBounds) if `at` is greater than
+    /// the length
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::from(vec![1, 2, 3, 4, 5]);
+    ///
+    /// let tail = queue.split_off(2).unwrap();
+    /// assert_eq!(queue.iter().collect::<Vec<_>>(), vec![1, 2]);
+    /// assert_eq!(tail.iter().collect::<Vec<_>>(), vec![3, 4, 5]);
+    /// assert!(queue.split_off(3).is_err());
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> Result<CircularQueue<T>, DataStructureError> {
+        if at > self.size {
+            return Err(DataStructureError::IndexOutOfBounds {
+                index: at,
+                len: self.size,
+            });
+        }
+
+        let mut tail = CircularQueue::new(self.max_size);
+        if at == self.size {
+            return Ok(tail);
+        }
+        if at == 0 {
+            tail.cursor = self.cursor.take();
+            tail.size = core::mem::take(&mut self.size);
+            return Ok(tail);
+        }
+
+        let front = self.cursor.clone().unwrap();
+        let back = self.storage.link(&front, Direction::Left).unwrap();
+        let kept_back = self.handles().nth(at - 1).unwrap();
+        let tail_front = self.storage.link(&kept_back, Direction::Right).unwrap();
+
+        // Close each part into its own ring. A single node ring has no links.
+        self.close_ring(&front, &kept_back, at);
+        self.close_ring(&tail_front, &back, self.size - at);
+
+        tail.cursor = Some(tail_front);
+        tail.size = self.size - at;
+        self.size = at;
+        Ok(tail)
+    }
+
+    // Link the back of a chain of `len` nodes to its front, making it a ring
+    fn close_ring(&mut self, front: &Shared<Vertex<T>>, back: &Shared<Vertex<T>>, len: usize) {
+        if len == 1 {
+            self.storage.set_link(front, Direction::Left, None);
+            self.storage.set_link(front, Direction::Right, None);
+        } else {
+            self.storage.set_link(back, Direction::Right, Some(front));
+            self.storage.set_link(front, Direction::Left, Some(back));
+        }
+    }
+
+    /// Take a snapshot of the vertexes of the ring, for debugging
+    /// # Returns
+    /// The topology of the vertexes reachable from the cursor, ready to be rendered as DOT or Mermaid
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueue;
+    ///
+    /// let queue = CircularQueue::from(vec!['a', 'b', 'c']);
+    /// let topology = queue.topology();
+    ///
+    /// assert_eq!(topology.node_count(), 3);
+    /// assert!(topology.broken_links().is_empty());
+    /// println!("{}", topology.to_dot());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn topology(&self) -> Topology
+    where
+        T: fmt::Debug,
+    {
+        Topology::from_vertices(&self.cursor)
+    }
+}
+
+impl<T, S: NodeStorage<T>> CircularQueue<T, S> {
+    /// Create a new CircularQueue with the given maximum size, storing its nodes in the given backend
+    ///
+    /// # Arguments
+    /// * `max_size`: The maximum number of elements the queue can hold. If 0, there is no size limit.
+    /// * `storage`: The backend holding the nodes
+    ///
+    /// # Returns
+    /// A new CircularQueue instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::storage::node_storage::SlabStorage;
+    ///
+    /// let mut queue = CircularQueue::with_storage(2, SlabStorage::with_capacity(2));
+    /// queue.insert(1, Direction::Left).unwrap();
+    /// queue.insert(2, Direction::Left).unwrap();
+    /// assert_eq!(queue.insert(3, Direction::Left), Err(DataStructureError::Full));
+    /// ```
+    ///
+    pub fn with_storage(max_size: usize, storage: S) -> Self {
+        CircularQueue {
+            storage,
             cursor: None,
             size: 0,
             max_size,
+            policy: Box::new(Reject),
+            observer: None,
         }
     }
 
+    /// Get the backend holding the nodes
+    /// # Returns
+    /// A reference to the storage
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::storage::node_storage::SlabStorage;
+    ///
+    /// let mut queue = CircularQueue::with_storage(0, SlabStorage::new());
+    /// queue.insert(1, Direction::Left).unwrap();
+    /// assert_eq!(queue.storage().len(), 1);
+    /// ```
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
     /// Get the maximum size of the queue
     /// # Returns
     /// The maximum size of the queue
@@ -110,10 +345,11 @@ impl<T> CircularQueue<T> {
     /// # Arguments
     /// * `max_size`: The new maximum size for the queue
     /// # Returns
-    /// Result<(), &'static str>
+    /// Result<(), DataStructureError>
     /// Ok if the new maximum size is set successfully, Err if the new maximum size is less than the current size
     /// # Example
     /// ```rust
+    /// use data_structures::error::DataStructureError;
     /// use data_structures::linked_list::circular_queue::CircularQueue;
     /// use data_structures::linked_list::circular_queue::Direction;
     ///
@@ -123,14 +359,17 @@ impl<T> CircularQueue<T> {
     /// queue.insert(2, Direction::Right);
     /// queue.insert(3, Direction::Right);
     ///
-    /// assert_eq!(queue.set_max_size(2), Err("New max size is less than current size"));
+    /// assert_eq!(queue.set_max_size(2), Err(DataStructureError::CapacityBelowLen { len: 3, requested: 2 }));
     /// assert_eq!(queue.set_max_size(3), Ok(()));
     ///
-    /// assert_eq!(queue.insert(4, Direction::Right), Err("Queue is full"));
+    /// assert_eq!(queue.insert(4, Direction::Right), Err(DataStructureError::Full));
     /// ```
-    pub fn set_max_size(&mut self, max_size: usize) -> Result<(), &'static str> {
+    pub fn set_max_size(&mut self, max_size: usize) -> Result<(), DataStructureError> {
         if self.len() > max_size {
-            Err("New max size is less than current size")
+            Err(DataStructureError::CapacityBelowLen {
+                len: self.len(),
+                requested: max_size,
+            })
         } else {
             self.max_size = max_size;
             Ok(())
@@ -215,7 +454,7 @@ impl<T> CircularQueue<T> {
     /// * `value`: The value to be added to the queue
     /// * `side`: The side to add the element to (Left or Right)
     /// # Returns
-    /// Result<(), &'static str>
+    /// Result<(), DataStructureError>
     /// Ok if the element was added successfully, Err if the queue is full
     /// # Example
     /// ```
@@ -228,106 +467,265 @@ impl<T> CircularQueue<T> {
     /// queue.insert(3, Direction::Right).unwrap();
     /// queue.insert(4, Direction::Right).unwrap_err();
     /// ```
-    pub fn insert(&mut self, value: T, side: Direction) -> Result<(), &'static str> {
-        // Returns an error if the queue is full
-        if self.is_full() {
-            return Err("Queue is full");
+    pub fn insert(&mut self, value: T, side: Direction) -> Result<(), DataStructureError> {
+        self.insert_or_evict(value, side).map(|_| ())
+    }
+
+    /// Add an element to the queue, applying the overflow policy if the queue is full
+    /// # Arguments
+    /// * `value`: The value to be added to the queue
+    /// * `side`: The side to add the element to (Left or Right)
+    /// # Returns
+    /// Result<Option<T>, DataStructureError>
+    /// Ok with the element evicted to make room, if any, or Err if the queue is full and the policy rejected the element
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::linked_list::overflow::EvictNewest;
+    ///
+    /// let mut queue = CircularQueue::new(2);
+    ///
+    /// assert_eq!(queue.insert_or_evict(1, Direction::Left), Ok(None));
+    /// assert_eq!(queue.insert_or_evict(2, Direction::Left), Ok(None));
+    /// assert_eq!(queue.insert_or_evict(3, Direction::Left), Err(DataStructureError::Full));
+    ///
+    /// queue.set_overflow_policy(EvictNewest);
+    /// assert_eq!(queue.insert_or_evict(3, Direction::Left), Ok(Some(2)));
+    /// ```
+    pub fn insert_or_evict(
+        &mut self,
+        value: T,
+        side: Direction,
+    ) -> Result<Option<T>, DataStructureError> {
+        let evicted = self.make_room(&value)?;
+
+        let new_node = self.storage.alloc(value);
+        self.link_new(new_node, side);
+        Ok(evicted)
+    }
+
+    /// Add an element to the queue, overwriting the element at the cursor if the queue is full, like a ring buffer
+    /// The cursor holds the oldest element when inserting on a single side, so the oldest element is overwritten and
+    /// the cursor moves to the next oldest one. The overflow policy is ignored.
+    /// # Arguments
+    /// * `value`: The value to be added to the queue
+    /// * `side`: The side to add the element to (Left or Right)
+    /// # Returns
+    /// The overwritten element, or None if the queue wasn't full
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    ///
+    /// let mut samples = CircularQueue::new(3);
+    /// for sample in 1..=3 {
+    ///     assert_eq!(samples.insert_overwrite(sample, Direction::Left), None);
+    /// }
+    ///
+    /// assert_eq!(samples.insert_overwrite(4, Direction::Left), Some(1));
+    /// assert_eq!(samples.insert_overwrite(5, Direction::Left), Some(2));
+    /// assert_eq!(samples.iter().collect::<Vec<_>>(), vec![3, 4, 5]);
+    /// ```
+    pub fn insert_overwrite(&mut self, value: T, side: Direction) -> Option<T> {
+        // The next oldest element is on the side opposite to the insertions
+        let overwritten = if self.is_full() {
+            self.notify_overflow(&value);
+            self.remove(side.opposite())
+        } else {
+            None
+        };
+
+        let new_node = self.storage.alloc(value);
+        self.link_new(new_node, side);
+        overwritten
+    }
+
+    /// Add an element to the queue like `insert_or_evict`, but report a failure to allocate the node instead of aborting
+    /// # Arguments
+    /// * `value`: The value to be added to the queue
+    /// * `side`: The side to add the element to (Left or Right)
+    /// # Returns
+    /// Result<Option<T>, DataStructureError>
+    /// Ok with the element evicted to make room, if any, or Err if the queue is full or the node couldn't be allocated.
+    /// Allocation failures are only detected by backends that support them, such as `SlabStorage`.
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::storage::node_storage::SlabStorage;
+    ///
+    /// let mut queue = CircularQueue::with_storage(1, SlabStorage::new());
+    ///
+    /// assert_eq!(queue.try_insert(1, Direction::Left), Ok(None));
+    /// assert_eq!(queue.try_insert(2, Direction::Left), Err(DataStructureError::Full));
+    /// ```
+    #[cfg(feature = "fallible-alloc")]
+    pub fn try_insert(
+        &mut self,
+        value: T,
+        side: Direction,
+    ) -> Result<Option<T>, DataStructureError> {
+        let evicted = self.make_room(&value)?;
+
+        // An eviction released a node, so the allocation below can only fail when nothing was evicted
+        let new_node = self
+            .storage
+            .try_alloc(value)
+            .map_err(|_| DataStructureError::AllocationFailed)?;
+        self.link_new(new_node, side);
+        Ok(evicted)
+    }
+
+    /// Set the behavior of the queue when inserting while it is full
+    /// # Arguments
+    /// * `policy`: The new overflow policy
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::linked_list::overflow::EvictOldest;
+    ///
+    /// let mut queue = CircularQueue::new(1);
+    /// queue.set_overflow_policy(EvictOldest);
+    ///
+    /// queue.insert(1, Direction::Left).unwrap();
+    /// queue.insert(2, Direction::Left).unwrap();
+    /// assert_eq!(queue.remove(Direction::Right), Some(2));
+    /// ```
+    pub fn set_overflow_policy(&mut self, policy: impl OverflowPolicy<T> + 'static) {
+        self.policy = Box::new(policy);
+    }
+
+    /// Set the observer told about the insertions, removals and overflows of the queue, replacing the previous one
+    /// `append` and `split_off` move whole rings without telling the observer, and dropping the queue tells it nothing.
+    /// Clones of the queue have no observer.
+    /// # Arguments
+    /// * `observer`: The new observer, a `QueueObserver` or a closure taking a `QueueEvent` and the length
+    /// # Example
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::linked_list::observer::QueueEvent;
+    ///
+    /// let events = Arc::new(Mutex::new(Vec::new()));
+    /// let log = events.clone();
+    ///
+    /// let mut queue = CircularQueue::new(1);
+    /// queue.set_observer(move |event: QueueEvent, len: usize| log.lock().unwrap().push((event, len)));
+    ///
+    /// queue.insert(1, Direction::Left).unwrap();
+    /// assert!(queue.insert(2, Direction::Left).is_err());
+    /// queue.remove(Direction::Right);
+    ///
+    /// assert_eq!(
+    ///     *events.lock().unwrap(),
+    ///     vec![(QueueEvent::Insert, 1), (QueueEvent::Overflow, 1), (QueueEvent::Remove, 0)]
+    /// );
+    /// ```
+    pub fn set_observer(&mut self, observer: impl QueueObserver<T> + 'static) {
+        self.observer = Some(Box::new(observer));
+    }
+
+    /// Remove the observer of the queue
+    /// # Returns
+    /// The observer, or None if the queue had none
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueue;
+    /// use data_structures::linked_list::observer::QueueEvent;
+    ///
+    /// let mut queue: CircularQueue<i32> = CircularQueue::new(0);
+    /// assert!(queue.take_observer().is_none());
+    ///
+    /// queue.set_observer(|_: QueueEvent, _: usize| {});
+    /// assert!(queue.take_observer().is_some());
+    /// ```
+    pub fn take_observer(&mut self) -> Option<Box<dyn QueueObserver<T>>> {
+        self.observer.take()
+    }
+
+    // Tell the observer that an element arrives while the queue is full
+    fn notify_overflow(&mut self, value: &T) {
+        if let Some(observer) = self.observer.as_mut() {
+            observer.on_overflow(value, self.size);
+        }
+    }
+
+    // Tell the observer that an element was removed, the size being already updated
+    fn notify_remove(&mut self, value: &T) {
+        if let Some(observer) = self.observer.as_mut() {
+            observer.on_remove(value, self.size);
+        }
+    }
+
+    // Apply the overflow policy if the queue is full, returning the evicted element
+    fn make_room(&mut self, value: &T) -> Result<Option<T>, DataStructureError> {
+        if !self.is_full() {
+            return Ok(None);
+        }
+        self.notify_overflow(value);
+
+        let mut policy = core::mem::replace(&mut self.policy, Box::new(Reject));
+        let position = policy.choose(&View(self), value);
+        self.policy = policy;
+
+        match position {
+            None => Err(DataStructureError::Full),
+            Some(position) if position >= self.len() => Err(DataStructureError::InvalidPosition {
+                position,
+                len: self.len(),
+            }),
+            Some(position) => Ok(Some(self.remove_at(position))),
         }
+    }
 
-        // Create new vertex
-        let new_vertex_ptr = Vertex::new(value);
+    // Link a new node next to the cursor, the queue not being full
+    fn link_new(&mut self, new_node: S::Handle, side: Direction) {
+        // Keep a handle on the new node to show it to the observer once linked
+        let observed = self.observer.is_some().then(|| new_node.clone());
 
         // Test if the queue is not empty
         if self.is_empty() {
-            // If the queue is empty, set the cursor to the new vertex
-            self.cursor = Some(new_vertex_ptr);
+            // If the queue is empty, set the cursor to the new node
+            self.cursor = Some(new_node);
         } else if self.len() == 1 {
-            // Get a reference to the current cursor pointer
-            let cursor_ref = self.cursor.as_ref().unwrap();
-
-            // Insert the new vertex. In this case, both directions points to the new vertex
-            cursor_ref
-                .borrow_mut()
-                .set_connection(Direction::Left.into(), Some(&new_vertex_ptr));
-            cursor_ref
-                .borrow_mut()
-                .set_connection(Direction::Right.into(), Some(&new_vertex_ptr));
-
-            // Adjust the new vertex's pointers. In this case both directions points to the previus added vertex.
-            new_vertex_ptr
-                .borrow_mut()
-                .set_connection(Direction::Right.into(), Some(cursor_ref));
-            new_vertex_ptr
-                .borrow_mut()
-                .set_connection(Direction::Left.into(), Some(cursor_ref));
+            // Get a reference to the current cursor node
+            let cursor = self.cursor.as_ref().unwrap();
+
+            // Insert the new node. In this case, both directions points to the new node
+            self.storage
+                .set_link(cursor, Direction::Left, Some(&new_node));
+            self.storage
+                .set_link(cursor, Direction::Right, Some(&new_node));
+
+            // Adjust the new node's links. In this case both directions points to the previus added node.
+            self.storage
+                .set_link(&new_node, Direction::Right, Some(cursor));
+            self.storage
+                .set_link(&new_node, Direction::Left, Some(cursor));
         } else {
-            // Get a reference to the current cursor pointer
-            let cursor_vertex_ref = self.cursor.as_ref().unwrap();
-
-            // Update the references based on the side
-            match side {
-                Direction::Left => {
-                    // Points the right cursor of the new vertex to the cursor's vertex
-                    new_vertex_ptr
-                        .borrow_mut()
-                        .set_connection(Direction::Right.into(), Some(cursor_vertex_ref));
-
-                    // Points the left cursor of the new vertex to the cursor's left vertex
-                    let cursor_left_vertex_ptr = self
-                        .cursor
-                        .as_ref()
-                        .unwrap()
-                        .borrow()
-                        .get_pointer(Direction::Left.into())
-                        .unwrap();
-                    new_vertex_ptr
-                        .borrow_mut()
-                        .set_connection(Direction::Left.into(), Some(&cursor_left_vertex_ptr));
-
-                    // Points the letf vertex's right pointer to the new vertex
-                    cursor_left_vertex_ptr
-                        .borrow_mut()
-                        .set_connection(Direction::Right.into(), Some(&new_vertex_ptr));
-
-                    // Points the cursor's left pointer to the new vertex
-                    cursor_vertex_ref
-                        .borrow_mut()
-                        .set_connection(Direction::Left.into(), Some(&new_vertex_ptr));
-                }
-                Direction::Right => {
-                    // Points the left cursor of the new vertex to the cursor's vertex
-                    new_vertex_ptr
-                        .borrow_mut()
-                        .set_connection(Direction::Left.into(), Some(cursor_vertex_ref));
-
-                    // Points the right cursor of the new vertex to the cursor's right vertex
-                    let cursor_right_vertex_ptr = self
-                        .cursor
-                        .as_ref()
-                        .unwrap()
-                        .borrow()
-                        .get_pointer(Direction::Right.into())
-                        .unwrap();
-                    new_vertex_ptr
-                        .borrow_mut()
-                        .set_connection(Direction::Right.into(), Some(&cursor_right_vertex_ptr));
-
-                    // Points the right vertex's left pointer to the new vertex
-                    cursor_right_vertex_ptr
-                        .borrow_mut()
-                        .set_connection(Direction::Left.into(), Some(&new_vertex_ptr));
-
-                    // Points the cursor's right pointer to the new vertex
-                    cursor_vertex_ref
-                        .borrow_mut()
-                        .set_connection(Direction::Right.into(), Some(&new_vertex_ptr));
-                }
-            }
+            // Get a reference to the current cursor node
+            let cursor = self.cursor.as_ref().unwrap();
+
+            // Update the links based on the side. The new node goes between the cursor and its neighbor on that side.
+            let neighbor = self.storage.link(cursor, side).unwrap();
+            let opposite = side.opposite();
+
+            // Points the new node to the cursor and to the cursor's neighbor
+            self.storage.set_link(&new_node, opposite, Some(cursor));
+            self.storage.set_link(&new_node, side, Some(&neighbor));
+
+            // Points the neighbor and the cursor to the new node
+            self.storage.set_link(&neighbor, opposite, Some(&new_node));
+            self.storage.set_link(cursor, side, Some(&new_node));
         }
 
         self.size += 1;
 
-        Ok(())
+        if let (Some(observer), Some(node)) = (self.observer.as_mut(), observed) {
+            let len = self.size;
+            self.storage
+                .read(&node, |element| observer.on_insert(element, len));
+        }
     }
 
     /// Remove and return an element from the queue
@@ -360,120 +758,1036 @@ impl<T> CircularQueue<T> {
             return None;
         }
 
-        // Get the current cursor Vertex pointer and erase the cursor
-        let vertex_to_remove_ref = self.cursor.take().unwrap();
+        // Get the current cursor node and erase the cursor
+        let node_to_remove = self.cursor.take().unwrap();
 
         match self.len().cmp(&2) {
-            std::cmp::Ordering::Equal => {
-                // Get the other vertex that will remain in the queue
-                let other_vertex_ptr = vertex_to_remove_ref
-                    .borrow()
-                    .get_pointer(side_to_move.into())
-                    .unwrap();
+            core::cmp::Ordering::Equal => {
+                // Get the other node that will remain in the queue
+                let other_node = self.storage.link(&node_to_remove, side_to_move).unwrap();
 
-                // Points the other vertex's left and right pointers to None
-                other_vertex_ptr
-                    .borrow_mut()
-                    .set_connection(Direction::Left.into(), None);
-                other_vertex_ptr
-                    .borrow_mut()
-                    .set_connection(Direction::Right.into(), None);
+                // Points the other node's left and right links to None
+                self.storage.set_link(&other_node, Direction::Left, None);
+                self.storage.set_link(&other_node, Direction::Right, None);
 
-                // Set the cursor to the other vertex
-                self.cursor = Some(other_vertex_ptr);
+                // Set the cursor to the other node
+                self.cursor = Some(other_node);
             }
-            std::cmp::Ordering::Greater => {
-                // Get the letf and right vertex reference
-                let left_vertex_ptr = vertex_to_remove_ref
-                    .borrow()
-                    .get_pointer(Direction::Left.into())
-                    .unwrap();
-                let right_vertex_ptr = vertex_to_remove_ref
-                    .borrow()
-                    .get_pointer(Direction::Right.into())
+            core::cmp::Ordering::Greater => {
+                // Get the letf and right nodes
+                let left_node = self.storage.link(&node_to_remove, Direction::Left).unwrap();
+                let right_node = self
+                    .storage
+                    .link(&node_to_remove, Direction::Right)
                     .unwrap();
 
-                // Points the left vertex's right pointer to the right vertex
-                left_vertex_ptr
-                    .borrow_mut()
-                    .set_connection(Direction::Right.into(), Some(&right_vertex_ptr));
+                // Points the left node's right link to the right node
+                self.storage
+                    .set_link(&left_node, Direction::Right, Some(&right_node));
 
-                // Points the right vertex's left pointer to the left vertex
-                right_vertex_ptr
-                    .borrow_mut()
-                    .set_connection(Direction::Left.into(), Some(&left_vertex_ptr));
+                // Points the right node's left link to the left node
+                self.storage
+                    .set_link(&right_node, Direction::Left, Some(&left_node));
 
                 // Update the cursor based on the side
                 match side_to_move {
                     Direction::Left => {
-                        // Set the cursor to the left vertex
-                        self.cursor = Some(left_vertex_ptr);
+                        // Set the cursor to the left node
+                        self.cursor = Some(left_node);
                     }
                     Direction::Right => {
-                        // Set the cursor to the right vertex
-                        self.cursor = Some(right_vertex_ptr);
+                        // Set the cursor to the right node
+                        self.cursor = Some(right_node);
                     }
                 }
             }
-            std::cmp::Ordering::Less => {
+            core::cmp::Ordering::Less => {
                 // In this case we don't have to do anything.
             }
         }
 
         self.size -= 1;
 
-        // Get data from vertex and discard the vertex
-        let data = vertex_to_remove_ref.borrow_mut().clear();
-        data
+        // Get data from the node and release it
+        let data = self.storage.free(node_to_remove);
+        self.notify_remove(&data);
+        Some(data)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_queue_no_size_limit() {
-        let mut queue: super::CircularQueue<i32> = super::CircularQueue::new(0);
 
-        assert!(queue.is_empty());
+    /// Get a clone of the element at the cursor, the next one `remove` returns
+    /// # Returns
+    /// The element at the cursor, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    ///
+    /// let mut queue = CircularQueue::new(0);
+    /// assert_eq!(queue.peek_cursor(), None);
+    ///
+    /// queue.insert(1, Direction::Left).unwrap();
+    /// queue.insert(2, Direction::Left).unwrap();
+    /// assert_eq!(queue.peek_cursor(), Some(1));
+    /// assert_eq!(queue.remove(Direction::Right), Some(1));
+    /// ```
+    pub fn peek_cursor(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let cursor = self.cursor.as_ref()?;
+        Some(self.with_element(cursor, T::clone))
+    }
 
-        for i in 0..10 {
-            queue.insert(i, Direction::Left).unwrap();
+    /// Get a clone of the neighbor of the cursor on one side, the end where `insert` puts elements on that side
+    /// In a queue of one element the cursor is its own neighbor.
+    /// # Arguments
+    /// * `side`: The side of the cursor to look at (Left or Right)
+    /// # Returns
+    /// The element next to the cursor on that side, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    ///
+    /// let mut queue = CircularQueue::new(0);
+    /// queue.insert('c', Direction::Left).unwrap();
+    /// assert_eq!(queue.peek(Direction::Left), Some('c'));
+    ///
+    /// queue.insert('l', Direction::Left).unwrap();
+    /// queue.insert('r', Direction::Right).unwrap();
+    /// assert_eq!(queue.peek(Direction::Left), Some('l'));
+    /// assert_eq!(queue.peek(Direction::Right), Some('r'));
+    /// assert_eq!(queue.peek_cursor(), Some('c'));
+    /// ```
+    pub fn peek(&self, side: Direction) -> Option<T>
+    where
+        T: Clone,
+    {
+        let cursor = self.cursor.as_ref()?;
+        match self.storage.link(cursor, side) {
+            Some(neighbor) => Some(self.with_element(&neighbor, T::clone)),
+            None => Some(self.with_element(cursor, T::clone)),
         }
+    }
 
-        assert_eq!(queue.len(), 10);
-
-        let removed = queue.remove(Direction::Right);
-        assert_eq!(removed, Some(0));
-
-        let removed = queue.remove(Direction::Right);
-        assert_eq!(removed, Some(1));
-
-        let removed = queue.remove(Direction::Left);
-        assert_eq!(removed, Some(2));
+    /// Call a function with a reference to the element at the cursor, without cloning it
+    /// # Arguments
+    /// * `f`: The function reading the element
+    /// # Returns
+    /// The result of `f`, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueue;
+    ///
+    /// let queue = CircularQueue::from(vec![String::from("first"), String::from("second")]);
+    /// assert_eq!(queue.read_cursor(|name| name.len()), Some(5));
+    /// ```
+    pub fn read_cursor<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let cursor = self.cursor.as_ref()?;
+        Some(self.with_element(cursor, f))
+    }
 
-        let removed = queue.remove(Direction::Left);
-        assert_eq!(removed, Some(9));
+    /// Replace the element at the cursor, keeping its place in the ring
+    /// # Arguments
+    /// * `value`: The new element
+    /// # Returns
+    /// The previous element, or None if the queue is empty, in which case `value` is dropped
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::from(vec![1, 2, 3]);
+    ///
+    /// assert_eq!(queue.replace_at_cursor(10), Some(1));
+    /// assert_eq!(queue.iter().collect::<Vec<_>>(), vec![10, 2, 3]);
+    /// ```
+    pub fn replace_at_cursor(&mut self, value: T) -> Option<T> {
+        let cursor = self.cursor.as_ref()?;
+        Some(self.storage.replace(cursor, value))
     }
 
-    #[test]
-    fn test_queue() {
-        let mut queue: super::CircularQueue<i32> = super::CircularQueue::new(10);
+    /// Move the cursor along the ring, without changing the order of the elements
+    /// Steps beyond the length of the queue wrap around, so only `steps % len` links are followed.
+    /// # Arguments
+    /// * `side`: The side to move the cursor to (Left or Right)
+    /// * `steps`: The number of elements to move by
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    ///
+    /// let mut queue = CircularQueue::from(vec![1, 2, 3, 4]);
+    ///
+    /// queue.rotate(Direction::Right, 1);
+    /// assert_eq!(queue.peek_cursor(), Some(2));
+    ///
+    /// queue.rotate(Direction::Left, 6);
+    /// assert_eq!(queue.iter().collect::<Vec<_>>(), vec![4, 1, 2, 3]);
+    /// ```
+    pub fn rotate(&mut self, side: Direction, steps: usize) {
+        if self.len() < 2 {
+            return;
+        }
 
-        assert!(queue.is_empty());
+        // Walk the shortest way around the ring
+        let steps = steps % self.len();
+        let (side, steps) = if steps > self.len() / 2 {
+            (side.opposite(), self.len() - steps)
+        } else {
+            (side, steps)
+        };
 
-        for i in 0..10 {
-            queue.insert(i, Direction::Left).unwrap();
+        for _ in 0..steps {
+            let cursor = self.cursor.take().unwrap();
+            self.cursor = self.storage.link(&cursor, side);
         }
+    }
 
-        assert_eq!(queue.len(), 10);
+    /// Move the cursor to the right until it reaches an element matching a predicate, the cursor being checked first
+    /// # Arguments
+    /// * `predicate`: The function returning true for the element to stop at
+    /// # Returns
+    /// The number of steps taken to the right, or None if no element matches, in which case the cursor doesn't move
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueue;
+    ///
+    /// let mut tasks = CircularQueue::from(vec!["idle", "idle", "ready", "idle"]);
+    ///
+    /// assert_eq!(tasks.seek(|task| *task == "ready"), Some(2));
+    /// assert_eq!(tasks.peek_cursor(), Some("ready"));
+    /// assert_eq!(tasks.seek(|task| *task == "done"), None);
+    /// assert_eq!(tasks.peek_cursor(), Some("ready"));
+    /// ```
+    pub fn seek(&mut self, mut predicate: impl FnMut(&T) -> bool) -> Option<usize> {
+        let (steps, node) = self
+            .handles()
+            .enumerate()
+            .find(|(_, node)| self.with_element(node, &mut predicate))?;
+
+        self.cursor = Some(node);
+        Some(steps)
+    }
 
-        let resul = queue.insert(10, Direction::Left).unwrap_err();
-        assert_eq!(resul, "Queue is full");
+    /// Check if the queue holds an element equal to a value
+    /// # Arguments
+    /// * `value`: The value to look for
+    /// # Returns
+    /// True if an element is equal to `value`
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueue;
+    ///
+    /// let queue = CircularQueue::from(vec!["a", "b"]);
+    ///
+    /// assert!(queue.contains(&"b"));
+    /// assert!(!queue.contains(&"c"));
+    /// ```
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.position(|element| element == value).is_some()
+    }
 
-        let removed = queue.remove(Direction::Right);
-        assert_eq!(removed, Some(0));
+    /// Find the first element matching a predicate, walking the ring from the cursor to the right
+    /// # Arguments
+    /// * `predicate`: The function returning true for the element to find
+    /// # Returns
+    /// The position of the element counted from the cursor, or None if no element matches
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    ///
+    /// let mut queue = CircularQueue::from(vec![1, 2, 3, 4]);
+    /// assert_eq!(queue.position(|value| value % 2 == 0), Some(1));
+    ///
+    /// queue.rotate(Direction::Right, 2);
+    /// assert_eq!(queue.position(|value| *value == 1), Some(2));
+    /// ```
+    pub fn position(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<usize> {
+        self.handles()
+            .position(|node| self.with_element(&node, &mut predicate))
+    }
+
+    /// Keep only the elements matching a predicate, in their order
+    /// If the element at the cursor is removed, the cursor moves to the next kept element on its right.
+    /// # Arguments
+    /// * `predicate`: The function returning true for the elements to keep, called once per element from the cursor
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::from(vec![1, 2, 3, 4, 5]);
+    /// queue.retain(|value| value % 2 == 0);
+    ///
+    /// assert_eq!(queue.iter().collect::<Vec<_>>(), vec![2, 4]);
+    /// ```
+    pub fn retain(&mut self, mut predicate: impl FnMut(&T) -> bool) {
+        // Visit each element at the cursor once. After a full turn the cursor is back on the first kept element.
+        for _ in 0..self.len() {
+            let keep = self.read_cursor(&mut predicate).unwrap();
+            if keep {
+                self.rotate(Direction::Right, 1);
+            } else {
+                self.remove(Direction::Right);
+            }
+        }
+    }
+
+    /// Remove the first element equal to a value, walking the ring from the cursor to the right
+    /// If the element is at the cursor, the cursor moves to its right neighbor.
+    /// # Arguments
+    /// * `value`: The value to remove
+    /// # Returns
+    /// The removed element, or None if no element is equal to `value`
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueue;
+    ///
+    /// let mut pending = CircularQueue::from(vec!["build", "test", "deploy"]);
+    ///
+    /// assert_eq!(pending.remove_first(&"test"), Some("test"));
+    /// assert_eq!(pending.remove_first(&"test"), None);
+    /// assert_eq!(pending.iter().collect::<Vec<_>>(), vec!["build", "deploy"]);
+    /// ```
+    pub fn remove_first(&mut self, value: &T) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        let position = self.position(|element| element == value)?;
+        Some(self.remove_at(position))
+    }
+
+    /// Remove all the elements, yielding them from the cursor walking the ring to one side
+    /// The elements not consumed by the iterator are dropped with it.
+    /// # Arguments
+    /// * `side`: The side to walk the ring to (Left or Right)
+    /// # Returns
+    /// An iterator removing the element at the cursor first, then its neighbors on the given side
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    ///
+    /// let mut queue = CircularQueue::from(vec![1, 2, 3]);
+    ///
+    /// assert_eq!(queue.drain(Direction::Left).collect::<Vec<_>>(), vec![1, 3, 2]);
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn drain(&mut self, side: Direction) -> Drain<'_, T, S> {
+        Drain { queue: self, side }
+    }
+
+    /// Remove and drop all the elements, keeping the maximum size, the overflow policy and the observer
+    /// The nodes are freed one by one, so clearing a long queue doesn't recurse through the ring. The observer is told
+    /// about every removed element.
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueue;
+    ///
+    /// let mut queue = CircularQueue::from(vec![1, 2, 3]);
+    /// queue.clear();
+    ///
+    /// assert!(queue.is_empty());
+    /// assert_eq!(queue.peek_cursor(), None);
+    /// ```
+    pub fn clear(&mut self) {
+        // Freeing a node drops its links, so the ring is opened by the first one and nothing keeps the others alive
+        let mut current = self.cursor.take();
+        while self.size > 0 {
+            let Some(node) = current else { break };
+            current = self.storage.link(&node, Direction::Right);
+            self.size -= 1;
+            let data = self.storage.free(node);
+            self.notify_remove(&data);
+        }
+        self.size = 0;
+    }
+
+    /// Iterate over clones of the elements without removing them, from the cursor to the right
+    /// The iterator is double ended: `rev` walks the ring to the left, ending at the cursor.
+    /// # Returns
+    /// An iterator over the elements in logical order, the order of `remove(Direction::Right)`
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    ///
+    /// let queue = CircularQueue::from(vec![1, 2, 3]);
+    ///
+    /// assert_eq!(queue.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// assert_eq!(queue.iter().rev().collect::<Vec<_>>(), vec![3, 2, 1]);
+    /// assert_eq!(queue.len(), 3);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T, S> {
+        self.iter_from(Direction::Right)
+    }
+
+    /// Iterate over clones of the elements without removing them, starting at the cursor and moving to one side
+    /// # Arguments
+    /// * `side`: The side to walk the ring to (Left or Right)
+    /// # Returns
+    /// An iterator yielding the element at the cursor first, then its neighbors on the given side
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    ///
+    /// let queue = CircularQueue::from(vec![1, 2, 3]);
+    ///
+    /// assert_eq!(queue.iter_from(Direction::Left).collect::<Vec<_>>(), vec![1, 3, 2]);
+    /// assert_eq!(queue.iter_from(Direction::Right).collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn iter_from(&self, side: Direction) -> Iter<'_, T, S> {
+        // The back end is the last node reached walking to the side, the neighbor of the cursor on the other side
+        let back = self.cursor.as_ref().map(|cursor| {
+            self.storage
+                .link(cursor, side.opposite())
+                .unwrap_or_else(|| cursor.clone())
+        });
+
+        Iter {
+            queue: self,
+            side,
+            front: self.cursor.clone(),
+            back,
+            remaining: self.size,
+        }
+    }
+
+    // Walk the ring from the cursor to the right, yielding each node once
+    pub(super) fn handles(&self) -> impl Iterator<Item = S::Handle> + '_ {
+        let mut current = self.cursor.clone();
+        let mut remaining = self.size;
+
+        core::iter::from_fn(move || {
+            if remaining == 0 {
+                return None;
+            }
+            remaining -= 1;
+
+            let node = current.take()?;
+            current = self.storage.link(&node, Direction::Right);
+            Some(node)
+        })
+    }
+
+    // Remove the element at the given position, counted from the cursor to the right
+    fn remove_at(&mut self, position: usize) -> T {
+        if position == 0 {
+            return self.remove(Direction::Right).unwrap();
+        }
+
+        let node = self.handles().nth(position).unwrap();
+        if self.len() == 2 {
+            // Only the cursor remains
+            let cursor = self.cursor.as_ref().unwrap();
+            self.storage.set_link(cursor, Direction::Left, None);
+            self.storage.set_link(cursor, Direction::Right, None);
+        } else {
+            let left_node = self.storage.link(&node, Direction::Left).unwrap();
+            let right_node = self.storage.link(&node, Direction::Right).unwrap();
+            self.storage
+                .set_link(&left_node, Direction::Right, Some(&right_node));
+            self.storage
+                .set_link(&right_node, Direction::Left, Some(&left_node));
+        }
+
+        self.size -= 1;
+        let data = self.storage.free(node);
+        self.notify_remove(&data);
+        data
+    }
+
+    // Call `f` with the element of a node of the queue
+    pub(super) fn with_element<R>(&self, node: &S::Handle, f: impl FnOnce(&T) -> R) -> R {
+        self.storage.read(node, f)
+    }
+
+    // Compare the elements of two queues lexicographically, in logical order
+    pub(super) fn partial_cmp_elements<O: NodeStorage<T>>(
+        &self,
+        other: &CircularQueue<T, O>,
+    ) -> Option<Ordering>
+    where
+        T: PartialOrd,
+    {
+        let mut nodes = self.handles();
+        let mut other_nodes = other.handles();
+
+        loop {
+            match (nodes.next(), other_nodes.next()) {
+                (None, None) => return Some(Ordering::Equal),
+                (None, Some(_)) => return Some(Ordering::Less),
+                (Some(_), None) => return Some(Ordering::Greater),
+                (Some(node), Some(other_node)) => {
+                    let ordering = self.with_element(&node, |a| {
+                        other.with_element(&other_node, |b| a.partial_cmp(b))
+                    });
+                    match ordering {
+                        Some(Ordering::Equal) => continue,
+                        ordering => return ordering,
+                    }
+                }
+            }
+        }
+    }
+}
+/// View of a queue given to its overflow policy
+struct View<'a, T, S: NodeStorage<T>>(&'a CircularQueue<T, S>);
+
+impl<T, S: NodeStorage<T>> QueueView<T> for View<'_, T, S> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(usize, &T)) {
+        for (position, node) in self.0.handles().enumerate() {
+            self.0.with_element(&node, |element| f(position, element));
+        }
+    }
+
+    fn position_min_by(&self, compare: &mut dyn FnMut(&T, &T) -> Ordering) -> Option<usize> {
+        let mut nodes = self.0.handles().enumerate();
+        let (mut min_position, mut min_node) = nodes.next()?;
+
+        for (position, node) in nodes {
+            let ordering = self.0.with_element(&node, |element| {
+                self.0.with_element(&min_node, |min| compare(element, min))
+            });
+            if ordering == Ordering::Less {
+                min_position = position;
+                min_node = node;
+            }
+        }
+        Some(min_position)
+    }
+}
+
+impl<T, S: NodeStorage<T> + Default> Default for CircularQueue<T, S> {
+    /// An unbounded queue, like `CircularQueue::new(0)`
+    fn default() -> Self {
+        Self::with_storage(0, S::default())
+    }
+}
+
+impl<T> From<Vec<T>> for CircularQueue<T> {
+    /// Build an unbounded queue holding the elements from left to right, so the first element is on the right end
+    fn from(values: Vec<T>) -> Self {
+        let mut queue = Self::new(0);
+        for value in values {
+            queue.insert(value, Direction::Left).unwrap();
+        }
+        queue
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for CircularQueue<T> {
+    fn from(values: [T; N]) -> Self {
+        Self::from(Vec::from(values))
+    }
+}
+
+impl<T: Clone> From<&[T]> for CircularQueue<T> {
+    fn from(values: &[T]) -> Self {
+        Self::from(values.to_vec())
+    }
+}
+
+impl<T> TryFrom<(usize, Vec<T>)> for CircularQueue<T> {
+    type Error = DataStructureError;
+
+    /// Build a queue with the given maximum size, holding the elements from left to right like `From<Vec<T>>`.
+    /// Fails with `Full` if there are more elements than the maximum size, which is never the case for a size of 0.
+    fn try_from((max_size, values): (usize, Vec<T>)) -> Result<Self, DataStructureError> {
+        if max_size != 0 && values.len() > max_size {
+            return Err(DataStructureError::Full);
+        }
+
+        let mut queue = Self::from(values);
+        queue.max_size = max_size;
+        Ok(queue)
+    }
+}
+
+impl<T, const N: usize> TryFrom<(usize, [T; N])> for CircularQueue<T> {
+    type Error = DataStructureError;
+
+    fn try_from((max_size, values): (usize, [T; N])) -> Result<Self, DataStructureError> {
+        Self::try_from((max_size, Vec::from(values)))
+    }
+}
+
+impl<T: Clone> TryFrom<(usize, &[T])> for CircularQueue<T> {
+    type Error = DataStructureError;
+
+    fn try_from((max_size, values): (usize, &[T])) -> Result<Self, DataStructureError> {
+        // Checked before cloning the elements
+        if max_size != 0 && values.len() > max_size {
+            return Err(DataStructureError::Full);
+        }
+        Self::try_from((max_size, values.to_vec()))
+    }
+}
+
+impl<T> FromIterator<T> for CircularQueue<T> {
+    /// Build an unbounded queue holding the elements from left to right, like `From<Vec<T>>`
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = Self::new(0);
+        queue.extend(iter);
+        queue
+    }
+}
+
+impl<T, S: NodeStorage<T>> Extend<T> for CircularQueue<T, S> {
+    /// Insert the elements on the left of the cursor, in order, applying the overflow policy when the queue is full
+    ///
+    /// # Panics
+    /// If the queue is full and the overflow policy rejects an element. Use `insert` to handle a full queue.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            if self.insert(value, Direction::Left).is_err() {
+                panic!("Can't extend a full queue");
+            }
+        }
+    }
+}
+
+impl<T: Clone, S: NodeStorage<T> + Default> Clone for CircularQueue<T, S> {
+    /// Copy the elements into a new ring with the same maximum size, its cursor on the same element.
+    /// The overflow policy can't be cloned: the copy rejects insertions when full, like a new queue.
+    fn clone(&self) -> Self {
+        let mut queue = Self::with_storage(self.max_size, S::default());
+        for node in self.handles() {
+            let value = self.with_element(&node, T::clone);
+            let new_node = queue.storage.alloc(value);
+            queue.link_new(new_node, Direction::Left);
+        }
+        queue
+    }
+}
+
+impl<T: PartialEq, S: NodeStorage<T>, O: NodeStorage<T>> PartialEq<CircularQueue<T, O>>
+    for CircularQueue<T, S>
+{
+    /// Two queues are equal if they hold equal elements in the same order from their cursors, whatever their
+    /// maximum sizes, overflow policies and storage backends
+    fn eq(&self, other: &CircularQueue<T, O>) -> bool {
+        self.len() == other.len()
+            && self
+                .handles()
+                .zip(other.handles())
+                .all(|(a, b)| self.with_element(&a, |a| other.with_element(&b, |b| a == b)))
+    }
+}
+
+impl<T: Eq, S: NodeStorage<T>> Eq for CircularQueue<T, S> {}
+
+/// Builder of a circular queue, taking the settings and the elements at once
+/// The size limit is checked a single time against all the elements when the queue is built, instead of on every
+/// insertion, and the overflow policy is only installed once the elements are in.
+///
+pub struct CircularQueueBuilder<T> {
+    max_size: usize,
+    policy: Box<dyn OverflowPolicy<T>>,
+    elements: Vec<T>,
+}
+
+impl<T> CircularQueueBuilder<T> {
+    /// Create a builder of an unbounded, empty queue rejecting insertions when full
+    ///
+    /// # Returns
+    /// A new CircularQueueBuilder instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueueBuilder;
+    ///
+    /// let queue = CircularQueueBuilder::<u8>::new().build().unwrap();
+    /// assert_eq!(queue.max_size(), 0);
+    /// ```
+    pub fn new() -> Self {
+        CircularQueueBuilder {
+            max_size: 0,
+            policy: Box::new(Reject),
+            elements: Vec::new(),
+        }
+    }
+
+    /// Set the maximum size of the queue
+    /// # Arguments
+    /// * `max_size`: The maximum number of elements the queue can hold. If 0, there is no size limit.
+    /// # Returns
+    /// The updated builder
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::CircularQueueBuilder;
+    ///
+    /// let queue = CircularQueueBuilder::<u8>::new().max_size(8).build().unwrap();
+    /// assert_eq!(queue.max_size(), 8);
+    /// ```
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Set the overflow policy of the queue
+    /// # Arguments
+    /// * `policy`: The behavior of the queue when inserting while it is full
+    /// # Returns
+    /// The updated builder
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueueBuilder, Direction};
+    /// use data_structures::linked_list::overflow::EvictOldest;
+    ///
+    /// let mut queue = CircularQueueBuilder::new()
+    ///     .max_size(1)
+    ///     .overflow_policy(EvictOldest)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// queue.insert(1, Direction::Left).unwrap();
+    /// assert!(queue.insert(2, Direction::Left).is_ok());
+    /// ```
+    pub fn overflow_policy(mut self, policy: impl OverflowPolicy<T> + 'static) -> Self {
+        self.policy = Box::new(policy);
+        self
+    }
+
+    /// Add elements to the queue
+    /// # Arguments
+    /// * `elements`: The elements, from left to right like `CircularQueue::from`, so the first one is on the right end
+    /// # Returns
+    /// The updated builder
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueueBuilder, Direction};
+    ///
+    /// let mut queue = CircularQueueBuilder::new().elements([1, 2]).elements([3]).build().unwrap();
+    ///
+    /// assert_eq!(queue.len(), 3);
+    /// assert_eq!(queue.remove(Direction::Right), Some(1));
+    /// ```
+    pub fn elements(mut self, elements: impl IntoIterator<Item = T>) -> Self {
+        self.elements.extend(elements);
+        self
+    }
+
+    /// Build the queue
+    /// # Returns
+    /// The queue, or an error if there are more elements than the maximum size
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::circular_queue::CircularQueueBuilder;
+    ///
+    /// let built = CircularQueueBuilder::new().max_size(2).elements([1, 2, 3]).build();
+    /// assert_eq!(built.err(), Some(DataStructureError::CapacityBelowLen { len: 3, requested: 2 }));
+    /// ```
+    pub fn build(self) -> Result<CircularQueue<T>, DataStructureError> {
+        if self.max_size != 0 && self.elements.len() > self.max_size {
+            return Err(DataStructureError::CapacityBelowLen {
+                len: self.elements.len(),
+                requested: self.max_size,
+            });
+        }
+
+        let mut queue = CircularQueue::new(self.max_size);
+        for value in self.elements {
+            let node = queue.storage.alloc(value);
+            queue.link_new(node, Direction::Left);
+        }
+        queue.policy = self.policy;
+
+        Ok(queue)
+    }
+}
+
+impl<T> Default for CircularQueueBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterator removing the elements of a queue, created by `drain`
+///
+pub struct Drain<'a, T, S: NodeStorage<T> = RcStorage> {
+    queue: &'a mut CircularQueue<T, S>,
+    side: Direction,
+}
+
+impl<T, S: NodeStorage<T>> Iterator for Drain<'_, T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.queue.remove(self.side)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.queue.len(), Some(self.queue.len()))
+    }
+}
+
+impl<T, S: NodeStorage<T>> ExactSizeIterator for Drain<'_, T, S> {}
+
+impl<T, S: NodeStorage<T>> Drop for CircularQueue<T, S> {
+    /// Frees the nodes one by one, the ring of strong pointers would otherwise keep every vertex alive
+    fn drop(&mut self) {
+        self.observer = None;
+        self.clear();
+    }
+}
+
+impl<T, S: NodeStorage<T>> Drop for Drain<'_, T, S> {
+    fn drop(&mut self) {
+        while self.queue.remove(self.side).is_some() {}
+    }
+}
+
+/// Iterator over clones of the elements of a queue, created by `iter` and `iter_from`
+///
+pub struct Iter<'a, T, S: NodeStorage<T> = RcStorage> {
+    queue: &'a CircularQueue<T, S>,
+    side: Direction,
+
+    front: Option<S::Handle>,
+    back: Option<S::Handle>,
+    remaining: usize,
+}
+
+impl<T: Clone, S: NodeStorage<T>> Iterator for Iter<'_, T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let node = self.front.take()?;
+        self.front = self.queue.storage.link(&node, self.side);
+        Some(self.queue.with_element(&node, T::clone))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: Clone, S: NodeStorage<T>> DoubleEndedIterator for Iter<'_, T, S> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let node = self.back.take()?;
+        self.back = self.queue.storage.link(&node, self.side.opposite());
+        Some(self.queue.with_element(&node, T::clone))
+    }
+}
+
+impl<T: Clone, S: NodeStorage<T>> ExactSizeIterator for Iter<'_, T, S> {}
+
+impl<'a, T: Clone, S: NodeStorage<T>> IntoIterator for &'a CircularQueue<T, S> {
+    type Item = T;
+    type IntoIter = Iter<'a, T, S>;
+
+    fn into_iter(self) -> Iter<'a, T, S> {
+        self.iter()
+    }
+}
+
+/// Iterator draining a queue from the cursor to the right, created by `into_iter`
+///
+pub struct IntoIter<T, S: NodeStorage<T> = RcStorage> {
+    queue: CircularQueue<T, S>,
+}
+
+impl<T, S: NodeStorage<T>> Iterator for IntoIter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.queue.remove(Direction::Right)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.queue.len(), Some(self.queue.len()))
+    }
+}
+
+impl<T, S: NodeStorage<T>> ExactSizeIterator for IntoIter<T, S> {}
+
+impl<T, S: NodeStorage<T>> IntoIterator for CircularQueue<T, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T, S>;
+
+    /// Drain the queue, yielding the elements in the order of `remove(Direction::Right)`
+    fn into_iter(self) -> IntoIter<T, S> {
+        IntoIter { queue: self }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Persist, S: NodeStorage<T> + Default> Persist for CircularQueue<T, S> {
+    /// The maximum size followed by the elements, from the cursor to the right. Loading rebuilds the ring with the
+    /// first element at the cursor. The overflow policy is not written; a loaded queue rejects insertions when full.
+    fn write_to(&self, out: &mut Vec<u8>) {
+        write_varint(out, self.max_size as u64);
+        write_varint(out, self.size as u64);
+        for node in self.handles() {
+            self.with_element(&node, |element| element.write_to(out));
+        }
+    }
+
+    fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+        let max_size = read_length(input)?;
+        let length = read_length(input)?;
+        if max_size != 0 && length > max_size {
+            return Err(DataStructureError::InvalidEncoding(
+                "More elements than the maximum size",
+            ));
+        }
+
+        // Inserting on the left of the cursor appends after the last element of the ring
+        let mut queue = CircularQueue::with_storage(max_size, S::default());
+        for _ in 0..length {
+            queue.insert(T::read_from(input)?, Direction::Left)?;
+        }
+        Ok(queue)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Serialize, S: NodeStorage<T>> Serialize for CircularQueue<T, S> {
+    /// A struct with the maximum size and the elements, from the cursor to the right. The overflow policy and the
+    /// observer are not serialized.
+    fn serialize<R: Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        let mut state = serializer.serialize_struct("CircularQueue", 2)?;
+        state.serialize_field("max_size", &self.max_size)?;
+        state.serialize_field("elements", &SerializeElements(self))?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>, S: NodeStorage<T> + Default> Deserialize<'de>
+    for CircularQueue<T, S>
+{
+    /// Rebuild the ring with the first element at the cursor. A loaded queue rejects insertions when full.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "CircularQueue")]
+        struct Snapshot<T> {
+            max_size: usize,
+            elements: Vec<T>,
+        }
+
+        let snapshot = Snapshot::deserialize(deserializer)?;
+        if snapshot.max_size != 0 && snapshot.elements.len() > snapshot.max_size {
+            return Err(D::Error::custom("more elements than the maximum size"));
+        }
+
+        // Inserting on the left of the cursor appends after the last element of the ring
+        let mut queue = CircularQueue::with_storage(snapshot.max_size, S::default());
+        for value in snapshot.elements {
+            let node = queue.storage.alloc(value);
+            queue.link_new(node, Direction::Left);
+        }
+        Ok(queue)
+    }
+}
+
+/// Helper to serialize the elements of a queue as a sequence, walking the ring from the cursor to the right
+#[cfg(feature = "serde")]
+struct SerializeElements<'a, T, S: NodeStorage<T>>(&'a CircularQueue<T, S>);
+
+#[cfg(feature = "serde")]
+impl<T: Serialize, S: NodeStorage<T>> Serialize for SerializeElements<'_, T, S> {
+    fn serialize<R: Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for node in self.0.handles() {
+            self.0
+                .with_element(&node, |element| seq.serialize_element(element))?;
+        }
+        seq.end()
+    }
+}
+
+impl<T: fmt::Debug, S: NodeStorage<T>> fmt::Debug for CircularQueue<T, S> {
+    /// Prints the elements in logical order, from the cursor to the right, instead of the web of vertex pointers
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircularQueue")
+            .field("len", &self.len())
+            .field("max_size", &self.max_size())
+            .field("elements", &DebugElements(self))
+            .finish()
+    }
+}
+
+/// Helper to print the elements of a queue as a list, walking the ring from the cursor to the right
+pub(super) struct DebugElements<'a, T, S: NodeStorage<T>>(pub(super) &'a CircularQueue<T, S>);
+
+impl<T: fmt::Debug, S: NodeStorage<T>> fmt::Debug for DebugElements<'_, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+
+        for node in self.0.handles() {
+            self.0.with_element(&node, |element| {
+                list.entry(element);
+            });
+        }
+
+        list.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linked_list::shared::{Ptr, WeakShared};
+    use crate::linked_list::vertex::Vertex;
+    use crate::test_util::Lcg;
+    use alloc::string::{String, ToString};
+
+    #[test]
+    fn test_queue_no_size_limit() {
+        let mut queue: super::CircularQueue<i32> = super::CircularQueue::new(0);
+
+        assert!(queue.is_empty());
+
+        for i in 0..10 {
+            queue.insert(i, Direction::Left).unwrap();
+        }
+
+        assert_eq!(queue.len(), 10);
+
+        let removed = queue.remove(Direction::Right);
+        assert_eq!(removed, Some(0));
+
+        let removed = queue.remove(Direction::Right);
+        assert_eq!(removed, Some(1));
+
+        let removed = queue.remove(Direction::Left);
+        assert_eq!(removed, Some(2));
+
+        let removed = queue.remove(Direction::Left);
+        assert_eq!(removed, Some(9));
+    }
+
+    #[test]
+    fn test_queue() {
+        let mut queue: super::CircularQueue<i32> = super::CircularQueue::new(10);
+
+        assert!(queue.is_empty());
+
+        for i in 0..10 {
+            queue.insert(i, Direction::Left).unwrap();
+        }
+
+        assert_eq!(queue.len(), 10);
+
+        let resul = queue.insert(10, Direction::Left).unwrap_err();
+        assert_eq!(resul, DataStructureError::Full);
+
+        let removed = queue.remove(Direction::Right);
+        assert_eq!(removed, Some(0));
 
         let removed = queue.remove(Direction::Right);
         assert_eq!(removed, Some(1));
@@ -511,8 +1825,43 @@ mod tests {
 
         // Check the reference count of each vertex
         for vertex in vertexes {
-            assert_eq!(Rc::strong_count(&vertex), 1);
+            assert_eq!(Ptr::strong_count(&vertex), 1);
+        }
+    }
+
+    #[test]
+    fn test_drop_and_clear_free_the_ring() {
+        // Weak pointers to every vertex of a full ring, walking it from the cursor
+        fn weak_vertexes(queue: &CircularQueue<u32>) -> Vec<WeakShared<Vertex<u32>>> {
+            let mut node = queue.cursor.clone();
+            (0..queue.len())
+                .map(|_| {
+                    let vertex = node.take().unwrap();
+                    node = queue.storage.link(&vertex, Direction::Right);
+                    Ptr::downgrade(&vertex)
+                })
+                .collect()
         }
+
+        let mut queue: CircularQueue<u32> = (0..100).collect();
+        let weak = weak_vertexes(&queue);
+        queue.clear();
+        assert!(queue.is_empty());
+        assert!(weak.iter().all(|vertex| vertex.upgrade().is_none()));
+
+        // A cleared queue is reused, and a full one is freed when dropped
+        queue.set_max_size(10).unwrap();
+        queue.extend(0..10);
+        assert!(queue.is_full());
+        let weak = weak_vertexes(&queue);
+        drop(queue);
+        assert!(weak.iter().all(|vertex| vertex.upgrade().is_none()));
+
+        // Dropping a long queue doesn't recurse through the vertexes
+        let long: CircularQueue<u32> = (0..1_000_000).collect();
+        let weak = weak_vertexes(&long);
+        drop(long);
+        assert!(weak[500_000].upgrade().is_none());
     }
 
     #[test]
@@ -559,6 +1908,33 @@ mod tests {
         println!("Stress test completed in {:?}", duration);
     }
 
+    #[test]
+    fn test_debug_output() {
+        let mut queue: CircularQueue<i32> = CircularQueue::new(5);
+
+        assert_eq!(
+            format!("{:?}", queue),
+            "CircularQueue { len: 0, max_size: 5, elements: [] }"
+        );
+
+        queue.insert(2, Direction::Right).unwrap();
+        queue.insert(3, Direction::Right).unwrap();
+        queue.insert(1, Direction::Left).unwrap();
+
+        // The cursor comes first, followed by the elements on its right
+        assert_eq!(
+            format!("{:?}", queue),
+            "CircularQueue { len: 3, max_size: 5, elements: [2, 3, 1] }"
+        );
+
+        // Large rings are printed without recursing through the vertexes
+        let mut large: CircularQueue<u32> = CircularQueue::new(0);
+        for i in 0..100_000 {
+            large.insert(i, Direction::Left).unwrap();
+        }
+        assert!(format!("{:?}", large).ends_with("99998, 99999] }"));
+    }
+
     #[test]
     #[ignore]
     fn test_vec_as_circular_queue_stress() {
@@ -600,4 +1976,444 @@ mod tests {
         let duration = start_time.elapsed();
         println!("Vec stress test completed in {:?}", duration);
     }
+
+    #[test]
+    fn test_queue_slab_storage() {
+        use crate::storage::node_storage::SlabStorage;
+
+        let mut queue = CircularQueue::with_storage(0, SlabStorage::new());
+        for i in 0..10 {
+            queue.insert(i, Direction::Left).unwrap();
+        }
+        assert_eq!(queue.remove(Direction::Right), Some(0));
+        assert_eq!(queue.remove(Direction::Left), Some(1));
+        assert_eq!(queue.remove(Direction::Left), Some(9));
+
+        // Freed slots are reused by the next insertions
+        queue.insert(10, Direction::Right).unwrap();
+        queue.insert(11, Direction::Right).unwrap();
+        assert_eq!(queue.storage().len(), 9);
+        assert_eq!(
+            format!("{:?}", queue),
+            "CircularQueue { len: 9, max_size: 0, elements: [8, 11, 10, 2, 3, 4, 5, 6, 7] }"
+        );
+
+        while queue.remove(Direction::Right).is_some() {}
+        assert!(queue.storage().is_empty());
+    }
+
+    #[test]
+    fn test_iteration_both_ways() {
+        use crate::storage::node_storage::SlabStorage;
+
+        for len in 0..6 {
+            let mut queue = CircularQueue::with_storage(0, SlabStorage::new());
+            for i in 0..len {
+                queue.insert(i, Direction::Left).unwrap();
+            }
+            let expected: Vec<i32> = (0..len).collect();
+
+            // Both ends meet in the middle without yielding an element twice
+            let mut iter = queue.iter();
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+            while let Some(value) = iter.next() {
+                front.push(value);
+                back.extend(iter.next_back());
+            }
+            front.extend(back.into_iter().rev());
+            assert_eq!(front, expected);
+
+            // Walking to the left starts at the cursor, then goes around the ring backwards
+            let mut left = expected.clone();
+            if len > 1 {
+                left[1..].reverse();
+            }
+            assert_eq!(queue.iter_from(Direction::Left).collect::<Vec<_>>(), left);
+
+            assert_eq!(queue.iter().len(), len as usize);
+            assert_eq!(queue.into_iter().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_overflow_policies() {
+        use crate::linked_list::overflow::{EvictLowestBy, EvictNewest, EvictOldest, QueueView};
+
+        let mut queue = CircularQueue::new(3);
+        for i in 1..=3 {
+            queue.insert(i, Direction::Left).unwrap();
+        }
+        assert_eq!(
+            queue.insert(4, Direction::Left),
+            Err(DataStructureError::Full)
+        );
+
+        queue.set_overflow_policy(EvictOldest);
+        assert_eq!(queue.insert_or_evict(4, Direction::Left), Ok(Some(1)));
+
+        queue.set_overflow_policy(EvictNewest);
+        assert_eq!(queue.insert_or_evict(5, Direction::Left), Ok(Some(4)));
+
+        // Evicting from the middle of the ring keeps the order of the others
+        queue.set_overflow_policy(EvictLowestBy(|a: &i32, b: &i32| b.cmp(a)));
+        assert_eq!(queue.insert_or_evict(0, Direction::Left), Ok(Some(5)));
+        assert_eq!(
+            format!("{:?}", queue),
+            "CircularQueue { len: 3, max_size: 3, elements: [2, 3, 0] }"
+        );
+
+        // Custom policy: evict the element at the middle position
+        queue.set_overflow_policy(|view: &dyn QueueView<i32>, _: &i32| Some(view.len() / 2));
+        assert_eq!(queue.insert_or_evict(9, Direction::Left), Ok(Some(3)));
+
+        queue.set_overflow_policy(|_: &dyn QueueView<i32>, _: &i32| Some(10));
+        assert_eq!(
+            queue.insert(1, Direction::Left),
+            Err(DataStructureError::InvalidPosition {
+                position: 10,
+                len: 3
+            })
+        );
+
+        assert_eq!(queue.remove(Direction::Right), Some(2));
+        assert_eq!(queue.remove(Direction::Right), Some(0));
+        assert_eq!(queue.remove(Direction::Right), Some(9));
+    }
+
+    #[test]
+    fn test_insert_overwrite() {
+        use std::collections::VecDeque;
+
+        for side in [Direction::Left, Direction::Right] {
+            let mut queue = CircularQueue::new(4);
+            let mut model = VecDeque::new();
+            for value in 0..20 {
+                let expected = (model.len() == 4).then(|| model.pop_front()).flatten();
+                model.push_back(value);
+                assert_eq!(queue.insert_overwrite(value, side), expected);
+            }
+
+            // The overwritten elements are always the oldest, whatever the side
+            let remove_side = match side {
+                Direction::Left => Direction::Right,
+                Direction::Right => Direction::Left,
+            };
+            while let Some(value) = model.pop_front() {
+                assert_eq!(queue.remove(remove_side), Some(value));
+            }
+            assert!(queue.is_empty());
+        }
+
+        // Without a size limit nothing is ever overwritten
+        let mut unbounded = CircularQueue::new(0);
+        assert!((0..100).all(|value| unbounded.insert_overwrite(value, Direction::Left).is_none()));
+        assert_eq!(unbounded.len(), 100);
+    }
+
+    #[test]
+    fn test_cursor_navigation() {
+        use crate::storage::node_storage::SlabStorage;
+        use std::collections::VecDeque;
+
+        let mut queue = CircularQueue::with_storage(0, SlabStorage::new());
+        let mut model = VecDeque::new();
+        let mut rng = Lcg::new(7);
+        for step in 0..2_000u64 {
+            let seed = rng.next_u64();
+            let steps = (seed >> 40) as usize % 9;
+            match (seed >> 33) % 5 {
+                0 => {
+                    queue.insert(step, Direction::Left).unwrap();
+                    model.push_back(step);
+                }
+                1 => {
+                    queue.rotate(Direction::Right, steps);
+                    let len = model.len().max(1);
+                    model.rotate_left(steps % len);
+                }
+                2 => {
+                    queue.rotate(Direction::Left, steps);
+                    let len = model.len().max(1);
+                    model.rotate_right(steps % len);
+                }
+                3 => {
+                    let target = step % 7;
+                    let expected = model.iter().position(|value| value % 7 == target);
+                    assert_eq!(queue.seek(|value| value % 7 == target), expected);
+                    model.rotate_left(expected.unwrap_or(0));
+                }
+                _ => {
+                    assert_eq!(queue.replace_at_cursor(step), model.front().copied());
+                    if let Some(front) = model.front_mut() {
+                        *front = step;
+                    }
+                }
+            }
+
+            assert_eq!(queue.read_cursor(|value| *value), model.front().copied());
+            assert_eq!(queue.iter().collect::<VecDeque<_>>(), model);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_snapshot_round_trip() {
+        use crate::persist::format::{from_bytes, to_bytes};
+        use crate::storage::node_storage::SlabStorage;
+
+        // The cursor is in the middle of the insertion order, and the ring is rebuilt from it
+        let mut queue = CircularQueue::new(8);
+        for value in ["a", "b", "c", "d"] {
+            queue.insert(value.to_string(), Direction::Left).unwrap();
+        }
+        queue.rotate(Direction::Right, 2);
+
+        let loaded: CircularQueue<String> = from_bytes(&to_bytes(&queue)).unwrap();
+        assert_eq!(loaded.max_size(), 8);
+        assert!(loaded.iter().eq(queue.iter()));
+        assert!(loaded.iter().rev().eq(queue.iter().rev()));
+
+        // The encoding doesn't depend on the storage backend
+        let slab: CircularQueue<String, SlabStorage<String>> =
+            from_bytes(&to_bytes(&queue)).unwrap();
+        assert_eq!(to_bytes(&slab), to_bytes(&queue));
+
+        // A snapshot holding more elements than its maximum size is rejected
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, 1);
+        write_varint(&mut bytes, 2);
+        assert!(CircularQueue::<u8>::read_from(&mut &bytes[..]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        use crate::storage::node_storage::SlabStorage;
+
+        let mut queue = CircularQueue::new(8);
+        for value in ["a", "b", "c", "d"] {
+            queue.insert(value.to_string(), Direction::Left).unwrap();
+        }
+        queue.rotate(Direction::Right, 2);
+
+        let bytes = postcard::to_allocvec(&queue).unwrap();
+        let loaded: CircularQueue<String, SlabStorage<String>> =
+            postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.max_size(), 8);
+        assert!(loaded.iter().eq(queue.iter()));
+        assert!(loaded.iter().rev().eq(queue.iter().rev()));
+
+        // More elements than the maximum size are rejected
+        let bytes = postcard::to_allocvec(&(1usize, vec![1u8, 2])).unwrap();
+        assert!(postcard::from_bytes::<CircularQueue<u8>>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_clone_eq_and_collect() {
+        use crate::linked_list::overflow::EvictOldest;
+        use crate::storage::node_storage::SlabStorage;
+
+        let mut queue: CircularQueue<String> = (0..5).map(|i| i.to_string()).collect();
+        queue.rotate(Direction::Right, 2);
+
+        // The copy is deep and starts at the same cursor
+        let mut copy = queue.clone();
+        assert_eq!(copy, queue);
+        assert_eq!(copy.max_size(), queue.max_size());
+        copy.replace_at_cursor("x".to_string());
+        assert_ne!(copy, queue);
+        assert_eq!(queue.peek_cursor(), Some("2".to_string()));
+
+        // Equality ignores the storage backend but not the cursor position
+        let mut slab = CircularQueue::with_storage(0, SlabStorage::default());
+        slab.extend(["2", "3", "4", "0", "1"].map(String::from));
+        assert!(queue == slab);
+        slab.rotate(Direction::Right, 1);
+        assert!(queue != slab);
+
+        // Extending applies the overflow policy
+        let mut recent = CircularQueue::new(3);
+        recent.set_overflow_policy(EvictOldest);
+        recent.extend(0..10);
+        assert!(recent.iter().eq(7..10));
+
+        let mut full = CircularQueue::new(1);
+        full.insert(0, Direction::Left).unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| full.extend([1])));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_append_and_split_off_against_vec() {
+        let mut queues: Vec<CircularQueue<u32>> = (0..4).map(|_| CircularQueue::new(0)).collect();
+        let mut models: Vec<Vec<u32>> = vec![Vec::new(); 4];
+        let mut rng = Lcg::new(5);
+
+        for step in 0..3_000u32 {
+            let seed = rng.next_u64();
+            let a = (seed >> 33) as usize % 4;
+            let b = (seed >> 40) as usize % 4;
+            match (seed >> 50) % 5 {
+                0 | 1 => {
+                    queues[a].insert(step, Direction::Left).unwrap();
+                    models[a].push(step);
+                }
+                2 if a != b => {
+                    let mut other = std::mem::take(&mut queues[b]);
+                    queues[a].append(&mut other).unwrap();
+                    queues[b] = other;
+                    let moved = std::mem::take(&mut models[b]);
+                    models[a].extend(moved);
+                }
+                3 => {
+                    let at = (seed >> 20) as usize % (models[a].len() + 2);
+                    match queues[a].split_off(at) {
+                        Ok(tail) => {
+                            assert!(tail.iter().eq(models[a].split_off(at)));
+                            queues[b] = tail;
+                            models[b] = queues[b].iter().collect();
+                        }
+                        Err(_) => assert!(at > models[a].len()),
+                    }
+                }
+                _ => {
+                    queues[a].rotate(Direction::Right, 1);
+                    if !models[a].is_empty() {
+                        models[a].rotate_left(1);
+                    }
+                }
+            }
+
+            for (queue, model) in queues.iter().zip(&models) {
+                assert_eq!(queue.len(), model.len());
+                assert!(queue.iter().eq(model.iter().copied()));
+                assert!(queue.iter().rev().eq(model.iter().rev().copied()));
+            }
+        }
+
+        // A partially consumed drain removes the rest of the elements
+        let mut queue = std::mem::take(&mut queues[0]);
+        let expected: Vec<u32> = models[0].iter().take(3).copied().collect();
+        assert_eq!(
+            queue.drain(Direction::Right).take(3).collect::<Vec<_>>(),
+            expected
+        );
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_search_and_removal_against_vec() {
+        let mut queue = CircularQueue::new(0);
+        let mut model: Vec<u32> = Vec::new();
+        let mut rng = Lcg::new(23);
+
+        for step in 0..2_000u32 {
+            let seed = rng.next_u64();
+            let value = (seed >> 33) as u32 % 16;
+            match (seed >> 50) % 6 {
+                0..=2 => {
+                    queue.insert(value, Direction::Left).unwrap();
+                    model.push(value);
+                }
+                3 => {
+                    let expected = model
+                        .iter()
+                        .position(|element| *element == value)
+                        .map(|position| model.remove(position));
+                    assert_eq!(queue.remove_first(&value), expected);
+                }
+                4 if step % 7 == 0 => {
+                    queue.retain(|element| element % 5 != value % 5);
+                    model.retain(|element| element % 5 != value % 5);
+                }
+                _ => {
+                    queue.rotate(Direction::Right, 1);
+                    if !model.is_empty() {
+                        model.rotate_left(1);
+                    }
+                }
+            }
+
+            assert_eq!(queue.contains(&value), model.contains(&value));
+            assert_eq!(
+                queue.position(|element| *element > value),
+                model.iter().position(|element| *element > value)
+            );
+            assert!(queue.iter().eq(model.iter().copied()));
+        }
+    }
+
+    #[test]
+    fn test_observer_sees_every_mutation() {
+        use crate::linked_list::observer::{QueueEvent, QueueObserver};
+        use crate::linked_list::overflow::EvictLowestBy;
+        use std::sync::{Arc, Mutex};
+
+        struct Recorder(Arc<Mutex<Vec<(QueueEvent, i32, usize)>>>);
+
+        impl QueueObserver<i32> for Recorder {
+            fn on_insert(&mut self, element: &i32, len: usize) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((QueueEvent::Insert, *element, len));
+            }
+
+            fn on_remove(&mut self, element: &i32, len: usize) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((QueueEvent::Remove, *element, len));
+            }
+
+            fn on_overflow(&mut self, element: &i32, len: usize) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((QueueEvent::Overflow, *element, len));
+            }
+        }
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut queue = CircularQueue::new(3);
+        queue.set_observer(Recorder(events.clone()));
+        queue.set_overflow_policy(EvictLowestBy(|a: &i32, b: &i32| a.cmp(b)));
+
+        for value in [5, 1, 7] {
+            queue.insert(value, Direction::Left).unwrap();
+        }
+        // The lowest element is evicted from the middle of the ring, then 0 is rejected
+        assert_eq!(queue.insert_or_evict(9, Direction::Left), Ok(Some(1)));
+        assert!(queue.insert(0, Direction::Left).is_err());
+        queue.retain(|value| *value != 7);
+        assert_eq!(
+            std::mem::take(&mut *events.lock().unwrap()),
+            vec![
+                (QueueEvent::Insert, 5, 1),
+                (QueueEvent::Insert, 1, 2),
+                (QueueEvent::Insert, 7, 3),
+                (QueueEvent::Overflow, 9, 3),
+                (QueueEvent::Remove, 1, 2),
+                (QueueEvent::Insert, 9, 3),
+                (QueueEvent::Overflow, 0, 3),
+                (QueueEvent::Remove, 7, 2),
+            ]
+        );
+
+        // Moving rings reports nothing, clearing reports every element and dropping reports nothing
+        let mut tail = queue.split_off(1).unwrap();
+        queue.append(&mut tail).unwrap();
+        queue.clear();
+        queue.insert(3, Direction::Left).unwrap();
+        drop(queue);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (QueueEvent::Remove, 5, 1),
+                (QueueEvent::Remove, 9, 0),
+                (QueueEvent::Insert, 3, 1),
+            ]
+        );
+    }
 }
@@ -0,0 +1,446 @@
+use alloc::vec::Vec;
+use core::{cmp::Ordering, fmt};
+
+use super::circular_queue::{CircularQueue, DebugElements, Direction, IntoIter, Iter};
+use crate::error::DataStructureError;
+
+pub struct Deque<T> {
+    deque: CircularQueue<T>,
+}
+
+impl<T> Deque<T> {
+    /// Creates a new double-ended queue with a specified maximum size.
+    /// If the maximum size is zero, the deque can grow indefinitely.
+    /// # Arguments
+    /// * `max_size` - The maximum number of elements the deque can hold.
+    /// # Returns
+    /// A new instance of Deque.
+    /// # Examples
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let deque: Deque<u32> = Deque::new(5);
+    ///
+    /// assert_eq!(deque.len(), 0);
+    /// assert_eq!(deque.max_size(), 5);
+    /// ```
+    pub fn new(max_size: usize) -> Self {
+        Deque {
+            deque: CircularQueue::new(max_size),
+        }
+    }
+
+    /// Get the number of elements in the deque
+    /// # Returns
+    /// The number of elements in the deque
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let mut deque = Deque::new(0);
+    /// deque.push_back(1).unwrap();
+    /// deque.push_front(2).unwrap();
+    ///
+    /// assert_eq!(deque.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.deque.len()
+    }
+
+    /// Check if the deque is empty
+    /// # Returns
+    /// True if the deque is empty, false otherwise
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let deque: Deque<u32> = Deque::new(5);
+    ///
+    /// assert!(deque.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.deque.is_empty()
+    }
+
+    /// Check if the deque is full
+    /// # Returns
+    /// True if the deque is full, false otherwise
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let mut deque = Deque::new(2);
+    /// deque.push_back(1).unwrap();
+    /// deque.push_front(0).unwrap();
+    ///
+    /// assert!(deque.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.deque.is_full()
+    }
+
+    /// Get the maximum size of the deque
+    /// # Returns
+    /// The maximum size of the deque
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let deque: Deque<u32> = Deque::new(5);
+    ///
+    /// assert_eq!(deque.max_size(), 5);
+    /// ```
+    pub fn max_size(&self) -> usize {
+        self.deque.max_size()
+    }
+
+    /// Set a new maximum size for the deque
+    /// # Arguments
+    /// * `max_size`: The new maximum size for the deque
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the new maximum size is set successfully, Err if the new maximum size is less than the current size
+    /// # Example
+    /// ```rust
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let mut deque = Deque::from(vec![1, 2]);
+    ///
+    /// assert_eq!(deque.set_max_size(1), Err(DataStructureError::CapacityBelowLen { len: 2, requested: 1 }));
+    /// assert_eq!(deque.set_max_size(2), Ok(()));
+    /// assert_eq!(deque.push_front(0), Err(DataStructureError::Full));
+    /// ```
+    pub fn set_max_size(&mut self, max_size: usize) -> Result<(), DataStructureError> {
+        self.deque.set_max_size(max_size)
+    }
+
+    /// Add an element at the front of the deque
+    /// # Arguments
+    /// * `value` - The value to be added
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok(()) if the push was successful, Err(DataStructureError::Full) if the deque is full
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let mut deque = Deque::new(0);
+    /// deque.push_front(1).unwrap();
+    /// deque.push_front(2).unwrap();
+    ///
+    /// assert_eq!(deque.front(), Some(2));
+    /// assert_eq!(deque.back(), Some(1));
+    /// ```
+    pub fn push_front(&mut self, value: T) -> Result<(), DataStructureError> {
+        // The new element goes between the back and the front, and the cursor moves onto it
+        self.deque.insert(value, Direction::Left)?;
+        self.deque.rotate(Direction::Left, 1);
+        Ok(())
+    }
+
+    /// Add an element at the back of the deque
+    /// # Arguments
+    /// * `value` - The value to be added
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok(()) if the push was successful, Err(DataStructureError::Full) if the deque is full
+    /// # Example
+    /// ```rust
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let mut deque = Deque::new(2);
+    ///
+    /// assert_eq!(deque.push_back(1), Ok(()));
+    /// assert_eq!(deque.push_back(2), Ok(()));
+    /// assert_eq!(deque.push_back(3), Err(DataStructureError::Full));
+    /// assert_eq!(deque.back(), Some(2));
+    /// ```
+    pub fn push_back(&mut self, value: T) -> Result<(), DataStructureError> {
+        // The front is at the cursor, so the back is its left neighbor
+        self.deque.insert(value, Direction::Left)
+    }
+
+    /// Remove the element at the front of the deque
+    /// # Returns
+    /// The front element, or None if the deque is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let mut deque = Deque::from(vec![1, 2]);
+    ///
+    /// assert_eq!(deque.pop_front(), Some(1));
+    /// assert_eq!(deque.pop_front(), Some(2));
+    /// assert_eq!(deque.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.deque.remove(Direction::Right)
+    }
+
+    /// Remove the element at the back of the deque
+    /// # Returns
+    /// The back element, or None if the deque is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let mut deque = Deque::from(vec![1, 2]);
+    ///
+    /// assert_eq!(deque.pop_back(), Some(2));
+    /// assert_eq!(deque.pop_back(), Some(1));
+    /// assert_eq!(deque.pop_back(), None);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        // Move the cursor onto the back, then remove it and move back to the front
+        self.deque.rotate(Direction::Left, 1);
+        self.deque.remove(Direction::Right)
+    }
+
+    /// Get a clone of the element at the front of the deque
+    /// # Returns
+    /// The front element, or None if the deque is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let deque = Deque::from(vec!['a', 'b']);
+    /// assert_eq!(deque.front(), Some('a'));
+    /// ```
+    pub fn front(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.deque.peek_cursor()
+    }
+
+    /// Get a clone of the element at the back of the deque
+    /// # Returns
+    /// The back element, or None if the deque is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let deque = Deque::from(vec!['a', 'b']);
+    /// assert_eq!(deque.back(), Some('b'));
+    /// ```
+    pub fn back(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.deque.peek(Direction::Left)
+    }
+
+    /// Remove and drop all the elements, keeping the maximum size
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let mut deque = Deque::from(vec!['a', 'b']);
+    /// deque.clear();
+    ///
+    /// assert!(deque.is_empty());
+    /// assert_eq!(deque.front(), None);
+    /// ```
+    pub fn clear(&mut self) {
+        self.deque.clear();
+    }
+
+    /// Iterate over clones of the elements without removing them
+    /// # Returns
+    /// An iterator from the front to the back, `rev` walks from the back
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::deque::Deque;
+    ///
+    /// let mut deque = Deque::new(0);
+    /// deque.push_back('b').unwrap();
+    /// deque.push_front('a').unwrap();
+    ///
+    /// assert_eq!(deque.iter().collect::<String>(), "ab");
+    /// assert_eq!(deque.iter().rev().collect::<String>(), "ba");
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.deque.iter()
+    }
+}
+
+impl<'a, T: Clone> IntoIterator for &'a Deque<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for Deque<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Drain the deque, yielding the elements from the front to the back
+    fn into_iter(self) -> IntoIter<T> {
+        self.deque.into_iter()
+    }
+}
+
+impl<T> Default for Deque<T> {
+    /// An unbounded deque, like `Deque::new(0)`
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T> From<Vec<T>> for Deque<T> {
+    /// Build an unbounded deque with the elements of the vector from the front to the back.
+    /// A bound can be set afterwards with `set_max_size`.
+    fn from(values: Vec<T>) -> Self {
+        Deque {
+            deque: CircularQueue::from(values),
+        }
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Deque<T> {
+    fn from(values: [T; N]) -> Self {
+        Self::from(Vec::from(values))
+    }
+}
+
+impl<T> FromIterator<T> for Deque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T> Extend<T> for Deque<T> {
+    /// Push the elements at the back, in order
+    ///
+    /// # Panics
+    /// If the deque becomes full. Use `push_back` to handle a full deque.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        // The queue inserts at the left of the cursor, which is the back of the deque
+        self.deque.extend(iter);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Deque<T> {
+    /// Prints the elements from the front to the back
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Deque")
+            .field("len", &self.len())
+            .field("max_size", &self.max_size())
+            .field("elements", &DebugElements(&self.deque))
+            .finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Deque<T> {
+    /// Two deques are equal if they hold equal elements in the same order, whatever their maximum sizes
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .deque
+                .handles()
+                .zip(other.deque.handles())
+                .all(|(a, b)| {
+                    self.deque
+                        .with_element(&a, |a| other.deque.with_element(&b, |b| a == b))
+                })
+    }
+}
+
+impl<T: Eq> Eq for Deque<T> {}
+
+impl<T: PartialOrd> PartialOrd for Deque<T> {
+    /// Lexicographic comparison of the elements, from the front to the back, like `VecDeque`
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.deque.partial_cmp_elements(&other.deque)
+    }
+}
+
+impl<T: Ord> Ord for Deque<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_deque_against_vec_deque() {
+        let mut deque = Deque::new(0);
+        let mut model = VecDeque::new();
+        let mut rng = Lcg::new(99);
+
+        for i in 0..2_000 {
+            let seed = rng.next_u64();
+            match (seed >> 33) % 5 {
+                0 => assert_eq!(deque.pop_front(), model.pop_front()),
+                1 => assert_eq!(deque.pop_back(), model.pop_back()),
+                2 | 3 => {
+                    deque.push_back(i).unwrap();
+                    model.push_back(i);
+                }
+                _ => {
+                    deque.push_front(i).unwrap();
+                    model.push_front(i);
+                }
+            }
+            assert_eq!(deque.len(), model.len());
+            assert_eq!(deque.front(), model.front().copied());
+            assert_eq!(deque.back(), model.back().copied());
+        }
+
+        assert!(deque.iter().eq(model.iter().copied()));
+        assert_eq!(deque, model.iter().copied().collect());
+        assert!(deque.into_iter().eq(model));
+    }
+
+    #[test]
+    fn test_deque_bounded() {
+        let mut deque = Deque::new(2);
+
+        assert_eq!(deque.push_back(1), Ok(()));
+        assert_eq!(deque.push_front(0), Ok(()));
+        assert_eq!(deque.push_front(-1), Err(DataStructureError::Full));
+        assert_eq!(deque.push_back(2), Err(DataStructureError::Full));
+
+        assert_eq!(deque.pop_back(), Some(1));
+        assert_eq!(deque.push_front(-1), Ok(()));
+        assert_eq!(
+            format!("{:?}", deque),
+            "Deque { len: 2, max_size: 2, elements: [-1, 0] }"
+        );
+    }
+
+    #[test]
+    fn test_deque_ordering() {
+        // Elements pushed at the front come first, whatever the insertion order
+        let mut front = Deque::new(0);
+        front.push_back(2).unwrap();
+        front.push_front(1).unwrap();
+
+        assert_eq!(front, Deque::from([1, 2]));
+        assert!(front < Deque::from([1, 3]));
+        assert!(front > Deque::from([1]));
+        assert!(Deque::from([]) < front);
+
+        let mut sorted = vec![Deque::from([2]), front, Deque::from([1]), Deque::from([])];
+        sorted.sort();
+        assert_eq!(
+            sorted,
+            vec![
+                Deque::from([]),
+                Deque::from([1]),
+                Deque::from([1, 2]),
+                Deque::from([2])
+            ]
+        );
+    }
+}
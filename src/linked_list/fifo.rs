@@ -1,4 +1,14 @@
-use super::circular_queue::{CircularQueue, Direction};
+use alloc::vec::Vec;
+use core::{cmp::Ordering, fmt};
+
+use super::circular_queue::{CircularQueue, DebugElements, Direction, Drain, IntoIter, Iter};
+use super::observer::QueueObserver;
+use crate::error::DataStructureError;
+#[cfg(feature = "std")]
+use crate::persist::{
+    format::{read_length, write_varint, Persist},
+    wal::Loggable,
+};
 
 pub struct FIFO<T> {
     fifo: CircularQueue<T>,
@@ -102,10 +112,11 @@ impl<T> FIFO<T> {
     /// # Arguments
     /// * `max_size`: The new maximum size for the queue
     /// # Returns
-    /// Result<(), &'static str>
+    /// Result<(), DataStructureError>
     /// Ok if the new maximum size is set successfully, Err if the new maximum size is less than the current size
     /// # Example
     /// ```rust
+    /// use data_structures::error::DataStructureError;
     /// use data_structures::linked_list::fifo::FIFO;
     ///
     /// let mut fifo: FIFO<i32> = FIFO::new(0);
@@ -114,23 +125,56 @@ impl<T> FIFO<T> {
     /// fifo.push(2);
     /// fifo.push(3);
     ///
-    /// assert_eq!(fifo.set_max_size(2), Err("New max size is less than current size"));
+    /// assert_eq!(fifo.set_max_size(2), Err(DataStructureError::CapacityBelowLen { len: 3, requested: 2 }));
     /// assert_eq!(fifo.set_max_size(3), Ok(()));
     ///
-    /// assert_eq!(fifo.push(4), Err("Queue is full"));
+    /// assert_eq!(fifo.push(4), Err(DataStructureError::Full));
     /// ```
-    pub fn set_max_size(&mut self, max_size: usize) -> Result<(), &'static str> {
+    pub fn set_max_size(&mut self, max_size: usize) -> Result<(), DataStructureError> {
         self.fifo.set_max_size(max_size)
     }
 
+    /// Set the observer told about the pushes, pops and overflows of the queue, replacing the previous one
+    /// # Arguments
+    /// * `observer` - The new observer, a `QueueObserver` or a closure taking a `QueueEvent` and the length
+    /// # Example
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    /// use data_structures::linked_list::fifo::FIFO;
+    /// use data_structures::linked_list::observer::QueueEvent;
+    ///
+    /// let events = Arc::new(Mutex::new(Vec::new()));
+    /// let log = events.clone();
+    ///
+    /// let mut fifo = FIFO::new(1);
+    /// fifo.set_observer(move |event: QueueEvent, len: usize| log.lock().unwrap().push((event, len)));
+    ///
+    /// fifo.push(1).unwrap();
+    /// assert_eq!(fifo.push_overwrite(2), Some(1));
+    ///
+    /// assert_eq!(
+    ///     *events.lock().unwrap(),
+    ///     vec![
+    ///         (QueueEvent::Insert, 1),
+    ///         (QueueEvent::Overflow, 1),
+    ///         (QueueEvent::Remove, 0),
+    ///         (QueueEvent::Insert, 1),
+    ///     ]
+    /// );
+    /// ```
+    pub fn set_observer(&mut self, observer: impl QueueObserver<T> + 'static) {
+        self.fifo.set_observer(observer);
+    }
+
     /// Push a new element to the begining of the queue
     /// # Arguments
     /// * `value` - The value to be added to the queue
     /// # Returns
-    /// Result<(), &'static str>
-    /// Ok(()) if the push was successful, Err("Queue is full") if the queue is full
+    /// Result<(), DataStructureError>
+    /// Ok(()) if the push was successful, Err(DataStructureError::Full) if the queue is full
     /// # Example
     /// ```rust
+    /// use data_structures::error::DataStructureError;
     /// use data_structures::linked_list::fifo::FIFO;
     ///
     /// let mut fifo = FIFO::new(3);
@@ -138,12 +182,32 @@ impl<T> FIFO<T> {
     /// assert_eq!(fifo.push(1), Ok(()));
     /// assert_eq!(fifo.push(2), Ok(()));
     /// assert_eq!(fifo.push(3), Ok(()));
-    /// assert_eq!(fifo.push(4), Err("Queue is full"));
+    /// assert_eq!(fifo.push(4), Err(DataStructureError::Full));
     /// ```
-    pub fn push(&mut self, value: T) -> Result<(), &'static str> {
+    pub fn push(&mut self, value: T) -> Result<(), DataStructureError> {
         self.fifo.insert(value, Direction::Left)
     }
 
+    /// Push an element to the queue, dropping the oldest one if the queue is full
+    /// # Arguments
+    /// * `value` - The value to push onto the queue
+    /// # Returns
+    /// The dropped element, or None if the queue wasn't full
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let mut recent = FIFO::new(2);
+    ///
+    /// assert_eq!(recent.push_overwrite(1), None);
+    /// assert_eq!(recent.push_overwrite(2), None);
+    /// assert_eq!(recent.push_overwrite(3), Some(1));
+    /// assert_eq!(recent.pop(), Some(2));
+    /// ```
+    pub fn push_overwrite(&mut self, value: T) -> Option<T> {
+        self.fifo.insert_overwrite(value, Direction::Left)
+    }
+
     /// Pop an element from the end of the queue
     /// # Returns
     /// Option<T>
@@ -165,11 +229,456 @@ impl<T> FIFO<T> {
     pub fn pop(&mut self) -> Option<T> {
         self.fifo.remove(Direction::Right)
     }
+
+    /// Get a clone of the oldest element, the next one `pop` returns
+    /// # Returns
+    /// The oldest element, or None if the queue is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let mut fifo = FIFO::new(0);
+    /// fifo.push(1).unwrap();
+    /// fifo.push(2).unwrap();
+    ///
+    /// assert_eq!(fifo.front(), Some(1));
+    /// assert_eq!(fifo.pop(), Some(1));
+    /// ```
+    pub fn front(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.fifo.peek_cursor()
+    }
+
+    /// Get a clone of the newest element, the last one pushed
+    /// # Returns
+    /// The newest element, or None if the queue is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let mut fifo = FIFO::new(0);
+    /// assert_eq!(fifo.back(), None);
+    ///
+    /// fifo.push(1).unwrap();
+    /// fifo.push(2).unwrap();
+    /// assert_eq!(fifo.back(), Some(2));
+    /// ```
+    pub fn back(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        // Pushes go to the left of the cursor, which holds the oldest element
+        self.fifo.peek(Direction::Left)
+    }
+
+    /// Iterate over clones of the elements without popping them
+    /// # Returns
+    /// An iterator from the oldest to the newest element, `rev` walks from the newest one
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let mut fifo = FIFO::new(0);
+    /// fifo.push('a').unwrap();
+    /// fifo.push('b').unwrap();
+    ///
+    /// assert_eq!(fifo.iter().collect::<String>(), "ab");
+    /// assert_eq!(fifo.iter().rev().collect::<String>(), "ba");
+    /// assert_eq!(fifo.pop(), Some('a'));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.fifo.iter()
+    }
+
+    /// Pop all the elements
+    /// # Returns
+    /// An iterator popping the elements from the oldest to the newest one. The elements not consumed are dropped with it.
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let mut fifo = FIFO::from(vec![1, 2, 3]);
+    ///
+    /// assert_eq!(fifo.drain().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// assert!(fifo.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        self.fifo.drain(Direction::Right)
+    }
+
+    /// Remove and drop all the elements, keeping the maximum size
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let mut fifo = FIFO::from(vec![1, 2, 3]);
+    /// fifo.clear();
+    ///
+    /// assert!(fifo.is_empty());
+    /// assert_eq!(fifo.pop(), None);
+    /// ```
+    pub fn clear(&mut self) {
+        self.fifo.clear();
+    }
+
+    /// Move all the elements of another FIFO after the newest element of this one, in O(1)
+    /// # Arguments
+    /// * `other` - The FIFO to empty into this one
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the elements were moved, Err(DataStructureError::Full) if they don't fit, in which case both FIFOs are
+    /// unchanged
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let mut fifo = FIFO::from(vec![1, 2]);
+    /// let mut other = FIFO::from(vec![3]);
+    ///
+    /// fifo.append(&mut other).unwrap();
+    /// assert_eq!(fifo, FIFO::from(vec![1, 2, 3]));
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut FIFO<T>) -> Result<(), DataStructureError> {
+        self.fifo.append(&mut other.fifo)
+    }
+
+    /// Split the FIFO in two, keeping the `at` oldest elements and returning the newer ones
+    /// # Arguments
+    /// * `at` - The number of elements kept
+    /// # Returns
+    /// Result<FIFO<T>, DataStructureError>
+    /// Ok with the newer elements in a FIFO of the same maximum size, Err(DataStructureError::IndexOutOfBounds) if
+    /// `at` is greater than the length
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let mut fifo = FIFO::from(vec![1, 2, 3]);
+    ///
+    /// let newer = fifo.split_off(1).unwrap();
+    /// assert_eq!(fifo, FIFO::from(vec![1]));
+    /// assert_eq!(newer, FIFO::from(vec![2, 3]));
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> Result<FIFO<T>, DataStructureError> {
+        Ok(FIFO {
+            fifo: self.fifo.split_off(at)?,
+        })
+    }
+
+    /// Check if the FIFO holds an element equal to a value
+    /// # Arguments
+    /// * `value` - The value to look for
+    /// # Returns
+    /// True if an element is equal to `value`
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let fifo = FIFO::from(vec![1, 2]);
+    /// assert!(fifo.contains(&2));
+    /// ```
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.fifo.contains(value)
+    }
+
+    /// Find the oldest element matching a predicate
+    /// # Arguments
+    /// * `predicate` - The function returning true for the element to find
+    /// # Returns
+    /// The number of older elements, the number of pops before it, or None if no element matches
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let fifo = FIFO::from(vec!["a", "bb", "cc"]);
+    /// assert_eq!(fifo.position(|value| value.len() == 2), Some(1));
+    /// ```
+    pub fn position(&self, predicate: impl FnMut(&T) -> bool) -> Option<usize> {
+        self.fifo.position(predicate)
+    }
+
+    /// Keep only the elements matching a predicate, in their order
+    /// # Arguments
+    /// * `predicate` - The function returning true for the elements to keep, called from the oldest element
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let mut tasks = FIFO::from(vec![(1, "done"), (2, "pending"), (3, "done")]);
+    /// tasks.retain(|(_, state)| *state != "done");
+    ///
+    /// assert_eq!(tasks.pop(), Some((2, "pending")));
+    /// assert!(tasks.is_empty());
+    /// ```
+    pub fn retain(&mut self, predicate: impl FnMut(&T) -> bool) {
+        self.fifo.retain(predicate)
+    }
+
+    /// Remove the oldest element equal to a value
+    /// # Arguments
+    /// * `value` - The value to remove
+    /// # Returns
+    /// The removed element, or None if no element is equal to `value`
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::fifo::FIFO;
+    ///
+    /// let mut pending = FIFO::from(vec![1, 2, 1]);
+    ///
+    /// assert_eq!(pending.remove_first(&1), Some(1));
+    /// assert_eq!(pending, FIFO::from(vec![2, 1]));
+    /// ```
+    pub fn remove_first(&mut self, value: &T) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        self.fifo.remove_first(value)
+    }
+}
+
+impl<'a, T: Clone> IntoIterator for &'a FIFO<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for FIFO<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Drain the FIFO, yielding the elements in the order they would be popped
+    fn into_iter(self) -> IntoIter<T> {
+        self.fifo.into_iter()
+    }
+}
+
+impl<T> Default for FIFO<T> {
+    /// An unbounded FIFO, like `FIFO::new(0)`
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T> From<Vec<T>> for FIFO<T> {
+    /// Build an unbounded FIFO that pops the elements in the order they have in the vector.
+    /// A bound can be set afterwards with `set_max_size`, or given with the elements to `TryFrom<(usize, Vec<T>)>`.
+    fn from(values: Vec<T>) -> Self {
+        FIFO {
+            fifo: CircularQueue::from(values),
+        }
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for FIFO<T> {
+    fn from(values: [T; N]) -> Self {
+        Self::from(Vec::from(values))
+    }
+}
+
+impl<T: Clone> From<&[T]> for FIFO<T> {
+    fn from(values: &[T]) -> Self {
+        Self::from(values.to_vec())
+    }
+}
+
+impl<T> TryFrom<(usize, Vec<T>)> for FIFO<T> {
+    type Error = DataStructureError;
+
+    /// Build a FIFO with the given maximum size that pops the elements in the order they have in the vector.
+    /// Fails with `Full` if there are more elements than the maximum size, which is never the case for a size of 0.
+    fn try_from(source: (usize, Vec<T>)) -> Result<Self, DataStructureError> {
+        Ok(FIFO {
+            fifo: CircularQueue::try_from(source)?,
+        })
+    }
+}
+
+impl<T, const N: usize> TryFrom<(usize, [T; N])> for FIFO<T> {
+    type Error = DataStructureError;
+
+    fn try_from(source: (usize, [T; N])) -> Result<Self, DataStructureError> {
+        Ok(FIFO {
+            fifo: CircularQueue::try_from(source)?,
+        })
+    }
+}
+
+impl<T: Clone> TryFrom<(usize, &[T])> for FIFO<T> {
+    type Error = DataStructureError;
+
+    fn try_from(source: (usize, &[T])) -> Result<Self, DataStructureError> {
+        Ok(FIFO {
+            fifo: CircularQueue::try_from(source)?,
+        })
+    }
+}
+
+impl<T> FromIterator<T> for FIFO<T> {
+    /// Build an unbounded FIFO that pops the elements in the order of the iterator
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        FIFO {
+            fifo: CircularQueue::from_iter(iter),
+        }
+    }
+}
+
+impl<T> Extend<T> for FIFO<T> {
+    /// Push the elements in order
+    ///
+    /// # Panics
+    /// If the FIFO becomes full. Use `push` to handle a full FIFO.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.fifo.extend(iter);
+    }
+}
+
+impl<T: Clone> Clone for FIFO<T> {
+    /// Copy the elements into a new FIFO with the same maximum size
+    fn clone(&self) -> Self {
+        FIFO {
+            fifo: self.fifo.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for FIFO<T> {
+    /// Prints the elements from the oldest to the newest one
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FIFO")
+            .field("len", &self.len())
+            .field("max_size", &self.max_size())
+            .field("elements", &DebugElements(&self.fifo))
+            .finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for FIFO<T> {
+    /// Two FIFOs are equal if they hold equal elements in the same order, whatever their maximum sizes
+    fn eq(&self, other: &Self) -> bool {
+        self.fifo == other.fifo
+    }
+}
+
+impl<T: Eq> Eq for FIFO<T> {}
+
+impl<T: PartialOrd> PartialOrd for FIFO<T> {
+    /// Lexicographic comparison of the elements, from the oldest to the newest one, like `Vec` and `VecDeque`
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.fifo.partial_cmp_elements(&other.fifo)
+    }
+}
+
+impl<T: Ord> Ord for FIFO<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).unwrap()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for FIFO<T> {
+    /// The maximum size and the elements, from the oldest to the newest one, like a `CircularQueue`
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.fifo.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for FIFO<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(FIFO {
+            fifo: CircularQueue::deserialize(deserializer)?,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Persist> Persist for FIFO<T> {
+    /// The maximum size followed by the elements, from the oldest to the newest one
+    fn write_to(&self, out: &mut Vec<u8>) {
+        write_varint(out, self.max_size() as u64);
+        write_varint(out, self.len() as u64);
+        for node in self.fifo.handles() {
+            self.fifo
+                .with_element(&node, |element| element.write_to(out));
+        }
+    }
+
+    fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+        let max_size = read_length(input)?;
+        let length = read_length(input)?;
+        if max_size != 0 && length > max_size {
+            return Err(DataStructureError::InvalidEncoding(
+                "More elements than the maximum size",
+            ));
+        }
+
+        let mut fifo = FIFO::new(max_size);
+        for _ in 0..length {
+            fifo.push(T::read_from(input)?)?;
+        }
+        Ok(fifo)
+    }
+}
+
+/// A mutating operation on a FIFO, as recorded by a write-ahead log
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FifoOp<T> {
+    Push(T),
+    Pop,
+}
+
+#[cfg(feature = "std")]
+impl<T: Persist> Persist for FifoOp<T> {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        match self {
+            FifoOp::Push(value) => {
+                out.push(0);
+                value.write_to(out);
+            }
+            FifoOp::Pop => out.push(1),
+        }
+    }
+
+    fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+        match u8::read_from(input)? {
+            0 => Ok(FifoOp::Push(T::read_from(input)?)),
+            1 => Ok(FifoOp::Pop),
+            _ => Err(DataStructureError::InvalidEncoding(
+                "Unknown FIFO operation",
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Persist> Loggable for FIFO<T> {
+    type Op = FifoOp<T>;
+
+    /// Ok(None) for a push, Ok with the popped element for a pop, or the error of a push on a full FIFO
+    type Output = Result<Option<T>, DataStructureError>;
+
+    fn apply(&mut self, op: FifoOp<T>) -> Self::Output {
+        match op {
+            FifoOp::Push(value) => self.push(value).map(|_| None),
+            FifoOp::Pop => Ok(self.pop()),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::Lcg;
 
     #[test]
     fn test_fifo() {
@@ -183,7 +692,7 @@ mod tests {
 
         assert_eq!(fifo.is_full(), true);
 
-        assert_eq!(fifo.push(4), Err("Queue is full"));
+        assert_eq!(fifo.push(4), Err(DataStructureError::Full));
 
         assert_eq!(fifo.pop(), Some(1));
         assert_eq!(fifo.pop(), Some(2));
@@ -191,4 +700,169 @@ mod tests {
 
         assert_eq!(fifo.pop(), None);
     }
+
+    #[test]
+    fn test_fifo_peek() {
+        use std::collections::VecDeque;
+
+        let mut fifo = FIFO::new(0);
+        let mut model = VecDeque::new();
+        let mut rng = Lcg::new(42);
+
+        for i in 0..1_000 {
+            let seed = rng.next_u64();
+            if seed >> 62 == 0 {
+                assert_eq!(fifo.pop(), model.pop_front());
+            } else {
+                fifo.push(i).unwrap();
+                model.push_back(i);
+            }
+
+            assert_eq!(fifo.front(), model.front().copied());
+            assert_eq!(fifo.back(), model.back().copied());
+        }
+    }
+
+    #[test]
+    fn test_fifo_debug() {
+        let mut fifo = FIFO::new(0);
+
+        fifo.push("a").unwrap();
+        fifo.push("b").unwrap();
+        fifo.push("c").unwrap();
+        fifo.pop();
+
+        assert_eq!(
+            format!("{:?}", fifo),
+            r#"FIFO { len: 2, max_size: 0, elements: ["b", "c"] }"#
+        );
+    }
+
+    #[test]
+    fn test_fifo_ordering() {
+        let build = |values: &[i32]| {
+            let mut fifo = FIFO::new(0);
+            for value in values {
+                fifo.push(*value).unwrap();
+            }
+            fifo
+        };
+
+        assert!(build(&[1, 2, 3]) < build(&[1, 3]));
+        assert!(build(&[1, 2]) < build(&[1, 2, 0]));
+        assert!(build(&[]) < build(&[0]));
+        assert_eq!(build(&[4, 5]), build(&[4, 5]));
+
+        // The maximum size doesn't take part in the comparison
+        let mut bounded = FIFO::new(2);
+        bounded.push(4).unwrap();
+        bounded.push(5).unwrap();
+        assert_eq!(bounded, build(&[4, 5]));
+
+        let mut sorted = vec![build(&[2]), build(&[1, 9]), build(&[1]), build(&[])];
+        sorted.sort();
+        assert_eq!(
+            sorted,
+            vec![build(&[]), build(&[1]), build(&[1, 9]), build(&[2])]
+        );
+    }
+
+    #[test]
+    fn test_fifo_default() {
+        fn build<C: Default>() -> C {
+            C::default()
+        }
+
+        // The default FIFO is unbounded
+        let mut fifo: FIFO<u32> = build();
+        assert_eq!(fifo.max_size(), 0);
+        for i in 0..100 {
+            fifo.push(i).unwrap();
+        }
+        assert_eq!(fifo.len(), 100);
+    }
+
+    #[test]
+    fn test_fifo_from() {
+        let mut fifo = FIFO::from([1, 2, 3]);
+        assert_eq!(fifo.max_size(), 0);
+        assert_eq!(fifo, FIFO::from(vec![1, 2, 3]));
+        assert_eq!(fifo, FIFO::from(&[1, 2, 3][..]));
+        assert_eq!(fifo, (1..=3).collect());
+
+        // A clone is independent of the original
+        let mut copy = fifo.clone();
+        copy.extend([4, 5]);
+        assert!(copy.iter().eq(1..=5));
+        assert_eq!(fifo.len(), 3);
+
+        // Bounding the converted FIFO fails if it already holds more elements
+        assert_eq!(
+            fifo.set_max_size(2),
+            Err(DataStructureError::CapacityBelowLen {
+                len: 3,
+                requested: 2
+            })
+        );
+        assert_eq!(fifo.set_max_size(3), Ok(()));
+
+        assert_eq!(fifo.pop(), Some(1));
+        assert_eq!(fifo.pop(), Some(2));
+        assert_eq!(fifo.pop(), Some(3));
+    }
+
+    #[test]
+    fn test_fifo_try_from() {
+        let mut fifo = FIFO::try_from((3, [1, 2, 3])).unwrap();
+        assert_eq!(fifo.max_size(), 3);
+        assert!(fifo.is_full());
+        assert_eq!(fifo, FIFO::try_from((3, vec![1, 2, 3])).unwrap());
+        assert_eq!(fifo, FIFO::try_from((3, &[1, 2, 3][..])).unwrap());
+        assert_eq!(fifo.pop(), Some(1));
+
+        // The conversion fails when the source exceeds the maximum size, and 0 keeps the FIFO unbounded
+        assert_eq!(
+            FIFO::try_from((2, [1, 2, 3])).err(),
+            Some(DataStructureError::Full)
+        );
+        assert_eq!(
+            FIFO::try_from((2, &[1, 2, 3][..])).err(),
+            Some(DataStructureError::Full)
+        );
+        assert_eq!(FIFO::try_from((0, vec![1, 2, 3])).unwrap().max_size(), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_fifo_persist() {
+        use crate::persist::format::{from_bytes, to_bytes};
+
+        let mut fifo = FIFO::new(4);
+        fifo.push("a".to_string()).unwrap();
+        fifo.push("b".to_string()).unwrap();
+        fifo.push("c".to_string()).unwrap();
+        fifo.pop();
+
+        let mut loaded: FIFO<String> = from_bytes(&to_bytes(&fifo)).unwrap();
+        assert_eq!(loaded, fifo);
+        assert_eq!(loaded.max_size(), 4);
+        assert_eq!(loaded.pop(), Some("b".to_string()));
+        assert_eq!(loaded.pop(), Some("c".to_string()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_fifo_serde() {
+        let mut fifo = FIFO::new(4);
+        for value in 0..4u32 {
+            fifo.push(value).unwrap();
+        }
+        fifo.pop();
+
+        let bytes = postcard::to_allocvec(&fifo).unwrap();
+        let mut loaded: FIFO<u32> = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded, fifo);
+        assert_eq!(loaded.max_size(), 4);
+        assert_eq!(loaded.pop(), Some(1));
+    }
 }
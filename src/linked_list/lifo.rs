@@ -0,0 +1,327 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::circular_queue::{CircularQueue, DebugElements, Direction, IntoIter, Iter};
+use crate::error::DataStructureError;
+
+pub struct Stack<T> {
+    stack: CircularQueue<T>,
+}
+
+impl<T> Stack<T> {
+    /// Creates a new stack with a specified maximum size.
+    /// If the maximum size is zero, the stack can grow indefinitely.
+    /// # Arguments
+    /// * `max_size` - The maximum number of elements the stack can hold.
+    /// # Returns
+    /// A new instance of Stack.
+    /// # Examples
+    /// ```rust
+    /// use data_structures::linked_list::lifo::Stack;
+    ///
+    /// let stack: Stack<u32> = Stack::new(5);
+    ///
+    /// assert_eq!(stack.len(), 0);
+    /// assert_eq!(stack.max_size(), 5);
+    /// ```
+    pub fn new(max_size: usize) -> Self {
+        Stack {
+            stack: CircularQueue::new(max_size),
+        }
+    }
+
+    /// Get the number of elements in the stack
+    /// # Returns
+    /// The number of elements in the stack
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::lifo::Stack;
+    ///
+    /// let mut stack: Stack<u32> = Stack::new(5);
+    ///
+    /// assert_eq!(stack.len(), 0);
+    ///
+    /// stack.push(1).unwrap();
+    /// stack.push(2).unwrap();
+    /// assert_eq!(stack.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Check if the stack is empty
+    /// # Returns
+    /// True if the stack is empty, false otherwise
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::lifo::Stack;
+    ///
+    /// let stack: Stack<u32> = Stack::new(5);
+    ///
+    /// assert!(stack.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// Check if the stack is full
+    /// # Returns
+    /// True if the stack is full, false otherwise
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::lifo::Stack;
+    ///
+    /// let mut stack: Stack<u32> = Stack::new(2);
+    ///
+    /// stack.push(1).unwrap();
+    /// stack.push(2).unwrap();
+    /// assert!(stack.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.stack.is_full()
+    }
+
+    /// Get the maximum size of the stack
+    /// # Returns
+    /// The maximum size of the stack
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::lifo::Stack;
+    ///
+    /// let stack: Stack<u32> = Stack::new(5);
+    ///
+    /// assert_eq!(stack.max_size(), 5);
+    /// ```
+    pub fn max_size(&self) -> usize {
+        self.stack.max_size()
+    }
+
+    /// Set a new maximum size for the stack
+    /// # Arguments
+    /// * `max_size`: The new maximum size for the stack
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the new maximum size is set successfully, Err if the new maximum size is less than the current size
+    /// # Example
+    /// ```rust
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::lifo::Stack;
+    ///
+    /// let mut stack = Stack::new(0);
+    /// stack.push(1).unwrap();
+    /// stack.push(2).unwrap();
+    ///
+    /// assert_eq!(stack.set_max_size(1), Err(DataStructureError::CapacityBelowLen { len: 2, requested: 1 }));
+    /// assert_eq!(stack.set_max_size(2), Ok(()));
+    /// assert_eq!(stack.push(3), Err(DataStructureError::Full));
+    /// ```
+    pub fn set_max_size(&mut self, max_size: usize) -> Result<(), DataStructureError> {
+        self.stack.set_max_size(max_size)
+    }
+
+    /// Push a new element on top of the stack
+    /// # Arguments
+    /// * `value` - The value to be added to the stack
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok(()) if the push was successful, Err(DataStructureError::Full) if the stack is full
+    /// # Example
+    /// ```rust
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::lifo::Stack;
+    ///
+    /// let mut stack = Stack::new(2);
+    ///
+    /// assert_eq!(stack.push(1), Ok(()));
+    /// assert_eq!(stack.push(2), Ok(()));
+    /// assert_eq!(stack.push(3), Err(DataStructureError::Full));
+    /// ```
+    pub fn push(&mut self, value: T) -> Result<(), DataStructureError> {
+        // The new element goes next to the top, and becomes the top
+        self.stack.insert(value, Direction::Left)?;
+        self.stack.rotate(Direction::Left, 1);
+        Ok(())
+    }
+
+    /// Pop the element on top of the stack, the last one pushed
+    /// # Returns
+    /// Option<T>
+    /// Some(T) if the stack is not empty, None if the stack is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::lifo::Stack;
+    ///
+    /// let mut stack = Stack::new(3);
+    ///
+    /// stack.push(1).unwrap();
+    /// stack.push(2).unwrap();
+    /// stack.push(3).unwrap();
+    /// assert_eq!(stack.pop(), Some(3));
+    /// assert_eq!(stack.pop(), Some(2));
+    /// assert_eq!(stack.pop(), Some(1));
+    /// assert_eq!(stack.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        // The element below the top is on the right of the cursor
+        self.stack.remove(Direction::Right)
+    }
+
+    /// Get a clone of the element on top of the stack, the next one `pop` returns
+    /// # Returns
+    /// The top element, or None if the stack is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::lifo::Stack;
+    ///
+    /// let mut stack = Stack::new(0);
+    /// assert_eq!(stack.peek(), None);
+    ///
+    /// stack.push(1).unwrap();
+    /// stack.push(2).unwrap();
+    /// assert_eq!(stack.peek(), Some(2));
+    /// assert_eq!(stack.len(), 2);
+    /// ```
+    pub fn peek(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.stack.peek_cursor()
+    }
+
+    /// Remove and drop all the elements, keeping the maximum size
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::lifo::Stack;
+    ///
+    /// let mut stack = Stack::new(2);
+    /// stack.push(1).unwrap();
+    /// stack.push(2).unwrap();
+    /// stack.clear();
+    ///
+    /// assert!(stack.is_empty());
+    /// assert_eq!(stack.max_size(), 2);
+    /// ```
+    pub fn clear(&mut self) {
+        self.stack.clear();
+    }
+
+    /// Iterate over clones of the elements without popping them
+    /// # Returns
+    /// An iterator from the top to the bottom of the stack, `rev` walks from the bottom
+    /// # Example
+    /// ```rust
+    /// use data_structures::linked_list::lifo::Stack;
+    ///
+    /// let mut stack = Stack::new(0);
+    /// stack.push('a').unwrap();
+    /// stack.push('b').unwrap();
+    ///
+    /// assert_eq!(stack.iter().collect::<String>(), "ba");
+    /// assert_eq!(stack.iter().rev().collect::<String>(), "ab");
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.stack.iter()
+    }
+}
+
+impl<'a, T: Clone> IntoIterator for &'a Stack<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for Stack<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Drain the stack, yielding the elements in the order they would be popped
+    fn into_iter(self) -> IntoIter<T> {
+        self.stack.into_iter()
+    }
+}
+
+impl<T> Default for Stack<T> {
+    /// An unbounded stack, like `Stack::new(0)`
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T> From<Vec<T>> for Stack<T> {
+    /// Build an unbounded stack by pushing the elements of the vector in order, so its last element is on top.
+    /// A bound can be set afterwards with `set_max_size`.
+    fn from(values: Vec<T>) -> Self {
+        let mut stack = Stack::new(0);
+        for value in values {
+            stack.push(value).expect("An unbounded stack is never full");
+        }
+        stack
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Stack<T> {
+    /// Prints the elements from the top to the bottom of the stack
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stack")
+            .field("len", &self.len())
+            .field("max_size", &self.max_size())
+            .field("elements", &DebugElements(&self.stack))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_stack() {
+        let mut stack = Stack::new(3);
+
+        assert!(stack.is_empty());
+
+        assert_eq!(stack.push(1), Ok(()));
+        assert_eq!(stack.push(2), Ok(()));
+        assert_eq!(stack.push(3), Ok(()));
+
+        assert!(stack.is_full());
+
+        assert_eq!(stack.push(4), Err(DataStructureError::Full));
+        assert_eq!(stack.peek(), Some(3));
+
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn test_stack_against_vec() {
+        let mut stack = Stack::new(0);
+        let mut model = Vec::new();
+        let mut rng = Lcg::new(7);
+
+        for i in 0..1_000 {
+            let seed = rng.next_u64();
+            if seed >> 62 == 0 {
+                assert_eq!(stack.pop(), model.pop());
+            } else {
+                stack.push(i).unwrap();
+                model.push(i);
+            }
+            assert_eq!(stack.peek(), model.last().copied());
+        }
+
+        assert!(stack.iter().eq(model.iter().rev().copied()));
+        assert_eq!(
+            format!("{:?}", Stack::from(vec!["a", "b"])),
+            r#"Stack { len: 2, max_size: 0, elements: ["b", "a"] }"#
+        );
+        assert!(stack.into_iter().eq(model.into_iter().rev()));
+    }
+}
@@ -0,0 +1,899 @@
+//! This module implements a doubly linked list built on the `Vertex` primitive of the crate.
+//! Elements can be pushed and popped at both ends, inserted and removed at any position, and two lists can be joined
+//! without moving their elements. Like `CircularQueue`, the list is generic over the `NodeStorage` holding its nodes:
+//! `LinkedList::new` uses reference counted vertexes, and `LinkedList::with_storage` takes another backend, such as
+//! `ArenaStorage` to keep every node in one buffer without reference counting.
+//!
+//! # Performance
+//! - O(1) for pushing and popping at both ends, and for `append`
+//! - O(min(i, n - i)) for `insert_at` and `remove_at` at index i, the walk starting from the nearest end
+//! - O(1) for each step of an iteration
+//!
+//! # Implementation Details
+//! - Each element lives in a node of the storage, linked to the previous one on its left and to the next one on its
+//!   right. The list holds the first and the last node and the number of elements.
+//! - With `RcStorage` both links are strong pointers, so the vertexes form reference cycles. Every removal frees the
+//!   node, dropping its links, and dropping the list frees the remaining nodes one by one, so no vertex is leaked and
+//!   long lists are dropped without recursion.
+//! - `append` splices the nodes of the other list, so it needs both lists to share their backend and is only provided
+//!   for `RcStorage`. `Extend` moves the elements of any list.
+//! - Iterating by reference yields clones of the elements, since the storage only lends them inside a closure.
+//!
+//! # Usage
+//! ```
+//! use data_structures::linked_list::list::LinkedList;
+//!
+//! let mut list = LinkedList::new();
+//! list.push_back(2);
+//! list.push_front(1);
+//! list.insert_at(2, 4).unwrap();
+//! list.insert_at(2, 3).unwrap();
+//!
+//! assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+//! assert_eq!(list.remove_at(1), Some(2));
+//! assert_eq!(list.pop_back(), Some(4));
+//! assert_eq!(list.len(), 2);
+//! ```
+//!
+use alloc::vec::Vec;
+use core::{cmp::Ordering, fmt};
+
+use super::circular_queue::Direction;
+use crate::error::DataStructureError;
+#[cfg(feature = "std")]
+use crate::persist::format::{read_length, write_varint, Persist};
+use crate::storage::node_storage::{NodeStorage, RcStorage};
+#[cfg(feature = "std")]
+use crate::visualize::topology::Topology;
+
+/// Struct representing a doubly linked list of nodes
+///
+pub struct LinkedList<T, S: NodeStorage<T> = RcStorage> {
+    storage: S,
+    head: Option<S::Handle>,
+    tail: Option<S::Handle>,
+    len: usize,
+}
+
+/// Iterator over clones of the elements of a list, from the front to the back
+///
+pub struct Iter<'a, T, S: NodeStorage<T> = RcStorage> {
+    list: &'a LinkedList<T, S>,
+    front: Option<S::Handle>,
+    back: Option<S::Handle>,
+    remaining: usize,
+}
+
+/// Iterator popping the elements of a list, from the front to the back
+///
+pub struct IntoIter<T, S: NodeStorage<T> = RcStorage> {
+    list: LinkedList<T, S>,
+}
+
+impl<T> LinkedList<T> {
+    /// Create a new empty list
+    ///
+    /// # Returns
+    /// A new LinkedList instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let list: LinkedList<u32> = LinkedList::new();
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_storage(RcStorage)
+    }
+
+    /// Move every element of another list to the back of this one, leaving the other list empty
+    /// # Arguments
+    /// * `other`: The list whose elements are moved
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let mut first = LinkedList::from(vec![1, 2]);
+    /// let mut second = LinkedList::from(vec![3, 4]);
+    ///
+    /// first.append(&mut second);
+    /// assert_eq!(first.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    /// assert!(second.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut LinkedList<T>) {
+        let Some(other_head) = other.head.take() else {
+            return;
+        };
+
+        // Both lists hold reference counted vertexes, which can be linked across lists
+        match self.tail.clone() {
+            Some(tail) => self.link(&tail, &other_head),
+            None => self.head = Some(other_head),
+        }
+        self.tail = other.tail.take();
+        self.len += core::mem::take(&mut other.len);
+    }
+
+    /// Take a snapshot of the vertexes of the list, for debugging
+    /// # Returns
+    /// The topology of the vertexes reachable from the front, ready to be rendered as DOT or Mermaid
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let list = LinkedList::from(vec![1, 2, 3]);
+    /// let topology = list.topology();
+    ///
+    /// assert_eq!(topology.node_count(), 3);
+    /// println!("{}", topology.to_mermaid());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn topology(&self) -> Topology
+    where
+        T: fmt::Debug,
+    {
+        Topology::from_vertices(&self.head)
+    }
+}
+
+impl<T, S: NodeStorage<T>> LinkedList<T, S> {
+    /// Create a new empty list storing its nodes in the given backend
+    ///
+    /// # Arguments
+    /// * `storage`: The backend holding the nodes
+    ///
+    /// # Returns
+    /// A new LinkedList instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    /// use data_structures::storage::node_storage::ArenaStorage;
+    ///
+    /// let mut list = LinkedList::with_storage(ArenaStorage::with_capacity(2));
+    /// list.push_back(1);
+    /// list.push_front(0);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![0, 1]);
+    /// ```
+    pub fn with_storage(storage: S) -> Self {
+        LinkedList {
+            storage,
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    /// Get the backend holding the nodes
+    /// # Returns
+    /// A reference to the storage
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    /// use data_structures::storage::node_storage::SlabStorage;
+    ///
+    /// let mut list = LinkedList::with_storage(SlabStorage::new());
+    /// list.push_back('a');
+    /// assert_eq!(list.storage().len(), 1);
+    /// ```
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Get the number of elements in the list
+    /// # Returns
+    /// The number of elements in the list
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let mut list = LinkedList::new();
+    /// list.push_back('a');
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the list is empty
+    /// # Returns
+    /// True if the list has no elements, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let mut list = LinkedList::new();
+    /// assert!(list.is_empty());
+    ///
+    /// list.push_front(1);
+    /// assert!(!list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add an element at the front of the list
+    /// # Arguments
+    /// * `value`: The element to add
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let mut list = LinkedList::new();
+    /// list.push_front(2);
+    /// list.push_front(1);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    pub fn push_front(&mut self, value: T) {
+        let node = self.storage.alloc(value);
+        match self.head.take() {
+            Some(head) => self.link(&node, &head),
+            None => self.tail = Some(node.clone()),
+        }
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    /// Add an element at the back of the list
+    /// # Arguments
+    /// * `value`: The element to add
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let mut list = LinkedList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    pub fn push_back(&mut self, value: T) {
+        let node = self.storage.alloc(value);
+        match self.tail.take() {
+            Some(tail) => self.link(&tail, &node),
+            None => self.head = Some(node.clone()),
+        }
+        self.tail = Some(node);
+        self.len += 1;
+    }
+
+    /// Remove the element at the front of the list
+    /// # Returns
+    /// The first element, or None if the list is empty
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let mut list = LinkedList::from(vec![1, 2]);
+    /// assert_eq!(list.pop_front(), Some(1));
+    /// assert_eq!(list.pop_front(), Some(2));
+    /// assert_eq!(list.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head.clone()?;
+        Some(self.unlink(head))
+    }
+
+    /// Remove the element at the back of the list
+    /// # Returns
+    /// The last element, or None if the list is empty
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let mut list = LinkedList::from(vec![1, 2]);
+    /// assert_eq!(list.pop_back(), Some(2));
+    /// assert_eq!(list.pop_back(), Some(1));
+    /// assert_eq!(list.pop_back(), None);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail.clone()?;
+        Some(self.unlink(tail))
+    }
+
+    /// Insert an element at a position, shifting the following elements towards the back
+    /// # Arguments
+    /// * `index`: The position of the new element, from 0 to the length of the list
+    /// * `value`: The element to insert
+    /// # Returns
+    /// Ok if the element was inserted, or Err(DataStructureError::IndexOutOfBounds) if `index` is greater than the
+    /// length, in which case `value` is dropped
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let mut list = LinkedList::from(vec!['a', 'c']);
+    ///
+    /// assert_eq!(list.insert_at(1, 'b'), Ok(()));
+    /// assert_eq!(list.iter().collect::<String>(), "abc");
+    /// assert_eq!(
+    ///     list.insert_at(5, 'z'),
+    ///     Err(DataStructureError::IndexOutOfBounds { index: 5, len: 3 })
+    /// );
+    /// ```
+    pub fn insert_at(&mut self, index: usize, value: T) -> Result<(), DataStructureError> {
+        if index > self.len {
+            return Err(DataStructureError::IndexOutOfBounds {
+                index,
+                len: self.len,
+            });
+        }
+
+        if index == 0 {
+            self.push_front(value);
+        } else if index == self.len {
+            self.push_back(value);
+        } else {
+            let next = self.node_at(index);
+            let previous = self.storage.link(&next, Direction::Left).unwrap();
+            let node = self.storage.alloc(value);
+            self.link(&previous, &node);
+            self.link(&node, &next);
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    /// Remove the element at a position, shifting the following elements towards the front
+    /// # Arguments
+    /// * `index`: The position of the element to remove
+    /// # Returns
+    /// The removed element, or None if `index` is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let mut list = LinkedList::from(vec![1, 2, 3]);
+    ///
+    /// assert_eq!(list.remove_at(1), Some(2));
+    /// assert_eq!(list.remove_at(2), None);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 3]);
+    /// ```
+    pub fn remove_at(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let node = self.node_at(index);
+        Some(self.unlink(node))
+    }
+
+    /// Remove and drop all the elements
+    /// The nodes are freed one by one, so clearing a long list doesn't recurse through the vertexes.
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let mut list = LinkedList::from(vec![1, 2, 3]);
+    /// list.clear();
+    ///
+    /// assert!(list.is_empty());
+    /// assert_eq!(list.pop_front(), None);
+    /// ```
+    pub fn clear(&mut self) {
+        // Free the nodes one by one, the neighbors pointing to each other would keep them alive
+        self.tail.take();
+        self.len = 0;
+        let mut current = self.head.take();
+        while let Some(node) = current {
+            current = self.storage.link(&node, Direction::Right);
+            self.storage.free(node);
+        }
+    }
+
+    /// Iterate over clones of the elements, from the front to the back
+    /// # Returns
+    /// A double ended iterator over the elements, `rev` walks from the back
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let list = LinkedList::from(vec![1, 2, 3]);
+    ///
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// assert_eq!(list.iter().rev().collect::<Vec<_>>(), vec![3, 2, 1]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T, S> {
+        Iter {
+            list: self,
+            front: self.head.clone(),
+            back: self.tail.clone(),
+            remaining: self.len,
+        }
+    }
+
+    // Link two nodes, `left` coming before `right`
+    fn link(&mut self, left: &S::Handle, right: &S::Handle) {
+        self.storage.set_link(left, Direction::Right, Some(right));
+        self.storage.set_link(right, Direction::Left, Some(left));
+    }
+
+    // Get the node at a position, walking from the nearest end. The position must be in bounds.
+    fn node_at(&self, index: usize) -> S::Handle {
+        if index < self.len / 2 {
+            let mut node = self.head.clone().unwrap();
+            for _ in 0..index {
+                node = self.storage.link(&node, Direction::Right).unwrap();
+            }
+            node
+        } else {
+            let mut node = self.tail.clone().unwrap();
+            for _ in index..self.len - 1 {
+                node = self.storage.link(&node, Direction::Left).unwrap();
+            }
+            node
+        }
+    }
+
+    // Remove a node of the list, linking its neighbors together, and return its element
+    fn unlink(&mut self, node: S::Handle) -> T {
+        let previous = self.storage.link(&node, Direction::Left);
+        let next = self.storage.link(&node, Direction::Right);
+
+        match &previous {
+            Some(previous) => self
+                .storage
+                .set_link(previous, Direction::Right, next.as_ref()),
+            None => self.head = next.clone(),
+        }
+        match &next {
+            Some(next) => self
+                .storage
+                .set_link(next, Direction::Left, previous.as_ref()),
+            None => self.tail = previous,
+        }
+
+        self.len -= 1;
+        // Freeing the node drops its links, breaking the cycles it was part of
+        self.storage.free(node)
+    }
+
+    // Compare the elements of two lists pairwise from the front, stopping at the first pair that isn't equal
+    fn partial_cmp_elements<O: NodeStorage<T>>(
+        &self,
+        other: &LinkedList<T, O>,
+        mut compare: impl FnMut(&T, &T) -> Option<Ordering>,
+    ) -> Option<Ordering> {
+        let mut current = self.head.clone();
+        let mut other_current = other.head.clone();
+
+        loop {
+            match (current, other_current) {
+                (None, None) => return Some(Ordering::Equal),
+                (None, Some(_)) => return Some(Ordering::Less),
+                (Some(_), None) => return Some(Ordering::Greater),
+                (Some(node), Some(other_node)) => {
+                    let ordering = self.storage.read(&node, |a| {
+                        other.storage.read(&other_node, |b| compare(a, b))
+                    });
+                    if ordering != Some(Ordering::Equal) {
+                        return ordering;
+                    }
+                    current = self.storage.link(&node, Direction::Right);
+                    other_current = other.storage.link(&other_node, Direction::Right);
+                }
+            }
+        }
+    }
+}
+
+impl<T, S: NodeStorage<T> + Default> Default for LinkedList<T, S> {
+    fn default() -> Self {
+        Self::with_storage(S::default())
+    }
+}
+
+impl<T, S: NodeStorage<T>> Drop for LinkedList<T, S> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> From<Vec<T>> for LinkedList<T> {
+    fn from(elements: Vec<T>) -> Self {
+        elements.into_iter().collect()
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(elements: I) -> Self {
+        let mut list = LinkedList::new();
+        list.extend(elements);
+        list
+    }
+}
+
+impl<T, S: NodeStorage<T>> Extend<T> for LinkedList<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, elements: I) {
+        for element in elements {
+            self.push_back(element);
+        }
+    }
+}
+
+impl<T: Clone, S: NodeStorage<T>> Iterator for Iter<'_, T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let node = self.front.take()?;
+        self.front = self.list.storage.link(&node, Direction::Right);
+        Some(self.list.storage.read(&node, T::clone))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: Clone, S: NodeStorage<T>> DoubleEndedIterator for Iter<'_, T, S> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let node = self.back.take()?;
+        self.back = self.list.storage.link(&node, Direction::Left);
+        Some(self.list.storage.read(&node, T::clone))
+    }
+}
+
+impl<T: Clone, S: NodeStorage<T>> ExactSizeIterator for Iter<'_, T, S> {}
+
+impl<'a, T: Clone, S: NodeStorage<T>> IntoIterator for &'a LinkedList<T, S> {
+    type Item = T;
+    type IntoIter = Iter<'a, T, S>;
+
+    fn into_iter(self) -> Iter<'a, T, S> {
+        self.iter()
+    }
+}
+
+impl<T, S: NodeStorage<T>> Iterator for IntoIter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len(), Some(self.list.len()))
+    }
+}
+
+impl<T, S: NodeStorage<T>> DoubleEndedIterator for IntoIter<T, S> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+}
+
+impl<T, S: NodeStorage<T>> ExactSizeIterator for IntoIter<T, S> {}
+
+impl<T, S: NodeStorage<T>> IntoIterator for LinkedList<T, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T, S>;
+
+    fn into_iter(self) -> IntoIter<T, S> {
+        IntoIter { list: self }
+    }
+}
+
+impl<T: PartialEq, S: NodeStorage<T>, O: NodeStorage<T>> PartialEq<LinkedList<T, O>>
+    for LinkedList<T, S>
+{
+    /// Two lists are equal if they hold equal elements in the same order, whatever their storage backends
+    fn eq(&self, other: &LinkedList<T, O>) -> bool {
+        self.len == other.len
+            && self.partial_cmp_elements(other, |a, b| (a == b).then_some(Ordering::Equal))
+                == Some(Ordering::Equal)
+    }
+}
+
+impl<T: Eq, S: NodeStorage<T>> Eq for LinkedList<T, S> {}
+
+impl<T: PartialOrd, S: NodeStorage<T>, O: NodeStorage<T>> PartialOrd<LinkedList<T, O>>
+    for LinkedList<T, S>
+{
+    /// Lexicographic comparison of the elements, from the front to the back, like `std::collections::LinkedList`
+    fn partial_cmp(&self, other: &LinkedList<T, O>) -> Option<Ordering> {
+        self.partial_cmp_elements(other, T::partial_cmp)
+    }
+}
+
+impl<T: Ord, S: NodeStorage<T>> Ord for LinkedList<T, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).unwrap()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Persist, S: NodeStorage<T> + Default> Persist for LinkedList<T, S> {
+    /// The length followed by the elements, from the front to the back
+    fn write_to(&self, out: &mut Vec<u8>) {
+        write_varint(out, self.len as u64);
+        let mut current = self.head.clone();
+        while let Some(node) = current {
+            self.storage.read(&node, |element| element.write_to(out));
+            current = self.storage.link(&node, Direction::Right);
+        }
+    }
+
+    fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+        let length = read_length(input)?;
+        let mut list = LinkedList::default();
+        for _ in 0..length {
+            list.push_back(T::read_from(input)?);
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, S: NodeStorage<T>> serde::Serialize for LinkedList<T, S> {
+    /// A sequence of the elements, from the front to the back
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        let mut current = self.head.clone();
+        while let Some(node) = current {
+            self.storage
+                .read(&node, |element| seq.serialize_element(element))?;
+            current = self.storage.link(&node, Direction::Right);
+        }
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, S: NodeStorage<T> + Default> serde::Deserialize<'de>
+    for LinkedList<T, S>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut list = LinkedList::default();
+        for value in Vec::<T>::deserialize(deserializer)? {
+            list.push_back(value);
+        }
+        Ok(list)
+    }
+}
+
+impl<T: fmt::Debug, S: NodeStorage<T>> fmt::Debug for LinkedList<T, S> {
+    /// Prints the elements from the front to the back, like a `Vec`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        let mut current = self.head.clone();
+        while let Some(node) = current {
+            self.storage.read(&node, |element| {
+                list.entry(element);
+            });
+            current = self.storage.link(&node, Direction::Right);
+        }
+        list.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linked_list::shared::Ptr;
+    use crate::test_util::Lcg;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_list_against_vec_deque() {
+        let mut list = LinkedList::new();
+        let mut model = VecDeque::new();
+        let mut rng = Lcg::new(11);
+
+        for step in 0..3_000usize {
+            let seed = rng.next_u64();
+            let index = (seed >> 40) as usize % (model.len() + 2);
+            match (seed >> 33) % 7 {
+                0 => {
+                    list.push_front(step);
+                    model.push_front(step);
+                }
+                1 => {
+                    list.push_back(step);
+                    model.push_back(step);
+                }
+                2 => assert_eq!(list.pop_front(), model.pop_front()),
+                3 => assert_eq!(list.pop_back(), model.pop_back()),
+                4 => {
+                    let expected = if index <= model.len() {
+                        model.insert(index, step);
+                        Ok(())
+                    } else {
+                        Err(DataStructureError::IndexOutOfBounds {
+                            index,
+                            len: model.len(),
+                        })
+                    };
+                    assert_eq!(list.insert_at(index, step), expected);
+                }
+                5 => assert_eq!(list.remove_at(index), model.remove(index)),
+                _ => {
+                    let count = index % 4;
+                    let mut other: LinkedList<_> = (0..count).map(|i| step + i).collect();
+                    model.extend((0..count).map(|i| step + i));
+                    list.append(&mut other);
+                    assert!(other.is_empty());
+                }
+            }
+
+            assert_eq!(list.len(), model.len());
+            assert_eq!(list.iter().collect::<VecDeque<_>>(), model);
+            assert!(list.iter().rev().eq(model.iter().rev().copied()));
+        }
+
+        assert_eq!(format!("{:?}", list), format!("{:?}", model));
+        assert!(list.into_iter().eq(model));
+    }
+
+    #[test]
+    fn test_list_ordering() {
+        use crate::storage::node_storage::SlabStorage;
+
+        let list = LinkedList::from(vec![1, 2, 3]);
+        assert!(list < LinkedList::from(vec![1, 3]));
+        assert!(list > LinkedList::from(vec![1, 2]));
+        assert!(LinkedList::new() < list);
+        assert_eq!(
+            list.cmp(&LinkedList::from(vec![1, 2, 3])),
+            std::cmp::Ordering::Equal
+        );
+
+        // Lists on different storage backends compare by their elements
+        let mut slab = LinkedList::with_storage(SlabStorage::new());
+        slab.extend([1, 2, 3]);
+        assert!(list == slab);
+        slab.push_back(0);
+        assert!(list < slab);
+        assert!(list != slab);
+
+        let mut sorted = [
+            LinkedList::from(vec![2]),
+            LinkedList::from(vec![1, 9]),
+            LinkedList::new(),
+        ];
+        sorted.sort();
+        assert!(sorted
+            .iter()
+            .map(|list| list.iter().collect::<Vec<_>>())
+            .eq([vec![], vec![1, 9], vec![2]]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_snapshot_round_trip() {
+        use crate::persist::format::{from_bytes, to_bytes};
+
+        let list: LinkedList<(u32, String)> = (0..50).map(|i| (i, i.to_string())).collect();
+        let loaded: LinkedList<(u32, String)> = from_bytes(&to_bytes(&list)).unwrap();
+        assert!(loaded.iter().eq(list.iter()));
+        assert!(loaded.iter().rev().eq(list.iter().rev()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        use alloc::string::{String, ToString};
+
+        let list: LinkedList<(u32, String)> = (0..50).map(|i| (i, i.to_string())).collect();
+
+        // A list is serialized like a vector of its elements
+        let bytes = postcard::to_allocvec(&list).unwrap();
+        assert_eq!(
+            bytes,
+            postcard::to_allocvec(&list.iter().collect::<Vec<_>>()).unwrap()
+        );
+
+        let loaded: LinkedList<(u32, String)> = postcard::from_bytes(&bytes).unwrap();
+        assert!(loaded.iter().eq(list.iter()));
+    }
+
+    #[test]
+    fn test_index_storages_match_rc_storage() {
+        use crate::storage::node_storage::{ArenaStorage, SlabStorage};
+
+        let mut rc = LinkedList::new();
+        let mut slab = LinkedList::with_storage(SlabStorage::new());
+        let mut arena = LinkedList::with_storage(ArenaStorage::new());
+        let mut rng = Lcg::new(23);
+
+        for step in 0..2_000u32 {
+            let seed = rng.next_u64();
+            let index = (seed >> 40) as usize % (rc.len() + 1);
+            match (seed >> 33) % 5 {
+                0 => {
+                    rc.push_front(step);
+                    slab.push_front(step);
+                    arena.push_front(step);
+                }
+                1 => {
+                    rc.insert_at(index, step).unwrap();
+                    slab.insert_at(index, step).unwrap();
+                    arena.insert_at(index, step).unwrap();
+                }
+                2 => {
+                    let expected = rc.remove_at(index);
+                    assert_eq!(slab.remove_at(index), expected);
+                    assert_eq!(arena.remove_at(index), expected);
+                }
+                3 => {
+                    let expected = rc.pop_back();
+                    assert_eq!(slab.pop_back(), expected);
+                    assert_eq!(arena.pop_back(), expected);
+                }
+                _ => {
+                    rc.extend([step, step + 1]);
+                    slab.extend([step, step + 1]);
+                    arena.extend([step, step + 1]);
+                }
+            }
+
+            // Removed nodes are freed from the backends
+            assert_eq!(slab.storage().len(), rc.len());
+            assert_eq!(arena.storage().len(), rc.len());
+        }
+
+        assert!(slab.iter().eq(rc.iter()));
+        assert!(arena.iter().rev().eq(rc.iter().rev()));
+        assert_eq!(format!("{:?}", arena), format!("{:?}", rc));
+    }
+
+    #[test]
+    fn test_no_vertex_leaks() {
+        // Elements counting their drops: a leaked vertex never drops its element
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        struct Counted;
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let mut list: LinkedList<Counted> = (0..100).map(|_| Counted).collect();
+        let mut other: LinkedList<Counted> = (0..50).map(|_| Counted).collect();
+        list.append(&mut other);
+        drop(list.remove_at(75));
+        drop(list.pop_front());
+        drop(list.pop_back());
+        list.insert_at(10, Counted).unwrap();
+        assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+
+        // Only the list and the neighbors hold the vertexes
+        let head = list.head.clone().unwrap();
+        assert_eq!(Ptr::strong_count(&head), 3);
+        drop(head);
+
+        // Clearing frees every vertex, and the list stays usable
+        let front = Ptr::downgrade(list.head.as_ref().unwrap());
+        let back = Ptr::downgrade(list.tail.as_ref().unwrap());
+        list.clear();
+        assert!(front.upgrade().is_none() && back.upgrade().is_none());
+        assert_eq!(DROPS.load(Ordering::SeqCst), 151);
+
+        list.push_back(Counted);
+        let front = Ptr::downgrade(list.head.as_ref().unwrap());
+        drop(list);
+        drop(other);
+        assert!(front.upgrade().is_none());
+        assert_eq!(DROPS.load(Ordering::SeqCst), 152);
+
+        // Dropping a long list doesn't recurse through the vertexes
+        let shared = Rc::new(());
+        let long: LinkedList<_> = (0..200_000).map(|_| shared.clone()).collect();
+        drop(long);
+        assert_eq!(Rc::strong_count(&shared), 1);
+    }
+}
@@ -0,0 +1,79 @@
+//! This module defines the observers of a `CircularQueue`, told about the mutations of the queue as they happen.
+//! An observer is called when an element is inserted, when an element is removed and when an element arrives while the
+//! queue is full, with the length of the queue after the event. Metrics, logging and back-pressure can hook into a
+//! queue, or a `FIFO` built on it, without wrapping every call site.
+//!
+//! # Performance
+//! - O(1) per event on top of the observer itself, and nothing for a queue without observer
+//!
+//! # Implementation Details
+//! - An overflow is reported before the overflow policy runs, so it is reported whether the element is then rejected or
+//!   makes room by evicting another one. The evicted element is reported as a removal, then the new one as an insertion.
+//! - `clear` reports every removed element. Dropping a queue reports nothing.
+//! - `append` and `split_off` move whole rings in O(1) and report nothing.
+//! - Any closure taking a `QueueEvent` and the length is an observer, like the closures of the overflow policies.
+//!   With the `arc` feature, observers must be `Send + Sync` so the queue holding them is too.
+//!
+//! # Usage
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//! use data_structures::linked_list::fifo::FIFO;
+//! use data_structures::linked_list::observer::QueueEvent;
+//!
+//! let high_water_mark = Arc::new(AtomicUsize::new(0));
+//! let mark = high_water_mark.clone();
+//!
+//! let mut jobs = FIFO::new(10);
+//! jobs.set_observer(move |event: QueueEvent, len: usize| {
+//!     if event == QueueEvent::Insert {
+//!         mark.fetch_max(len, Ordering::Relaxed);
+//!     }
+//! });
+//!
+//! jobs.push("build").unwrap();
+//! jobs.push("test").unwrap();
+//! jobs.pop();
+//! jobs.push("deploy").unwrap();
+//! assert_eq!(high_water_mark.load(Ordering::Relaxed), 2);
+//! ```
+//!
+use super::shared::ThreadSafe;
+
+/// Kind of mutation reported to a `QueueObserver`
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueueEvent {
+    Insert,
+    Remove,
+    Overflow,
+}
+
+/// Trait of the callbacks told about the mutations of a queue
+///
+/// Every method does nothing by default, so an observer only implements the events it needs.
+///
+pub trait QueueObserver<T>: ThreadSafe {
+    /// Called after an element is inserted, with the new length
+    fn on_insert(&mut self, _element: &T, _len: usize) {}
+
+    /// Called after an element is removed, with the new length
+    fn on_remove(&mut self, _element: &T, _len: usize) {}
+
+    /// Called when an element is inserted in a full queue, with its length, before the overflow policy runs
+    fn on_overflow(&mut self, _element: &T, _len: usize) {}
+}
+
+impl<T, F: FnMut(QueueEvent, usize) + ThreadSafe> QueueObserver<T> for F {
+    fn on_insert(&mut self, _element: &T, len: usize) {
+        self(QueueEvent::Insert, len)
+    }
+
+    fn on_remove(&mut self, _element: &T, len: usize) {
+        self(QueueEvent::Remove, len)
+    }
+
+    fn on_overflow(&mut self, _element: &T, len: usize) {
+        self(QueueEvent::Overflow, len)
+    }
+}
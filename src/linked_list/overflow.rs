@@ -0,0 +1,171 @@
+//! This module defines what a full `CircularQueue` does when a new element is inserted.
+//! The behavior is an `OverflowPolicy`: it looks at the elements of the queue and the incoming one and either rejects the
+//! insertion or picks an element to evict in its place. Common policies are provided, and any closure with the right
+//! signature is a policy too, so specialized buffers don't need their own wrapper type.
+//!
+//! # Performance
+//! - O(1) for `Reject` and `EvictOldest`
+//! - O(n) for `EvictNewest` and `EvictLowestBy`, which walk the ring
+//!
+//! # Implementation Details
+//! - Policies see the queue through the `QueueView` trait. Positions are counted from the cursor to the right, which is
+//!   from the oldest to the newest element when elements are inserted on the left, as `FIFO` does.
+//! - Reading elements goes through callbacks, since the nodes may be stored behind `RefCell`s.
+//!
+//! # Usage
+//! ```
+//! use data_structures::error::DataStructureError;
+//! use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+//! use data_structures::linked_list::overflow::{EvictLowestBy, EvictOldest};
+//!
+//! let mut queue = CircularQueue::new(2);
+//! queue.set_overflow_policy(EvictOldest);
+//!
+//! queue.insert(1, Direction::Left).unwrap();
+//! queue.insert(2, Direction::Left).unwrap();
+//! assert_eq!(queue.insert_or_evict(3, Direction::Left), Ok(Some(1)));
+//!
+//! // Keep the largest elements seen so far
+//! queue.set_overflow_policy(EvictLowestBy(|a: &i32, b: &i32| a.cmp(b)));
+//! assert_eq!(queue.insert_or_evict(0, Direction::Left), Err(DataStructureError::Full));
+//! assert_eq!(queue.insert_or_evict(7, Direction::Left), Ok(Some(2)));
+//! ```
+//!
+use core::cmp::Ordering;
+
+use super::shared::ThreadSafe;
+
+/// Read-only view of the elements of a queue, given to the overflow policies
+///
+pub trait QueueView<T> {
+    /// Number of elements in the queue
+    fn len(&self) -> usize;
+
+    /// Check if the queue is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Call `f` with the position and a reference to each element, from the cursor to the right
+    fn for_each(&self, f: &mut dyn FnMut(usize, &T));
+
+    /// Position of the lowest element according to `compare`, the first one on ties, or None if the queue is empty
+    fn position_min_by(&self, compare: &mut dyn FnMut(&T, &T) -> Ordering) -> Option<usize>;
+}
+
+/// Trait of the behaviors of a full queue
+///
+/// With the `arc` feature, policies must be `Send + Sync` so the queue holding them is too.
+///
+pub trait OverflowPolicy<T>: ThreadSafe {
+    /// Choose the position of the element evicted to make room for `incoming`, or None to reject the insertion
+    fn choose(&mut self, queue: &dyn QueueView<T>, incoming: &T) -> Option<usize>;
+}
+
+/// Reject the insertion, which fails with DataStructureError::Full. This is the default policy.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Reject;
+
+/// Evict the element at the cursor, which is the oldest one in FIFO usage
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EvictOldest;
+
+/// Evict the element on the left of the cursor, which is the newest one in FIFO usage
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EvictNewest;
+
+/// Evict the lowest element according to a comparison, or reject the incoming one if it is lower than all of them
+#[derive(Debug, Clone, Copy)]
+pub struct EvictLowestBy<F>(pub F);
+
+impl<T> OverflowPolicy<T> for Reject {
+    fn choose(&mut self, _queue: &dyn QueueView<T>, _incoming: &T) -> Option<usize> {
+        None
+    }
+}
+
+impl<T> OverflowPolicy<T> for EvictOldest {
+    fn choose(&mut self, queue: &dyn QueueView<T>, _incoming: &T) -> Option<usize> {
+        (!queue.is_empty()).then_some(0)
+    }
+}
+
+impl<T> OverflowPolicy<T> for EvictNewest {
+    fn choose(&mut self, queue: &dyn QueueView<T>, _incoming: &T) -> Option<usize> {
+        queue.len().checked_sub(1)
+    }
+}
+
+impl<T, F: FnMut(&T, &T) -> Ordering + ThreadSafe> OverflowPolicy<T> for EvictLowestBy<F> {
+    fn choose(&mut self, queue: &dyn QueueView<T>, incoming: &T) -> Option<usize> {
+        let position = queue.position_min_by(&mut self.0)?;
+
+        let mut incoming_is_lower = false;
+        queue.for_each(&mut |current, element| {
+            if current == position {
+                incoming_is_lower = (self.0)(incoming, element) == Ordering::Less;
+            }
+        });
+        (!incoming_is_lower).then_some(position)
+    }
+}
+
+impl<T, F: FnMut(&dyn QueueView<T>, &T) -> Option<usize> + ThreadSafe> OverflowPolicy<T> for F {
+    fn choose(&mut self, queue: &dyn QueueView<T>, incoming: &T) -> Option<usize> {
+        self(queue, incoming)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct SliceView<'a>(&'a [i32]);
+
+    impl QueueView<i32> for SliceView<'_> {
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn for_each(&self, f: &mut dyn FnMut(usize, &i32)) {
+            self.0.iter().enumerate().for_each(|(i, e)| f(i, e));
+        }
+
+        fn position_min_by(
+            &self,
+            compare: &mut dyn FnMut(&i32, &i32) -> Ordering,
+        ) -> Option<usize> {
+            (0..self.0.len()).reduce(|min, i| {
+                if compare(&self.0[i], &self.0[min]) == Ordering::Less {
+                    i
+                } else {
+                    min
+                }
+            })
+        }
+    }
+
+    #[test]
+    fn test_policies() {
+        let view = SliceView(&[5, 3, 8, 3]);
+
+        assert_eq!(Reject.choose(&view, &1), None);
+        assert_eq!(EvictOldest.choose(&view, &1), Some(0));
+        assert_eq!(EvictNewest.choose(&view, &1), Some(3));
+
+        let mut lowest = EvictLowestBy(|a: &i32, b: &i32| a.cmp(b));
+        assert_eq!(lowest.choose(&view, &4), Some(1));
+        assert_eq!(lowest.choose(&view, &2), None);
+
+        let mut even_first = |queue: &dyn QueueView<i32>, _: &i32| {
+            let mut found = None;
+            queue.for_each(&mut |i, e| {
+                if found.is_none() && e % 2 == 0 {
+                    found = Some(i);
+                }
+            });
+            found
+        };
+        assert_eq!(even_first.choose(&view, &0), Some(2));
+    }
+}
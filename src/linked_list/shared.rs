@@ -0,0 +1,171 @@
+//! This module defines the shared pointer layer used by the vertexes and every structure built on them.
+//! By default vertexes are shared through `Rc<RefCell<...>>`, which is cheap but confines a structure to the thread that
+//! created it. With the `arc` feature the same aliases resolve to `Arc` and a `RwLock` based cell, so the structures
+//! become `Send` and `Sync` when their elements are, and can be moved to or shared with other threads.
+//!
+//! # Performance
+//! - Without `arc`, borrowing a vertex is a counter update and cloning a pointer is a non-atomic increment.
+//! - With `arc`, borrowing takes a read or write lock and cloning a pointer is an atomic increment.
+//!
+//! # Implementation Details
+//! - `Ptr` and `WeakPtr` are `Rc`/`rc::Weak` or `Arc`/`sync::Weak`. The `Rc` side only needs `alloc`, while `arc`
+//!   needs `std` for its `RwLock`.
+//! - `Lock` is `RefCell`, or a wrapper over `RwLock` exposing the same `borrow` and `borrow_mut` methods. A poisoned
+//!   lock panics, as a conflicting `RefCell` borrow would.
+//! - `ThreadSafe` is implemented by every type without `arc`, and by the `Send + Sync` types with it. Traits whose
+//!   objects are stored inside the structures, such as `OverflowPolicy`, use it as a supertrait.
+//!
+//! # Usage
+//! ```
+//! use data_structures::linked_list::shared::{Ptr, Shared};
+//! use data_structures::linked_list::vertex::Vertex;
+//!
+//! let vertex: Shared<Vertex<u32>> = Vertex::new(1);
+//! let other = vertex.clone();
+//!
+//! assert!(Ptr::ptr_eq(&vertex, &other));
+//! assert_eq!(other.borrow().read_data(), &Some(1));
+//! ```
+//!
+#[cfg(not(feature = "arc"))]
+pub use alloc::rc::{Rc as Ptr, Weak as WeakPtr};
+
+#[cfg(not(feature = "arc"))]
+pub use core::cell::RefCell as Lock;
+
+#[cfg(feature = "arc")]
+pub use std::sync::{Arc as Ptr, Weak as WeakPtr};
+
+/// A value shared between the owners of the pointer, with interior mutability
+pub type Shared<T> = Ptr<Lock<T>>;
+
+/// A non-owning reference to a shared value
+pub type WeakShared<T> = WeakPtr<Lock<T>>;
+
+/// Marker for the types that can be stored in the structures: all of them by default, `Send + Sync` ones with `arc`
+#[cfg(not(feature = "arc"))]
+pub trait ThreadSafe {}
+
+#[cfg(not(feature = "arc"))]
+impl<T: ?Sized> ThreadSafe for T {}
+
+/// Marker for the types that can be stored in the structures: all of them by default, `Send + Sync` ones with `arc`
+#[cfg(feature = "arc")]
+pub trait ThreadSafe: Send + Sync {}
+
+#[cfg(feature = "arc")]
+impl<T: Send + Sync + ?Sized> ThreadSafe for T {}
+
+/// Struct representing a `RwLock` with the borrowing interface of `RefCell`
+///
+#[cfg(feature = "arc")]
+#[derive(Debug, Default)]
+pub struct Lock<T>(std::sync::RwLock<T>);
+
+#[cfg(feature = "arc")]
+impl<T> Lock<T> {
+    /// Create a new Lock holding `value`
+    ///
+    /// # Arguments
+    /// * `value`: The value to be protected
+    ///
+    /// # Returns
+    /// A new Lock instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::shared::Lock;
+    ///
+    /// let lock = Lock::new(1);
+    /// assert_eq!(*lock.borrow(), 1);
+    /// ```
+    pub fn new(value: T) -> Self {
+        Lock(std::sync::RwLock::new(value))
+    }
+
+    /// Take a shared borrow of the value, blocking while it is mutably borrowed on another thread
+    ///
+    /// # Returns
+    /// A guard dereferencing to the value
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::shared::Lock;
+    ///
+    /// let lock = Lock::new(vec![1]);
+    /// assert_eq!(lock.borrow().len(), 1);
+    /// ```
+    pub fn borrow(&self) -> std::sync::RwLockReadGuard<'_, T> {
+        self.0.read().expect("Lock poisoned")
+    }
+
+    /// Take an exclusive borrow of the value, blocking while it is borrowed on another thread
+    ///
+    /// # Returns
+    /// A guard dereferencing mutably to the value
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::shared::Lock;
+    ///
+    /// let lock = Lock::new(vec![1]);
+    /// lock.borrow_mut().push(2);
+    /// assert_eq!(*lock.borrow(), vec![1, 2]);
+    /// ```
+    pub fn borrow_mut(&self) -> std::sync::RwLockWriteGuard<'_, T> {
+        self.0.write().expect("Lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_shared_borrow() {
+        let shared: Shared<Vec<u32>> = Ptr::new(Lock::new(vec![1]));
+        let weak = Ptr::downgrade(&shared);
+
+        weak.upgrade().unwrap().borrow_mut().push(2);
+        assert_eq!(*shared.borrow(), vec![1, 2]);
+
+        drop(shared);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[cfg(feature = "arc")]
+    #[test]
+    fn test_structures_are_send_and_sync() {
+        use crate::linked_list::{circular_queue::CircularQueue, fifo::FIFO};
+        use crate::text::aho_corasick::AhoCorasick;
+        use crate::tree::{binary_tree::BinaryTree, nary_tree::NaryTree};
+
+        fn assert_send_sync<X: Send + Sync>() {}
+        assert_send_sync::<FIFO<String>>();
+        assert_send_sync::<CircularQueue<String>>();
+        assert_send_sync::<BinaryTree<u32>>();
+        assert_send_sync::<NaryTree<u32>>();
+        assert_send_sync::<AhoCorasick>();
+
+        // Build on one thread, consume on another
+        let fifo = FIFO::from(vec![1, 2, 3]);
+        let handle = std::thread::spawn(move || {
+            let mut fifo = fifo;
+            std::iter::from_fn(|| fifo.pop()).collect::<Vec<_>>()
+        });
+        assert_eq!(handle.join().unwrap(), vec![1, 2, 3]);
+
+        // Read from several threads at once
+        let tree = std::sync::Arc::new(BinaryTree::from_sorted_slice(&[1, 2, 3, 4, 5]));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let tree = tree.clone();
+                std::thread::spawn(move || tree.iter_inorder().sum::<i32>())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 15);
+        }
+    }
+}
@@ -0,0 +1,462 @@
+//! This module implements a skip list: a sorted set stored as a stack of linked lists of vertexes. Every element is on
+//! the bottom list, and each list above skips about half of the elements of the list below, so a search can run along
+//! the top list and drop a level each time it would overshoot, finding an element in O(log n) steps like a balanced
+//! tree, while iterating in order is a plain walk along the bottom list.
+//!
+//! # Performance
+//! - O(log n) expected for `insert`, `contains` and `remove`
+//! - O(log n + k) expected to iterate over k elements of a range
+//!
+//! # Implementation Details
+//! - Nodes are `Vertex` instances handled through `Shared<Vertex<T>>` pointers. The pointer to the next node on level
+//!   l is the `Custom("level{l}")` pointer, so a node has as many pointers as levels in its tower.
+//! - A new node reaches each level above the first with probability 1/2, up to the maximum level of the list.
+//! - The list only links forward, so there are no cycles, and it unlinks its nodes iteratively when dropped.
+//! - `lsm::skip_list` is the index-based map variant used by the LSM memtable.
+//!
+//! # Usage
+//! ```
+//! use data_structures::linked_list::skip_list::SkipList;
+//!
+//! let mut list = SkipList::new(12);
+//! for value in [40, 10, 30, 20] {
+//!     list.insert(value);
+//! }
+//!
+//! assert!(list.contains(&30));
+//! assert_eq!(list.remove(&30), Some(30));
+//! assert_eq!(list.range(15..).collect::<Vec<_>>(), vec![20, 40]);
+//! ```
+//!
+use alloc::{format, vec, vec::Vec};
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Bound, RangeBounds};
+
+use crate::linked_list::shared::{Ptr, Shared};
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+/// The maximum level of a list created with `default`
+pub const DEFAULT_MAX_LEVEL: usize = 16;
+
+type Link<T> = Shared<Vertex<T>>;
+
+// Name of the pointer to the next node on a level
+fn level_pointer(level: usize) -> PointerName {
+    PointerName::Custom(format!("level{}", level))
+}
+
+fn compare<T: Ord>(value: &T, node: &Link<T>) -> Ordering {
+    value.cmp(node.borrow().read_data().as_ref().unwrap())
+}
+
+/// Struct representing a sorted set stored as a skip list
+///
+pub struct SkipList<T> {
+    head: Vec<Option<Link<T>>>, // first node of each level
+    size: usize,
+    seed: u64, // state of the level generator
+}
+
+/// Iterator over the elements of a skip list in increasing order
+pub struct Iter<T> {
+    next: Option<Link<T>>,
+}
+
+/// Iterator over the elements of a skip list in a range, in increasing order
+pub struct Range<T> {
+    iter: Iter<T>,
+    end: Bound<T>,
+}
+
+impl<T: Clone> Iterator for Iter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.take()?;
+        let vertex = node.borrow();
+        self.next = vertex.get_pointer(level_pointer(0));
+        vertex.read_data().clone()
+    }
+}
+
+impl<T: Ord + Clone> Iterator for Range<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.iter.next()?;
+        let in_range = match &self.end {
+            Bound::Included(end) => value <= *end,
+            Bound::Excluded(end) => value < *end,
+            Bound::Unbounded => true,
+        };
+        if !in_range {
+            // Every following element is past the end too
+            self.iter.next = None;
+            return None;
+        }
+        Some(value)
+    }
+}
+
+impl<T: Ord> SkipList<T> {
+    /// Create a new empty skip list
+    /// # Arguments
+    /// * `max_level`: The maximum number of levels of a node, at least 1. About 2^max_level elements can be searched in
+    ///   O(log n), larger lists slow down gradually.
+    /// # Returns
+    /// A new SkipList instance
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::skip_list::SkipList;
+    ///
+    /// let list: SkipList<u32> = SkipList::new(20);
+    /// assert_eq!(list.max_level(), 20);
+    /// ```
+    pub fn new(max_level: usize) -> Self {
+        SkipList {
+            head: vec![None; max_level.max(1)],
+            size: 0,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    /// Get the maximum number of levels of a node
+    /// # Returns
+    /// The maximum level of the list
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::skip_list::SkipList;
+    ///
+    /// let list: SkipList<u32> = SkipList::new(0);
+    /// assert_eq!(list.max_level(), 1);
+    /// ```
+    pub fn max_level(&self) -> usize {
+        self.head.len()
+    }
+
+    /// Get the number of elements
+    /// # Returns
+    /// The number of elements in the list
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::skip_list::SkipList;
+    ///
+    /// let list: SkipList<char> = "hello".chars().collect();
+    /// assert_eq!(list.len(), 4);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the list is empty
+    /// # Returns
+    /// True if the list has no elements
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::skip_list::SkipList;
+    ///
+    /// let list: SkipList<u8> = SkipList::default();
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Insert an element in order
+    /// # Arguments
+    /// * `value`: The element to be inserted
+    /// # Returns
+    /// True if the element was inserted, false if an equal element was already in the list
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::skip_list::SkipList;
+    ///
+    /// let mut list = SkipList::default();
+    /// assert!(list.insert("b"));
+    /// assert!(list.insert("a"));
+    /// assert!(!list.insert("b"));
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec!["a", "b"]);
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        let predecessors = self.predecessors(&value);
+        if let Some(next) = self.next_of(predecessors[0].as_ref(), 0) {
+            if compare(&value, &next) == Ordering::Equal {
+                return false;
+            }
+        }
+
+        let node = Vertex::new(value);
+        let height = self.random_height();
+        for (level, predecessor) in predecessors.iter().enumerate().take(height) {
+            let predecessor = predecessor.as_ref();
+            let next = self.next_of(predecessor, level);
+            node.borrow_mut()
+                .set_connection(level_pointer(level), next.as_ref());
+            self.set_next(predecessor, level, Some(&node));
+        }
+
+        self.size += 1;
+        true
+    }
+
+    /// Check if an element is in the list
+    /// # Arguments
+    /// * `value`: The element to look for
+    /// # Returns
+    /// True if an equal element is in the list
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::skip_list::SkipList;
+    ///
+    /// let list: SkipList<u32> = (0..100).step_by(10).collect();
+    /// assert!(list.contains(&40));
+    /// assert!(!list.contains(&45));
+    /// ```
+    pub fn contains(&self, value: &T) -> bool {
+        let predecessors = self.predecessors(value);
+        self.next_of(predecessors[0].as_ref(), 0)
+            .is_some_and(|node| compare(value, &node) == Ordering::Equal)
+    }
+
+    /// Remove an element from the list
+    /// # Arguments
+    /// * `value`: The element to remove
+    /// # Returns
+    /// The removed element, or None if no equal element was in the list
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::skip_list::SkipList;
+    ///
+    /// let mut list: SkipList<u32> = (1..=3).collect();
+    /// assert_eq!(list.remove(&2), Some(2));
+    /// assert_eq!(list.remove(&2), None);
+    /// assert_eq!(list.len(), 2);
+    /// ```
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let predecessors = self.predecessors(value);
+        let node = self
+            .next_of(predecessors[0].as_ref(), 0)
+            .filter(|node| compare(value, node) == Ordering::Equal)?;
+
+        // Unlink the node from every level of its tower, which are the levels where a predecessor points to it
+        for (level, predecessor) in predecessors.iter().enumerate() {
+            let predecessor = predecessor.as_ref();
+            match self.next_of(predecessor, level) {
+                Some(next) if Ptr::ptr_eq(&next, &node) => {
+                    let after = node.borrow().get_pointer(level_pointer(level));
+                    self.set_next(predecessor, level, after.as_ref());
+                }
+                _ => break,
+            }
+        }
+
+        self.size -= 1;
+        let value = node.borrow_mut().clear();
+        value
+    }
+
+    /// Get an iterator over the elements in increasing order
+    /// # Returns
+    /// A lazy iterator yielding clones of the elements
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::skip_list::SkipList;
+    ///
+    /// let list: SkipList<i32> = [3, -1, 2].into_iter().collect();
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![-1, 2, 3]);
+    /// ```
+    pub fn iter(&self) -> Iter<T> {
+        Iter {
+            next: self.head[0].clone(),
+        }
+    }
+
+    /// Get an iterator over the elements in a range, in increasing order
+    /// # Arguments
+    /// * `range`: The range of elements, such as `a..b`, `..=b` or `(Bound::Excluded(a), Bound::Unbounded)`
+    /// # Returns
+    /// A lazy iterator yielding clones of the elements
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::skip_list::SkipList;
+    ///
+    /// let list: SkipList<u32> = (0..50).collect();
+    /// assert_eq!(list.range(10..13).collect::<Vec<_>>(), vec![10, 11, 12]);
+    /// assert_eq!(list.range(48..).count(), 2);
+    /// ```
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<T>
+    where
+        T: Clone,
+    {
+        let next = match range.start_bound() {
+            Bound::Included(start) => self.next_of(self.predecessors(start)[0].as_ref(), 0),
+            Bound::Excluded(start) => {
+                let next = self.next_of(self.predecessors(start)[0].as_ref(), 0);
+                match next {
+                    Some(node) if compare(start, &node) == Ordering::Equal => {
+                        node.borrow().get_pointer(level_pointer(0))
+                    }
+                    next => next,
+                }
+            }
+            Bound::Unbounded => self.head[0].clone(),
+        };
+
+        Range {
+            iter: Iter { next },
+            end: range.end_bound().cloned(),
+        }
+    }
+
+    // Find the last node before the value on each level, None standing for the head
+    fn predecessors(&self, value: &T) -> Vec<Option<Link<T>>> {
+        let mut predecessors = vec![None; self.head.len()];
+        let mut current: Option<Link<T>> = None;
+
+        for level in (0..self.head.len()).rev() {
+            while let Some(next) = self.next_of(current.as_ref(), level) {
+                if compare(value, &next) != Ordering::Greater {
+                    break;
+                }
+                current = Some(next);
+            }
+            predecessors[level] = current.clone();
+        }
+
+        predecessors
+    }
+
+    // Follow the pointer of a node, or of the head, on a level
+    fn next_of(&self, node: Option<&Link<T>>, level: usize) -> Option<Link<T>> {
+        match node {
+            Some(node) => node.borrow().get_pointer(level_pointer(level)),
+            None => self.head[level].clone(),
+        }
+    }
+
+    // Set the pointer of a node, or of the head, on a level
+    fn set_next(&mut self, node: Option<&Link<T>>, level: usize, next: Option<&Link<T>>) {
+        match node {
+            Some(node) => {
+                node.borrow_mut().set_connection(level_pointer(level), next);
+            }
+            None => self.head[level] = next.cloned(),
+        }
+    }
+
+    // Draw the height of a new tower, halving the odds at each level
+    fn random_height(&mut self) -> usize {
+        self.seed = self
+            .seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let height = (self.seed >> 32).trailing_ones() as usize + 1;
+        height.min(self.head.len())
+    }
+}
+
+impl<T> Drop for SkipList<T> {
+    fn drop(&mut self) {
+        // Unlink the nodes one by one along the bottom level, dropping a long chain would recurse once per node
+        let mut current = self.head.first_mut().and_then(Option::take);
+        self.head.clear();
+        while let Some(node) = current {
+            current = node.borrow().get_pointer(level_pointer(0));
+            node.borrow_mut().clear();
+        }
+    }
+}
+
+impl<T: Ord> Default for SkipList<T> {
+    /// Creates an empty list with `DEFAULT_MAX_LEVEL` levels
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_LEVEL)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SkipList<T> {
+    /// Builds a list with `DEFAULT_MAX_LEVEL` levels, ignoring duplicates
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = SkipList::default();
+        for value in iter {
+            list.insert(value);
+        }
+        list
+    }
+}
+
+impl<T: Ord> Extend<T> for SkipList<T> {
+    /// Inserts every element, ignoring duplicates
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Clone + fmt::Debug> fmt::Debug for SkipList<T> {
+    /// Prints the elements as a set, in increasing order
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let iter = Iter {
+            next: self.head.first().cloned().flatten(),
+        };
+        f.debug_set().entries(iter).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_skip_list_against_btree_set() {
+        let mut list = SkipList::new(10);
+        let mut reference = BTreeSet::new();
+        let mut rng = Lcg::new(31);
+
+        for _ in 0..4000 {
+            let seed = rng.next_u64();
+            let value = (seed >> 33) % 500;
+
+            match seed >> 62 {
+                0 => assert_eq!(list.remove(&value), reference.take(&value)),
+                1 => assert_eq!(list.contains(&value), reference.contains(&value)),
+                _ => assert_eq!(list.insert(value), reference.insert(value)),
+            }
+            assert_eq!(list.len(), reference.len());
+        }
+
+        assert!(list.iter().eq(reference.iter().copied()));
+        assert!(list.range(100..250).eq(reference.range(100..250).copied()));
+        assert!(list.range(..=42).eq(reference.range(..=42).copied()));
+        let bounds = (Bound::Excluded(300), Bound::Included(480));
+        assert!(list.range(bounds).eq(reference.range(bounds).copied()));
+        let inverted = (Bound::Included(400), Bound::Excluded(100));
+        assert_eq!(list.range(inverted).count(), 0);
+        assert_eq!(format!("{:?}", list), format!("{:?}", reference));
+
+        // Every node is reachable from the head on each level of its tower, in order
+        for level in 0..list.max_level() {
+            let mut previous: Option<u64> = None;
+            let mut current = list.head[level].clone();
+            while let Some(node) = current {
+                let value = *node.borrow().read_data().as_ref().unwrap();
+                assert!(previous < Some(value));
+                previous = Some(value);
+                current = node.borrow().get_pointer(level_pointer(level));
+            }
+        }
+    }
+
+    #[test]
+    fn test_skip_list_drops_long_lists() {
+        // Sorted insertions go to the end of every level, and the drop mustn't recurse along the chain
+        let list: SkipList<u32> = (0..100_000).collect();
+        assert_eq!(list.len(), 100_000);
+        assert!(list.contains(&99_999));
+        drop(list);
+    }
+}
@@ -5,21 +5,21 @@
 //! - Accessing the data in a vertex is O(1).
 //! - Updating the pointers to the next and previous vertex is O(1).
 //! - Creating a new vertex is O(1).
+//! - Finding a pointer is linear in the number of pointers set on the vertex, a handful for every structure of the
+//!   crate, which is faster than hashing the name.
 //!
 //! # Usage
 //! ```
 //! ```
-use std::{
-    cell::RefCell,
-    collections::HashMap,
-    rc::{Rc, Weak},
-};
+use alloc::{string::String, vec::Vec};
+
+use super::shared::{Lock, Ptr, Shared, WeakShared};
 
 /// Direction of the pointer inside the Vertex
 ///
 /// This enum is used to specify the direction of the pointer in a vertex of a doubly linked list.
 /// It helps in identifying whether the pointer is pointing to the next vertex.
-#[derive(Debug, Hash, Eq, PartialEq)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum PointerName {
     Left,
     Right,
@@ -32,17 +32,61 @@ pub enum PointerName {
     Custom(String), // Custom pointer name for more flexibility
 }
 
+// Small map from pointer names to values, searched linearly. It needs nothing but `alloc`, unlike `HashMap`.
+#[derive(Debug)]
+struct PointerMap<V>(Vec<(PointerName, V)>);
+
+impl<V> PointerMap<V> {
+    fn new() -> Self {
+        PointerMap(Vec::new())
+    }
+
+    fn get(&self, name: &PointerName) -> Option<&V> {
+        self.0
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    // Set the value of a name and return its previous value
+    fn insert(&mut self, name: PointerName, value: V) -> Option<V> {
+        match self.0.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, slot)) => Some(core::mem::replace(slot, value)),
+            None => {
+                self.0.push((name, value));
+                None
+            }
+        }
+    }
+
+    fn remove(&mut self, name: &PointerName) -> Option<V> {
+        let index = self.0.iter().position(|(key, _)| key == name)?;
+        Some(self.0.swap_remove(index).1)
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&PointerName, &V)> {
+        self.0.iter().map(|(key, value)| (key, value))
+    }
+}
+
 /// A Vertex in a linked list
 /// # Fields
 /// * `data`: The data contained in the vertex
 /// * `self_ref`: A weak reference to the vertex itself
-/// * `connections`: A HashMap that stores pointers to other vertexes in the list, allowing for bidirectional traversal.
+/// * `connections`: A small map that stores pointers to other vertexes in the list, allowing for bidirectional traversal.
+/// * `weights`: The weight or label of the connections set with `set_edge`. The weight type defaults to `()` for
+///   vertexes whose connections carry nothing.
 ///
 #[derive(Debug)]
-pub struct Vertex<T> {
+pub struct Vertex<T, W = ()> {
     data: Option<T>,
-    self_ref: Option<Weak<RefCell<Vertex<T>>>>, // reference to the vertex itself
-    connections: HashMap<PointerName, Option<Rc<RefCell<Vertex<T>>>>>, // vector of pointers to other vertexes
+    self_ref: Option<WeakShared<Vertex<T, W>>>, // reference to the vertex itself
+    connections: PointerMap<Option<Shared<Vertex<T, W>>>>, // vector of pointers to other vertexes
+    weights: PointerMap<W>,
 }
 
 impl<T> Vertex<T> {
@@ -57,20 +101,44 @@ impl<T> Vertex<T> {
     /// # Example
     /// ```
     /// use data_structures::linked_list::vertex::Vertex;
-    /// use std::rc::Rc;
+    /// use data_structures::linked_list::shared::Ptr;
     ///
     /// let vertex_ptr = Vertex::new(10);
     /// ```
-    pub fn new(data: T) -> Rc<RefCell<Self>> {
+    pub fn new(data: T) -> Shared<Self> {
+        Self::new_weighted(data)
+    }
+}
+
+impl<T, W> Vertex<T, W> {
+    /// Create a new vertex whose connections carry a weight of type `W`, and return a pointer to it
+    /// # Arguments
+    /// * `data`: The data contained in the vertex
+    ///
+    /// # Returns
+    /// A pointer to the newly created vertex
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::vertex::{PointerName, Vertex};
+    ///
+    /// let city = Vertex::<&str, u32>::new_weighted("Lisbon");
+    /// let other = Vertex::new_weighted("Porto");
+    ///
+    /// city.borrow_mut().set_edge(PointerName::To, &other, 313);
+    /// assert_eq!(city.borrow().edge_weight(&PointerName::To), Some(&313));
+    /// ```
+    pub fn new_weighted(data: T) -> Shared<Self> {
         // Create new empty vertex
-        let new_vertex_ptr = Rc::new(RefCell::new(Vertex {
+        let new_vertex_ptr = Ptr::new(Lock::new(Vertex {
             data: None,
             self_ref: None, // Temporariamente None
-            connections: HashMap::new(),
+            connections: PointerMap::new(),
+            weights: PointerMap::new(),
         }));
 
         // Set the self_ref to point to itself
-        new_vertex_ptr.borrow_mut().self_ref = Some(Rc::downgrade(&new_vertex_ptr));
+        new_vertex_ptr.borrow_mut().self_ref = Some(Ptr::downgrade(&new_vertex_ptr));
 
         // Set the data in the new vertex
         new_vertex_ptr.borrow_mut().data = Some(data);
@@ -84,14 +152,14 @@ impl<T> Vertex<T> {
     /// # Example
     /// ```
     /// use data_structures::linked_list::vertex::Vertex;
-    /// use std::rc::Rc;
+    /// use data_structures::linked_list::shared::Ptr;
     ///
     /// let vertex_ptr = Vertex::new(10);
     /// let new_vertex_ptr = vertex_ptr.borrow().get_reference();
-    /// assert_eq!(Rc::strong_count(&vertex_ptr), 2);
-    /// assert_eq!(Rc::strong_count(&new_vertex_ptr), 2);
+    /// assert_eq!(Ptr::strong_count(&vertex_ptr), 2);
+    /// assert_eq!(Ptr::strong_count(&new_vertex_ptr), 2);
     /// ```
-    pub fn get_reference(&self) -> Rc<RefCell<Vertex<T>>> {
+    pub fn get_reference(&self) -> Shared<Vertex<T, W>> {
         self.self_ref
             .as_ref()
             .and_then(|weak_ref| weak_ref.upgrade())
@@ -115,6 +183,25 @@ impl<T> Vertex<T> {
         &self.data
     }
 
+    /// Get a mutable reference to the data
+    /// Useful for in-place updates
+    ///
+    /// # Returns
+    /// A mutable reference to the data, or None if the vertex was cleared
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::vertex::Vertex;
+    /// let vertex_ptr = Vertex::new(vec![1]);
+    ///
+    /// vertex_ptr.borrow_mut().data_mut().unwrap().push(2);
+    /// assert_eq!(vertex_ptr.borrow().read_data().as_ref().unwrap(), &vec![1, 2]);
+    /// ```
+    ///
+    pub fn data_mut(&mut self) -> Option<&mut T> {
+        self.data.as_mut()
+    }
+
     /// Set the data of the vertex and return the old data
     /// # Arguments
     /// * `data`: The new data to be set in the vertex
@@ -147,14 +234,24 @@ impl<T> Vertex<T> {
     ///
     pub fn clear(&mut self) -> Option<T> {
         self.connections.clear();
-        self.connections = HashMap::new(); // This was the only way I found to deallocate hasmap memory.
+        self.connections = PointerMap::new(); // This was the only way I found to deallocate hasmap memory.
+        self.weights = PointerMap::new();
 
         self.self_ref.take();
         self.data.take()
     }
 
+    // Take the data and drop the connections, keeping the vertex allocated and ready to hold new data
+    #[cfg(feature = "std")]
+    pub(crate) fn recycle(&mut self) -> Option<T> {
+        self.connections.clear();
+        self.weights.clear();
+        self.data.take()
+    }
+
     /// Set a connection in the Vertex.
     /// If the connectio already exists, it will be replaced with the new one and return the old connection.
+    /// The weight of the old connection, if any, is removed.
     ///
     /// # Arguments
     /// * `connection`: The new vertex to be set as the right pointer
@@ -177,8 +274,9 @@ impl<T> Vertex<T> {
     pub fn set_connection(
         &mut self,
         pointer_name: PointerName,
-        connection: Option<&Rc<RefCell<Vertex<T>>>>,
-    ) -> Option<Rc<RefCell<Vertex<T>>>> {
+        connection: Option<&Shared<Vertex<T, W>>>,
+    ) -> Option<Shared<Vertex<T, W>>> {
+        self.weights.remove(&pointer_name);
         match connection {
             Some(new_connection) => self
                 .connections
@@ -209,17 +307,126 @@ impl<T> Vertex<T> {
     /// assert!(vertex_ptr.borrow().get_pointer(PointerName::Left).is_none());
     /// assert!(vertex_ptr.borrow().get_pointer(PointerName::Right).is_some());
     /// ```
-    pub fn get_pointer(&self, pointer_name: PointerName) -> Option<Rc<RefCell<Vertex<T>>>> {
+    pub fn get_pointer(&self, pointer_name: PointerName) -> Option<Shared<Vertex<T, W>>> {
         match self.connections.get(&pointer_name) {
             Some(ptr) => ptr.clone(),
             None => None, // In this case there is no key with pointer_name.
         }
     }
+
+    /// Remove a connection from the Vertex, forgetting its name
+    ///
+    /// # Arguments
+    /// * `pointer_name`: The name of the connection to remove
+    /// # Returns
+    /// The removed pointer, or None if the connection wasn't set
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::vertex::{PointerName, Vertex};
+    ///
+    /// let vertex_ptr = Vertex::new(10);
+    /// let vertex_ptr2 = Vertex::new(20);
+    ///
+    /// vertex_ptr.borrow_mut().set_connection(PointerName::To, Some(&vertex_ptr2));
+    ///
+    /// assert!(vertex_ptr.borrow_mut().remove_connection(&PointerName::To).is_some());
+    /// assert_eq!(vertex_ptr.borrow().connection_names().count(), 0);
+    /// ```
+    pub fn remove_connection(
+        &mut self,
+        pointer_name: &PointerName,
+    ) -> Option<Shared<Vertex<T, W>>> {
+        self.weights.remove(pointer_name);
+        self.connections.remove(pointer_name).flatten()
+    }
+
+    /// Set a connection carrying a weight or label, replacing the previous connection with that name
+    ///
+    /// # Arguments
+    /// * `pointer_name`: The name of the connection
+    /// * `target`: The vertex the connection points to
+    /// * `weight`: The weight of the connection
+    /// # Returns
+    /// The previous pointer and its weight, if they were set
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::vertex::{PointerName, Vertex};
+    ///
+    /// let from = Vertex::<u8, &str>::new_weighted(1);
+    /// let to = Vertex::new_weighted(2);
+    ///
+    /// assert!(from.borrow_mut().set_edge(PointerName::To, &to, "road").0.is_none());
+    /// let (previous, weight) = from.borrow_mut().set_edge(PointerName::To, &to, "rail");
+    /// assert!(previous.is_some());
+    /// assert_eq!(weight, Some("road"));
+    /// ```
+    pub fn set_edge(
+        &mut self,
+        pointer_name: PointerName,
+        target: &Shared<Vertex<T, W>>,
+        weight: W,
+    ) -> (Option<Shared<Vertex<T, W>>>, Option<W>) {
+        let previous_weight = self.weights.insert(pointer_name.clone(), weight);
+        let previous = self
+            .connections
+            .insert(pointer_name, Some(target.clone()))
+            .flatten();
+        (previous, previous_weight)
+    }
+
+    /// Get the weight of a connection
+    ///
+    /// # Arguments
+    /// * `pointer_name`: The name of the connection
+    /// # Returns
+    /// A reference to the weight, or None if the connection wasn't set with `set_edge`
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::vertex::{PointerName, Vertex};
+    ///
+    /// let from = Vertex::<u8, f64>::new_weighted(1);
+    /// let to = Vertex::new_weighted(2);
+    ///
+    /// from.borrow_mut().set_edge(PointerName::Next, &to, 0.5);
+    /// assert_eq!(from.borrow().edge_weight(&PointerName::Next), Some(&0.5));
+    ///
+    /// from.borrow_mut().set_connection(PointerName::Next, Some(&to));
+    /// assert_eq!(from.borrow().edge_weight(&PointerName::Next), None);
+    /// ```
+    pub fn edge_weight(&self, pointer_name: &PointerName) -> Option<&W> {
+        self.weights.get(pointer_name)
+    }
+
+    /// List the names of the connections pointing to a vertex, in no particular order
+    ///
+    /// # Returns
+    /// An iterator over the names whose pointer is set
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::vertex::{PointerName, Vertex};
+    ///
+    /// let vertex_ptr = Vertex::new(10);
+    /// let vertex_ptr2 = Vertex::new(20);
+    ///
+    /// vertex_ptr.borrow_mut().set_connection(PointerName::Next, Some(&vertex_ptr2));
+    /// vertex_ptr.borrow_mut().set_connection(PointerName::Previous, None);
+    ///
+    /// let vertex = vertex_ptr.borrow();
+    /// let names: Vec<_> = vertex.connection_names().collect();
+    /// assert_eq!(names, vec![&PointerName::Next]);
+    /// ```
+    pub fn connection_names(&self) -> impl Iterator<Item = &PointerName> {
+        self.connections
+            .iter()
+            .filter(|(_, pointer)| pointer.is_some())
+            .map(|(name, _)| name)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::string::ToString;
 
     #[test]
     fn test_vertex_new() {
@@ -231,27 +438,47 @@ mod tests {
     fn test_vertex_reference_count() {
         // Receive a new reference to the vertex, so there is two references to the vertex, this new pointer and the vertex inner pointer.
         let vertex_ptr = Vertex::new(10);
-        assert_eq!(Rc::strong_count(&vertex_ptr), 1);
+        assert_eq!(Ptr::strong_count(&vertex_ptr), 1);
 
         {
             // Get a new references to the vertex
             // This will increase the reference count by 1
             #[allow(unused_variables)]
             let new_vertex_ptr_1 = vertex_ptr.borrow().get_reference();
-            assert_eq!(Rc::strong_count(&vertex_ptr), 2);
+            assert_eq!(Ptr::strong_count(&vertex_ptr), 2);
 
             #[allow(unused_variables)]
             let new_vertex_ptr_2 = vertex_ptr.borrow().get_reference();
-            assert_eq!(Rc::strong_count(&vertex_ptr), 3);
+            assert_eq!(Ptr::strong_count(&vertex_ptr), 3);
         }
 
         // The end of the prevous vertex should decrease the reference count by 1
-        assert_eq!(Rc::strong_count(&vertex_ptr), 1);
+        assert_eq!(Ptr::strong_count(&vertex_ptr), 1);
 
         // Drop the last strong reference
         //drop(vertex_ptr);
 
-        //assert_eq!(Rc::strong_count(&vertex_ptr), 0);
+        //assert_eq!(Ptr::strong_count(&vertex_ptr), 0);
+    }
+
+    #[test]
+    fn test_vertex_edge_weights() {
+        let source = Vertex::<i32, u32>::new_weighted(1);
+        let target = Vertex::new_weighted(2);
+        let name = PointerName::Custom("road".to_string());
+
+        let (old, weight) = source.borrow_mut().set_edge(name.clone(), &target, 7);
+        assert!(old.is_none() && weight.is_none());
+        assert_eq!(source.borrow().edge_weight(&name), Some(&7));
+
+        let (_, weight) = source.borrow_mut().set_edge(name.clone(), &target, 3);
+        assert_eq!(weight, Some(7));
+
+        source
+            .borrow_mut()
+            .set_connection(name.clone(), Some(&target));
+        assert_eq!(source.borrow().edge_weight(&name), None);
+        assert!(source.borrow().get_pointer(name).is_some());
     }
 
     #[test]
@@ -0,0 +1,189 @@
+//! This module implements the memtable of the LSM model: the mutable, in-memory buffer receiving every write.
+//! Writes go to a skip list kept in key order, and a deletion is written as a tombstone rather than removing the key,
+//! since older runs may still hold a value for it. When the memtable is large enough it is frozen into an immutable
+//! `SortedRun`, which takes its entries in order without sorting them again.
+//!
+//! # Performance
+//! - O(log n) expected for `insert`, `delete` and `get`
+//! - O(n) for `freeze`
+//!
+//! # Implementation Details
+//! - The entries are stored in a `SkipList` of `Option` values, `None` being a tombstone.
+//! - Overwriting a key replaces its entry, so the memtable holds one version per key.
+//!
+//! # Usage
+//! ```
+//! use data_structures::lsm::mem_table::MemTable;
+//!
+//! let mut table = MemTable::new();
+//! table.insert("apple", 3);
+//! table.insert("pear", 5);
+//! table.delete("pear");
+//!
+//! assert_eq!(table.get(&"apple"), Some(&Some(3)));
+//! assert_eq!(table.get(&"pear"), Some(&None));
+//!
+//! let run = table.freeze();
+//! assert_eq!(run.len(), 2);
+//! ```
+//!
+use std::ops::RangeBounds;
+
+use super::skip_list::SkipList;
+use super::sorted_run::SortedRun;
+
+/// Struct representing the mutable memtable of an LSM store
+///
+#[derive(Debug, Clone)]
+pub struct MemTable<K, V> {
+    entries: SkipList<K, Option<V>>, // None is a tombstone
+}
+
+impl<K: Ord, V> MemTable<K, V> {
+    /// Create a new empty memtable
+    /// # Returns
+    /// A new MemTable instance
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let table: MemTable<String, u32> = MemTable::new();
+    /// assert!(table.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        MemTable {
+            entries: SkipList::new(),
+        }
+    }
+
+    /// Get the number of entries, tombstones included
+    /// # Returns
+    /// The number of keys written to the memtable
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let mut table = MemTable::new();
+    /// table.insert(1, 'a');
+    /// table.delete(2);
+    /// assert_eq!(table.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the memtable is empty
+    /// # Returns
+    /// True if nothing was written to the memtable
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let mut table: MemTable<u32, String> = MemTable::new();
+    /// table.delete(1);
+    /// assert!(!table.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Write the value of a key
+    /// # Arguments
+    /// * `key`: The key to be written
+    /// * `value`: Its new value
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let mut table = MemTable::new();
+    /// table.insert("k", 1);
+    /// table.insert("k", 2);
+    /// assert_eq!(table.get(&"k"), Some(&Some(2)));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.entries.insert(key, Some(value));
+    }
+
+    /// Write a tombstone for a key, hiding its value in this memtable and in older runs
+    /// # Arguments
+    /// * `key`: The key to be deleted
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let mut table = MemTable::new();
+    /// table.insert("k", 1);
+    /// table.delete("k");
+    /// assert_eq!(table.get(&"k"), Some(&None));
+    /// ```
+    pub fn delete(&mut self, key: K) {
+        self.entries.insert(key, None);
+    }
+
+    /// Get the version of a key written to the memtable
+    /// # Arguments
+    /// * `key`: The key to be looked up
+    /// # Returns
+    /// None if the key wasn't written, Some(None) if it was deleted, Some(Some(value)) otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let mut table = MemTable::new();
+    /// table.insert(7, "seven");
+    /// assert_eq!(table.get(&7), Some(&Some("seven")));
+    /// assert_eq!(table.get(&8), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&Option<V>> {
+        self.entries.get(key)
+    }
+
+    /// Get an iterator over the entries whose key is in a range, tombstones included
+    /// # Arguments
+    /// * `range`: The range of keys
+    /// # Returns
+    /// An iterator of keys and versions, in key order
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let mut table = MemTable::new();
+    /// table.insert(3, 'c');
+    /// table.insert(1, 'a');
+    /// table.delete(2);
+    ///
+    /// let entries: Vec<_> = table.range(..3).collect();
+    /// assert_eq!(entries, vec![(&1, &Some('a')), (&2, &None)]);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> impl Iterator<Item = (&K, &Option<V>)> + '_ {
+        self.entries.range(range)
+    }
+
+    /// Turn the memtable into an immutable sorted run
+    /// # Returns
+    /// The run holding the entries of the memtable, tombstones included
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let mut table = MemTable::new();
+    /// table.insert(2, "b");
+    /// table.insert(1, "a");
+    ///
+    /// let run = table.freeze();
+    /// assert_eq!(run.iter().next(), Some((&1, &Some("a"))));
+    /// ```
+    pub fn freeze(self) -> SortedRun<K, V> {
+        SortedRun::from_sorted(self.entries.into_sorted_vec())
+    }
+}
+
+impl<K: Ord, V> Default for MemTable<K, V> {
+    /// Creates an empty memtable
+    fn default() -> Self {
+        Self::new()
+    }
+}
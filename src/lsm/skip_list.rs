@@ -0,0 +1,350 @@
+//! This module implements a skip list: a sorted linked list where each node also gets a random number of express
+//! links jumping over the nodes below it. A search follows the highest links first and drops a level each time it
+//! would overshoot, so it skips about half of the remaining nodes at every level, like a binary search over a list.
+//! Insertions only relink the neighbors of the new node, which makes it the usual ordered buffer of LSM memtables.
+//!
+//! # Performance
+//! - O(log n) expected for `insert` and `get`
+//! - O(log n + k) expected to iterate over k entries of a range
+//!
+//! # Implementation Details
+//! - The nodes are stored in a `Vec` and linked by index, each with one link per level of its tower.
+//! - A node reaches level l + 1 with probability 1/2^l, drawn from a deterministic generator, up to `MAX_LEVEL`.
+//! - Entries can be replaced but not removed: an LSM memtable records deletions as tombstones instead.
+//!
+//! # Usage
+//! ```
+//! use data_structures::lsm::skip_list::SkipList;
+//!
+//! let mut list = SkipList::new();
+//! list.insert(30, "c");
+//! list.insert(10, "a");
+//! list.insert(20, "b");
+//!
+//! assert_eq!(list.get(&20), Some(&"b"));
+//! assert_eq!(list.range(15..).map(|(key, _)| *key).collect::<Vec<_>>(), vec![20, 30]);
+//! ```
+//!
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+/// The number of levels of the list, enough for millions of entries
+const MAX_LEVEL: usize = 20;
+
+// A node of the list, with one link per level of its tower
+#[derive(Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    next: Vec<Option<usize>>,
+}
+
+/// Struct representing a skip list ordered by key
+///
+#[derive(Clone)]
+pub struct SkipList<K, V> {
+    nodes: Vec<Node<K, V>>,
+    head: [Option<usize>; MAX_LEVEL], // first node of each level
+    seed: u64,                        // state of the level generator
+}
+
+impl<K: Ord, V> SkipList<K, V> {
+    /// Create a new empty skip list
+    /// # Returns
+    /// A new SkipList instance
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::skip_list::SkipList;
+    ///
+    /// let list: SkipList<u32, u32> = SkipList::new();
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        SkipList {
+            nodes: Vec::new(),
+            head: [None; MAX_LEVEL],
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    /// Get the number of entries
+    /// # Returns
+    /// The number of keys in the list
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::skip_list::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// list.insert('a', 1);
+    /// list.insert('a', 2);
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check if the list is empty
+    /// # Returns
+    /// True if the list has no entries
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::skip_list::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// list.insert(1, ());
+    /// assert!(!list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Insert an entry, replacing the value of an existing key
+    /// # Arguments
+    /// * `key`: The key of the entry
+    /// * `value`: The value of the entry
+    /// # Returns
+    /// The previous value of the key, if any
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::skip_list::SkipList;
+    ///
+    /// let mut list = SkipList::new();
+    /// assert_eq!(list.insert("k", 1), None);
+    /// assert_eq!(list.insert("k", 2), Some(1));
+    /// assert_eq!(list.get(&"k"), Some(&2));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let predecessors = self.predecessors(|other| *other < key);
+        if let Some(index) = self.next_of(predecessors[0], 0) {
+            if self.nodes[index].key == key {
+                return Some(std::mem::replace(&mut self.nodes[index].value, value));
+            }
+        }
+
+        let index = self.nodes.len();
+        let height = self.random_height();
+        let next = (0..height)
+            .map(|level| self.next_of(predecessors[level], level))
+            .collect();
+        self.nodes.push(Node { key, value, next });
+
+        for (level, predecessor) in predecessors.iter().enumerate().take(height) {
+            match predecessor {
+                Some(predecessor) => self.nodes[*predecessor].next[level] = Some(index),
+                None => self.head[level] = Some(index),
+            }
+        }
+        None
+    }
+
+    /// Get the value of a key
+    /// # Arguments
+    /// * `key`: The key to be looked up
+    /// # Returns
+    /// A reference to the value, or None if the key isn't in the list
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::skip_list::SkipList;
+    ///
+    /// let list: SkipList<_, _> = [(1, "one"), (2, "two")].into_iter().collect();
+    /// assert_eq!(list.get(&2), Some(&"two"));
+    /// assert_eq!(list.get(&3), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let predecessors = self.predecessors(|other| other < key);
+        let node = &self.nodes[self.next_of(predecessors[0], 0)?];
+        (node.key == *key).then_some(&node.value)
+    }
+
+    /// Get an iterator over the entries in key order
+    /// # Returns
+    /// An iterator of key and value references
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::skip_list::SkipList;
+    ///
+    /// let list: SkipList<_, _> = [(2, 'b'), (1, 'a')].into_iter().collect();
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![(&1, &'a'), (&2, &'b')]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.iter_from(self.head[0], None)
+    }
+
+    /// Get an iterator over the entries whose key is in a range, in key order
+    /// # Arguments
+    /// * `range`: The range of keys, such as `a..b` or `(Bound::Excluded(a), Bound::Unbounded)`
+    /// # Returns
+    /// An iterator of key and value references
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::skip_list::SkipList;
+    ///
+    /// let list: SkipList<_, _> = (0..10).map(|key| (key, key * key)).collect();
+    /// assert_eq!(list.range(3..=4).collect::<Vec<_>>(), vec![(&3, &9), (&4, &16)]);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        let first = match range.start_bound() {
+            Bound::Included(start) => self.predecessors(|other| other < start),
+            Bound::Excluded(start) => self.predecessors(|other| other <= start),
+            Bound::Unbounded => [None; MAX_LEVEL],
+        };
+        let past_end = match range.end_bound() {
+            Bound::Included(end) => self.predecessors(|other| other <= end),
+            Bound::Excluded(end) => self.predecessors(|other| other < end),
+            Bound::Unbounded => return self.iter_from(self.next_of(first[0], 0), None),
+        };
+
+        // An empty or inverted range has no first entry
+        let next = self
+            .next_of(first[0], 0)
+            .filter(|&index| range.contains(&self.nodes[index].key));
+        self.iter_from(next, self.next_of(past_end[0], 0))
+    }
+
+    /// Move the entries out of the list, in key order
+    /// # Returns
+    /// The entries sorted by key
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::skip_list::SkipList;
+    ///
+    /// let list: SkipList<_, _> = [("b", 2), ("a", 1)].into_iter().collect();
+    /// assert_eq!(list.into_sorted_vec(), vec![("a", 1), ("b", 2)]);
+    /// ```
+    pub fn into_sorted_vec(self) -> Vec<(K, V)> {
+        let order: Vec<usize> =
+            std::iter::successors(self.head[0], |&index| self.nodes[index].next[0]).collect();
+        let mut slots: Vec<Option<(K, V)>> = self
+            .nodes
+            .into_iter()
+            .map(|node| Some((node.key, node.value)))
+            .collect();
+        order
+            .into_iter()
+            .filter_map(|index| slots[index].take())
+            .collect()
+    }
+
+    // Iterate from a node up to another one, excluded
+    fn iter_from(&self, next: Option<usize>, end: Option<usize>) -> Iter<'_, K, V> {
+        Iter {
+            list: self,
+            next,
+            end,
+        }
+    }
+
+    // Find the last node before the target on each level, None standing for the head
+    fn predecessors(&self, is_before: impl Fn(&K) -> bool) -> [Option<usize>; MAX_LEVEL] {
+        let mut predecessors = [None; MAX_LEVEL];
+        let mut current = None;
+        for level in (0..MAX_LEVEL).rev() {
+            while let Some(next) = self.next_of(current, level) {
+                if !is_before(&self.nodes[next].key) {
+                    break;
+                }
+                current = Some(next);
+            }
+            predecessors[level] = current;
+        }
+        predecessors
+    }
+
+    // Follow the link of a node, or of the head, on a level
+    fn next_of(&self, node: Option<usize>, level: usize) -> Option<usize> {
+        match node {
+            Some(index) => self.nodes[index].next[level],
+            None => self.head[level],
+        }
+    }
+
+    // Draw the height of a new tower, halving the odds at each level
+    fn random_height(&mut self) -> usize {
+        self.seed = self
+            .seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.seed >> 32) as u32 | 1 << (MAX_LEVEL - 1)).trailing_zeros() as usize + 1
+    }
+}
+
+/// Iterator over the entries of a skip list in key order
+///
+pub struct Iter<'a, K, V> {
+    list: &'a SkipList<K, V>,
+    next: Option<usize>,
+    end: Option<usize>, // first node past the iterated ones
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    /// Returns the next entry in key order
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.next.filter(|&index| Some(index) != self.end)?;
+        let node = &self.list.nodes[index];
+        self.next = node.next[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K: Ord, V> Default for SkipList<K, V> {
+    /// Creates an empty skip list
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipList<K, V> {
+    /// Builds a skip list from entries, the last value of a key winning
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = SkipList::new();
+        for (key, value) in iter {
+            list.insert(key, value);
+        }
+        list
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SkipList<K, V> {
+    /// Prints the entries in key order
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(Iter {
+                list: self,
+                next: self.head[0],
+                end: None,
+            })
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_skip_list_against_btree_map() {
+        let mut list = SkipList::new();
+        let mut model = BTreeMap::new();
+        let mut rng = Lcg::new(11);
+
+        for step in 0..5_000u32 {
+            let seed = rng.next_u64();
+            let key = (seed >> 33) as u32 % 1_000;
+            assert_eq!(list.insert(key, step), model.insert(key, step));
+            assert_eq!(list.get(&(key + 1)), model.get(&(key + 1)));
+        }
+        assert_eq!(list.len(), model.len());
+
+        assert!(list.iter().eq(model.iter()));
+        assert!(list.range(250..500).eq(model.range(250..500)));
+        assert!(list.range(..=17).eq(model.range(..=17)));
+        assert!(list
+            .range((Bound::Excluded(998), Bound::Unbounded))
+            .eq(model.range((Bound::Excluded(998), Bound::Unbounded))));
+        assert!(list.into_sorted_vec().into_iter().eq(model));
+    }
+}
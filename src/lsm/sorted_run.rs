@@ -0,0 +1,306 @@
+//! This module implements the immutable sorted runs of the LSM model, and the k-way merge reading across them.
+//! A run is what a memtable becomes when it is frozen: its entries sorted by key, deleted keys kept as tombstones so
+//! they still hide the older versions in earlier runs. `MergeIter` merges sorted sources given from the newest to the
+//! oldest and yields each key once with its newest version, which serves both range reads and compaction.
+//!
+//! # Performance
+//! - O(log n) for `get` and to start a range, with a binary search
+//! - O(log k) per entry read by a `MergeIter` over k sources
+//!
+//! # Implementation Details
+//! - A run is a `Vec` of keys and `Option` values, `None` being a tombstone.
+//! - `MergeIter` keeps the next key of each source in the crate's `SkewHeap`, with the index of its source. Ties go to
+//!   the lowest index, the newest source, and the same key is then skipped in the older ones.
+//! - `SortedRun::merge` drops the tombstones only when asked: they are still needed while an older run may hold the key.
+//!
+//! # Usage
+//! ```
+//! use data_structures::lsm::mem_table::MemTable;
+//! use data_structures::lsm::sorted_run::SortedRun;
+//!
+//! let mut older = MemTable::new();
+//! older.insert("a", 1);
+//! older.insert("b", 2);
+//!
+//! let mut newer = MemTable::new();
+//! newer.insert("a", 10);
+//! newer.delete("b");
+//!
+//! let merged = SortedRun::merge(vec![newer.freeze(), older.freeze()], true);
+//! assert_eq!(merged.iter().collect::<Vec<_>>(), vec![(&"a", &Some(10))]);
+//! ```
+//!
+use std::cmp::Reverse;
+use std::ops::{Bound, RangeBounds};
+
+use crate::heap::skew_heap::SkewHeap;
+
+/// Struct representing an immutable run of entries sorted by key
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortedRun<K, V> {
+    entries: Vec<(K, Option<V>)>, // sorted by key, None being a tombstone
+}
+
+impl<K: Ord, V> SortedRun<K, V> {
+    // Wrap entries already sorted by key, each key appearing once
+    pub(crate) fn from_sorted(entries: Vec<(K, Option<V>)>) -> Self {
+        SortedRun { entries }
+    }
+
+    /// Merge runs into one, keeping the newest version of each key
+    /// # Arguments
+    /// * `runs`: The runs to merge, from the newest to the oldest
+    /// * `drop_tombstones`: Whether to leave the deleted keys out, which is only right if no older run remains
+    /// # Returns
+    /// The merged run
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    /// use data_structures::lsm::sorted_run::SortedRun;
+    ///
+    /// let mut newer = MemTable::new();
+    /// newer.delete(1);
+    /// let mut older = MemTable::new();
+    /// older.insert(1, 'x');
+    ///
+    /// let merged = SortedRun::merge(vec![newer.freeze(), older.freeze()], false);
+    /// assert_eq!(merged.get(&1), Some(&None));
+    /// ```
+    pub fn merge(runs: Vec<SortedRun<K, V>>, drop_tombstones: bool) -> Self {
+        let sources = runs
+            .into_iter()
+            .map(|run| run.entries.into_iter())
+            .collect();
+        let entries = MergeIter::new(sources)
+            .filter(|(_, value)| !drop_tombstones || value.is_some())
+            .collect();
+        SortedRun { entries }
+    }
+
+    /// Get the number of entries, tombstones included
+    /// # Returns
+    /// The number of keys in the run
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let mut table = MemTable::new();
+    /// table.insert(1, 1);
+    /// table.delete(2);
+    /// assert_eq!(table.freeze().len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the run is empty
+    /// # Returns
+    /// True if the run has no entries
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let table: MemTable<u32, u32> = MemTable::new();
+    /// assert!(table.freeze().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the version of a key recorded in the run
+    /// # Arguments
+    /// * `key`: The key to be looked up
+    /// # Returns
+    /// None if the run doesn't know the key, Some(None) if it records its deletion, Some(Some(value)) otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let mut table = MemTable::new();
+    /// table.insert("kept", 1);
+    /// table.delete("gone");
+    /// let run = table.freeze();
+    ///
+    /// assert_eq!(run.get(&"kept"), Some(&Some(1)));
+    /// assert_eq!(run.get(&"gone"), Some(&None));
+    /// assert_eq!(run.get(&"other"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&Option<V>> {
+        let index = self
+            .entries
+            .binary_search_by(|(other, _)| other.cmp(key))
+            .ok()?;
+        Some(&self.entries[index].1)
+    }
+
+    /// Get an iterator over the entries in key order, tombstones included
+    /// # Returns
+    /// An iterator of keys and versions
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let mut table = MemTable::new();
+    /// table.insert(2, 'b');
+    /// table.delete(1);
+    /// let run = table.freeze();
+    ///
+    /// assert_eq!(run.iter().collect::<Vec<_>>(), vec![(&1, &None), (&2, &Some('b'))]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &Option<V>)> + '_ {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    /// Get an iterator over the entries whose key is in a range, tombstones included
+    /// # Arguments
+    /// * `range`: The range of keys
+    /// # Returns
+    /// An iterator of keys and versions, in key order
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::mem_table::MemTable;
+    ///
+    /// let mut table = MemTable::new();
+    /// for key in 0..10 {
+    ///     table.insert(key, key);
+    /// }
+    /// let run = table.freeze();
+    ///
+    /// assert_eq!(run.range(8..).count(), 2);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> impl Iterator<Item = (&K, &Option<V>)> + '_ {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.entries.partition_point(|(key, _)| key < start),
+            Bound::Excluded(start) => self.entries.partition_point(|(key, _)| key <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.entries.partition_point(|(key, _)| key <= end),
+            Bound::Excluded(end) => self.entries.partition_point(|(key, _)| key < end),
+            Bound::Unbounded => self.entries.len(),
+        };
+        self.entries[start..end.max(start)]
+            .iter()
+            .map(|(key, value)| (key, value))
+    }
+}
+
+/// Iterator merging sorted sources, yielding each key once with its value from the newest source holding it
+///
+/// The sources must each be sorted by key without duplicates, and be given from the newest to the oldest.
+///
+/// # Example
+/// ```
+/// use data_structures::lsm::sorted_run::MergeIter;
+///
+/// let newer = vec![(2, "new"), (5, "new")];
+/// let older = vec![(1, "old"), (2, "old")];
+///
+/// let merged: Vec<_> = MergeIter::new(vec![newer.into_iter(), older.into_iter()]).collect();
+/// assert_eq!(merged, vec![(1, "old"), (2, "new"), (5, "new")]);
+/// ```
+pub struct MergeIter<K, V, I> {
+    sources: Vec<I>,
+    values: Vec<Option<V>>, // value of the next entry of each source
+    heads: SkewHeap<Reverse<(K, usize)>>, // key of the next entry of each source, with the index of the source
+}
+
+impl<K: Ord, V, I: Iterator<Item = (K, V)>> MergeIter<K, V, I> {
+    /// Create a merge of sorted sources
+    /// # Arguments
+    /// * `sources`: The sources, from the newest to the oldest
+    /// # Returns
+    /// A new MergeIter instance
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::sorted_run::MergeIter;
+    ///
+    /// let merged = MergeIter::new(vec![vec![(1, 'a')].into_iter(), vec![(1, 'b')].into_iter()]);
+    /// assert_eq!(merged.collect::<Vec<_>>(), vec![(1, 'a')]);
+    /// ```
+    pub fn new(sources: Vec<I>) -> Self {
+        let mut merge = MergeIter {
+            values: sources.iter().map(|_| None).collect(),
+            sources,
+            heads: SkewHeap::new(),
+        };
+        for index in 0..merge.sources.len() {
+            merge.advance(index);
+        }
+        merge
+    }
+
+    // Read the next entry of a source into the heap
+    fn advance(&mut self, index: usize) {
+        if let Some((key, value)) = self.sources[index].next() {
+            self.values[index] = Some(value);
+            self.heads.push(Reverse((key, index)));
+        }
+    }
+}
+
+impl<K: Ord, V, I: Iterator<Item = (K, V)>> Iterator for MergeIter<K, V, I> {
+    type Item = (K, V);
+
+    /// Returns the smallest key left, with its newest value
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((key, index)) = self.heads.pop()?;
+        let value = self.values[index].take()?;
+        self.advance(index);
+
+        // Older versions of the key come next, from the sources with a greater index
+        while self
+            .heads
+            .peek_with(|Reverse((other, _))| *other == key)
+            .unwrap_or(false)
+        {
+            let Reverse((_, older)) = self.heads.pop()?;
+            self.values[older] = None;
+            self.advance(older);
+        }
+        Some((key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_merge_keeps_the_newest_versions() {
+        // Three overlapping runs, each written after the previous one
+        let mut model = BTreeMap::new();
+        let mut runs = Vec::new();
+        let mut rng = Lcg::new(19);
+        for generation in 0..3u32 {
+            let mut entries = BTreeMap::new();
+            for _ in 0..300 {
+                let seed = rng.next_u64();
+                let key = (seed >> 33) as u32 % 400;
+                let value = (seed >> 62 != 0).then_some(generation);
+                entries.insert(key, value);
+                model.insert(key, value);
+            }
+            runs.insert(0, SortedRun::from_sorted(entries.into_iter().collect()));
+        }
+
+        let kept = SortedRun::merge(runs.clone(), false);
+        assert!(kept.iter().map(|(k, v)| (*k, *v)).eq(model.clone()));
+        assert!(kept
+            .range(100..=200)
+            .map(|(k, v)| (*k, *v))
+            .eq(model.range(100..=200).map(|(k, v)| (*k, *v))));
+
+        let compacted = SortedRun::merge(runs, true);
+        assert!(compacted
+            .iter()
+            .map(|(k, v)| (*k, *v))
+            .eq(model.into_iter().filter(|(_, v)| v.is_some())));
+    }
+}
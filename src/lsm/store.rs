@@ -0,0 +1,336 @@
+//! This module implements an in-memory model of a log-structured merge (LSM) store, built from the memtable and sorted
+//! runs of this module. Writes go to the memtable, which is frozen into a new run once it holds `memtable_limit`
+//! entries. Reads look at the memtable, then at the runs from the newest to the oldest, and range reads merge all of
+//! them. Compaction merges runs into one, dropping the overwritten versions and, once no older run remains, the
+//! tombstones.
+//!
+//! # Performance
+//! - O(log n) expected for `insert` and `delete`, plus O(n) to freeze a full memtable
+//! - O(r log n) for `get` with r runs, which compaction keeps small
+//! - O(log n + k log r) to read k entries of a range
+//!
+//! # Implementation Details
+//! - The runs are kept from the oldest to the newest, and a freeze appends one.
+//! - Range reads use a `MergeIter` over the memtable and the runs, and skip the keys whose newest version is a
+//!   tombstone.
+//! - `compact_newest` merges the newest runs and keeps their tombstones unless every run is merged, since an older run
+//!   may still hold the deleted keys.
+//! - Nothing is written to disk: the model shows the data flow, not the I/O.
+//!
+//! # Usage
+//! ```
+//! use data_structures::lsm::store::LsmStore;
+//!
+//! let mut store = LsmStore::new(2);
+//! store.insert("a", 1);
+//! store.insert("b", 2); // the memtable is frozen into a run
+//! store.insert("a", 3);
+//! store.delete("b");
+//!
+//! assert_eq!(store.get(&"a"), Some(&3));
+//! assert_eq!(store.get(&"b"), None);
+//! assert_eq!(store.runs().len(), 2);
+//!
+//! store.compact();
+//! assert_eq!(store.runs().len(), 1);
+//! assert_eq!(store.range(..).collect::<Vec<_>>(), vec![(&"a", &3)]);
+//! ```
+//!
+use std::ops::RangeBounds;
+
+use super::mem_table::MemTable;
+use super::sorted_run::{MergeIter, SortedRun};
+
+// The entries of the memtable or of a run, tombstones included
+type Versions<'a, K, V> = Box<dyn Iterator<Item = (&'a K, &'a Option<V>)> + 'a>;
+
+/// Struct representing an in-memory LSM store
+///
+#[derive(Debug, Clone)]
+pub struct LsmStore<K, V> {
+    memtable: MemTable<K, V>,
+    runs: Vec<SortedRun<K, V>>, // from the oldest to the newest
+    memtable_limit: usize,
+}
+
+impl<K: Ord, V> LsmStore<K, V> {
+    /// Create a new empty store
+    /// # Arguments
+    /// * `memtable_limit`: The number of entries at which the memtable is frozen, at least 1
+    /// # Returns
+    /// A new LsmStore instance
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::store::LsmStore;
+    ///
+    /// let store: LsmStore<u64, String> = LsmStore::new(1024);
+    /// assert_eq!(store.memtable_limit(), 1024);
+    /// ```
+    pub fn new(memtable_limit: usize) -> Self {
+        LsmStore {
+            memtable: MemTable::new(),
+            runs: Vec::new(),
+            memtable_limit: memtable_limit.max(1),
+        }
+    }
+
+    /// Get the number of entries at which the memtable is frozen
+    /// # Returns
+    /// The memtable limit
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::store::LsmStore;
+    ///
+    /// let store: LsmStore<u64, u64> = LsmStore::new(0);
+    /// assert_eq!(store.memtable_limit(), 1);
+    /// ```
+    pub fn memtable_limit(&self) -> usize {
+        self.memtable_limit
+    }
+
+    /// Get the memtable receiving the writes
+    /// # Returns
+    /// A reference to the memtable
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::store::LsmStore;
+    ///
+    /// let mut store = LsmStore::new(10);
+    /// store.insert(1, 'a');
+    /// assert_eq!(store.memtable().len(), 1);
+    /// ```
+    pub fn memtable(&self) -> &MemTable<K, V> {
+        &self.memtable
+    }
+
+    /// Get the frozen runs
+    /// # Returns
+    /// The runs, from the oldest to the newest
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::store::LsmStore;
+    ///
+    /// let mut store = LsmStore::new(1);
+    /// store.insert(1, 'a');
+    /// store.insert(2, 'b');
+    /// assert_eq!(store.runs().len(), 2);
+    /// ```
+    pub fn runs(&self) -> &[SortedRun<K, V>] {
+        &self.runs
+    }
+
+    /// Write the value of a key, freezing the memtable if it reaches its limit
+    /// # Arguments
+    /// * `key`: The key to be written
+    /// * `value`: Its new value
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::store::LsmStore;
+    ///
+    /// let mut store = LsmStore::new(4);
+    /// store.insert("k", 1);
+    /// assert_eq!(store.get(&"k"), Some(&1));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.memtable.insert(key, value);
+        self.freeze_if_full();
+    }
+
+    /// Delete a key by writing a tombstone, freezing the memtable if it reaches its limit
+    /// # Arguments
+    /// * `key`: The key to be deleted
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::store::LsmStore;
+    ///
+    /// let mut store = LsmStore::new(1);
+    /// store.insert("k", 1);
+    /// store.delete("k");
+    ///
+    /// assert_eq!(store.get(&"k"), None);
+    /// assert_eq!(store.runs().len(), 2);
+    /// ```
+    pub fn delete(&mut self, key: K) {
+        self.memtable.delete(key);
+        self.freeze_if_full();
+    }
+
+    /// Get the current value of a key
+    /// # Arguments
+    /// * `key`: The key to be looked up
+    /// # Returns
+    /// A reference to the newest value, or None if the key was never written or its newest version is a deletion
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::store::LsmStore;
+    ///
+    /// let mut store = LsmStore::new(1);
+    /// store.insert(1, "old");
+    /// store.insert(1, "new");
+    /// assert_eq!(store.get(&1), Some(&"new"));
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        if let Some(version) = self.memtable.get(key) {
+            return version.as_ref();
+        }
+        self.runs
+            .iter()
+            .rev()
+            .find_map(|run| run.get(key))
+            .and_then(|version| version.as_ref())
+    }
+
+    /// Get an iterator over the current entries whose key is in a range
+    /// # Arguments
+    /// * `range`: The range of keys
+    /// # Returns
+    /// An iterator of keys and newest values in key order, deleted keys left out
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::store::LsmStore;
+    ///
+    /// let mut store = LsmStore::new(3);
+    /// for key in 0..10 {
+    ///     store.insert(key, key * 10);
+    /// }
+    /// store.delete(4);
+    ///
+    /// let entries: Vec<_> = store.range(3..6).collect();
+    /// assert_eq!(entries, vec![(&3, &30), (&5, &50)]);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> impl Iterator<Item = (&K, &V)> + '_ {
+        let bounds = (range.start_bound(), range.end_bound());
+        let mut sources: Vec<Versions<'_, K, V>> = vec![Box::new(self.memtable.range(bounds))];
+        for run in self.runs.iter().rev() {
+            sources.push(Box::new(run.range(bounds)));
+        }
+        MergeIter::new(sources).filter_map(|(key, version)| Some((key, version.as_ref()?)))
+    }
+
+    /// Freeze the memtable into a new run, even if it isn't full
+    /// # Returns
+    /// True if a run was added, false if the memtable was empty
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::store::LsmStore;
+    ///
+    /// let mut store = LsmStore::new(100);
+    /// store.insert(1, 1);
+    ///
+    /// assert!(store.freeze());
+    /// assert!(!store.freeze());
+    /// assert_eq!(store.runs().len(), 1);
+    /// ```
+    pub fn freeze(&mut self) -> bool {
+        if self.memtable.is_empty() {
+            return false;
+        }
+        let memtable = std::mem::take(&mut self.memtable);
+        self.runs.push(memtable.freeze());
+        true
+    }
+
+    /// Merge every run into one, dropping the overwritten versions and the tombstones
+    /// The memtable isn't frozen first.
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::store::LsmStore;
+    ///
+    /// let mut store = LsmStore::new(1);
+    /// store.insert(1, 'a');
+    /// store.insert(1, 'b');
+    /// store.delete(2);
+    /// store.compact();
+    ///
+    /// assert_eq!(store.runs().len(), 1);
+    /// assert_eq!(store.runs()[0].len(), 1);
+    /// ```
+    pub fn compact(&mut self) {
+        self.compact_newest(self.runs.len());
+    }
+
+    /// Merge the newest runs into one
+    /// The tombstones are dropped only if every run is merged.
+    /// # Arguments
+    /// * `count`: The number of runs to merge, capped to the number of runs
+    /// # Example
+    /// ```
+    /// use data_structures::lsm::store::LsmStore;
+    ///
+    /// let mut store = LsmStore::new(1);
+    /// store.insert(1, 'a');
+    /// store.delete(1);
+    /// store.insert(2, 'b');
+    ///
+    /// store.compact_newest(2);
+    /// assert_eq!(store.runs().len(), 2);
+    /// assert_eq!(store.runs()[1].get(&1), Some(&None)); // still hides the oldest run
+    /// assert_eq!(store.get(&1), None);
+    /// ```
+    pub fn compact_newest(&mut self, count: usize) {
+        let count = count.min(self.runs.len());
+        if count == 0 {
+            return;
+        }
+        let drop_tombstones = count == self.runs.len();
+        let newest: Vec<SortedRun<K, V>> =
+            self.runs.drain(self.runs.len() - count..).rev().collect();
+        self.runs.push(SortedRun::merge(newest, drop_tombstones));
+    }
+
+    // Freeze the memtable once it reaches its limit
+    fn freeze_if_full(&mut self) {
+        if self.memtable.len() >= self.memtable_limit {
+            self.freeze();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_lsm_store_against_btree_map() {
+        let mut store = LsmStore::new(64);
+        let mut model = BTreeMap::new();
+        let mut rng = Lcg::new(23);
+
+        for step in 0..10_000u32 {
+            let seed = rng.next_u64();
+            let key = (seed >> 33) as u32 % 500;
+            match (seed >> 58) % 8 {
+                0 | 1 => {
+                    store.delete(key);
+                    model.remove(&key);
+                }
+                2 => assert_eq!(store.get(&key), model.get(&key)),
+                _ => {
+                    store.insert(key, step);
+                    model.insert(key, step);
+                }
+            }
+
+            if step % 1_000 == 999 {
+                store.compact_newest(4);
+            }
+            if step % 3_000 == 2_999 {
+                store.compact();
+                assert!(store.runs()[0].iter().all(|(_, version)| version.is_some()));
+            }
+            if step % 500 == 0 {
+                assert!(store.range(100..300).eq(model.range(100..300)));
+            }
+        }
+
+        assert!(store.runs().len() > 1);
+        assert!(store.range(..).eq(model.iter()));
+        store.freeze();
+        store.compact();
+        assert_eq!(store.runs().len(), 1);
+        assert_eq!(store.runs()[0].len(), model.len());
+        assert!(store.memtable().is_empty());
+    }
+}
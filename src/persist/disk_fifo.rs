@@ -0,0 +1,881 @@
+//! This module implements a durable FIFO that keeps its elements in an append-only segment file.
+//! Every push appends a record to the file and every acknowledged pop appends a tombstone, so reopening the file after a
+//! restart rebuilds the queue. Popped elements that were never acknowledged are delivered again after a restart, which
+//! gives at-least-once delivery to consumers that acknowledge only after processing.
+//!
+//! # Performance
+//! - O(1) amortized for `push`, `pop` and `ack`, each touching the file once
+//! - O(n) to open a segment, which is scanned once to build the in-memory index
+//! - O(n) for `compact`, which rewrites the live records
+//!
+//! # Implementation Details
+//! - Elements are encoded by a `Codec`. The default, `PersistCodec`, uses the `Persist` trait of the `format` module;
+//!   with the `serde` feature, `SerdeCodec` encodes any `Serialize` type in the postcard format.
+//! - A record is a tag, a sequence number, the payload for a push, and the CRC-32 of all of it. The index keeps the
+//!   file offset of each live record, so the payloads stay on disk until they are popped.
+//! - A torn record at the end of the file, left by a crash in the middle of a write, is detected by its checksum and
+//!   truncated away when the segment is opened. A bad record is only torn if it runs to the end of the file and no
+//!   valid record starts after it; anything else, such as a corrupted length running past the end, is corruption:
+//!   `open` fails with an `InvalidData` error and leaves the file untouched instead of dropping the records after it.
+//! - Writes go to the operating system cache; call `sync` to force them to the disk.
+//! - Acknowledged records keep taking space until `compact` rewrites the segment without them. The new segment is
+//!   synced before it replaces the old one.
+//! - With the `mmap` feature, `set_mmap` reads the payloads through a memory map of the segment instead of a seek and a
+//!   read per pop. The map is only valid as long as no other process writes to the segment.
+//!
+//! # Usage
+//! ```
+//! use data_structures::persist::disk_fifo::DiskFifo;
+//!
+//! let path = std::env::temp_dir().join("disk_fifo_usage_example.segment");
+//! # let _ = std::fs::remove_file(&path);
+//!
+//! let mut queue: DiskFifo<String> = DiskFifo::open(&path).unwrap();
+//! queue.push(&"first".to_string()).unwrap();
+//! queue.push(&"second".to_string()).unwrap();
+//!
+//! let (ticket, value) = queue.pop().unwrap().unwrap();
+//! assert_eq!(value, "first");
+//! drop(queue); // crash before the acknowledgment
+//!
+//! // The unacknowledged element is delivered again
+//! let mut queue: DiskFifo<String> = DiskFifo::open(&path).unwrap();
+//! let (ticket, value) = queue.pop().unwrap().unwrap();
+//! assert_eq!(value, "first");
+//! assert!(queue.ack(ticket).unwrap());
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+//!
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+
+use super::format::{
+    self, crc32, is_torn_tail, read_length, read_varint, take, write_varint, Persist,
+};
+
+use crate::error::DataStructureError;
+
+const PUSH: u8 = 1;
+const ACK: u8 = 2;
+
+/// Trait of the encodings of the elements in the segment file
+///
+/// `decode` must accept exactly the bytes written by `encode`.
+///
+pub trait Codec<T> {
+    /// Append the encoding of the value to the buffer
+    fn encode(&self, value: &T, out: &mut Vec<u8>) -> Result<(), DataStructureError>;
+
+    /// Decode a value from the whole input
+    fn decode(&self, input: &[u8]) -> Result<T, DataStructureError>;
+}
+
+/// Codec of the types implementing `Persist`, used by default
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PersistCodec;
+
+impl<T: Persist> Codec<T> for PersistCodec {
+    fn encode(&self, value: &T, out: &mut Vec<u8>) -> Result<(), DataStructureError> {
+        value.write_to(out);
+        Ok(())
+    }
+
+    fn decode(&self, mut input: &[u8]) -> Result<T, DataStructureError> {
+        let value = T::read_from(&mut input)?;
+        if !input.is_empty() {
+            return Err(DataStructureError::TrailingBytes);
+        }
+        Ok(value)
+    }
+}
+
+/// Codec of the types implementing serde's `Serialize` and `Deserialize`, in the postcard format
+///
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerdeCodec;
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> Codec<T> for SerdeCodec {
+    fn encode(&self, value: &T, out: &mut Vec<u8>) -> Result<(), DataStructureError> {
+        let bytes = postcard::to_allocvec(value)
+            .map_err(|_| DataStructureError::InvalidEncoding("Value can't be serialized"))?;
+        out.extend_from_slice(&bytes);
+        Ok(())
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<T, DataStructureError> {
+        let (value, rest) = postcard::take_from_bytes(input)
+            .map_err(|_| DataStructureError::InvalidEncoding("Invalid serde payload"))?;
+        if !rest.is_empty() {
+            return Err(DataStructureError::TrailingBytes);
+        }
+        Ok(value)
+    }
+}
+
+/// Location of a pushed element in the segment file
+#[derive(Debug, Clone, Copy)]
+struct Record {
+    sequence: u64,
+    offset: u64, // offset of the payload
+    length: usize,
+}
+
+/// Struct representing a FIFO persisted in a segment file
+///
+pub struct DiskFifo<T, C = PersistCodec> {
+    file: File,
+    path: PathBuf,
+    end: u64, // offset where the next record is appended
+    #[cfg(feature = "mmap")]
+    map: Option<Mmap>, // mapped on demand, None until the first read when reads go through a memory map
+    #[cfg(feature = "mmap")]
+    use_mmap: bool,
+
+    ready: VecDeque<Record>,          // records waiting to be popped
+    in_flight: BTreeMap<u64, Record>, // popped records waiting for their acknowledgment
+    next_sequence: u64,
+
+    codec: C,
+    marker: PhantomData<T>,
+}
+
+impl<T: Persist> DiskFifo<T> {
+    /// Open the queue stored in a segment file, creating an empty one if it doesn't exist
+    ///
+    /// # Arguments
+    /// * `path`: The segment file
+    ///
+    /// # Returns
+    /// io::Result<DiskFifo<T>>
+    /// The queue holds every element pushed and not acknowledged, in push order. An `InvalidData` error is returned if
+    /// a record before the end of the file is corrupted.
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::DiskFifo;
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_open_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let queue: DiskFifo<u32> = DiskFifo::open(&path).unwrap();
+    /// assert!(queue.is_empty());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::open_with_codec(path, PersistCodec)
+    }
+}
+
+impl<T, C: Codec<T>> DiskFifo<T, C> {
+    /// Open the queue stored in a segment file with a custom encoding of the elements
+    ///
+    /// # Arguments
+    /// * `path`: The segment file
+    /// * `codec`: The encoding of the elements, which must be the one the segment was written with
+    ///
+    /// # Returns
+    /// io::Result<DiskFifo<T, C>>
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::{DiskFifo, PersistCodec};
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_open_with_codec_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let mut queue = DiskFifo::open_with_codec(&path, PersistCodec).unwrap();
+    /// queue.push(&(1u8, "one".to_string())).unwrap();
+    /// assert_eq!(queue.pop().unwrap().unwrap().1, (1, "one".to_string()));
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn open_with_codec(path: impl AsRef<Path>, codec: C) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let mut queue = DiskFifo {
+            file,
+            path,
+            end: 0,
+            #[cfg(feature = "mmap")]
+            map: None,
+            #[cfg(feature = "mmap")]
+            use_mmap: false,
+            ready: VecDeque::new(),
+            in_flight: BTreeMap::new(),
+            next_sequence: 0,
+            codec,
+            marker: PhantomData,
+        };
+
+        let mut live = BTreeMap::new();
+        let mut input = bytes.as_slice();
+        while !input.is_empty() {
+            let start = (bytes.len() - input.len()) as u64;
+            let remaining = input;
+            match Self::parse_record(&mut input, start) {
+                Ok((PUSH, record)) => {
+                    queue.next_sequence = queue.next_sequence.max(record.sequence + 1);
+                    live.insert(record.sequence, record);
+                }
+                Ok((_, record)) => {
+                    live.remove(&record.sequence);
+                }
+                // Torn write at the end of the segment
+                Err(error)
+                    if is_torn_tail(remaining, input, error, |bytes| {
+                        Self::parse_record(&mut &bytes[..], 0).is_ok()
+                    }) =>
+                {
+                    break
+                }
+                Err(error) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Corrupted record at offset {}: {}", start, error),
+                    ));
+                }
+            }
+            queue.end = (bytes.len() - input.len()) as u64;
+        }
+
+        if queue.end < bytes.len() as u64 {
+            queue.file.set_len(queue.end)?;
+        }
+        queue.ready = live.into_values().collect();
+        Ok(queue)
+    }
+
+    /// Get the number of elements waiting to be popped
+    /// # Returns
+    /// The number of elements that `pop` can still return
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::DiskFifo;
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_len_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let mut queue = DiskFifo::open(&path).unwrap();
+    /// queue.push(&1u32).unwrap();
+    /// assert_eq!(queue.len(), 1);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn len(&self) -> usize {
+        self.ready.len()
+    }
+
+    /// Check if there is no element waiting to be popped
+    /// # Returns
+    /// True if `pop` would return None
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::DiskFifo;
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_is_empty_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let queue: DiskFifo<u32> = DiskFifo::open(&path).unwrap();
+    /// assert!(queue.is_empty());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.ready.is_empty()
+    }
+
+    /// Get the number of popped elements waiting for their acknowledgment
+    /// # Returns
+    /// The number of unacknowledged elements
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::DiskFifo;
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_in_flight_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let mut queue = DiskFifo::open(&path).unwrap();
+    /// queue.push(&1u32).unwrap();
+    /// queue.pop().unwrap();
+    /// assert_eq!(queue.in_flight(), 1);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Append an element to the queue
+    ///
+    /// # Arguments
+    /// * `value`: The element to be pushed
+    ///
+    /// # Returns
+    /// io::Result<()>
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::DiskFifo;
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_push_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let mut queue = DiskFifo::open(&path).unwrap();
+    /// queue.push(&"message".to_string()).unwrap();
+    /// drop(queue);
+    ///
+    /// let queue: DiskFifo<String> = DiskFifo::open(&path).unwrap();
+    /// assert_eq!(queue.len(), 1);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn push(&mut self, value: &T) -> io::Result<()> {
+        let mut payload = Vec::new();
+        self.codec
+            .encode(value, &mut payload)
+            .map_err(|reason| io::Error::new(io::ErrorKind::InvalidData, reason))?;
+
+        let sequence = self.next_sequence;
+        let record = self.append(PUSH, sequence, Some(&payload))?;
+        self.next_sequence += 1;
+        self.ready.push_back(record);
+        Ok(())
+    }
+
+    /// Take the oldest element out of the queue
+    ///
+    /// The element stays in the segment file until it is acknowledged with `ack`. If the process stops before that,
+    /// the element is delivered again when the segment is reopened.
+    ///
+    /// # Returns
+    /// io::Result<Option<(u64, T)>>
+    /// The ticket to acknowledge the element with and the element, or None if the queue is empty
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::DiskFifo;
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_pop_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let mut queue = DiskFifo::open(&path).unwrap();
+    /// queue.push(&1u32).unwrap();
+    /// queue.push(&2u32).unwrap();
+    ///
+    /// assert_eq!(queue.pop().unwrap().unwrap().1, 1);
+    /// assert_eq!(queue.pop().unwrap().unwrap().1, 2);
+    /// assert!(queue.pop().unwrap().is_none());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn pop(&mut self) -> io::Result<Option<(u64, T)>> {
+        let Some(record) = self.ready.front().copied() else {
+            return Ok(None);
+        };
+
+        let value = self.read_payload(record)?;
+        self.ready.pop_front();
+        self.in_flight.insert(record.sequence, record);
+        Ok(Some((record.sequence, value)))
+    }
+
+    /// Acknowledge a popped element, removing it from the queue for good
+    ///
+    /// # Arguments
+    /// * `ticket`: The ticket returned by `pop`
+    ///
+    /// # Returns
+    /// io::Result<bool>
+    /// Ok(true) if the element was acknowledged, Ok(false) if the ticket is unknown or was already acknowledged
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::DiskFifo;
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_ack_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let mut queue = DiskFifo::open(&path).unwrap();
+    /// queue.push(&1u32).unwrap();
+    ///
+    /// let (ticket, _) = queue.pop().unwrap().unwrap();
+    /// assert!(queue.ack(ticket).unwrap());
+    /// assert!(!queue.ack(ticket).unwrap());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn ack(&mut self, ticket: u64) -> io::Result<bool> {
+        if !self.in_flight.contains_key(&ticket) {
+            return Ok(false);
+        }
+
+        self.append(ACK, ticket, None)?;
+        self.in_flight.remove(&ticket);
+        Ok(true)
+    }
+
+    /// Put every unacknowledged element back at the front of the queue, in push order
+    ///
+    /// This is what reopening the segment does, without the restart.
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::DiskFifo;
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_requeue_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let mut queue = DiskFifo::open(&path).unwrap();
+    /// queue.push(&1u32).unwrap();
+    /// queue.pop().unwrap();
+    ///
+    /// queue.requeue();
+    /// assert_eq!(queue.pop().unwrap().unwrap().1, 1);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn requeue(&mut self) {
+        while let Some((_, record)) = self.in_flight.pop_last() {
+            self.ready.push_front(record);
+        }
+    }
+
+    /// Force the written records to the disk
+    ///
+    /// # Returns
+    /// io::Result<()>
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::DiskFifo;
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_sync_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let mut queue = DiskFifo::open(&path).unwrap();
+    /// queue.push(&1u32).unwrap();
+    /// queue.sync().unwrap();
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn sync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    /// Read the payloads through a memory map of the segment instead of a seek and a read per pop
+    ///
+    /// The segment must not be modified by another process while the queue is open.
+    ///
+    /// # Arguments
+    /// * `enabled`: True to read through a memory map, false to go back to plain reads
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::DiskFifo;
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_set_mmap_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let mut queue = DiskFifo::open(&path).unwrap();
+    /// queue.set_mmap(true);
+    ///
+    /// queue.push(&1u32).unwrap();
+    /// assert_eq!(queue.pop().unwrap().unwrap().1, 1);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(feature = "mmap")]
+    pub fn set_mmap(&mut self, enabled: bool) {
+        self.use_mmap = enabled;
+        self.map = None;
+    }
+
+    /// Rewrite the segment file with the live records only, reclaiming the space of the acknowledged ones
+    ///
+    /// The new segment is written and synced to a temporary file, then renamed over the old one, so a crash during
+    /// the compaction leaves either the old or the new segment in place.
+    ///
+    /// # Returns
+    /// io::Result<()>
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::persist::disk_fifo::DiskFifo;
+    ///
+    /// let path = std::env::temp_dir().join("disk_fifo_compact_example.segment");
+    /// # let _ = std::fs::remove_file(&path);
+    /// let mut queue = DiskFifo::open(&path).unwrap();
+    /// for i in 0..100u32 {
+    ///     queue.push(&i).unwrap();
+    ///     let (ticket, _) = queue.pop().unwrap().unwrap();
+    ///     queue.ack(ticket).unwrap();
+    /// }
+    ///
+    /// queue.compact().unwrap();
+    /// assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn compact(&mut self) -> io::Result<()> {
+        // Live records, keeping the in-flight ones ahead of the ready ones
+        let records: Vec<Record> = self
+            .in_flight
+            .values()
+            .chain(self.ready.iter())
+            .copied()
+            .collect();
+
+        let mut out = Vec::new();
+        let mut moved = Vec::with_capacity(records.len());
+        for record in records {
+            let payload = self.read_bytes(record)?;
+            let offset = out.len() as u64;
+            moved.push(Self::encode_record(
+                &mut out,
+                offset,
+                PUSH,
+                record.sequence,
+                Some(&payload),
+            ));
+        }
+
+        format::save_bytes(&self.path, &out)?;
+        self.file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+        self.end = out.len() as u64;
+        #[cfg(feature = "mmap")]
+        {
+            self.map = None;
+        }
+
+        let in_flight = self.in_flight.len();
+        self.in_flight = moved[..in_flight]
+            .iter()
+            .map(|record| (record.sequence, *record))
+            .collect();
+        self.ready = moved[in_flight..].iter().copied().collect();
+        Ok(())
+    }
+
+    // Append a record to the segment and return its location
+    fn append(&mut self, tag: u8, sequence: u64, payload: Option<&[u8]>) -> io::Result<Record> {
+        let mut out = Vec::new();
+        let record = Self::encode_record(&mut out, self.end, tag, sequence, payload);
+
+        self.file.seek(SeekFrom::Start(self.end))?;
+        if let Err(error) = self.file.write_all(&out) {
+            // Drop whatever part of the record made it to the file, so it doesn't shadow the next one
+            self.file.set_len(self.end)?;
+            return Err(error);
+        }
+        self.end += out.len() as u64;
+        Ok(record)
+    }
+
+    // Encode a record placed at `offset` in the segment
+    fn encode_record(
+        out: &mut Vec<u8>,
+        offset: u64,
+        tag: u8,
+        sequence: u64,
+        payload: Option<&[u8]>,
+    ) -> Record {
+        let start = out.len();
+        out.push(tag);
+        write_varint(out, sequence);
+
+        let mut length = 0;
+        if let Some(payload) = payload {
+            length = payload.len();
+            write_varint(out, length as u64);
+            out.extend_from_slice(payload);
+        }
+        let payload_offset = offset + (out.len() - start - length) as u64;
+
+        let checksum = crc32(&out[start..]);
+        out.extend_from_slice(&checksum.to_le_bytes());
+
+        Record {
+            sequence,
+            offset: payload_offset,
+            length,
+        }
+    }
+
+    // Decode the record at the front of the input, which starts at `offset` in the segment
+    fn parse_record(input: &mut &[u8], offset: u64) -> Result<(u8, Record), DataStructureError> {
+        let start = *input;
+
+        let tag = take(input, 1)?[0];
+        let sequence = read_varint(input)?;
+        let length = match tag {
+            PUSH => read_length(input)?,
+            ACK => 0,
+            _ => return Err(DataStructureError::InvalidEncoding("Unknown record")),
+        };
+        take(input, length)?;
+
+        let body = &start[..start.len() - input.len()];
+        if crc32(body).to_le_bytes() != take(input, 4)? {
+            return Err(DataStructureError::ChecksumMismatch);
+        }
+
+        Ok((
+            tag,
+            Record {
+                sequence,
+                offset: offset + (body.len() - length) as u64,
+                length,
+            },
+        ))
+    }
+
+    fn read_bytes(&mut self, record: Record) -> io::Result<Vec<u8>> {
+        let mut payload = vec![0; record.length];
+        self.file.seek(SeekFrom::Start(record.offset))?;
+        self.file.read_exact(&mut payload)?;
+        Ok(payload)
+    }
+
+    fn read_payload(&mut self, record: Record) -> io::Result<T> {
+        #[cfg(feature = "mmap")]
+        if self.use_mmap {
+            let start = record.offset as usize;
+            let end = start + record.length;
+            if self.map.as_ref().is_none_or(|map| map.len() < end) {
+                // SAFETY: the queue never rewrites the bytes of a record in place, and the segment must not be modified
+                // by another process while it is open
+                self.map = Some(unsafe { Mmap::map(&self.file)? });
+            }
+            let map = self.map.as_ref().unwrap();
+            return self
+                .codec
+                .decode(&map[start..end])
+                .map_err(|reason| io::Error::new(io::ErrorKind::InvalidData, reason));
+        }
+
+        let payload = self.read_bytes(record)?;
+        self.codec
+            .decode(&payload)
+            .map_err(|reason| io::Error::new(io::ErrorKind::InvalidData, reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn segment(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("disk_fifo_{}_{}.segment", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_disk_fifo_restart() {
+        let path = segment("restart");
+
+        let mut queue = DiskFifo::open(&path).unwrap();
+        for i in 0..5u32 {
+            queue.push(&i).unwrap();
+        }
+        let (first, _) = queue.pop().unwrap().unwrap();
+        queue.pop().unwrap();
+        queue.ack(first).unwrap();
+        drop(queue);
+
+        // 0 was acknowledged, 1 was popped only
+        let mut queue: DiskFifo<u32> = DiskFifo::open(&path).unwrap();
+        assert_eq!(queue.len(), 4);
+        let mut values = Vec::new();
+        while let Some((ticket, value)) = queue.pop().unwrap() {
+            values.push(value);
+            queue.ack(ticket).unwrap();
+        }
+        assert_eq!(values, vec![1, 2, 3, 4]);
+
+        // New pushes don't reuse the sequence numbers of the acknowledged records
+        queue.push(&5).unwrap();
+        drop(queue);
+        let mut queue: DiskFifo<u32> = DiskFifo::open(&path).unwrap();
+        assert_eq!(queue.pop().unwrap().unwrap().1, 5);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_disk_fifo_torn_write() {
+        let path = segment("torn");
+
+        let mut queue = DiskFifo::open(&path).unwrap();
+        queue.push(&"kept".to_string()).unwrap();
+        queue.push(&"torn".to_string()).unwrap();
+        drop(queue);
+
+        // Simulate a crash in the middle of the last write
+        let length = fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(length - 3)
+            .unwrap();
+
+        let mut queue: DiskFifo<String> = DiskFifo::open(&path).unwrap();
+        assert_eq!(queue.len(), 1);
+        queue.push(&"next".to_string()).unwrap();
+        drop(queue);
+
+        let mut queue: DiskFifo<String> = DiskFifo::open(&path).unwrap();
+        assert_eq!(queue.pop().unwrap().unwrap().1, "kept");
+        assert_eq!(queue.pop().unwrap().unwrap().1, "next");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_disk_fifo_corrupted_record() {
+        let path = segment("corrupted");
+
+        let mut queue = DiskFifo::open(&path).unwrap();
+        for i in 0..3u32 {
+            queue.push(&i).unwrap();
+        }
+        drop(queue);
+
+        // A flipped byte in the first record must not silently drop the records after it
+        let mut bytes = fs::read(&path).unwrap();
+        let original = bytes.clone();
+        bytes[3] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+
+        let error = DiskFifo::<u32>::open(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+
+        // The same damage on the last record is a torn write
+        let mut bytes = original;
+        let last = bytes.len() - 2;
+        bytes[last] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+
+        let queue: DiskFifo<u32> = DiskFifo::open(&path).unwrap();
+        assert_eq!(queue.len(), 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_disk_fifo_corrupted_length() {
+        let path = segment("corrupted_length");
+
+        let mut queue = DiskFifo::open(&path).unwrap();
+        for i in 0..100u8 {
+            queue.push(&i).unwrap();
+        }
+        drop(queue);
+
+        // A length running past the end of the segment swallows the records after it, it isn't a torn write
+        let mut bytes = fs::read(&path).unwrap();
+        let original = bytes.clone();
+        assert_eq!(bytes.len(), 800);
+        bytes[2] = 0xff;
+        bytes[3] = 0x7f;
+        fs::write(&path, &bytes).unwrap();
+
+        let error = DiskFifo::<u8>::open(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+
+        // The same damage on the last record is a torn write
+        let mut bytes = original;
+        let last = bytes.len() - 8;
+        bytes[last + 2] = 0xff;
+        bytes[last + 3] = 0x7f;
+        fs::write(&path, &bytes).unwrap();
+
+        let queue: DiskFifo<u8> = DiskFifo::open(&path).unwrap();
+        assert_eq!(queue.len(), 99);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 792);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_disk_fifo_mmap() {
+        let path = segment("mmap");
+
+        let mut queue = DiskFifo::open(&path).unwrap();
+        queue.set_mmap(true);
+
+        // Pops interleaved with pushes make the map grow with the segment
+        let mut values = Vec::new();
+        for i in 0..50u64 {
+            queue.push(&i).unwrap();
+            queue.push(&(i + 1000)).unwrap();
+            let (ticket, value) = queue.pop().unwrap().unwrap();
+            queue.ack(ticket).unwrap();
+            values.push(value);
+        }
+        queue.compact().unwrap();
+        while let Some((_, value)) = queue.pop().unwrap() {
+            values.push(value);
+        }
+
+        let expected: Vec<u64> = (0..50).flat_map(|i| [i, i + 1000]).collect();
+        assert_eq!(values, expected);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_disk_fifo_serde_codec() {
+        let path = segment("serde");
+
+        let mut queue = DiskFifo::open_with_codec(&path, SerdeCodec).unwrap();
+        queue.push(&(1.5f64, Some('x'))).unwrap();
+        queue.push(&(-2.0, None)).unwrap();
+        drop(queue);
+
+        let mut queue: DiskFifo<(f64, Option<char>), _> =
+            DiskFifo::open_with_codec(&path, SerdeCodec).unwrap();
+        assert_eq!(queue.pop().unwrap().unwrap().1, (1.5, Some('x')));
+        assert_eq!(queue.pop().unwrap().unwrap().1, (-2.0, None));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_disk_fifo_compact() {
+        let path = segment("compact");
+
+        let mut queue = DiskFifo::open(&path).unwrap();
+        for i in 0..10u64 {
+            queue.push(&i).unwrap();
+        }
+        for _ in 0..6 {
+            let (ticket, _) = queue.pop().unwrap().unwrap();
+            queue.ack(ticket).unwrap();
+        }
+        let (in_flight, _) = queue.pop().unwrap().unwrap();
+
+        let before = fs::metadata(&path).unwrap().len();
+        queue.compact().unwrap();
+        assert!(fs::metadata(&path).unwrap().len() < before);
+
+        // The in-flight element can still be acknowledged after the compaction
+        assert!(queue.ack(in_flight).unwrap());
+        queue.push(&10).unwrap();
+        drop(queue);
+
+        let mut queue: DiskFifo<u64> = DiskFifo::open(&path).unwrap();
+        let mut values = Vec::new();
+        while let Some((_, value)) = queue.pop().unwrap() {
+            values.push(value);
+        }
+        assert_eq!(values, vec![7, 8, 9, 10]);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,513 @@
+//! This module implements a compact, versioned binary format to snapshot containers and load them back, without serde.
+//! Types opt in by implementing the `Persist` trait, which encodes a value into a byte buffer and decodes it back.
+//! A snapshot wraps the encoded value in a frame with a magic number, the format version, the payload length and a
+//! checksum, so truncated or corrupted files are detected when they are read back.
+//!
+//! # Performance
+//! - O(n) to encode or decode a value of n bytes
+//! - The checksum adds a single pass over the payload
+//!
+//! # Implementation Details
+//! - Unsigned integers are written as LEB128 varints and signed ones are zigzag encoded first, so small numbers take a
+//!   single byte.
+//! - Strings and sequences are prefixed with their length.
+//! - A frame is `MAGIC`, the format version as a varint, the payload length as a varint, the payload and the CRC-32 of
+//!   the payload in little endian.
+//! - Readers accept every version up to `FORMAT_VERSION` and reject newer ones. A new version may only add new
+//!   encodings; the encodings of a released version never change, so old snapshots stay readable.
+//!
+//! # Usage
+//! ```
+//! use data_structures::persist::format::{from_bytes, to_bytes};
+//!
+//! let value = vec![(1u32, "one".to_string()), (2, "two".to_string())];
+//!
+//! let bytes = to_bytes(&value);
+//! let loaded: Vec<(u32, String)> = from_bytes(&bytes).unwrap();
+//! assert_eq!(loaded, value);
+//!
+//! // A corrupted snapshot is rejected
+//! let mut corrupted = bytes.clone();
+//! corrupted[8] ^= 0xff;
+//! assert!(from_bytes::<Vec<(u32, String)>>(&corrupted).is_err());
+//! ```
+//!
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::error::DataStructureError;
+
+/// Magic number at the start of every snapshot
+pub const MAGIC: [u8; 4] = *b"GMDS";
+
+/// Version of the format written by this build
+pub const FORMAT_VERSION: u64 = 1;
+
+/// Trait of the values that can be written to the binary format
+///
+/// Implementations must be deterministic and `read_from` must consume exactly the bytes written by `write_to`.
+///
+pub trait Persist: Sized {
+    /// Append the encoding of the value to the buffer
+    fn write_to(&self, out: &mut Vec<u8>);
+
+    /// Decode a value from the front of the input, advancing it past the consumed bytes
+    fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError>;
+}
+
+/// Write a value in a snapshot frame
+///
+/// # Arguments
+/// * `value`: The value to be written
+///
+/// # Returns
+/// The bytes of the snapshot
+///
+/// # Example
+/// ```
+/// use data_structures::persist::format::{to_bytes, MAGIC};
+///
+/// let bytes = to_bytes(&42u64);
+/// assert_eq!(bytes[..4], MAGIC);
+/// ```
+pub fn to_bytes<T: Persist>(value: &T) -> Vec<u8> {
+    let mut payload = Vec::new();
+    value.write_to(&mut payload);
+    frame(&payload)
+}
+
+/// Wrap an encoded payload in a snapshot frame
+pub(crate) fn frame(payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len() + 16);
+    out.extend_from_slice(&MAGIC);
+    write_varint(&mut out, FORMAT_VERSION);
+    write_varint(&mut out, payload.len() as u64);
+    out.extend_from_slice(payload);
+    out.extend_from_slice(&crc32(payload).to_le_bytes());
+    out
+}
+
+/// Read a value back from a snapshot frame
+///
+/// # Arguments
+/// * `bytes`: The bytes of the snapshot
+///
+/// # Returns
+/// Result<T, DataStructureError>
+/// Ok with the value, Err if the snapshot is truncated, corrupted or was written by a newer format version
+///
+/// # Example
+/// ```
+/// use data_structures::error::DataStructureError;
+/// use data_structures::persist::format::{from_bytes, to_bytes};
+///
+/// let bytes = to_bytes(&"hello".to_string());
+/// assert_eq!(from_bytes::<String>(&bytes), Ok("hello".to_string()));
+/// assert_eq!(from_bytes::<String>(&bytes[..6]), Err(DataStructureError::UnexpectedEnd));
+/// ```
+pub fn from_bytes<T: Persist>(bytes: &[u8]) -> Result<T, DataStructureError> {
+    let mut input = bytes;
+
+    if take(&mut input, MAGIC.len())? != MAGIC {
+        return Err(DataStructureError::NotASnapshot);
+    }
+    let version = read_varint(&mut input)?;
+    if version > FORMAT_VERSION {
+        return Err(DataStructureError::UnsupportedVersion(version));
+    }
+
+    let length = read_length(&mut input)?;
+    let mut payload = take(&mut input, length)?;
+    let checksum = take(&mut input, 4)?;
+    if crc32(payload).to_le_bytes() != checksum {
+        return Err(DataStructureError::ChecksumMismatch);
+    }
+    if !input.is_empty() {
+        return Err(DataStructureError::TrailingBytes);
+    }
+
+    let value = T::read_from(&mut payload)?;
+    if !payload.is_empty() {
+        return Err(DataStructureError::TrailingBytes);
+    }
+    Ok(value)
+}
+
+/// Write a snapshot of a value to a file, replacing it atomically
+///
+/// The snapshot is first written and synced to a temporary file next to the destination, then renamed over it, so a
+/// crash never leaves a half written snapshot behind.
+///
+/// # Arguments
+/// * `path`: The destination file
+/// * `value`: The value to be written
+///
+/// # Returns
+/// io::Result<()>
+///
+/// # Example
+/// ```
+/// use data_structures::persist::format::{load, save};
+///
+/// let path = std::env::temp_dir().join("format_save_example.snapshot");
+/// save(&path, &vec![1u8, 2, 3]).unwrap();
+/// assert_eq!(load::<Vec<u8>>(&path).unwrap(), vec![1, 2, 3]);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn save<T: Persist>(path: impl AsRef<Path>, value: &T) -> io::Result<()> {
+    save_bytes(path.as_ref(), &to_bytes(value))
+}
+
+/// Replace a file atomically with the given bytes
+///
+/// The temporary file is synced before the rename and the directory after it, so once this returns the new content is
+/// on the disk, and a crash at any point leaves either the old or the new file under `path`.
+pub(crate) fn save_bytes(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+
+    let mut file = File::create(&temporary)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temporary, path)?;
+    sync_parent(path)
+}
+
+/// Force the directory entry of a file to the disk, making a rename or a creation durable
+pub(crate) fn sync_parent(path: &Path) -> io::Result<()> {
+    // Only Unix lets a directory be opened and synced like a file; elsewhere there is no portable way to do it
+    #[cfg(unix)]
+    {
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Read a snapshot of a value from a file
+///
+/// # Arguments
+/// * `path`: The snapshot file
+///
+/// # Returns
+/// io::Result<T>
+/// A decoding failure is reported as an `InvalidData` error carrying the reason
+///
+/// # Example
+/// ```
+/// use data_structures::persist::format::load;
+///
+/// assert!(load::<u32>("/nonexistent/snapshot").is_err());
+/// ```
+pub fn load<T: Persist>(path: impl AsRef<Path>) -> io::Result<T> {
+    let bytes = fs::read(path)?;
+    from_bytes(&bytes).map_err(|reason| io::Error::new(io::ErrorKind::InvalidData, reason))
+}
+
+/// Append an unsigned integer as a LEB128 varint
+///
+/// # Arguments
+/// * `out`: The buffer
+/// * `value`: The integer to be written
+///
+/// # Example
+/// ```
+/// use data_structures::persist::format::write_varint;
+///
+/// let mut out = Vec::new();
+/// write_varint(&mut out, 300);
+/// assert_eq!(out, vec![0xac, 0x02]);
+/// ```
+pub fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Read a LEB128 varint from the front of the input
+///
+/// # Arguments
+/// * `input`: The input, advanced past the varint
+///
+/// # Returns
+/// Result<u64, DataStructureError>
+/// Ok with the integer, Err if the input ends early or the varint overflows 64 bits
+///
+/// # Example
+/// ```
+/// use data_structures::persist::format::read_varint;
+///
+/// let mut input: &[u8] = &[0xac, 0x02, 0x01];
+/// assert_eq!(read_varint(&mut input), Ok(300));
+/// assert_eq!(input, &[0x01]);
+/// ```
+pub fn read_varint(input: &mut &[u8]) -> Result<u64, DataStructureError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = take(input, 1)?[0];
+        if shift == 63 && byte > 1 {
+            return Err(DataStructureError::InvalidEncoding("Varint overflow"));
+        }
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DataStructureError::InvalidEncoding("Varint overflow"))
+}
+
+/// Read a length prefix, checking that it fits in memory on this platform
+pub(crate) fn read_length(input: &mut &[u8]) -> Result<usize, DataStructureError> {
+    usize::try_from(read_varint(input)?)
+        .map_err(|_| DataStructureError::InvalidEncoding("Length out of range"))
+}
+
+/// Split the first `length` bytes off the input
+pub(crate) fn take<'a>(
+    input: &mut &'a [u8],
+    length: usize,
+) -> Result<&'a [u8], DataStructureError> {
+    if input.len() < length {
+        return Err(DataStructureError::UnexpectedEnd);
+    }
+    let (head, tail) = input.split_at(length);
+    *input = tail;
+    Ok(head)
+}
+
+/// Check if a record that failed to decode is the torn tail of an append-only file, rather than corruption
+///
+/// A crash in the middle of an append leaves a last record that is cut short by the end of the file, or that is
+/// filled with zeros by the file system. A record is only torn if it runs to the end of the file and no valid record
+/// starts after its first byte: a corrupted length also runs past the end, but the records it swallowed still parse.
+///
+/// # Arguments
+/// * `record`: The input from the start of the failed record to the end of the file
+/// * `rest`: What was left of the input when the decoding failed
+/// * `error`: The decoding failure
+/// * `parses`: Check if a valid record starts at the beginning of the bytes
+pub(crate) fn is_torn_tail(
+    record: &[u8],
+    rest: &[u8],
+    error: DataStructureError,
+    parses: impl Fn(&[u8]) -> bool,
+) -> bool {
+    if record.iter().all(|byte| *byte == 0) {
+        return true;
+    }
+    let cut_short = error == DataStructureError::UnexpectedEnd || rest.is_empty();
+    cut_short && !(1..record.len()).any(|start| parses(&record[start..]))
+}
+
+/// CRC-32 (IEEE) of the bytes, computed bit by bit
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+macro_rules! persist_unsigned {
+    ($($ty:ty),*) => {$(
+        impl Persist for $ty {
+            fn write_to(&self, out: &mut Vec<u8>) {
+                write_varint(out, *self as u64);
+            }
+
+            fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+                <$ty>::try_from(read_varint(input)?).map_err(|_| DataStructureError::InvalidEncoding("Integer out of range"))
+            }
+        }
+    )*};
+}
+
+macro_rules! persist_signed {
+    ($($ty:ty),*) => {$(
+        impl Persist for $ty {
+            fn write_to(&self, out: &mut Vec<u8>) {
+                // Zigzag encoding keeps small negative numbers short
+                let value = *self as i64;
+                write_varint(out, ((value << 1) ^ (value >> 63)) as u64);
+            }
+
+            fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+                let encoded = read_varint(input)?;
+                let value = (encoded >> 1) as i64 ^ -((encoded & 1) as i64);
+                <$ty>::try_from(value).map_err(|_| DataStructureError::InvalidEncoding("Integer out of range"))
+            }
+        }
+    )*};
+}
+
+persist_unsigned!(u16, u32, u64, usize);
+persist_signed!(i8, i16, i32, i64, isize);
+
+impl Persist for u8 {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+
+    fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+        Ok(take(input, 1)?[0])
+    }
+}
+
+impl Persist for bool {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+        match u8::read_from(input)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(DataStructureError::InvalidEncoding("Invalid boolean")),
+        }
+    }
+}
+
+impl Persist for String {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        write_varint(out, self.len() as u64);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+        let length = read_length(input)?;
+        let bytes = take(input, length)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| DataStructureError::InvalidEncoding("Invalid UTF-8 string"))
+    }
+}
+
+impl<T: Persist> Persist for Option<T> {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        self.is_some().write_to(out);
+        if let Some(value) = self {
+            value.write_to(out);
+        }
+    }
+
+    fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+        if bool::read_from(input)? {
+            Ok(Some(T::read_from(input)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Persist> Persist for Vec<T> {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        write_varint(out, self.len() as u64);
+        for value in self {
+            value.write_to(out);
+        }
+    }
+
+    fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+        let length = read_length(input)?;
+
+        // Every element takes at least one byte, which bounds the allocation of a corrupted length
+        let mut values = Vec::with_capacity(length.min(input.len()));
+        for _ in 0..length {
+            values.push(T::read_from(input)?);
+        }
+        Ok(values)
+    }
+}
+
+impl<A: Persist, B: Persist> Persist for (A, B) {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        self.0.write_to(out);
+        self.1.write_to(out);
+    }
+
+    fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+        Ok((A::read_from(input)?, B::read_from(input)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_round_trip() {
+        for value in [0i64, 1, -1, 63, -64, 64, i64::MIN, i64::MAX] {
+            assert_eq!(from_bytes::<i64>(&to_bytes(&value)), Ok(value));
+        }
+        for value in [0u64, 127, 128, u64::MAX] {
+            assert_eq!(from_bytes::<u64>(&to_bytes(&value)), Ok(value));
+        }
+
+        // Small values take a single byte
+        let mut out = Vec::new();
+        (-3i32).write_to(&mut out);
+        assert_eq!(out.len(), 1);
+
+        // Decoding into a narrower type fails instead of truncating
+        assert_eq!(
+            from_bytes::<u16>(&to_bytes(&70_000u32)),
+            Err(DataStructureError::InvalidEncoding("Integer out of range"))
+        );
+    }
+
+    #[test]
+    fn test_frame_validation() {
+        let bytes = to_bytes(&vec![Some(true), None]);
+        assert_eq!(from_bytes(&bytes), Ok(vec![Some(true), None]));
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            from_bytes::<Vec<Option<bool>>>(&bad_magic),
+            Err(DataStructureError::NotASnapshot)
+        );
+
+        let mut newer = bytes.clone();
+        newer[4] = FORMAT_VERSION as u8 + 1;
+        assert_eq!(
+            from_bytes::<Vec<Option<bool>>>(&newer),
+            Err(DataStructureError::UnsupportedVersion(FORMAT_VERSION + 1))
+        );
+
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 5;
+        corrupted[last] ^= 1;
+        assert_eq!(
+            from_bytes::<Vec<Option<bool>>>(&corrupted),
+            Err(DataStructureError::ChecksumMismatch)
+        );
+
+        for length in 0..bytes.len() {
+            assert!(from_bytes::<Vec<Option<bool>>>(&bytes[..length]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_crc32() {
+        // Standard check value of CRC-32/IEEE
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+}
@@ -0,0 +1,552 @@
+//! This module implements a write-ahead log that makes any loggable container durable.
+//! Each mutating operation is appended to a log file before it is applied, so replaying the log after a crash rebuilds
+//! the container. Checkpoints write a snapshot of the container and empty the log, keeping the replay short.
+//!
+//! # Performance
+//! - O(1) amortized log overhead per operation, on top of the operation itself
+//! - O(n) for a checkpoint, which writes the whole container
+//! - Recovery is O(snapshot + log length)
+//!
+//! # Implementation Details
+//! - A container opts in by implementing `Loggable`: its operations must be `Persist` values and applying them must be
+//!   deterministic, so the replay reaches the same state as the original run.
+//! - The log is a file of checksummed records holding a sequence number and an operation. The snapshot stores the
+//!   sequence number of the first operation it doesn't include, so records already covered by the snapshot are
+//!   skipped if a crash happens between writing the snapshot and truncating the log.
+//! - A torn record at the end of the log is truncated away on recovery. The operation it held was never applied. A bad
+//!   record is only torn if it runs to the end of the log and no valid record starts after it; anything else, such as
+//!   a corrupted length running past the end, is corruption, and recovery fails with an `InvalidData` error without
+//!   truncating the log instead of dropping the operations after it.
+//! - Writes go to the operating system cache; call `sync` to force them to the disk. A checkpoint syncs the snapshot
+//!   before it empties the log, so the operations made durable by `sync` survive a power loss in the middle of it.
+//!
+//! # Usage
+//! ```
+//! use data_structures::linked_list::fifo::{FifoOp, FIFO};
+//! use data_structures::persist::wal::Wal;
+//!
+//! let path = std::env::temp_dir().join("wal_usage_example");
+//! # let _ = std::fs::remove_file(path.with_extension("log"));
+//! # let _ = std::fs::remove_file(path.with_extension("snapshot"));
+//!
+//! let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+//! wal.apply(FifoOp::Push(1)).unwrap().unwrap();
+//! wal.apply(FifoOp::Push(2)).unwrap().unwrap();
+//! assert_eq!(wal.apply(FifoOp::Pop).unwrap(), Ok(Some(1)));
+//! drop(wal); // crash
+//!
+//! let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+//! assert_eq!(wal.container().len(), 1);
+//! assert_eq!(wal.apply(FifoOp::Pop).unwrap(), Ok(Some(2)));
+//! # std::fs::remove_file(path.with_extension("log")).unwrap();
+//! ```
+//!
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use super::format::{
+    self, crc32, is_torn_tail, read_length, read_varint, take, write_varint, Persist,
+};
+
+use crate::error::DataStructureError;
+
+/// Trait of the containers whose mutations can be logged and replayed
+///
+pub trait Loggable: Persist + Default {
+    /// A mutating operation on the container
+    type Op: Persist;
+
+    /// What applying an operation returns, such as a popped element
+    type Output;
+
+    /// Apply an operation. It must only depend on the container and the operation.
+    fn apply(&mut self, op: Self::Op) -> Self::Output;
+}
+
+/// Struct representing a container made durable by a write-ahead log
+///
+pub struct Wal<C> {
+    container: C,
+
+    log: File,
+    log_path: PathBuf,
+    log_end: u64,
+    snapshot_path: PathBuf,
+
+    next_sequence: u64,
+    logged: usize, // operations in the log since the last checkpoint
+    checkpoint_interval: usize,
+}
+
+impl<C: Loggable> Wal<C> {
+    /// Recover a container from its snapshot and log, or start an empty one
+    ///
+    /// The snapshot and the log are stored next to `path`, with the `snapshot` and `log` extensions.
+    ///
+    /// # Arguments
+    /// * `path`: The base path of the snapshot and log files
+    ///
+    /// # Returns
+    /// io::Result<Wal<C>>
+    /// An `InvalidData` error is returned if the snapshot or a record before the end of the log is corrupted
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::fifo::FIFO;
+    /// use data_structures::persist::wal::Wal;
+    ///
+    /// let path = std::env::temp_dir().join("wal_open_example");
+    /// # let _ = std::fs::remove_file(path.with_extension("log"));
+    /// # let _ = std::fs::remove_file(path.with_extension("snapshot"));
+    /// let wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+    /// assert!(wal.container().is_empty());
+    /// # std::fs::remove_file(path.with_extension("log")).unwrap();
+    /// ```
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let log_path = path.as_ref().with_extension("log");
+        let snapshot_path = path.as_ref().with_extension("snapshot");
+
+        let (first_sequence, mut container) = if snapshot_path.exists() {
+            format::load::<(u64, C)>(&snapshot_path)?
+        } else {
+            (0, C::default())
+        };
+
+        let mut log = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&log_path)?;
+        let mut bytes = Vec::new();
+        log.read_to_end(&mut bytes)?;
+
+        let mut next_sequence = first_sequence;
+        let mut logged = 0;
+        let mut input = bytes.as_slice();
+        let mut log_end = 0;
+        while !input.is_empty() {
+            let remaining = input;
+            // A record with a matching checksum that doesn't decode is never a torn write
+            let (sequence, op) = match Self::parse_record(&mut input) {
+                Ok(record) => Self::decode_record(record),
+                // Torn write at the end of the log
+                Err(error)
+                    if is_torn_tail(remaining, input, error, |bytes| {
+                        Self::parse_record(&mut &bytes[..]).is_ok()
+                    }) =>
+                {
+                    break
+                }
+                Err(error) => Err(error),
+            }
+            .map_err(|error| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Corrupted record at offset {}: {}", log_end, error),
+                )
+            })?;
+            log_end = (bytes.len() - input.len()) as u64;
+
+            if sequence >= next_sequence {
+                container.apply(op);
+                next_sequence = sequence + 1;
+                logged += 1;
+            }
+        }
+
+        if log_end < bytes.len() as u64 {
+            log.set_len(log_end)?;
+        }
+
+        Ok(Wal {
+            container,
+            log,
+            log_path,
+            log_end,
+            snapshot_path,
+            next_sequence,
+            logged,
+            checkpoint_interval: 0,
+        })
+    }
+
+    /// Get read-only access to the container
+    /// # Returns
+    /// A reference to the container
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::fifo::{FifoOp, FIFO};
+    /// use data_structures::persist::wal::Wal;
+    ///
+    /// let path = std::env::temp_dir().join("wal_container_example");
+    /// # let _ = std::fs::remove_file(path.with_extension("log"));
+    /// # let _ = std::fs::remove_file(path.with_extension("snapshot"));
+    /// let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+    /// wal.apply(FifoOp::Push(7)).unwrap().unwrap();
+    /// assert_eq!(wal.container().len(), 1);
+    /// # std::fs::remove_file(path.with_extension("log")).unwrap();
+    /// ```
+    pub fn container(&self) -> &C {
+        &self.container
+    }
+
+    /// Get the number of operations logged since the last checkpoint
+    /// # Returns
+    /// The number of operations a recovery would replay
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::fifo::{FifoOp, FIFO};
+    /// use data_structures::persist::wal::Wal;
+    ///
+    /// let path = std::env::temp_dir().join("wal_logged_example");
+    /// # let _ = std::fs::remove_file(path.with_extension("log"));
+    /// # let _ = std::fs::remove_file(path.with_extension("snapshot"));
+    /// let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+    /// wal.apply(FifoOp::Push(7)).unwrap().unwrap();
+    /// assert_eq!(wal.logged(), 1);
+    /// # std::fs::remove_file(path.with_extension("log")).unwrap();
+    /// ```
+    pub fn logged(&self) -> usize {
+        self.logged
+    }
+
+    /// Set after how many logged operations a checkpoint is taken automatically
+    /// # Arguments
+    /// * `interval`: The number of operations between checkpoints. If 0, checkpoints are only taken explicitly.
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::fifo::{FifoOp, FIFO};
+    /// use data_structures::persist::wal::Wal;
+    ///
+    /// let path = std::env::temp_dir().join("wal_interval_example");
+    /// # let _ = std::fs::remove_file(path.with_extension("log"));
+    /// # let _ = std::fs::remove_file(path.with_extension("snapshot"));
+    /// let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+    /// wal.set_checkpoint_interval(2);
+    ///
+    /// wal.apply(FifoOp::Push(1)).unwrap().unwrap();
+    /// wal.apply(FifoOp::Push(2)).unwrap().unwrap();
+    /// assert_eq!(wal.logged(), 0);
+    /// # std::fs::remove_file(path.with_extension("log")).unwrap();
+    /// # std::fs::remove_file(path.with_extension("snapshot")).unwrap();
+    /// ```
+    pub fn set_checkpoint_interval(&mut self, interval: usize) {
+        self.checkpoint_interval = interval;
+    }
+
+    /// Log an operation and apply it to the container
+    ///
+    /// # Arguments
+    /// * `op`: The operation
+    ///
+    /// # Returns
+    /// io::Result<C::Output>
+    /// The output of the operation, or an error if it couldn't be logged, in which case it isn't applied either
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::fifo::{FifoOp, FIFO};
+    /// use data_structures::persist::wal::Wal;
+    ///
+    /// let path = std::env::temp_dir().join("wal_apply_example");
+    /// # let _ = std::fs::remove_file(path.with_extension("log"));
+    /// # let _ = std::fs::remove_file(path.with_extension("snapshot"));
+    /// let mut wal: Wal<FIFO<String>> = Wal::open(&path).unwrap();
+    /// assert_eq!(wal.apply(FifoOp::Push("a".to_string())).unwrap(), Ok(None));
+    /// assert_eq!(wal.apply(FifoOp::Pop).unwrap(), Ok(Some("a".to_string())));
+    /// assert_eq!(wal.apply(FifoOp::Pop).unwrap(), Ok(None));
+    /// # std::fs::remove_file(path.with_extension("log")).unwrap();
+    /// ```
+    pub fn apply(&mut self, op: C::Op) -> io::Result<C::Output> {
+        let mut record = Vec::new();
+        write_varint(&mut record, self.next_sequence);
+        op.write_to(&mut record);
+
+        let mut out = Vec::with_capacity(record.len() + 8);
+        write_varint(&mut out, record.len() as u64);
+        out.extend_from_slice(&record);
+        out.extend_from_slice(&crc32(&out).to_le_bytes());
+
+        if let Err(error) = self.log.write_all(&out) {
+            // Drop whatever part of the record made it to the file, so it isn't replayed
+            self.log.set_len(self.log_end)?;
+            return Err(error);
+        }
+        self.log_end += out.len() as u64;
+        self.next_sequence += 1;
+        self.logged += 1;
+
+        let output = self.container.apply(op);
+        if self.checkpoint_interval != 0 && self.logged >= self.checkpoint_interval {
+            self.checkpoint()?;
+        }
+        Ok(output)
+    }
+
+    /// Write a snapshot of the container and empty the log
+    ///
+    /// The snapshot is synced to the disk before the log is truncated, and the truncation is synced too.
+    ///
+    /// # Returns
+    /// io::Result<()>
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::fifo::{FifoOp, FIFO};
+    /// use data_structures::persist::wal::Wal;
+    ///
+    /// let path = std::env::temp_dir().join("wal_checkpoint_example");
+    /// # let _ = std::fs::remove_file(path.with_extension("log"));
+    /// # let _ = std::fs::remove_file(path.with_extension("snapshot"));
+    /// let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+    /// wal.apply(FifoOp::Push(1)).unwrap().unwrap();
+    /// wal.checkpoint().unwrap();
+    /// drop(wal);
+    ///
+    /// let wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+    /// assert_eq!(wal.logged(), 0);
+    /// assert_eq!(wal.container().len(), 1);
+    /// # std::fs::remove_file(path.with_extension("log")).unwrap();
+    /// # std::fs::remove_file(path.with_extension("snapshot")).unwrap();
+    /// ```
+    pub fn checkpoint(&mut self) -> io::Result<()> {
+        let mut snapshot = Vec::new();
+        write_varint(&mut snapshot, self.next_sequence);
+        self.container.write_to(&mut snapshot);
+        format::save_bytes(&self.snapshot_path, &format::frame(&snapshot))?;
+
+        // A crash before the truncation only leaves records that the snapshot already covers
+        self.log.set_len(0)?;
+        self.log.sync_data()?;
+        self.log_end = 0;
+        self.logged = 0;
+        Ok(())
+    }
+
+    /// Force the log to the disk
+    ///
+    /// # Returns
+    /// io::Result<()>
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::fifo::{FifoOp, FIFO};
+    /// use data_structures::persist::wal::Wal;
+    ///
+    /// let path = std::env::temp_dir().join("wal_sync_example");
+    /// # let _ = std::fs::remove_file(path.with_extension("log"));
+    /// # let _ = std::fs::remove_file(path.with_extension("snapshot"));
+    /// let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+    /// wal.apply(FifoOp::Push(1)).unwrap().unwrap();
+    /// wal.sync().unwrap();
+    /// # std::fs::remove_file(path.with_extension("log")).unwrap();
+    /// ```
+    pub fn sync(&self) -> io::Result<()> {
+        // Snapshots are synced when they are written
+        self.log.sync_data()
+    }
+
+    /// Get the path of the log file
+    /// # Returns
+    /// The path of the log file
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::fifo::FIFO;
+    /// use data_structures::persist::wal::Wal;
+    ///
+    /// let path = std::env::temp_dir().join("wal_log_path_example");
+    /// # let _ = std::fs::remove_file(path.with_extension("log"));
+    /// # let _ = std::fs::remove_file(path.with_extension("snapshot"));
+    /// let wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+    /// assert_eq!(wal.log_path(), path.with_extension("log"));
+    /// # std::fs::remove_file(path.with_extension("log")).unwrap();
+    /// ```
+    pub fn log_path(&self) -> &Path {
+        &self.log_path
+    }
+
+    // Split the record at the front of the input off it and check its checksum
+    fn parse_record<'a>(input: &mut &'a [u8]) -> Result<&'a [u8], DataStructureError> {
+        let start = *input;
+        let length = read_length(input)?;
+        let record = take(input, length)?;
+
+        let body = &start[..start.len() - input.len()];
+        if crc32(body).to_le_bytes() != take(input, 4)? {
+            return Err(DataStructureError::ChecksumMismatch);
+        }
+        Ok(record)
+    }
+
+    // Decode the sequence number and the operation of a record whose checksum matched
+    fn decode_record(mut record: &[u8]) -> Result<(u64, C::Op), DataStructureError> {
+        let sequence = read_varint(&mut record)?;
+        let op = C::Op::read_from(&mut record)?;
+        if !record.is_empty() {
+            return Err(DataStructureError::TrailingBytes);
+        }
+        Ok((sequence, op))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linked_list::fifo::{FifoOp, FIFO};
+    use std::fs;
+
+    fn base(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("wal_{}_{}", name, std::process::id()));
+        let _ = fs::remove_file(path.with_extension("log"));
+        let _ = fs::remove_file(path.with_extension("snapshot"));
+        path
+    }
+
+    fn cleanup(path: &Path) {
+        let _ = fs::remove_file(path.with_extension("log"));
+        let _ = fs::remove_file(path.with_extension("snapshot"));
+    }
+
+    #[test]
+    fn test_wal_recovery() {
+        let path = base("recovery");
+
+        let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+        wal.set_checkpoint_interval(4);
+        for i in 0..10 {
+            wal.apply(FifoOp::Push(i)).unwrap().unwrap();
+        }
+        for _ in 0..3 {
+            wal.apply(FifoOp::Pop).unwrap().unwrap();
+        }
+        assert_eq!(wal.logged(), 1);
+        drop(wal);
+
+        let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+        let mut values = Vec::new();
+        while let Some(value) = wal.apply(FifoOp::Pop).unwrap().unwrap() {
+            values.push(value);
+        }
+        assert_eq!(values, (3..10).collect::<Vec<_>>());
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_wal_crash_during_checkpoint() {
+        let path = base("checkpoint_crash");
+
+        let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+        wal.apply(FifoOp::Push(1)).unwrap().unwrap();
+        wal.apply(FifoOp::Push(2)).unwrap().unwrap();
+        let log = fs::read(wal.log_path()).unwrap();
+        wal.checkpoint().unwrap();
+        wal.apply(FifoOp::Push(3)).unwrap().unwrap();
+        drop(wal);
+
+        // Simulate a crash between the snapshot and the truncation of the log
+        let mut stale = log.clone();
+        stale.extend(fs::read(path.with_extension("log")).unwrap());
+        fs::write(path.with_extension("log"), stale).unwrap();
+
+        let wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+        assert_eq!(wal.container(), &FIFO::from([1, 2, 3]));
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_wal_torn_record() {
+        let path = base("torn");
+
+        let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+        wal.apply(FifoOp::Push(1)).unwrap().unwrap();
+        wal.apply(FifoOp::Push(2)).unwrap().unwrap();
+        drop(wal);
+
+        let log = path.with_extension("log");
+        let length = fs::metadata(&log).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&log)
+            .unwrap()
+            .set_len(length - 1)
+            .unwrap();
+
+        let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+        assert_eq!(wal.container(), &FIFO::from([1]));
+        wal.apply(FifoOp::Push(3)).unwrap().unwrap();
+        drop(wal);
+
+        let wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+        assert_eq!(wal.container(), &FIFO::from([1, 3]));
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_wal_corrupted_record() {
+        let path = base("corrupted");
+
+        let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+        for i in 0..3 {
+            wal.apply(FifoOp::Push(i)).unwrap().unwrap();
+        }
+        drop(wal);
+
+        // A flipped byte in the first record must not silently drop the operations after it
+        let log = path.with_extension("log");
+        let mut bytes = fs::read(&log).unwrap();
+        bytes[3] ^= 0xff;
+        fs::write(&log, &bytes).unwrap();
+
+        let error = Wal::<FIFO<u32>>::open(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::read(&log).unwrap(), bytes);
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_wal_corrupted_length() {
+        let path = base("corrupted_length");
+
+        let mut wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+        for i in 0..10 {
+            wal.apply(FifoOp::Push(i)).unwrap().unwrap();
+        }
+        drop(wal);
+
+        // A length running past the end of the log, on the first or a middle record, isn't a torn write
+        let log = path.with_extension("log");
+        let original = fs::read(&log).unwrap();
+        let record = original.len() / 10;
+        assert_eq!(original.len() % 10, 0);
+        for start in [0, 4 * record] {
+            let mut bytes = original.clone();
+            bytes[start] = 0xff;
+            bytes[start + 1] = 0x7f;
+            fs::write(&log, &bytes).unwrap();
+
+            let error = Wal::<FIFO<u32>>::open(&path).err().unwrap();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            assert_eq!(fs::read(&log).unwrap(), bytes);
+        }
+
+        // The same damage on the last record is a torn write
+        let mut bytes = original;
+        let last = bytes.len() - record;
+        bytes[last] = 0xff;
+        bytes[last + 1] = 0x7f;
+        fs::write(&log, &bytes).unwrap();
+
+        let wal: Wal<FIFO<u32>> = Wal::open(&path).unwrap();
+        assert_eq!(wal.container(), &FIFO::from([0, 1, 2, 3, 4, 5, 6, 7, 8]));
+        assert_eq!(fs::metadata(&log).unwrap().len(), last as u64);
+
+        cleanup(&path);
+    }
+}
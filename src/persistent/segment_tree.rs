@@ -0,0 +1,394 @@
+//! This module implements a persistent segment tree, where every update creates a new version of the sequence and all
+//! the previous versions stay available for range queries. Versions are numbered from 0, the initial sequence, and an
+//! update can start from any of them, so the history may branch.
+//!
+//! # Performance
+//! - O(n) to build the first version
+//! - O(log n) time and O(log n) new nodes for `set`
+//! - O(log n) for `get` and `query` in any version
+//!
+//! # Implementation Details
+//! - Nodes are immutable and shared through `Ptr`. An update copies the nodes on the path from the root to the
+//!   changed leaf and points the copies at the untouched subtrees of the old version, so versions share all but
+//!   O(log n) nodes.
+//! - A version is just its root, kept in a `Vec` indexed by version number.
+//! - Nodes split their span in the middle, the left child getting the smaller half.
+//!
+//! # Usage
+//! ```
+//! use data_structures::persistent::segment_tree::SegmentTree;
+//!
+//! let mut tree = SegmentTree::new(vec![1, 2, 3, 4], 0, |a: &i32, b: &i32| a + b);
+//!
+//! let v1 = tree.set(0, 1, 20).unwrap();
+//! let v2 = tree.set(v1, 3, 40).unwrap();
+//!
+//! assert_eq!(tree.query(0, 0..4), Ok(10));
+//! assert_eq!(tree.query(v1, 0..4), Ok(28));
+//! assert_eq!(tree.query(v2, 0..4), Ok(64));
+//! ```
+//!
+use std::fmt;
+use std::ops::Range;
+
+use crate::error::DataStructureError;
+use crate::linked_list::shared::Ptr;
+
+// Immutable node, the leaves having no children
+struct Node<T> {
+    value: T,
+    children: Option<Children<T>>,
+}
+
+type Children<T> = (Ptr<Node<T>>, Ptr<Node<T>>);
+
+/// Struct representing every version of a sequence updated by a persistent segment tree
+///
+pub struct SegmentTree<T, F> {
+    versions: Vec<Option<Ptr<Node<T>>>>, // None for an empty sequence
+    len: usize,
+
+    identity: T,
+    combine: F,
+}
+
+impl<T: Clone, F: Fn(&T, &T) -> T> SegmentTree<T, F> {
+    /// Build a persistent segment tree whose version 0 holds some values
+    ///
+    /// # Arguments
+    /// * `values`: The initial sequence
+    /// * `identity`: The identity of the operation, the result of an empty range
+    /// * `combine`: The associative operation
+    ///
+    /// # Returns
+    /// A new SegmentTree instance with a single version
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::segment_tree::SegmentTree;
+    ///
+    /// let tree = SegmentTree::new(vec![1, 2, 3], 0, |a: &i32, b: &i32| a + b);
+    /// assert_eq!(tree.version_count(), 1);
+    /// ```
+    pub fn new(values: Vec<T>, identity: T, combine: F) -> Self {
+        let len = values.len();
+        let root = (len > 0).then(|| build(&mut values.into_iter(), len, &combine));
+
+        SegmentTree {
+            versions: vec![root],
+            len,
+            identity,
+            combine,
+        }
+    }
+
+    /// Get the number of values, the same in every version
+    /// # Returns
+    /// The length of the sequence
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::segment_tree::SegmentTree;
+    ///
+    /// let tree = SegmentTree::new(vec![0u8; 10], 0, |a: &u8, b: &u8| a ^ b);
+    /// assert_eq!(tree.len(), 10);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the sequence is empty
+    /// # Returns
+    /// True if there are no values
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::segment_tree::SegmentTree;
+    ///
+    /// let tree = SegmentTree::new(Vec::new(), 0, |a: &u32, b: &u32| a + b);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of versions
+    /// # Returns
+    /// The number of versions, the newest one being this number minus one
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::segment_tree::SegmentTree;
+    ///
+    /// let mut tree = SegmentTree::new(vec![1, 2], 0, |a: &i32, b: &i32| a + b);
+    /// tree.set(0, 0, 5).unwrap();
+    /// assert_eq!(tree.version_count(), 2);
+    /// ```
+    pub fn version_count(&self) -> usize {
+        self.versions.len()
+    }
+
+    /// Get a value of a version
+    /// # Arguments
+    /// * `version`: The version to read
+    /// * `index`: The position of the value
+    /// # Returns
+    /// A reference to the value, or None if the version or the index doesn't exist
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::segment_tree::SegmentTree;
+    ///
+    /// let mut tree = SegmentTree::new(vec![4, 5], 0, |a: &i32, b: &i32| a + b);
+    /// let v1 = tree.set(0, 1, 6).unwrap();
+    ///
+    /// assert_eq!(tree.get(0, 1), Some(&5));
+    /// assert_eq!(tree.get(v1, 1), Some(&6));
+    /// assert_eq!(tree.get(2, 1), None);
+    /// ```
+    pub fn get(&self, version: usize, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let mut node = self.versions.get(version)?.as_ref()?;
+        let mut span = 0..self.len;
+        while let Some((left, right)) = &node.children {
+            let middle = span.start + span.len() / 2;
+            if index < middle {
+                node = left;
+                span.end = middle;
+            } else {
+                node = right;
+                span.start = middle;
+            }
+        }
+
+        Some(&node.value)
+    }
+
+    /// Replace a value, creating a new version
+    /// # Arguments
+    /// * `version`: The version to start from, which isn't changed
+    /// * `index`: The position of the value
+    /// * `value`: The new value
+    /// # Returns
+    /// The number of the new version, or an error if the version or the index doesn't exist
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::persistent::segment_tree::SegmentTree;
+    ///
+    /// let mut tree = SegmentTree::new(vec![1, 2, 3], 0, |a: &i32, b: &i32| a + b);
+    ///
+    /// assert_eq!(tree.set(0, 0, 10), Ok(1));
+    /// // Branch from the initial version again
+    /// assert_eq!(tree.set(0, 2, 30), Ok(2));
+    /// assert_eq!(tree.query(2, 0..3), Ok(33));
+    ///
+    /// assert_eq!(tree.set(0, 3, 0), Err(DataStructureError::IndexOutOfBounds { index: 3, len: 3 }));
+    /// assert_eq!(tree.set(5, 0, 0), Err(DataStructureError::UnknownVersion(5)));
+    /// ```
+    pub fn set(
+        &mut self,
+        version: usize,
+        index: usize,
+        value: T,
+    ) -> Result<usize, DataStructureError> {
+        let root = self
+            .versions
+            .get(version)
+            .ok_or(DataStructureError::UnknownVersion(version))?;
+        let root = match root {
+            Some(root) if index < self.len => root,
+            _ => {
+                return Err(DataStructureError::IndexOutOfBounds {
+                    index,
+                    len: self.len,
+                })
+            }
+        };
+
+        let root = self.set_node(root, 0..self.len, index, value);
+        self.versions.push(Some(root));
+        Ok(self.versions.len() - 1)
+    }
+
+    /// Combine the values of a range in a version, in order
+    /// # Arguments
+    /// * `version`: The version to read
+    /// * `range`: The positions to combine
+    /// # Returns
+    /// The combination of the values, the identity for an empty range, or an error if the version doesn't exist or
+    /// the range is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::persistent::segment_tree::SegmentTree;
+    ///
+    /// let mut tree = SegmentTree::new(vec![5, 2, 8], i32::MAX, |a: &i32, b: &i32| *a.min(b));
+    /// let v1 = tree.set(0, 1, 9).unwrap();
+    ///
+    /// assert_eq!(tree.query(0, 0..3), Ok(2));
+    /// assert_eq!(tree.query(v1, 0..3), Ok(5));
+    /// assert_eq!(tree.query(v1, 0..4), Err(DataStructureError::RangeOutOfBounds { start: 0, end: 4, len: 3 }));
+    /// ```
+    pub fn query(&self, version: usize, range: Range<usize>) -> Result<T, DataStructureError> {
+        let root = self
+            .versions
+            .get(version)
+            .ok_or(DataStructureError::UnknownVersion(version))?;
+        if range.start > range.end || range.end > self.len {
+            return Err(DataStructureError::RangeOutOfBounds {
+                start: range.start,
+                end: range.end,
+                len: self.len,
+            });
+        }
+
+        match root {
+            Some(root) if !range.is_empty() => Ok(self.query_node(root, 0..self.len, &range)),
+            _ => Ok(self.identity.clone()),
+        }
+    }
+
+    // Copy of the path to the changed leaf, sharing everything else
+    fn set_node(
+        &self,
+        node: &Ptr<Node<T>>,
+        span: Range<usize>,
+        index: usize,
+        value: T,
+    ) -> Ptr<Node<T>> {
+        let Some((left, right)) = &node.children else {
+            return Ptr::new(Node {
+                value,
+                children: None,
+            });
+        };
+
+        let middle = span.start + span.len() / 2;
+        let (left, right) = if index < middle {
+            (
+                self.set_node(left, span.start..middle, index, value),
+                right.clone(),
+            )
+        } else {
+            (
+                left.clone(),
+                self.set_node(right, middle..span.end, index, value),
+            )
+        };
+
+        Ptr::new(Node {
+            value: (self.combine)(&left.value, &right.value),
+            children: Some((left, right)),
+        })
+    }
+
+    fn query_node(&self, node: &Ptr<Node<T>>, span: Range<usize>, range: &Range<usize>) -> T {
+        if range.start <= span.start && span.end <= range.end {
+            return node.value.clone();
+        }
+
+        let (left, right) = node.children.as_ref().unwrap();
+        let middle = span.start + span.len() / 2;
+        match (range.start < middle, middle < range.end) {
+            (true, true) => (self.combine)(
+                &self.query_node(left, span.start..middle, range),
+                &self.query_node(right, middle..span.end, range),
+            ),
+            (true, false) => self.query_node(left, span.start..middle, range),
+            _ => self.query_node(right, middle..span.end, range),
+        }
+    }
+}
+
+// Build the nodes over the next `len` values
+fn build<T, F: Fn(&T, &T) -> T>(
+    values: &mut impl Iterator<Item = T>,
+    len: usize,
+    combine: &F,
+) -> Ptr<Node<T>> {
+    if len == 1 {
+        return Ptr::new(Node {
+            value: values.next().unwrap(),
+            children: None,
+        });
+    }
+
+    let left = build(values, len / 2, combine);
+    let right = build(values, len - len / 2, combine);
+    Ptr::new(Node {
+        value: combine(&left.value, &right.value),
+        children: Some((left, right)),
+    })
+}
+
+impl<T, F> fmt::Debug for SegmentTree<T, F> {
+    /// Prints the length of the sequence and the number of versions
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SegmentTree")
+            .field("len", &self.len)
+            .field("versions", &self.versions.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_every_version_matches_its_snapshot() {
+        let initial: Vec<i64> = (0..29).map(|n| (n * 7919) % 101 - 50).collect();
+        let mut tree = SegmentTree::new(initial.clone(), 0, |a: &i64, b: &i64| a + b);
+        let mut snapshots = vec![initial];
+        let mut rng = Lcg::new(53);
+
+        for _ in 0..300 {
+            let seed = rng.next_u64();
+            // Branch from a random earlier version
+            let base = (seed >> 40) as usize % snapshots.len();
+            let index = (seed >> 20) as usize % 29;
+            let value = (seed >> 52) as i64 - 2000;
+
+            let mut snapshot = snapshots[base].clone();
+            snapshot[index] = value;
+            assert_eq!(tree.set(base, index, value), Ok(snapshots.len()));
+            snapshots.push(snapshot);
+        }
+
+        for (version, snapshot) in snapshots.iter().enumerate() {
+            for start in (0..29).step_by(4) {
+                for end in start..=29 {
+                    assert_eq!(
+                        tree.query(version, start..end),
+                        Ok(snapshot[start..end].iter().sum())
+                    );
+                }
+            }
+            let values: Vec<_> = (0..29).map(|i| *tree.get(version, i).unwrap()).collect();
+            assert_eq!(&values, snapshot);
+        }
+    }
+
+    #[test]
+    fn test_versions_share_nodes() {
+        let mut tree = SegmentTree::new(vec![0u32; 64], 0, |a: &u32, b: &u32| a + b);
+        let v1 = tree.set(0, 63, 1).unwrap();
+
+        // Only the right half of the root was copied
+        let (old_left, old_right) = tree.versions[0]
+            .as_ref()
+            .unwrap()
+            .children
+            .as_ref()
+            .unwrap();
+        let (new_left, new_right) = tree.versions[v1]
+            .as_ref()
+            .unwrap()
+            .children
+            .as_ref()
+            .unwrap();
+        assert!(Ptr::ptr_eq(old_left, new_left));
+        assert!(!Ptr::ptr_eq(old_right, new_right));
+    }
+}
@@ -0,0 +1,405 @@
+//! This module implements a versioned map, where every write creates a new version and reads can target any version
+//! still in the history. It suits audit trails and log-structured data, where the state at some past point must be
+//! reconstructed, without keeping a full copy of the map per version.
+//!
+//! # Performance
+//! - O(1) expected for `insert`, `remove` and `get`
+//! - O(log h) for `get_at`, where h is the number of writes to the key still in the history
+//! - One history entry per write: memory grows with the writes until the history is pruned
+//!
+//! # Implementation Details
+//! - Each key keeps its own list of `(version, value)` entries in version order ("fat nodes"), a removal being an
+//!   entry without value. Reading a key at a version binary searches for its last entry at or before that version.
+//! - Version 0 is the empty map, and each write increments the current version.
+//! - Pruning keeps, for each key, the last entry at or before the oldest kept version and everything after it, so
+//!   every kept version reads exactly as before. With a history limit, a key is pruned when it is written; a full
+//!   sweep is done by `prune_before`.
+//!
+//! # Usage
+//! ```
+//! use data_structures::persistent::versioned_map::VersionedMap;
+//!
+//! let mut balances = VersionedMap::new();
+//! let v1 = balances.insert("alice", 100);
+//! let v2 = balances.insert("alice", 80);
+//! balances.remove(&"alice");
+//!
+//! assert_eq!(balances.get(&"alice"), None);
+//! assert_eq!(balances.get_at(v1, &"alice"), Ok(Some(&100)));
+//! assert_eq!(balances.get_at(v2, &"alice"), Ok(Some(&80)));
+//! assert_eq!(balances.get_at(0, &"alice"), Ok(None));
+//! ```
+//!
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::error::DataStructureError;
+
+// Writes of a key, in version order; None for a removal
+type History<V> = Vec<(usize, Option<V>)>;
+
+/// Struct representing a map whose past versions stay readable
+///
+#[derive(Debug, Clone)]
+pub struct VersionedMap<K, V> {
+    entries: HashMap<K, History<V>>,
+    version: usize,
+    oldest: usize, // oldest version still readable
+    history_limit: Option<usize>,
+    len: usize,
+}
+
+impl<K: Hash + Eq, V> VersionedMap<K, V> {
+    /// Create an empty map keeping its whole history
+    ///
+    /// # Returns
+    /// A new VersionedMap instance at version 0
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::versioned_map::VersionedMap;
+    ///
+    /// let map: VersionedMap<u32, String> = VersionedMap::new();
+    /// assert_eq!(map.version(), 0);
+    /// ```
+    pub fn new() -> Self {
+        VersionedMap {
+            entries: HashMap::new(),
+            version: 0,
+            oldest: 0,
+            history_limit: None,
+            len: 0,
+        }
+    }
+
+    /// Create an empty map keeping only its last versions
+    ///
+    /// # Arguments
+    /// * `limit`: The number of past versions kept readable besides the current one
+    ///
+    /// # Returns
+    /// A new VersionedMap instance at version 0
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::persistent::versioned_map::VersionedMap;
+    ///
+    /// let mut map = VersionedMap::with_history_limit(1);
+    /// map.insert("k", 1);
+    /// map.insert("k", 2);
+    /// map.insert("k", 3);
+    ///
+    /// assert_eq!(map.get_at(2, &"k"), Ok(Some(&2)));
+    /// assert_eq!(map.get_at(1, &"k"), Err(DataStructureError::PrunedVersion(1)));
+    /// ```
+    pub fn with_history_limit(limit: usize) -> Self {
+        VersionedMap {
+            history_limit: Some(limit),
+            ..Self::new()
+        }
+    }
+
+    /// Get the number of keys in the current version
+    /// # Returns
+    /// The number of keys
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::versioned_map::VersionedMap;
+    ///
+    /// let mut map = VersionedMap::new();
+    /// map.insert(1, 'a');
+    /// map.insert(1, 'b');
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the current version is empty
+    /// # Returns
+    /// True if there are no keys
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::versioned_map::VersionedMap;
+    ///
+    /// let mut map = VersionedMap::new();
+    /// map.insert(1, 'a');
+    /// map.remove(&1);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the current version
+    /// # Returns
+    /// The id of the latest version, 0 before any write
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::versioned_map::VersionedMap;
+    ///
+    /// let mut map = VersionedMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// assert_eq!(map.version(), 2);
+    /// ```
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// Get the oldest version that can still be read
+    /// # Returns
+    /// The id of the oldest readable version
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::versioned_map::VersionedMap;
+    ///
+    /// let mut map = VersionedMap::new();
+    /// map.insert("a", 1);
+    /// map.prune_before(1).unwrap();
+    /// assert_eq!(map.oldest_version(), 1);
+    /// ```
+    pub fn oldest_version(&self) -> usize {
+        self.oldest
+    }
+
+    /// Insert or replace a value, creating a new version
+    /// # Arguments
+    /// * `key`: The key
+    /// * `value`: The new value
+    /// # Returns
+    /// The id of the new version
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::versioned_map::VersionedMap;
+    ///
+    /// let mut map = VersionedMap::new();
+    /// assert_eq!(map.insert("a", 1), 1);
+    /// assert_eq!(map.insert("a", 2), 2);
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> usize {
+        self.version += 1;
+        self.advance_oldest();
+
+        let history = self.entries.entry(key).or_default();
+        if !matches!(history.last(), Some((_, Some(_)))) {
+            self.len += 1;
+        }
+        history.push((self.version, Some(value)));
+        prune_history(history, self.oldest);
+
+        self.version
+    }
+
+    /// Remove a key, creating a new version if it was present
+    /// # Arguments
+    /// * `key`: The key
+    /// # Returns
+    /// The id of the new version, or None if the key wasn't in the current version
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::versioned_map::VersionedMap;
+    ///
+    /// let mut map = VersionedMap::new();
+    /// map.insert("a", 1);
+    ///
+    /// assert_eq!(map.remove(&"a"), Some(2));
+    /// assert_eq!(map.remove(&"a"), None);
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<usize> {
+        let history = self.entries.get_mut(key)?;
+        if !matches!(history.last(), Some((_, Some(_)))) {
+            return None;
+        }
+
+        self.version += 1;
+        self.len -= 1;
+        history.push((self.version, None));
+
+        self.advance_oldest();
+        let history = self.entries.get_mut(key).unwrap();
+        if prune_history(history, self.oldest) {
+            self.entries.remove(key);
+        }
+
+        Some(self.version)
+    }
+
+    /// Get the value of a key in the current version
+    /// # Arguments
+    /// * `key`: The key
+    /// # Returns
+    /// A reference to the value, or None if the key isn't present
+    /// # Example
+    /// ```
+    /// use data_structures::persistent::versioned_map::VersionedMap;
+    ///
+    /// let mut map = VersionedMap::new();
+    /// map.insert("a", 1);
+    /// assert_eq!(map.get(&"a"), Some(&1));
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key)?.last()?.1.as_ref()
+    }
+
+    /// Get the value of a key in a past version
+    /// # Arguments
+    /// * `version`: The version to read
+    /// * `key`: The key
+    /// # Returns
+    /// A reference to the value, None if the key wasn't present in that version, or an error if the version doesn't
+    /// exist yet or was pruned
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::persistent::versioned_map::VersionedMap;
+    ///
+    /// let mut map = VersionedMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    ///
+    /// assert_eq!(map.get_at(1, &"a"), Ok(Some(&1)));
+    /// assert_eq!(map.get_at(1, &"b"), Ok(None));
+    /// assert_eq!(map.get_at(3, &"a"), Err(DataStructureError::UnknownVersion(3)));
+    /// ```
+    pub fn get_at(&self, version: usize, key: &K) -> Result<Option<&V>, DataStructureError> {
+        if version > self.version {
+            return Err(DataStructureError::UnknownVersion(version));
+        }
+        if version < self.oldest {
+            return Err(DataStructureError::PrunedVersion(version));
+        }
+
+        let Some(history) = self.entries.get(key) else {
+            return Ok(None);
+        };
+        let position = history.partition_point(|(written, _)| *written <= version);
+        Ok(position
+            .checked_sub(1)
+            .and_then(|last| history[last].1.as_ref()))
+    }
+
+    /// Forget the versions older than a given one
+    /// # Arguments
+    /// * `version`: The oldest version to keep readable
+    /// # Returns
+    /// An error if the version doesn't exist yet
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::persistent::versioned_map::VersionedMap;
+    ///
+    /// let mut map = VersionedMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("a", 2);
+    /// map.prune_before(2).unwrap();
+    ///
+    /// assert_eq!(map.get_at(2, &"a"), Ok(Some(&2)));
+    /// assert_eq!(map.get_at(1, &"a"), Err(DataStructureError::PrunedVersion(1)));
+    /// assert_eq!(map.prune_before(5), Err(DataStructureError::UnknownVersion(5)));
+    /// ```
+    pub fn prune_before(&mut self, version: usize) -> Result<(), DataStructureError> {
+        if version > self.version {
+            return Err(DataStructureError::UnknownVersion(version));
+        }
+
+        self.oldest = self.oldest.max(version);
+        let oldest = self.oldest;
+        self.entries
+            .retain(|_, history| !prune_history(history, oldest));
+        Ok(())
+    }
+
+    // Move the oldest readable version to respect the history limit
+    fn advance_oldest(&mut self) {
+        if let Some(limit) = self.history_limit {
+            self.oldest = self.oldest.max(self.version.saturating_sub(limit));
+        }
+    }
+}
+
+// Drop the entries no version from `oldest` on can read, returning true if the key is absent from all of them
+fn prune_history<V>(history: &mut History<V>, oldest: usize) -> bool {
+    let readable = history.partition_point(|(written, _)| *written <= oldest);
+    if readable > 1 {
+        history.drain(..readable - 1);
+    }
+    history.len() == 1 && history[0].1.is_none() && history[0].0 <= oldest
+}
+
+impl<K: Hash + Eq, V> Default for VersionedMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_every_version_matches_its_snapshot() {
+        let mut map = VersionedMap::new();
+        let mut snapshots = vec![HashMap::new()];
+        let mut rng = Lcg::new(83);
+
+        for round in 0..1000u32 {
+            let seed = rng.next_u64();
+            let key = (seed >> 58) as u8;
+            let mut snapshot = snapshots.last().unwrap().clone();
+
+            if (seed >> 30).is_multiple_of(3) {
+                if snapshot.remove(&key).is_some() {
+                    assert_eq!(map.remove(&key), Some(snapshots.len()));
+                    snapshots.push(snapshot);
+                } else {
+                    assert_eq!(map.remove(&key), None);
+                }
+            } else {
+                snapshot.insert(key, round);
+                assert_eq!(map.insert(key, round), snapshots.len());
+                snapshots.push(snapshot);
+            }
+            assert_eq!(map.len(), snapshots.last().unwrap().len());
+        }
+
+        let check = |map: &VersionedMap<u8, u32>, from: usize| {
+            for (version, snapshot) in snapshots.iter().enumerate().skip(from) {
+                for key in 0..64 {
+                    assert_eq!(map.get_at(version, &key), Ok(snapshot.get(&key)));
+                }
+            }
+        };
+        check(&map, 0);
+
+        // Pruning changes nothing for the versions kept
+        map.prune_before(700).unwrap();
+        check(&map, 700);
+        assert_eq!(
+            map.get_at(699, &0),
+            Err(DataStructureError::PrunedVersion(699))
+        );
+    }
+
+    #[test]
+    fn test_history_limit_bounds_memory() {
+        let mut map = VersionedMap::with_history_limit(3);
+        for value in 0..100 {
+            map.insert("key", value);
+        }
+
+        assert_eq!(map.entries[&"key"].len(), 4);
+        assert_eq!(map.oldest_version(), 97);
+        assert_eq!(map.get_at(97, &"key"), Ok(Some(&96)));
+
+        // A key removed before the oldest version is forgotten entirely
+        map.remove(&"key");
+        for value in 0..5 {
+            map.insert("other", value);
+        }
+        map.prune_before(map.version()).unwrap();
+        assert!(!map.entries.contains_key(&"key"));
+    }
+}
@@ -0,0 +1,486 @@
+//! This module implements a pool of reusable byte buffers, grouped in size classes.
+//! A buffer is taken from the pool with `get` and returns to it when dropped, so a pipeline that keeps taking and
+//! releasing buffers of similar sizes stops allocating once the pool holds its working set. Occupancy and reuse metrics
+//! show how many buffers each size class has in use and idle, and how often the pool had to allocate.
+//!
+//! # Performance
+//! - O(log c) for `get`, with c the number of size classes, plus the time to zero the buffer
+//! - O(1) to return a buffer to the pool on drop
+//!
+//! # Implementation Details
+//! - A request is served by the smallest size class that fits it. The buffer has the requested length, is zeroed, and
+//!   its capacity is the size of the class, so it can be truncated without giving memory back.
+//! - Each class keeps at most `max_idle` idle buffers. A buffer returned to a class that already holds that many is
+//!   freed, which bounds the memory kept by the pool after a burst.
+//! - The pool state is shared behind an `Rc<RefCell<...>>`; clones of the pool share it. Buffers hold a weak reference,
+//!   so a buffer that outlives its pool is simply freed on drop.
+//!
+//! # Usage
+//! ```
+//! use data_structures::pool::buffer_pool::BufferPool;
+//!
+//! let pool = BufferPool::new(&[512, 4096], 8);
+//!
+//! let mut packet = pool.get(1500).unwrap();
+//! packet[..5].copy_from_slice(b"hello");
+//! assert_eq!(packet.len(), 1500);
+//! assert_eq!(packet.capacity(), 4096);
+//! drop(packet);
+//!
+//! // The buffer is reused by the next request of the same class
+//! let packet = pool.get(2000).unwrap();
+//! assert_eq!(pool.stats().allocations, 1);
+//! assert_eq!(pool.stats().reuses, 1);
+//! assert!(packet.iter().all(|&byte| byte == 0));
+//! ```
+//!
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::rc::{Rc, Weak};
+
+use crate::error::DataStructureError;
+
+/// Counters of the buffers handed out and returned by a pool
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    pub allocations: u64,
+    pub reuses: u64,
+    pub returns: u64,
+    pub discards: u64,
+}
+
+impl PoolStats {
+    /// Get the ratio of requests served by an idle buffer
+    /// # Returns
+    /// The reuse rate between 0.0 and 1.0, or 0.0 if no buffer was requested
+    /// # Example
+    /// ```
+    /// use data_structures::pool::buffer_pool::PoolStats;
+    ///
+    /// let stats = PoolStats { allocations: 1, reuses: 3, returns: 4, discards: 0 };
+    /// assert_eq!(stats.reuse_rate(), 0.75);
+    /// ```
+    pub fn reuse_rate(&self) -> f64 {
+        let requests = self.allocations + self.reuses;
+        if requests == 0 {
+            0.0
+        } else {
+            self.reuses as f64 / requests as f64
+        }
+    }
+}
+
+/// Number of buffers of a size class handed out and kept idle
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassOccupancy {
+    pub size: usize,
+    pub in_use: usize,
+    pub idle: usize,
+}
+
+/// A size class and its idle buffers
+struct Class {
+    size: usize,
+    idle: Vec<Vec<u8>>,
+    in_use: usize,
+}
+
+/// State shared by a pool and its buffers
+struct Inner {
+    classes: Vec<Class>,
+    max_idle: usize,
+    stats: PoolStats,
+}
+
+/// Struct representing a pool of byte buffers reused across requests
+///
+#[derive(Clone)]
+pub struct BufferPool {
+    inner: Rc<RefCell<Inner>>,
+}
+
+/// Struct representing a byte buffer borrowed from a pool, returned to it on drop
+///
+pub struct PooledBuf {
+    buf: Vec<u8>,
+    class: usize,
+    capacity: usize,
+    pool: Weak<RefCell<Inner>>,
+}
+
+impl BufferPool {
+    /// Create a new empty pool
+    /// # Arguments
+    /// * `size_classes`: The capacities of the buffers handed out, in any order. Must not be empty or hold 0.
+    /// * `max_idle`: The maximum number of idle buffers kept in each size class
+    /// # Returns
+    /// A new BufferPool instance
+    /// # Example
+    /// ```
+    /// use data_structures::pool::buffer_pool::BufferPool;
+    ///
+    /// let pool = BufferPool::new(&[4096, 256, 256], 16);
+    /// assert_eq!(pool.size_classes(), vec![256, 4096]);
+    /// ```
+    pub fn new(size_classes: &[usize], max_idle: usize) -> Self {
+        let mut sizes = size_classes.to_vec();
+        sizes.sort_unstable();
+        sizes.dedup();
+        assert!(
+            sizes.first().is_some_and(|&size| size > 0),
+            "Size classes must not be empty or hold 0"
+        );
+
+        let classes = sizes
+            .into_iter()
+            .map(|size| Class {
+                size,
+                idle: Vec::new(),
+                in_use: 0,
+            })
+            .collect();
+        BufferPool {
+            inner: Rc::new(RefCell::new(Inner {
+                classes,
+                max_idle,
+                stats: PoolStats::default(),
+            })),
+        }
+    }
+
+    /// Get the capacities of the buffers handed out by the pool
+    /// # Returns
+    /// The size classes in increasing order
+    /// # Example
+    /// ```
+    /// use data_structures::pool::buffer_pool::BufferPool;
+    ///
+    /// let pool = BufferPool::new(&[64, 1024], 4);
+    /// assert_eq!(pool.size_classes(), vec![64, 1024]);
+    /// ```
+    pub fn size_classes(&self) -> Vec<usize> {
+        let inner = self.inner.borrow();
+        inner.classes.iter().map(|class| class.size).collect()
+    }
+
+    /// Take a zeroed buffer from the pool, allocating it if no idle buffer of its class is left
+    /// # Arguments
+    /// * `len`: The length of the buffer
+    /// # Returns
+    /// Result<PooledBuf, DataStructureError>
+    /// Ok with a buffer of `len` bytes from the smallest class that fits, Err(DataStructureError::ElementTooLarge) if
+    /// `len` is larger than the largest class
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::pool::buffer_pool::BufferPool;
+    ///
+    /// let pool = BufferPool::new(&[64, 1024], 4);
+    ///
+    /// let buf = pool.get(100).unwrap();
+    /// assert_eq!(buf.len(), 100);
+    /// assert_eq!(buf.capacity(), 1024);
+    /// assert_eq!(pool.get(2048).unwrap_err(), DataStructureError::ElementTooLarge { size: 2048, budget: 1024 });
+    /// ```
+    pub fn get(&self, len: usize) -> Result<PooledBuf, DataStructureError> {
+        let mut inner = self.inner.borrow_mut();
+        let inner = &mut *inner;
+        let index = inner.classes.partition_point(|class| class.size < len);
+        let Some(class) = inner.classes.get_mut(index) else {
+            let largest = inner.classes.last().map_or(0, |class| class.size);
+            return Err(DataStructureError::ElementTooLarge {
+                size: len,
+                budget: largest,
+            });
+        };
+
+        class.in_use += 1;
+        let (mut buf, reused) = match class.idle.pop() {
+            Some(buf) => (buf, true),
+            None => (Vec::with_capacity(class.size), false),
+        };
+        if reused {
+            inner.stats.reuses += 1;
+        } else {
+            inner.stats.allocations += 1;
+        }
+
+        // Returned buffers are cleared, so resizing zeroes the whole buffer
+        buf.resize(len, 0);
+        Ok(PooledBuf {
+            buf,
+            class: index,
+            capacity: class.size,
+            pool: Rc::downgrade(&self.inner),
+        })
+    }
+
+    /// Get the number of buffers handed out and not returned yet
+    /// # Returns
+    /// The number of buffers in use across all size classes
+    /// # Example
+    /// ```
+    /// use data_structures::pool::buffer_pool::BufferPool;
+    ///
+    /// let pool = BufferPool::new(&[64], 4);
+    /// let buf = pool.get(10).unwrap();
+    /// assert_eq!(pool.in_use(), 1);
+    ///
+    /// drop(buf);
+    /// assert_eq!(pool.in_use(), 0);
+    /// ```
+    pub fn in_use(&self) -> usize {
+        let inner = self.inner.borrow();
+        inner.classes.iter().map(|class| class.in_use).sum()
+    }
+
+    /// Get the number of buffers kept by the pool for reuse
+    /// # Returns
+    /// The number of idle buffers across all size classes
+    /// # Example
+    /// ```
+    /// use data_structures::pool::buffer_pool::BufferPool;
+    ///
+    /// let pool = BufferPool::new(&[64], 4);
+    /// drop(pool.get(10).unwrap());
+    /// assert_eq!(pool.idle(), 1);
+    /// ```
+    pub fn idle(&self) -> usize {
+        let inner = self.inner.borrow();
+        inner.classes.iter().map(|class| class.idle.len()).sum()
+    }
+
+    /// Get the number of buffers in use and idle in each size class
+    /// # Returns
+    /// The occupancy of each class, in increasing order of size
+    /// # Example
+    /// ```
+    /// use data_structures::pool::buffer_pool::{BufferPool, ClassOccupancy};
+    ///
+    /// let pool = BufferPool::new(&[64, 1024], 4);
+    /// let small = pool.get(10).unwrap();
+    /// drop(pool.get(500).unwrap());
+    ///
+    /// assert_eq!(
+    ///     pool.occupancy(),
+    ///     vec![
+    ///         ClassOccupancy { size: 64, in_use: 1, idle: 0 },
+    ///         ClassOccupancy { size: 1024, in_use: 0, idle: 1 },
+    ///     ]
+    /// );
+    /// ```
+    pub fn occupancy(&self) -> Vec<ClassOccupancy> {
+        let inner = self.inner.borrow();
+        inner
+            .classes
+            .iter()
+            .map(|class| ClassOccupancy {
+                size: class.size,
+                in_use: class.in_use,
+                idle: class.idle.len(),
+            })
+            .collect()
+    }
+
+    /// Free the idle buffers, keeping the buffers in use
+    /// # Example
+    /// ```
+    /// use data_structures::pool::buffer_pool::BufferPool;
+    ///
+    /// let pool = BufferPool::new(&[64], 4);
+    /// drop(pool.get(10).unwrap());
+    ///
+    /// pool.clear_idle();
+    /// assert_eq!(pool.idle(), 0);
+    /// ```
+    pub fn clear_idle(&self) {
+        let mut inner = self.inner.borrow_mut();
+        for class in inner.classes.iter_mut() {
+            class.idle = Vec::new();
+        }
+    }
+
+    /// Get the counters of the pool since its creation or the last reset
+    /// # Returns
+    /// The allocation, reuse, return and discard counts
+    /// # Example
+    /// ```
+    /// use data_structures::pool::buffer_pool::BufferPool;
+    ///
+    /// let pool = BufferPool::new(&[64], 0);
+    /// drop(pool.get(10).unwrap());
+    ///
+    /// let stats = pool.stats();
+    /// assert_eq!((stats.allocations, stats.returns, stats.discards), (1, 0, 1));
+    /// ```
+    pub fn stats(&self) -> PoolStats {
+        self.inner.borrow().stats
+    }
+
+    /// Reset the counters of the pool, keeping its buffers
+    /// # Example
+    /// ```
+    /// use data_structures::pool::buffer_pool::{BufferPool, PoolStats};
+    ///
+    /// let pool = BufferPool::new(&[64], 4);
+    /// drop(pool.get(10).unwrap());
+    ///
+    /// pool.reset_stats();
+    /// assert_eq!(pool.stats(), PoolStats::default());
+    /// assert_eq!(pool.idle(), 1);
+    /// ```
+    pub fn reset_stats(&self) {
+        self.inner.borrow_mut().stats = PoolStats::default();
+    }
+}
+
+impl fmt::Debug for BufferPool {
+    /// Prints the occupancy of each size class and the counters
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("occupancy", &self.occupancy())
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl PooledBuf {
+    /// Get the size class of the buffer, the largest length it can have
+    /// # Returns
+    /// The capacity of the buffer
+    /// # Example
+    /// ```
+    /// use data_structures::pool::buffer_pool::BufferPool;
+    ///
+    /// let pool = BufferPool::new(&[64], 4);
+    /// assert_eq!(pool.get(10).unwrap().capacity(), 64);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Shorten the buffer, keeping its capacity. Does nothing if `len` is not smaller than the length.
+    /// # Arguments
+    /// * `len`: The new length
+    /// # Example
+    /// ```
+    /// use data_structures::pool::buffer_pool::BufferPool;
+    ///
+    /// let pool = BufferPool::new(&[64], 4);
+    /// let mut buf = pool.get(64).unwrap();
+    /// buf[..3].copy_from_slice(b"abc");
+    ///
+    /// buf.truncate(3);
+    /// assert_eq!(&buf[..], b"abc");
+    /// ```
+    pub fn truncate(&mut self, len: usize) {
+        self.buf.truncate(len);
+    }
+}
+
+impl Deref for PooledBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buf
+    }
+}
+
+impl DerefMut for PooledBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.buf
+    }
+}
+
+impl Drop for PooledBuf {
+    /// Return the buffer to its size class, or free it if the class has enough idle buffers or the pool is gone
+    fn drop(&mut self) {
+        let Some(pool) = self.pool.upgrade() else {
+            return;
+        };
+        let mut inner = pool.borrow_mut();
+        let inner = &mut *inner;
+        let max_idle = inner.max_idle;
+        let class = &mut inner.classes[self.class];
+        class.in_use -= 1;
+        if class.idle.len() < max_idle {
+            let mut buf = std::mem::take(&mut self.buf);
+            buf.clear();
+            class.idle.push(buf);
+            inner.stats.returns += 1;
+        } else {
+            inner.stats.discards += 1;
+        }
+    }
+}
+
+impl fmt::Debug for PooledBuf {
+    /// Prints the length and capacity of the buffer, not its bytes
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledBuf")
+            .field("len", &self.buf.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_pool_reuse_and_occupancy() {
+        let pool = BufferPool::new(&[16, 256], 4);
+        let mut rng = Lcg::new(3);
+        let mut held = Vec::new();
+
+        for _ in 0..1_000 {
+            let seed = rng.next_u64();
+            if held.len() < 4 && (seed >> 63 == 0 || held.is_empty()) {
+                let len = (seed >> 33) as usize % 257;
+                let mut buf = pool.get(len).unwrap();
+                assert_eq!(buf.len(), len);
+                assert_eq!(buf.capacity(), if len <= 16 { 16 } else { 256 });
+                assert!(buf.iter().all(|&byte| byte == 0));
+                buf.fill(0xff);
+                held.push(buf);
+            } else {
+                held.swap_remove((seed >> 33) as usize % held.len());
+            }
+
+            let occupancy = pool.occupancy();
+            assert_eq!(
+                occupancy.iter().map(|c| c.in_use).sum::<usize>(),
+                held.len()
+            );
+            assert!(occupancy.iter().all(|c| c.idle <= 4));
+        }
+
+        // Every buffer handed out was either allocated or reused, and every one returned was kept or freed
+        held.clear();
+        let stats = pool.stats();
+        assert_eq!(
+            stats.allocations + stats.reuses,
+            stats.returns + stats.discards
+        );
+        // At most 4 buffers are held at once, so each class allocates at most 4 and never discards
+        assert!(stats.allocations <= 8);
+        assert_eq!(stats.discards, 0);
+        assert_eq!(pool.in_use(), 0);
+    }
+
+    #[test]
+    fn test_buffer_outlives_pool() {
+        let pool = BufferPool::new(&[8], 1);
+        let mut buf = pool.get(8).unwrap();
+        drop(pool);
+
+        buf.copy_from_slice(b"pipeline");
+        assert_eq!(&buf[..], b"pipeline");
+        assert_eq!(format!("{:?}", buf), "PooledBuf { len: 8, capacity: 8 }");
+    }
+}
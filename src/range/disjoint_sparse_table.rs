@@ -0,0 +1,173 @@
+//! This module implements a disjoint sparse table, which answers range queries over a static sequence in constant time
+//! for any associative operation. Unlike a plain sparse table, which covers a range with two overlapping halves and so
+//! only works for idempotent operations such as minimum, the two parts combined here never overlap: sums, products
+//! modulo p or string concatenation are answered as easily.
+//!
+//! # Performance
+//! - O(n log n) time and memory to build the table
+//! - O(1) for `query`, a single call of the operation
+//!
+//! # Implementation Details
+//! - At level h the positions are cut into blocks of 2^(h+1), each split at its middle. For every position the level
+//!   stores the combination from it to the middle of its block: towards the middle on the left half, from the middle
+//!   on the right half.
+//! - A range `l..=r` with `l != r` is split at the middle of the smallest block holding both ends, whose level is the
+//!   highest bit of `l ^ r`, so the answer is the left entry of `l` combined with the right entry of `r`.
+//!
+//! # Usage
+//! ```
+//! use data_structures::range::disjoint_sparse_table::DisjointSparseTable;
+//!
+//! // Products modulo a prime, which isn't idempotent
+//! let table = DisjointSparseTable::new(vec![3u64, 5, 7, 11, 13], |a: &u64, b: &u64| a * b % 1_000_000_007);
+//!
+//! assert_eq!(table.query(1..4), Ok(385));
+//! assert_eq!(table.query(0..5), Ok(15015));
+//! ```
+//!
+use std::ops::Range;
+
+use crate::error::DataStructureError;
+
+/// Struct representing a static sequence with precomputed combinations for constant-time range queries
+///
+#[derive(Debug, Clone)]
+pub struct DisjointSparseTable<T, F> {
+    values: Vec<T>,
+    levels: Vec<Vec<T>>,
+    combine: F,
+}
+
+impl<T: Clone, F: Fn(&T, &T) -> T> DisjointSparseTable<T, F> {
+    /// Build the table over some values
+    ///
+    /// # Arguments
+    /// * `values`: The sequence, which can't be changed afterwards
+    /// * `combine`: The associative operation
+    ///
+    /// # Returns
+    /// A new DisjointSparseTable instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::range::disjoint_sparse_table::DisjointSparseTable;
+    ///
+    /// let table = DisjointSparseTable::new(vec![1, 2, 3], |a: &i32, b: &i32| a + b);
+    /// assert_eq!(table.len(), 3);
+    /// ```
+    pub fn new(values: Vec<T>, combine: F) -> Self {
+        let len = values.len();
+        let mut levels = Vec::new();
+        let mut half = 1;
+
+        while half < len {
+            let mut level = values.clone();
+
+            for middle in (half..len).step_by(2 * half) {
+                for i in (middle - half..middle - 1).rev() {
+                    level[i] = combine(&values[i], &level[i + 1]);
+                }
+                for i in middle + 1..(middle + half).min(len) {
+                    level[i] = combine(&level[i - 1], &values[i]);
+                }
+            }
+
+            levels.push(level);
+            half *= 2;
+        }
+
+        DisjointSparseTable {
+            values,
+            levels,
+            combine,
+        }
+    }
+
+    /// Get the number of values
+    /// # Returns
+    /// The length of the sequence
+    /// # Example
+    /// ```
+    /// use data_structures::range::disjoint_sparse_table::DisjointSparseTable;
+    ///
+    /// let table = DisjointSparseTable::new(vec![0u8; 10], |a: &u8, b: &u8| a ^ b);
+    /// assert_eq!(table.len(), 10);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check if the sequence is empty
+    /// # Returns
+    /// True if there are no values
+    /// # Example
+    /// ```
+    /// use data_structures::range::disjoint_sparse_table::DisjointSparseTable;
+    ///
+    /// let table = DisjointSparseTable::new(Vec::new(), |a: &u32, b: &u32| a + b);
+    /// assert!(table.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Combine the values of a range, in order
+    /// # Arguments
+    /// * `range`: The positions to combine
+    /// # Returns
+    /// The combination of the values, or an error if the range is empty or out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::range::disjoint_sparse_table::DisjointSparseTable;
+    ///
+    /// let words = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    /// let table = DisjointSparseTable::new(words, |a: &String, b: &String| format!("{}{}", a, b));
+    ///
+    /// assert_eq!(table.query(0..3), Ok("abc".to_string()));
+    /// assert_eq!(table.query(2..2), Err(DataStructureError::EmptyRange));
+    /// assert_eq!(table.query(1..4), Err(DataStructureError::RangeOutOfBounds { start: 1, end: 4, len: 3 }));
+    /// ```
+    pub fn query(&self, range: Range<usize>) -> Result<T, DataStructureError> {
+        if range.start > range.end || range.end > self.len() {
+            return Err(DataStructureError::RangeOutOfBounds {
+                start: range.start,
+                end: range.end,
+                len: self.len(),
+            });
+        }
+        if range.is_empty() {
+            return Err(DataStructureError::EmptyRange);
+        }
+
+        let (first, last) = (range.start, range.end - 1);
+        if first == last {
+            return Ok(self.values[first].clone());
+        }
+
+        let level = &self.levels[(first ^ last).ilog2() as usize];
+        Ok((self.combine)(&level[first], &level[last]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disjoint_sparse_table_against_fold() {
+        // Concatenation keeps track of the order of the operands
+        for len in [1, 2, 3, 7, 8, 9, 33] {
+            let values: Vec<String> = (0..len).map(|n| format!("{},", n)).collect();
+            let table = DisjointSparseTable::new(values.clone(), |a: &String, b: &String| {
+                format!("{}{}", a, b)
+            });
+
+            for start in 0..len {
+                for end in start + 1..=len {
+                    assert_eq!(table.query(start..end), Ok(values[start..end].concat()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,451 @@
+//! This module implements a segment tree with lazy propagation, which answers range queries over a sequence while
+//! allowing updates of whole ranges at once, such as adding a value to every element or assigning one.
+//! What the values are, how they combine and how an update changes them is described by a `LazyOperation`, so new
+//! kinds of updates can be plugged in. The common ones are provided: `AddSum`, `AssignSum`, `AddMin` and `AssignMin`.
+//!
+//! # Performance
+//! - O(n) to build the tree
+//! - O(log n) for `update` and `query`
+//!
+//! # Implementation Details
+//! - The tree is stored top-down in a `Vec`, node `i` having children `2i` and `2i + 1`, with the root at 1.
+//! - An update covering a whole node changes its value and is kept on the node instead of being passed to its
+//!   children. It is pushed to the children, composed with their own pending updates, only when a later operation
+//!   needs to go below that node.
+//!
+//! # Usage
+//! ```
+//! use data_structures::range::lazy_segment_tree::{AddSum, LazySegmentTree};
+//!
+//! let mut tree: LazySegmentTree<AddSum<i64>> = LazySegmentTree::new(vec![1, 2, 3, 4, 5]);
+//!
+//! tree.update(1..4, 10).unwrap();
+//! assert_eq!(tree.query(0..5), Ok(45));
+//! assert_eq!(tree.query(3..5), Ok(19));
+//! ```
+//!
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Range};
+
+use crate::error::DataStructureError;
+use crate::range::sqrt_blocks::times;
+
+/// Trait describing the values of a lazy segment tree and the updates applied to ranges of them
+///
+pub trait LazyOperation {
+    /// The values of the sequence, and the result of queries
+    type Value: Clone;
+    /// The updates applied to ranges
+    type Update: Clone;
+
+    /// The value of an empty range, neutral for `combine`
+    fn identity() -> Self::Value;
+
+    /// Combine the values of two adjacent ranges, in order; must be associative
+    fn combine(left: &Self::Value, right: &Self::Value) -> Self::Value;
+
+    /// Apply an update to the combined value of a range of `len` elements
+    fn apply(update: &Self::Update, value: &Self::Value, len: usize) -> Self::Value;
+
+    /// Merge two updates into one, `newer` being applied after `older`
+    fn compose(newer: &Self::Update, older: &Self::Update) -> Self::Update;
+}
+
+/// Range addition with range sum queries, `T::default()` being zero
+///
+pub struct AddSum<T>(PhantomData<T>);
+
+impl<T: Copy + Default + Add<Output = T>> LazyOperation for AddSum<T> {
+    type Value = T;
+    type Update = T;
+
+    fn identity() -> T {
+        T::default()
+    }
+
+    fn combine(left: &T, right: &T) -> T {
+        *left + *right
+    }
+
+    fn apply(update: &T, value: &T, len: usize) -> T {
+        *value + times(*update, len)
+    }
+
+    fn compose(newer: &T, older: &T) -> T {
+        *newer + *older
+    }
+}
+
+/// Range assignment with range sum queries, `T::default()` being zero
+///
+pub struct AssignSum<T>(PhantomData<T>);
+
+impl<T: Copy + Default + Add<Output = T>> LazyOperation for AssignSum<T> {
+    type Value = T;
+    type Update = T;
+
+    fn identity() -> T {
+        T::default()
+    }
+
+    fn combine(left: &T, right: &T) -> T {
+        *left + *right
+    }
+
+    fn apply(update: &T, _value: &T, len: usize) -> T {
+        times(*update, len)
+    }
+
+    fn compose(newer: &T, _older: &T) -> T {
+        *newer
+    }
+}
+
+fn min_of<T: Copy + Ord>(left: &Option<T>, right: &Option<T>) -> Option<T> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(*left.min(right)),
+        (other, None) | (None, other) => *other,
+    }
+}
+
+/// Range addition with range minimum queries, None being the minimum of an empty range
+///
+pub struct AddMin<T>(PhantomData<T>);
+
+impl<T: Copy + Ord + Add<Output = T>> LazyOperation for AddMin<T> {
+    type Value = Option<T>;
+    type Update = T;
+
+    fn identity() -> Option<T> {
+        None
+    }
+
+    fn combine(left: &Option<T>, right: &Option<T>) -> Option<T> {
+        min_of(left, right)
+    }
+
+    fn apply(update: &T, value: &Option<T>, _len: usize) -> Option<T> {
+        value.map(|value| value + *update)
+    }
+
+    fn compose(newer: &T, older: &T) -> T {
+        *newer + *older
+    }
+}
+
+/// Range assignment with range minimum queries, None being the minimum of an empty range
+///
+pub struct AssignMin<T>(PhantomData<T>);
+
+impl<T: Copy + Ord> LazyOperation for AssignMin<T> {
+    type Value = Option<T>;
+    type Update = T;
+
+    fn identity() -> Option<T> {
+        None
+    }
+
+    fn combine(left: &Option<T>, right: &Option<T>) -> Option<T> {
+        min_of(left, right)
+    }
+
+    fn apply(update: &T, _value: &Option<T>, _len: usize) -> Option<T> {
+        Some(*update)
+    }
+
+    fn compose(newer: &T, _older: &T) -> T {
+        *newer
+    }
+}
+
+/// Struct representing a segment tree whose updates apply to whole ranges
+///
+pub struct LazySegmentTree<O: LazyOperation> {
+    tree: Vec<O::Value>,
+    pending: Vec<Option<O::Update>>,
+    len: usize,
+}
+
+impl<O: LazyOperation> LazySegmentTree<O> {
+    /// Build a lazy segment tree over some values
+    ///
+    /// # Arguments
+    /// * `values`: The initial sequence
+    ///
+    /// # Returns
+    /// A new LazySegmentTree instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::range::lazy_segment_tree::{AddMin, LazySegmentTree};
+    ///
+    /// let tree: LazySegmentTree<AddMin<i32>> = LazySegmentTree::new(vec![Some(3), Some(1)]);
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn new(values: Vec<O::Value>) -> Self {
+        let len = values.len();
+        let mut tree = LazySegmentTree {
+            tree: vec![O::identity(); 4 * len.max(1)],
+            pending: vec![None; 4 * len.max(1)],
+            len,
+        };
+
+        if len > 0 {
+            tree.build(1, 0..len, &mut values.into_iter());
+        }
+        tree
+    }
+
+    /// Get the number of values
+    /// # Returns
+    /// The length of the sequence
+    /// # Example
+    /// ```
+    /// use data_structures::range::lazy_segment_tree::{AssignSum, LazySegmentTree};
+    ///
+    /// let tree: LazySegmentTree<AssignSum<u32>> = LazySegmentTree::new(vec![0; 10]);
+    /// assert_eq!(tree.len(), 10);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the sequence is empty
+    /// # Returns
+    /// True if there are no values
+    /// # Example
+    /// ```
+    /// use data_structures::range::lazy_segment_tree::{AddSum, LazySegmentTree};
+    ///
+    /// let tree: LazySegmentTree<AddSum<i32>> = LazySegmentTree::new(Vec::new());
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Apply an update to every value of a range
+    /// # Arguments
+    /// * `range`: The positions to update
+    /// * `update`: The update to apply
+    /// # Returns
+    /// An error if the range is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::range::lazy_segment_tree::{AssignMin, LazySegmentTree};
+    ///
+    /// let mut tree: LazySegmentTree<AssignMin<i32>> = LazySegmentTree::new(vec![Some(5); 4]);
+    ///
+    /// tree.update(1..3, 2).unwrap();
+    /// assert_eq!(tree.query(0..4), Ok(Some(2)));
+    /// assert_eq!(tree.update(2..5, 0), Err(DataStructureError::RangeOutOfBounds { start: 2, end: 5, len: 4 }));
+    /// ```
+    pub fn update(
+        &mut self,
+        range: Range<usize>,
+        update: O::Update,
+    ) -> Result<(), DataStructureError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(DataStructureError::RangeOutOfBounds {
+                start: range.start,
+                end: range.end,
+                len: self.len,
+            });
+        }
+
+        if !range.is_empty() {
+            self.update_node(1, 0..self.len, &range, &update);
+        }
+        Ok(())
+    }
+
+    /// Combine the values of a range, in order
+    /// # Arguments
+    /// * `range`: The positions to combine
+    /// # Returns
+    /// The combination of the values, the identity for an empty range, or an error if the range is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::range::lazy_segment_tree::{AssignSum, LazySegmentTree};
+    ///
+    /// let mut tree: LazySegmentTree<AssignSum<i32>> = LazySegmentTree::new(vec![1, 2, 3, 4]);
+    /// tree.update(0..2, 7).unwrap();
+    ///
+    /// assert_eq!(tree.query(1..3), Ok(10));
+    /// assert_eq!(tree.query(2..2), Ok(0));
+    /// ```
+    pub fn query(&mut self, range: Range<usize>) -> Result<O::Value, DataStructureError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(DataStructureError::RangeOutOfBounds {
+                start: range.start,
+                end: range.end,
+                len: self.len,
+            });
+        }
+
+        if range.is_empty() {
+            return Ok(O::identity());
+        }
+        Ok(self.query_node(1, 0..self.len, &range))
+    }
+
+    fn build(
+        &mut self,
+        node: usize,
+        span: Range<usize>,
+        values: &mut impl Iterator<Item = O::Value>,
+    ) {
+        if span.len() == 1 {
+            self.tree[node] = values.next().unwrap();
+            return;
+        }
+
+        let middle = span.start + span.len() / 2;
+        self.build(2 * node, span.start..middle, values);
+        self.build(2 * node + 1, middle..span.end, values);
+        self.tree[node] = O::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    // Change the value of a node covered by an update, and keep the update for its children
+    fn apply_node(&mut self, node: usize, len: usize, update: &O::Update) {
+        self.tree[node] = O::apply(update, &self.tree[node], len);
+        self.pending[node] = Some(match &self.pending[node] {
+            Some(older) => O::compose(update, older),
+            None => update.clone(),
+        });
+    }
+
+    // Pass the pending update of a node to its children
+    fn push_down(&mut self, node: usize, span: &Range<usize>) {
+        if let Some(update) = self.pending[node].take() {
+            let middle = span.start + span.len() / 2;
+            self.apply_node(2 * node, middle - span.start, &update);
+            self.apply_node(2 * node + 1, span.end - middle, &update);
+        }
+    }
+
+    fn update_node(
+        &mut self,
+        node: usize,
+        span: Range<usize>,
+        range: &Range<usize>,
+        update: &O::Update,
+    ) {
+        if range.end <= span.start || span.end <= range.start {
+            return;
+        }
+        if range.start <= span.start && span.end <= range.end {
+            self.apply_node(node, span.len(), update);
+            return;
+        }
+
+        self.push_down(node, &span);
+        let middle = span.start + span.len() / 2;
+        self.update_node(2 * node, span.start..middle, range, update);
+        self.update_node(2 * node + 1, middle..span.end, range, update);
+        self.tree[node] = O::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    fn query_node(&mut self, node: usize, span: Range<usize>, range: &Range<usize>) -> O::Value {
+        if range.end <= span.start || span.end <= range.start {
+            return O::identity();
+        }
+        if range.start <= span.start && span.end <= range.end {
+            return self.tree[node].clone();
+        }
+
+        self.push_down(node, &span);
+        let middle = span.start + span.len() / 2;
+        let left = self.query_node(2 * node, span.start..middle, range);
+        let right = self.query_node(2 * node + 1, middle..span.end, range);
+        O::combine(&left, &right)
+    }
+}
+
+impl<O: LazyOperation> fmt::Debug for LazySegmentTree<O> {
+    /// Prints the number of values, since the values themselves may be waiting on pending updates
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazySegmentTree")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    // Affine updates x -> a * x + b with sum queries, composed in order
+    struct AffineSum;
+
+    impl LazyOperation for AffineSum {
+        type Value = i64;
+        type Update = (i64, i64);
+
+        fn identity() -> i64 {
+            0
+        }
+
+        fn combine(left: &i64, right: &i64) -> i64 {
+            left + right
+        }
+
+        fn apply(&(a, b): &(i64, i64), value: &i64, len: usize) -> i64 {
+            a * value + b * len as i64
+        }
+
+        fn compose(&(a2, b2): &(i64, i64), &(a1, b1): &(i64, i64)) -> (i64, i64) {
+            (a2 * a1, a2 * b1 + b2)
+        }
+    }
+
+    #[test]
+    fn test_lazy_segment_tree_against_vec() {
+        let mut values: Vec<i64> = (0..41).map(|n| (n * 7919) % 101 - 50).collect();
+        let mut sums: LazySegmentTree<AddSum<i64>> = LazySegmentTree::new(values.clone());
+        let mut mins: LazySegmentTree<AddMin<i64>> =
+            LazySegmentTree::new(values.iter().copied().map(Some).collect());
+        let mut rng = Lcg::new(41);
+
+        for _ in 0..500 {
+            let seed = rng.next_u64();
+            let (a, b) = ((seed >> 33) as usize % 42, (seed >> 45) as usize % 42);
+            let range = a.min(b)..a.max(b);
+            let delta = (seed >> 52) as i64 - 2000;
+
+            sums.update(range.clone(), delta).unwrap();
+            mins.update(range.clone(), delta).unwrap();
+            values[range.clone()].iter_mut().for_each(|v| *v += delta);
+
+            let (c, d) = ((seed >> 20) as usize % 42, (seed >> 8) as usize % 42);
+            let query = c.min(d)..c.max(d);
+            assert_eq!(
+                sums.query(query.clone()),
+                Ok(values[query.clone()].iter().sum())
+            );
+            assert_eq!(
+                mins.query(query.clone()),
+                Ok(values[query].iter().min().copied())
+            );
+        }
+    }
+
+    #[test]
+    fn test_user_defined_operation() {
+        let mut tree: LazySegmentTree<AffineSum> = LazySegmentTree::new(vec![1, 2, 3, 4]);
+
+        // Double everything, then add 1 to the middle: [2, 5, 7, 8]
+        tree.update(0..4, (2, 0)).unwrap();
+        tree.update(1..3, (1, 1)).unwrap();
+        assert_eq!(tree.query(0..4), Ok(22));
+        assert_eq!(tree.query(1..2), Ok(5));
+
+        // Reset the left half to 3: [3, 3, 7, 8]
+        tree.update(0..2, (0, 3)).unwrap();
+        assert_eq!(tree.query(0..3), Ok(13));
+        assert_eq!(tree.query(3..4), Ok(8));
+    }
+}
@@ -0,0 +1,227 @@
+//! This module implements a merge-sort tree, which answers order-statistics questions about ranges of a static
+//! sequence: how many values of a range are at most `x`, and which value is the k-th smallest of a range.
+//! Every node of a segment tree over the sequence keeps the values of its span in sorted order.
+//!
+//! # Performance
+//! - O(n log n) time and memory to build the tree
+//! - O(log² n) for `count_le`
+//! - O(log³ n) for `kth_smallest`
+//!
+//! # Implementation Details
+//! - The tree is laid out bottom-up like the `SegmentTree`: leaves at `n..2n`, node `i` merging the sorted lists of
+//!   `2i` and `2i + 1`. Node 1 ends up holding every value.
+//! - `count_le` splits the range into O(log n) nodes and binary searches each list.
+//! - `kth_smallest` binary searches the sorted list of all values for the first one with more than k values of the
+//!   range at or below it.
+//!
+//! # Usage
+//! ```
+//! use data_structures::range::merge_sort_tree::MergeSortTree;
+//!
+//! let tree = MergeSortTree::new(vec![5, 1, 4, 2, 8, 3]);
+//!
+//! assert_eq!(tree.count_le(1..5, &4), Ok(3));
+//! assert_eq!(tree.kth_smallest(1..5, 0), Ok(&1));
+//! assert_eq!(tree.kth_smallest(1..5, 3), Ok(&8));
+//! ```
+//!
+use std::ops::Range;
+
+use crate::error::DataStructureError;
+
+/// Struct representing a static sequence with sorted copies of its ranges, for order statistics
+///
+#[derive(Debug, Clone)]
+pub struct MergeSortTree<T> {
+    nodes: Vec<Vec<T>>,
+    len: usize,
+}
+
+impl<T: Ord + Clone> MergeSortTree<T> {
+    /// Build a merge-sort tree over some values
+    ///
+    /// # Arguments
+    /// * `values`: The sequence, which can't be changed afterwards
+    ///
+    /// # Returns
+    /// A new MergeSortTree instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::range::merge_sort_tree::MergeSortTree;
+    ///
+    /// let tree = MergeSortTree::new(vec![3, 1, 2]);
+    /// assert_eq!(tree.len(), 3);
+    /// ```
+    pub fn new(values: Vec<T>) -> Self {
+        let len = values.len();
+        let mut nodes = vec![Vec::new(); len];
+        nodes.extend(values.into_iter().map(|value| vec![value]));
+
+        for node in (1..len).rev() {
+            nodes[node] = merge(&nodes[2 * node], &nodes[2 * node + 1]);
+        }
+
+        MergeSortTree { nodes, len }
+    }
+
+    /// Get the number of values
+    /// # Returns
+    /// The length of the sequence
+    /// # Example
+    /// ```
+    /// use data_structures::range::merge_sort_tree::MergeSortTree;
+    ///
+    /// let tree = MergeSortTree::new(vec!['a'; 4]);
+    /// assert_eq!(tree.len(), 4);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the sequence is empty
+    /// # Returns
+    /// True if there are no values
+    /// # Example
+    /// ```
+    /// use data_structures::range::merge_sort_tree::MergeSortTree;
+    ///
+    /// let tree: MergeSortTree<u8> = MergeSortTree::new(Vec::new());
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Count the values of a range that are less than or equal to a bound
+    /// # Arguments
+    /// * `range`: The positions to look at
+    /// * `bound`: The largest value to count
+    /// # Returns
+    /// The number of values at or below the bound, or an error if the range is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::range::merge_sort_tree::MergeSortTree;
+    ///
+    /// let tree = MergeSortTree::new(vec![7, 3, 9, 3]);
+    ///
+    /// assert_eq!(tree.count_le(0..4, &3), Ok(2));
+    /// assert_eq!(tree.count_le(0..2, &100), Ok(2));
+    /// assert_eq!(tree.count_le(2..5, &0), Err(DataStructureError::RangeOutOfBounds { start: 2, end: 5, len: 4 }));
+    /// ```
+    pub fn count_le(&self, range: Range<usize>, bound: &T) -> Result<usize, DataStructureError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(DataStructureError::RangeOutOfBounds {
+                start: range.start,
+                end: range.end,
+                len: self.len,
+            });
+        }
+
+        let count = |node: usize| self.nodes[node].partition_point(|value| value <= bound);
+        let (mut left, mut right) = (range.start + self.len, range.end + self.len);
+        let mut total = 0;
+
+        while left < right {
+            if left % 2 == 1 {
+                total += count(left);
+                left += 1;
+            }
+            if right % 2 == 1 {
+                right -= 1;
+                total += count(right);
+            }
+            left /= 2;
+            right /= 2;
+        }
+
+        Ok(total)
+    }
+
+    /// Find the k-th smallest value of a range
+    /// # Arguments
+    /// * `range`: The positions to look at
+    /// * `k`: The rank of the value, 0 for the smallest
+    /// # Returns
+    /// A reference to the value, or an error if the range is out of bounds or has k values or less
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::range::merge_sort_tree::MergeSortTree;
+    ///
+    /// let tree = MergeSortTree::new(vec![7, 3, 9, 3]);
+    ///
+    /// assert_eq!(tree.kth_smallest(0..4, 1), Ok(&3));
+    /// assert_eq!(tree.kth_smallest(0..4, 2), Ok(&7));
+    /// assert_eq!(tree.kth_smallest(0..2, 2), Err(DataStructureError::RankOutOfRange { rank: 2, len: 2 }));
+    /// ```
+    pub fn kth_smallest(&self, range: Range<usize>, k: usize) -> Result<&T, DataStructureError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(DataStructureError::RangeOutOfBounds {
+                start: range.start,
+                end: range.end,
+                len: self.len,
+            });
+        }
+        if k >= range.len() {
+            return Err(DataStructureError::RankOutOfRange {
+                rank: k,
+                len: range.len(),
+            });
+        }
+
+        // The first value of the whole sequence with more than k values of the range at or below it
+        let all = &self.nodes[1];
+        let position =
+            all.partition_point(|value| self.count_le(range.clone(), value).unwrap() <= k);
+        Ok(&all[position])
+    }
+}
+
+// Merge two sorted lists
+fn merge<T: Ord + Clone>(left: &[T], right: &[T]) -> Vec<T> {
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let (mut i, mut j) = (0, 0);
+
+    while i < left.len() && j < right.len() {
+        if right[j] < left[i] {
+            merged.push(right[j].clone());
+            j += 1;
+        } else {
+            merged.push(left[i].clone());
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&left[i..]);
+    merged.extend_from_slice(&right[j..]);
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_sort_tree_against_sorting() {
+        let values: Vec<i32> = (0..45).map(|n| (n * 7919) % 61 - 30).collect();
+        let tree = MergeSortTree::new(values.clone());
+
+        for start in 0..=45 {
+            for end in start..=45 {
+                let mut sorted = values[start..end].to_vec();
+                sorted.sort();
+
+                for bound in [-31, -5, 0, 12, 30] {
+                    let expected = sorted.iter().filter(|&&v| v <= bound).count();
+                    assert_eq!(tree.count_le(start..end, &bound), Ok(expected));
+                }
+                for (k, value) in sorted.iter().enumerate() {
+                    assert_eq!(tree.kth_smallest(start..end, k), Ok(value));
+                }
+                assert!(tree.kth_smallest(start..end, sorted.len()).is_err());
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! This module implements a segment tree, which answers range queries over a sequence while allowing point updates.
+//! The values are combined with any associative operation that has an identity element (a monoid): sum, minimum,
+//! maximum, gcd, matrix product, and so on. The operation doesn't have to be commutative, ranges are folded in order.
+//!
+//! # Performance
+//! - O(n) to build the tree
+//! - O(log n) for `set` and `query`
+//! - O(1) for `get`
+//!
+//! # Implementation Details
+//! - The tree is stored bottom-up in a single `Vec` of 2n values: the leaves are at `n..2n`, and node `i` combines its
+//!   children `2i` and `2i + 1`.
+//! - Queries climb from both ends of the range at once, folding the left side and the right side separately so the
+//!   order of the values is kept.
+//!
+//! # Usage
+//! ```
+//! use data_structures::range::segment_tree::SegmentTree;
+//!
+//! let mut tree = SegmentTree::new(vec![5, 2, 8, 1, 9], i32::MAX, |a: &i32, b: &i32| *a.min(b));
+//!
+//! assert_eq!(tree.query(0..3), Ok(2));
+//! tree.set(1, 7).unwrap();
+//! assert_eq!(tree.query(0..3), Ok(5));
+//! assert_eq!(tree.query(0..5), Ok(1));
+//! ```
+//!
+use std::fmt;
+use std::ops::Range;
+
+use crate::error::DataStructureError;
+
+/// Struct representing a segment tree over a sequence of values combined by an associative operation
+///
+#[derive(Clone)]
+pub struct SegmentTree<T, F> {
+    tree: Vec<T>,
+    len: usize,
+
+    identity: T,
+    combine: F,
+}
+
+impl<T: Clone, F: Fn(&T, &T) -> T> SegmentTree<T, F> {
+    /// Build a segment tree over some values
+    ///
+    /// # Arguments
+    /// * `values`: The initial sequence
+    /// * `identity`: The identity of the operation, the result of an empty range
+    /// * `combine`: The associative operation
+    ///
+    /// # Returns
+    /// A new SegmentTree instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::range::segment_tree::SegmentTree;
+    ///
+    /// let tree = SegmentTree::new(vec![1, 2, 3], 0, |a: &i32, b: &i32| a + b);
+    /// assert_eq!(tree.len(), 3);
+    /// ```
+    pub fn new(values: Vec<T>, identity: T, combine: F) -> Self {
+        let len = values.len();
+        let mut tree = vec![identity.clone(); len];
+        tree.extend(values);
+
+        for node in (1..len).rev() {
+            tree[node] = combine(&tree[2 * node], &tree[2 * node + 1]);
+        }
+
+        SegmentTree {
+            tree,
+            len,
+            identity,
+            combine,
+        }
+    }
+
+    /// Get the number of values
+    /// # Returns
+    /// The length of the sequence
+    /// # Example
+    /// ```
+    /// use data_structures::range::segment_tree::SegmentTree;
+    ///
+    /// let tree = SegmentTree::new(vec![0u8; 10], 0, |a: &u8, b: &u8| a ^ b);
+    /// assert_eq!(tree.len(), 10);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the sequence is empty
+    /// # Returns
+    /// True if there are no values
+    /// # Example
+    /// ```
+    /// use data_structures::range::segment_tree::SegmentTree;
+    ///
+    /// let tree = SegmentTree::new(Vec::new(), 0, |a: &u32, b: &u32| a + b);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get a value of the sequence
+    /// # Arguments
+    /// * `index`: The position of the value
+    /// # Returns
+    /// A reference to the value, or None if the index is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::range::segment_tree::SegmentTree;
+    ///
+    /// let tree = SegmentTree::new(vec![4, 5], 0, |a: &i32, b: &i32| a + b);
+    /// assert_eq!(tree.get(1), Some(&5));
+    /// assert_eq!(tree.get(2), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(&self.tree[self.len + index])
+    }
+
+    /// Replace a value of the sequence
+    /// # Arguments
+    /// * `index`: The position of the value
+    /// * `value`: The new value
+    /// # Returns
+    /// The old value, or an error if the index is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::range::segment_tree::SegmentTree;
+    ///
+    /// let mut tree = SegmentTree::new(vec![1, 2, 3], 0, |a: &i32, b: &i32| a + b);
+    ///
+    /// assert_eq!(tree.set(0, 10), Ok(1));
+    /// assert_eq!(tree.query(0..3), Ok(15));
+    /// assert_eq!(tree.set(3, 0), Err(DataStructureError::IndexOutOfBounds { index: 3, len: 3 }));
+    /// ```
+    pub fn set(&mut self, index: usize, value: T) -> Result<T, DataStructureError> {
+        if index >= self.len {
+            return Err(DataStructureError::IndexOutOfBounds {
+                index,
+                len: self.len,
+            });
+        }
+
+        let mut node = self.len + index;
+        let old = std::mem::replace(&mut self.tree[node], value);
+
+        while node > 1 {
+            node /= 2;
+            self.tree[node] = (self.combine)(&self.tree[2 * node], &self.tree[2 * node + 1]);
+        }
+
+        Ok(old)
+    }
+
+    /// Combine the values of a range, in order
+    /// # Arguments
+    /// * `range`: The positions to combine
+    /// # Returns
+    /// The combination of the values, the identity for an empty range, or an error if the range is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::range::segment_tree::SegmentTree;
+    ///
+    /// // String concatenation isn't commutative, the order is kept
+    /// let words = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    /// let tree = SegmentTree::new(words, String::new(), |a: &String, b: &String| format!("{}{}", a, b));
+    ///
+    /// assert_eq!(tree.query(1..3), Ok("bc".to_string()));
+    /// assert_eq!(tree.query(2..2), Ok(String::new()));
+    /// assert_eq!(tree.query(0..4), Err(DataStructureError::RangeOutOfBounds { start: 0, end: 4, len: 3 }));
+    /// ```
+    pub fn query(&self, range: Range<usize>) -> Result<T, DataStructureError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(DataStructureError::RangeOutOfBounds {
+                start: range.start,
+                end: range.end,
+                len: self.len,
+            });
+        }
+
+        let mut left_result = self.identity.clone();
+        let mut right_result = self.identity.clone();
+        let (mut left, mut right) = (range.start + self.len, range.end + self.len);
+
+        while left < right {
+            if left % 2 == 1 {
+                left_result = (self.combine)(&left_result, &self.tree[left]);
+                left += 1;
+            }
+            if right % 2 == 1 {
+                right -= 1;
+                right_result = (self.combine)(&self.tree[right], &right_result);
+            }
+            left /= 2;
+            right /= 2;
+        }
+
+        Ok((self.combine)(&left_result, &right_result))
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for SegmentTree<T, F> {
+    /// Prints the sequence, without the inner nodes
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.tree[self.len..]).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_segment_tree_against_fold() {
+        let mut values: Vec<i64> = (0..37).map(|n| (n * 7919) % 101 - 50).collect();
+        let mut tree = SegmentTree::new(values.clone(), 0, |a: &i64, b: &i64| a + b);
+        let mut rng = Lcg::new(17);
+
+        for _ in 0..500 {
+            let seed = rng.next_u64();
+            let (a, b) = ((seed >> 33) as usize % 38, (seed >> 45) as usize % 38);
+            let (start, end) = (a.min(b), a.max(b));
+
+            assert_eq!(tree.query(start..end), Ok(values[start..end].iter().sum()));
+
+            let index = (seed >> 20) as usize % values.len();
+            let value = (seed >> 50) as i64 - 2000;
+            assert_eq!(tree.set(index, value), Ok(values[index]));
+            values[index] = value;
+        }
+
+        assert_eq!(format!("{:?}", tree), format!("{:?}", values));
+    }
+}
@@ -0,0 +1,409 @@
+//! This module implements square-root decomposition over a sequence of numbers, for range additions and range queries.
+//! The sequence is cut into about √n blocks of √n values, and each block keeps the sum, minimum and maximum of its
+//! values along with an addition that applies to the whole block. An operation on a range touches the whole blocks it
+//! covers through their summaries and only the values at its two ends one by one.
+//!
+//! It is slower than a segment tree, but much simpler: a block is a plain slice of values, so new kinds of updates can
+//! be added by rewriting the values of a block and recomputing its summary, without any rule for composing them.
+//!
+//! # Performance
+//! - O(n) to build the blocks
+//! - O(√n) for `add`, `sum`, `min`, `max` and `set`
+//! - O(1) for `get`
+//!
+//! # Implementation Details
+//! - `T::default()` is used as zero, so the sum of an empty range is the default value.
+//! - The addition waiting on a block is applied to its summary right away; multiplying it by the length of the block
+//!   only needs `Add`, by doubling.
+//! - Before changing single values of a block, the waiting addition is pushed down to the values and the summary is
+//!   recomputed from them.
+//!
+//! # Usage
+//! ```
+//! use data_structures::range::sqrt_blocks::SqrtBlocks;
+//!
+//! let mut blocks = SqrtBlocks::new(vec![3, 1, 4, 1, 5, 9, 2, 6]);
+//!
+//! blocks.add(2..6, 10).unwrap();
+//! assert_eq!(blocks.sum(0..8), Ok(71));
+//! assert_eq!(blocks.min(2..8), Ok(2));
+//! assert_eq!(blocks.max(0..4), Ok(14));
+//! assert_eq!(blocks.get(5), Some(19));
+//! ```
+//!
+use std::ops::{Add, Range};
+
+use crate::error::DataStructureError;
+
+// Summary of a block, with the addition that hasn't been pushed down to its values yet
+#[derive(Debug, Clone, Copy)]
+struct Block<T> {
+    pending: T,
+    sum: T,
+    min: T,
+    max: T,
+}
+
+// Add a value to itself `count` times
+pub(crate) fn times<T: Copy + Default + Add<Output = T>>(value: T, mut count: usize) -> T {
+    let mut result = T::default();
+    let mut power = value;
+
+    while count > 0 {
+        if count & 1 == 1 {
+            result = result + power;
+        }
+        count >>= 1;
+        if count > 0 {
+            power = power + power;
+        }
+    }
+
+    result
+}
+
+/// Struct representing a sequence of numbers cut into blocks of about √n values
+///
+#[derive(Debug, Clone)]
+pub struct SqrtBlocks<T> {
+    values: Vec<T>,
+    block_len: usize,
+    blocks: Vec<Block<T>>,
+}
+
+impl<T: Copy + Ord + Default + Add<Output = T>> SqrtBlocks<T> {
+    /// Build the blocks over some values
+    ///
+    /// # Arguments
+    /// * `values`: The initial sequence
+    ///
+    /// # Returns
+    /// A new SqrtBlocks instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::range::sqrt_blocks::SqrtBlocks;
+    ///
+    /// let blocks = SqrtBlocks::new(vec![1, 2, 3]);
+    /// assert_eq!(blocks.len(), 3);
+    /// ```
+    pub fn new(values: Vec<T>) -> Self {
+        let block_len = values.len().isqrt().max(1);
+        let mut blocks = SqrtBlocks {
+            blocks: Vec::with_capacity(values.len().div_ceil(block_len)),
+            values,
+            block_len,
+        };
+
+        for start in (0..blocks.values.len()).step_by(block_len) {
+            let first = blocks.values[start];
+            blocks.blocks.push(Block {
+                pending: T::default(),
+                sum: first,
+                min: first,
+                max: first,
+            });
+            blocks.rebuild(start / block_len);
+        }
+
+        blocks
+    }
+
+    /// Get the number of values
+    /// # Returns
+    /// The length of the sequence
+    /// # Example
+    /// ```
+    /// use data_structures::range::sqrt_blocks::SqrtBlocks;
+    ///
+    /// let blocks = SqrtBlocks::new(vec![0u64; 10]);
+    /// assert_eq!(blocks.len(), 10);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check if the sequence is empty
+    /// # Returns
+    /// True if there are no values
+    /// # Example
+    /// ```
+    /// use data_structures::range::sqrt_blocks::SqrtBlocks;
+    ///
+    /// let blocks: SqrtBlocks<i32> = SqrtBlocks::new(Vec::new());
+    /// assert!(blocks.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Get a value of the sequence
+    /// # Arguments
+    /// * `index`: The position of the value
+    /// # Returns
+    /// The value, or None if the index is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::range::sqrt_blocks::SqrtBlocks;
+    ///
+    /// let mut blocks = SqrtBlocks::new(vec![4, 5]);
+    /// blocks.add(0..2, 1).unwrap();
+    ///
+    /// assert_eq!(blocks.get(1), Some(6));
+    /// assert_eq!(blocks.get(2), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<T> {
+        let value = *self.values.get(index)?;
+        Some(value + self.blocks[index / self.block_len].pending)
+    }
+
+    /// Replace a value of the sequence
+    /// # Arguments
+    /// * `index`: The position of the value
+    /// * `value`: The new value
+    /// # Returns
+    /// The old value, or an error if the index is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::range::sqrt_blocks::SqrtBlocks;
+    ///
+    /// let mut blocks = SqrtBlocks::new(vec![1, 2, 3]);
+    ///
+    /// assert_eq!(blocks.set(0, 10), Ok(1));
+    /// assert_eq!(blocks.sum(0..3), Ok(15));
+    /// assert_eq!(blocks.set(3, 0), Err(DataStructureError::IndexOutOfBounds { index: 3, len: 3 }));
+    /// ```
+    pub fn set(&mut self, index: usize, value: T) -> Result<T, DataStructureError> {
+        if index >= self.len() {
+            return Err(DataStructureError::IndexOutOfBounds {
+                index,
+                len: self.len(),
+            });
+        }
+
+        let block = index / self.block_len;
+        self.push_down(block);
+        let old = std::mem::replace(&mut self.values[index], value);
+        self.rebuild(block);
+
+        Ok(old)
+    }
+
+    /// Add a value to every value of a range
+    /// # Arguments
+    /// * `range`: The positions to update
+    /// * `delta`: The value to add
+    /// # Returns
+    /// An error if the range is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::range::sqrt_blocks::SqrtBlocks;
+    ///
+    /// let mut blocks = SqrtBlocks::new(vec![0; 5]);
+    /// blocks.add(1..4, 2).unwrap();
+    ///
+    /// assert_eq!(blocks.sum(0..5), Ok(6));
+    /// assert_eq!(blocks.add(0..6, 1), Err(DataStructureError::RangeOutOfBounds { start: 0, end: 6, len: 5 }));
+    /// ```
+    pub fn add(&mut self, range: Range<usize>, delta: T) -> Result<(), DataStructureError> {
+        self.check(&range)?;
+
+        let mut index = range.start;
+        while index < range.end {
+            let block = index / self.block_len;
+            let block_end = ((block + 1) * self.block_len).min(self.len());
+
+            if index == block * self.block_len && block_end <= range.end {
+                // Whole block: only the summary changes
+                let summary = &mut self.blocks[block];
+                summary.pending = summary.pending + delta;
+                summary.sum = summary.sum + times(delta, block_end - index);
+                summary.min = summary.min + delta;
+                summary.max = summary.max + delta;
+            } else {
+                self.push_down(block);
+                for value in &mut self.values[index..block_end.min(range.end)] {
+                    *value = *value + delta;
+                }
+                self.rebuild(block);
+            }
+
+            index = block_end;
+        }
+
+        Ok(())
+    }
+
+    /// Get the sum of a range
+    /// # Arguments
+    /// * `range`: The positions to add up
+    /// # Returns
+    /// The sum, the default value for an empty range, or an error if the range is out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::range::sqrt_blocks::SqrtBlocks;
+    ///
+    /// let blocks = SqrtBlocks::new(vec![1, 2, 3, 4]);
+    ///
+    /// assert_eq!(blocks.sum(1..3), Ok(5));
+    /// assert_eq!(blocks.sum(2..2), Ok(0));
+    /// ```
+    pub fn sum(&self, range: Range<usize>) -> Result<T, DataStructureError> {
+        self.check(&range)?;
+        Ok(self
+            .fold(range, |block| block.sum)
+            .fold(T::default(), |a, b| a + b))
+    }
+
+    /// Get the minimum of a range
+    /// # Arguments
+    /// * `range`: The positions to compare
+    /// # Returns
+    /// The smallest value, or an error if the range is empty or out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::range::sqrt_blocks::SqrtBlocks;
+    ///
+    /// let blocks = SqrtBlocks::new(vec![5, 2, 8]);
+    ///
+    /// assert_eq!(blocks.min(0..3), Ok(2));
+    /// assert_eq!(blocks.min(1..1), Err(DataStructureError::EmptyRange));
+    /// ```
+    pub fn min(&self, range: Range<usize>) -> Result<T, DataStructureError> {
+        self.check(&range)?;
+        self.fold(range, |block| block.min)
+            .min()
+            .ok_or(DataStructureError::EmptyRange)
+    }
+
+    /// Get the maximum of a range
+    /// # Arguments
+    /// * `range`: The positions to compare
+    /// # Returns
+    /// The largest value, or an error if the range is empty or out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::range::sqrt_blocks::SqrtBlocks;
+    ///
+    /// let blocks = SqrtBlocks::new(vec![5, 2, 8]);
+    ///
+    /// assert_eq!(blocks.max(0..2), Ok(5));
+    /// assert_eq!(blocks.max(0..4), Err(DataStructureError::RangeOutOfBounds { start: 0, end: 4, len: 3 }));
+    /// ```
+    pub fn max(&self, range: Range<usize>) -> Result<T, DataStructureError> {
+        self.check(&range)?;
+        self.fold(range, |block| block.max)
+            .max()
+            .ok_or(DataStructureError::EmptyRange)
+    }
+
+    fn check(&self, range: &Range<usize>) -> Result<(), DataStructureError> {
+        if range.start > range.end || range.end > self.len() {
+            return Err(DataStructureError::RangeOutOfBounds {
+                start: range.start,
+                end: range.end,
+                len: self.len(),
+            });
+        }
+        Ok(())
+    }
+
+    // The pieces of a range: summaries of whole blocks, single values at the ends
+    fn fold<'a>(
+        &'a self,
+        range: Range<usize>,
+        summary: impl Fn(&Block<T>) -> T + 'a,
+    ) -> impl Iterator<Item = T> + 'a {
+        let mut index = range.start;
+
+        std::iter::from_fn(move || {
+            if index >= range.end {
+                return None;
+            }
+
+            let block = index / self.block_len;
+            let block_end = ((block + 1) * self.block_len).min(self.len());
+
+            if index == block * self.block_len && block_end <= range.end {
+                index = block_end;
+                Some(summary(&self.blocks[block]))
+            } else {
+                index += 1;
+                Some(self.values[index - 1] + self.blocks[block].pending)
+            }
+        })
+    }
+
+    // Apply the waiting addition of a block to its values
+    fn push_down(&mut self, block: usize) {
+        let pending = std::mem::take(&mut self.blocks[block].pending);
+        let start = block * self.block_len;
+        let end = (start + self.block_len).min(self.len());
+
+        for value in &mut self.values[start..end] {
+            *value = *value + pending;
+        }
+    }
+
+    // Recompute the summary of a block whose waiting addition has been pushed down
+    fn rebuild(&mut self, block: usize) {
+        let start = block * self.block_len;
+        let end = (start + self.block_len).min(self.len());
+        let values = &self.values[start..end];
+
+        let summary = &mut self.blocks[block];
+        summary.sum = values.iter().fold(T::default(), |a, &b| a + b);
+        summary.min = *values.iter().min().unwrap();
+        summary.max = *values.iter().max().unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_sqrt_blocks_against_vec() {
+        let mut values: Vec<i64> = (0..53).map(|n| (n * 7919) % 101 - 50).collect();
+        let mut blocks = SqrtBlocks::new(values.clone());
+        let mut rng = Lcg::new(29);
+
+        for round in 0..600 {
+            let seed = rng.next_u64();
+            let (a, b) = ((seed >> 33) as usize % 54, (seed >> 45) as usize % 54);
+            let range = a.min(b)..a.max(b);
+            let delta = (seed >> 52) as i64 - 2000;
+
+            if round % 5 == 4 {
+                let index = (seed >> 20) as usize % values.len();
+                assert_eq!(blocks.set(index, delta), Ok(values[index]));
+                values[index] = delta;
+            } else {
+                blocks.add(range.clone(), delta).unwrap();
+                values[range.clone()].iter_mut().for_each(|v| *v += delta);
+            }
+
+            let slice = &values[range.clone()];
+            assert_eq!(blocks.sum(range.clone()), Ok(slice.iter().sum()));
+            assert_eq!(blocks.min(range.clone()).ok(), slice.iter().min().copied());
+            assert_eq!(blocks.max(range.clone()).ok(), slice.iter().max().copied());
+        }
+
+        let all: Vec<_> = (0..values.len()).map(|i| blocks.get(i).unwrap()).collect();
+        assert_eq!(all, values);
+    }
+
+    #[test]
+    fn test_times_matches_repeated_addition() {
+        for count in 0..40 {
+            assert_eq!(times(3i32, count), 3 * count as i32);
+        }
+        // No overflow from doubling past the last bit
+        assert_eq!(times(i32::MAX / 3, 3), i32::MAX / 3 * 3);
+    }
+}
@@ -0,0 +1,314 @@
+//! This module implements a paced queue, a FIFO queue that releases its elements no faster than a configured rate.
+//! Elements are pushed at any time and popped at most once per interval on average. Up to `burst` elements can be
+//! released back to back after the queue has been idle, and a pop that comes too early returns the instant the next
+//! element will be ready, so outbound calls can be throttled by the queue that holds them.
+//!
+//! # Performance
+//! - O(1) for `push` and `pop_at`
+//!
+//! # Implementation Details
+//! - The elements are kept in a `FIFO`.
+//! - The pace is enforced with the generic cell rate algorithm: the queue keeps the theoretical instant of the next
+//!   release, which moves one interval forward on each release, and a pop is allowed up to `burst - 1` intervals
+//!   before that instant. This is a token bucket of `burst` tokens refilled one per interval, without a refill timer.
+//! - Pops take the current instant as an argument, so the pace can be tested without sleeping. `pop` reads the clock.
+//!
+//! # Usage
+//! ```
+//! use std::time::{Duration, Instant};
+//! use data_structures::rate::paced_queue::{PacedQueue, Release};
+//!
+//! let mut calls = PacedQueue::new(0, Duration::from_millis(100), 2);
+//! for call in ["a", "b", "c"] {
+//!     calls.push(call).unwrap();
+//! }
+//!
+//! // Two calls can leave at once, then one every 100 ms
+//! let start = Instant::now();
+//! assert_eq!(calls.pop_at(start), Release::Ready("a"));
+//! assert_eq!(calls.pop_at(start), Release::Ready("b"));
+//! assert_eq!(calls.pop_at(start), Release::NotReadyUntil(start + Duration::from_millis(100)));
+//! assert_eq!(calls.pop_at(start + Duration::from_millis(100)), Release::Ready("c"));
+//! assert_eq!(calls.pop_at(start + Duration::from_secs(1)), Release::Empty);
+//! ```
+//!
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::error::DataStructureError;
+use crate::linked_list::fifo::FIFO;
+
+/// Result of a pop from a paced queue
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Release<T> {
+    /// The next element, released now
+    Ready(T),
+    /// An element is waiting, but the pace allows no release before this instant
+    NotReadyUntil(Instant),
+    /// No element is waiting
+    Empty,
+}
+
+/// Struct representing a FIFO queue releasing its elements at a limited rate
+///
+pub struct PacedQueue<T> {
+    queue: FIFO<T>,
+    interval: Duration,
+    burst: u32,
+    next_release: Option<Instant>, // theoretical instant of the next release, None before the first one
+}
+
+impl<T> PacedQueue<T> {
+    /// Create a new paced queue
+    /// # Arguments
+    /// * `max_size`: The maximum number of waiting elements, or 0 for a queue without limit
+    /// * `interval`: The minimum average time between two releases
+    /// * `burst`: The number of elements that can be released back to back after an idle period. Must be greater than 0.
+    /// # Returns
+    /// A new PacedQueue instance
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use data_structures::rate::paced_queue::PacedQueue;
+    ///
+    /// let queue: PacedQueue<u32> = PacedQueue::new(10, Duration::from_secs(1), 3);
+    /// assert_eq!(queue.interval(), Duration::from_secs(1));
+    /// assert_eq!(queue.burst(), 3);
+    /// ```
+    pub fn new(max_size: usize, interval: Duration, burst: u32) -> Self {
+        assert!(burst > 0, "Burst must be greater than 0");
+        PacedQueue {
+            queue: FIFO::new(max_size),
+            interval,
+            burst,
+            next_release: None,
+        }
+    }
+
+    /// Get the minimum average time between two releases
+    /// # Returns
+    /// The interval of the queue
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use data_structures::rate::paced_queue::PacedQueue;
+    ///
+    /// let queue: PacedQueue<u32> = PacedQueue::new(0, Duration::from_millis(5), 1);
+    /// assert_eq!(queue.interval(), Duration::from_millis(5));
+    /// ```
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Get the number of elements that can be released back to back
+    /// # Returns
+    /// The burst allowance of the queue
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use data_structures::rate::paced_queue::PacedQueue;
+    ///
+    /// let queue: PacedQueue<u32> = PacedQueue::new(0, Duration::from_millis(5), 4);
+    /// assert_eq!(queue.burst(), 4);
+    /// ```
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// Get the number of waiting elements
+    /// # Returns
+    /// The number of elements in the queue
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use data_structures::rate::paced_queue::PacedQueue;
+    ///
+    /// let mut queue = PacedQueue::new(0, Duration::from_millis(5), 1);
+    /// queue.push(1).unwrap();
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Check if no element is waiting
+    /// # Returns
+    /// True if the queue is empty
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use data_structures::rate::paced_queue::PacedQueue;
+    ///
+    /// let queue: PacedQueue<u32> = PacedQueue::new(0, Duration::from_millis(5), 1);
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Add an element at the end of the queue
+    /// # Arguments
+    /// * `value`: The element
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the element was added, Err(DataStructureError::Full) if the queue is full
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::rate::paced_queue::PacedQueue;
+    ///
+    /// let mut queue = PacedQueue::new(1, Duration::from_millis(5), 1);
+    /// assert_eq!(queue.push(1), Ok(()));
+    /// assert_eq!(queue.push(2), Err(DataStructureError::Full));
+    /// ```
+    pub fn push(&mut self, value: T) -> Result<(), DataStructureError> {
+        self.queue.push(value)
+    }
+
+    /// Get the first instant the pace allows a release, whether an element is waiting or not
+    /// # Returns
+    /// The instant of the next allowed release, or None if a release is allowed at any instant
+    /// # Example
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use data_structures::rate::paced_queue::PacedQueue;
+    ///
+    /// let mut queue = PacedQueue::new(0, Duration::from_secs(1), 1);
+    /// assert_eq!(queue.ready_at(), None);
+    ///
+    /// let now = Instant::now();
+    /// queue.push(()).unwrap();
+    /// queue.pop_at(now);
+    /// assert_eq!(queue.ready_at(), Some(now + Duration::from_secs(1)));
+    /// ```
+    pub fn ready_at(&self) -> Option<Instant> {
+        // With a burst, releases are allowed up to burst - 1 intervals ahead of the theoretical instant
+        let tolerance = self.interval * (self.burst - 1);
+        self.next_release?.checked_sub(tolerance)
+    }
+
+    /// Release the next element if the pace allows it at the given instant
+    /// # Arguments
+    /// * `now`: The current instant
+    /// # Returns
+    /// Release::Ready with the oldest element, Release::NotReadyUntil with the instant it can be released, or
+    /// Release::Empty if no element is waiting
+    /// # Example
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use data_structures::rate::paced_queue::{PacedQueue, Release};
+    ///
+    /// let mut queue = PacedQueue::new(0, Duration::from_secs(1), 1);
+    /// queue.push('a').unwrap();
+    /// queue.push('b').unwrap();
+    ///
+    /// let now = Instant::now();
+    /// assert_eq!(queue.pop_at(now), Release::Ready('a'));
+    /// assert_eq!(queue.pop_at(now), Release::NotReadyUntil(now + Duration::from_secs(1)));
+    /// ```
+    pub fn pop_at(&mut self, now: Instant) -> Release<T> {
+        if self.queue.is_empty() {
+            return Release::Empty;
+        }
+        if let Some(ready_at) = self.ready_at() {
+            if now < ready_at {
+                return Release::NotReadyUntil(ready_at);
+            }
+        }
+
+        // An idle period doesn't earn more than the burst: the theoretical instant never lags behind now
+        let base = self.next_release.map_or(now, |next| next.max(now));
+        self.next_release = Some(base + self.interval);
+        match self.queue.pop() {
+            Some(value) => Release::Ready(value),
+            None => Release::Empty,
+        }
+    }
+
+    /// Release the next element if the pace allows it now
+    /// # Returns
+    /// Release::Ready with the oldest element, Release::NotReadyUntil with the instant it can be released, or
+    /// Release::Empty if no element is waiting
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use data_structures::rate::paced_queue::{PacedQueue, Release};
+    ///
+    /// let mut queue = PacedQueue::new(0, Duration::from_secs(60), 1);
+    /// queue.push(1).unwrap();
+    /// queue.push(2).unwrap();
+    ///
+    /// assert_eq!(queue.pop(), Release::Ready(1));
+    /// assert!(matches!(queue.pop(), Release::NotReadyUntil(_)));
+    /// ```
+    pub fn pop(&mut self) -> Release<T> {
+        self.pop_at(Instant::now())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PacedQueue<T> {
+    /// Prints the pace and the waiting elements from the oldest to the newest
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PacedQueue")
+            .field("interval", &self.interval)
+            .field("burst", &self.burst)
+            .field("ready_at", &self.ready_at())
+            .field("queue", &self.queue)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pace_and_burst() {
+        let interval = Duration::from_millis(10);
+        let mut queue = PacedQueue::new(0, interval, 3);
+        for i in 0..100 {
+            queue.push(i).unwrap();
+        }
+
+        // Poll every millisecond for a second and record the release times
+        let start = Instant::now();
+        let mut released = Vec::new();
+        for tick in 0..1000u32 {
+            let now = start + Duration::from_millis(tick.into());
+            loop {
+                match queue.pop_at(now) {
+                    Release::Ready(value) => released.push((tick, value)),
+                    Release::NotReadyUntil(at) => {
+                        assert!(at > now);
+                        break;
+                    }
+                    Release::Empty => break,
+                }
+            }
+        }
+
+        // The burst leaves at once, then one element per interval, in FIFO order
+        let ticks: Vec<u32> = released.iter().map(|&(tick, _)| tick).collect();
+        assert_eq!(&ticks[..4], &[0, 0, 0, 10]);
+        assert!(ticks[3..].windows(2).all(|pair| pair[1] - pair[0] == 10));
+        assert!(released.iter().map(|&(_, value)| value).eq(0..100));
+        assert_eq!(ticks.last(), Some(&970));
+
+        // After an idle period the burst is available again, but not more
+        for i in 0..5 {
+            queue.push(i).unwrap();
+        }
+        let later = start + Duration::from_secs(10);
+        let burst = std::iter::from_fn(|| match queue.pop_at(later) {
+            Release::Ready(value) => Some(value),
+            _ => None,
+        });
+        assert_eq!(burst.count(), 3);
+        assert_eq!(
+            queue.pop_at(later),
+            Release::NotReadyUntil(later + interval)
+        );
+    }
+}
@@ -0,0 +1,497 @@
+//! This module implements epoch-based memory reclamation, for lock-free structures that unlink nodes other threads
+//! may still be reading. Instead of freeing an unlinked node right away, a thread defers its destruction; the node is
+//! destroyed once every thread that could have seen it has moved on.
+//!
+//! # Performance
+//! - O(1) for `pin` and `defer`, apart from the collection attempts
+//! - O(p + g) for a collection attempt, where p is the number of registered threads and g the number of deferred
+//!   functions. One is made every 64 unpins, and on `flush`.
+//!
+//! # Implementation Details
+//! - A `Collector` holds a global epoch counter, the list of registered threads and the deferred functions.
+//! - Each thread registers once and gets a `LocalHandle`. Pinning it publishes the global epoch the thread is in,
+//!   and the returned `Guard` unpins it when dropped. Pins nest.
+//! - The global epoch only advances when every pinned thread has seen the current one. A function deferred in epoch
+//!   e therefore runs once the global epoch reaches e + 2: every thread that was pinned when it was deferred has
+//!   unpinned since.
+//! - The deferred functions are kept in a mutex-protected list: the reclaimer is lock-free on the read path (pinning
+//!   is a store and a fence) but not when deferring or collecting.
+//! - The functions left when the collector and every handle are dropped run at that point.
+//!
+//! # Usage
+//! ```
+//! use data_structures::reclaim::epoch::Collector;
+//! use std::sync::atomic::{AtomicPtr, Ordering};
+//!
+//! let collector = Collector::new();
+//! let handle = collector.register();
+//! let shared = AtomicPtr::new(Box::into_raw(Box::new(1)));
+//!
+//! {
+//!     let guard = handle.pin();
+//!     let old = shared.swap(Box::into_raw(Box::new(2)), Ordering::AcqRel);
+//!     // SAFETY: `old` is unlinked, so no thread can load it anymore, and it is never used again here
+//!     unsafe { guard.defer_destroy(old) };
+//! }
+//!
+//! handle.pin().flush();
+//! // SAFETY: no other thread has access to the pointer, which is never used again
+//! drop(unsafe { Box::from_raw(shared.load(Ordering::Acquire)) });
+//! ```
+//!
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// Number of unpins between two collection attempts of a handle
+const COLLECT_PERIOD: u64 = 64;
+
+type Deferred = Box<dyn FnOnce() + Send>;
+
+/// State shared by the collector and the handles
+struct Global {
+    epoch: AtomicU64,
+    locals: Mutex<Vec<Arc<Local>>>,
+    garbage: Mutex<Vec<(u64, Deferred)>>,
+}
+
+/// Published state of a registered thread: its epoch shifted left by one, with the lowest bit set while pinned
+struct Local {
+    state: AtomicU64,
+}
+
+impl Global {
+    fn locals(&self) -> MutexGuard<'_, Vec<Arc<Local>>> {
+        self.locals.lock().expect("Collector lock poisoned")
+    }
+
+    fn garbage(&self) -> MutexGuard<'_, Vec<(u64, Deferred)>> {
+        self.garbage.lock().expect("Collector lock poisoned")
+    }
+
+    // Advance the global epoch if every pinned thread has seen it, then run the functions that became safe
+    fn collect(&self) {
+        let epoch = self.epoch.load(Ordering::SeqCst);
+        let all_caught_up = self.locals().iter().all(|local| {
+            let state = local.state.load(Ordering::SeqCst);
+            state & 1 == 0 || state >> 1 == epoch
+        });
+        if all_caught_up {
+            // Another thread may have advanced it already, which is as good
+            let _ =
+                self.epoch
+                    .compare_exchange(epoch, epoch + 1, Ordering::SeqCst, Ordering::SeqCst);
+        }
+
+        let epoch = self.epoch.load(Ordering::SeqCst);
+        let ready: Vec<Deferred> = {
+            let mut garbage = self.garbage();
+            let (ready, kept) = std::mem::take(&mut *garbage)
+                .into_iter()
+                .partition(|(deferred_at, _)| deferred_at + 2 <= epoch);
+            *garbage = kept;
+            ready.into_iter().map(|(_, function)| function).collect()
+        };
+
+        // Run outside of the lock, the functions may defer more work
+        for function in ready {
+            function();
+        }
+    }
+}
+
+impl Drop for Global {
+    fn drop(&mut self) {
+        // No handle is left, so nothing can still be read
+        let garbage = std::mem::take(self.garbage.get_mut().expect("Collector lock poisoned"));
+        for (_, function) in garbage {
+            function();
+        }
+    }
+}
+
+/// Struct representing an epoch-based garbage collector shared by the threads of a structure
+///
+#[derive(Clone)]
+pub struct Collector {
+    global: Arc<Global>,
+}
+
+/// Registration of a thread in a collector, used to pin the thread
+///
+pub struct LocalHandle {
+    global: Arc<Global>,
+    local: Arc<Local>,
+    pins: Cell<usize>,
+    unpins: Cell<u64>,
+}
+
+/// Proof that the current thread is pinned: nothing deferred after the pin is destroyed while the guard lives
+///
+pub struct Guard<'a> {
+    handle: &'a LocalHandle,
+}
+
+impl Collector {
+    /// Create a new collector, in epoch 0 and without registered threads
+    ///
+    /// # Returns
+    /// A new Collector instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::reclaim::epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// assert_eq!(collector.epoch(), 0);
+    /// ```
+    pub fn new() -> Self {
+        Collector {
+            global: Arc::new(Global {
+                epoch: AtomicU64::new(0),
+                locals: Mutex::new(Vec::new()),
+                garbage: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Register the current thread, or any thread the handle will be sent to
+    /// # Returns
+    /// A handle to pin that thread, unregistered when dropped
+    /// # Example
+    /// ```
+    /// use data_structures::reclaim::epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    /// assert!(!handle.is_pinned());
+    /// ```
+    pub fn register(&self) -> LocalHandle {
+        let local = Arc::new(Local {
+            state: AtomicU64::new(0),
+        });
+        self.global.locals().push(local.clone());
+
+        LocalHandle {
+            global: self.global.clone(),
+            local,
+            pins: Cell::new(0),
+            unpins: Cell::new(0),
+        }
+    }
+
+    /// Get the global epoch
+    /// # Returns
+    /// The number of times the global epoch advanced
+    /// # Example
+    /// ```
+    /// use data_structures::reclaim::epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    ///
+    /// handle.pin().flush();
+    /// assert_eq!(collector.epoch(), 1);
+    /// ```
+    pub fn epoch(&self) -> u64 {
+        self.global.epoch.load(Ordering::SeqCst)
+    }
+
+    /// Get the number of deferred functions that haven't run yet
+    /// # Returns
+    /// The number of pending functions
+    /// # Example
+    /// ```
+    /// use data_structures::reclaim::epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    ///
+    /// handle.pin().defer(|| ());
+    /// assert_eq!(collector.pending(), 1);
+    /// ```
+    pub fn pending(&self) -> usize {
+        self.global.garbage().len()
+    }
+}
+
+impl Default for Collector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Collector {
+    /// Prints the epoch and the number of pending functions
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Collector")
+            .field("epoch", &self.epoch())
+            .field("pending", &self.pending())
+            .finish()
+    }
+}
+
+impl LocalHandle {
+    /// Pin the thread, so that nothing it can still read is destroyed until the guard is dropped
+    /// # Returns
+    /// A guard keeping the thread pinned
+    /// # Example
+    /// ```
+    /// use data_structures::reclaim::epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    ///
+    /// let guard = handle.pin();
+    /// assert!(handle.is_pinned());
+    /// drop(guard);
+    /// assert!(!handle.is_pinned());
+    /// ```
+    pub fn pin(&self) -> Guard<'_> {
+        let pins = self.pins.get();
+        self.pins.set(pins + 1);
+
+        if pins == 0 {
+            let epoch = self.global.epoch.load(Ordering::SeqCst);
+            self.local.state.store(epoch << 1 | 1, Ordering::SeqCst);
+            // The loads made while pinned must not be reordered before the state is published
+            fence(Ordering::SeqCst);
+        }
+
+        Guard { handle: self }
+    }
+
+    /// Check if the thread is pinned through this handle
+    /// # Returns
+    /// True while a guard of this handle is alive
+    /// # Example
+    /// ```
+    /// use data_structures::reclaim::epoch::Collector;
+    ///
+    /// let handle = Collector::new().register();
+    /// let _outer = handle.pin();
+    /// let _inner = handle.pin();
+    /// assert!(handle.is_pinned());
+    /// ```
+    pub fn is_pinned(&self) -> bool {
+        self.pins.get() > 0
+    }
+
+    /// Check if the handle was registered in a given collector
+    /// Structures owning a collector use it to reject handles of another one, whose pins wouldn't protect their nodes.
+    /// # Arguments
+    /// * `collector`: The collector to compare with
+    /// # Returns
+    /// True if `collector` is the one the handle was registered in, or a clone of it
+    /// # Example
+    /// ```
+    /// use data_structures::reclaim::epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    ///
+    /// assert!(handle.is_registered_in(&collector.clone()));
+    /// assert!(!handle.is_registered_in(&Collector::new()));
+    /// ```
+    pub fn is_registered_in(&self, collector: &Collector) -> bool {
+        Arc::ptr_eq(&self.global, &collector.global)
+    }
+
+    fn unpin(&self) {
+        let pins = self.pins.get() - 1;
+        self.pins.set(pins);
+        if pins > 0 {
+            return;
+        }
+
+        let state = self.local.state.load(Ordering::Relaxed);
+        self.local.state.store(state & !1, Ordering::Release);
+
+        let unpins = self.unpins.get() + 1;
+        self.unpins.set(unpins);
+        if unpins.is_multiple_of(COLLECT_PERIOD) {
+            self.global.collect();
+        }
+    }
+}
+
+impl Drop for LocalHandle {
+    fn drop(&mut self) {
+        self.global
+            .locals()
+            .retain(|local| !Arc::ptr_eq(local, &self.local));
+    }
+}
+
+impl fmt::Debug for LocalHandle {
+    /// Prints whether the thread is pinned
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalHandle")
+            .field("pinned", &self.is_pinned())
+            .finish()
+    }
+}
+
+impl Guard<'_> {
+    /// Run a function once no thread pinned at this point is still pinned
+    /// # Arguments
+    /// * `function`: The function to run, usually freeing an unlinked node
+    /// # Example
+    /// ```
+    /// use data_structures::reclaim::epoch::Collector;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let collector = Collector::new();
+    /// let (writer, reader) = (collector.register(), collector.register());
+    /// let done = Arc::new(AtomicBool::new(false));
+    ///
+    /// let reading = reader.pin();
+    /// let flag = done.clone();
+    /// writer.pin().defer(move || flag.store(true, Ordering::SeqCst));
+    ///
+    /// // The reader pinned before the function was deferred, so it can't run yet
+    /// writer.pin().flush();
+    /// writer.pin().flush();
+    /// assert!(!done.load(Ordering::SeqCst));
+    ///
+    /// drop(reading);
+    /// writer.pin().flush();
+    /// writer.pin().flush();
+    /// assert!(done.load(Ordering::SeqCst));
+    /// ```
+    pub fn defer(&self, function: impl FnOnce() + Send + 'static) {
+        let epoch = self.handle.global.epoch.load(Ordering::SeqCst);
+        self.handle
+            .global
+            .garbage()
+            .push((epoch, Box::new(function)));
+    }
+
+    /// Free a boxed value once no thread pinned at this point is still pinned
+    ///
+    /// # Arguments
+    /// * `pointer`: A pointer obtained from `Box::into_raw`
+    ///
+    /// # Safety
+    /// The value must have been unlinked from every shared location before the call, so that only the threads
+    /// pinned at this point can still hold the pointer, and it must not be freed or deferred anywhere else.
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::reclaim::epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    ///
+    /// let node = Box::into_raw(Box::new(String::from("unlinked")));
+    /// // SAFETY: the node was never shared
+    /// unsafe { handle.pin().defer_destroy(node) };
+    /// assert_eq!(collector.pending(), 1);
+    /// ```
+    pub unsafe fn defer_destroy<T: Send + 'static>(&self, pointer: *mut T) {
+        // Raw pointers are not Send, the address is carried as an integer
+        let address = pointer as usize;
+        self.defer(move || {
+            // SAFETY: the caller guarantees the pointer comes from Box::into_raw and is freed only here, and the
+            // collector runs this once no thread can still read it
+            drop(unsafe { Box::from_raw(address as *mut T) });
+        });
+    }
+
+    /// Try to advance the global epoch and run the deferred functions that became safe
+    /// The current thread being pinned, the epoch advances at most once per call.
+    /// # Example
+    /// ```
+    /// use data_structures::reclaim::epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    ///
+    /// handle.pin().defer(|| ());
+    /// handle.pin().flush();
+    /// handle.pin().flush();
+    /// assert_eq!(collector.pending(), 0);
+    /// ```
+    pub fn flush(&self) {
+        self.handle.global.collect();
+    }
+}
+
+impl Drop for Guard<'_> {
+    fn drop(&mut self) {
+        self.handle.unpin();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicPtr, AtomicUsize};
+    use std::thread;
+
+    // Node counting its destructions
+    struct Node {
+        value: usize,
+        drops: Arc<AtomicUsize>,
+    }
+
+    impl Drop for Node {
+        fn drop(&mut self) {
+            self.drops.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_readers_never_see_freed_nodes() {
+        let collector = Collector::new();
+        let drops = Arc::new(AtomicUsize::new(0));
+        let new_node = |value| {
+            Box::into_raw(Box::new(Node {
+                value,
+                drops: drops.clone(),
+            }))
+        };
+        let shared = Arc::new(AtomicPtr::new(new_node(0)));
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = collector.register();
+                let shared = shared.clone();
+                thread::spawn(move || {
+                    let mut last = 0;
+                    for _ in 0..20_000 {
+                        let guard = handle.pin();
+                        // SAFETY: the node is only freed through the collector, after this guard is dropped
+                        let node = unsafe { &*shared.load(Ordering::Acquire) };
+                        assert!(node.value >= last, "read a freed or stale node");
+                        last = node.value;
+                        drop(guard);
+                    }
+                })
+            })
+            .collect();
+
+        let writer = collector.register();
+        for value in 1..=5_000 {
+            let guard = writer.pin();
+            let old = shared.swap(new_node(value), Ordering::AcqRel);
+            // SAFETY: the old node is unlinked and deferred once
+            unsafe { guard.defer_destroy(old) };
+        }
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        // Once nobody reads, two advances free everything but the live node
+        writer.pin().flush();
+        writer.pin().flush();
+        assert_eq!(collector.pending(), 0);
+        assert_eq!(drops.load(Ordering::SeqCst), 5_000);
+
+        // SAFETY: no thread can read the node anymore
+        drop(unsafe { Box::from_raw(shared.load(Ordering::Acquire)) });
+        assert_eq!(drops.load(Ordering::SeqCst), 5_001);
+    }
+}
@@ -0,0 +1,519 @@
+//! This module implements a ring buffer, a FIFO queue stored in a single contiguous buffer.
+//! It has the interface of `linked_list::fifo::FIFO`, so either can be used by changing the type, but keeps its elements
+//! in one allocation instead of one vertex per element: pushing and popping don't touch the allocator once the buffer
+//! has grown to its working size, and walking the elements is cache friendly.
+//!
+//! # Performance
+//! - O(1) for `push` and `pop`, `push` being amortized when the buffer grows
+//! - O(1) for `len`, `is_full`, `front` and `back`
+//!
+//! # Implementation Details
+//! - The elements live in a `Vec` of slots used circularly: `head` is the slot of the oldest element and the others
+//!   follow it, wrapping around the end of the buffer.
+//! - The buffer grows by doubling, up to the maximum size for a bounded buffer, and keeps its capacity when elements are
+//!   popped. Growing moves the elements to the start of a new buffer.
+//! - Slots are `Option`s, so the buffer is safe code: a popped slot is left `None`.
+//!
+//! # Usage
+//! ```
+//! use data_structures::ring::ring_buffer::RingBuffer;
+//!
+//! let mut buffer = RingBuffer::new(3);
+//!
+//! buffer.push(1).unwrap();
+//! buffer.push(2).unwrap();
+//! buffer.push(3).unwrap();
+//! assert!(buffer.is_full());
+//!
+//! assert_eq!(buffer.push_overwrite(4), Some(1));
+//! assert_eq!(buffer.pop(), Some(2));
+//! assert_eq!(buffer.front(), Some(3));
+//! assert_eq!(buffer.back(), Some(4));
+//! ```
+//!
+use std::fmt;
+
+use crate::error::DataStructureError;
+
+/// Smallest capacity allocated by the first push
+const MIN_CAPACITY: usize = 4;
+
+/// Struct representing a FIFO queue stored in a contiguous circular buffer
+///
+#[derive(Clone)]
+pub struct RingBuffer<T> {
+    slots: Vec<Option<T>>,
+    head: usize,
+    len: usize,
+    max_size: usize,
+}
+
+impl<T> RingBuffer<T> {
+    /// Create a new empty ring buffer with a maximum size
+    /// The buffer allocates on the first push, and grows up to the maximum size.
+    /// # Arguments
+    /// * `max_size` - The maximum number of elements, or 0 for a buffer that grows without limit
+    /// # Returns
+    /// A new RingBuffer instance
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let buffer: RingBuffer<u32> = RingBuffer::new(5);
+    ///
+    /// assert_eq!(buffer.len(), 0);
+    /// assert_eq!(buffer.max_size(), 5);
+    /// ```
+    pub fn new(max_size: usize) -> Self {
+        RingBuffer {
+            slots: Vec::new(),
+            head: 0,
+            len: 0,
+            max_size,
+        }
+    }
+
+    /// Get the number of elements in the buffer
+    /// # Returns
+    /// The number of elements in the buffer
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let mut buffer = RingBuffer::new(0);
+    /// buffer.push('a').unwrap();
+    /// assert_eq!(buffer.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the buffer is empty
+    /// # Returns
+    /// True if the buffer is empty, false otherwise
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let buffer: RingBuffer<u32> = RingBuffer::new(2);
+    /// assert!(buffer.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Check if the buffer is full
+    /// # Returns
+    /// True if the buffer holds its maximum number of elements, always false for a buffer without limit
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let mut buffer = RingBuffer::new(1);
+    /// buffer.push(1).unwrap();
+    /// assert!(buffer.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.max_size != 0 && self.len == self.max_size
+    }
+
+    /// Get the maximum size of the buffer
+    /// # Returns
+    /// The maximum number of elements, or 0 for a buffer without limit
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let buffer: RingBuffer<u32> = RingBuffer::new(7);
+    /// assert_eq!(buffer.max_size(), 7);
+    /// ```
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Get the number of elements the buffer can hold without growing
+    /// # Returns
+    /// The number of slots allocated
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let mut buffer = RingBuffer::new(0);
+    /// assert_eq!(buffer.capacity(), 0);
+    ///
+    /// buffer.push(1).unwrap();
+    /// assert!(buffer.capacity() >= 1);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Set a new maximum size for the buffer
+    /// # Arguments
+    /// * `max_size`: The new maximum size, or 0 for a buffer without limit
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the new maximum size is set successfully, Err if the new maximum size is less than the current size
+    /// # Example
+    /// ```rust
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let mut buffer = RingBuffer::from(vec![1, 2]);
+    ///
+    /// assert_eq!(buffer.set_max_size(1), Err(DataStructureError::CapacityBelowLen { len: 2, requested: 1 }));
+    /// assert_eq!(buffer.set_max_size(2), Ok(()));
+    /// assert_eq!(buffer.push(3), Err(DataStructureError::Full));
+    /// ```
+    pub fn set_max_size(&mut self, max_size: usize) -> Result<(), DataStructureError> {
+        if max_size != 0 && max_size < self.len {
+            return Err(DataStructureError::CapacityBelowLen {
+                len: self.len,
+                requested: max_size,
+            });
+        }
+
+        self.max_size = max_size;
+        if max_size != 0 && self.capacity() > max_size {
+            self.relayout(max_size);
+        }
+        Ok(())
+    }
+
+    /// Push an element at the end of the buffer
+    /// # Arguments
+    /// * `value` - The value to push
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok(()) if the push was successful, Err(DataStructureError::Full) if the buffer is full
+    /// # Example
+    /// ```rust
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let mut buffer = RingBuffer::new(2);
+    ///
+    /// assert_eq!(buffer.push(1), Ok(()));
+    /// assert_eq!(buffer.push(2), Ok(()));
+    /// assert_eq!(buffer.push(3), Err(DataStructureError::Full));
+    /// ```
+    pub fn push(&mut self, value: T) -> Result<(), DataStructureError> {
+        if self.is_full() {
+            return Err(DataStructureError::Full);
+        }
+        if self.len == self.capacity() {
+            self.grow();
+        }
+
+        let tail = self.slot(self.len);
+        self.slots[tail] = Some(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Push an element at the end of the buffer, dropping the oldest one if the buffer is full
+    /// # Arguments
+    /// * `value` - The value to push
+    /// # Returns
+    /// The dropped element, or None if the buffer wasn't full
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let mut recent = RingBuffer::new(2);
+    ///
+    /// assert_eq!(recent.push_overwrite(1), None);
+    /// assert_eq!(recent.push_overwrite(2), None);
+    /// assert_eq!(recent.push_overwrite(3), Some(1));
+    /// assert_eq!(recent.pop(), Some(2));
+    /// ```
+    pub fn push_overwrite(&mut self, value: T) -> Option<T> {
+        let dropped = if self.is_full() { self.pop() } else { None };
+        self.push(value)
+            .expect("A buffer that isn't full accepts a push");
+        dropped
+    }
+
+    /// Pop the oldest element of the buffer
+    /// # Returns
+    /// Option<T>
+    /// Some(T) if the buffer is not empty, None if the buffer is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let mut buffer = RingBuffer::from(vec![1, 2]);
+    ///
+    /// assert_eq!(buffer.pop(), Some(1));
+    /// assert_eq!(buffer.pop(), Some(2));
+    /// assert_eq!(buffer.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let value = self.slots[self.head].take();
+        self.head = self.slot(1);
+        self.len -= 1;
+        value
+    }
+
+    /// Get a clone of the oldest element, the next one `pop` returns
+    /// # Returns
+    /// The oldest element, or None if the buffer is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let buffer = RingBuffer::from(vec!['a', 'b']);
+    /// assert_eq!(buffer.front(), Some('a'));
+    /// ```
+    pub fn front(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.get(0).cloned()
+    }
+
+    /// Get a clone of the newest element, the last one pushed
+    /// # Returns
+    /// The newest element, or None if the buffer is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let buffer = RingBuffer::from(vec!['a', 'b']);
+    /// assert_eq!(buffer.back(), Some('b'));
+    /// ```
+    pub fn back(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.get(self.len.checked_sub(1)?).cloned()
+    }
+
+    /// Get a reference to an element by its position from the oldest one
+    /// # Arguments
+    /// * `index` - The position of the element, 0 being the oldest
+    /// # Returns
+    /// A reference to the element, or None if `index` is not smaller than the length
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let buffer = RingBuffer::from(vec![10, 20, 30]);
+    /// assert_eq!(buffer.get(1), Some(&20));
+    /// assert_eq!(buffer.get(3), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.slots[self.slot(index)].as_ref()
+    }
+
+    /// Iterate over references to the elements without popping them
+    /// # Returns
+    /// An iterator from the oldest to the newest element, `rev` walks from the newest one
+    /// # Example
+    /// ```rust
+    /// use data_structures::ring::ring_buffer::RingBuffer;
+    ///
+    /// let mut buffer = RingBuffer::new(2);
+    /// buffer.push('a').unwrap();
+    /// buffer.push('b').unwrap();
+    /// buffer.push_overwrite('c');
+    ///
+    /// assert_eq!(buffer.iter().collect::<String>(), "bc");
+    /// assert_eq!(buffer.iter().rev().collect::<String>(), "cb");
+    /// ```
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator + '_ {
+        (0..self.len).map(move |index| self.slots[self.slot(index)].as_ref().unwrap())
+    }
+
+    // Get the slot of the element at a position from the head
+    fn slot(&self, index: usize) -> usize {
+        let slot = self.head + index;
+        if slot >= self.slots.len() {
+            slot - self.slots.len()
+        } else {
+            slot
+        }
+    }
+
+    // Double the capacity, without going past the maximum size
+    fn grow(&mut self) {
+        let mut capacity = (self.capacity() * 2).max(MIN_CAPACITY);
+        if self.max_size != 0 {
+            capacity = capacity.min(self.max_size);
+        }
+        self.relayout(capacity);
+    }
+
+    // Move the elements to the start of a new buffer of the given capacity, not smaller than the length
+    fn relayout(&mut self, capacity: usize) {
+        let mut slots: Vec<Option<T>> = Vec::with_capacity(capacity);
+        for index in 0..self.len {
+            let slot = self.slot(index);
+            slots.push(self.slots[slot].take());
+        }
+        slots.resize_with(capacity, || None);
+
+        self.slots = slots;
+        self.head = 0;
+    }
+}
+
+impl<T> Default for RingBuffer<T> {
+    /// A buffer without limit, like `RingBuffer::new(0)`
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T> From<Vec<T>> for RingBuffer<T> {
+    /// Build a buffer without limit that pops the elements in the order they have in the vector, without copying them
+    fn from(values: Vec<T>) -> Self {
+        RingBuffer {
+            len: values.len(),
+            slots: values.into_iter().map(Some).collect(),
+            head: 0,
+            max_size: 0,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for RingBuffer<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T> Extend<T> for RingBuffer<T> {
+    /// Push the elements in order
+    ///
+    /// # Panics
+    /// If the buffer becomes full. Use `push` to handle a full buffer.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            if self.push(value).is_err() {
+                panic!("Can't extend a full buffer");
+            }
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for RingBuffer<T> {
+    /// Two buffers are equal if they hold equal elements in the same order, whatever their maximum sizes
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for RingBuffer<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for RingBuffer<T> {
+    /// Prints the elements from the oldest to the newest one
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RingBuffer")
+            .field("len", &self.len)
+            .field("max_size", &self.max_size)
+            .field("elements", &self.iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_ring_buffer_against_vec_deque() {
+        let mut buffer = RingBuffer::new(0);
+        let mut model = VecDeque::new();
+        let mut rng = Lcg::new(41);
+
+        for i in 0..5_000u32 {
+            let seed = rng.next_u64();
+            match (seed >> 33) % 8 {
+                0..=2 => assert_eq!(buffer.pop(), model.pop_front()),
+                3 if i % 97 == 0 => {
+                    // Bound the buffer to its length, then lift the bound
+                    buffer.set_max_size(model.len().max(1)).unwrap();
+                    if buffer.is_full() {
+                        let dropped = buffer.push_overwrite(i);
+                        model.push_back(i);
+                        assert_eq!(dropped, model.pop_front());
+                    }
+                    buffer.set_max_size(0).unwrap();
+                }
+                _ => {
+                    buffer.push(i).unwrap();
+                    model.push_back(i);
+                }
+            }
+
+            assert_eq!(buffer.len(), model.len());
+            assert_eq!(buffer.front(), model.front().copied());
+            assert_eq!(buffer.back(), model.back().copied());
+            assert!(buffer.capacity() >= buffer.len());
+        }
+
+        assert!(buffer.iter().eq(model.iter()));
+        assert!(buffer.iter().rev().eq(model.iter().rev()));
+        assert_eq!(buffer, model.iter().copied().collect());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ring_buffer_vs_fifo_stress() {
+        use crate::linked_list::fifo::FIFO;
+        use std::time::Instant;
+
+        let max_size = 10_000;
+        let iterations = 1_000_000u32;
+
+        // The same workload on both backends: fill up, then pop one element for each push
+        let start_time = Instant::now();
+        let mut buffer = RingBuffer::new(max_size);
+        let mut sum = 0u64;
+        for i in 0..iterations {
+            if buffer.is_full() {
+                sum += buffer.pop().unwrap() as u64;
+            }
+            buffer.push(i).unwrap();
+        }
+        println!(
+            "RingBuffer stress test completed in {:?}",
+            start_time.elapsed()
+        );
+
+        let start_time = Instant::now();
+        let mut fifo = FIFO::new(max_size);
+        for i in 0..iterations {
+            if fifo.is_full() {
+                sum -= fifo.pop().unwrap() as u64;
+            }
+            fifo.push(i).unwrap();
+        }
+        println!("FIFO stress test completed in {:?}", start_time.elapsed());
+
+        let start_time = Instant::now();
+        let mut deque = VecDeque::with_capacity(max_size);
+        for i in 0..iterations {
+            if deque.len() == max_size {
+                sum += deque.pop_front().unwrap() as u64;
+            }
+            deque.push_back(i);
+        }
+        println!(
+            "VecDeque stress test completed in {:?}",
+            start_time.elapsed()
+        );
+
+        assert_eq!(sum, (0..iterations as u64 - max_size as u64).sum());
+    }
+}
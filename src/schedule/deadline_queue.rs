@@ -0,0 +1,376 @@
+//! This module implements a deadline queue, a work queue ordered by the absolute deadline of each item.
+//! Items are dispatched earliest deadline first: `pop_earliest` takes the most urgent item whatever its deadline, and
+//! `pop_due` only the items whose deadline has been reached. Times are plain `u64` ticks, as in the `time` module, and
+//! the queue records how late the items were when they were dispatched, for soft real-time monitoring.
+//!
+//! # Performance
+//! - O(log n) amortized for `push`, `pop_earliest` and `pop_due`
+//! - O(1) for `peek_deadline`, `len` and `stats`
+//!
+//! # Implementation Details
+//! - The items are kept in a `SkewHeap` ordered by deadline, then by insertion, so items with the same deadline are
+//!   dispatched in the order they were pushed.
+//! - The lateness of an item is the time between its deadline and its dispatch, 0 if it was dispatched in time. The
+//!   statistics count the dispatched and late items and sum the lateness until they are reset.
+//!
+//! # Usage
+//! ```
+//! use data_structures::schedule::deadline_queue::DeadlineQueue;
+//!
+//! let mut tasks = DeadlineQueue::new();
+//! tasks.push(50, "flush logs");
+//! tasks.push(20, "send heartbeat");
+//! tasks.push(30, "rotate keys");
+//!
+//! // At tick 25 only the heartbeat is due
+//! assert_eq!(tasks.pop_due(25), Some((20, "send heartbeat")));
+//! assert_eq!(tasks.pop_due(25), None);
+//!
+//! // The worker is idle, so it runs the most urgent task ahead of time
+//! assert_eq!(tasks.pop_earliest(26), Some((30, "rotate keys")));
+//! assert_eq!(tasks.pop_earliest(60), Some((50, "flush logs")));
+//!
+//! let stats = tasks.stats();
+//! assert_eq!((stats.dispatched, stats.late), (3, 2));
+//! assert_eq!(stats.max_lateness, 10);
+//! ```
+//!
+use std::{cmp::Ordering, fmt};
+
+use crate::heap::skew_heap::SkewHeap;
+
+/// Item of the queue with its deadline
+struct Entry<T> {
+    deadline: u64,
+    order: u64, // insertion number, to dispatch equal deadlines in FIFO order
+    item: T,
+}
+
+// The heap is a max-heap, so the earliest entry compares as the greatest
+impl<T> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.deadline, other.order).cmp(&(self.deadline, self.order))
+    }
+}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+/// Lateness counters of the items dispatched by a deadline queue
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LatenessStats {
+    pub dispatched: u64,
+    pub late: u64,
+    pub total_lateness: u64,
+    pub max_lateness: u64,
+}
+
+impl LatenessStats {
+    /// Get the average lateness of the dispatched items, counting the items dispatched in time as 0
+    /// # Returns
+    /// The mean lateness, or 0.0 if no item was dispatched
+    /// # Example
+    /// ```
+    /// use data_structures::schedule::deadline_queue::LatenessStats;
+    ///
+    /// let stats = LatenessStats { dispatched: 4, late: 1, total_lateness: 6, max_lateness: 6 };
+    /// assert_eq!(stats.mean_lateness(), 1.5);
+    /// ```
+    pub fn mean_lateness(&self) -> f64 {
+        if self.dispatched == 0 {
+            0.0
+        } else {
+            self.total_lateness as f64 / self.dispatched as f64
+        }
+    }
+}
+
+/// Struct representing a queue of items ordered by deadline
+///
+pub struct DeadlineQueue<T> {
+    heap: SkewHeap<Entry<T>>,
+    next_order: u64,
+    stats: LatenessStats,
+}
+
+impl<T> DeadlineQueue<T> {
+    /// Create a new empty deadline queue
+    ///
+    /// # Returns
+    /// A new DeadlineQueue instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::schedule::deadline_queue::DeadlineQueue;
+    ///
+    /// let queue: DeadlineQueue<&str> = DeadlineQueue::new();
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        DeadlineQueue {
+            heap: SkewHeap::new(),
+            next_order: 0,
+            stats: LatenessStats::default(),
+        }
+    }
+
+    /// Get the number of items waiting
+    /// # Returns
+    /// The number of items in the queue
+    /// # Example
+    /// ```
+    /// use data_structures::schedule::deadline_queue::DeadlineQueue;
+    ///
+    /// let mut queue = DeadlineQueue::new();
+    /// queue.push(10, 'a');
+    /// queue.push(10, 'b');
+    /// assert_eq!(queue.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Check if the queue is empty
+    /// # Returns
+    /// True if no item is waiting
+    /// # Example
+    /// ```
+    /// use data_structures::schedule::deadline_queue::DeadlineQueue;
+    ///
+    /// let mut queue = DeadlineQueue::new();
+    /// queue.push(10, 'a');
+    /// assert!(!queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Add an item with its deadline
+    /// The deadline can already be past, the item is then late as soon as it is pushed.
+    /// # Arguments
+    /// * `deadline`: The time by which the item should be dispatched
+    /// * `item`: The item
+    /// # Example
+    /// ```
+    /// use data_structures::schedule::deadline_queue::DeadlineQueue;
+    ///
+    /// let mut queue = DeadlineQueue::new();
+    /// queue.push(30, "later");
+    /// queue.push(10, "sooner");
+    /// assert_eq!(queue.peek_deadline(), Some(10));
+    /// ```
+    pub fn push(&mut self, deadline: u64, item: T) {
+        let order = self.next_order;
+        self.next_order += 1;
+        self.heap.push(Entry {
+            deadline,
+            order,
+            item,
+        });
+    }
+
+    /// Get the earliest deadline in the queue
+    /// # Returns
+    /// The deadline of the next item `pop_earliest` returns, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::schedule::deadline_queue::DeadlineQueue;
+    ///
+    /// let mut queue = DeadlineQueue::new();
+    /// assert_eq!(queue.peek_deadline(), None);
+    ///
+    /// queue.push(42, ());
+    /// assert_eq!(queue.peek_deadline(), Some(42));
+    /// ```
+    pub fn peek_deadline(&self) -> Option<u64> {
+        self.heap.peek_with(|entry| entry.deadline)
+    }
+
+    /// Remove the item with the earliest deadline, whether it is due or not
+    /// # Arguments
+    /// * `now`: The dispatch time, used for the lateness statistics
+    /// # Returns
+    /// The deadline and the item, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::schedule::deadline_queue::DeadlineQueue;
+    ///
+    /// let mut queue = DeadlineQueue::new();
+    /// queue.push(20, "b");
+    /// queue.push(10, "a");
+    ///
+    /// assert_eq!(queue.pop_earliest(0), Some((10, "a")));
+    /// assert_eq!(queue.pop_earliest(25), Some((20, "b")));
+    /// assert_eq!(queue.stats().late, 1);
+    /// ```
+    pub fn pop_earliest(&mut self, now: u64) -> Option<(u64, T)> {
+        let entry = self.heap.pop()?;
+        self.record(entry.deadline, now);
+        Some((entry.deadline, entry.item))
+    }
+
+    /// Remove the item with the earliest deadline if that deadline has been reached
+    /// # Arguments
+    /// * `now`: The current time
+    /// # Returns
+    /// The deadline and the item, or None if no deadline is at or before `now`
+    /// # Example
+    /// ```
+    /// use data_structures::schedule::deadline_queue::DeadlineQueue;
+    ///
+    /// let mut queue = DeadlineQueue::new();
+    /// for (deadline, task) in [(5, 'a'), (15, 'b'), (10, 'c')] {
+    ///     queue.push(deadline, task);
+    /// }
+    ///
+    /// let mut due = Vec::new();
+    /// while let Some((_, task)) = queue.pop_due(12) {
+    ///     due.push(task);
+    /// }
+    /// assert_eq!(due, vec!['a', 'c']);
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn pop_due(&mut self, now: u64) -> Option<(u64, T)> {
+        if self.peek_deadline()? > now {
+            return None;
+        }
+        self.pop_earliest(now)
+    }
+
+    /// Get the lateness counters of the items dispatched since the creation of the queue or the last reset
+    /// # Returns
+    /// A copy of the statistics of the queue
+    /// # Example
+    /// ```
+    /// use data_structures::schedule::deadline_queue::DeadlineQueue;
+    ///
+    /// let mut queue = DeadlineQueue::new();
+    /// queue.push(10, ());
+    /// queue.push(10, ());
+    /// queue.pop_due(10);
+    /// queue.pop_due(13);
+    ///
+    /// let stats = queue.stats();
+    /// assert_eq!((stats.dispatched, stats.late, stats.total_lateness), (2, 1, 3));
+    /// ```
+    pub fn stats(&self) -> LatenessStats {
+        self.stats
+    }
+
+    /// Reset the lateness counters
+    /// # Example
+    /// ```
+    /// use data_structures::schedule::deadline_queue::DeadlineQueue;
+    ///
+    /// let mut queue = DeadlineQueue::new();
+    /// queue.push(0, ());
+    /// queue.pop_due(5);
+    ///
+    /// queue.reset_stats();
+    /// assert_eq!(queue.stats().dispatched, 0);
+    /// ```
+    pub fn reset_stats(&mut self) {
+        self.stats = LatenessStats::default();
+    }
+
+    // Count a dispatch in the statistics
+    fn record(&mut self, deadline: u64, now: u64) {
+        let lateness = now.saturating_sub(deadline);
+        self.stats.dispatched += 1;
+        self.stats.total_lateness += lateness;
+        self.stats.max_lateness = self.stats.max_lateness.max(lateness);
+        if lateness > 0 {
+            self.stats.late += 1;
+        }
+    }
+}
+
+impl<T> Default for DeadlineQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Debug prints the number of items, the earliest deadline and the statistics, not the items
+///
+impl<T> fmt::Debug for DeadlineQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeadlineQueue")
+            .field("len", &self.len())
+            .field("earliest", &self.peek_deadline())
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_dispatch_against_sorted_model() {
+        let mut queue = DeadlineQueue::new();
+        let mut model: Vec<(u64, u64)> = Vec::new(); // (deadline, id), kept sorted
+        let mut expected = LatenessStats::default();
+        let mut now = 0;
+        let mut rng = Lcg::new(17);
+
+        for id in 0..2000 {
+            let seed = rng.next_u64();
+            now += (seed >> 60) % 3;
+            match (seed >> 33) % 4 {
+                0 | 1 => {
+                    let deadline = now + (seed >> 40) % 50;
+                    queue.push(deadline, id);
+                    // Equal deadlines go after the ones pushed before
+                    let position = model.partition_point(|&(known, _)| known <= deadline);
+                    model.insert(position, (deadline, id));
+                }
+                2 => {
+                    let expected_item = match model.first() {
+                        Some(&(deadline, _)) if deadline <= now => Some(model.remove(0)),
+                        _ => None,
+                    };
+                    assert_eq!(queue.pop_due(now), expected_item);
+                    if let Some((deadline, _)) = expected_item {
+                        let lateness = now - deadline;
+                        expected.dispatched += 1;
+                        expected.late += u64::from(lateness > 0);
+                        expected.total_lateness += lateness;
+                        expected.max_lateness = expected.max_lateness.max(lateness);
+                    }
+                }
+                _ => {
+                    let expected_item = (!model.is_empty()).then(|| model.remove(0));
+                    assert_eq!(queue.pop_earliest(now), expected_item);
+                    if let Some((deadline, _)) = expected_item {
+                        let lateness = now.saturating_sub(deadline);
+                        expected.dispatched += 1;
+                        expected.late += u64::from(lateness > 0);
+                        expected.total_lateness += lateness;
+                        expected.max_lateness = expected.max_lateness.max(lateness);
+                    }
+                }
+            }
+            assert_eq!(queue.len(), model.len());
+            assert_eq!(
+                queue.peek_deadline(),
+                model.first().map(|&(deadline, _)| deadline)
+            );
+        }
+        assert_eq!(queue.stats(), expected);
+        assert!(expected.late > 0 && expected.late < expected.dispatched);
+    }
+}
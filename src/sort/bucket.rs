@@ -0,0 +1,116 @@
+//! This module implements a stable bucket sort for elements keyed by a small integer.
+//! The sorted elements are emitted into any container implementing `Extend`, so they can go straight into the crate's
+//! `FIFO` or `Deque` to be consumed in order, or into a `Vec`.
+//!
+//! # Performance
+//! - O(n + k) for n elements and k buckets
+//!
+//! # Implementation Details
+//! - Each element is moved into the `Vec` of its bucket, then the buckets are emitted in order, so elements with equal
+//!   keys keep their order.
+//! - Every key is checked before anything is emitted: an out of range key leaves the output untouched.
+//!
+//! # Usage
+//! ```
+//! use data_structures::linked_list::fifo::FIFO;
+//! use data_structures::sort::bucket::bucket_sort_into;
+//!
+//! let jobs = vec![("backup", 2), ("alert", 0), ("report", 1), ("page", 0)];
+//!
+//! let mut queue = FIFO::new(0);
+//! bucket_sort_into(jobs, 3, |&(_, priority)| priority, &mut queue).unwrap();
+//!
+//! assert_eq!(queue.pop(), Some(("alert", 0)));
+//! assert_eq!(queue.pop(), Some(("page", 0)));
+//! assert_eq!(queue.pop(), Some(("report", 1)));
+//! assert_eq!(queue.pop(), Some(("backup", 2)));
+//! ```
+//!
+use crate::error::DataStructureError;
+
+/// Sort elements by a small integer key and append them to a container
+/// The sort is stable: elements with equal keys are emitted in the order they were given.
+/// # Arguments
+/// * `values`: The elements to sort
+/// * `buckets`: The number of buckets k, keys go from 0 to k - 1
+/// * `key`: The function giving the bucket of an element
+/// * `out`: The container receiving the sorted elements, after the ones it already holds
+/// # Returns
+/// Result<(), DataStructureError>
+/// Ok if the elements were emitted, Err(DataStructureError::IndexOutOfBounds) with the first key not smaller than the
+/// number of buckets, in which case nothing is emitted
+/// # Panics
+/// If `out` panics when extended, as a bounded `FIFO` or `Deque` does when it becomes full
+/// # Example
+/// ```
+/// use data_structures::error::DataStructureError;
+/// use data_structures::linked_list::deque::Deque;
+/// use data_structures::sort::bucket::bucket_sort_into;
+///
+/// let mut deque = Deque::new(0);
+/// bucket_sort_into(vec![3u8, 1, 2, 1], 4, |&v| v as usize, &mut deque).unwrap();
+/// assert_eq!(deque.pop_back(), Some(3));
+/// assert_eq!(deque.pop_front(), Some(1));
+///
+/// let mut sorted = Vec::new();
+/// assert_eq!(
+///     bucket_sort_into(vec![1u8, 9], 4, |&v| v as usize, &mut sorted),
+///     Err(DataStructureError::IndexOutOfBounds { index: 9, len: 4 })
+/// );
+/// assert!(sorted.is_empty());
+/// ```
+pub fn bucket_sort_into<T, E: Extend<T>>(
+    values: impl IntoIterator<Item = T>,
+    buckets: usize,
+    key: impl Fn(&T) -> usize,
+    out: &mut E,
+) -> Result<(), DataStructureError> {
+    let mut sorted: Vec<Vec<T>> = (0..buckets).map(|_| Vec::new()).collect();
+    for value in values {
+        let index = key(&value);
+        sorted
+            .get_mut(index)
+            .ok_or(DataStructureError::IndexOutOfBounds {
+                index,
+                len: buckets,
+            })?
+            .push(value);
+    }
+
+    out.extend(sorted.into_iter().flatten());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linked_list::deque::Deque;
+    use crate::linked_list::fifo::FIFO;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_bucket_sort_is_stable_in_every_container() {
+        let mut rng = Lcg::new(5);
+        let values: Vec<(usize, u32)> = (0..2_000)
+            .map(|position| {
+                let seed = rng.next_u64();
+                ((seed >> 33) as usize % 16, position)
+            })
+            .collect();
+        let mut expected = values.clone();
+        expected.sort_by_key(|&(key, _)| key);
+
+        let mut sorted = vec![(99, 99)];
+        bucket_sort_into(values.clone(), 16, |&(key, _)| key, &mut sorted).unwrap();
+        assert_eq!(sorted[0], (99, 99));
+        assert_eq!(sorted[1..], expected[..]);
+
+        let mut fifo = FIFO::new(0);
+        bucket_sort_into(values.clone(), 16, |&(key, _)| key, &mut fifo).unwrap();
+        assert_eq!(fifo, expected.iter().copied().collect());
+
+        let mut deque = Deque::new(0);
+        bucket_sort_into(values, 16, |&(key, _)| key, &mut deque).unwrap();
+        assert_eq!(deque, expected.into_iter().collect());
+    }
+}
@@ -0,0 +1,415 @@
+//! This module implements an external merge sort, for datasets that don't fit in memory.
+//! Elements are pushed into a buffer of a fixed number of elements. Each time it fills up, the buffer is sorted and
+//! spilled to a run file. `finish` then merges the runs, streaming the elements back in order through an iterator, so
+//! only one element per run is held in memory at a time.
+//!
+//! # Performance
+//! - O(n log r) to sort n elements in runs of r elements, plus O(n log k) for the merge of k runs
+//! - Every element is written to disk and read back once, except those of the last run, which stays in memory
+//!
+//! # Implementation Details
+//! - Elements are encoded with the `Persist` trait of the `persist::format` module. A record in a run file is the
+//!   length of the encoded element, as 8 little endian bytes, followed by the element.
+//! - The merge keeps the next element of every run in a `SkewHeap`, keyed by the element and the index of its run.
+//! - The runs are sorted with the stable `sort`, and ties between runs go to the earlier one, so the whole sort is
+//!   stable: equal elements come out in the order they were pushed.
+//! - Run files are created in the given directory, the system temporary directory by default, and removed as soon as
+//!   the sorter or the iterator reading them is dropped.
+//!
+//! # Usage
+//! ```
+//! use data_structures::sort::external::ExternalSorter;
+//!
+//! // Hold at most 2 elements in memory before spilling a run
+//! let mut sorter = ExternalSorter::new(2);
+//! for value in [5u32, 3, 9, 1, 4] {
+//!     sorter.push(value).unwrap();
+//! }
+//! assert_eq!(sorter.runs(), 2);
+//!
+//! let sorted: Vec<u32> = sorter.finish().unwrap().collect::<Result<_, _>>().unwrap();
+//! assert_eq!(sorted, [1, 3, 4, 5, 9]);
+//! ```
+//!
+use std::cmp::Reverse;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::heap::skew_heap::SkewHeap;
+use crate::persist::format::Persist;
+
+// Distinguishes the run files of the sorters of a process
+static NEXT_SORTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Struct representing an external merge sort in progress
+///
+pub struct ExternalSorter<T> {
+    dir: PathBuf,
+    id: usize,
+    run_len: usize,
+
+    buffer: Vec<T>,
+    runs: Vec<PathBuf>,
+    len: usize,
+}
+
+/// A sorted run being merged
+enum Run<T> {
+    File {
+        reader: BufReader<File>,
+        path: PathBuf,
+    },
+    Memory(std::vec::IntoIter<T>),
+}
+
+/// Iterator over the sorted elements, created by `ExternalSorter::finish`
+///
+/// It yields `io::Result`s: a run file that can't be read or decoded yields its error, and the iteration ends.
+pub struct SortedIter<T: Ord> {
+    runs: Vec<Run<T>>,
+    heads: SkewHeap<Reverse<(T, usize)>>, // next element of each run, with the index of the run
+    failed: bool,
+}
+
+impl<T: Persist + Ord> ExternalSorter<T> {
+    /// Create a new sorter spilling its runs to the system temporary directory
+    /// # Arguments
+    /// * `run_len`: The number of elements held in memory before a run is spilled, at least 1
+    /// # Returns
+    /// A new ExternalSorter instance
+    /// # Example
+    /// ```
+    /// use data_structures::sort::external::ExternalSorter;
+    ///
+    /// let sorter: ExternalSorter<u64> = ExternalSorter::new(1_000_000);
+    /// assert_eq!(sorter.run_len(), 1_000_000);
+    /// ```
+    pub fn new(run_len: usize) -> Self {
+        Self::with_dir(std::env::temp_dir(), run_len)
+    }
+
+    /// Create a new sorter spilling its runs to a directory
+    /// # Arguments
+    /// * `dir`: The existing directory receiving the run files
+    /// * `run_len`: The number of elements held in memory before a run is spilled, at least 1
+    /// # Returns
+    /// A new ExternalSorter instance
+    /// # Example
+    /// ```
+    /// use data_structures::sort::external::ExternalSorter;
+    ///
+    /// let sorter: ExternalSorter<String> = ExternalSorter::with_dir(std::env::temp_dir(), 4096);
+    /// assert!(sorter.is_empty());
+    /// ```
+    pub fn with_dir(dir: impl AsRef<Path>, run_len: usize) -> Self {
+        ExternalSorter {
+            dir: dir.as_ref().to_path_buf(),
+            id: NEXT_SORTER.fetch_add(1, Ordering::Relaxed),
+            run_len: run_len.max(1),
+            buffer: Vec::new(),
+            runs: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Get the number of elements held in memory before a run is spilled
+    /// # Returns
+    /// The length of a run
+    /// # Example
+    /// ```
+    /// use data_structures::sort::external::ExternalSorter;
+    ///
+    /// let sorter: ExternalSorter<u8> = ExternalSorter::new(0);
+    /// assert_eq!(sorter.run_len(), 1);
+    /// ```
+    pub fn run_len(&self) -> usize {
+        self.run_len
+    }
+
+    /// Get the number of elements pushed
+    /// # Returns
+    /// The number of elements, spilled or in memory
+    /// # Example
+    /// ```
+    /// use data_structures::sort::external::ExternalSorter;
+    ///
+    /// let mut sorter = ExternalSorter::new(1);
+    /// sorter.push(1u32).unwrap();
+    /// sorter.push(2u32).unwrap();
+    /// assert_eq!(sorter.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if no element was pushed
+    /// # Returns
+    /// True if the sorter is empty
+    /// # Example
+    /// ```
+    /// use data_structures::sort::external::ExternalSorter;
+    ///
+    /// let sorter: ExternalSorter<u32> = ExternalSorter::new(8);
+    /// assert!(sorter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of runs spilled to disk
+    /// # Returns
+    /// The number of run files
+    /// # Example
+    /// ```
+    /// use data_structures::sort::external::ExternalSorter;
+    ///
+    /// let mut sorter = ExternalSorter::new(2);
+    /// for value in 0..5u32 {
+    ///     sorter.push(value).unwrap();
+    /// }
+    /// assert_eq!(sorter.runs(), 2);
+    /// ```
+    pub fn runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Add an element, spilling a sorted run if the buffer is full
+    /// # Arguments
+    /// * `value`: The element
+    /// # Returns
+    /// io::Result<()>
+    /// Err if the run file couldn't be written, in which case the buffered elements are lost
+    /// # Example
+    /// ```
+    /// use data_structures::sort::external::ExternalSorter;
+    ///
+    /// let mut sorter = ExternalSorter::new(16);
+    /// sorter.push("b".to_string()).unwrap();
+    /// sorter.push("a".to_string()).unwrap();
+    ///
+    /// let sorted: Vec<String> = sorter.finish().unwrap().map(Result::unwrap).collect();
+    /// assert_eq!(sorted, ["a", "b"]);
+    /// ```
+    pub fn push(&mut self, value: T) -> io::Result<()> {
+        self.buffer.push(value);
+        self.len += 1;
+        if self.buffer.len() == self.run_len {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Stop pushing and merge the runs
+    /// # Returns
+    /// io::Result<SortedIter<T>>
+    /// An iterator over the elements in ascending order, or Err if a run file couldn't be opened or its first element
+    /// couldn't be read
+    /// # Example
+    /// ```
+    /// use data_structures::sort::external::ExternalSorter;
+    ///
+    /// let mut sorter = ExternalSorter::new(3);
+    /// for value in (0..10u32).rev() {
+    ///     sorter.push(value).unwrap();
+    /// }
+    ///
+    /// let mut sorted = sorter.finish().unwrap();
+    /// assert_eq!(sorted.next().unwrap().unwrap(), 0);
+    /// assert_eq!(sorted.count(), 9);
+    /// ```
+    pub fn finish(mut self) -> io::Result<SortedIter<T>> {
+        // The last run is merged from memory without being spilled
+        self.buffer.sort();
+        let mut runs = Vec::with_capacity(self.runs.len() + 1);
+        for path in &self.runs {
+            runs.push(Run::File {
+                reader: BufReader::new(File::open(path)?),
+                path: path.clone(),
+            });
+        }
+        // The runs remove their files from now on, the sorter removes them all if an open failed
+        self.runs.clear();
+        runs.push(Run::Memory(mem::take(&mut self.buffer).into_iter()));
+
+        let mut sorted = SortedIter {
+            runs,
+            heads: SkewHeap::new(),
+            failed: false,
+        };
+        for index in 0..sorted.runs.len() {
+            sorted.advance(index)?;
+        }
+        Ok(sorted)
+    }
+
+    // Sort the buffer and write it to a new run file
+    fn spill(&mut self) -> io::Result<()> {
+        self.buffer.sort();
+        let path = self.dir.join(format!(
+            "external_sort_{}_{}_{}.run",
+            std::process::id(),
+            self.id,
+            self.runs.len()
+        ));
+        self.runs.push(path.clone());
+
+        let mut writer = BufWriter::new(File::create(&path)?);
+        let mut record = Vec::new();
+        for value in self.buffer.drain(..) {
+            record.clear();
+            value.write_to(&mut record);
+            writer.write_all(&(record.len() as u64).to_le_bytes())?;
+            writer.write_all(&record)?;
+        }
+        writer.flush()
+    }
+}
+
+impl<T> Drop for ExternalSorter<T> {
+    fn drop(&mut self) {
+        for path in &self.runs {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl<T: Persist> Run<T> {
+    // Read the next element of the run
+    fn next(&mut self) -> io::Result<Option<T>> {
+        let reader = match self {
+            Run::Memory(elements) => return Ok(elements.next()),
+            Run::File { reader, .. } => reader,
+        };
+        if reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+
+        let mut length = [0; 8];
+        reader.read_exact(&mut length)?;
+        let length = usize::try_from(u64::from_le_bytes(length)).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "Record length out of range")
+        })?;
+        let mut record = vec![0; length];
+        reader.read_exact(&mut record)?;
+
+        T::read_from(&mut record.as_slice())
+            .map(Some)
+            .map_err(|reason| io::Error::new(io::ErrorKind::InvalidData, reason))
+    }
+}
+
+impl<T> Drop for Run<T> {
+    fn drop(&mut self) {
+        if let Run::File { path, .. } = self {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl<T: Persist + Ord> SortedIter<T> {
+    // Queue the next element of a run, if it has one
+    fn advance(&mut self, index: usize) -> io::Result<()> {
+        if let Some(value) = self.runs[index].next()? {
+            self.heads.push(Reverse((value, index)));
+        }
+        Ok(())
+    }
+}
+
+impl<T: Persist + Ord> Iterator for SortedIter<T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<io::Result<T>> {
+        if self.failed {
+            return None;
+        }
+
+        let Reverse((value, index)) = self.heads.pop()?;
+        if let Err(error) = self.advance(index) {
+            self.failed = true;
+            return Some(Err(error));
+        }
+        Some(Ok(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DataStructureError;
+    use crate::test_util::Lcg;
+    use std::cmp::Ordering as CmpOrdering;
+
+    // Ordered by key only, so equal elements can be told apart by their tag
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Keyed {
+        key: u8,
+        tag: u32,
+    }
+
+    impl Ord for Keyed {
+        fn cmp(&self, other: &Self) -> CmpOrdering {
+            self.key.cmp(&other.key)
+        }
+    }
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Persist for Keyed {
+        fn write_to(&self, out: &mut Vec<u8>) {
+            (self.key, self.tag).write_to(out);
+        }
+
+        fn read_from(input: &mut &[u8]) -> Result<Self, DataStructureError> {
+            let (key, tag) = <(u8, u32)>::read_from(input)?;
+            Ok(Keyed { key, tag })
+        }
+    }
+
+    #[test]
+    fn test_external_sort_is_stable_and_cleans_up() {
+        let dir = std::env::temp_dir().join(format!("external_sort_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut rng = Lcg::new(19);
+        let values: Vec<Keyed> = (0..5_000)
+            .map(|tag| {
+                let seed = rng.next_u64();
+                Keyed {
+                    key: (seed >> 56) as u8,
+                    tag,
+                }
+            })
+            .collect();
+
+        let mut sorter = ExternalSorter::with_dir(&dir, 300);
+        for value in values.iter().cloned() {
+            sorter.push(value).unwrap();
+        }
+        assert_eq!(sorter.runs(), 16);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 16);
+
+        let mut expected = values.clone();
+        expected.sort();
+        let sorted: Vec<Keyed> = sorter.finish().unwrap().map(Result::unwrap).collect();
+        assert_eq!(sorted, expected);
+
+        // The runs are removed once read, and when a sorter is dropped without finishing
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        let mut abandoned = ExternalSorter::with_dir(&dir, 10);
+        for value in values.into_iter().take(50) {
+            abandoned.push(value).unwrap();
+        }
+        drop(abandoned);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        fs::remove_dir(&dir).unwrap();
+    }
+}
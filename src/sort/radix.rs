@@ -0,0 +1,309 @@
+//! This module implements radix sorts, which sort by looking at the bytes of the keys instead of comparing them.
+//! `lsd_radix_sort` sorts fixed width integer keys one byte at a time from the least significant one, and is stable.
+//! `msd_radix_sort` sorts byte strings from their first byte, in lexicographic order. The work grows with the width of
+//! the keys instead of with log n comparisons, which pays off for large inputs of narrow keys or slow comparisons.
+//! Whether it beats `sort_unstable` on a machine can be checked with `test_lsd_radix_sort_vs_sort_unstable_stress`.
+//!
+//! # Performance
+//! - O(w * n) for `lsd_radix_sort` with w the width of the key in bytes, plus a buffer of n elements
+//! - O(L * n) for `msd_radix_sort` with L the length of the common prefixes, in place
+//!
+//! # Implementation Details
+//! - Signed integers are sorted by flipping their sign bit, which orders negative numbers before positive ones.
+//! - The counts of every byte are taken in one read before the passes. A pass where every key has the same byte is
+//!   skipped, so small values in wide types cost fewer passes.
+//! - `msd_radix_sort` is an American flag sort: it counts the keys of each bucket, permutes them in place, and recurses
+//!   into each bucket on the next byte. Keys that end come first, and small buckets fall back to an insertion sort. It
+//!   is not stable, and recurses once per byte of the longest common prefix.
+//!
+//! # Usage
+//! ```
+//! use data_structures::sort::radix::{lsd_radix_sort, msd_radix_sort};
+//!
+//! let mut ids = vec![907u32, 3, 70_000, 42, 3];
+//! lsd_radix_sort(&mut ids);
+//! assert_eq!(ids, [3, 3, 42, 907, 70_000]);
+//!
+//! let mut words = vec!["pear", "apple", "app", "banana"];
+//! msd_radix_sort(&mut words);
+//! assert_eq!(words, ["app", "apple", "banana", "pear"]);
+//! ```
+//!
+
+/// Buckets smaller than this are sorted by insertion by `msd_radix_sort`
+const INSERTION_THRESHOLD: usize = 32;
+
+/// Trait for fixed width keys that can be radix sorted
+///
+/// The order of the keys must be the order of their bytes compared from the most significant one.
+pub trait RadixKey: Copy {
+    /// The number of bytes of the key
+    const BYTES: usize;
+
+    /// Get a byte of the key
+    /// # Arguments
+    /// * `index`: The index of the byte, 0 being the least significant one
+    /// # Returns
+    /// The byte
+    fn byte(self, index: usize) -> u8;
+}
+
+macro_rules! impl_radix_key_unsigned {
+    ($($type:ty),*) => {
+        $(
+            impl RadixKey for $type {
+                const BYTES: usize = std::mem::size_of::<$type>();
+
+                fn byte(self, index: usize) -> u8 {
+                    (self >> (8 * index)) as u8
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_radix_key_signed {
+    ($($type:ty => $unsigned:ty),*) => {
+        $(
+            impl RadixKey for $type {
+                const BYTES: usize = std::mem::size_of::<$type>();
+
+                fn byte(self, index: usize) -> u8 {
+                    // Flipping the sign bit puts the negative numbers first
+                    ((self as $unsigned ^ (1 << (<$unsigned>::BITS - 1))) >> (8 * index)) as u8
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_key_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_radix_key_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+/// Sort keys in ascending order, one byte at a time from the least significant one
+/// # Arguments
+/// * `values`: The keys to sort
+/// # Example
+/// ```
+/// use data_structures::sort::radix::lsd_radix_sort;
+///
+/// let mut values = vec![5i64, -1, 300, -70_000, 0];
+/// lsd_radix_sort(&mut values);
+/// assert_eq!(values, [-70_000, -1, 0, 5, 300]);
+/// ```
+pub fn lsd_radix_sort<K: RadixKey>(values: &mut [K]) {
+    lsd_radix_sort_by_key(values, |&value| value);
+}
+
+/// Sort elements by a key in ascending order, one byte at a time from the least significant one
+/// The sort is stable: elements with equal keys keep their order.
+/// # Arguments
+/// * `values`: The elements to sort
+/// * `key`: The function giving the key of an element, called once per element and pass
+/// # Example
+/// ```
+/// use data_structures::sort::radix::lsd_radix_sort_by_key;
+///
+/// let mut users = vec![(30u8, "carol"), (25, "alice"), (30, "bob"), (25, "dave")];
+/// lsd_radix_sort_by_key(&mut users, |&(age, _)| age);
+/// assert_eq!(users, [(25, "alice"), (25, "dave"), (30, "carol"), (30, "bob")]);
+/// ```
+pub fn lsd_radix_sort_by_key<T: Copy, K: RadixKey>(values: &mut [T], key: impl Fn(&T) -> K) {
+    if values.len() < 2 {
+        return;
+    }
+
+    // The counts of every byte are taken in a single read, then each pass scatters the elements to the other side
+    let mut counts = vec![[0usize; 256]; K::BYTES];
+    for value in values.iter() {
+        let value = key(value);
+        for (index, count) in counts.iter_mut().enumerate() {
+            count[value.byte(index) as usize] += 1;
+        }
+    }
+
+    let mut buffer = values.to_vec();
+    let mut in_buffer = false;
+    for (index, count) in counts.iter().enumerate() {
+        // Every key has the same byte, the pass wouldn't move anything
+        if count.contains(&values.len()) {
+            continue;
+        }
+        if in_buffer {
+            scatter(&buffer, values, index, count, &key);
+        } else {
+            scatter(values, &mut buffer, index, count, &key);
+        }
+        in_buffer = !in_buffer;
+    }
+
+    if in_buffer {
+        values.copy_from_slice(&buffer);
+    }
+}
+
+// Move the elements from source to target, stably ordered by one byte of their key
+fn scatter<T: Copy, K: RadixKey>(
+    source: &[T],
+    target: &mut [T],
+    index: usize,
+    count: &[usize; 256],
+    key: &impl Fn(&T) -> K,
+) {
+    let mut offsets = [0usize; 256];
+    let mut offset = 0;
+    for (start, &bucket_len) in offsets.iter_mut().zip(count.iter()) {
+        *start = offset;
+        offset += bucket_len;
+    }
+    for value in source {
+        let byte = key(value).byte(index) as usize;
+        target[offsets[byte]] = *value;
+        offsets[byte] += 1;
+    }
+}
+
+/// Sort byte strings in lexicographic order, from their first byte
+/// A string comes before the strings it is a prefix of, as with the `Ord` of slices. The sort is not stable.
+/// # Arguments
+/// * `values`: The strings to sort, anything that can be viewed as bytes
+/// # Example
+/// ```
+/// use data_structures::sort::radix::msd_radix_sort;
+///
+/// let mut keys: Vec<Vec<u8>> = vec![b"b".to_vec(), b"ab".to_vec(), b"".to_vec(), b"a".to_vec()];
+/// msd_radix_sort(&mut keys);
+/// assert_eq!(keys, [b"".to_vec(), b"a".to_vec(), b"ab".to_vec(), b"b".to_vec()]);
+/// ```
+pub fn msd_radix_sort<B: AsRef<[u8]>>(values: &mut [B]) {
+    msd_sort_from(values, 0);
+}
+
+// Sort strings sharing their first `depth` bytes
+fn msd_sort_from<B: AsRef<[u8]>>(values: &mut [B], depth: usize) {
+    if values.len() < INSERTION_THRESHOLD {
+        for sorted in 1..values.len() {
+            let mut index = sorted;
+            while index > 0 && values[index - 1].as_ref() > values[index].as_ref() {
+                values.swap(index - 1, index);
+                index -= 1;
+            }
+        }
+        return;
+    }
+
+    // Bucket 0 holds the strings that end at this depth, bucket b + 1 the strings with byte b
+    let bucket = |value: &B| {
+        value
+            .as_ref()
+            .get(depth)
+            .map_or(0, |&byte| byte as usize + 1)
+    };
+    let mut ends = [0usize; 257];
+    for value in values.iter() {
+        ends[bucket(value)] += 1;
+    }
+    let mut starts = [0usize; 257];
+    let mut offset = 0;
+    for (start, end) in starts.iter_mut().zip(ends.iter_mut()) {
+        *start = offset;
+        offset += *end;
+        *end = offset;
+    }
+
+    // Swap each element into the next free place of its bucket, until every bucket is filled
+    let mut next = starts;
+    for current in 0..next.len() {
+        while next[current] < ends[current] {
+            let target = bucket(&values[next[current]]);
+            if target != current {
+                values.swap(next[current], next[target]);
+            }
+            next[target] += 1;
+        }
+    }
+
+    for (&start, &end) in starts.iter().zip(ends.iter()).skip(1) {
+        if end - start > 1 {
+            msd_sort_from(&mut values[start..end], depth + 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    fn random_values(count: usize, seed: u64) -> Vec<u64> {
+        let mut rng = Lcg::new(seed);
+        (0..count).map(|_| rng.next_u64()).collect()
+    }
+
+    #[test]
+    fn test_radix_sorts_against_std() {
+        let values = random_values(5_000, 3);
+
+        let mut unsigned = values.clone();
+        lsd_radix_sort(&mut unsigned);
+        let mut expected = values.clone();
+        expected.sort();
+        assert_eq!(unsigned, expected);
+
+        let mut signed: Vec<i32> = values.iter().map(|&v| (v >> 32) as i32 >> 8).collect();
+        let mut expected = signed.clone();
+        lsd_radix_sort(&mut signed);
+        expected.sort();
+        assert_eq!(signed, expected);
+
+        // Few distinct keys, each element tagged with its position to check stability
+        let mut tagged: Vec<(u16, usize)> = values
+            .iter()
+            .enumerate()
+            .map(|(position, &v)| ((v >> 60) as u16, position))
+            .collect();
+        let mut expected = tagged.clone();
+        lsd_radix_sort_by_key(&mut tagged, |&(key, _)| key);
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(tagged, expected);
+
+        // Strings over a small alphabet share long prefixes and include every length from 0
+        let mut strings: Vec<Vec<u8>> = values
+            .iter()
+            .map(|&v| {
+                let len = (v >> 59) as usize % 12;
+                (0..len)
+                    .map(|i| b"ab\0\xff"[(v >> (2 * i)) as usize % 4])
+                    .collect()
+            })
+            .collect();
+        let mut expected = strings.clone();
+        msd_radix_sort(&mut strings);
+        expected.sort();
+        assert_eq!(strings, expected);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_lsd_radix_sort_vs_sort_unstable_stress() {
+        use std::time::Instant;
+
+        // IDs below 2^32 in a 64 bit type, the passes over the high bytes are skipped
+        let values: Vec<u64> = random_values(5_000_000, 7)
+            .into_iter()
+            .map(|v| v >> 32)
+            .collect();
+
+        let mut radix = values.clone();
+        let start_time = Instant::now();
+        lsd_radix_sort(&mut radix);
+        println!("lsd_radix_sort completed in {:?}", start_time.elapsed());
+
+        let mut std = values;
+        let start_time = Instant::now();
+        std.sort_unstable();
+        println!("sort_unstable completed in {:?}", start_time.elapsed());
+
+        assert_eq!(radix, std);
+    }
+}
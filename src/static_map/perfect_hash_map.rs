@@ -0,0 +1,219 @@
+//! This module implements a read-only hash map indexed by a minimal perfect hash function.
+//! The map is built once from distinct keys: an `Mphf` gives each key its own slot in `0..n`, and the entries are
+//! stored in a flat array at those slots. A lookup hashes the key, reads its slot and compares the key stored there,
+//! with no probing and no empty slots, which suits dictionaries shipped with an application and never modified.
+//!
+//! # Performance
+//! - O(n) expected to build the map, O(n log n) with the duplicate check of the `Mphf`
+//! - O(1) for `get`, with a single key comparison
+//! - The entries themselves plus less than 3 bits per key for the `Mphf`
+//!
+//! # Implementation Details
+//! - The entries are stored at the index the `Mphf` gives their key, so iteration follows no particular order.
+//! - The `Mphf` gives an index to keys outside the map too, which is why the stored key is compared before returning.
+//! - The map can't be modified after it is built.
+//!
+//! # Usage
+//! ```
+//! use data_structures::static_map::perfect_hash_map::StaticMap;
+//!
+//! let units = StaticMap::from_entries(vec![("km", 1000.0), ("m", 1.0), ("cm", 0.01), ("mm", 0.001)]).unwrap();
+//!
+//! assert_eq!(units.get(&"cm"), Some(&0.01));
+//! assert_eq!(units.get(&"mi"), None);
+//! assert_eq!(units.len(), 4);
+//! ```
+//!
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::error::DataStructureError;
+use crate::hash::mphf::Mphf;
+
+/// Struct representing a static map indexed by a minimal perfect hash function
+///
+#[derive(Debug, Clone)]
+pub struct StaticMap<K, V> {
+    mphf: Mphf,
+    entries: Vec<(K, V)>, // the entry of a key is at its index in the mphf
+}
+
+impl<K: Hash + Eq, V> StaticMap<K, V> {
+    /// Build the map from its entries
+    ///
+    /// # Arguments
+    /// * `pairs`: The entries of the map, in any order, with distinct keys
+    ///
+    /// # Returns
+    /// Result<StaticMap<K, V>, DataStructureError>
+    /// Ok with the map, Err(DataStructureError::DuplicateKeys) if a key is repeated
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::static_map::perfect_hash_map::StaticMap;
+    ///
+    /// assert!(StaticMap::from_entries(vec![(2, 'b'), (1, 'a')]).is_ok());
+    /// assert_eq!(
+    ///     StaticMap::from_entries(vec![(1, 'a'), (1, 'b')]).unwrap_err(),
+    ///     DataStructureError::DuplicateKeys
+    /// );
+    /// ```
+    pub fn from_entries(pairs: Vec<(K, V)>) -> Result<Self, DataStructureError> {
+        let mphf = Mphf::new(pairs.iter().map(|(key, _)| key))?;
+
+        let mut slots: Vec<Option<(K, V)>> = (0..pairs.len()).map(|_| None).collect();
+        for (key, value) in pairs {
+            let index = mphf.index(&key).unwrap();
+            slots[index] = Some((key, value));
+        }
+
+        let entries = slots.into_iter().map(Option::unwrap).collect();
+        Ok(StaticMap { mphf, entries })
+    }
+
+    /// Get the number of entries of the map
+    /// # Returns
+    /// The number of entries
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::perfect_hash_map::StaticMap;
+    ///
+    /// let map = StaticMap::from_entries(vec![("x", 1), ("y", 2)]).unwrap();
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the map is empty
+    /// # Returns
+    /// True if the map has no entries, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::perfect_hash_map::StaticMap;
+    ///
+    /// let map: StaticMap<u8, u8> = StaticMap::from_entries(vec![]).unwrap();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the value of a key
+    /// # Arguments
+    /// * `key`: The key to be searched
+    /// # Returns
+    /// A reference to the value, or None if the key isn't in the map
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::perfect_hash_map::StaticMap;
+    ///
+    /// let map = StaticMap::from_entries(vec![("a", 1), ("b", 2)]).unwrap();
+    ///
+    /// assert_eq!(map.get(&"b"), Some(&2));
+    /// assert_eq!(map.get(&"c"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let (stored, value) = &self.entries[self.mphf.index(key)?];
+        (stored == key).then_some(value)
+    }
+
+    /// Check if a key is in the map
+    /// # Arguments
+    /// * `key`: The key to be checked
+    /// # Returns
+    /// True if the key is in the map, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::perfect_hash_map::StaticMap;
+    ///
+    /// let map = StaticMap::from_entries(vec![(1, ())]).unwrap();
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Get an iterator over the entries, in no particular order
+    /// # Returns
+    /// An iterator of references to the keys and values
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::perfect_hash_map::StaticMap;
+    ///
+    /// let map = StaticMap::from_entries(vec![(1, 10), (2, 20), (3, 30)]).unwrap();
+    /// assert_eq!(map.iter().map(|(_, v)| *v).sum::<i32>(), 60);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    /// Get an iterator over the keys, in no particular order
+    /// # Returns
+    /// An iterator of references to the keys
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::perfect_hash_map::StaticMap;
+    ///
+    /// let map = StaticMap::from_entries(vec![(5, ()), (1, ())]).unwrap();
+    ///
+    /// let mut keys: Vec<_> = map.keys().collect();
+    /// keys.sort();
+    /// assert_eq!(keys, vec![&1, &5]);
+    /// ```
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+}
+
+impl<K: Hash + Eq, V> Default for StaticMap<K, V> {
+    fn default() -> Self {
+        Self::from_entries(Vec::new()).unwrap()
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for StaticMap<K, V> {
+    /// Build the map from pairs in any order. When a key is repeated, the last value wins.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let pairs: HashMap<K, V> = iter.into_iter().collect();
+        Self::from_entries(pairs.into_iter().collect()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_static_map_against_hash_map() {
+        let mut rng = Lcg::new(11);
+        let mut model = HashMap::new();
+        for _ in 0..5_000 {
+            let seed = rng.next_u64();
+            model.insert(seed >> 40, seed);
+        }
+        let map: StaticMap<u64, u64> = model.iter().map(|(k, v)| (*k, *v)).collect();
+
+        assert_eq!(map.len(), model.len());
+        for key in 0..(1 << 24) / 64 {
+            let key = key * 64 + 17;
+            assert_eq!(map.get(&key), model.get(&key));
+        }
+        for (key, value) in &model {
+            assert_eq!(map.get(key), Some(value));
+        }
+        assert_eq!(map.iter().count(), model.len());
+    }
+
+    #[test]
+    fn test_static_map_from_iter_keeps_last_value() {
+        let map: StaticMap<&str, u32> = vec![("b", 1), ("a", 2), ("b", 3)].into_iter().collect();
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&"b"), Some(&3));
+        assert_eq!(map.get(&"a"), Some(&2));
+    }
+}
@@ -0,0 +1,329 @@
+//! This module implements a read-only sorted map laid out in Eytzinger (BFS) order.
+//! The map is built once from sorted pairs and stored as an implicit binary search tree in a flat array: the root first,
+//! then its two children, then the four grandchildren, and so on. The first levels of the tree share a few cache lines,
+//! so lookups touch far less memory than a binary search over a sorted slice or a walk down a `BTreeMap`.
+//!
+//! # Performance
+//! - O(n) to build the map from sorted pairs
+//! - O(log n) for `get`, with a branchless inner loop
+//! - O(n) to iterate in key order
+//!
+//! # Implementation Details
+//! - Node `k` (1-based) has its children at `2k` and `2k + 1`. Keys and values are kept in two parallel arrays,
+//!   so the search loop only touches keys.
+//! - The search descends without comparing for equality, computing the next node as `2k + (key[k] < x)`. The last node
+//!   where the search went left is recovered from the trailing ones of `k`, and is the lower bound of `x`.
+//! - The map can't be modified after it is built.
+//!
+//! # Usage
+//! ```
+//! use data_structures::static_map::sorted_slice_map::SortedSliceMap;
+//!
+//! let codes = SortedSliceMap::from_sorted(vec![(200, "OK"), (404, "Not Found"), (500, "Internal Server Error")]).unwrap();
+//!
+//! assert_eq!(codes.get(&404), Some(&"Not Found"));
+//! assert_eq!(codes.get(&418), None);
+//! assert_eq!(codes.keys().collect::<Vec<_>>(), vec![&200, &404, &500]);
+//! ```
+//!
+use crate::error::DataStructureError;
+
+/// Struct representing a static map stored in Eytzinger order
+///
+#[derive(Debug, Clone)]
+pub struct SortedSliceMap<K, V> {
+    keys: Vec<K>,   // node k (1-based) is stored at index k - 1
+    values: Vec<V>, // same layout as the keys
+}
+
+impl<K: Ord, V> SortedSliceMap<K, V> {
+    /// Build the map from pairs sorted by key
+    ///
+    /// # Arguments
+    /// * `pairs`: The entries of the map, sorted by strictly increasing keys
+    ///
+    /// # Returns
+    /// Result<SortedSliceMap<K, V>, DataStructureError>
+    /// Ok with the map, Err if the keys aren't strictly increasing
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::static_map::sorted_slice_map::SortedSliceMap;
+    ///
+    /// assert!(SortedSliceMap::from_sorted(vec![(1, 'a'), (2, 'b')]).is_ok());
+    /// assert_eq!(
+    ///     SortedSliceMap::from_sorted(vec![(2, 'b'), (1, 'a')]).unwrap_err(),
+    ///     DataStructureError::UnsortedKeys
+    /// );
+    /// ```
+    pub fn from_sorted(pairs: Vec<(K, V)>) -> Result<Self, DataStructureError> {
+        if pairs.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err(DataStructureError::UnsortedKeys);
+        }
+
+        // Compute, for each sorted position, its node in the Eytzinger layout with an in-order walk of the implicit tree
+        let n = pairs.len();
+        let mut nodes = Vec::with_capacity(n);
+        let mut stack = Vec::new();
+        let mut node = 1;
+        while node <= n || !stack.is_empty() {
+            while node <= n {
+                stack.push(node);
+                node *= 2;
+            }
+            let current = stack.pop().unwrap();
+            nodes.push(current);
+            node = 2 * current + 1;
+        }
+
+        let mut slots: Vec<Option<(K, V)>> = (0..n).map(|_| None).collect();
+        for ((key, value), node) in pairs.into_iter().zip(nodes) {
+            slots[node - 1] = Some((key, value));
+        }
+
+        let (keys, values) = slots.into_iter().map(Option::unwrap).unzip();
+        Ok(SortedSliceMap { keys, values })
+    }
+
+    /// Get the number of entries of the map
+    /// # Returns
+    /// The number of entries
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::sorted_slice_map::SortedSliceMap;
+    ///
+    /// let map = SortedSliceMap::from_sorted(vec![(1, ()), (2, ())]).unwrap();
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Check if the map is empty
+    /// # Returns
+    /// True if the map has no entries, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::sorted_slice_map::SortedSliceMap;
+    ///
+    /// let map: SortedSliceMap<u8, u8> = SortedSliceMap::from_sorted(vec![]).unwrap();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    // Find the node of the smallest key greater than or equal to the given one, 0 if there is none
+    fn lower_bound_node(&self, key: &K) -> usize {
+        let n = self.keys.len();
+        let mut node = 1;
+
+        while node <= n {
+            node = 2 * node + usize::from(self.keys[node - 1] < *key);
+        }
+
+        // Drop the right turns taken after the last left turn, and the left turn itself
+        node >> (node.trailing_ones() + 1)
+    }
+
+    /// Get the value of a key
+    /// # Arguments
+    /// * `key`: The key to be searched
+    /// # Returns
+    /// A reference to the value, or None if the key isn't in the map
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::sorted_slice_map::SortedSliceMap;
+    ///
+    /// let map = SortedSliceMap::from_sorted(vec![("a", 1), ("b", 2)]).unwrap();
+    ///
+    /// assert_eq!(map.get(&"b"), Some(&2));
+    /// assert_eq!(map.get(&"c"), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let node = self.lower_bound_node(key);
+
+        if node != 0 && self.keys[node - 1] == *key {
+            Some(&self.values[node - 1])
+        } else {
+            None
+        }
+    }
+
+    /// Check if a key is in the map
+    /// # Arguments
+    /// * `key`: The key to be checked
+    /// # Returns
+    /// True if the key is in the map, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::sorted_slice_map::SortedSliceMap;
+    ///
+    /// let map = SortedSliceMap::from_sorted(vec![(1, ())]).unwrap();
+    /// assert!(map.contains_key(&1));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Get the entry with the smallest key greater than or equal to the given one
+    /// # Arguments
+    /// * `key`: The searched key
+    /// # Returns
+    /// References to the key and value of the entry, or None if every key is smaller
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::sorted_slice_map::SortedSliceMap;
+    ///
+    /// let map = SortedSliceMap::from_sorted(vec![(10, 'a'), (20, 'b')]).unwrap();
+    ///
+    /// assert_eq!(map.lower_bound(&15), Some((&20, &'b')));
+    /// assert_eq!(map.lower_bound(&10), Some((&10, &'a')));
+    /// assert_eq!(map.lower_bound(&21), None);
+    /// ```
+    pub fn lower_bound(&self, key: &K) -> Option<(&K, &V)> {
+        match self.lower_bound_node(key) {
+            0 => None,
+            node => Some((&self.keys[node - 1], &self.values[node - 1])),
+        }
+    }
+
+    /// Get an iterator over the entries in key order
+    /// # Returns
+    /// An iterator of references to the keys and values
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::sorted_slice_map::SortedSliceMap;
+    ///
+    /// let map = SortedSliceMap::from_sorted(vec![(1, 'a'), (2, 'b'), (3, 'c')]).unwrap();
+    /// assert_eq!(map.iter().map(|(_, v)| *v).collect::<String>(), "abc");
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        let n = self.keys.len();
+        let mut stack = Vec::new();
+        let mut node = 1;
+
+        // In-order walk of the implicit tree
+        std::iter::from_fn(move || {
+            while node <= n {
+                stack.push(node);
+                node *= 2;
+            }
+            let current = stack.pop()?;
+            node = 2 * current + 1;
+
+            Some((&self.keys[current - 1], &self.values[current - 1]))
+        })
+    }
+
+    /// Get an iterator over the keys in order
+    /// # Returns
+    /// An iterator of references to the keys
+    /// # Example
+    /// ```
+    /// use data_structures::static_map::sorted_slice_map::SortedSliceMap;
+    ///
+    /// let map = SortedSliceMap::from_sorted(vec![(1, ()), (5, ())]).unwrap();
+    /// assert_eq!(map.keys().collect::<Vec<_>>(), vec![&1, &5]);
+    /// ```
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+}
+
+impl<K: Ord, V> Default for SortedSliceMap<K, V> {
+    fn default() -> Self {
+        SortedSliceMap {
+            keys: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SortedSliceMap<K, V> {
+    /// Build the map from unsorted pairs. When a key is repeated, the last value wins.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut pairs: Vec<(K, V)> = iter.into_iter().collect();
+
+        // Stable sort keeps the insertion order of repeated keys, so the last one is kept
+        pairs.sort_by(|a, b| a.0.cmp(&b.0));
+        pairs.reverse();
+        pairs.dedup_by(|a, b| a.0 == b.0);
+        pairs.reverse();
+
+        Self::from_sorted(pairs).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorted_slice_map_lookup() {
+        for n in 0..100u32 {
+            let pairs: Vec<(u32, u32)> = (0..n).map(|i| (i * 2, i)).collect();
+            let map = SortedSliceMap::from_sorted(pairs).unwrap();
+
+            assert_eq!(map.len(), n as usize);
+            for i in 0..n {
+                assert_eq!(map.get(&(i * 2)), Some(&i));
+                assert_eq!(map.get(&(i * 2 + 1)), None);
+                assert_eq!(map.lower_bound(&(i * 2 + 1)).map(|(k, _)| *k), {
+                    if i + 1 < n {
+                        Some(i * 2 + 2)
+                    } else {
+                        None
+                    }
+                });
+            }
+            assert_eq!(
+                map.keys().copied().collect::<Vec<_>>(),
+                (0..n).map(|i| i * 2).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_sorted_slice_map_from_iter() {
+        let map: SortedSliceMap<&str, u32> = vec![("b", 1), ("a", 2), ("b", 3), ("c", 4)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get(&"b"), Some(&3));
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            vec![(&"a", &2), (&"b", &3), (&"c", &4)]
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_sorted_slice_map_vs_btree_map_stress() {
+        use std::{collections::BTreeMap, time::Instant};
+
+        let n = 1_000_000u64;
+        let map = SortedSliceMap::from_sorted((0..n).map(|i| (i * 3, i)).collect()).unwrap();
+        let btree: BTreeMap<u64, u64> = (0..n).map(|i| (i * 3, i)).collect();
+
+        let start_time = Instant::now();
+        let mut found = 0;
+        for i in 0..3 * n {
+            found += map.contains_key(&i) as u64;
+        }
+        println!(
+            "SortedSliceMap lookups completed in {:?}",
+            start_time.elapsed()
+        );
+
+        let start_time = Instant::now();
+        for i in 0..3 * n {
+            found -= btree.contains_key(&i) as u64;
+        }
+        println!("BTreeMap lookups completed in {:?}", start_time.elapsed());
+
+        assert_eq!(found, 0);
+    }
+}
@@ -0,0 +1,467 @@
+//! This module implements a generational arena, a growable buffer handing out indices that detect reuse.
+//! Like `FreeListVec` it stores values in place in a single `Vec` and reuses the slots of removed values, but each index
+//! carries the generation of its slot: once a value is removed, its index stops matching the slot, even after the slot
+//! is reused. A stale index is reported instead of silently reaching the value that took its place.
+//!
+//! # Performance
+//! - O(1) amortized for `insert`
+//! - O(1) for `remove`, `get` and `get_mut`
+//!
+//! # Implementation Details
+//! - Each slot holds a generation, bumped every time its value is removed, and either a value or the index of the next
+//!   vacant slot.
+//! - An `ArenaIndex` is a slot and a generation, and is valid while they match.
+//! - `clear` keeps the slots so their generations survive, and old indices stay stale.
+//! - Generations are 32 bits and wrap around: an index kept while its slot is reused 2^32 times would match again.
+//!
+//! # Usage
+//! ```
+//! use data_structures::storage::arena::Arena;
+//!
+//! let mut arena = Arena::new();
+//!
+//! let a = arena.insert("a");
+//! assert_eq!(arena.remove(a), Some("a"));
+//!
+//! // The slot is reused, but the old index doesn't reach the new value
+//! let b = arena.insert("b");
+//! assert_eq!(b.slot(), a.slot());
+//! assert_eq!(arena.get(a), None);
+//! assert_eq!(arena.get(b), Some(&"b"));
+//! ```
+//!
+
+#[cfg(feature = "fallible-alloc")]
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
+
+/// Struct representing the index of a value in an `Arena`
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArenaIndex {
+    slot: usize,
+    generation: u32,
+}
+
+impl ArenaIndex {
+    /// Get the slot of the index
+    /// # Returns
+    /// The position of the value in the arena buffer
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let mut arena = Arena::new();
+    /// assert_eq!(arena.insert(1).slot(), 0);
+    /// ```
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+    /// Get the generation of the index
+    /// # Returns
+    /// The number of values removed from the slot before this one was inserted
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let mut arena = Arena::new();
+    /// let first = arena.insert(1);
+    /// arena.remove(first);
+    ///
+    /// assert_eq!(first.generation(), 0);
+    /// assert_eq!(arena.insert(2).generation(), 1);
+    /// ```
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+/// A slot of the arena
+#[derive(Debug, Clone)]
+enum Entry<T> {
+    Occupied {
+        generation: u32,
+        value: T,
+    },
+    // Holds the generation of the next value and the index of the next vacant slot
+    Vacant {
+        generation: u32,
+        next: Option<usize>,
+    },
+}
+
+/// Struct representing a buffer of values with generational indices
+///
+#[derive(Debug, Clone)]
+pub struct Arena<T> {
+    entries: Vec<Entry<T>>,
+    free_head: Option<usize>,
+
+    size: usize,
+}
+
+impl<T> Arena<T> {
+    /// Create a new empty Arena
+    ///
+    /// # Returns
+    /// A new Arena instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let arena: Arena<u32> = Arena::new();
+    /// assert!(arena.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a new empty Arena with room for `capacity` values before reallocating
+    ///
+    /// # Arguments
+    /// * `capacity`: The number of slots to allocate
+    ///
+    /// # Returns
+    /// A new Arena instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let arena: Arena<u32> = Arena::with_capacity(16);
+    /// assert!(arena.capacity() >= 16);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Arena {
+            entries: Vec::with_capacity(capacity),
+            free_head: None,
+            size: 0,
+        }
+    }
+
+    /// Get the number of values in the arena
+    /// # Returns
+    /// The number of occupied slots
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let mut arena = Arena::new();
+    /// arena.insert(1);
+    /// assert_eq!(arena.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the arena is empty
+    /// # Returns
+    /// True if there are no values, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let arena: Arena<u8> = Arena::new();
+    /// assert!(arena.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Get the number of slots the arena can hold without reallocating
+    /// # Returns
+    /// The allocated capacity
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let arena: Arena<u8> = Arena::with_capacity(4);
+    /// assert!(arena.capacity() >= 4);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Reserve room for at least `additional` more values, reporting an allocation failure instead of aborting
+    /// # Arguments
+    /// * `additional`: The number of values to make room for. Vacant slots count as room.
+    /// # Returns
+    /// Result<(), TryReserveError>
+    /// Ok if the next `additional` insertions won't allocate, Err if the memory couldn't be allocated
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let mut arena: Arena<u8> = Arena::new();
+    /// arena.try_reserve(8).unwrap();
+    /// assert!(arena.capacity() >= 8);
+    ///
+    /// assert!(arena.try_reserve(usize::MAX).is_err());
+    /// ```
+    #[cfg(feature = "fallible-alloc")]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let vacant = self.entries.len() - self.size;
+        self.entries.try_reserve(additional.saturating_sub(vacant))
+    }
+
+    /// Store a value, reusing a vacant slot if there is one
+    /// # Arguments
+    /// * `value`: The value to be stored
+    /// # Returns
+    /// The index of the value
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let mut arena = Arena::new();
+    /// let index = arena.insert('x');
+    /// assert_eq!(arena.get(index), Some(&'x'));
+    /// ```
+    pub fn insert(&mut self, value: T) -> ArenaIndex {
+        self.size += 1;
+
+        match self.free_head {
+            Some(slot) => {
+                let (generation, next) = match self.entries[slot] {
+                    Entry::Vacant { generation, next } => (generation, next),
+                    Entry::Occupied { .. } => unreachable!("free list points to an occupied slot"),
+                };
+                self.free_head = next;
+                self.entries[slot] = Entry::Occupied { generation, value };
+                ArenaIndex { slot, generation }
+            }
+            None => {
+                self.entries.push(Entry::Occupied {
+                    generation: 0,
+                    value,
+                });
+                ArenaIndex {
+                    slot: self.entries.len() - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Remove a value and release its slot, making its index stale
+    /// # Arguments
+    /// * `index`: The index of the value
+    /// # Returns
+    /// The removed value, or None if the index is stale or out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let mut arena = Arena::new();
+    /// let index = arena.insert(5);
+    ///
+    /// assert_eq!(arena.remove(index), Some(5));
+    /// assert_eq!(arena.remove(index), None);
+    /// ```
+    pub fn remove(&mut self, index: ArenaIndex) -> Option<T> {
+        if !self.contains(index) {
+            return None;
+        }
+
+        let vacant = Entry::Vacant {
+            generation: index.generation.wrapping_add(1),
+            next: self.free_head,
+        };
+        let entry = core::mem::replace(&mut self.entries[index.slot], vacant);
+        self.free_head = Some(index.slot);
+        self.size -= 1;
+
+        match entry {
+            Entry::Occupied { value, .. } => Some(value),
+            Entry::Vacant { .. } => unreachable!("checked by contains"),
+        }
+    }
+
+    /// Check if an index holds a value
+    /// # Arguments
+    /// * `index`: The index to be checked
+    /// # Returns
+    /// True if the value of the index is still in the arena, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let mut arena = Arena::new();
+    /// let index = arena.insert(());
+    /// assert!(arena.contains(index));
+    ///
+    /// arena.remove(index);
+    /// assert!(!arena.contains(index));
+    /// ```
+    pub fn contains(&self, index: ArenaIndex) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// Get a reference to a value
+    /// # Arguments
+    /// * `index`: The index of the value
+    /// # Returns
+    /// A reference to the value, or None if the index is stale or out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let mut arena = Arena::new();
+    /// let index = arena.insert("value");
+    /// assert_eq!(arena.get(index), Some(&"value"));
+    /// ```
+    pub fn get(&self, index: ArenaIndex) -> Option<&T> {
+        match self.entries.get(index.slot) {
+            Some(Entry::Occupied { generation, value }) if *generation == index.generation => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to a value
+    /// # Arguments
+    /// * `index`: The index of the value
+    /// # Returns
+    /// A mutable reference to the value, or None if the index is stale or out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let mut arena = Arena::new();
+    /// let index = arena.insert(1);
+    ///
+    /// *arena.get_mut(index).unwrap() += 1;
+    /// assert_eq!(arena.get(index), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, index: ArenaIndex) -> Option<&mut T> {
+        match self.entries.get_mut(index.slot) {
+            Some(Entry::Occupied { generation, value }) if *generation == index.generation => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Remove every value, keeping the slots so that every index handed out so far becomes stale
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let mut arena = Arena::new();
+    /// let index = arena.insert(1);
+    /// arena.clear();
+    ///
+    /// assert!(arena.is_empty());
+    /// let reused = arena.insert(2);
+    /// assert_eq!(arena.get(index), None);
+    /// assert_eq!(arena.get(reused), Some(&2));
+    /// ```
+    pub fn clear(&mut self) {
+        // Chain the slots in increasing order, so the next insertions fill the buffer from its start
+        let mut next = None;
+        for (slot, entry) in self.entries.iter_mut().enumerate().rev() {
+            let generation = match entry {
+                Entry::Occupied { generation, .. } => generation.wrapping_add(1),
+                Entry::Vacant { generation, .. } => *generation,
+            };
+            *entry = Entry::Vacant { generation, next };
+            next = Some(slot);
+        }
+        self.free_head = next;
+        self.size = 0;
+    }
+
+    /// Get an iterator over the stored values
+    /// # Returns
+    /// An iterator of (index, value) tuples, ordered by slot
+    /// # Example
+    /// ```
+    /// use data_structures::storage::arena::Arena;
+    ///
+    /// let mut arena = Arena::new();
+    /// let a = arena.insert('a');
+    /// let b = arena.insert('b');
+    /// arena.remove(a);
+    ///
+    /// assert_eq!(arena.iter().collect::<Vec<_>>(), vec![(b, &'b')]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (ArenaIndex, &T)> + '_ {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, entry)| match entry {
+                Entry::Occupied { generation, value } => Some((
+                    ArenaIndex {
+                        slot,
+                        generation: *generation,
+                    },
+                    value,
+                )),
+                Entry::Vacant { .. } => None,
+            })
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> core::ops::Index<ArenaIndex> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, index: ArenaIndex) -> &Self::Output {
+        self.get(index).expect("Stale arena index")
+    }
+}
+
+impl<T> core::ops::IndexMut<ArenaIndex> for Arena<T> {
+    fn index_mut(&mut self, index: ArenaIndex) -> &mut Self::Output {
+        self.get_mut(index).expect("Stale arena index")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_arena_detects_stale_indices() {
+        let mut arena = Arena::new();
+        let mut live: HashMap<ArenaIndex, u32> = HashMap::new();
+        let mut removed: Vec<ArenaIndex> = Vec::new();
+        let mut rng = Lcg::new(13);
+
+        for value in 0..4_000u32 {
+            let seed = rng.next_u64();
+            if (seed >> 62) == 0 && !live.is_empty() {
+                let index = *live.keys().nth((seed >> 33) as usize % live.len()).unwrap();
+                assert_eq!(arena.remove(index), live.remove(&index));
+                removed.push(index);
+            } else {
+                let index = arena.insert(value);
+                assert!(live.insert(index, value).is_none());
+            }
+            assert_eq!(arena.len(), live.len());
+        }
+
+        // Slots were reused, yet no removed index reaches a value
+        assert!(arena.capacity() < 4_000);
+        for index in &removed {
+            assert_eq!(arena.get(*index), None);
+        }
+        for (index, value) in &live {
+            assert_eq!(arena[*index], *value);
+        }
+        assert_eq!(arena.iter().count(), live.len());
+
+        arena.clear();
+        assert!(live.keys().all(|&index| !arena.contains(index)));
+    }
+}
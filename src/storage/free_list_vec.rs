@@ -0,0 +1,409 @@
+//! This module implements a growable buffer with stable indices and a free list of vacant slots.
+//! Values are stored in place in a single `Vec`, and the slots of removed values are chained in a free list and reused by
+//! the next insertions. It is meant as the node storage of churn-heavy structures: allocating and freeing a node doesn't
+//! touch the allocator once the buffer has grown to its working size.
+//!
+//! # Performance
+//! - O(1) amortized for `insert`
+//! - O(1) for `remove`, `get` and `get_mut`
+//!
+//! # Implementation Details
+//! - Each slot is either occupied by a value or vacant, holding the index of the next vacant slot.
+//! - The most recently freed slot is reused first, which keeps the working set small.
+//! - Indices stay valid until their value is removed. A removed index may be handed out again by a later insertion,
+//!   so callers that need to detect stale indices must track that themselves.
+//!
+//! # Usage
+//! ```
+//! use data_structures::storage::free_list_vec::FreeListVec;
+//!
+//! let mut nodes = FreeListVec::new();
+//!
+//! let a = nodes.insert("a");
+//! let b = nodes.insert("b");
+//!
+//! assert_eq!(nodes.remove(a), Some("a"));
+//! assert_eq!(nodes.get(b), Some(&"b"));
+//!
+//! // The freed slot is reused
+//! assert_eq!(nodes.insert("c"), a);
+//! assert_eq!(nodes.slots(), 2);
+//! ```
+//!
+
+#[cfg(feature = "fallible-alloc")]
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
+
+/// A slot of the buffer
+#[derive(Debug, Clone)]
+enum Slot<T> {
+    Occupied(T),
+    Vacant(Option<usize>), // index of the next vacant slot
+}
+
+/// Struct representing a buffer of values with stable indices and O(1) allocation and release
+///
+#[derive(Debug, Clone)]
+pub struct FreeListVec<T> {
+    slots: Vec<Slot<T>>,
+    free_head: Option<usize>,
+
+    size: usize,
+}
+
+impl<T> FreeListVec<T> {
+    /// Create a new empty FreeListVec
+    ///
+    /// # Returns
+    /// A new FreeListVec instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let buffer: FreeListVec<u32> = FreeListVec::new();
+    /// assert!(buffer.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a new empty FreeListVec with room for `capacity` values before reallocating
+    ///
+    /// # Arguments
+    /// * `capacity`: The number of slots to allocate
+    ///
+    /// # Returns
+    /// A new FreeListVec instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let buffer: FreeListVec<u32> = FreeListVec::with_capacity(16);
+    /// assert!(buffer.capacity() >= 16);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        FreeListVec {
+            slots: Vec::with_capacity(capacity),
+            free_head: None,
+            size: 0,
+        }
+    }
+
+    /// Get the number of values in the buffer
+    /// # Returns
+    /// The number of occupied slots
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let mut buffer = FreeListVec::new();
+    /// buffer.insert(1);
+    /// assert_eq!(buffer.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the buffer is empty
+    /// # Returns
+    /// True if there are no values, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let buffer: FreeListVec<u8> = FreeListVec::new();
+    /// assert!(buffer.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Get the number of slots, occupied or vacant
+    /// # Returns
+    /// The number of slots in use by the buffer, which is the upper bound of the indices
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let mut buffer = FreeListVec::new();
+    /// let index = buffer.insert(1);
+    /// buffer.remove(index);
+    ///
+    /// assert_eq!(buffer.len(), 0);
+    /// assert_eq!(buffer.slots(), 1);
+    /// ```
+    pub fn slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Get the number of slots the buffer can hold without reallocating
+    /// # Returns
+    /// The allocated capacity
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let buffer: FreeListVec<u8> = FreeListVec::with_capacity(4);
+    /// assert!(buffer.capacity() >= 4);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    /// Reserve room for at least `additional` more values, reporting an allocation failure instead of aborting
+    /// # Arguments
+    /// * `additional`: The number of values to make room for. Vacant slots count as room.
+    /// # Returns
+    /// Result<(), TryReserveError>
+    /// Ok if the next `additional` insertions won't allocate, Err if the memory couldn't be allocated
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let mut buffer: FreeListVec<u8> = FreeListVec::new();
+    /// buffer.try_reserve(8).unwrap();
+    /// assert!(buffer.capacity() >= 8);
+    ///
+    /// assert!(buffer.try_reserve(usize::MAX).is_err());
+    /// ```
+    #[cfg(feature = "fallible-alloc")]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let vacant = self.slots.len() - self.size;
+        self.slots.try_reserve(additional.saturating_sub(vacant))
+    }
+
+    /// Store a value, reusing a vacant slot if there is one
+    /// # Arguments
+    /// * `value`: The value to be stored
+    /// # Returns
+    /// The index of the value
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let mut buffer = FreeListVec::new();
+    /// let index = buffer.insert('x');
+    /// assert_eq!(buffer.get(index), Some(&'x'));
+    /// ```
+    pub fn insert(&mut self, value: T) -> usize {
+        self.size += 1;
+
+        match self.free_head {
+            Some(index) => {
+                self.free_head = match self.slots[index] {
+                    Slot::Vacant(next) => next,
+                    Slot::Occupied(_) => unreachable!("free list points to an occupied slot"),
+                };
+                self.slots[index] = Slot::Occupied(value);
+                index
+            }
+            None => {
+                self.slots.push(Slot::Occupied(value));
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    /// Remove a value and release its slot
+    /// # Arguments
+    /// * `index`: The index of the value
+    /// # Returns
+    /// The removed value, or None if the slot is vacant or out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let mut buffer = FreeListVec::new();
+    /// let index = buffer.insert(5);
+    ///
+    /// assert_eq!(buffer.remove(index), Some(5));
+    /// assert_eq!(buffer.remove(index), None);
+    /// ```
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if !self.contains(index) {
+            return None;
+        }
+
+        let slot = core::mem::replace(&mut self.slots[index], Slot::Vacant(self.free_head));
+        self.free_head = Some(index);
+        self.size -= 1;
+
+        match slot {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => unreachable!("checked by contains"),
+        }
+    }
+
+    /// Check if an index holds a value
+    /// # Arguments
+    /// * `index`: The index to be checked
+    /// # Returns
+    /// True if the slot is occupied, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let mut buffer = FreeListVec::new();
+    /// let index = buffer.insert(());
+    ///
+    /// assert!(buffer.contains(index));
+    /// assert!(!buffer.contains(index + 1));
+    /// ```
+    pub fn contains(&self, index: usize) -> bool {
+        matches!(self.slots.get(index), Some(Slot::Occupied(_)))
+    }
+
+    /// Get a reference to a value
+    /// # Arguments
+    /// * `index`: The index of the value
+    /// # Returns
+    /// A reference to the value, or None if the slot is vacant or out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let mut buffer = FreeListVec::new();
+    /// let index = buffer.insert("value");
+    /// assert_eq!(buffer.get(index), Some(&"value"));
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&T> {
+        match self.slots.get(index) {
+            Some(Slot::Occupied(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to a value
+    /// # Arguments
+    /// * `index`: The index of the value
+    /// # Returns
+    /// A mutable reference to the value, or None if the slot is vacant or out of bounds
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let mut buffer = FreeListVec::new();
+    /// let index = buffer.insert(1);
+    ///
+    /// *buffer.get_mut(index).unwrap() += 1;
+    /// assert_eq!(buffer.get(index), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        match self.slots.get_mut(index) {
+            Some(Slot::Occupied(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Remove every value, keeping the allocated memory
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let mut buffer = FreeListVec::new();
+    /// buffer.insert(1);
+    /// buffer.clear();
+    ///
+    /// assert!(buffer.is_empty());
+    /// assert_eq!(buffer.slots(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.free_head = None;
+        self.size = 0;
+    }
+
+    /// Get an iterator over the stored values
+    /// # Returns
+    /// An iterator of (index, value) tuples, ordered by index
+    /// # Example
+    /// ```
+    /// use data_structures::storage::free_list_vec::FreeListVec;
+    ///
+    /// let mut buffer = FreeListVec::new();
+    /// buffer.insert('a');
+    /// let b = buffer.insert('b');
+    /// buffer.insert('c');
+    /// buffer.remove(b);
+    ///
+    /// assert_eq!(buffer.iter().collect::<Vec<_>>(), vec![(0, &'a'), (2, &'c')]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| match slot {
+                Slot::Occupied(value) => Some((index, value)),
+                Slot::Vacant(_) => None,
+            })
+    }
+}
+
+impl<T> Default for FreeListVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> core::ops::Index<usize> for FreeListVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("No value at this index")
+    }
+}
+
+impl<T> core::ops::IndexMut<usize> for FreeListVec<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("No value at this index")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_free_list_vec_reuses_slots() {
+        let mut buffer = FreeListVec::with_capacity(100);
+        let indices: Vec<usize> = (0..100).map(|i| buffer.insert(i)).collect();
+
+        // Free every other slot, then allocate again: no new slot is created
+        for index in indices.iter().step_by(2) {
+            buffer.remove(*index);
+        }
+        assert_eq!(buffer.len(), 50);
+
+        for i in 0..50 {
+            buffer.insert(1_000 + i);
+        }
+
+        assert_eq!(buffer.len(), 100);
+        assert_eq!(buffer.slots(), 100);
+        assert!(buffer
+            .iter()
+            .all(|(index, value)| index % 2 == 1 || *value >= 1_000));
+        assert_eq!(buffer[1], 1);
+    }
+
+    #[test]
+    fn test_free_list_vec_churn() {
+        // Queue-like churn keeps a small working set of slots
+        let mut buffer = FreeListVec::new();
+        let mut live = std::collections::VecDeque::new();
+
+        for i in 0..100_000u32 {
+            live.push_back(buffer.insert(i));
+            if live.len() > 10 {
+                let index = live.pop_front().unwrap();
+                assert_eq!(buffer.remove(index), Some(i - 10));
+            }
+        }
+
+        assert_eq!(buffer.len(), 10);
+        assert_eq!(buffer.slots(), 11);
+    }
+}
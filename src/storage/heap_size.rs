@@ -0,0 +1,124 @@
+//! This module defines the `HeapSize` trait, through which elements report how many bytes they own on the heap.
+//! Containers use it to enforce budgets in bytes rather than in elements, which is what matters when elements vary a
+//! lot in size, such as the messages of a broker.
+//!
+//! # Implementation Details
+//! - `heap_size` only counts the memory owned through pointers. The inline part of a value is `size_of::<T>()`, and
+//!   `byte_size` adds both.
+//! - Collections report their allocated capacity, not their length, since that is what they actually hold.
+//!
+//! # Usage
+//! ```
+//! use data_structures::storage::heap_size::{byte_size, HeapSize};
+//!
+//! let mut message = String::with_capacity(64);
+//! message.push_str("hello");
+//!
+//! assert_eq!(message.heap_size(), 64);
+//! assert_eq!(byte_size(&message), 64 + std::mem::size_of::<String>());
+//! assert_eq!(42u32.heap_size(), 0);
+//! ```
+//!
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::mem;
+
+/// Trait of the values that can report the heap memory they own
+///
+pub trait HeapSize {
+    /// Bytes owned on the heap by the value, not counting its inline size
+    fn heap_size(&self) -> usize;
+}
+
+/// Total bytes used by a value: its inline size plus the heap memory it owns
+///
+/// # Arguments
+/// * `value`: The value to be measured
+///
+/// # Returns
+/// The size of the value in bytes
+///
+/// # Example
+/// ```
+/// use data_structures::storage::heap_size::byte_size;
+///
+/// assert_eq!(byte_size(&7u64), 8);
+/// ```
+pub fn byte_size<T: HeapSize>(value: &T) -> usize {
+    mem::size_of::<T>() + value.heap_size()
+}
+
+macro_rules! no_heap {
+    ($($ty:ty),*) => {$(
+        impl HeapSize for $ty {
+            fn heap_size(&self) -> usize {
+                0
+            }
+        }
+    )*};
+}
+
+no_heap!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    bool,
+    char,
+    ()
+);
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * mem::size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_size(&self) -> usize {
+        byte_size(self.as_ref())
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size)
+    }
+}
+
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size() + self.1.heap_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_sizes() {
+        let words = vec![String::with_capacity(10), String::with_capacity(20)];
+        let expected = words.capacity() * mem::size_of::<String>() + 30;
+        assert_eq!(words.heap_size(), expected);
+
+        let boxed = Box::new(5u16);
+        assert_eq!(boxed.heap_size(), 2);
+        assert_eq!(Some(String::with_capacity(3)).heap_size(), 3);
+    }
+}
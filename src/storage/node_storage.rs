@@ -0,0 +1,653 @@
+//! This module defines the `NodeStorage` trait, which abstracts where the nodes of a linked structure live and how they
+//! point to each other. A structure written against it, such as `CircularQueue`, can be instantiated with the backend
+//! that fits the use case instead of being tied to one memory layout.
+//!
+//! Four backends are provided:
+//! - `RcStorage` keeps each node in its own reference counted `Vertex`, like the rest of the `linked_list` module.
+//! - `CachedStorage` is `RcStorage` with a thread-local cache of freed vertexes, which are reused by the next
+//!   allocations of the same thread instead of going back to the global allocator.
+//! - `SlabStorage` keeps every node in a single `FreeListVec` and links them by index. Nodes are allocated from the
+//!   buffer and freed slots are reused, so a structure that churns doesn't hit the allocator once it reached its
+//!   working size.
+//! - `ArenaStorage` is `SlabStorage` over a generational `Arena`: its handles carry the generation of their slot, so
+//!   using the handle of a freed node panics instead of reaching the node that reused its slot.
+//!
+//! # Performance
+//! - O(1) for every operation of every backend, `alloc` being amortized for `SlabStorage` and `ArenaStorage`
+//!
+//! # Implementation Details
+//! - Nodes are addressed through handles: a vertex pointer for `RcStorage`, a slot index for `SlabStorage` and an
+//!   `ArenaIndex` for `ArenaStorage`.
+//! - Each node has a left and a right link, named after `Direction`.
+//! - A handle is only valid with the storage that allocated it and until the node is freed.
+//! - `CachedStorage` needs the `std` feature for its thread-local cache, the other backends only need `alloc`.
+//! - The vertex cache of `CachedStorage` is per thread and per element type, and holds at most `VERTEX_CACHE_LIMIT`
+//!   vertexes. A freed vertex is only cached when nothing else points to it, and is dropped otherwise.
+//!
+//! # Usage
+//! ```
+//! use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+//! use data_structures::storage::node_storage::SlabStorage;
+//!
+//! let mut queue = CircularQueue::with_storage(0, SlabStorage::new());
+//!
+//! queue.insert(1, Direction::Left).unwrap();
+//! queue.insert(2, Direction::Left).unwrap();
+//! assert_eq!(queue.remove(Direction::Right), Some(1));
+//! ```
+//!
+#[cfg(feature = "std")]
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+};
+
+use super::arena::{Arena, ArenaIndex};
+use super::free_list_vec::FreeListVec;
+#[cfg(feature = "std")]
+use crate::linked_list::shared::Ptr;
+use crate::linked_list::{circular_queue::Direction, shared::Shared, vertex::Vertex};
+
+/// Maximum number of vertexes kept by the cache of `CachedStorage`, per thread and element type
+pub const VERTEX_CACHE_LIMIT: usize = 1024;
+
+#[cfg(feature = "std")]
+thread_local! {
+    // Freed vertexes of each element type, as a Vec<Shared<Vertex<T>>> behind the type id of T
+    static VERTEX_CACHES: RefCell<HashMap<TypeId, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+// Call `f` with the vertex cache of the current thread for the element type T
+#[cfg(feature = "std")]
+fn with_vertex_cache<T: 'static, R>(f: impl FnOnce(&mut Vec<Shared<Vertex<T>>>) -> R) -> R {
+    VERTEX_CACHES.with(|caches| {
+        let mut caches = caches.borrow_mut();
+        let cache = caches
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Vec::<Shared<Vertex<T>>>::new()));
+        f(cache.downcast_mut().unwrap())
+    })
+}
+
+/// Trait of the backends that store the nodes of a linked structure
+///
+pub trait NodeStorage<T> {
+    /// Reference to a node of the storage
+    type Handle: Clone;
+
+    /// Store a new unlinked node holding `data` and return its handle
+    fn alloc(&mut self, data: T) -> Self::Handle;
+
+    /// Remove a node from the storage and return its data. Its links are dropped.
+    fn free(&mut self, node: Self::Handle) -> T;
+
+    /// Get the node linked on the given side
+    fn link(&self, node: &Self::Handle, side: Direction) -> Option<Self::Handle>;
+
+    /// Link a node to another one on the given side, or unlink that side with None
+    fn set_link(&mut self, node: &Self::Handle, side: Direction, to: Option<&Self::Handle>);
+
+    /// Call `f` with a reference to the data of a node
+    fn read<R>(&self, node: &Self::Handle, f: impl FnOnce(&T) -> R) -> R;
+
+    /// Replace the data of a node, keeping its links, and return the previous data
+    fn replace(&mut self, node: &Self::Handle, data: T) -> T;
+
+    /// Store a new unlinked node, reporting an allocation failure instead of aborting. `data` is dropped on failure.
+    ///
+    /// Backends that can't detect allocation failures keep this default, which calls `alloc`.
+    #[cfg(feature = "fallible-alloc")]
+    fn try_alloc(&mut self, data: T) -> Result<Self::Handle, AllocError> {
+        Ok(self.alloc(data))
+    }
+}
+
+/// Error returned when the memory for a node couldn't be allocated
+///
+#[cfg(feature = "fallible-alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+#[cfg(feature = "fallible-alloc")]
+impl core::fmt::Display for AllocError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Memory allocation failed")
+    }
+}
+
+#[cfg(all(feature = "fallible-alloc", feature = "std"))]
+impl std::error::Error for AllocError {}
+
+/// Struct representing the reference counted vertex backend
+///
+/// It holds no state: every node is a separate `Vertex` allocation.
+///
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RcStorage;
+
+impl<T> NodeStorage<T> for RcStorage {
+    type Handle = Shared<Vertex<T>>;
+
+    fn alloc(&mut self, data: T) -> Self::Handle {
+        Vertex::new(data)
+    }
+
+    fn free(&mut self, node: Self::Handle) -> T {
+        let data = node.borrow_mut().clear();
+        data.unwrap()
+    }
+
+    fn link(&self, node: &Self::Handle, side: Direction) -> Option<Self::Handle> {
+        node.borrow().get_pointer(side.into())
+    }
+
+    fn set_link(&mut self, node: &Self::Handle, side: Direction, to: Option<&Self::Handle>) {
+        node.borrow_mut().set_connection(side.into(), to);
+    }
+
+    fn read<R>(&self, node: &Self::Handle, f: impl FnOnce(&T) -> R) -> R {
+        f(node.borrow().read_data().as_ref().unwrap())
+    }
+
+    fn replace(&mut self, node: &Self::Handle, data: T) -> T {
+        node.borrow_mut().set_data(data).unwrap()
+    }
+}
+
+/// Struct representing the reference counted vertex backend with a thread-local cache of freed vertexes
+///
+/// Like `RcStorage` it holds no state: the cache belongs to the thread, so every storage of the same element type on a
+/// thread shares it. Push and pop heavy structures, especially with the `arc` feature, reuse their vertexes instead of
+/// allocating a new one for every element.
+///
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CachedStorage;
+
+#[cfg(feature = "std")]
+impl CachedStorage {
+    /// Get the number of vertexes cached by the current thread for an element type
+    ///
+    /// # Returns
+    /// The number of freed vertexes waiting to be reused
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::storage::node_storage::CachedStorage;
+    ///
+    /// let mut queue = CircularQueue::with_storage(0, CachedStorage);
+    /// queue.insert(1u64, Direction::Left).unwrap();
+    /// queue.remove(Direction::Right);
+    ///
+    /// assert_eq!(CachedStorage::cached::<u64>(), 1);
+    /// ```
+    pub fn cached<T: 'static>() -> usize {
+        with_vertex_cache::<T, _>(|cache| cache.len())
+    }
+
+    /// Drop the vertexes cached by the current thread for an element type, giving their memory back
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::storage::node_storage::CachedStorage;
+    ///
+    /// let mut queue = CircularQueue::with_storage(0, CachedStorage);
+    /// queue.insert('a', Direction::Left).unwrap();
+    /// queue.remove(Direction::Right);
+    ///
+    /// CachedStorage::clear_cache::<char>();
+    /// assert_eq!(CachedStorage::cached::<char>(), 0);
+    /// ```
+    pub fn clear_cache<T: 'static>() {
+        with_vertex_cache::<T, _>(|cache| cache.clear());
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: 'static> NodeStorage<T> for CachedStorage {
+    type Handle = Shared<Vertex<T>>;
+
+    fn alloc(&mut self, data: T) -> Self::Handle {
+        match with_vertex_cache::<T, _>(|cache| cache.pop()) {
+            Some(node) => {
+                node.borrow_mut().set_data(data);
+                node
+            }
+            None => Vertex::new(data),
+        }
+    }
+
+    fn free(&mut self, node: Self::Handle) -> T {
+        let data = node.borrow_mut().recycle().unwrap();
+
+        // A vertex still pointed to can't be reused, it is dropped with its last pointer
+        if Ptr::strong_count(&node) == 1 {
+            with_vertex_cache(|cache| {
+                if cache.len() < VERTEX_CACHE_LIMIT {
+                    cache.push(node);
+                }
+            });
+        }
+        data
+    }
+
+    fn link(&self, node: &Self::Handle, side: Direction) -> Option<Self::Handle> {
+        NodeStorage::<T>::link(&RcStorage, node, side)
+    }
+
+    fn set_link(&mut self, node: &Self::Handle, side: Direction, to: Option<&Self::Handle>) {
+        RcStorage.set_link(node, side, to);
+    }
+
+    fn read<R>(&self, node: &Self::Handle, f: impl FnOnce(&T) -> R) -> R {
+        RcStorage.read(node, f)
+    }
+
+    fn replace(&mut self, node: &Self::Handle, data: T) -> T {
+        RcStorage.replace(node, data)
+    }
+}
+
+/// A node of the slab and arena backends, linked by handle
+#[derive(Debug, Clone)]
+struct SlabNode<T, H = usize> {
+    data: T,
+    left: Option<H>,
+    right: Option<H>,
+}
+
+/// Struct representing the slab backend, where nodes are slots of a single buffer linked by index
+///
+#[derive(Debug, Clone)]
+pub struct SlabStorage<T> {
+    nodes: FreeListVec<SlabNode<T>>,
+}
+
+impl<T> SlabStorage<T> {
+    /// Create a new empty SlabStorage
+    ///
+    /// # Returns
+    /// A new SlabStorage instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::storage::node_storage::SlabStorage;
+    ///
+    /// let storage: SlabStorage<u32> = SlabStorage::new();
+    /// assert_eq!(storage.len(), 0);
+    /// ```
+    pub fn new() -> Self {
+        SlabStorage {
+            nodes: FreeListVec::new(),
+        }
+    }
+
+    /// Create a new empty SlabStorage with room for `capacity` nodes before reallocating
+    ///
+    /// # Arguments
+    /// * `capacity`: The number of nodes to allocate room for
+    ///
+    /// # Returns
+    /// A new SlabStorage instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::storage::node_storage::SlabStorage;
+    ///
+    /// let storage: SlabStorage<u32> = SlabStorage::with_capacity(128);
+    /// assert_eq!(storage.len(), 0);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        SlabStorage {
+            nodes: FreeListVec::with_capacity(capacity),
+        }
+    }
+
+    /// Get the number of nodes in the storage
+    /// # Returns
+    /// The number of live nodes
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::storage::node_storage::SlabStorage;
+    ///
+    /// let mut queue = CircularQueue::with_storage(0, SlabStorage::new());
+    /// queue.insert(1, Direction::Left).unwrap();
+    /// assert_eq!(queue.storage().len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check if the storage holds no node
+    /// # Returns
+    /// True if there is no live node
+    /// # Example
+    /// ```
+    /// use data_structures::storage::node_storage::SlabStorage;
+    ///
+    /// let storage: SlabStorage<u32> = SlabStorage::new();
+    /// assert!(storage.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Reserve room for at least `additional` more nodes, reporting an allocation failure instead of aborting
+    /// # Arguments
+    /// * `additional`: The number of nodes to make room for
+    /// # Returns
+    /// Result<(), AllocError>
+    /// Ok if the next `additional` allocations won't allocate memory, Err if the memory couldn't be allocated
+    /// # Example
+    /// ```
+    /// use data_structures::storage::node_storage::{AllocError, SlabStorage};
+    ///
+    /// let mut storage: SlabStorage<u64> = SlabStorage::new();
+    /// assert_eq!(storage.try_reserve(64), Ok(()));
+    /// assert_eq!(storage.try_reserve(usize::MAX), Err(AllocError));
+    /// ```
+    #[cfg(feature = "fallible-alloc")]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        self.nodes.try_reserve(additional).map_err(|_| AllocError)
+    }
+}
+
+impl<T> Default for SlabStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> NodeStorage<T> for SlabStorage<T> {
+    type Handle = usize;
+
+    fn alloc(&mut self, data: T) -> usize {
+        self.nodes.insert(SlabNode {
+            data,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn free(&mut self, node: usize) -> T {
+        self.nodes.remove(node).unwrap().data
+    }
+
+    fn link(&self, node: &usize, side: Direction) -> Option<usize> {
+        let node = &self.nodes[*node];
+        match side {
+            Direction::Left => node.left,
+            Direction::Right => node.right,
+        }
+    }
+
+    fn set_link(&mut self, node: &usize, side: Direction, to: Option<&usize>) {
+        let node = &mut self.nodes[*node];
+        match side {
+            Direction::Left => node.left = to.copied(),
+            Direction::Right => node.right = to.copied(),
+        }
+    }
+
+    fn read<R>(&self, node: &usize, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.nodes[*node].data)
+    }
+
+    fn replace(&mut self, node: &usize, data: T) -> T {
+        core::mem::replace(&mut self.nodes[*node].data, data)
+    }
+
+    #[cfg(feature = "fallible-alloc")]
+    fn try_alloc(&mut self, data: T) -> Result<usize, AllocError> {
+        self.try_reserve(1)?;
+        Ok(self.alloc(data))
+    }
+}
+
+/// Struct representing the arena backend, where nodes are slots of a single buffer linked by generational index
+///
+#[derive(Debug, Clone)]
+pub struct ArenaStorage<T> {
+    nodes: Arena<SlabNode<T, ArenaIndex>>,
+}
+
+impl<T> ArenaStorage<T> {
+    /// Create a new empty ArenaStorage
+    ///
+    /// # Returns
+    /// A new ArenaStorage instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::storage::node_storage::ArenaStorage;
+    ///
+    /// let storage: ArenaStorage<u32> = ArenaStorage::new();
+    /// assert_eq!(storage.len(), 0);
+    /// ```
+    pub fn new() -> Self {
+        ArenaStorage {
+            nodes: Arena::new(),
+        }
+    }
+
+    /// Create a new empty ArenaStorage with room for `capacity` nodes before reallocating
+    ///
+    /// # Arguments
+    /// * `capacity`: The number of nodes to allocate room for
+    ///
+    /// # Returns
+    /// A new ArenaStorage instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::storage::node_storage::ArenaStorage;
+    ///
+    /// let storage: ArenaStorage<u32> = ArenaStorage::with_capacity(128);
+    /// assert_eq!(storage.len(), 0);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        ArenaStorage {
+            nodes: Arena::with_capacity(capacity),
+        }
+    }
+
+    /// Get the number of nodes in the storage
+    /// # Returns
+    /// The number of live nodes
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    /// use data_structures::storage::node_storage::ArenaStorage;
+    ///
+    /// let mut list = LinkedList::with_storage(ArenaStorage::new());
+    /// list.push_back(1);
+    /// assert_eq!(list.storage().len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check if the storage holds no node
+    /// # Returns
+    /// True if there is no live node
+    /// # Example
+    /// ```
+    /// use data_structures::storage::node_storage::ArenaStorage;
+    ///
+    /// let storage: ArenaStorage<u32> = ArenaStorage::new();
+    /// assert!(storage.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Reserve room for at least `additional` more nodes, reporting an allocation failure instead of aborting
+    /// # Arguments
+    /// * `additional`: The number of nodes to make room for
+    /// # Returns
+    /// Result<(), AllocError>
+    /// Ok if the next `additional` allocations won't allocate memory, Err if the memory couldn't be allocated
+    /// # Example
+    /// ```
+    /// use data_structures::storage::node_storage::{AllocError, ArenaStorage};
+    ///
+    /// let mut storage: ArenaStorage<u64> = ArenaStorage::new();
+    /// assert_eq!(storage.try_reserve(64), Ok(()));
+    /// assert_eq!(storage.try_reserve(usize::MAX), Err(AllocError));
+    /// ```
+    #[cfg(feature = "fallible-alloc")]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        self.nodes.try_reserve(additional).map_err(|_| AllocError)
+    }
+}
+
+impl<T> Default for ArenaStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> NodeStorage<T> for ArenaStorage<T> {
+    type Handle = ArenaIndex;
+
+    fn alloc(&mut self, data: T) -> ArenaIndex {
+        self.nodes.insert(SlabNode {
+            data,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn free(&mut self, node: ArenaIndex) -> T {
+        self.nodes.remove(node).expect("Stale arena index").data
+    }
+
+    fn link(&self, node: &ArenaIndex, side: Direction) -> Option<ArenaIndex> {
+        let node = &self.nodes[*node];
+        match side {
+            Direction::Left => node.left,
+            Direction::Right => node.right,
+        }
+    }
+
+    fn set_link(&mut self, node: &ArenaIndex, side: Direction, to: Option<&ArenaIndex>) {
+        let node = &mut self.nodes[*node];
+        match side {
+            Direction::Left => node.left = to.copied(),
+            Direction::Right => node.right = to.copied(),
+        }
+    }
+
+    fn read<R>(&self, node: &ArenaIndex, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.nodes[*node].data)
+    }
+
+    fn replace(&mut self, node: &ArenaIndex, data: T) -> T {
+        core::mem::replace(&mut self.nodes[*node].data, data)
+    }
+
+    #[cfg(feature = "fallible-alloc")]
+    fn try_alloc(&mut self, data: T) -> Result<ArenaIndex, AllocError> {
+        self.try_reserve(1)?;
+        Ok(self.alloc(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise<S: NodeStorage<u32>>(mut storage: S) {
+        let a = storage.alloc(1);
+        let b = storage.alloc(2);
+
+        storage.set_link(&a, Direction::Right, Some(&b));
+        storage.set_link(&b, Direction::Left, Some(&a));
+
+        let right = storage.link(&a, Direction::Right).unwrap();
+        assert_eq!(storage.read(&right, |data| *data), 2);
+        assert_eq!(storage.replace(&right, 3), 2);
+        assert!(storage.link(&b, Direction::Left).is_some());
+        assert!(storage.link(&a, Direction::Left).is_none());
+
+        storage.set_link(&a, Direction::Right, None);
+        assert!(storage.link(&a, Direction::Right).is_none());
+
+        assert_eq!(storage.free(a), 1);
+        assert_eq!(storage.free(b), 3);
+    }
+
+    #[test]
+    fn test_backends() {
+        exercise(RcStorage);
+        #[cfg(feature = "std")]
+        exercise(CachedStorage);
+        exercise(SlabStorage::new());
+        exercise(ArenaStorage::new());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_cached_storage_reuses_vertexes() {
+        let mut storage = CachedStorage;
+        let a = storage.alloc(1u16);
+        let b = storage.alloc(2u16);
+
+        // b is still pointed to by a, so it isn't cached
+        storage.set_link(&a, Direction::Right, Some(&b));
+        let kept = b.clone();
+        assert_eq!(storage.free(b), 2);
+        assert_eq!(CachedStorage::cached::<u16>(), 0);
+
+        // Freeing a drops its links, and the next allocation gets the same vertex back
+        let address = Ptr::as_ptr(&a);
+        assert_eq!(storage.free(a), 1);
+        assert_eq!(CachedStorage::cached::<u16>(), 1);
+        assert_eq!(Ptr::strong_count(&kept), 1);
+
+        let c = storage.alloc(3u16);
+        assert_eq!(Ptr::as_ptr(&c), address);
+        assert_eq!(storage.read(&c, |data| *data), 3);
+        assert!(storage.link(&c, Direction::Right).is_none());
+        assert!(Ptr::ptr_eq(&c.borrow().get_reference(), &c));
+
+        // The cache is bounded
+        let nodes: Vec<_> = (0..VERTEX_CACHE_LIMIT + 10)
+            .map(|i| storage.alloc(i as u16))
+            .collect();
+        for node in nodes {
+            storage.free(node);
+        }
+        assert_eq!(CachedStorage::cached::<u16>(), VERTEX_CACHE_LIMIT);
+        CachedStorage::clear_cache::<u16>();
+        assert_eq!(CachedStorage::cached::<u16>(), 0);
+    }
+
+    #[test]
+    fn test_slab_reuses_slots() {
+        let mut storage = SlabStorage::new();
+        let a = storage.alloc(1);
+        storage.alloc(2);
+
+        storage.free(a);
+        assert_eq!(storage.alloc(3), a);
+        assert_eq!(storage.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Stale arena index")]
+    fn test_arena_storage_rejects_stale_handles() {
+        let mut storage = ArenaStorage::new();
+        let a = storage.alloc(1);
+        storage.free(a);
+
+        // The slot is reused, but the old handle doesn't reach the new node
+        let b = storage.alloc(2);
+        assert_eq!(b.slot(), a.slot());
+        storage.read(&a, |data| *data);
+    }
+}
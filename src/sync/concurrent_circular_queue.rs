@@ -0,0 +1,494 @@
+//! This module implements a circular queue that can be shared between threads, available with the `arc` feature.
+//! `ConcurrentCircularQueue` is a handle to a `CircularQueue` behind a `Mutex`: cloning it shares the same queue, so
+//! threads can insert and remove on both ends concurrently. Besides the non-blocking methods of `CircularQueue`, it
+//! has `insert_wait` and `remove_wait`, which block until the queue has room or an element.
+//!
+//! # Performance
+//! - The same as `CircularQueue` for each operation, plus taking the lock
+//! - Every operation takes the lock, so threads don't run in parallel on the same queue
+//!
+//! # Implementation Details
+//! - The `arc` feature makes the vertexes and the overflow policy of the `CircularQueue` `Send` and `Sync`, which
+//!   lets it be moved behind the `Mutex`.
+//! - Two `Condvar`s wake up the threads waiting for an element and for room. `update` gives access to the whole queue
+//!   for compound operations, such as iterating or seeking, and wakes up every waiting thread afterwards.
+//! - `insert_wait` waits for room even when the overflow policy would evict an element; the other inserts apply the
+//!   policy as `CircularQueue` does.
+//!
+//! # Usage
+//! ```
+//! use data_structures::linked_list::circular_queue::Direction;
+//! use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+//!
+//! let queue = ConcurrentCircularQueue::new(2);
+//! let worker = queue.clone();
+//!
+//! let handle = std::thread::spawn(move || {
+//!     worker.insert_wait("first", Direction::Left);
+//!     worker.insert_wait("second", Direction::Left);
+//!     worker.insert_wait("third", Direction::Left); // waits for the main thread to remove "first"
+//! });
+//!
+//! assert_eq!(queue.remove_wait(Direction::Right), "first");
+//! handle.join().unwrap();
+//! assert_eq!(queue.remove(Direction::Right), Some("second"));
+//! assert_eq!(queue.remove(Direction::Right), Some("third"));
+//! ```
+//!
+use std::{
+    fmt,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+};
+
+use crate::error::DataStructureError;
+use crate::linked_list::circular_queue::{CircularQueue, Direction};
+
+/// State shared by the handles of a queue
+struct Inner<T> {
+    queue: Mutex<CircularQueue<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+/// Struct representing a handle to a circular queue shared between threads
+///
+pub struct ConcurrentCircularQueue<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> ConcurrentCircularQueue<T> {
+    /// Create a new shared circular queue with a maximum size
+    /// # Arguments
+    /// * `max_size`: The maximum number of elements, or 0 for a queue without limit
+    /// # Returns
+    /// A handle to the new queue
+    /// # Example
+    /// ```
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue: ConcurrentCircularQueue<i32> = ConcurrentCircularQueue::new(3);
+    /// assert_eq!(queue.max_size(), 3);
+    /// ```
+    pub fn new(max_size: usize) -> Self {
+        Self::from(CircularQueue::new(max_size))
+    }
+
+    /// Get the maximum number of elements of the queue
+    /// # Returns
+    /// The maximum size, 0 meaning no limit
+    /// # Example
+    /// ```
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue: ConcurrentCircularQueue<i32> = ConcurrentCircularQueue::new(3);
+    /// assert_eq!(queue.max_size(), 3);
+    /// ```
+    pub fn max_size(&self) -> usize {
+        self.lock().max_size()
+    }
+
+    /// Set a new maximum size for the queue
+    /// # Arguments
+    /// * `max_size`: The new maximum size, 0 meaning no limit
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the new maximum size is set, Err if it is less than the number of elements
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::circular_queue::Direction;
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue = ConcurrentCircularQueue::new(0);
+    /// queue.insert(1, Direction::Left).unwrap();
+    /// queue.insert(2, Direction::Left).unwrap();
+    ///
+    /// assert_eq!(queue.set_max_size(1), Err(DataStructureError::CapacityBelowLen { len: 2, requested: 1 }));
+    /// assert_eq!(queue.set_max_size(2), Ok(()));
+    /// ```
+    pub fn set_max_size(&self, max_size: usize) -> Result<(), DataStructureError> {
+        self.lock().set_max_size(max_size)?;
+        // A larger queue may have room for the waiting threads
+        self.inner.not_full.notify_all();
+        Ok(())
+    }
+
+    /// Check if the queue is full
+    /// # Returns
+    /// true if the queue holds its maximum number of elements
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::Direction;
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue = ConcurrentCircularQueue::new(1);
+    /// queue.insert(1, Direction::Left).unwrap();
+    /// assert!(queue.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.lock().is_full()
+    }
+
+    /// Check if the queue is empty
+    /// # Returns
+    /// true if the queue has no element
+    /// # Example
+    /// ```
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue: ConcurrentCircularQueue<i32> = ConcurrentCircularQueue::new(1);
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Get the number of elements in the queue
+    /// # Returns
+    /// The number of elements
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::Direction;
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue = ConcurrentCircularQueue::new(0);
+    /// queue.insert(1, Direction::Left).unwrap();
+    /// queue.insert(2, Direction::Right).unwrap();
+    /// assert_eq!(queue.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Add an element to the queue without blocking
+    /// # Arguments
+    /// * `value`: The value to be added to the queue
+    /// * `side`: The side to add the element to (Left or Right)
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the element was added, Err if the queue is full and the overflow policy rejected it
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::linked_list::circular_queue::Direction;
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue = ConcurrentCircularQueue::new(1);
+    /// assert_eq!(queue.insert(1, Direction::Left), Ok(()));
+    /// assert_eq!(queue.insert(2, Direction::Left), Err(DataStructureError::Full));
+    /// ```
+    pub fn insert(&self, value: T, side: Direction) -> Result<(), DataStructureError> {
+        self.insert_or_evict(value, side).map(|_| ())
+    }
+
+    /// Add an element to the queue, applying the overflow policy if the queue is full
+    /// # Arguments
+    /// * `value`: The value to be added to the queue
+    /// * `side`: The side to add the element to (Left or Right)
+    /// # Returns
+    /// Result<Option<T>, DataStructureError>
+    /// Ok with the element evicted to make room, if any, or Err if the queue is full and the policy rejected the element
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::linked_list::overflow::EvictOldest;
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let mut ring = CircularQueue::new(1);
+    /// ring.set_overflow_policy(EvictOldest);
+    /// let queue = ConcurrentCircularQueue::from(ring);
+    ///
+    /// assert_eq!(queue.insert_or_evict(1, Direction::Left), Ok(None));
+    /// assert_eq!(queue.insert_or_evict(2, Direction::Left), Ok(Some(1)));
+    /// ```
+    pub fn insert_or_evict(
+        &self,
+        value: T,
+        side: Direction,
+    ) -> Result<Option<T>, DataStructureError> {
+        let evicted = self.lock().insert_or_evict(value, side)?;
+        self.inner.not_empty.notify_one();
+        Ok(evicted)
+    }
+
+    /// Add an element to the queue, overwriting the element at the cursor if the queue is full, like a ring buffer
+    /// # Arguments
+    /// * `value`: The value to be added to the queue
+    /// * `side`: The side to add the element to (Left or Right)
+    /// # Returns
+    /// The overwritten element, or None if the queue wasn't full
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::Direction;
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let samples = ConcurrentCircularQueue::new(2);
+    /// samples.insert_overwrite(1, Direction::Left);
+    /// samples.insert_overwrite(2, Direction::Left);
+    ///
+    /// assert_eq!(samples.insert_overwrite(3, Direction::Left), Some(1));
+    /// ```
+    pub fn insert_overwrite(&self, value: T, side: Direction) -> Option<T> {
+        let overwritten = self.lock().insert_overwrite(value, side);
+        self.inner.not_empty.notify_one();
+        overwritten
+    }
+
+    /// Add an element to the queue, blocking until it has room
+    /// # Arguments
+    /// * `value`: The value to be added to the queue
+    /// * `side`: The side to add the element to (Left or Right)
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::Direction;
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue = ConcurrentCircularQueue::new(1);
+    /// queue.insert_wait(1, Direction::Left);
+    ///
+    /// let consumer = queue.clone();
+    /// let handle = std::thread::spawn(move || consumer.remove_wait(Direction::Right));
+    ///
+    /// queue.insert_wait(2, Direction::Left); // waits for the consumer to remove 1
+    /// assert_eq!(handle.join().unwrap(), 1);
+    /// ```
+    pub fn insert_wait(&self, value: T, side: Direction) {
+        let mut queue = self.lock();
+        while queue.is_full() {
+            queue = self
+                .inner
+                .not_full
+                .wait(queue)
+                .expect("ConcurrentCircularQueue lock poisoned");
+        }
+        queue
+            .insert(value, side)
+            .expect("The queue has room after the wait");
+        drop(queue);
+        self.inner.not_empty.notify_one();
+    }
+
+    /// Remove the element at the cursor without blocking, moving the cursor to one side
+    /// # Arguments
+    /// * `side_to_move`: The side the cursor moves to (Left or Right)
+    /// # Returns
+    /// The removed element, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::Direction;
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue = ConcurrentCircularQueue::new(0);
+    /// queue.insert(1, Direction::Right).unwrap();
+    ///
+    /// assert_eq!(queue.remove(Direction::Left), Some(1));
+    /// assert_eq!(queue.remove(Direction::Left), None);
+    /// ```
+    pub fn remove(&self, side_to_move: Direction) -> Option<T> {
+        let value = self.lock().remove(side_to_move)?;
+        self.inner.not_full.notify_one();
+        Some(value)
+    }
+
+    /// Remove the element at the cursor, blocking until there is one
+    /// # Arguments
+    /// * `side_to_move`: The side the cursor moves to (Left or Right)
+    /// # Returns
+    /// The removed element
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::Direction;
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue = ConcurrentCircularQueue::new(0);
+    /// let producer = queue.clone();
+    ///
+    /// std::thread::spawn(move || producer.insert(7, Direction::Left).unwrap());
+    /// assert_eq!(queue.remove_wait(Direction::Right), 7);
+    /// ```
+    pub fn remove_wait(&self, side_to_move: Direction) -> T {
+        let mut queue = self.lock();
+        loop {
+            if let Some(value) = queue.remove(side_to_move) {
+                drop(queue);
+                self.inner.not_full.notify_one();
+                return value;
+            }
+            queue = self
+                .inner
+                .not_empty
+                .wait(queue)
+                .expect("ConcurrentCircularQueue lock poisoned");
+        }
+    }
+
+    /// Get a clone of the element at the cursor, the next one `remove` returns
+    /// # Returns
+    /// The element at the cursor, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::Direction;
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue = ConcurrentCircularQueue::new(0);
+    /// queue.insert(1, Direction::Left).unwrap();
+    /// queue.insert(2, Direction::Left).unwrap();
+    /// assert_eq!(queue.peek_cursor(), Some(1));
+    /// ```
+    pub fn peek_cursor(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.lock().peek_cursor()
+    }
+
+    /// Get a clone of the neighbor of the cursor on one side, the end where `insert` puts elements on that side
+    /// # Arguments
+    /// * `side`: The side of the cursor to look at (Left or Right)
+    /// # Returns
+    /// The element next to the cursor on that side, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::Direction;
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue = ConcurrentCircularQueue::new(0);
+    /// queue.insert('c', Direction::Left).unwrap();
+    /// queue.insert('l', Direction::Left).unwrap();
+    /// assert_eq!(queue.peek(Direction::Left), Some('l'));
+    /// ```
+    pub fn peek(&self, side: Direction) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.lock().peek(side)
+    }
+
+    /// Move the cursor along the ring, without changing the order of the elements
+    /// # Arguments
+    /// * `side`: The side to move the cursor to (Left or Right)
+    /// * `steps`: The number of elements to move by
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue = ConcurrentCircularQueue::from(CircularQueue::from(vec![1, 2, 3]));
+    /// queue.rotate(Direction::Right, 1);
+    /// assert_eq!(queue.peek_cursor(), Some(2));
+    /// ```
+    pub fn rotate(&self, side: Direction, steps: usize) {
+        self.lock().rotate(side, steps)
+    }
+
+    /// Run a function with exclusive access to the queue, for operations that must not be interleaved with others
+    /// Every waiting thread is woken up afterwards, since the function may have inserted or removed elements.
+    /// # Arguments
+    /// * `f`: The function using the queue
+    /// # Returns
+    /// The result of `f`
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+    /// use data_structures::sync::concurrent_circular_queue::ConcurrentCircularQueue;
+    ///
+    /// let queue = ConcurrentCircularQueue::from(CircularQueue::from(vec![1, 2, 3]));
+    ///
+    /// let snapshot: Vec<_> = queue.update(|queue| queue.iter().collect());
+    /// assert_eq!(snapshot, vec![1, 2, 3]);
+    /// ```
+    pub fn update<R>(&self, f: impl FnOnce(&mut CircularQueue<T>) -> R) -> R {
+        let result = f(&mut self.lock());
+        self.inner.not_empty.notify_all();
+        self.inner.not_full.notify_all();
+        result
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CircularQueue<T>> {
+        self.inner
+            .queue
+            .lock()
+            .expect("ConcurrentCircularQueue lock poisoned")
+    }
+}
+
+impl<T> Clone for ConcurrentCircularQueue<T> {
+    fn clone(&self) -> Self {
+        ConcurrentCircularQueue {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Default for ConcurrentCircularQueue<T> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T> From<CircularQueue<T>> for ConcurrentCircularQueue<T> {
+    fn from(queue: CircularQueue<T>) -> Self {
+        ConcurrentCircularQueue {
+            inner: Arc::new(Inner {
+                queue: Mutex::new(queue),
+                not_empty: Condvar::new(),
+                not_full: Condvar::new(),
+            }),
+        }
+    }
+}
+
+/// Debug prints the elements held when the Debug is called, from the cursor
+///
+impl<T: fmt::Debug> fmt::Debug for ConcurrentCircularQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ConcurrentCircularQueue")
+            .field(&*self.lock())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threads_on_both_ends() {
+        // Producers insert on both sides, consumers remove moving both ways, all at once
+        let queue = ConcurrentCircularQueue::new(16);
+        let producers: Vec<_> = [Direction::Left, Direction::Right]
+            .into_iter()
+            .enumerate()
+            .map(|(producer, side)| {
+                let queue = queue.clone();
+                std::thread::spawn(move || {
+                    for i in 0..2000u64 {
+                        queue.insert_wait(producer as u64 * 2000 + i, side);
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = [Direction::Left, Direction::Right]
+            .into_iter()
+            .map(|side| {
+                let queue = queue.clone();
+                std::thread::spawn(move || {
+                    (0..2000)
+                        .map(|_| queue.remove_wait(side))
+                        .collect::<Vec<u64>>()
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let mut received: Vec<u64> = consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect::<Vec<_>>();
+        received.sort();
+        assert_eq!(received, (0..4000).collect::<Vec<_>>());
+        assert!(queue.is_empty());
+    }
+}
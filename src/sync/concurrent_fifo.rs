@@ -0,0 +1,528 @@
+//! This module implements a FIFO queue that can be shared between threads, available with the `arc` feature.
+//! `ConcurrentFifo` is a handle to a `FIFO` behind a `Mutex`: cloning it shares the same queue, so producers and
+//! consumers on different threads can each hold one. Besides the non-blocking methods of `FIFO`, it has `push_wait`
+//! and `pop_wait`, which block until the queue has room or an element, and `push_timeout` and `pop_timeout`, which
+//! give up after a while, like a bounded channel.
+//!
+//! # Performance
+//! - The same as `FIFO` for each operation, plus taking the lock
+//! - Every operation takes the lock, so producers and consumers don't run in parallel on the same queue
+//!
+//! # Implementation Details
+//! - The `arc` feature makes the vertexes of the `FIFO` `Send` and `Sync`, which lets it be moved behind the `Mutex`.
+//! - Two `Condvar`s wake up the threads waiting for an element and for room. `update` gives access to the whole `FIFO`
+//!   for compound operations and wakes up every waiting thread afterwards.
+//! - A thread blocked in `pop_wait` waits until an element is pushed; it isn't woken up when the other handles are
+//!   dropped.
+//!
+//! # Usage
+//! ```
+//! use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+//!
+//! let queue = ConcurrentFifo::new(4);
+//! let consumer = queue.clone();
+//!
+//! let handle = std::thread::spawn(move || (0..10).map(|_| consumer.pop_wait()).sum::<u32>());
+//! for i in 0..10 {
+//!     queue.push_wait(i);
+//! }
+//!
+//! assert_eq!(handle.join().unwrap(), 45);
+//! assert!(queue.is_empty());
+//! ```
+//!
+use std::{
+    fmt,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use crate::error::DataStructureError;
+use crate::linked_list::fifo::FIFO;
+
+/// State shared by the handles of a queue
+struct Inner<T> {
+    fifo: Mutex<FIFO<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+/// Struct representing a handle to a FIFO queue shared between threads
+///
+pub struct ConcurrentFifo<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> ConcurrentFifo<T> {
+    /// Creates a new shared FIFO with a specified maximum size.
+    /// If the maximum size is zero, the FIFO can grow indefinitely.
+    /// # Arguments
+    /// * `max_size` - The maximum number of elements the FIFO can hold.
+    /// # Returns
+    /// A handle to the new queue
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo: ConcurrentFifo<u32> = ConcurrentFifo::new(5);
+    ///
+    /// assert_eq!(fifo.len(), 0);
+    /// assert_eq!(fifo.max_size(), 5);
+    /// ```
+    pub fn new(max_size: usize) -> Self {
+        Self::from(FIFO::new(max_size))
+    }
+
+    /// Get the number of elements in the queue
+    /// # Returns
+    /// The number of elements in the queue
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(5);
+    /// fifo.push(1).unwrap();
+    ///
+    /// assert_eq!(fifo.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Check if the queue is empty
+    /// # Returns
+    /// True if the queue is empty, false otherwise
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo: ConcurrentFifo<u32> = ConcurrentFifo::new(5);
+    ///
+    /// assert!(fifo.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Check if the queue is full
+    /// # Returns
+    /// True if the queue is full, false otherwise
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(1);
+    /// fifo.push(1).unwrap();
+    ///
+    /// assert!(fifo.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.lock().is_full()
+    }
+
+    /// Get the maximum size of the queue
+    /// # Returns
+    /// The maximum size of the queue
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo: ConcurrentFifo<u32> = ConcurrentFifo::new(5);
+    ///
+    /// assert_eq!(fifo.max_size(), 5);
+    /// ```
+    pub fn max_size(&self) -> usize {
+        self.lock().max_size()
+    }
+
+    /// Set a new maximum size for the queue
+    /// # Arguments
+    /// * `max_size`: The new maximum size for the queue
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the new maximum size is set successfully, Err if the new maximum size is less than the current size
+    /// # Example
+    /// ```rust
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(0);
+    /// fifo.push(1).unwrap();
+    /// fifo.push(2).unwrap();
+    ///
+    /// assert_eq!(fifo.set_max_size(1), Err(DataStructureError::CapacityBelowLen { len: 2, requested: 1 }));
+    /// assert_eq!(fifo.set_max_size(2), Ok(()));
+    /// assert_eq!(fifo.push(3), Err(DataStructureError::Full));
+    /// ```
+    pub fn set_max_size(&self, max_size: usize) -> Result<(), DataStructureError> {
+        self.lock().set_max_size(max_size)?;
+        // A larger queue may have room for the waiting producers
+        self.inner.not_full.notify_all();
+        Ok(())
+    }
+
+    /// Push a new element to the queue without blocking
+    /// # Arguments
+    /// * `value` - The value to be added to the queue
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok(()) if the push was successful, Err(DataStructureError::Full) if the queue is full
+    /// # Example
+    /// ```rust
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(1);
+    ///
+    /// assert_eq!(fifo.push(1), Ok(()));
+    /// assert_eq!(fifo.push(2), Err(DataStructureError::Full));
+    /// ```
+    pub fn push(&self, value: T) -> Result<(), DataStructureError> {
+        self.lock().push(value)?;
+        self.inner.not_empty.notify_one();
+        Ok(())
+    }
+
+    /// Push a new element to the queue, blocking until it has room
+    /// # Arguments
+    /// * `value` - The value to be added to the queue
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(1);
+    /// fifo.push_wait(1);
+    ///
+    /// let consumer = fifo.clone();
+    /// let handle = std::thread::spawn(move || consumer.pop_wait());
+    ///
+    /// fifo.push_wait(2); // waits for the consumer to pop 1
+    /// assert_eq!(handle.join().unwrap(), 1);
+    /// assert_eq!(fifo.pop(), Some(2));
+    /// ```
+    pub fn push_wait(&self, value: T) {
+        let mut fifo = self.lock();
+        while fifo.is_full() {
+            fifo = self
+                .inner
+                .not_full
+                .wait(fifo)
+                .expect("ConcurrentFifo lock poisoned");
+        }
+        fifo.push(value).expect("The queue has room after the wait");
+        drop(fifo);
+        self.inner.not_empty.notify_one();
+    }
+
+    /// Push an element to the queue, dropping the oldest one if the queue is full
+    /// # Arguments
+    /// * `value` - The value to push onto the queue
+    /// # Returns
+    /// The dropped element, or None if the queue wasn't full
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let recent = ConcurrentFifo::new(2);
+    ///
+    /// assert_eq!(recent.push_overwrite(1), None);
+    /// assert_eq!(recent.push_overwrite(2), None);
+    /// assert_eq!(recent.push_overwrite(3), Some(1));
+    /// assert_eq!(recent.pop(), Some(2));
+    /// ```
+    pub fn push_overwrite(&self, value: T) -> Option<T> {
+        let dropped = self.lock().push_overwrite(value);
+        self.inner.not_empty.notify_one();
+        dropped
+    }
+
+    /// Pop the oldest element without blocking
+    /// # Returns
+    /// Option<T>
+    /// Some(T) if the queue is not empty, None if the queue is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(3);
+    /// fifo.push(1).unwrap();
+    ///
+    /// assert_eq!(fifo.pop(), Some(1));
+    /// assert_eq!(fifo.pop(), None);
+    /// ```
+    pub fn pop(&self) -> Option<T> {
+        let value = self.lock().pop()?;
+        self.inner.not_full.notify_one();
+        Some(value)
+    }
+
+    /// Pop the oldest element, blocking until there is one
+    /// # Returns
+    /// The oldest element
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(0);
+    /// let producer = fifo.clone();
+    ///
+    /// std::thread::spawn(move || producer.push(7).unwrap());
+    /// assert_eq!(fifo.pop_wait(), 7);
+    /// ```
+    pub fn pop_wait(&self) -> T {
+        let mut fifo = self.lock();
+        loop {
+            if let Some(value) = fifo.pop() {
+                drop(fifo);
+                self.inner.not_full.notify_one();
+                return value;
+            }
+            fifo = self
+                .inner
+                .not_empty
+                .wait(fifo)
+                .expect("ConcurrentFifo lock poisoned");
+        }
+    }
+
+    /// Push a new element to the queue, blocking until it has room or the timeout expires
+    /// # Arguments
+    /// * `value` - The value to be added to the queue
+    /// * `timeout` - The longest time to wait for room
+    /// # Returns
+    /// Result<(), T>
+    /// Ok(()) if the element was pushed, Err with the element if the queue was still full after the timeout
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(1);
+    /// assert_eq!(fifo.push_timeout(1, Duration::from_millis(10)), Ok(()));
+    /// assert_eq!(fifo.push_timeout(2, Duration::from_millis(10)), Err(2));
+    /// ```
+    pub fn push_timeout(&self, value: T, timeout: Duration) -> Result<(), T> {
+        let (mut fifo, _) = self
+            .inner
+            .not_full
+            .wait_timeout_while(self.lock(), timeout, |fifo| fifo.is_full())
+            .expect("ConcurrentFifo lock poisoned");
+        if fifo.is_full() {
+            return Err(value);
+        }
+        fifo.push(value).expect("The queue has room after the wait");
+        drop(fifo);
+        self.inner.not_empty.notify_one();
+        Ok(())
+    }
+
+    /// Pop the oldest element, blocking until there is one or the timeout expires
+    /// # Arguments
+    /// * `timeout` - The longest time to wait for an element
+    /// # Returns
+    /// Option<T>
+    /// The oldest element, or None if the queue was still empty after the timeout
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(0);
+    /// assert_eq!(fifo.pop_timeout(Duration::from_millis(10)), None);
+    ///
+    /// let producer = fifo.clone();
+    /// std::thread::spawn(move || producer.push(7).unwrap());
+    /// assert_eq!(fifo.pop_timeout(Duration::from_secs(10)), Some(7));
+    /// ```
+    pub fn pop_timeout(&self, timeout: Duration) -> Option<T> {
+        let (mut fifo, _) = self
+            .inner
+            .not_empty
+            .wait_timeout_while(self.lock(), timeout, |fifo| fifo.is_empty())
+            .expect("ConcurrentFifo lock poisoned");
+        let value = fifo.pop()?;
+        drop(fifo);
+        self.inner.not_full.notify_one();
+        Some(value)
+    }
+
+    /// Get a clone of the oldest element, the next one `pop` returns
+    /// # Returns
+    /// The oldest element, or None if the queue is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(0);
+    /// fifo.push(1).unwrap();
+    /// fifo.push(2).unwrap();
+    ///
+    /// assert_eq!(fifo.front(), Some(1));
+    /// ```
+    pub fn front(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.lock().front()
+    }
+
+    /// Get a clone of the newest element, the last one pushed
+    /// # Returns
+    /// The newest element, or None if the queue is empty
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(0);
+    /// fifo.push(1).unwrap();
+    /// fifo.push(2).unwrap();
+    ///
+    /// assert_eq!(fifo.back(), Some(2));
+    /// ```
+    pub fn back(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.lock().back()
+    }
+
+    /// Run a function with exclusive access to the queue, for operations that must not be interleaved with others
+    /// Every waiting thread is woken up afterwards, since the function may have pushed or popped elements.
+    /// # Arguments
+    /// * `f`: The function using the queue
+    /// # Returns
+    /// The result of `f`
+    /// # Example
+    /// ```rust
+    /// use data_structures::sync::concurrent_fifo::ConcurrentFifo;
+    ///
+    /// let fifo = ConcurrentFifo::new(0);
+    /// for i in 0..5 {
+    ///     fifo.push(i).unwrap();
+    /// }
+    ///
+    /// // Pop a batch atomically
+    /// let batch: Vec<_> = fifo.update(|fifo| (0..3).filter_map(|_| fifo.pop()).collect());
+    /// assert_eq!(batch, vec![0, 1, 2]);
+    /// assert_eq!(fifo.len(), 2);
+    /// ```
+    pub fn update<R>(&self, f: impl FnOnce(&mut FIFO<T>) -> R) -> R {
+        let result = f(&mut self.lock());
+        self.inner.not_empty.notify_all();
+        self.inner.not_full.notify_all();
+        result
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FIFO<T>> {
+        self.inner
+            .fifo
+            .lock()
+            .expect("ConcurrentFifo lock poisoned")
+    }
+}
+
+impl<T> Clone for ConcurrentFifo<T> {
+    fn clone(&self) -> Self {
+        ConcurrentFifo {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Default for ConcurrentFifo<T> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T> From<FIFO<T>> for ConcurrentFifo<T> {
+    fn from(fifo: FIFO<T>) -> Self {
+        ConcurrentFifo {
+            inner: Arc::new(Inner {
+                fifo: Mutex::new(fifo),
+                not_empty: Condvar::new(),
+                not_full: Condvar::new(),
+            }),
+        }
+    }
+}
+
+/// Debug prints the elements held when the Debug is called, from the oldest to the newest
+///
+impl<T: fmt::Debug> fmt::Debug for ConcurrentFifo<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ConcurrentFifo")
+            .field(&*self.lock())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_producers_and_consumers() {
+        let queue = ConcurrentFifo::new(8);
+        let producers: Vec<_> = (0..4u64)
+            .map(|producer| {
+                let queue = queue.clone();
+                std::thread::spawn(move || {
+                    for i in 0..1000 {
+                        queue.push_wait((producer, i));
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..2)
+            .map(|_| {
+                let queue = queue.clone();
+                std::thread::spawn(move || {
+                    (0..2000)
+                        .map(|_| queue.pop_wait())
+                        .collect::<Vec<(u64, u64)>>()
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let mut received = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+        for consumer in consumers {
+            // Each consumer sees the elements of a producer in the order they were pushed
+            let mut last = [None; 4];
+            for (producer, i) in consumer.join().unwrap() {
+                assert!(last[producer as usize] < Some(i));
+                last[producer as usize] = Some(i);
+                received[producer as usize].push(i);
+            }
+        }
+        for mut values in received {
+            values.sort();
+            assert_eq!(values, (0..1000).collect::<Vec<_>>());
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_timeouts() {
+        let queue = ConcurrentFifo::new(1);
+        let start = std::time::Instant::now();
+        assert_eq!(queue.pop_timeout(Duration::from_millis(20)), None);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        queue.push(1).unwrap();
+        assert_eq!(queue.push_timeout(2, Duration::from_millis(20)), Err(2));
+
+        // A consumer makes room before the timeout
+        let consumer = queue.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            consumer.pop_wait()
+        });
+        assert_eq!(queue.push_timeout(2, Duration::from_secs(10)), Ok(()));
+        assert_eq!(handle.join().unwrap(), 1);
+        assert_eq!(queue.pop_timeout(Duration::ZERO), Some(2));
+    }
+}
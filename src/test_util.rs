@@ -0,0 +1,22 @@
+//! This module holds the helpers shared by the unit tests of the crate.
+//!
+
+/// Linear congruential generator, so the randomized tests are reproducible and don't depend on external crates
+///
+pub(crate) struct Lcg(u64);
+
+impl Lcg {
+    /// Create a generator starting from the given seed
+    pub(crate) fn new(seed: u64) -> Self {
+        Lcg(seed)
+    }
+
+    /// Advance the generator and return its new state. The high bits are the most random ones.
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0
+    }
+}
@@ -0,0 +1,390 @@
+//! This module implements the Aho-Corasick automaton for multi-pattern matching.
+//! The automaton is a trie of all the patterns with failure links between its vertexes, so a haystack is scanned once,
+//! whatever the number of patterns, reporting every occurrence of every pattern (including overlapping ones).
+//!
+//! # Performance
+//! - O(m) to build the automaton, where m is the total length of the patterns (each state probes the 256 possible bytes once)
+//! - O(n + z) to scan a haystack of length n with z matches
+//!
+//! # Implementation Details
+//! - Each state of the automaton is a `Vertex` holding the patterns that end on it.
+//! - Trie transitions are stored in custom pointers named after the byte, and the failure link in the `fail` custom pointer.
+//! - Failure links point back to shallower vertexes, so the automaton keeps a list of its vertexes and breaks the
+//!   reference cycles when it is dropped.
+//! - Patterns and haystacks are compared byte by byte, so match positions are byte offsets. Empty patterns are ignored.
+//!
+//! # Usage
+//! ```
+//! use data_structures::text::aho_corasick::AhoCorasick;
+//!
+//! let automaton = AhoCorasick::new(["he", "she", "his", "hers"]);
+//!
+//! let matches: Vec<(usize, usize, usize)> = automaton
+//!     .find_iter("ushers")
+//!     .map(|m| (m.pattern, m.start, m.end))
+//!     .collect();
+//!
+//! assert_eq!(matches, vec![(1, 1, 4), (0, 2, 4), (3, 2, 6)]);
+//! ```
+//!
+use std::collections::VecDeque;
+
+use crate::linked_list::shared::{Ptr, Shared};
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+// Name of the pointer holding the transition for the given byte
+fn transition_pointer(byte: u8) -> PointerName {
+    PointerName::Custom(byte.to_string())
+}
+
+fn fail_pointer() -> PointerName {
+    PointerName::Custom("fail".to_string())
+}
+
+/// Data of a state of the automaton
+#[derive(Debug)]
+struct State {
+    outputs: Vec<usize>, // patterns ending on this state, from the longest to the shortest
+}
+
+/// An occurrence of a pattern in the haystack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    /// Index of the pattern, in the order given to the constructor
+    pub pattern: usize,
+    /// Byte offset where the occurrence starts
+    pub start: usize,
+    /// Byte offset right after the end of the occurrence
+    pub end: usize,
+}
+
+/// Struct representing an Aho-Corasick automaton built from a set of patterns
+///
+#[derive(Debug)]
+pub struct AhoCorasick {
+    root: Shared<Vertex<State>>,
+    states: Vec<Shared<Vertex<State>>>, // every state, used to break the reference cycles on drop
+    pattern_lengths: Vec<usize>,
+}
+
+/// Iterator over the matches of an automaton in a haystack, ordered by end position
+pub struct Matches<'a, 'h> {
+    automaton: &'a AhoCorasick,
+    haystack: &'h [u8],
+    position: usize,
+    state: Shared<Vertex<State>>,
+    pending: VecDeque<Match>,
+}
+
+impl Iterator for Matches<'_, '_> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            let byte = *self.haystack.get(self.position)?;
+            self.position += 1;
+            self.state = self.automaton.next_state(&self.state, byte);
+
+            let end = self.position;
+            let lengths = &self.automaton.pattern_lengths;
+            self.pending.extend(
+                self.state
+                    .borrow()
+                    .read_data()
+                    .as_ref()
+                    .unwrap()
+                    .outputs
+                    .iter()
+                    .map(|&pattern| Match {
+                        pattern,
+                        start: end - lengths[pattern],
+                        end,
+                    }),
+            );
+        }
+
+        self.pending.pop_front()
+    }
+}
+
+impl AhoCorasick {
+    /// Build the automaton from a set of patterns
+    ///
+    /// # Arguments
+    /// * `patterns`: The patterns to be searched. Their index in this sequence identifies them in the matches.
+    ///
+    /// # Returns
+    /// A new AhoCorasick instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::text::aho_corasick::AhoCorasick;
+    ///
+    /// let automaton = AhoCorasick::new(vec!["error".to_string(), "warn".to_string()]);
+    /// assert_eq!(automaton.pattern_count(), 2);
+    /// ```
+    pub fn new<I, P>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let root = Vertex::new(State {
+            outputs: Vec::new(),
+        });
+        let mut states = vec![root.clone()];
+        let mut pattern_lengths = Vec::new();
+
+        // Build the trie of the patterns
+        for (index, pattern) in patterns.into_iter().enumerate() {
+            let pattern = pattern.as_ref();
+            pattern_lengths.push(pattern.len());
+
+            if pattern.is_empty() {
+                continue;
+            }
+
+            let mut current = root.clone();
+            for &byte in pattern {
+                let next = current.borrow().get_pointer(transition_pointer(byte));
+                current = match next {
+                    Some(next) => next,
+                    None => {
+                        let next = Vertex::new(State {
+                            outputs: Vec::new(),
+                        });
+                        current
+                            .borrow_mut()
+                            .set_connection(transition_pointer(byte), Some(&next));
+                        states.push(next.clone());
+                        next
+                    }
+                };
+            }
+
+            current.borrow_mut().data_mut().unwrap().outputs.push(index);
+        }
+
+        let automaton = AhoCorasick {
+            root,
+            states,
+            pattern_lengths,
+        };
+        automaton.link_failures();
+
+        automaton
+    }
+
+    // Compute the failure links breadth-first, so the failure of a state is always computed before its children
+    fn link_failures(&self) {
+        let mut queue = VecDeque::new();
+
+        for byte in 0..=u8::MAX {
+            if let Some(child) = self.root.borrow().get_pointer(transition_pointer(byte)) {
+                child
+                    .borrow_mut()
+                    .set_connection(fail_pointer(), Some(&self.root));
+                queue.push_back(child);
+            }
+        }
+
+        while let Some(state) = queue.pop_front() {
+            let fail = state.borrow().get_pointer(fail_pointer()).unwrap();
+
+            for byte in 0..=u8::MAX {
+                let child = match state.borrow().get_pointer(transition_pointer(byte)) {
+                    Some(child) => child,
+                    None => continue,
+                };
+
+                let child_fail = self.next_state(&fail, byte);
+                child
+                    .borrow_mut()
+                    .set_connection(fail_pointer(), Some(&child_fail));
+
+                // Patterns ending on the failure state end on the child too
+                let inherited = child_fail
+                    .borrow()
+                    .read_data()
+                    .as_ref()
+                    .unwrap()
+                    .outputs
+                    .clone();
+                child
+                    .borrow_mut()
+                    .data_mut()
+                    .unwrap()
+                    .outputs
+                    .extend(inherited);
+
+                queue.push_back(child);
+            }
+        }
+    }
+
+    // Follow the transition for a byte, falling back through the failure links when it doesn't exist
+    fn next_state(&self, state: &Shared<Vertex<State>>, byte: u8) -> Shared<Vertex<State>> {
+        let mut current = state.clone();
+
+        loop {
+            if let Some(next) = current.borrow().get_pointer(transition_pointer(byte)) {
+                return next;
+            }
+            if Ptr::ptr_eq(&current, &self.root) {
+                return current;
+            }
+
+            let fail = current.borrow().get_pointer(fail_pointer()).unwrap();
+            current = fail;
+        }
+    }
+
+    /// Get the number of patterns of the automaton
+    /// # Returns
+    /// The number of patterns, including the ignored empty ones
+    /// # Example
+    /// ```
+    /// use data_structures::text::aho_corasick::AhoCorasick;
+    ///
+    /// let automaton = AhoCorasick::new(["a", "b", "c"]);
+    /// assert_eq!(automaton.pattern_count(), 3);
+    /// ```
+    pub fn pattern_count(&self) -> usize {
+        self.pattern_lengths.len()
+    }
+
+    /// Get an iterator over every occurrence of the patterns in a haystack
+    /// # Arguments
+    /// * `haystack`: The text (or bytes) to be scanned
+    /// # Returns
+    /// A lazy iterator of matches, ordered by end position. Matches ending at the same position are ordered from the longest to the shortest.
+    /// # Example
+    /// ```
+    /// use data_structures::text::aho_corasick::AhoCorasick;
+    ///
+    /// let automaton = AhoCorasick::new(["aa"]);
+    /// assert_eq!(automaton.find_iter("aaaa").count(), 3);
+    /// ```
+    pub fn find_iter<'a, 'h, H>(&'a self, haystack: &'h H) -> Matches<'a, 'h>
+    where
+        H: AsRef<[u8]> + ?Sized,
+    {
+        Matches {
+            automaton: self,
+            haystack: haystack.as_ref(),
+            position: 0,
+            state: self.root.clone(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Check if any pattern occurs in a haystack
+    /// # Arguments
+    /// * `haystack`: The text (or bytes) to be scanned
+    /// # Returns
+    /// True if at least one pattern occurs, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::text::aho_corasick::AhoCorasick;
+    ///
+    /// let automaton = AhoCorasick::new(["cat", "dog"]);
+    ///
+    /// assert!(automaton.is_match("hotdog"));
+    /// assert!(!automaton.is_match("bird"));
+    /// ```
+    pub fn is_match<H>(&self, haystack: &H) -> bool
+    where
+        H: AsRef<[u8]> + ?Sized,
+    {
+        self.find_iter(haystack).next().is_some()
+    }
+}
+
+impl Default for AhoCorasick {
+    /// An automaton without patterns, which never matches
+    fn default() -> Self {
+        Self::new(Vec::<&[u8]>::new())
+    }
+}
+
+impl Drop for AhoCorasick {
+    fn drop(&mut self) {
+        // Failure links form reference cycles, so every vertex is unlinked explicitly
+        for state in self.states.drain(..) {
+            state.borrow_mut().clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Naive search used as reference
+    fn naive_matches(patterns: &[&str], haystack: &str) -> Vec<Match> {
+        let mut matches = Vec::new();
+        for end in 1..=haystack.len() {
+            let mut ending: Vec<Match> = patterns
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| !p.is_empty() && p.len() <= end)
+                .filter(|(_, p)| &haystack.as_bytes()[end - p.len()..end] == p.as_bytes())
+                .map(|(pattern, p)| Match {
+                    pattern,
+                    start: end - p.len(),
+                    end,
+                })
+                .collect();
+            ending.sort_by_key(|m| m.start);
+            matches.extend(ending);
+        }
+        matches
+    }
+
+    #[test]
+    fn test_aho_corasick_against_naive_search() {
+        let patterns = ["a", "ab", "bab", "bc", "bca", "c", "caa", ""];
+        let haystack = "abccab bcaabcaa cbabcab";
+
+        let automaton = AhoCorasick::new(patterns);
+        let found: Vec<Match> = automaton.find_iter(haystack).collect();
+
+        assert_eq!(found, naive_matches(&patterns, haystack));
+    }
+
+    #[test]
+    fn test_aho_corasick_bytes_and_no_match() {
+        let automaton = AhoCorasick::new([vec![0u8, 255], vec![255, 255]]);
+        let found: Vec<Match> = automaton.find_iter(&[1u8, 0, 255, 255, 3][..]).collect();
+
+        assert_eq!(
+            found,
+            vec![
+                Match {
+                    pattern: 0,
+                    start: 1,
+                    end: 3
+                },
+                Match {
+                    pattern: 1,
+                    start: 2,
+                    end: 4
+                }
+            ]
+        );
+
+        let empty = AhoCorasick::new(Vec::<&str>::new());
+        assert!(!empty.is_match("anything"));
+    }
+
+    #[test]
+    fn test_aho_corasick_memory_leak() {
+        let automaton = AhoCorasick::new(["abc", "bcd", "cde"]);
+        let states: Vec<_> = automaton.states.iter().map(Ptr::downgrade).collect();
+
+        drop(automaton);
+
+        for state in states {
+            assert!(state.upgrade().is_none());
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! This module implements a trie mapping byte-string keys to values, with longest-prefix matching.
+//! Keys are any `&[u8]`, not just strings, so the map fits binary protocols, IP prefixes written as bytes, or token
+//! vocabularies. `get_longest_prefix` finds the longest key that is a prefix of the input, the lookup behind routing
+//! tables and greedy tokenizers.
+//!
+//! # Performance
+//! - O(k) for `insert`, `get`, `contains_key`, `remove` and `get_longest_prefix`, where k is the length of the key
+//!   or input, independent of the number of keys
+//!
+//! # Implementation Details
+//! - Each trie node is a `Vertex` holding the value of the key ending there, if any, and its number of children.
+//! - Transitions are stored in custom pointers named after the byte, as in the `AhoCorasick` automaton.
+//! - `remove` prunes the nodes left without a value and without children, so the trie never keeps dead branches.
+//! - The empty key is a valid key, stored on the root.
+//!
+//! # Usage
+//! ```
+//! use data_structures::text::bytes_trie_map::BytesTrieMap;
+//!
+//! let mut routes = BytesTrieMap::new();
+//! routes.insert(&[10], "internal");
+//! routes.insert(&[10, 1], "lab");
+//! routes.insert(&[], "default");
+//!
+//! assert_eq!(routes.get_longest_prefix(&[10, 1, 7, 3]), Some((2, "lab")));
+//! assert_eq!(routes.get_longest_prefix(&[10, 2, 0, 1]), Some((1, "internal")));
+//! assert_eq!(routes.get_longest_prefix(&[192, 168, 0, 1]), Some((0, "default")));
+//! ```
+//!
+use std::fmt;
+
+use crate::linked_list::shared::Shared;
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+// Name of the pointer holding the transition for the given byte
+fn transition_pointer(byte: u8) -> PointerName {
+    PointerName::Custom(byte.to_string())
+}
+
+/// Data of a node of the trie
+struct Node<V> {
+    value: Option<V>, // value of the key ending on this node
+    children: usize,
+}
+
+/// Struct representing a map from byte strings to values, stored in a trie
+///
+pub struct BytesTrieMap<V> {
+    root: Shared<Vertex<Node<V>>>,
+    len: usize,
+}
+
+impl<V> BytesTrieMap<V> {
+    /// Create an empty map
+    ///
+    /// # Returns
+    /// A new BytesTrieMap instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::text::bytes_trie_map::BytesTrieMap;
+    ///
+    /// let map: BytesTrieMap<u32> = BytesTrieMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        BytesTrieMap {
+            root: Vertex::new(Node {
+                value: None,
+                children: 0,
+            }),
+            len: 0,
+        }
+    }
+
+    /// Get the number of keys
+    /// # Returns
+    /// The number of keys in the map
+    /// # Example
+    /// ```
+    /// use data_structures::text::bytes_trie_map::BytesTrieMap;
+    ///
+    /// let mut map = BytesTrieMap::new();
+    /// map.insert(b"a", 1);
+    /// map.insert(b"ab", 2);
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the map is empty
+    /// # Returns
+    /// True if there are no keys
+    /// # Example
+    /// ```
+    /// use data_structures::text::bytes_trie_map::BytesTrieMap;
+    ///
+    /// let mut map = BytesTrieMap::new();
+    /// map.insert(b"key", ());
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert a key, or replace its value
+    /// # Arguments
+    /// * `key`: The bytes of the key
+    /// * `value`: The value to store
+    /// # Returns
+    /// The previous value of the key, if it was present
+    /// # Example
+    /// ```
+    /// use data_structures::text::bytes_trie_map::BytesTrieMap;
+    ///
+    /// let mut map = BytesTrieMap::new();
+    /// assert_eq!(map.insert(b"key", 1), None);
+    /// assert_eq!(map.insert(b"key", 2), Some(1));
+    /// ```
+    pub fn insert(&mut self, key: &[u8], value: V) -> Option<V> {
+        let mut current = self.root.clone();
+
+        for &byte in key {
+            let next = current.borrow().get_pointer(transition_pointer(byte));
+            current = match next {
+                Some(next) => next,
+                None => {
+                    let next = Vertex::new(Node {
+                        value: None,
+                        children: 0,
+                    });
+                    let mut vertex = current.borrow_mut();
+                    vertex.set_connection(transition_pointer(byte), Some(&next));
+                    vertex.data_mut().unwrap().children += 1;
+                    drop(vertex);
+                    next
+                }
+            };
+        }
+
+        let old = current
+            .borrow_mut()
+            .data_mut()
+            .unwrap()
+            .value
+            .replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Check if a key is present
+    /// # Arguments
+    /// * `key`: The bytes of the key
+    /// # Returns
+    /// True if the key has a value
+    /// # Example
+    /// ```
+    /// use data_structures::text::bytes_trie_map::BytesTrieMap;
+    ///
+    /// let mut map = BytesTrieMap::new();
+    /// map.insert(b"abc", 1);
+    ///
+    /// assert!(map.contains_key(b"abc"));
+    /// assert!(!map.contains_key(b"ab"));
+    /// ```
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.find(key)
+            .is_some_and(|node| node.borrow().read_data().as_ref().unwrap().value.is_some())
+    }
+
+    /// Get the value of a key
+    /// # Arguments
+    /// * `key`: The bytes of the key
+    /// # Returns
+    /// A copy of the value, or None if the key isn't present
+    /// # Example
+    /// ```
+    /// use data_structures::text::bytes_trie_map::BytesTrieMap;
+    ///
+    /// let mut map = BytesTrieMap::new();
+    /// map.insert(&[0xff, 0x00], "binary");
+    ///
+    /// assert_eq!(map.get(&[0xff, 0x00]), Some("binary"));
+    /// assert_eq!(map.get(&[0xff]), None);
+    /// ```
+    pub fn get(&self, key: &[u8]) -> Option<V>
+    where
+        V: Clone,
+    {
+        let node = self.find(key)?;
+        let value = node.borrow().read_data().as_ref().unwrap().value.clone();
+        value
+    }
+
+    /// Find the longest key that is a prefix of some input
+    /// # Arguments
+    /// * `input`: The bytes to match
+    /// # Returns
+    /// The length of the longest matching key and a copy of its value, or None if no key is a prefix of the input
+    /// # Example
+    /// ```
+    /// use data_structures::text::bytes_trie_map::BytesTrieMap;
+    ///
+    /// let mut tokens = BytesTrieMap::new();
+    /// tokens.insert(b"un", 1);
+    /// tokens.insert(b"under", 2);
+    ///
+    /// assert_eq!(tokens.get_longest_prefix(b"understand"), Some((5, 2)));
+    /// assert_eq!(tokens.get_longest_prefix(b"unless"), Some((2, 1)));
+    /// assert_eq!(tokens.get_longest_prefix(b"over"), None);
+    /// ```
+    pub fn get_longest_prefix(&self, input: &[u8]) -> Option<(usize, V)>
+    where
+        V: Clone,
+    {
+        let mut current = self.root.clone();
+        let mut best = None;
+
+        for (depth, &byte) in input.iter().enumerate() {
+            if let Some(value) = &current.borrow().read_data().as_ref().unwrap().value {
+                best = Some((depth, value.clone()));
+            }
+
+            let next = current.borrow().get_pointer(transition_pointer(byte));
+            match next {
+                Some(next) => current = next,
+                None => return best,
+            }
+        }
+
+        if let Some(value) = &current.borrow().read_data().as_ref().unwrap().value {
+            best = Some((input.len(), value.clone()));
+        }
+        best
+    }
+
+    /// Remove a key
+    /// # Arguments
+    /// * `key`: The bytes of the key
+    /// # Returns
+    /// The value of the key, or None if it wasn't present
+    /// # Example
+    /// ```
+    /// use data_structures::text::bytes_trie_map::BytesTrieMap;
+    ///
+    /// let mut map = BytesTrieMap::new();
+    /// map.insert(b"a", 1);
+    /// map.insert(b"abc", 2);
+    ///
+    /// assert_eq!(map.remove(b"abc"), Some(2));
+    /// assert_eq!(map.remove(b"abc"), None);
+    /// assert_eq!(map.get(b"a"), Some(1));
+    /// ```
+    pub fn remove(&mut self, key: &[u8]) -> Option<V> {
+        // Path from the root to the node of the key
+        let mut path = vec![self.root.clone()];
+        for &byte in key {
+            let next = path
+                .last()
+                .unwrap()
+                .borrow()
+                .get_pointer(transition_pointer(byte))?;
+            path.push(next);
+        }
+
+        let value = path
+            .last()
+            .unwrap()
+            .borrow_mut()
+            .data_mut()
+            .unwrap()
+            .value
+            .take()?;
+        self.len -= 1;
+
+        // Prune the nodes left with no value and no children, from the bottom up
+        for (depth, &byte) in key.iter().enumerate().rev() {
+            let useless = {
+                let node = path[depth + 1].borrow();
+                let node = node.read_data().as_ref().unwrap();
+                node.value.is_none() && node.children == 0
+            };
+            if !useless {
+                break;
+            }
+
+            let mut parent = path[depth].borrow_mut();
+            parent.set_connection(transition_pointer(byte), None);
+            parent.data_mut().unwrap().children -= 1;
+        }
+
+        Some(value)
+    }
+
+    // Node reached by following a key from the root
+    fn find(&self, key: &[u8]) -> Option<Shared<Vertex<Node<V>>>> {
+        let mut current = self.root.clone();
+        for &byte in key {
+            let next = current.borrow().get_pointer(transition_pointer(byte))?;
+            current = next;
+        }
+        Some(current)
+    }
+}
+
+impl<V> Default for BytesTrieMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> fmt::Debug for BytesTrieMap<V> {
+    /// Prints the number of keys, the trie itself being a graph of vertexes
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BytesTrieMap")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_bytes_trie_map_against_hash_map() {
+        let mut trie = BytesTrieMap::new();
+        let mut expected = HashMap::new();
+        let mut rng = Lcg::new(61);
+
+        for round in 0..2000u32 {
+            let seed = rng.next_u64();
+            // Short keys over a small alphabet, so they share prefixes
+            let key: Vec<u8> = (0..(seed >> 60) as usize % 5)
+                .map(|i| (seed >> (8 * i + 16)) as u8 % 3)
+                .collect();
+
+            if (seed >> 40).is_multiple_of(3) {
+                assert_eq!(trie.remove(&key), expected.remove(&key));
+            } else {
+                assert_eq!(
+                    trie.insert(&key, round),
+                    expected.insert(key.clone(), round)
+                );
+            }
+            assert_eq!(trie.len(), expected.len());
+            assert_eq!(trie.get(&key), expected.get(&key).copied());
+
+            let longest = (0..=key.len())
+                .rev()
+                .find_map(|len| expected.get(&key[..len]).map(|&value| (len, value)));
+            assert_eq!(trie.get_longest_prefix(&key), longest);
+        }
+    }
+
+    #[test]
+    fn test_remove_prunes_branches() {
+        let mut trie = BytesTrieMap::new();
+        trie.insert(b"ab", 1);
+        trie.insert(b"abcd", 2);
+
+        trie.remove(b"abcd");
+        // Only the branch of "ab" is left
+        let b = trie.find(b"ab").unwrap();
+        assert_eq!(b.borrow().read_data().as_ref().unwrap().children, 0);
+
+        trie.remove(b"ab");
+        assert_eq!(trie.root.borrow().read_data().as_ref().unwrap().children, 0);
+        assert!(trie.is_empty());
+    }
+}
@@ -0,0 +1,465 @@
+//! This module implements a calendar queue, a priority queue specialized for discrete-event simulation.
+//! Events are scheduled at an absolute time and are distributed into buckets, each one covering a time slice ("day") of the calendar.
+//! Dequeuing walks the calendar day by day, so for dense event streams both operations are O(1) on average instead of the O(log n) of a binary heap.
+//!
+//! # Performance
+//! - O(1) average for `schedule` and `pop_next` when the bucket width matches the event distribution
+//! - O(n) for a resize, which happens only when the number of events doubles or halves
+//!
+//! # Implementation Details
+//! - Each bucket keeps its events sorted by time. Events scheduled for the same time are dequeued in insertion order.
+//! - The bucket of an event is `(time / bucket_width) % bucket_count`, so a bucket holds events from several "years" of the calendar.
+//! - When auto-tuning is enabled, the number of buckets follows the number of events and the bucket width is recomputed
+//!   from the average separation between the next events in the queue.
+//! - Events can't be scheduled before the time of the last dequeued event.
+//!
+//! # Usage
+//! ```
+//! use data_structures::time::calendar_queue::CalendarQueue;
+//!
+//! let mut queue = CalendarQueue::new();
+//!
+//! queue.schedule(30, "c").unwrap();
+//! queue.schedule(10, "a").unwrap();
+//! queue.schedule(20, "b").unwrap();
+//!
+//! assert_eq!(queue.pop_next(), Some((10, "a")));
+//! assert_eq!(queue.pop_next(), Some((20, "b")));
+//! assert_eq!(queue.pop_next(), Some((30, "c")));
+//! assert!(queue.is_empty());
+//! ```
+//!
+use std::collections::VecDeque;
+
+use crate::error::DataStructureError;
+
+// Number of upcoming events used to estimate the bucket width during a resize
+const WIDTH_SAMPLE_SIZE: usize = 25;
+
+/// Struct representing a calendar queue of events ordered by their scheduled time
+/// The queue is made of buckets, each one covering `bucket_width` time units of the calendar.
+/// The cursor of the calendar always points to the bucket that contains the time of the last dequeued event.
+///
+#[derive(Debug)]
+pub struct CalendarQueue<T> {
+    buckets: Vec<VecDeque<(u64, T)>>,
+    bucket_width: u64,
+
+    current_bucket: usize,
+    bucket_top: u64, // exclusive upper bound of the time slice covered by the current bucket
+    last_time: u64,
+
+    size: usize,
+    auto_tune: bool,
+}
+
+impl<T> CalendarQueue<T> {
+    /// Create a new empty CalendarQueue with auto-tuning enabled
+    ///
+    /// # Returns
+    /// A new CalendarQueue instance with two buckets of width 1
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::time::calendar_queue::CalendarQueue;
+    ///
+    /// let queue: CalendarQueue<u32> = CalendarQueue::new();
+    /// assert!(queue.is_empty());
+    /// assert!(queue.auto_tune());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_buckets(2, 1)
+    }
+
+    /// Create a new empty CalendarQueue with the given initial layout
+    /// Zero values are replaced by 1.
+    ///
+    /// # Arguments
+    /// * `bucket_count`: The initial number of buckets
+    /// * `bucket_width`: The initial time slice covered by each bucket
+    ///
+    /// # Returns
+    /// A new CalendarQueue instance with auto-tuning enabled
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::time::calendar_queue::CalendarQueue;
+    ///
+    /// let queue: CalendarQueue<u32> = CalendarQueue::with_buckets(16, 100);
+    /// assert_eq!(queue.bucket_count(), 16);
+    /// assert_eq!(queue.bucket_width(), 100);
+    /// ```
+    pub fn with_buckets(bucket_count: usize, bucket_width: u64) -> Self {
+        let bucket_count = bucket_count.max(1);
+        let bucket_width = bucket_width.max(1);
+
+        CalendarQueue {
+            buckets: (0..bucket_count).map(|_| VecDeque::new()).collect(),
+            bucket_width,
+            current_bucket: 0,
+            bucket_top: bucket_width,
+            last_time: 0,
+            size: 0,
+            auto_tune: true,
+        }
+    }
+
+    /// Get the number of events in the queue
+    /// # Returns
+    /// The number of scheduled events
+    /// # Example
+    /// ```
+    /// use data_structures::time::calendar_queue::CalendarQueue;
+    ///
+    /// let mut queue = CalendarQueue::new();
+    /// queue.schedule(5, ()).unwrap();
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the queue is empty
+    /// # Returns
+    /// True if there is no scheduled event, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::time::calendar_queue::CalendarQueue;
+    ///
+    /// let queue: CalendarQueue<()> = CalendarQueue::new();
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Get the current number of buckets
+    /// # Returns
+    /// The number of buckets of the calendar
+    /// # Example
+    /// ```
+    /// use data_structures::time::calendar_queue::CalendarQueue;
+    ///
+    /// let queue: CalendarQueue<()> = CalendarQueue::new();
+    /// assert_eq!(queue.bucket_count(), 2);
+    /// ```
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Get the current bucket width
+    /// # Returns
+    /// The time slice covered by each bucket
+    /// # Example
+    /// ```
+    /// use data_structures::time::calendar_queue::CalendarQueue;
+    ///
+    /// let queue: CalendarQueue<()> = CalendarQueue::new();
+    /// assert_eq!(queue.bucket_width(), 1);
+    /// ```
+    pub fn bucket_width(&self) -> u64 {
+        self.bucket_width
+    }
+
+    /// Check if the bucket layout is automatically tuned
+    /// # Returns
+    /// True if auto-tuning is enabled, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::time::calendar_queue::CalendarQueue;
+    ///
+    /// let mut queue: CalendarQueue<()> = CalendarQueue::new();
+    /// queue.set_auto_tune(false);
+    /// assert!(!queue.auto_tune());
+    /// ```
+    pub fn auto_tune(&self) -> bool {
+        self.auto_tune
+    }
+
+    /// Enable or disable the auto-tuning of the bucket layout
+    /// When disabled, the number of buckets and the bucket width keep their current values.
+    /// # Arguments
+    /// * `enabled`: True to enable auto-tuning, false to disable it
+    /// # Example
+    /// ```
+    /// use data_structures::time::calendar_queue::CalendarQueue;
+    ///
+    /// let mut queue = CalendarQueue::with_buckets(4, 10);
+    /// queue.set_auto_tune(false);
+    ///
+    /// for i in 0..100 {
+    ///     queue.schedule(i, i).unwrap();
+    /// }
+    ///
+    /// assert_eq!(queue.bucket_count(), 4);
+    /// assert_eq!(queue.bucket_width(), 10);
+    /// ```
+    pub fn set_auto_tune(&mut self, enabled: bool) {
+        self.auto_tune = enabled;
+    }
+
+    /// Get the time of the next event without removing it
+    /// # Returns
+    /// The time of the earliest event, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::time::calendar_queue::CalendarQueue;
+    ///
+    /// let mut queue = CalendarQueue::new();
+    /// queue.schedule(7, 'b').unwrap();
+    /// queue.schedule(3, 'a').unwrap();
+    /// assert_eq!(queue.peek_time(), Some(3));
+    /// ```
+    pub fn peek_time(&self) -> Option<u64> {
+        self.buckets
+            .iter()
+            .filter_map(|bucket| bucket.front().map(|(at, _)| *at))
+            .min()
+    }
+
+    /// Schedule an event at the given time
+    /// # Arguments
+    /// * `at`: The time of the event
+    /// * `event`: The event to be scheduled
+    /// # Returns
+    /// Result<(), DataStructureError>
+    /// Ok if the event was scheduled, Err if `at` is earlier than the time of the last dequeued event
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::time::calendar_queue::CalendarQueue;
+    ///
+    /// let mut queue = CalendarQueue::new();
+    ///
+    /// queue.schedule(10, "a").unwrap();
+    /// queue.pop_next();
+    ///
+    /// assert_eq!(queue.schedule(5, "b"), Err(DataStructureError::EventInPast { at: 5, now: 10 }));
+    /// assert_eq!(queue.schedule(10, "c"), Ok(()));
+    /// ```
+    pub fn schedule(&mut self, at: u64, event: T) -> Result<(), DataStructureError> {
+        if at < self.last_time {
+            return Err(DataStructureError::EventInPast {
+                at,
+                now: self.last_time,
+            });
+        }
+
+        self.enqueue(at, event);
+        self.size += 1;
+
+        if self.auto_tune && self.size > 2 * self.buckets.len() {
+            self.resize(2 * self.buckets.len());
+        }
+
+        Ok(())
+    }
+
+    /// Remove and return the earliest event
+    /// Events scheduled for the same time are returned in the order they were scheduled.
+    /// # Returns
+    /// A tuple with the time and the event, or None if the queue is empty
+    /// # Example
+    /// ```
+    /// use data_structures::time::calendar_queue::CalendarQueue;
+    ///
+    /// let mut queue = CalendarQueue::new();
+    ///
+    /// queue.schedule(4, "second").unwrap();
+    /// queue.schedule(1, "first").unwrap();
+    /// queue.schedule(4, "third").unwrap();
+    ///
+    /// assert_eq!(queue.pop_next(), Some((1, "first")));
+    /// assert_eq!(queue.pop_next(), Some((4, "second")));
+    /// assert_eq!(queue.pop_next(), Some((4, "third")));
+    /// assert_eq!(queue.pop_next(), None);
+    /// ```
+    pub fn pop_next(&mut self) -> Option<(u64, T)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        // Walk the calendar one bucket (day) at a time looking for an event of the current year
+        let mut found = None;
+        for _ in 0..self.buckets.len() {
+            let bucket = &self.buckets[self.current_bucket];
+            if matches!(bucket.front(), Some((at, _)) if *at < self.bucket_top) {
+                found = Some(self.current_bucket);
+                break;
+            }
+
+            self.current_bucket = (self.current_bucket + 1) % self.buckets.len();
+            self.bucket_top = self.bucket_top.saturating_add(self.bucket_width);
+        }
+
+        // If a whole year was empty, jump directly to the bucket holding the earliest event
+        let bucket_index = match found {
+            Some(index) => index,
+            None => {
+                let index = self
+                    .buckets
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, bucket)| bucket.front().map(|(at, _)| (*at, index)))
+                    .min()
+                    .map(|(_, index)| index)
+                    .unwrap();
+
+                let at = self.buckets[index].front().unwrap().0;
+                self.current_bucket = index;
+                self.bucket_top = (at / self.bucket_width)
+                    .saturating_add(1)
+                    .saturating_mul(self.bucket_width);
+                index
+            }
+        };
+
+        let (at, event) = self.buckets[bucket_index].pop_front().unwrap();
+        self.last_time = at;
+        self.size -= 1;
+
+        if self.auto_tune && self.buckets.len() > 2 && self.size < self.buckets.len() / 2 {
+            self.resize(self.buckets.len() / 2);
+        }
+
+        Some((at, event))
+    }
+
+    // Insert an event in its bucket keeping the bucket sorted by time
+    fn enqueue(&mut self, at: u64, event: T) {
+        let index = self.bucket_index(at);
+        let bucket = &mut self.buckets[index];
+
+        // Events with the same time keep their insertion order
+        let position = bucket.partition_point(|(other, _)| *other <= at);
+        bucket.insert(position, (at, event));
+    }
+
+    fn bucket_index(&self, at: u64) -> usize {
+        ((at / self.bucket_width) % self.buckets.len() as u64) as usize
+    }
+
+    // Rebuild the calendar with a new number of buckets and a bucket width estimated from the next events
+    fn resize(&mut self, bucket_count: usize) {
+        let mut events: Vec<(u64, T)> = self.buckets.iter_mut().flat_map(|b| b.drain(..)).collect();
+
+        // Stable sort, so events with the same time keep their insertion order
+        events.sort_by_key(|(at, _)| *at);
+
+        let sample = events.len().min(WIDTH_SAMPLE_SIZE);
+        if sample >= 2 {
+            let average_separation = (events[sample - 1].0 - events[0].0) / (sample as u64 - 1);
+            self.bucket_width = average_separation.saturating_mul(3).max(1);
+        }
+
+        self.buckets = (0..bucket_count).map(|_| VecDeque::new()).collect();
+        self.current_bucket = self.bucket_index(self.last_time);
+        self.bucket_top = (self.last_time / self.bucket_width)
+            .saturating_add(1)
+            .saturating_mul(self.bucket_width);
+
+        for (at, event) in events {
+            let index = self.bucket_index(at);
+            self.buckets[index].push_back((at, event));
+        }
+    }
+}
+
+impl<T> Default for CalendarQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_calendar_queue_order() {
+        let mut queue = CalendarQueue::new();
+        let mut expected = Vec::new();
+        let mut rng = Lcg::new(42);
+
+        for i in 0..1_000 {
+            let at = (rng.next_u64() >> 33) % 10_000;
+            queue.schedule(at, i).unwrap();
+            expected.push((at, i));
+        }
+
+        // Stable sort keeps the scheduling order for equal times
+        expected.sort_by_key(|(at, _)| *at);
+
+        let mut dequeued = Vec::new();
+        while let Some(event) = queue.pop_next() {
+            dequeued.push(event);
+        }
+
+        assert_eq!(dequeued, expected);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_calendar_queue_simulation() {
+        // Hold model: each dequeued event schedules a new one in the future
+        let mut queue = CalendarQueue::new();
+        let mut rng = Lcg::new(7);
+
+        for i in 0..100 {
+            queue.schedule((rng.next_u64() >> 33) % 1_000, i).unwrap();
+        }
+
+        let mut now = 0;
+        for i in 0..10_000 {
+            let (at, _) = queue.pop_next().unwrap();
+            assert!(at >= now);
+            now = at;
+
+            queue
+                .schedule(now + (rng.next_u64() >> 33) % 1_000, i)
+                .unwrap();
+        }
+
+        assert_eq!(queue.len(), 100);
+        assert_eq!(
+            queue.schedule(now - 1, 0),
+            Err(DataStructureError::EventInPast { at: now - 1, now })
+        );
+    }
+
+    #[test]
+    fn test_calendar_queue_auto_tune() {
+        let mut queue = CalendarQueue::new();
+
+        for i in 0..1_000 {
+            queue.schedule(i * 100, i).unwrap();
+        }
+
+        assert!(queue.bucket_count() >= 500);
+        assert!(queue.bucket_width() >= 100);
+
+        while queue.len() > 1 {
+            queue.pop_next();
+        }
+
+        assert!(queue.bucket_count() <= 4);
+        assert_eq!(queue.pop_next(), Some((99_900, 999)));
+    }
+
+    #[test]
+    fn test_calendar_queue_sparse_events() {
+        // Events far apart from each other force the direct search path
+        let mut queue = CalendarQueue::with_buckets(4, 1);
+        queue.set_auto_tune(false);
+
+        queue.schedule(1_000_000, 'c').unwrap();
+        queue.schedule(5, 'a').unwrap();
+        queue.schedule(50_000, 'b').unwrap();
+
+        assert_eq!(queue.pop_next(), Some((5, 'a')));
+        assert_eq!(queue.pop_next(), Some((50_000, 'b')));
+        assert_eq!(queue.pop_next(), Some((1_000_000, 'c')));
+        assert_eq!(queue.pop_next(), None);
+    }
+}
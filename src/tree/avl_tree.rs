@@ -0,0 +1,678 @@
+//! This module implements an AVL tree, the height-balanced binary search tree of Adelson-Velsky and Landis, as an
+//! ordered map. The heights of the two subtrees of every node differ by at most one, which keeps the tree within 1.44
+//! log2(n) levels, lower than the red-black and WAVL trees of this module, at the price of rebalancing all the way up
+//! after a deletion. Besides point lookups, the tree answers the smallest and greatest keys and range queries.
+//!
+//! # Performance
+//! - O(log n) for `insert`, `get`, `remove`, `min` and `max`
+//! - O(log n + k) to iterate over k entries of a range, O(n) for a full iteration
+//!
+//! # Implementation Details
+//! - Nodes are `Vertex` instances handled through `Shared<Vertex<T>>` pointers, using the `Left` and `Right` pointers
+//!   for the children. Each node stores the height of its subtree, a missing child having height 0.
+//! - Insertion and deletion are recursive. On the way back each node updates its height and, if one subtree is two
+//!   levels taller than the other, restores the balance with a single or double rotation.
+//! - A node with two children is removed by moving its successor's entry into it and removing the successor instead.
+//! - The range iterator starts with the path to the first key in the range, and stops at the first key past its end.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::avl_tree::AvlTree;
+//!
+//! let mut tree: AvlTree<u32, &str> = AvlTree::new();
+//! tree.insert(20, "twenty");
+//! tree.insert(10, "ten");
+//! tree.insert(30, "thirty");
+//! tree.insert(25, "twenty-five");
+//!
+//! assert_eq!(tree.get(&25), Some("twenty-five"));
+//! assert_eq!(tree.min(), Some((10, "ten")));
+//! assert_eq!(tree.range(15..=25).map(|(key, _)| key).collect::<Vec<_>>(), vec![20, 25]);
+//!
+//! tree.remove(&20);
+//! assert_eq!(tree.iter().map(|(key, _)| key).collect::<Vec<_>>(), vec![10, 25, 30]);
+//! ```
+//!
+use std::cmp::Ordering;
+use std::fmt;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+use crate::linked_list::shared::Shared;
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+// Data of a node
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    height: i32,
+}
+
+type Link<K, V> = Shared<Vertex<Entry<K, V>>>;
+
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    fn pointer(self) -> PointerName {
+        match self {
+            Side::Left => PointerName::Left,
+            Side::Right => PointerName::Right,
+        }
+    }
+
+    fn opposite(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+/// Struct representing an AVL tree mapping ordered keys to values
+///
+pub struct AvlTree<K, V> {
+    root: Option<Link<K, V>>,
+    size: usize,
+}
+
+/// In-order iterator over the entries of a tree
+pub struct Iter<K, V> {
+    stack: Vec<Link<K, V>>,
+    current: Option<Link<K, V>>,
+}
+
+/// In-order iterator over the entries of a tree whose key is in a range
+pub struct Range<K, V> {
+    iter: Iter<K, V>,
+    end: Bound<K>,
+}
+
+fn child<K, V>(node: &Link<K, V>, side: Side) -> Option<Link<K, V>> {
+    node.borrow().get_pointer(side.pointer())
+}
+
+fn set_child<K, V>(node: &Link<K, V>, side: Side, child: Option<&Link<K, V>>) {
+    node.borrow_mut().set_connection(side.pointer(), child);
+}
+
+fn height<K, V>(node: Option<&Link<K, V>>) -> i32 {
+    node.map_or(0, |node| node.borrow().read_data().as_ref().unwrap().height)
+}
+
+// Recompute the height of a node from its children
+fn update_height<K, V>(node: &Link<K, V>) {
+    let left = height(child(node, Side::Left).as_ref());
+    let right = height(child(node, Side::Right).as_ref());
+    node.borrow_mut().data_mut().unwrap().height = left.max(right) + 1;
+}
+
+// Height of the subtree on one side minus the height of the other one
+fn lean<K, V>(node: &Link<K, V>, side: Side) -> i32 {
+    height(child(node, side).as_ref()) - height(child(node, side.opposite()).as_ref())
+}
+
+fn compare<K: Ord, V>(key: &K, node: &Link<K, V>) -> Ordering {
+    key.cmp(&node.borrow().read_data().as_ref().unwrap().key)
+}
+
+fn read<K: Clone, V: Clone>(node: &Link<K, V>) -> (K, V) {
+    let vertex = node.borrow();
+    let entry = vertex.read_data().as_ref().unwrap();
+    (entry.key.clone(), entry.value.clone())
+}
+
+// Lift the child on one side above the node, returning the new root of the subtree
+fn rotate<K, V>(node: &Link<K, V>, side: Side) -> Link<K, V> {
+    let lifted = child(node, side).unwrap();
+    set_child(node, side, child(&lifted, side.opposite()).as_ref());
+    set_child(&lifted, side.opposite(), Some(node));
+    update_height(node);
+    update_height(&lifted);
+    lifted
+}
+
+// Update the height of a node and rotate it if a subtree became two levels taller, returning the new root
+fn rebalance<K, V>(node: Link<K, V>) -> Link<K, V> {
+    update_height(&node);
+
+    for side in [Side::Left, Side::Right] {
+        if lean(&node, side) > 1 {
+            // An inner grandchild taller than the outer one is lifted first, making it a single rotation
+            let tall = child(&node, side).unwrap();
+            if lean(&tall, side.opposite()) > 0 {
+                let lifted = rotate(&tall, side.opposite());
+                set_child(&node, side, Some(&lifted));
+            }
+            return rotate(&node, side);
+        }
+    }
+    node
+}
+
+impl<K: Clone, V: Clone> Iterator for Iter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.current.take() {
+            self.current = child(&node, Side::Left);
+            self.stack.push(node);
+        }
+
+        let node = self.stack.pop()?;
+        self.current = child(&node, Side::Right);
+        Some(read(&node))
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Iterator for Range<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.iter.next()?;
+        let in_range = match &self.end {
+            Bound::Included(end) => key <= *end,
+            Bound::Excluded(end) => key < *end,
+            Bound::Unbounded => true,
+        };
+        if !in_range {
+            // Every following key is past the end too
+            self.iter.stack.clear();
+            self.iter.current = None;
+            return None;
+        }
+        Some((key, value))
+    }
+}
+
+impl<K: Ord, V> AvlTree<K, V> {
+    /// Create a new empty tree
+    ///
+    /// # Returns
+    /// A new AvlTree instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    ///
+    /// let tree: AvlTree<String, u8> = AvlTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        AvlTree {
+            root: None,
+            size: 0,
+        }
+    }
+
+    /// Get the number of entries in the tree
+    /// # Returns
+    /// The number of keys stored
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    ///
+    /// let tree: AvlTree<u8, ()> = [(1, ()), (2, ()), (1, ())].into_iter().collect();
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the tree is empty
+    /// # Returns
+    /// True if the tree has no entries
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    ///
+    /// let mut tree = AvlTree::default();
+    /// tree.insert(1, 1);
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Insert a key with its value, replacing the value if the key is already present
+    /// # Arguments
+    /// * `key`: The key of the entry
+    /// * `value`: The value of the entry
+    /// # Returns
+    /// The previous value of the key, or None if it wasn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    ///
+    /// let mut tree = AvlTree::new();
+    /// assert_eq!(tree.insert('k', 1), None);
+    /// assert_eq!(tree.insert('k', 2), Some(1));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut previous = None;
+        self.root = Some(Self::insert_at(
+            self.root.clone(),
+            key,
+            value,
+            &mut previous,
+        ));
+
+        if previous.is_none() {
+            self.size += 1;
+        }
+        previous
+    }
+
+    /// Check if a key is in the tree
+    /// # Arguments
+    /// * `key`: The key to look for
+    /// # Returns
+    /// True if the key is present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    ///
+    /// let mut tree = AvlTree::new();
+    /// tree.insert("a", ());
+    /// assert!(tree.contains_key(&"a"));
+    /// assert!(!tree.contains_key(&"b"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Get the value of a key
+    /// # Arguments
+    /// * `key`: The key to look for
+    /// # Returns
+    /// A clone of the value, or None if the key isn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    ///
+    /// let mut tree = AvlTree::new();
+    /// tree.insert(3, "three");
+    /// assert_eq!(tree.get(&3), Some("three"));
+    /// assert_eq!(tree.get(&4), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.find(key)
+            .map(|node| node.borrow().read_data().as_ref().unwrap().value.clone())
+    }
+
+    /// Remove a key from the tree
+    /// # Arguments
+    /// * `key`: The key to remove
+    /// # Returns
+    /// The value of the key, or None if it wasn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    ///
+    /// let mut tree: AvlTree<i32, i32> = (0..5).map(|key| (key, -key)).collect();
+    ///
+    /// assert_eq!(tree.remove(&2), Some(-2));
+    /// assert_eq!(tree.remove(&2), None);
+    /// assert_eq!(tree.len(), 4);
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let root = self.root.take()?;
+
+        let mut removed = None;
+        self.root = Self::remove_at(root, key, &mut removed);
+
+        if removed.is_some() {
+            self.size -= 1;
+        }
+        removed
+    }
+
+    /// Get the entry with the smallest key
+    /// # Returns
+    /// A clone of the key and value, or None if the tree is empty
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    ///
+    /// let tree: AvlTree<i32, char> = [(5, 'e'), (-1, 'z'), (3, 'c')].into_iter().collect();
+    /// assert_eq!(tree.min(), Some((-1, 'z')));
+    /// ```
+    pub fn min(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.outermost(Side::Left)
+    }
+
+    /// Get the entry with the greatest key
+    /// # Returns
+    /// A clone of the key and value, or None if the tree is empty
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    ///
+    /// let tree: AvlTree<i32, char> = [(5, 'e'), (-1, 'z'), (3, 'c')].into_iter().collect();
+    /// assert_eq!(tree.max(), Some((5, 'e')));
+    /// ```
+    pub fn max(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.outermost(Side::Right)
+    }
+
+    /// Get the height of the tree, which is the number of nodes in the longest path from the root to a leaf
+    /// # Returns
+    /// The height of the tree, 0 for an empty tree
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    ///
+    /// // Sorted insertions would make a plain binary search tree a list of 7 levels
+    /// let tree: AvlTree<u32, ()> = (0..7).map(|key| (key, ())).collect();
+    /// assert_eq!(tree.height(), 3);
+    /// ```
+    pub fn height(&self) -> usize {
+        height(self.root.as_ref()) as usize
+    }
+
+    /// Get an in-order iterator over the entries of the tree
+    /// # Returns
+    /// A lazy iterator yielding clones of the keys and values, by increasing key
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    ///
+    /// let tree: AvlTree<u8, char> = [(2, 'b'), (1, 'a')].into_iter().collect();
+    /// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![(1, 'a'), (2, 'b')]);
+    /// ```
+    pub fn iter(&self) -> Iter<K, V> {
+        Iter {
+            stack: Vec::new(),
+            current: self.root.clone(),
+        }
+    }
+
+    /// Get an in-order iterator over the entries whose key is in a range
+    /// # Arguments
+    /// * `range`: The range of keys, such as `a..b`, `a..` or `(Bound::Excluded(a), Bound::Included(b))`
+    /// # Returns
+    /// A lazy iterator yielding clones of the keys and values, by increasing key
+    /// # Example
+    /// ```
+    /// use data_structures::tree::avl_tree::AvlTree;
+    /// use std::ops::Bound;
+    ///
+    /// let tree: AvlTree<u32, u32> = (0..100).map(|key| (key, key * key)).collect();
+    ///
+    /// assert_eq!(tree.range(10..13).collect::<Vec<_>>(), vec![(10, 100), (11, 121), (12, 144)]);
+    /// assert_eq!(tree.range((Bound::Excluded(97), Bound::Unbounded)).count(), 2);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<K, V>
+    where
+        K: Clone,
+    {
+        // Stack the path to the first key in the range, as the in-order iterator would have it
+        let mut stack = Vec::new();
+        let mut current = self.root.clone();
+        while let Some(node) = current {
+            let after_start = match range.start_bound() {
+                Bound::Included(start) => compare(start, &node) != Ordering::Greater,
+                Bound::Excluded(start) => compare(start, &node) == Ordering::Less,
+                Bound::Unbounded => true,
+            };
+            if after_start {
+                current = child(&node, Side::Left);
+                stack.push(node);
+            } else {
+                current = child(&node, Side::Right);
+            }
+        }
+
+        Range {
+            iter: Iter {
+                stack,
+                current: None,
+            },
+            end: range.end_bound().cloned(),
+        }
+    }
+
+    // Find the node holding a key
+    fn find(&self, key: &K) -> Option<Link<K, V>> {
+        let mut current = self.root.clone();
+
+        while let Some(node) = current {
+            current = match compare(key, &node) {
+                Ordering::Less => child(&node, Side::Left),
+                Ordering::Greater => child(&node, Side::Right),
+                Ordering::Equal => return Some(node),
+            };
+        }
+
+        None
+    }
+
+    // Read the entry at the end of the path following one side from the root
+    fn outermost(&self, side: Side) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut node = self.root.clone()?;
+        while let Some(next) = child(&node, side) {
+            node = next;
+        }
+        Some(read(&node))
+    }
+
+    fn insert_at(
+        node: Option<Link<K, V>>,
+        key: K,
+        value: V,
+        previous: &mut Option<V>,
+    ) -> Link<K, V> {
+        let Some(node) = node else {
+            return Vertex::new(Entry {
+                key,
+                value,
+                height: 1,
+            });
+        };
+
+        let side = match compare(&key, &node) {
+            Ordering::Less => Side::Left,
+            Ordering::Greater => Side::Right,
+            Ordering::Equal => {
+                *previous = Some(mem::replace(
+                    &mut node.borrow_mut().data_mut().unwrap().value,
+                    value,
+                ));
+                return node;
+            }
+        };
+
+        let subtree = Self::insert_at(child(&node, side), key, value, previous);
+        set_child(&node, side, Some(&subtree));
+
+        rebalance(node)
+    }
+
+    // Remove the smallest entry of a subtree, returning the new root of the subtree
+    fn remove_min_at(node: Link<K, V>, removed: &mut Option<(K, V)>) -> Option<Link<K, V>> {
+        let Some(left) = child(&node, Side::Left) else {
+            let right = child(&node, Side::Right);
+            let entry = node.borrow_mut().clear().unwrap();
+            *removed = Some((entry.key, entry.value));
+            return right;
+        };
+
+        let subtree = Self::remove_min_at(left, removed);
+        set_child(&node, Side::Left, subtree.as_ref());
+
+        Some(rebalance(node))
+    }
+
+    fn remove_at(node: Link<K, V>, key: &K, removed: &mut Option<V>) -> Option<Link<K, V>> {
+        let side = match compare(key, &node) {
+            Ordering::Less => Side::Left,
+            Ordering::Greater => Side::Right,
+            Ordering::Equal => return Self::remove_node(node, removed),
+        };
+
+        let Some(next) = child(&node, side) else {
+            return Some(node);
+        };
+
+        let subtree = Self::remove_at(next, key, removed);
+        set_child(&node, side, subtree.as_ref());
+
+        Some(rebalance(node))
+    }
+
+    // Remove the entry of a node, returning the new root of its subtree
+    fn remove_node(node: Link<K, V>, removed: &mut Option<V>) -> Option<Link<K, V>> {
+        let left = child(&node, Side::Left);
+        let right = child(&node, Side::Right);
+
+        let Some(right) = right.filter(|_| left.is_some()) else {
+            // A node with at most one child is replaced by it
+            let replacement = left.or(child(&node, Side::Right));
+            *removed = Some(node.borrow_mut().clear().unwrap().value);
+            return replacement;
+        };
+
+        let mut successor = None;
+        let subtree = Self::remove_min_at(right, &mut successor);
+        set_child(&node, Side::Right, subtree.as_ref());
+
+        let (successor_key, successor_value) = successor.unwrap();
+        {
+            let mut vertex = node.borrow_mut();
+            let entry = vertex.data_mut().unwrap();
+            entry.key = successor_key;
+            *removed = Some(mem::replace(&mut entry.value, successor_value));
+        }
+
+        Some(rebalance(node))
+    }
+}
+
+impl<K: Ord, V> Default for AvlTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for AvlTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = AvlTree::new();
+        for (key, value) in iter {
+            tree.insert(key, value);
+        }
+        tree
+    }
+}
+
+impl<K: Ord + Clone + fmt::Debug, V: Clone + fmt::Debug> fmt::Debug for AvlTree<K, V> {
+    /// Prints the entries as a map, by increasing key, instead of the web of vertex pointers
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::BTreeMap;
+
+    // Check the stored heights, the balance and the key order below a node, returning its height
+    fn check<K: Ord, V>(node: &Link<K, V>) -> i32 {
+        let mut heights = [0; 2];
+
+        for (index, side) in [Side::Left, Side::Right].into_iter().enumerate() {
+            if let Some(child) = child(node, side) {
+                let order = compare(&child.borrow().read_data().as_ref().unwrap().key, node);
+                let expected = match side {
+                    Side::Left => Ordering::Less,
+                    Side::Right => Ordering::Greater,
+                };
+                assert_eq!(order, expected);
+                heights[index] = check(&child);
+            }
+        }
+
+        assert!((heights[0] - heights[1]).abs() <= 1, "unbalanced node");
+        assert_eq!(height(Some(node)), heights[0].max(heights[1]) + 1);
+        height(Some(node))
+    }
+
+    #[test]
+    fn test_avl_tree_against_btree_map() {
+        let mut tree = AvlTree::new();
+        let mut reference = BTreeMap::new();
+        let mut rng = Lcg::new(29);
+
+        for _ in 0..3000 {
+            let seed = rng.next_u64();
+            let key = (seed >> 33) % 300;
+
+            if (seed >> 20).is_multiple_of(3) {
+                assert_eq!(tree.remove(&key), reference.remove(&key));
+            } else {
+                assert_eq!(tree.insert(key, seed), reference.insert(key, seed));
+            }
+
+            if let Some(root) = &tree.root {
+                check(root);
+            }
+            assert_eq!(tree.len(), reference.len());
+            assert_eq!(
+                tree.min(),
+                reference.first_key_value().map(|(k, v)| (*k, *v))
+            );
+            assert_eq!(
+                tree.max(),
+                reference.last_key_value().map(|(k, v)| (*k, *v))
+            );
+        }
+
+        assert!(tree.iter().eq(reference.clone()));
+        for (start, end) in [(0, 300), (50, 60), (120, 121), (299, 400), (70, 30)] {
+            let expected = reference.range(start..end.max(start));
+            assert!(tree.range(start..end).eq(expected.map(|(k, v)| (*k, *v))));
+        }
+        assert!(tree
+            .range((Bound::Excluded(100), Bound::Included(200)))
+            .eq(reference
+                .range((Bound::Excluded(100), Bound::Included(200)))
+                .map(|(k, v)| (*k, *v))));
+
+        for key in reference.keys() {
+            tree.remove(key);
+        }
+        assert!(tree.is_empty());
+        assert!(tree.root.is_none());
+    }
+
+    #[test]
+    fn test_avl_height_bound() {
+        // An AVL tree of n nodes has a height below 1.44 log2(n + 2), even after deletions
+        let mut tree: AvlTree<u32, ()> = (0..4096).map(|key| (key, ())).collect();
+        for key in (0..4096).step_by(3) {
+            tree.remove(&key);
+        }
+        let bound = 1.44 * ((tree.len() + 2) as f64).log2();
+        assert!(tree.height() as f64 <= bound);
+        check(tree.root.as_ref().unwrap());
+    }
+}
@@ -0,0 +1,613 @@
+//! This module implements a binary tree using linked list vertexes as nodes.
+//! Each node points to its left and right children, and the tree can be walked with lazy iterators in the four classic orders:
+//! in-order, pre-order, post-order and level-order.
+//!
+//! # Performance
+//! - O(1) to add a child to a node
+//! - O(n) to build a balanced tree from a sorted slice
+//! - O(1) per step for every traversal iterator, using O(h) memory (O(w) for level-order, where w is the width of the tree)
+//!
+//! # Implementation Details
+//! - Nodes are `Vertex` instances handled through `Shared<Vertex<T>>` pointers, using the `Left` and `Right` pointers for the children.
+//! - The iterators keep their own stack (or queue) of vertex pointers, so they don't borrow the tree and yield clones of the data.
+//! - The tree unlinks its nodes iteratively when dropped, so degenerate (list-like) trees don't overflow the stack.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::binary_tree::BinaryTree;
+//!
+//! let tree = BinaryTree::from_sorted_slice(&[1, 2, 3, 4, 5, 6, 7]);
+//!
+//! assert_eq!(tree.iter_inorder().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6, 7]);
+//! assert_eq!(tree.iter_preorder().collect::<Vec<_>>(), vec![4, 2, 1, 3, 6, 5, 7]);
+//! assert_eq!(tree.iter_postorder().collect::<Vec<_>>(), vec![1, 3, 2, 5, 7, 6, 4]);
+//! assert_eq!(tree.iter_level_order().collect::<Vec<_>>(), vec![4, 2, 6, 1, 3, 5, 7]);
+//! ```
+//!
+use std::collections::VecDeque;
+
+use crate::error::DataStructureError;
+use crate::linked_list::shared::Shared;
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+/// Struct representing a binary tree made of vertexes
+/// Nodes are identified by their vertex pointers, which are returned when they are added to the tree.
+///
+#[derive(Debug)]
+pub struct BinaryTree<T> {
+    root: Option<Shared<Vertex<T>>>,
+
+    size: usize,
+}
+
+/// In-order iterator: left subtree, node, right subtree
+pub struct InOrder<T> {
+    stack: Vec<Shared<Vertex<T>>>,
+    current: Option<Shared<Vertex<T>>>,
+}
+
+/// Pre-order iterator: node, left subtree, right subtree
+pub struct PreOrder<T> {
+    stack: Vec<Shared<Vertex<T>>>,
+}
+
+/// Post-order iterator: left subtree, right subtree, node
+pub struct PostOrder<T> {
+    stack: Vec<(Shared<Vertex<T>>, bool)>, // the flag tells if the children were already pushed
+}
+
+/// Level-order iterator: nodes by increasing depth, from left to right
+pub struct LevelOrder<T> {
+    queue: VecDeque<Shared<Vertex<T>>>,
+}
+
+// Clone the data of a vertex
+fn data_of<T: Clone>(vertex: &Shared<Vertex<T>>) -> T {
+    vertex.borrow().read_data().clone().unwrap()
+}
+
+impl<T: Clone> Iterator for InOrder<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Go down the left side, keeping the path in the stack
+        while let Some(node) = self.current.take() {
+            self.current = node.borrow().get_pointer(PointerName::Left);
+            self.stack.push(node);
+        }
+
+        let node = self.stack.pop()?;
+        self.current = node.borrow().get_pointer(PointerName::Right);
+
+        Some(data_of(&node))
+    }
+}
+
+impl<T: Clone> Iterator for PreOrder<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+
+        // The right child is pushed first so the left one is visited first
+        self.stack
+            .extend(node.borrow().get_pointer(PointerName::Right));
+        self.stack
+            .extend(node.borrow().get_pointer(PointerName::Left));
+
+        Some(data_of(&node))
+    }
+}
+
+impl<T: Clone> Iterator for PostOrder<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+
+            if expanded {
+                return Some(data_of(&node));
+            }
+
+            let left = node.borrow().get_pointer(PointerName::Left);
+            let right = node.borrow().get_pointer(PointerName::Right);
+
+            self.stack.push((node, true));
+            self.stack.extend(right.map(|child| (child, false)));
+            self.stack.extend(left.map(|child| (child, false)));
+        }
+    }
+}
+
+impl<T: Clone> Iterator for LevelOrder<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+
+        self.queue
+            .extend(node.borrow().get_pointer(PointerName::Left));
+        self.queue
+            .extend(node.borrow().get_pointer(PointerName::Right));
+
+        Some(data_of(&node))
+    }
+}
+
+impl<T> BinaryTree<T> {
+    /// Create a new empty tree
+    ///
+    /// # Returns
+    /// A new BinaryTree instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree: BinaryTree<i32> = BinaryTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        BinaryTree {
+            root: None,
+            size: 0,
+        }
+    }
+
+    /// Build a balanced tree from a sorted slice
+    /// The middle element of each range becomes the root of the subtree, so the in-order traversal returns the slice.
+    ///
+    /// # Arguments
+    /// * `values`: The sorted values of the tree
+    ///
+    /// # Returns
+    /// A new balanced BinaryTree instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from_sorted_slice(&[1, 2, 3]);
+    ///
+    /// assert_eq!(tree.len(), 3);
+    /// assert_eq!(tree.height(), 2);
+    /// assert_eq!(tree.root().unwrap().borrow().read_data().unwrap(), 2);
+    /// ```
+    pub fn from_sorted_slice(values: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        BinaryTree {
+            root: Self::build_balanced(values),
+            size: values.len(),
+        }
+    }
+
+    fn build_balanced(values: &[T]) -> Option<Shared<Vertex<T>>>
+    where
+        T: Clone,
+    {
+        if values.is_empty() {
+            return None;
+        }
+
+        let middle = values.len() / 2;
+        let node = Vertex::new(values[middle].clone());
+
+        let left = Self::build_balanced(&values[..middle]);
+        let right = Self::build_balanced(&values[middle + 1..]);
+
+        node.borrow_mut()
+            .set_connection(PointerName::Left, left.as_ref());
+        node.borrow_mut()
+            .set_connection(PointerName::Right, right.as_ref());
+
+        Some(node)
+    }
+
+    /// Build a tree from a root value and two subtrees
+    /// The subtrees are consumed and become the left and right children of the new root.
+    ///
+    /// # Arguments
+    /// * `data`: The data of the new root
+    /// * `left`: The left subtree, which may be empty
+    /// * `right`: The right subtree, which may be empty
+    ///
+    /// # Returns
+    /// A new BinaryTree instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let left = BinaryTree::from_sorted_slice(&[1]);
+    /// let right = BinaryTree::from_sorted_slice(&[3, 4]);
+    ///
+    /// let tree = BinaryTree::join(2, left, right);
+    ///
+    /// assert_eq!(tree.len(), 4);
+    /// assert_eq!(tree.iter_inorder().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    /// ```
+    pub fn join(data: T, mut left: BinaryTree<T>, mut right: BinaryTree<T>) -> Self {
+        let root = Vertex::new(data);
+
+        // The nodes move to the new tree, so the subtrees must not clear them when dropped
+        root.borrow_mut()
+            .set_connection(PointerName::Left, left.root.take().as_ref());
+        root.borrow_mut()
+            .set_connection(PointerName::Right, right.root.take().as_ref());
+
+        BinaryTree {
+            root: Some(root),
+            size: 1 + left.size + right.size,
+        }
+    }
+
+    /// Get the number of nodes in the tree
+    /// # Returns
+    /// The number of nodes
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.set_root(1).unwrap();
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the tree is empty
+    /// # Returns
+    /// True if the tree has no nodes, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree: BinaryTree<i32> = BinaryTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Get a pointer to the root node
+    /// # Returns
+    /// The vertex pointer of the root, or None if the tree is empty
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// assert!(tree.root().is_none());
+    ///
+    /// tree.set_root(1).unwrap();
+    /// assert_eq!(tree.root().unwrap().borrow().read_data().unwrap(), 1);
+    /// ```
+    pub fn root(&self) -> Option<Shared<Vertex<T>>> {
+        self.root.clone()
+    }
+
+    /// Add the root node to an empty tree
+    /// # Arguments
+    /// * `data`: The data of the root node
+    /// # Returns
+    /// Result<Shared<Vertex<T>>, DataStructureError>
+    /// Ok with the pointer to the root, Err if the tree already has a root
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    ///
+    /// assert!(tree.set_root(1).is_ok());
+    /// assert_eq!(tree.set_root(2).unwrap_err(), DataStructureError::RootOccupied);
+    /// ```
+    pub fn set_root(&mut self, data: T) -> Result<Shared<Vertex<T>>, DataStructureError> {
+        if self.root.is_some() {
+            return Err(DataStructureError::RootOccupied);
+        }
+
+        let node = Vertex::new(data);
+        self.root = Some(node.clone());
+        self.size = 1;
+
+        Ok(node)
+    }
+
+    /// Add a left child to a node
+    /// # Arguments
+    /// * `parent`: The node that receives the child. It must belong to this tree.
+    /// * `data`: The data of the new node
+    /// # Returns
+    /// Result<Shared<Vertex<T>>, DataStructureError>
+    /// Ok with the pointer to the new node, Err if the node already has a left child
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// let root = tree.set_root(2).unwrap();
+    ///
+    /// tree.insert_left(&root, 1).unwrap();
+    /// assert_eq!(tree.insert_left(&root, 0).unwrap_err(), DataStructureError::ChildOccupied);
+    /// ```
+    pub fn insert_left(
+        &mut self,
+        parent: &Shared<Vertex<T>>,
+        data: T,
+    ) -> Result<Shared<Vertex<T>>, DataStructureError> {
+        if self.left(parent).is_some() {
+            return Err(DataStructureError::ChildOccupied);
+        }
+
+        Ok(self.link_child(parent, PointerName::Left, data))
+    }
+
+    /// Add a right child to a node
+    /// # Arguments
+    /// * `parent`: The node that receives the child. It must belong to this tree.
+    /// * `data`: The data of the new node
+    /// # Returns
+    /// Result<Shared<Vertex<T>>, DataStructureError>
+    /// Ok with the pointer to the new node, Err if the node already has a right child
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// let root = tree.set_root(1).unwrap();
+    ///
+    /// tree.insert_right(&root, 2).unwrap();
+    /// assert_eq!(tree.iter_inorder().collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    pub fn insert_right(
+        &mut self,
+        parent: &Shared<Vertex<T>>,
+        data: T,
+    ) -> Result<Shared<Vertex<T>>, DataStructureError> {
+        if self.right(parent).is_some() {
+            return Err(DataStructureError::ChildOccupied);
+        }
+
+        Ok(self.link_child(parent, PointerName::Right, data))
+    }
+
+    // Link a new node as a child of the parent
+    fn link_child(
+        &mut self,
+        parent: &Shared<Vertex<T>>,
+        side: PointerName,
+        data: T,
+    ) -> Shared<Vertex<T>> {
+        let node = Vertex::new(data);
+        parent.borrow_mut().set_connection(side, Some(&node));
+        self.size += 1;
+
+        node
+    }
+
+    /// Get the left child of a node
+    /// # Arguments
+    /// * `node`: The parent node
+    /// # Returns
+    /// The vertex pointer of the left child, or None if there is no left child
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from_sorted_slice(&[1, 2]);
+    /// let root = tree.root().unwrap();
+    ///
+    /// assert_eq!(tree.left(&root).unwrap().borrow().read_data().unwrap(), 1);
+    /// ```
+    pub fn left(&self, node: &Shared<Vertex<T>>) -> Option<Shared<Vertex<T>>> {
+        node.borrow().get_pointer(PointerName::Left)
+    }
+
+    /// Get the right child of a node
+    /// # Arguments
+    /// * `node`: The parent node
+    /// # Returns
+    /// The vertex pointer of the right child, or None if there is no right child
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from_sorted_slice(&[1, 2]);
+    /// let root = tree.root().unwrap();
+    ///
+    /// assert!(tree.right(&root).is_none());
+    /// ```
+    pub fn right(&self, node: &Shared<Vertex<T>>) -> Option<Shared<Vertex<T>>> {
+        node.borrow().get_pointer(PointerName::Right)
+    }
+
+    /// Get the height of the tree, which is the number of nodes in the longest path from the root to a leaf
+    /// # Returns
+    /// The height of the tree, 0 for an empty tree
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from_sorted_slice(&[1, 2, 3, 4]);
+    /// assert_eq!(tree.height(), 3);
+    /// ```
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut stack: Vec<_> = self.root.iter().map(|root| (root.clone(), 1)).collect();
+
+        while let Some((node, depth)) = stack.pop() {
+            height = height.max(depth);
+            stack.extend(self.left(&node).map(|child| (child, depth + 1)));
+            stack.extend(self.right(&node).map(|child| (child, depth + 1)));
+        }
+
+        height
+    }
+
+    /// Get an in-order iterator over the data of the tree
+    /// # Returns
+    /// A lazy iterator yielding clones of the data: left subtree, node, right subtree
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from_sorted_slice(&['a', 'b', 'c']);
+    /// assert_eq!(tree.iter_inorder().collect::<String>(), "abc");
+    /// ```
+    pub fn iter_inorder(&self) -> InOrder<T> {
+        InOrder {
+            stack: Vec::new(),
+            current: self.root.clone(),
+        }
+    }
+
+    /// Get a pre-order iterator over the data of the tree
+    /// # Returns
+    /// A lazy iterator yielding clones of the data: node, left subtree, right subtree
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from_sorted_slice(&['a', 'b', 'c']);
+    /// assert_eq!(tree.iter_preorder().collect::<String>(), "bac");
+    /// ```
+    pub fn iter_preorder(&self) -> PreOrder<T> {
+        PreOrder {
+            stack: self.root.iter().cloned().collect(),
+        }
+    }
+
+    /// Get a post-order iterator over the data of the tree
+    /// # Returns
+    /// A lazy iterator yielding clones of the data: left subtree, right subtree, node
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from_sorted_slice(&['a', 'b', 'c']);
+    /// assert_eq!(tree.iter_postorder().collect::<String>(), "acb");
+    /// ```
+    pub fn iter_postorder(&self) -> PostOrder<T> {
+        PostOrder {
+            stack: self.root.iter().map(|root| (root.clone(), false)).collect(),
+        }
+    }
+
+    /// Get a level-order (breadth-first) iterator over the data of the tree
+    /// # Returns
+    /// A lazy iterator yielding clones of the data by increasing depth, from left to right
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from_sorted_slice(&['a', 'b', 'c', 'd']);
+    /// assert_eq!(tree.iter_level_order().collect::<String>(), "cbda");
+    /// ```
+    pub fn iter_level_order(&self) -> LevelOrder<T> {
+        LevelOrder {
+            queue: self.root.iter().cloned().collect(),
+        }
+    }
+}
+
+impl<T> Default for BinaryTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for BinaryTree<T> {
+    fn drop(&mut self) {
+        // Unlink the nodes iteratively, so dropping a deep tree doesn't recurse through every vertex
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            stack.extend(node.borrow().get_pointer(PointerName::Left));
+            stack.extend(node.borrow().get_pointer(PointerName::Right));
+            node.borrow_mut().clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linked_list::shared::Ptr;
+
+    #[test]
+    fn test_binary_tree_traversals() {
+        //        1
+        //       / \
+        //      2   3
+        //     / \   \
+        //    4   5   6
+        let mut tree = BinaryTree::new();
+        let root = tree.set_root(1).unwrap();
+        let two = tree.insert_left(&root, 2).unwrap();
+        let three = tree.insert_right(&root, 3).unwrap();
+        tree.insert_left(&two, 4).unwrap();
+        tree.insert_right(&two, 5).unwrap();
+        tree.insert_right(&three, 6).unwrap();
+
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree.height(), 3);
+        assert_eq!(
+            tree.iter_inorder().collect::<Vec<_>>(),
+            vec![4, 2, 5, 1, 3, 6]
+        );
+        assert_eq!(
+            tree.iter_preorder().collect::<Vec<_>>(),
+            vec![1, 2, 4, 5, 3, 6]
+        );
+        assert_eq!(
+            tree.iter_postorder().collect::<Vec<_>>(),
+            vec![4, 5, 2, 6, 3, 1]
+        );
+        assert_eq!(
+            tree.iter_level_order().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn test_binary_tree_from_sorted_slice() {
+        let values: Vec<u32> = (0..1_000).collect();
+        let tree = BinaryTree::from_sorted_slice(&values);
+
+        assert_eq!(tree.len(), 1_000);
+        assert_eq!(tree.height(), 10);
+        assert_eq!(tree.iter_inorder().collect::<Vec<_>>(), values);
+
+        // Iterators are lazy, so partial traversals don't visit the whole tree
+        assert_eq!(
+            tree.iter_level_order().take(3).collect::<Vec<_>>(),
+            vec![500, 250, 750]
+        );
+
+        let empty: BinaryTree<u32> = BinaryTree::from_sorted_slice(&[]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.iter_postorder().count(), 0);
+    }
+
+    #[test]
+    fn test_binary_tree_deep_drop() {
+        let mut tree = BinaryTree::new();
+        let mut node = tree.set_root(0).unwrap();
+        let first = Ptr::downgrade(&node);
+
+        for i in 1..100_000 {
+            node = tree.insert_right(&node, i).unwrap();
+        }
+        drop(node);
+
+        assert_eq!(tree.height(), 100_000);
+        drop(tree);
+        assert!(first.upgrade().is_none());
+    }
+}
@@ -0,0 +1,476 @@
+//! This module implements a binary trie over fixed-width unsigned integer keys, reading the keys bit by bit from the
+//! most significant one. Besides map operations it answers questions about the binary form of the keys: the key with
+//! the largest XOR with a given number, and the predecessor and successor of a number.
+//!
+//! # Performance
+//! - O(w) for `insert`, `get`, `contains_key`, `remove`, `max_xor_with`, `predecessor` and `successor`, where w is
+//!   the width of the key type (32 for `u32`, 64 for `u64`), independent of the number of keys
+//!
+//! # Implementation Details
+//! - Each node is a `Vertex`, the `Left` pointer following a 0 bit and the `Right` pointer a 1 bit. Leaves, at depth
+//!   w, hold the key and its value.
+//! - `remove` prunes the nodes left without children, so every node leads to at least one key. The searches rely on
+//!   it: once they leave the path of the query they go straight down to the smallest or largest key of a subtree.
+//! - Key types implement `BitKey`, provided for the unsigned integers.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::bit_trie::BitTrie;
+//!
+//! let mut trie = BitTrie::new();
+//! for key in [3u32, 10, 5, 25] {
+//!     trie.insert(key, ());
+//! }
+//!
+//! // 25 ^ 2 = 27 is the largest XOR with 2
+//! assert_eq!(trie.max_xor_with(2), Some(25));
+//! assert_eq!(trie.predecessor(10), Some(5));
+//! assert_eq!(trie.successor(10), Some(25));
+//! ```
+//!
+use std::fmt;
+
+use crate::linked_list::shared::Shared;
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+/// Trait for the fixed-width keys of a `BitTrie`
+///
+pub trait BitKey: Copy + Ord {
+    /// Number of bits of the key
+    const BITS: u32;
+
+    /// Get a bit of the key, 0 being the least significant
+    fn bit(self, index: u32) -> bool;
+}
+
+macro_rules! bit_key {
+    ($($ty:ty),*) => {$(
+        impl BitKey for $ty {
+            const BITS: u32 = <$ty>::BITS;
+
+            fn bit(self, index: u32) -> bool {
+                (self >> index) & 1 == 1
+            }
+        }
+    )*};
+}
+
+bit_key!(u8, u16, u32, u64, u128, usize);
+
+// Pointer followed for the given bit
+fn child_pointer(bit: bool) -> PointerName {
+    if bit {
+        PointerName::Right
+    } else {
+        PointerName::Left
+    }
+}
+
+/// Data of a node of the trie
+struct Node<K, V> {
+    entry: Option<(K, V)>, // only on leaves
+}
+
+type NodePtr<K, V> = Shared<Vertex<Node<K, V>>>;
+
+/// Struct representing a map from unsigned integers to values, stored in a binary trie
+///
+pub struct BitTrie<K, V> {
+    root: NodePtr<K, V>,
+    len: usize,
+}
+
+impl<K: BitKey, V> BitTrie<K, V> {
+    /// Create an empty trie
+    ///
+    /// # Returns
+    /// A new BitTrie instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bit_trie::BitTrie;
+    ///
+    /// let trie: BitTrie<u64, &str> = BitTrie::new();
+    /// assert!(trie.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        BitTrie {
+            root: Vertex::new(Node { entry: None }),
+            len: 0,
+        }
+    }
+
+    /// Get the number of keys
+    /// # Returns
+    /// The number of keys in the trie
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bit_trie::BitTrie;
+    ///
+    /// let mut trie = BitTrie::new();
+    /// trie.insert(1u32, 'a');
+    /// trie.insert(2u32, 'b');
+    /// assert_eq!(trie.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the trie is empty
+    /// # Returns
+    /// True if there are no keys
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bit_trie::BitTrie;
+    ///
+    /// let mut trie = BitTrie::new();
+    /// trie.insert(0u8, ());
+    /// assert!(!trie.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert a key, or replace its value
+    /// # Arguments
+    /// * `key`: The key
+    /// * `value`: The value to store
+    /// # Returns
+    /// The previous value of the key, if it was present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bit_trie::BitTrie;
+    ///
+    /// let mut trie = BitTrie::new();
+    /// assert_eq!(trie.insert(7u32, "seven"), None);
+    /// assert_eq!(trie.insert(7u32, "SEVEN"), Some("seven"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut current = self.root.clone();
+
+        for index in (0..K::BITS).rev() {
+            let bit = key.bit(index);
+            let next = current.borrow().get_pointer(child_pointer(bit));
+            current = match next {
+                Some(next) => next,
+                None => {
+                    let next = Vertex::new(Node { entry: None });
+                    current
+                        .borrow_mut()
+                        .set_connection(child_pointer(bit), Some(&next));
+                    next
+                }
+            };
+        }
+
+        let old = current
+            .borrow_mut()
+            .data_mut()
+            .unwrap()
+            .entry
+            .replace((key, value));
+        match old {
+            Some((_, old)) => Some(old),
+            None => {
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    /// Check if a key is present
+    /// # Arguments
+    /// * `key`: The key
+    /// # Returns
+    /// True if the key is in the trie
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bit_trie::BitTrie;
+    ///
+    /// let mut trie = BitTrie::new();
+    /// trie.insert(42u64, ());
+    ///
+    /// assert!(trie.contains_key(42));
+    /// assert!(!trie.contains_key(43));
+    /// ```
+    pub fn contains_key(&self, key: K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Get the value of a key
+    /// # Arguments
+    /// * `key`: The key
+    /// # Returns
+    /// A copy of the value, or None if the key isn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bit_trie::BitTrie;
+    ///
+    /// let mut trie = BitTrie::new();
+    /// trie.insert(0xc0a8_0001u32, "gateway");
+    ///
+    /// assert_eq!(trie.get(0xc0a8_0001), Some("gateway"));
+    /// assert_eq!(trie.get(0xc0a8_0002), None);
+    /// ```
+    pub fn get(&self, key: K) -> Option<V>
+    where
+        V: Clone,
+    {
+        let leaf = self.find(key)?;
+        let value = leaf
+            .borrow()
+            .read_data()
+            .as_ref()
+            .unwrap()
+            .entry
+            .as_ref()
+            .map(|(_, value)| value.clone());
+        value
+    }
+
+    /// Remove a key
+    /// # Arguments
+    /// * `key`: The key
+    /// # Returns
+    /// The value of the key, or None if it wasn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bit_trie::BitTrie;
+    ///
+    /// let mut trie = BitTrie::new();
+    /// trie.insert(5u16, 'x');
+    ///
+    /// assert_eq!(trie.remove(5), Some('x'));
+    /// assert_eq!(trie.remove(5), None);
+    /// assert!(trie.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        // Path from the root to the leaf of the key
+        let mut path = vec![self.root.clone()];
+        for index in (0..K::BITS).rev() {
+            let next = path
+                .last()
+                .unwrap()
+                .borrow()
+                .get_pointer(child_pointer(key.bit(index)))?;
+            path.push(next);
+        }
+
+        let (_, value) = path
+            .last()
+            .unwrap()
+            .borrow_mut()
+            .data_mut()
+            .unwrap()
+            .entry
+            .take()?;
+        self.len -= 1;
+
+        // Prune the nodes left without children, starting with the leaf
+        for depth in (0..K::BITS as usize).rev() {
+            let bit = key.bit(K::BITS - 1 - depth as u32);
+            path[depth]
+                .borrow_mut()
+                .set_connection(child_pointer(bit), None);
+
+            let parent = path[depth].borrow();
+            if parent.get_pointer(PointerName::Left).is_some()
+                || parent.get_pointer(PointerName::Right).is_some()
+            {
+                break;
+            }
+        }
+
+        Some(value)
+    }
+
+    /// Find the key whose XOR with a number is the largest
+    /// # Arguments
+    /// * `number`: The number to XOR the keys with
+    /// # Returns
+    /// The key maximizing `key ^ number`, or None if the trie is empty
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bit_trie::BitTrie;
+    ///
+    /// let mut trie = BitTrie::new();
+    /// trie.insert(0b0101u8, ());
+    /// trie.insert(0b1100u8, ());
+    ///
+    /// assert_eq!(trie.max_xor_with(0b0110), Some(0b1100));
+    /// assert_eq!(trie.max_xor_with(0b1000), Some(0b0101));
+    /// ```
+    pub fn max_xor_with(&self, number: K) -> Option<K> {
+        if self.is_empty() {
+            return None;
+        }
+
+        // Take the opposite bit whenever there is a key with it
+        let mut current = self.root.clone();
+        for index in (0..K::BITS).rev() {
+            let wanted = !number.bit(index);
+            let next = current
+                .borrow()
+                .get_pointer(child_pointer(wanted))
+                .or_else(|| current.borrow().get_pointer(child_pointer(!wanted)))
+                .unwrap();
+            current = next;
+        }
+
+        Self::leaf_key(&current)
+    }
+
+    /// Find the largest key smaller than a number
+    /// # Arguments
+    /// * `number`: The bound, excluded
+    /// # Returns
+    /// The predecessor of the number, or None if every key is at least the number
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bit_trie::BitTrie;
+    ///
+    /// let mut trie = BitTrie::new();
+    /// trie.insert(10u32, ());
+    /// trie.insert(20u32, ());
+    ///
+    /// assert_eq!(trie.predecessor(20), Some(10));
+    /// assert_eq!(trie.predecessor(15), Some(10));
+    /// assert_eq!(trie.predecessor(10), None);
+    /// ```
+    pub fn predecessor(&self, number: K) -> Option<K> {
+        self.neighbor(number, true)
+    }
+
+    /// Find the smallest key larger than a number
+    /// # Arguments
+    /// * `number`: The bound, excluded
+    /// # Returns
+    /// The successor of the number, or None if every key is at most the number
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bit_trie::BitTrie;
+    ///
+    /// let mut trie = BitTrie::new();
+    /// trie.insert(10u32, ());
+    /// trie.insert(20u32, ());
+    ///
+    /// assert_eq!(trie.successor(10), Some(20));
+    /// assert_eq!(trie.successor(0), Some(10));
+    /// assert_eq!(trie.successor(20), None);
+    /// ```
+    pub fn successor(&self, number: K) -> Option<K> {
+        self.neighbor(number, false)
+    }
+
+    // Predecessor or successor: follow the number and remember the deepest branch on the wanted side of it, then go
+    // down that branch to its largest or smallest key
+    fn neighbor(&self, number: K, below: bool) -> Option<K> {
+        let mut current = Some(self.root.clone());
+        let mut branch = None;
+
+        for (depth, index) in (0..K::BITS).rev().enumerate() {
+            let Some(node) = current else { break };
+            let bit = number.bit(index);
+
+            // A subtree entirely below (or above) the number
+            if bit == below {
+                if let Some(side) = node.borrow().get_pointer(child_pointer(!below)) {
+                    branch = Some((side, depth + 1));
+                }
+            }
+
+            current = node.borrow().get_pointer(child_pointer(bit));
+        }
+
+        let (mut node, depth) = branch?;
+        for _ in depth..K::BITS as usize {
+            let next = node
+                .borrow()
+                .get_pointer(child_pointer(below))
+                .or_else(|| node.borrow().get_pointer(child_pointer(!below)))
+                .unwrap();
+            node = next;
+        }
+
+        Self::leaf_key(&node)
+    }
+
+    fn leaf_key(leaf: &NodePtr<K, V>) -> Option<K> {
+        leaf.borrow()
+            .read_data()
+            .as_ref()
+            .unwrap()
+            .entry
+            .as_ref()
+            .map(|(key, _)| *key)
+    }
+
+    // Leaf of a key, if it is present
+    fn find(&self, key: K) -> Option<NodePtr<K, V>> {
+        let mut current = self.root.clone();
+        for index in (0..K::BITS).rev() {
+            let next = current
+                .borrow()
+                .get_pointer(child_pointer(key.bit(index)))?;
+            current = next;
+        }
+        Some(current)
+    }
+}
+
+impl<K: BitKey, V> Default for BitTrie<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> fmt::Debug for BitTrie<K, V> {
+    /// Prints the number of keys, the trie itself being a graph of vertexes
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitTrie").field("len", &self.len).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+
+    #[test]
+    fn test_bit_trie_against_btree_map() {
+        let mut trie = BitTrie::new();
+        let mut expected = BTreeMap::new();
+        let mut rng = Lcg::new(71);
+
+        for round in 0..3000u32 {
+            let seed = rng.next_u64();
+            let key = (seed >> 56) as u8;
+            let probe = (seed >> 40) as u8;
+
+            if (seed >> 20).is_multiple_of(3) {
+                assert_eq!(trie.remove(key), expected.remove(&key));
+            } else {
+                assert_eq!(trie.insert(key, round), expected.insert(key, round));
+            }
+            assert_eq!(trie.len(), expected.len());
+            assert_eq!(trie.get(probe), expected.get(&probe).copied());
+
+            assert_eq!(
+                trie.predecessor(probe),
+                expected.range(..probe).next_back().map(|(&k, _)| k)
+            );
+            let above = (Bound::Excluded(probe), Bound::Unbounded);
+            assert_eq!(
+                trie.successor(probe),
+                expected.range(above).next().map(|(&k, _)| k)
+            );
+            let best = expected.keys().max_by_key(|&&k| k ^ probe).copied();
+            assert_eq!(
+                trie.max_xor_with(probe).map(|k| k ^ probe),
+                best.map(|k| k ^ probe)
+            );
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! This module implements a BK-tree (Burkhard-Keller tree), a metric tree for approximate matching.
+//! Every item is stored in a node, and the children of a node are indexed by their distance to it. The triangle inequality
+//! allows a query to skip every subtree that can't hold an item within the requested distance.
+//!
+//! # Performance
+//! - O(log n) average for `add`, O(n) in the worst case
+//! - Queries visit only a fraction of the tree for small distances, O(n) in the worst case
+//!
+//! # Implementation Details
+//! - Nodes are `Vertex` instances handled through `Shared<Vertex<T>>` pointers.
+//! - The child at distance d of a node is stored in the custom pointer named after d, so a query looks up the
+//!   children with distances in `[d - k, d + k]` directly instead of scanning all of them.
+//! - The distance function is given by the `Metric` trait, which defaults to the Levenshtein (edit) distance on strings.
+//!   Closures with the signature `Fn(&T, &T) -> usize` can be used as metrics too.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::bk_tree::BkTree;
+//!
+//! let mut tree = BkTree::new();
+//!
+//! for word in ["book", "books", "cake", "boo", "cape", "cart"] {
+//!     tree.add(word.to_string());
+//! }
+//!
+//! let mut matches = tree.query(&"bo".to_string(), 2);
+//! matches.sort();
+//! assert_eq!(matches, vec![(1, "boo".to_string()), (2, "book".to_string())]);
+//! ```
+//!
+
+use crate::linked_list::shared::Shared;
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+/// A distance function between items
+/// To keep queries correct the distance must be a metric: zero only for equal items, symmetric,
+/// and respecting the triangle inequality.
+pub trait Metric<T> {
+    fn distance(&self, a: &T, b: &T) -> usize;
+}
+
+impl<T, F> Metric<T> for F
+where
+    F: Fn(&T, &T) -> usize,
+{
+    fn distance(&self, a: &T, b: &T) -> usize {
+        self(a, b)
+    }
+}
+
+/// The Levenshtein (edit) distance between strings
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Levenshtein;
+
+impl<T: AsRef<str>> Metric<T> for Levenshtein {
+    fn distance(&self, a: &T, b: &T) -> usize {
+        levenshtein(a.as_ref(), b.as_ref())
+    }
+}
+
+/// Compute the Levenshtein distance between two strings
+/// The distance is the minimum number of single character insertions, deletions and substitutions to turn `a` into `b`.
+/// # Arguments
+/// * `a`: The first string
+/// * `b`: The second string
+/// # Returns
+/// The edit distance, counted in characters
+/// # Example
+/// ```
+/// use data_structures::tree::bk_tree::levenshtein;
+///
+/// assert_eq!(levenshtein("kitten", "sitting"), 3);
+/// assert_eq!(levenshtein("", "abc"), 3);
+/// ```
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+
+    // Only the previous row of the dynamic programming matrix is kept
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_char != *b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+// Name of the pointer holding the child at the given distance
+fn distance_pointer(distance: usize) -> PointerName {
+    PointerName::Custom(distance.to_string())
+}
+
+/// Struct representing a BK-tree of items compared by a metric
+///
+#[derive(Debug)]
+pub struct BkTree<T, M = Levenshtein> {
+    root: Option<Shared<Vertex<T>>>,
+    metric: M,
+
+    size: usize,
+}
+
+impl<T: AsRef<str>> BkTree<T, Levenshtein> {
+    /// Create a new empty BkTree of strings using the Levenshtein distance
+    ///
+    /// # Returns
+    /// A new BkTree instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bk_tree::BkTree;
+    ///
+    /// let tree: BkTree<String> = BkTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_metric(Levenshtein)
+    }
+}
+
+impl<T, M: Metric<T>> BkTree<T, M> {
+    /// Create a new empty BkTree using the given metric
+    ///
+    /// # Arguments
+    /// * `metric`: The distance function between items
+    ///
+    /// # Returns
+    /// A new BkTree instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bk_tree::BkTree;
+    ///
+    /// let mut tree = BkTree::with_metric(|a: &i32, b: &i32| a.abs_diff(*b) as usize);
+    ///
+    /// tree.add(10);
+    /// tree.add(14);
+    /// tree.add(30);
+    ///
+    /// assert_eq!(tree.query(&12, 2).len(), 2);
+    /// ```
+    pub fn with_metric(metric: M) -> Self {
+        BkTree {
+            root: None,
+            metric,
+            size: 0,
+        }
+    }
+
+    /// Get the number of items in the tree
+    /// # Returns
+    /// The number of items
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bk_tree::BkTree;
+    ///
+    /// let mut tree = BkTree::new();
+    /// tree.add("a");
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the tree is empty
+    /// # Returns
+    /// True if the tree has no items, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bk_tree::BkTree;
+    ///
+    /// let tree: BkTree<&str> = BkTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Add an item to the tree
+    /// # Arguments
+    /// * `item`: The item to be added
+    /// # Returns
+    /// True if the item was added, false if an item at distance 0 was already in the tree
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bk_tree::BkTree;
+    ///
+    /// let mut tree = BkTree::new();
+    ///
+    /// assert!(tree.add("hello"));
+    /// assert!(!tree.add("hello"));
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn add(&mut self, item: T) -> bool {
+        let mut current = match &self.root {
+            Some(root) => root.clone(),
+            None => {
+                self.root = Some(Vertex::new(item));
+                self.size = 1;
+                return true;
+            }
+        };
+
+        loop {
+            let distance = self
+                .metric
+                .distance(current.borrow().read_data().as_ref().unwrap(), &item);
+            if distance == 0 {
+                return false;
+            }
+
+            let child = current.borrow().get_pointer(distance_pointer(distance));
+            match child {
+                Some(child) => current = child,
+                None => {
+                    let node = Vertex::new(item);
+                    current
+                        .borrow_mut()
+                        .set_connection(distance_pointer(distance), Some(&node));
+                    self.size += 1;
+                    return true;
+                }
+            }
+        }
+    }
+
+    /// Check if an item at distance 0 is in the tree
+    /// # Arguments
+    /// * `item`: The item to be searched
+    /// # Returns
+    /// True if the item is in the tree, false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bk_tree::BkTree;
+    ///
+    /// let mut tree = BkTree::new();
+    /// tree.add("hello");
+    ///
+    /// assert!(tree.contains(&"hello"));
+    /// assert!(!tree.contains(&"hell"));
+    /// ```
+    pub fn contains(&self, item: &T) -> bool {
+        let mut current = self.root.clone();
+
+        while let Some(node) = current {
+            let distance = self
+                .metric
+                .distance(node.borrow().read_data().as_ref().unwrap(), item);
+            if distance == 0 {
+                return true;
+            }
+            current = node.borrow().get_pointer(distance_pointer(distance));
+        }
+
+        false
+    }
+
+    /// Find every item within a maximum distance of the given one
+    /// # Arguments
+    /// * `item`: The item to be searched
+    /// * `max_distance`: The maximum distance of the returned items
+    /// # Returns
+    /// A vector of (distance, item) tuples, in no particular order
+    /// # Example
+    /// ```
+    /// use data_structures::tree::bk_tree::BkTree;
+    ///
+    /// let mut tree = BkTree::new();
+    /// for word in ["hello", "help", "shell", "world"] {
+    ///     tree.add(word);
+    /// }
+    ///
+    /// let mut matches = tree.query(&"hell", 1);
+    /// matches.sort();
+    /// assert_eq!(matches, vec![(1, "hello"), (1, "help"), (1, "shell")]);
+    /// ```
+    pub fn query(&self, item: &T, max_distance: usize) -> Vec<(usize, T)>
+    where
+        T: Clone,
+    {
+        let mut matches = Vec::new();
+        let mut stack: Vec<_> = self.root.iter().cloned().collect();
+
+        while let Some(node) = stack.pop() {
+            let node = node.borrow();
+            let data = node.read_data().as_ref().unwrap();
+            let distance = self.metric.distance(data, item);
+
+            if distance <= max_distance {
+                matches.push((distance, data.clone()));
+            }
+
+            // By the triangle inequality, only children in [distance - k, distance + k] can hold matches
+            let low = distance.saturating_sub(max_distance).max(1);
+            let high = distance.saturating_add(max_distance);
+            for child_distance in low..=high {
+                stack.extend(node.get_pointer(distance_pointer(child_distance)));
+            }
+        }
+
+        matches
+    }
+}
+
+impl<T, M: Metric<T> + Default> Default for BkTree<T, M> {
+    fn default() -> Self {
+        Self::with_metric(M::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("flaw", "lawn"), 2);
+        assert_eq!(levenshtein("saturday", "sunday"), 3);
+        assert_eq!(levenshtein("ação", "acao"), 2);
+    }
+
+    #[test]
+    fn test_bk_tree_query_matches_linear_scan() {
+        let words = [
+            "apple", "apply", "ample", "maple", "angle", "ankle", "apples", "applet", "happy",
+            "sapling", "dapple", "appeal",
+        ];
+
+        let mut tree = BkTree::new();
+        for word in words {
+            tree.add(word.to_string());
+        }
+        assert_eq!(tree.len(), words.len());
+
+        for max_distance in 0..4 {
+            let mut found = tree.query(&"appel".to_string(), max_distance);
+            found.sort();
+
+            let mut expected: Vec<(usize, String)> = words
+                .iter()
+                .map(|word| (levenshtein(word, "appel"), word.to_string()))
+                .filter(|(distance, _)| *distance <= max_distance)
+                .collect();
+            expected.sort();
+
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn test_bk_tree_custom_metric() {
+        let hamming = |a: &u32, b: &u32| (a ^ b).count_ones() as usize;
+        let mut tree = BkTree::with_metric(hamming);
+
+        for value in 0..256u32 {
+            tree.add(value);
+        }
+
+        // Values differing in exactly one of the 8 low bits
+        let mut matches: Vec<u32> = tree
+            .query(&0b1010_1010, 1)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        matches.sort();
+
+        assert_eq!(matches.len(), 9);
+        assert!(matches.contains(&0b1010_1011));
+        assert!(tree.contains(&255));
+        assert!(!tree.contains(&256));
+    }
+}
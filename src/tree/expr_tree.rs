@@ -0,0 +1,395 @@
+//! This module implements an arithmetic expression tree on top of the binary tree.
+//! Internal nodes are binary operators and leaves are numeric operands. The tree can be built from a Reverse Polish Notation (RPN)
+//! token stream, evaluated, and rendered back in infix or postfix notation.
+//!
+//! # Performance
+//! - O(n) to build the tree from n RPN tokens
+//! - O(n) to evaluate or render the expression
+//!
+//! # Implementation Details
+//! - The expression is stored in a `BinaryTree<Token>`, where the left and right children of an operator are its operands.
+//! - Evaluation walks the tree in post-order with an operand stack, so it doesn't recurse.
+//! - Infix rendering only adds the parentheses required by the operator precedence and associativity.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::expr_tree::ExprTree;
+//!
+//! let expr = ExprTree::parse_rpn("3 4 2 * 1 5 - / +").unwrap();
+//!
+//! assert_eq!(expr.eval(), Ok(1.0));
+//! assert_eq!(expr.to_infix(), "3 + 4 * 2 / (1 - 5)");
+//! assert_eq!(expr.to_postfix(), "3 4 2 * 1 5 - / +");
+//! ```
+//!
+use std::fmt;
+
+use super::binary_tree::BinaryTree;
+use crate::error::DataStructureError;
+use crate::linked_list::shared::Shared;
+use crate::linked_list::vertex::Vertex;
+
+/// Binary operators supported by the expression tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+impl Operator {
+    // Binding strength of the operator, higher binds tighter
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Add | Operator::Sub => 1,
+            Operator::Mul | Operator::Div => 2,
+            Operator::Pow => 3,
+        }
+    }
+
+    fn is_right_associative(&self) -> bool {
+        matches!(self, Operator::Pow)
+    }
+
+    fn apply(&self, left: f64, right: f64) -> Result<f64, DataStructureError> {
+        match self {
+            Operator::Add => Ok(left + right),
+            Operator::Sub => Ok(left - right),
+            Operator::Mul => Ok(left * right),
+            Operator::Div if right == 0.0 => Err(DataStructureError::DivisionByZero),
+            Operator::Div => Ok(left / right),
+            Operator::Pow => Ok(left.powf(right)),
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Mul => "*",
+            Operator::Div => "/",
+            Operator::Pow => "^",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// A token of an expression: an operand (leaf) or an operator (internal node)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token {
+    Operand(f64),
+    Operator(Operator),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Operand(value) => write!(f, "{}", value),
+            Token::Operator(operator) => write!(f, "{}", operator),
+        }
+    }
+}
+
+impl std::str::FromStr for Token {
+    type Err = DataStructureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "+" => Ok(Token::Operator(Operator::Add)),
+            "-" => Ok(Token::Operator(Operator::Sub)),
+            "*" => Ok(Token::Operator(Operator::Mul)),
+            "/" => Ok(Token::Operator(Operator::Div)),
+            "^" => Ok(Token::Operator(Operator::Pow)),
+            _ => s
+                .parse::<f64>()
+                .map(Token::Operand)
+                .map_err(|_| DataStructureError::InvalidToken),
+        }
+    }
+}
+
+/// Struct representing an arithmetic expression as a binary tree of tokens
+///
+#[derive(Debug)]
+pub struct ExprTree {
+    tree: BinaryTree<Token>,
+}
+
+impl ExprTree {
+    /// Build an expression tree from a stream of tokens in Reverse Polish Notation
+    ///
+    /// # Arguments
+    /// * `tokens`: The tokens of the expression, operands before their operator
+    ///
+    /// # Returns
+    /// Result<ExprTree, DataStructureError>
+    /// Ok with the expression tree, Err if the token stream isn't a single valid expression
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::tree::expr_tree::{ExprTree, Operator, Token};
+    ///
+    /// let tokens = vec![Token::Operand(1.0), Token::Operand(2.0), Token::Operator(Operator::Add)];
+    /// let expr = ExprTree::from_rpn(tokens).unwrap();
+    /// assert_eq!(expr.eval(), Ok(3.0));
+    ///
+    /// let tokens = vec![Token::Operand(1.0), Token::Operator(Operator::Add)];
+    /// assert_eq!(ExprTree::from_rpn(tokens).unwrap_err(), DataStructureError::MissingOperand);
+    /// ```
+    pub fn from_rpn<I>(tokens: I) -> Result<Self, DataStructureError>
+    where
+        I: IntoIterator<Item = Token>,
+    {
+        let mut stack: Vec<BinaryTree<Token>> = Vec::new();
+
+        for token in tokens {
+            match token {
+                Token::Operand(_) => stack.push(BinaryTree::join(
+                    token,
+                    BinaryTree::new(),
+                    BinaryTree::new(),
+                )),
+                Token::Operator(_) => {
+                    let right = stack.pop().ok_or(DataStructureError::MissingOperand)?;
+                    let left = stack.pop().ok_or(DataStructureError::MissingOperand)?;
+                    stack.push(BinaryTree::join(token, left, right));
+                }
+            }
+        }
+
+        match (stack.pop(), stack.is_empty()) {
+            (Some(tree), true) => Ok(ExprTree { tree }),
+            (Some(_), false) => Err(DataStructureError::MissingOperator),
+            (None, _) => Err(DataStructureError::EmptyExpression),
+        }
+    }
+
+    /// Build an expression tree from a whitespace separated RPN string
+    ///
+    /// # Arguments
+    /// * `expression`: The expression, such as `"1 2 + 3 *"`
+    ///
+    /// # Returns
+    /// Result<ExprTree, DataStructureError>
+    /// Ok with the expression tree, Err if a token is invalid or the expression is malformed
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::tree::expr_tree::ExprTree;
+    ///
+    /// let expr = ExprTree::parse_rpn("1 2 + 3 *").unwrap();
+    /// assert_eq!(expr.eval(), Ok(9.0));
+    ///
+    /// assert_eq!(ExprTree::parse_rpn("1 x +").unwrap_err(), DataStructureError::InvalidToken);
+    /// ```
+    pub fn parse_rpn(expression: &str) -> Result<Self, DataStructureError> {
+        let tokens = expression
+            .split_whitespace()
+            .map(str::parse::<Token>)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_rpn(tokens)
+    }
+
+    /// Get the underlying binary tree
+    /// # Returns
+    /// A reference to the binary tree of tokens
+    /// # Example
+    /// ```
+    /// use data_structures::tree::expr_tree::ExprTree;
+    ///
+    /// let expr = ExprTree::parse_rpn("1 2 +").unwrap();
+    /// assert_eq!(expr.tree().len(), 3);
+    /// ```
+    pub fn tree(&self) -> &BinaryTree<Token> {
+        &self.tree
+    }
+
+    /// Evaluate the expression
+    /// # Returns
+    /// Result<f64, DataStructureError>
+    /// Ok with the value of the expression, Err if a division by zero happens
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::tree::expr_tree::ExprTree;
+    ///
+    /// assert_eq!(ExprTree::parse_rpn("2 3 ^ 2 /").unwrap().eval(), Ok(4.0));
+    /// assert_eq!(ExprTree::parse_rpn("1 0 /").unwrap().eval(), Err(DataStructureError::DivisionByZero));
+    /// ```
+    pub fn eval(&self) -> Result<f64, DataStructureError> {
+        let mut operands = Vec::new();
+
+        for token in self.tree.iter_postorder() {
+            match token {
+                Token::Operand(value) => operands.push(value),
+                Token::Operator(operator) => {
+                    // The tree is built from valid expressions, so operators always have two operands
+                    let right = operands.pop().unwrap();
+                    let left = operands.pop().unwrap();
+                    operands.push(operator.apply(left, right)?);
+                }
+            }
+        }
+
+        Ok(operands.pop().unwrap())
+    }
+
+    /// Render the expression in postfix (RPN) notation
+    /// # Returns
+    /// The tokens in post-order, separated by spaces
+    /// # Example
+    /// ```
+    /// use data_structures::tree::expr_tree::ExprTree;
+    ///
+    /// let expr = ExprTree::parse_rpn("1 2 3 * +").unwrap();
+    /// assert_eq!(expr.to_postfix(), "1 2 3 * +");
+    /// ```
+    pub fn to_postfix(&self) -> String {
+        self.tree
+            .iter_postorder()
+            .map(|token| token.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Render the expression in infix notation
+    /// Parentheses are only added where the precedence or associativity of the operators requires them.
+    /// # Returns
+    /// The expression in infix notation
+    /// # Example
+    /// ```
+    /// use data_structures::tree::expr_tree::ExprTree;
+    ///
+    /// assert_eq!(ExprTree::parse_rpn("1 2 + 3 *").unwrap().to_infix(), "(1 + 2) * 3");
+    /// assert_eq!(ExprTree::parse_rpn("1 2 3 - -").unwrap().to_infix(), "1 - (2 - 3)");
+    /// assert_eq!(ExprTree::parse_rpn("2 3 2 ^ ^").unwrap().to_infix(), "2 ^ 3 ^ 2");
+    /// ```
+    pub fn to_infix(&self) -> String {
+        let mut output = String::new();
+        self.render_infix(&self.tree.root().unwrap(), &mut output);
+        output
+    }
+
+    fn render_infix(&self, node: &Shared<Vertex<Token>>, output: &mut String) {
+        let token = node.borrow().read_data().unwrap();
+
+        let operator = match token {
+            Token::Operand(value) => {
+                output.push_str(&value.to_string());
+                return;
+            }
+            Token::Operator(operator) => operator,
+        };
+
+        let left = self.tree.left(node).unwrap();
+        let right = self.tree.right(node).unwrap();
+
+        // The left operand needs parentheses if it binds looser, or equally tight with a right associative operator
+        let left_parens = match left.borrow().read_data().unwrap() {
+            Token::Operator(child) => {
+                child.precedence() < operator.precedence()
+                    || (child.precedence() == operator.precedence()
+                        && operator.is_right_associative())
+            }
+            Token::Operand(_) => false,
+        };
+
+        // The right operand needs parentheses if it binds looser, or equally tight with a left associative operator
+        let right_parens = match right.borrow().read_data().unwrap() {
+            Token::Operator(child) => {
+                child.precedence() < operator.precedence()
+                    || (child.precedence() == operator.precedence()
+                        && !operator.is_right_associative()
+                        && matches!(operator, Operator::Sub | Operator::Div))
+            }
+            Token::Operand(_) => false,
+        };
+
+        self.render_operand(&left, left_parens, output);
+        output.push_str(&format!(" {} ", operator));
+        self.render_operand(&right, right_parens, output);
+    }
+
+    fn render_operand(&self, node: &Shared<Vertex<Token>>, parens: bool, output: &mut String) {
+        if parens {
+            output.push('(');
+        }
+        self.render_infix(node, output);
+        if parens {
+            output.push(')');
+        }
+    }
+}
+
+impl fmt::Display for ExprTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_infix())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expr_tree_eval() {
+        let expr = ExprTree::parse_rpn("5 1 2 + 4 * + 3 -").unwrap();
+
+        assert_eq!(expr.eval(), Ok(14.0));
+        assert_eq!(expr.to_infix(), "5 + (1 + 2) * 4 - 3");
+        assert_eq!(expr.to_postfix(), "5 1 2 + 4 * + 3 -");
+        assert_eq!(expr.to_string(), expr.to_infix());
+
+        let single = ExprTree::parse_rpn("-2.5").unwrap();
+        assert_eq!(single.eval(), Ok(-2.5));
+        assert_eq!(single.to_infix(), "-2.5");
+    }
+
+    #[test]
+    fn test_expr_tree_associativity() {
+        // Addition and multiplication are associative, so they don't need parentheses on the right
+        assert_eq!(
+            ExprTree::parse_rpn("1 2 3 + +").unwrap().to_infix(),
+            "1 + 2 + 3"
+        );
+        assert_eq!(
+            ExprTree::parse_rpn("1 2 3 / /").unwrap().to_infix(),
+            "1 / (2 / 3)"
+        );
+        assert_eq!(
+            ExprTree::parse_rpn("1 2 / 3 /").unwrap().to_infix(),
+            "1 / 2 / 3"
+        );
+        assert_eq!(
+            ExprTree::parse_rpn("2 3 ^ 2 ^").unwrap().to_infix(),
+            "(2 ^ 3) ^ 2"
+        );
+    }
+
+    #[test]
+    fn test_expr_tree_malformed() {
+        assert_eq!(
+            ExprTree::parse_rpn("").unwrap_err(),
+            DataStructureError::EmptyExpression
+        );
+        assert_eq!(
+            ExprTree::parse_rpn("1 2").unwrap_err(),
+            DataStructureError::MissingOperator
+        );
+        assert_eq!(
+            ExprTree::parse_rpn("+").unwrap_err(),
+            DataStructureError::MissingOperand
+        );
+        assert_eq!(
+            ExprTree::parse_rpn("1 2 %").unwrap_err(),
+            DataStructureError::InvalidToken
+        );
+    }
+}
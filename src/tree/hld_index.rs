@@ -0,0 +1,421 @@
+//! This module implements heavy-light decomposition (HLD) over a weighted n-ary tree, for path queries with updates.
+//! Every node carries a weight, and the index answers the sum, minimum and maximum of the weights on the path between
+//! any two nodes, while weights can be changed one node at a time.
+//!
+//! # Performance
+//! - O(n) to build the index
+//! - O(log² n) for `path_sum`, `path_min`, `path_max` and O(log n) for `update`
+//!
+//! # Implementation Details
+//! - Each node continues the chain of its parent if it has the largest subtree among its siblings (heavy child), and
+//!   starts a new chain otherwise. Any path crosses O(log n) chains.
+//! - Nodes are laid out so every chain is contiguous, and the weights are stored in that order in a `SegmentTree`
+//!   whose values are the sum, minimum and maximum of a range. A path query is one range query per chain crossed.
+//! - Like the `LcaIndex`, nodes are recognized by the address of their vertex and the index is a snapshot: weights are
+//!   copied when it is built, and `update` changes the index only.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::hld_index::HldIndex;
+//! use data_structures::tree::nary_tree::NaryTree;
+//!
+//! let mut tree = NaryTree::new(1);
+//! let root = tree.root();
+//! let a = tree.add_child(&root, 5);
+//! let b = tree.add_child(&root, -2);
+//! let a1 = tree.add_child(&a, 3);
+//!
+//! let mut index = HldIndex::from_nary(&tree);
+//!
+//! assert_eq!(index.path_sum(&a1, &b), Some(7));
+//! assert_eq!(index.path_min(&a1, &b), Some(-2));
+//!
+//! index.update(&root, 10).unwrap();
+//! assert_eq!(index.path_max(&a1, &b), Some(10));
+//! ```
+//!
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Add;
+
+use crate::error::DataStructureError;
+use crate::linked_list::shared::Shared;
+use crate::linked_list::vertex::Vertex;
+use crate::range::segment_tree::SegmentTree;
+use crate::tree::lca_index::address;
+use crate::tree::nary_tree::NaryTree;
+
+// Summary of the weights of a range, None for an empty range
+#[derive(Debug, Clone, Copy)]
+struct PathStats<W> {
+    sum: W,
+    min: W,
+    max: W,
+}
+
+type Summary<W> = Option<PathStats<W>>;
+type Combine<W> = fn(&Summary<W>, &Summary<W>) -> Summary<W>;
+
+fn combine<W: Copy + Ord + Add<Output = W>>(a: &Summary<W>, b: &Summary<W>) -> Summary<W> {
+    match (a, b) {
+        (None, other) | (other, None) => *other,
+        (Some(a), Some(b)) => Some(PathStats {
+            sum: a.sum + b.sum,
+            min: a.min.min(b.min),
+            max: a.max.max(b.max),
+        }),
+    }
+}
+
+fn single<W: Copy>(weight: W) -> Summary<W> {
+    Some(PathStats {
+        sum: weight,
+        min: weight,
+        max: weight,
+    })
+}
+
+/// Struct representing a heavy-light decomposition of a weighted tree, answering path queries
+///
+pub struct HldIndex<W> {
+    nodes: Vec<Shared<Vertex<W>>>, // by id, in pre-order
+    ids: HashMap<usize, usize>,    // vertex address to id
+    parent: Vec<Option<usize>>,
+    depth: Vec<usize>,
+    head: Vec<usize>,     // first node of the chain of each node
+    position: Vec<usize>, // position of each node in the segment tree
+    segments: SegmentTree<Summary<W>, Combine<W>>,
+}
+
+impl<W: Copy + Ord + Add<Output = W>> HldIndex<W> {
+    /// Decompose an n-ary tree, using the data of the nodes as their weights
+    ///
+    /// # Arguments
+    /// * `tree`: The tree to index
+    ///
+    /// # Returns
+    /// A new HldIndex instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::hld_index::HldIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let tree = NaryTree::new(4u32);
+    /// let index = HldIndex::from_nary(&tree);
+    /// assert_eq!(index.weight(&tree.root()), Some(4));
+    /// ```
+    pub fn from_nary(tree: &NaryTree<W>) -> Self {
+        // Pre-order walk recording parents, depths and children
+        let mut nodes = Vec::new();
+        let mut ids = HashMap::new();
+        let mut parent = Vec::new();
+        let mut depth = Vec::new();
+        let mut children: Vec<Vec<usize>> = Vec::new();
+        let mut stack = vec![(tree.root(), None)];
+
+        while let Some((node, node_parent)) = stack.pop() {
+            let id = nodes.len();
+            ids.insert(address(&node), id);
+            parent.push(node_parent);
+            depth.push(node_parent.map_or(0, |p: usize| depth[p] + 1));
+            children.push(Vec::new());
+            if let Some(p) = node_parent {
+                children[p].push(id);
+            }
+
+            let mut pending: Vec<_> = tree.children(&node).collect();
+            pending.reverse();
+            stack.extend(pending.into_iter().map(|child| (child, Some(id))));
+            nodes.push(node);
+        }
+
+        // Subtree sizes, children before parents
+        let mut size = vec![1; nodes.len()];
+        for id in (1..nodes.len()).rev() {
+            size[parent[id].unwrap()] += size[id];
+        }
+
+        // Lay out the chains: the heavy child is visited right after its parent
+        let mut head = vec![0; nodes.len()];
+        let mut position = vec![0; nodes.len()];
+        let mut weights = vec![None; nodes.len()];
+        let mut stack = vec![0];
+        let mut next = 0;
+
+        while let Some(id) = stack.pop() {
+            position[id] = next;
+            weights[next] = single(nodes[id].borrow().read_data().unwrap());
+            next += 1;
+
+            let heavy = children[id]
+                .iter()
+                .copied()
+                .max_by_key(|&child| size[child]);
+            for &child in &children[id] {
+                if Some(child) != heavy {
+                    head[child] = child;
+                    stack.push(child);
+                }
+            }
+            if let Some(heavy) = heavy {
+                head[heavy] = head[id];
+                stack.push(heavy);
+            }
+        }
+
+        HldIndex {
+            nodes,
+            ids,
+            parent,
+            depth,
+            head,
+            position,
+            segments: SegmentTree::new(weights, None, combine as Combine<W>),
+        }
+    }
+
+    /// Get the number of indexed nodes
+    /// # Returns
+    /// The number of nodes of the tree when the index was built
+    /// # Example
+    /// ```
+    /// use data_structures::tree::hld_index::HldIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// tree.add_child(&tree.root(), 1);
+    /// assert_eq!(HldIndex::from_nary(&tree).len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check if the index has no nodes, which never happens for an index built from an n-ary tree
+    /// # Returns
+    /// True if there are no nodes
+    /// # Example
+    /// ```
+    /// use data_structures::tree::hld_index::HldIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// assert!(!HldIndex::from_nary(&NaryTree::new(0)).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Get the weight of a node
+    /// # Arguments
+    /// * `node`: The node
+    /// # Returns
+    /// The current weight, or None if the node isn't indexed
+    /// # Example
+    /// ```
+    /// use data_structures::tree::hld_index::HldIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let tree = NaryTree::new(-3);
+    /// let index = HldIndex::from_nary(&tree);
+    /// assert_eq!(index.weight(&tree.root()), Some(-3));
+    /// ```
+    pub fn weight(&self, node: &Shared<Vertex<W>>) -> Option<W> {
+        let id = self.id(node)?;
+        self.segments
+            .get(self.position[id])
+            .and_then(|summary| summary.map(|stats| stats.sum))
+    }
+
+    /// Change the weight of a node
+    /// # Arguments
+    /// * `node`: The node
+    /// * `weight`: The new weight
+    /// # Returns
+    /// The old weight, or an error if the node isn't indexed
+    /// # Example
+    /// ```
+    /// use data_structures::error::DataStructureError;
+    /// use data_structures::tree::hld_index::HldIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let tree = NaryTree::new(1);
+    /// let mut index = HldIndex::from_nary(&tree);
+    ///
+    /// assert_eq!(index.update(&tree.root(), 2), Ok(1));
+    /// assert_eq!(index.update(&NaryTree::new(0).root(), 2), Err(DataStructureError::ForeignNode));
+    /// ```
+    pub fn update(&mut self, node: &Shared<Vertex<W>>, weight: W) -> Result<W, DataStructureError> {
+        let id = self.id(node).ok_or(DataStructureError::ForeignNode)?;
+        let old = self.segments.set(self.position[id], single(weight))?;
+        Ok(old.unwrap().sum)
+    }
+
+    /// Sum the weights on the path between two nodes, both included
+    /// # Arguments
+    /// * `u`, `v`: The ends of the path
+    /// # Returns
+    /// The sum, or None if a node isn't indexed
+    /// # Example
+    /// ```
+    /// use data_structures::tree::hld_index::HldIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(1);
+    /// let a = tree.add_child(&tree.root(), 2);
+    /// let b = tree.add_child(&tree.root(), 4);
+    ///
+    /// assert_eq!(HldIndex::from_nary(&tree).path_sum(&a, &b), Some(7));
+    /// ```
+    pub fn path_sum(&self, u: &Shared<Vertex<W>>, v: &Shared<Vertex<W>>) -> Option<W> {
+        self.path(u, v).map(|stats| stats.sum)
+    }
+
+    /// Get the smallest weight on the path between two nodes, both included
+    /// # Arguments
+    /// * `u`, `v`: The ends of the path
+    /// # Returns
+    /// The minimum, or None if a node isn't indexed
+    /// # Example
+    /// ```
+    /// use data_structures::tree::hld_index::HldIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(1);
+    /// let a = tree.add_child(&tree.root(), 2);
+    /// let a1 = tree.add_child(&a, 0);
+    ///
+    /// let index = HldIndex::from_nary(&tree);
+    /// assert_eq!(index.path_min(&a, &tree.root()), Some(1));
+    /// assert_eq!(index.path_min(&a1, &a1), Some(0));
+    /// ```
+    pub fn path_min(&self, u: &Shared<Vertex<W>>, v: &Shared<Vertex<W>>) -> Option<W> {
+        self.path(u, v).map(|stats| stats.min)
+    }
+
+    /// Get the largest weight on the path between two nodes, both included
+    /// # Arguments
+    /// * `u`, `v`: The ends of the path
+    /// # Returns
+    /// The maximum, or None if a node isn't indexed
+    /// # Example
+    /// ```
+    /// use data_structures::tree::hld_index::HldIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(1);
+    /// let a = tree.add_child(&tree.root(), 9);
+    /// let b = tree.add_child(&tree.root(), 3);
+    ///
+    /// assert_eq!(HldIndex::from_nary(&tree).path_max(&tree.root(), &b), Some(3));
+    /// ```
+    pub fn path_max(&self, u: &Shared<Vertex<W>>, v: &Shared<Vertex<W>>) -> Option<W> {
+        self.path(u, v).map(|stats| stats.max)
+    }
+
+    fn id(&self, node: &Shared<Vertex<W>>) -> Option<usize> {
+        self.ids.get(&address(node)).copied()
+    }
+
+    // Summarize the path by climbing chain by chain from the end whose chain starts deeper
+    fn path(&self, u: &Shared<Vertex<W>>, v: &Shared<Vertex<W>>) -> Summary<W> {
+        let (mut u, mut v) = (self.id(u)?, self.id(v)?);
+        let mut summary = None;
+
+        while self.head[u] != self.head[v] {
+            if self.depth[self.head[u]] < self.depth[self.head[v]] {
+                std::mem::swap(&mut u, &mut v);
+            }
+
+            let chain = self
+                .segments
+                .query(self.position[self.head[u]]..self.position[u] + 1)
+                .ok()?;
+            summary = combine(&summary, &chain);
+            u = self.parent[self.head[u]]?;
+        }
+
+        let (start, end) = (
+            self.position[u].min(self.position[v]),
+            self.position[u].max(self.position[v]),
+        );
+        let chain = self.segments.query(start..end + 1).ok()?;
+        combine(&summary, &chain)
+    }
+}
+
+impl<W> fmt::Debug for HldIndex<W> {
+    /// Prints the size only, the nodes are vertexes pointing to the rest of the tree
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HldIndex")
+            .field("len", &self.nodes.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_hld_against_parent_walk() {
+        let mut rng = Lcg::new(21);
+        let mut next = || (rng.next_u64() >> 33) as i64;
+
+        let mut weights = vec![next() % 100 - 50];
+        let mut tree = NaryTree::new(weights[0]);
+        let mut nodes = vec![tree.root()];
+        let mut parents = vec![None];
+
+        for _ in 1..200 {
+            let parent = next() as usize % nodes.len();
+            let weight = next() % 100 - 50;
+            nodes.push(tree.add_child(&nodes[parent], weight));
+            parents.push(Some(parent));
+            weights.push(weight);
+        }
+
+        let mut index = HldIndex::from_nary(&tree);
+        let path = |u: usize, v: usize| {
+            let climb = |mut node: usize| {
+                let mut path = vec![node];
+                while let Some(parent) = parents[node] {
+                    path.push(parent);
+                    node = parent;
+                }
+                path
+            };
+            let (up, vp) = (climb(u), climb(v));
+            let common = *up.iter().find(|node| vp.contains(node)).unwrap();
+            let mut nodes: Vec<usize> = up.iter().take_while(|&&n| n != common).copied().collect();
+            nodes.extend(vp.iter().take_while(|&&n| n != common));
+            nodes.push(common);
+            nodes
+        };
+
+        for round in 0..300 {
+            let (u, v) = (next() as usize % 200, next() as usize % 200);
+            let expected: Vec<i64> = path(u, v).iter().map(|&node| weights[node]).collect();
+
+            assert_eq!(
+                index.path_sum(&nodes[u], &nodes[v]),
+                Some(expected.iter().sum())
+            );
+            assert_eq!(
+                index.path_min(&nodes[u], &nodes[v]),
+                expected.iter().min().copied()
+            );
+            assert_eq!(
+                index.path_max(&nodes[u], &nodes[v]),
+                expected.iter().max().copied()
+            );
+
+            if round % 3 == 0 {
+                let node = next() as usize % 200;
+                let weight = next() % 100 - 50;
+                assert_eq!(index.update(&nodes[node], weight), Ok(weights[node]));
+                weights[node] = weight;
+            }
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! This module implements a lowest common ancestor (LCA) index over the nodes of a tree.
+//! The index is built once from a `NaryTree` or a `BinaryTree`, and then answers which node is the deepest common
+//! ancestor of two nodes, and how many edges separate them, in constant time.
+//!
+//! # Performance
+//! - O(n log n) time and memory to build the index
+//! - O(1) for `lca`, `distance` and `depth`
+//!
+//! # Implementation Details
+//! - The Euler tour of the tree (the nodes in the order a depth-first walk visits them, with repetitions) is recorded
+//!   with the depth of each visit. The LCA of two nodes is the shallowest node of the tour between their first visits.
+//! - That range minimum is answered with a sparse table: for every position and power of two, the shallowest node of
+//!   the window starting there. Any range is covered by two overlapping windows.
+//! - Nodes are recognized by the address of their vertex. The index keeps a pointer to every node, so the addresses
+//!   can't be reused while it exists; the index is a snapshot and doesn't see nodes added to the tree afterwards.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::lca_index::LcaIndex;
+//! use data_structures::tree::nary_tree::NaryTree;
+//!
+//! let mut tree = NaryTree::new("root");
+//! let root = tree.root();
+//! let a = tree.add_child(&root, "a");
+//! let b = tree.add_child(&root, "b");
+//! let a1 = tree.add_child(&a, "a1");
+//! let a2 = tree.add_child(&a, "a2");
+//!
+//! let index = LcaIndex::from_nary(&tree);
+//!
+//! assert_eq!(index.lca(&a1, &a2).unwrap().borrow().read_data(), &Some("a"));
+//! assert_eq!(index.lca(&a1, &b).unwrap().borrow().read_data(), &Some("root"));
+//! assert_eq!(index.distance(&a1, &b), Some(3));
+//! ```
+//!
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::linked_list::shared::{Ptr, Shared};
+use crate::linked_list::vertex::Vertex;
+use crate::tree::binary_tree::BinaryTree;
+use crate::tree::nary_tree::NaryTree;
+
+/// Struct representing a constant-time lowest common ancestor index over the nodes of a tree
+///
+pub struct LcaIndex<T> {
+    nodes: Vec<Shared<Vertex<T>>>, // by id, in pre-order
+    ids: HashMap<usize, usize>,    // vertex address to id
+    depth: Vec<usize>,             // by id
+    first: Vec<usize>,             // first position of each id in the tour
+    table: Vec<Vec<usize>>,        // table[k][i]: shallowest id in tour[i..i + 2^k]
+}
+
+// Identity of a vertex, stable while a pointer to it is kept
+pub(super) fn address<T>(node: &Shared<Vertex<T>>) -> usize {
+    Ptr::as_ptr(node) as *const () as usize
+}
+
+impl<T> LcaIndex<T> {
+    /// Build the index over the nodes of an n-ary tree
+    ///
+    /// # Arguments
+    /// * `tree`: The tree to index
+    ///
+    /// # Returns
+    /// A new LcaIndex instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::lca_index::LcaIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let child = tree.add_child(&tree.root(), 1);
+    ///
+    /// let index = LcaIndex::from_nary(&tree);
+    /// assert_eq!(index.depth(&child), Some(1));
+    /// ```
+    pub fn from_nary(tree: &NaryTree<T>) -> Self {
+        Self::build(Some(tree.root()), |node| tree.children(node).collect())
+    }
+
+    /// Build the index over the nodes of a binary tree
+    ///
+    /// # Arguments
+    /// * `tree`: The tree to index
+    ///
+    /// # Returns
+    /// A new LcaIndex instance, empty if the tree is empty
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    /// use data_structures::tree::lca_index::LcaIndex;
+    ///
+    /// let tree = BinaryTree::from_sorted_slice(&[1, 2, 3]);
+    /// let root = tree.root().unwrap();
+    /// let left = tree.left(&root).unwrap();
+    /// let right = tree.right(&root).unwrap();
+    ///
+    /// let index = LcaIndex::from_binary(&tree);
+    /// assert_eq!(index.lca(&left, &right).unwrap().borrow().read_data(), &Some(2));
+    /// ```
+    pub fn from_binary(tree: &BinaryTree<T>) -> Self {
+        Self::build(tree.root(), |node| {
+            tree.left(node)
+                .into_iter()
+                .chain(tree.right(node))
+                .collect()
+        })
+    }
+
+    /// Get the number of indexed nodes
+    /// # Returns
+    /// The number of nodes of the tree when the index was built
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    /// use data_structures::tree::lca_index::LcaIndex;
+    ///
+    /// let index = LcaIndex::from_binary(&BinaryTree::from_sorted_slice(&[1, 2, 3, 4]));
+    /// assert_eq!(index.len(), 4);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check if the index has no nodes
+    /// # Returns
+    /// True if it was built from an empty tree
+    /// # Example
+    /// ```
+    /// use data_structures::tree::binary_tree::BinaryTree;
+    /// use data_structures::tree::lca_index::LcaIndex;
+    ///
+    /// let index = LcaIndex::from_binary(&BinaryTree::<u8>::new());
+    /// assert!(index.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Get the depth of a node
+    /// # Arguments
+    /// * `node`: The node
+    /// # Returns
+    /// The number of edges between the node and the root, or None if the node isn't indexed
+    /// # Example
+    /// ```
+    /// use data_structures::tree::lca_index::LcaIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let tree = NaryTree::new('r');
+    /// let index = LcaIndex::from_nary(&tree);
+    ///
+    /// assert_eq!(index.depth(&tree.root()), Some(0));
+    /// assert_eq!(index.depth(&NaryTree::new('x').root()), None);
+    /// ```
+    pub fn depth(&self, node: &Shared<Vertex<T>>) -> Option<usize> {
+        self.id(node).map(|id| self.depth[id])
+    }
+
+    /// Find the lowest common ancestor of two nodes
+    /// # Arguments
+    /// * `u`, `v`: The nodes
+    /// # Returns
+    /// The deepest node that has both nodes in its subtree (a node is in its own subtree), or None if a node isn't indexed
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::shared::Ptr;
+    /// use data_structures::tree::lca_index::LcaIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let child = tree.add_child(&tree.root(), 1);
+    /// let grandchild = tree.add_child(&child, 2);
+    ///
+    /// let index = LcaIndex::from_nary(&tree);
+    /// assert!(Ptr::ptr_eq(&index.lca(&child, &grandchild).unwrap(), &child));
+    /// ```
+    pub fn lca(&self, u: &Shared<Vertex<T>>, v: &Shared<Vertex<T>>) -> Option<Shared<Vertex<T>>> {
+        let id = self.lca_id(self.id(u)?, self.id(v)?);
+        Some(self.nodes[id].clone())
+    }
+
+    /// Get the distance between two nodes
+    /// # Arguments
+    /// * `u`, `v`: The nodes
+    /// # Returns
+    /// The number of edges on the path between the nodes, or None if a node isn't indexed
+    /// # Example
+    /// ```
+    /// use data_structures::tree::lca_index::LcaIndex;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let a = tree.add_child(&tree.root(), 1);
+    /// let b = tree.add_child(&tree.root(), 2);
+    /// let b1 = tree.add_child(&b, 3);
+    ///
+    /// let index = LcaIndex::from_nary(&tree);
+    /// assert_eq!(index.distance(&a, &b1), Some(3));
+    /// assert_eq!(index.distance(&b1, &b1), Some(0));
+    /// ```
+    pub fn distance(&self, u: &Shared<Vertex<T>>, v: &Shared<Vertex<T>>) -> Option<usize> {
+        let (u, v) = (self.id(u)?, self.id(v)?);
+        let ancestor = self.lca_id(u, v);
+        Some(self.depth[u] + self.depth[v] - 2 * self.depth[ancestor])
+    }
+
+    fn id(&self, node: &Shared<Vertex<T>>) -> Option<usize> {
+        self.ids.get(&address(node)).copied()
+    }
+
+    fn shallower(&self, a: usize, b: usize) -> usize {
+        if self.depth[a] <= self.depth[b] {
+            a
+        } else {
+            b
+        }
+    }
+
+    fn lca_id(&self, u: usize, v: usize) -> usize {
+        let (start, end) = {
+            let (a, b) = (self.first[u], self.first[v]);
+            (a.min(b), a.max(b) + 1)
+        };
+
+        // Two windows of the largest power of two that fits cover the range
+        let level = (end - start).ilog2() as usize;
+        let window = 1 << level;
+        self.shallower(self.table[level][start], self.table[level][end - window])
+    }
+
+    // Record the Euler tour with an explicit stack, then fill the sparse table over it
+    fn build(
+        root: Option<Shared<Vertex<T>>>,
+        children: impl Fn(&Shared<Vertex<T>>) -> Vec<Shared<Vertex<T>>>,
+    ) -> Self {
+        let mut index = LcaIndex {
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            depth: Vec::new(),
+            first: Vec::new(),
+            table: Vec::new(),
+        };
+
+        let mut tour = Vec::new();
+        // Each entry is a node id with its children still to visit, in reverse order
+        let mut stack: Vec<(usize, Vec<Shared<Vertex<T>>>)> = Vec::new();
+
+        let visit = |index: &mut LcaIndex<T>,
+                     node: Shared<Vertex<T>>,
+                     depth: usize,
+                     tour: &mut Vec<usize>| {
+            let id = index.nodes.len();
+            let mut pending = children(&node);
+            pending.reverse();
+
+            index.ids.insert(address(&node), id);
+            index.nodes.push(node);
+            index.depth.push(depth);
+            index.first.push(tour.len());
+            tour.push(id);
+            (id, pending)
+        };
+
+        if let Some(root) = root {
+            let entry = visit(&mut index, root, 0, &mut tour);
+            stack.push(entry);
+        }
+
+        while let Some((id, pending)) = stack.last_mut() {
+            let id = *id;
+            match pending.pop() {
+                Some(child) => {
+                    let depth = index.depth[id] + 1;
+                    let entry = visit(&mut index, child, depth, &mut tour);
+                    stack.push(entry);
+                }
+                None => {
+                    stack.pop();
+                    // Back in the parent, which is visited again
+                    if let Some((parent, _)) = stack.last() {
+                        tour.push(*parent);
+                    }
+                }
+            }
+        }
+
+        let len = tour.len();
+        index.table.push(tour);
+
+        let mut window = 1;
+        while 2 * window <= len {
+            let previous = index.table.last().unwrap();
+            let level = (0..=len - 2 * window)
+                .map(|start| index.shallower(previous[start], previous[start + window]))
+                .collect();
+            index.table.push(level);
+            window *= 2;
+        }
+
+        index
+    }
+}
+
+impl<T> fmt::Debug for LcaIndex<T> {
+    /// Prints the size only, the nodes are vertexes pointing to the rest of the tree
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LcaIndex")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    #[test]
+    fn test_lca_against_parent_walk() {
+        // Random tree: every node hangs from an earlier one
+        let mut tree = NaryTree::new(0usize);
+        let mut nodes = vec![tree.root()];
+        let mut parents = vec![None];
+        let mut rng = Lcg::new(9);
+
+        for value in 1..300 {
+            let seed = rng.next_u64();
+            let parent = (seed >> 33) as usize % nodes.len();
+            nodes.push(tree.add_child(&nodes[parent], value));
+            parents.push(Some(parent));
+        }
+
+        let index = LcaIndex::from_nary(&tree);
+        let ancestors = |mut node: usize| {
+            let mut path = vec![node];
+            while let Some(parent) = parents[node] {
+                path.push(parent);
+                node = parent;
+            }
+            path
+        };
+
+        for u in (0..300).step_by(7) {
+            for v in (0..300).step_by(11) {
+                let (path_u, path_v) = (ancestors(u), ancestors(v));
+                let common = *path_u.iter().find(|node| path_v.contains(node)).unwrap();
+                let distance = path_u.iter().position(|&node| node == common).unwrap()
+                    + path_v.iter().position(|&node| node == common).unwrap();
+
+                let lca = index.lca(&nodes[u], &nodes[v]).unwrap();
+                assert_eq!(lca.borrow().read_data(), &Some(common));
+                assert_eq!(index.distance(&nodes[u], &nodes[v]), Some(distance));
+            }
+        }
+    }
+
+    #[test]
+    fn test_lca_binary_tree() {
+        let tree = BinaryTree::from_sorted_slice(&[1, 2, 3, 4, 5, 6, 7]);
+        let index = LcaIndex::from_binary(&tree);
+        let root = tree.root().unwrap();
+        let left = tree.left(&root).unwrap();
+        let left_left = tree.left(&left).unwrap();
+        let right_right = tree.right(&tree.right(&root).unwrap()).unwrap();
+
+        assert_eq!(index.len(), 7);
+        assert_eq!(
+            index.lca(&left_left, &left).unwrap().borrow().read_data(),
+            &Some(2)
+        );
+        assert_eq!(index.distance(&left_left, &right_right), Some(4));
+        assert_eq!(
+            index.lca(&root, &root).unwrap().borrow().read_data(),
+            &Some(4)
+        );
+    }
+}
@@ -0,0 +1,968 @@
+//! This module implements a left-leaning red-black tree (LLRB), the red-black variant described by Sedgewick, as an
+//! ordered map. Red links always lean left, which keeps the rebalancing code down to three local operations: rotate
+//! left, rotate right and flip colors. The tree can report each of them to a hook as it happens, and can be exported
+//! to Graphviz, so the rebalancing can be watched step by step.
+//!
+//! # Performance
+//! - O(log n) for `insert`, `get` and `remove`, the height is at most 2 log2(n + 1)
+//! - O(n) for `to_dot` and for a full iteration
+//!
+//! # Implementation Details
+//! - Nodes are `Vertex` instances handled through `Shared<Vertex<T>>` pointers, using the `Left` and `Right` pointers
+//!   for the children. The color of a node is the color of the link coming from its parent.
+//! - Insertion and deletion are recursive and fix the tree up on the way back, following Sedgewick's algorithms.
+//! - The hook is called with the operation and the key of the node the operation was applied to: the node that moves
+//!   down for a rotation, and the parent whose links are recolored for a flip.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::llrb_tree::{LlrbTree, Rebalance};
+//! use std::sync::{Arc, Mutex};
+//!
+//! let events = Arc::new(Mutex::new(Vec::new()));
+//! let log = events.clone();
+//!
+//! let mut tree = LlrbTree::new();
+//! tree.set_hook(move |event: Rebalance, key: &i32| log.lock().unwrap().push((event, *key)));
+//!
+//! for key in 1..=3 {
+//!     tree.insert(key, key * 10);
+//! }
+//!
+//! assert_eq!(tree.get(&2), Some(20));
+//! assert_eq!(*events.lock().unwrap(), vec![(Rebalance::RotateLeft, 1), (Rebalance::FlipColors, 2)]);
+//! assert!(tree.to_dot().contains("n0 [label=\"2\"]"));
+//! ```
+//!
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Write;
+use std::mem;
+
+use crate::linked_list::shared::{Shared, ThreadSafe};
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+/// Rebalancing operation reported to the hook of a tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rebalance {
+    /// A right-leaning red link was turned into a left-leaning one
+    RotateLeft,
+    /// A left-leaning red link was turned into a right-leaning one
+    RotateRight,
+    /// The colors of a node and its two children were inverted
+    FlipColors,
+}
+
+/// Receiver of the rebalancing operations of a tree
+/// Closures with the signature `FnMut(Rebalance, &K)` can be used as hooks too.
+pub trait RebalanceHook<K>: ThreadSafe {
+    fn on_rebalance(&mut self, event: Rebalance, key: &K);
+}
+
+impl<K, F: FnMut(Rebalance, &K) + ThreadSafe> RebalanceHook<K> for F {
+    fn on_rebalance(&mut self, event: Rebalance, key: &K) {
+        self(event, key)
+    }
+}
+
+// Data of a node, the color is the one of the link from the parent
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    red: bool,
+}
+
+type Link<K, V> = Shared<Vertex<Entry<K, V>>>;
+
+/// Struct representing a left-leaning red-black tree mapping ordered keys to values
+///
+pub struct LlrbTree<K, V> {
+    root: Option<Link<K, V>>,
+    size: usize,
+
+    hook: Option<Box<dyn RebalanceHook<K>>>,
+}
+
+/// In-order iterator over the entries of a tree
+pub struct Iter<K, V> {
+    stack: Vec<Link<K, V>>,
+    current: Option<Link<K, V>>,
+}
+
+fn child<K, V>(node: &Link<K, V>, side: PointerName) -> Option<Link<K, V>> {
+    node.borrow().get_pointer(side)
+}
+
+fn set_child<K, V>(node: &Link<K, V>, side: PointerName, child: Option<&Link<K, V>>) {
+    node.borrow_mut().set_connection(side, child);
+}
+
+fn is_red<K, V>(node: Option<&Link<K, V>>) -> bool {
+    node.is_some_and(|node| node.borrow().read_data().as_ref().unwrap().red)
+}
+
+fn set_red<K, V>(node: &Link<K, V>, red: bool) {
+    node.borrow_mut().data_mut().unwrap().red = red;
+}
+
+// Color of the left child of the left child, used to detect two red links in a row
+fn is_left_left_red<K, V>(node: &Link<K, V>) -> bool {
+    is_red(
+        child(node, PointerName::Left)
+            .and_then(|left| child(&left, PointerName::Left))
+            .as_ref(),
+    )
+}
+
+// Color of the left child of the right child
+fn is_right_left_red<K, V>(node: &Link<K, V>) -> bool {
+    is_red(
+        child(node, PointerName::Right)
+            .and_then(|right| child(&right, PointerName::Left))
+            .as_ref(),
+    )
+}
+
+fn compare<K: Ord, V>(key: &K, node: &Link<K, V>) -> Ordering {
+    key.cmp(&node.borrow().read_data().as_ref().unwrap().key)
+}
+
+impl<K: Clone, V: Clone> Iterator for Iter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.current.take() {
+            self.current = child(&node, PointerName::Left);
+            self.stack.push(node);
+        }
+
+        let node = self.stack.pop()?;
+        self.current = child(&node, PointerName::Right);
+
+        let vertex = node.borrow();
+        let entry = vertex.read_data().as_ref().unwrap();
+        Some((entry.key.clone(), entry.value.clone()))
+    }
+}
+
+impl<K: Ord, V> LlrbTree<K, V> {
+    /// Create a new empty tree, without a hook
+    ///
+    /// # Returns
+    /// A new LlrbTree instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTree;
+    ///
+    /// let tree: LlrbTree<i32, &str> = LlrbTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        LlrbTree {
+            root: None,
+            size: 0,
+            hook: None,
+        }
+    }
+
+    /// Get the number of entries in the tree
+    /// # Returns
+    /// The number of keys stored
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTree;
+    ///
+    /// let mut tree = LlrbTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(1, 'b');
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the tree is empty
+    /// # Returns
+    /// True if the tree has no entries
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTree;
+    ///
+    /// let tree: LlrbTree<u8, u8> = LlrbTree::default();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Set the hook called for every rotation and color flip, replacing the previous one
+    /// # Arguments
+    /// * `hook`: The receiver of the operations, called with the operation and the key of the node it was applied to
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::{LlrbTree, Rebalance};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let flips = Arc::new(Mutex::new(0));
+    /// let counter = flips.clone();
+    ///
+    /// let mut tree = LlrbTree::new();
+    /// tree.set_hook(move |event: Rebalance, _: &char| {
+    ///     if event == Rebalance::FlipColors {
+    ///         *counter.lock().unwrap() += 1;
+    ///     }
+    /// });
+    ///
+    /// tree.insert('b', ());
+    /// tree.insert('a', ());
+    /// tree.insert('c', ());
+    /// assert_eq!(*flips.lock().unwrap(), 1);
+    /// ```
+    pub fn set_hook(&mut self, hook: impl RebalanceHook<K> + 'static) {
+        self.hook = Some(Box::new(hook));
+    }
+
+    /// Remove the hook, so the operations are no longer reported
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::{LlrbTree, Rebalance};
+    ///
+    /// let mut tree = LlrbTree::new();
+    /// tree.set_hook(|_: Rebalance, _: &u8| panic!("not reported"));
+    /// tree.clear_hook();
+    ///
+    /// tree.insert(1, ());
+    /// tree.insert(2, ());
+    /// ```
+    pub fn clear_hook(&mut self) {
+        self.hook = None;
+    }
+
+    /// Insert a key with its value, replacing the value if the key is already present
+    /// # Arguments
+    /// * `key`: The key of the entry
+    /// * `value`: The value of the entry
+    /// # Returns
+    /// The previous value of the key, or None if it wasn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTree;
+    ///
+    /// let mut tree = LlrbTree::new();
+    /// assert_eq!(tree.insert("one", 1), None);
+    /// assert_eq!(tree.insert("one", 2), Some(1));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut previous = None;
+        let root = self.insert_at(self.root.clone(), key, value, &mut previous);
+
+        set_red(&root, false);
+        self.root = Some(root);
+
+        if previous.is_none() {
+            self.size += 1;
+        }
+        previous
+    }
+
+    /// Check if a key is in the tree
+    /// # Arguments
+    /// * `key`: The key to look for
+    /// # Returns
+    /// True if the key is present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTree;
+    ///
+    /// let mut tree = LlrbTree::new();
+    /// tree.insert(7, ());
+    /// assert!(tree.contains_key(&7));
+    /// assert!(!tree.contains_key(&8));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Get the value of a key
+    /// # Arguments
+    /// * `key`: The key to look for
+    /// # Returns
+    /// A clone of the value, or None if the key isn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTree;
+    ///
+    /// let mut tree = LlrbTree::new();
+    /// tree.insert(1, "one".to_string());
+    /// assert_eq!(tree.get(&1), Some("one".to_string()));
+    /// assert_eq!(tree.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.find(key)
+            .map(|node| node.borrow().read_data().as_ref().unwrap().value.clone())
+    }
+
+    /// Remove a key from the tree
+    /// # Arguments
+    /// * `key`: The key to remove
+    /// # Returns
+    /// The value of the key, or None if it wasn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTree;
+    ///
+    /// let mut tree: LlrbTree<i32, i32> = (0..10).map(|key| (key, key * key)).collect();
+    ///
+    /// assert_eq!(tree.remove(&3), Some(9));
+    /// assert_eq!(tree.remove(&3), None);
+    /// assert_eq!(tree.len(), 9);
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.contains_key(key) {
+            return None;
+        }
+
+        let root = self.root.take().unwrap();
+        if !is_red(child(&root, PointerName::Left).as_ref())
+            && !is_red(child(&root, PointerName::Right).as_ref())
+        {
+            set_red(&root, true);
+        }
+
+        let mut removed = None;
+        self.root = self.remove_at(root, key, &mut removed);
+        if let Some(root) = &self.root {
+            set_red(root, false);
+        }
+
+        self.size -= 1;
+        removed
+    }
+
+    /// Get the height of the tree, which is the number of nodes in the longest path from the root to a leaf
+    /// # Returns
+    /// The height of the tree, 0 for an empty tree
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTree;
+    ///
+    /// let tree: LlrbTree<u32, ()> = (0..1000).map(|key| (key, ())).collect();
+    /// assert!(tree.height() <= 20);
+    /// ```
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut stack: Vec<_> = self.root.iter().map(|root| (root.clone(), 1)).collect();
+
+        while let Some((node, depth)) = stack.pop() {
+            height = height.max(depth);
+            stack.extend(child(&node, PointerName::Left).map(|left| (left, depth + 1)));
+            stack.extend(child(&node, PointerName::Right).map(|right| (right, depth + 1)));
+        }
+
+        height
+    }
+
+    /// Get an in-order iterator over the entries of the tree
+    /// # Returns
+    /// A lazy iterator yielding clones of the keys and values, by increasing key
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTree;
+    ///
+    /// let tree: LlrbTree<char, u8> = [('b', 2), ('c', 3), ('a', 1)].into_iter().collect();
+    /// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![('a', 1), ('b', 2), ('c', 3)]);
+    /// ```
+    pub fn iter(&self) -> Iter<K, V> {
+        Iter {
+            stack: Vec::new(),
+            current: self.root.clone(),
+        }
+    }
+
+    /// Export the tree in the Graphviz DOT language
+    /// Nodes are labeled with the Debug output of their keys, and red nodes and the red links leading to them are
+    /// drawn in red. Nodes are numbered in pre-order, so the root is always `n0`.
+    /// # Returns
+    /// The description of a directed graph, to be rendered with `dot -Tsvg` or a similar tool
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTree;
+    ///
+    /// let mut tree = LlrbTree::new();
+    /// tree.insert(2, ());
+    /// tree.insert(1, ());
+    ///
+    /// assert_eq!(
+    ///     tree.to_dot(),
+    ///     "digraph LlrbTree {\n    n0 [label=\"2\"];\n    n1 [label=\"1\", color=red, fontcolor=red];\n    n0 -> n1 [color=red];\n}\n"
+    /// );
+    /// ```
+    pub fn to_dot(&self) -> String
+    where
+        K: fmt::Debug,
+    {
+        let mut dot = String::from("digraph LlrbTree {\n");
+        let mut next_id = 0;
+        let mut stack: Vec<(Link<K, V>, Option<usize>)> =
+            self.root.iter().map(|root| (root.clone(), None)).collect();
+
+        while let Some((node, parent)) = stack.pop() {
+            let id = next_id;
+            next_id += 1;
+
+            let red = {
+                let vertex = node.borrow();
+                let entry = vertex.read_data().as_ref().unwrap();
+                let color = if entry.red {
+                    ", color=red, fontcolor=red"
+                } else {
+                    ""
+                };
+                let label = format!("{:?}", entry.key).replace('"', "\\\"");
+                writeln!(dot, "    n{} [label=\"{}\"{}];", id, label, color).unwrap();
+                entry.red
+            };
+
+            if let Some(parent) = parent {
+                let color = if red { " [color=red]" } else { "" };
+                writeln!(dot, "    n{} -> n{}{};", parent, id, color).unwrap();
+            }
+
+            // The right child is pushed first so the left subtree gets the lower numbers
+            stack.extend(child(&node, PointerName::Right).map(|right| (right, Some(id))));
+            stack.extend(child(&node, PointerName::Left).map(|left| (left, Some(id))));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    // Find the node holding a key
+    fn find(&self, key: &K) -> Option<Link<K, V>> {
+        let mut current = self.root.clone();
+
+        while let Some(node) = current {
+            current = match compare(key, &node) {
+                Ordering::Less => child(&node, PointerName::Left),
+                Ordering::Greater => child(&node, PointerName::Right),
+                Ordering::Equal => return Some(node),
+            };
+        }
+
+        None
+    }
+
+    fn report(&mut self, event: Rebalance, node: &Link<K, V>) {
+        if let Some(hook) = self.hook.as_mut() {
+            hook.on_rebalance(event, &node.borrow().read_data().as_ref().unwrap().key);
+        }
+    }
+
+    // Turn the red right link of a node into a left one, returning the new root of the subtree
+    fn rotate_left(&mut self, node: Link<K, V>) -> Link<K, V> {
+        let right = child(&node, PointerName::Right).unwrap();
+        set_child(
+            &node,
+            PointerName::Right,
+            child(&right, PointerName::Left).as_ref(),
+        );
+        set_child(&right, PointerName::Left, Some(&node));
+
+        set_red(&right, is_red(Some(&node)));
+        set_red(&node, true);
+
+        self.report(Rebalance::RotateLeft, &node);
+        right
+    }
+
+    // Turn the red left link of a node into a right one, returning the new root of the subtree
+    fn rotate_right(&mut self, node: Link<K, V>) -> Link<K, V> {
+        let left = child(&node, PointerName::Left).unwrap();
+        set_child(
+            &node,
+            PointerName::Left,
+            child(&left, PointerName::Right).as_ref(),
+        );
+        set_child(&left, PointerName::Right, Some(&node));
+
+        set_red(&left, is_red(Some(&node)));
+        set_red(&node, true);
+
+        self.report(Rebalance::RotateRight, &node);
+        left
+    }
+
+    // Invert the colors of a node and its two children
+    fn flip_colors(&mut self, node: &Link<K, V>) {
+        set_red(node, !is_red(Some(node)));
+        for side in [PointerName::Left, PointerName::Right] {
+            let child = child(node, side).unwrap();
+            set_red(&child, !is_red(Some(&child)));
+        }
+
+        self.report(Rebalance::FlipColors, node);
+    }
+
+    // Restore the invariants on the way back from an insertion or a deletion
+    fn balance(&mut self, mut node: Link<K, V>) -> Link<K, V> {
+        if is_red(child(&node, PointerName::Right).as_ref())
+            && !is_red(child(&node, PointerName::Left).as_ref())
+        {
+            node = self.rotate_left(node);
+        }
+        if is_red(child(&node, PointerName::Left).as_ref()) && is_left_left_red(&node) {
+            node = self.rotate_right(node);
+        }
+        if is_red(child(&node, PointerName::Left).as_ref())
+            && is_red(child(&node, PointerName::Right).as_ref())
+        {
+            self.flip_colors(&node);
+        }
+
+        node
+    }
+
+    // Make the left child or one of its children red, so a deletion can go down the left side
+    fn move_red_left(&mut self, mut node: Link<K, V>) -> Link<K, V> {
+        self.flip_colors(&node);
+
+        if is_right_left_red(&node) {
+            let right = self.rotate_right(child(&node, PointerName::Right).unwrap());
+            set_child(&node, PointerName::Right, Some(&right));
+            node = self.rotate_left(node);
+            self.flip_colors(&node);
+        }
+
+        node
+    }
+
+    // Make the right child or one of its children red, so a deletion can go down the right side
+    fn move_red_right(&mut self, mut node: Link<K, V>) -> Link<K, V> {
+        self.flip_colors(&node);
+
+        if is_left_left_red(&node) {
+            node = self.rotate_right(node);
+            self.flip_colors(&node);
+        }
+
+        node
+    }
+
+    fn insert_at(
+        &mut self,
+        node: Option<Link<K, V>>,
+        key: K,
+        value: V,
+        previous: &mut Option<V>,
+    ) -> Link<K, V> {
+        let Some(node) = node else {
+            return Vertex::new(Entry {
+                key,
+                value,
+                red: true,
+            });
+        };
+
+        match compare(&key, &node) {
+            Ordering::Less => {
+                let left = self.insert_at(child(&node, PointerName::Left), key, value, previous);
+                set_child(&node, PointerName::Left, Some(&left));
+            }
+            Ordering::Greater => {
+                let right = self.insert_at(child(&node, PointerName::Right), key, value, previous);
+                set_child(&node, PointerName::Right, Some(&right));
+            }
+            Ordering::Equal => {
+                *previous = Some(mem::replace(
+                    &mut node.borrow_mut().data_mut().unwrap().value,
+                    value,
+                ));
+            }
+        }
+
+        self.balance(node)
+    }
+
+    // Remove the smallest entry of a subtree, returning the new root of the subtree
+    fn remove_min_at(
+        &mut self,
+        mut node: Link<K, V>,
+        removed: &mut Option<(K, V)>,
+    ) -> Option<Link<K, V>> {
+        if child(&node, PointerName::Left).is_none() {
+            let entry = node.borrow_mut().clear().unwrap();
+            *removed = Some((entry.key, entry.value));
+            return None;
+        }
+
+        if !is_red(child(&node, PointerName::Left).as_ref()) && !is_left_left_red(&node) {
+            node = self.move_red_left(node);
+        }
+
+        let left = self.remove_min_at(child(&node, PointerName::Left).unwrap(), removed);
+        set_child(&node, PointerName::Left, left.as_ref());
+
+        Some(self.balance(node))
+    }
+
+    // Remove a key known to be in the subtree, returning the new root of the subtree
+    fn remove_at(
+        &mut self,
+        mut node: Link<K, V>,
+        key: &K,
+        removed: &mut Option<V>,
+    ) -> Option<Link<K, V>> {
+        if compare(key, &node) == Ordering::Less {
+            if !is_red(child(&node, PointerName::Left).as_ref()) && !is_left_left_red(&node) {
+                node = self.move_red_left(node);
+            }
+
+            let left = self.remove_at(child(&node, PointerName::Left).unwrap(), key, removed);
+            set_child(&node, PointerName::Left, left.as_ref());
+        } else {
+            if is_red(child(&node, PointerName::Left).as_ref()) {
+                node = self.rotate_right(node);
+            }
+
+            if compare(key, &node) == Ordering::Equal && child(&node, PointerName::Right).is_none()
+            {
+                *removed = Some(node.borrow_mut().clear().unwrap().value);
+                return None;
+            }
+
+            if !is_red(child(&node, PointerName::Right).as_ref()) && !is_right_left_red(&node) {
+                node = self.move_red_right(node);
+            }
+
+            if compare(key, &node) == Ordering::Equal {
+                // Replace the entry with its successor, which is removed from the right subtree
+                let mut successor = None;
+                let right =
+                    self.remove_min_at(child(&node, PointerName::Right).unwrap(), &mut successor);
+                set_child(&node, PointerName::Right, right.as_ref());
+
+                let (successor_key, successor_value) = successor.unwrap();
+                let mut vertex = node.borrow_mut();
+                let entry = vertex.data_mut().unwrap();
+                entry.key = successor_key;
+                *removed = Some(mem::replace(&mut entry.value, successor_value));
+            } else {
+                let right = self.remove_at(child(&node, PointerName::Right).unwrap(), key, removed);
+                set_child(&node, PointerName::Right, right.as_ref());
+            }
+        }
+
+        Some(self.balance(node))
+    }
+}
+
+impl<K: Ord, V> Default for LlrbTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for LlrbTree<K, V> {
+    /// Builds the tree in one pass with a `LlrbTreeBuilder`, the last value of a repeated key winning
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        LlrbTreeBuilder::new().entries(iter).build()
+    }
+}
+
+/// Builder of a tree from many entries at once
+/// Instead of n insertions, each walking down the tree and rebalancing it, the entries are sorted and the tree is laid
+/// out directly in its balanced shape, in O(n) once sorted. No rebalancing happens, so no hook is involved.
+///
+#[derive(Debug, Clone)]
+pub struct LlrbTreeBuilder<K, V> {
+    entries: Vec<(K, V)>,
+}
+
+impl<K: Ord, V> LlrbTreeBuilder<K, V> {
+    /// Create a builder without entries
+    ///
+    /// # Returns
+    /// A new LlrbTreeBuilder instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTreeBuilder;
+    ///
+    /// let tree = LlrbTreeBuilder::<i32, ()>::new().build();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        LlrbTreeBuilder {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add an entry
+    /// # Arguments
+    /// * `key`: The key
+    /// * `value`: The value, replacing the value of an earlier entry with the same key
+    /// # Returns
+    /// The updated builder
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTreeBuilder;
+    ///
+    /// let tree = LlrbTreeBuilder::new().entry(1, "a").entry(1, "b").build();
+    /// assert_eq!(tree.get(&1), Some("b"));
+    /// ```
+    pub fn entry(mut self, key: K, value: V) -> Self {
+        self.entries.push((key, value));
+        self
+    }
+
+    /// Add many entries
+    /// # Arguments
+    /// * `entries`: The entries, in any order; sorted input is cheaper to build from
+    /// # Returns
+    /// The updated builder
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTreeBuilder;
+    ///
+    /// let tree = LlrbTreeBuilder::new().entries((0..100).map(|key| (key, key * 2))).build();
+    /// assert_eq!(tree.len(), 100);
+    /// assert_eq!(tree.get(&21), Some(42));
+    /// ```
+    pub fn entries(mut self, entries: impl IntoIterator<Item = (K, V)>) -> Self {
+        self.entries.extend(entries);
+        self
+    }
+
+    /// Build the tree
+    /// # Returns
+    /// A balanced tree holding the entries
+    /// # Example
+    /// ```
+    /// use data_structures::tree::llrb_tree::LlrbTreeBuilder;
+    ///
+    /// let tree = LlrbTreeBuilder::new().entries((0..1000).map(|key| (key, ()))).build();
+    /// // Black height 9, with red links in the lower levels only
+    /// assert!(tree.height() <= 11);
+    /// ```
+    pub fn build(mut self) -> LlrbTree<K, V> {
+        // Stable sort, then keep the last entry of each key
+        self.entries.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut entries: Vec<(K, V)> = Vec::with_capacity(self.entries.len());
+        for (key, value) in self.entries {
+            match entries.last_mut() {
+                Some(last) if last.0 == key => last.1 = value,
+                _ => entries.push((key, value)),
+            }
+        }
+
+        // Largest black height whose 2-3 trees can hold every entry, so few 3-nodes are needed
+        let size = entries.len();
+        let black_height = (size + 1).ilog2();
+
+        let root = build_23(&mut entries.into_iter(), size, black_height);
+        if let Some(root) = &root {
+            set_red(root, false);
+        }
+
+        LlrbTree {
+            root,
+            size,
+            hook: None,
+        }
+    }
+}
+
+impl<K: Ord, V> Default for LlrbTreeBuilder<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Lay out the next `size` sorted entries as a 2-3 tree with the given black height, written as an LLRB tree: a
+// 2-node is a black node, a 3-node a black node with a red left child. The black height must be able to hold `size`
+// entries, between 2^h - 1 and 3^h - 1.
+fn build_23<K, V>(
+    entries: &mut impl Iterator<Item = (K, V)>,
+    size: usize,
+    black_height: u32,
+) -> Option<Link<K, V>> {
+    if size == 0 {
+        return None;
+    }
+
+    let node = |entries: &mut dyn Iterator<Item = (K, V)>, red: bool| {
+        let (key, value) = entries.next().unwrap();
+        Vertex::new(Entry { key, value, red })
+    };
+    let child_max = 3usize.saturating_pow(black_height - 1) - 1;
+
+    if size - 1 <= 2 * child_max {
+        // 2-node, the children sharing the other entries evenly
+        let rest = size - 1;
+        let left = build_23(entries, rest / 2, black_height - 1);
+        let middle = node(entries, false);
+        let right = build_23(entries, rest - rest / 2, black_height - 1);
+
+        set_child(&middle, PointerName::Left, left.as_ref());
+        set_child(&middle, PointerName::Right, right.as_ref());
+        Some(middle)
+    } else {
+        // 3-node, the three children sharing the other entries evenly
+        let rest = size - 2;
+        let first = rest / 3;
+        let second = (rest - first) / 2;
+
+        let left = build_23(entries, first, black_height - 1);
+        let low = node(entries, true);
+        let middle = build_23(entries, second, black_height - 1);
+        let high = node(entries, false);
+        let right = build_23(entries, rest - first - second, black_height - 1);
+
+        set_child(&low, PointerName::Left, left.as_ref());
+        set_child(&low, PointerName::Right, middle.as_ref());
+        set_child(&high, PointerName::Left, Some(&low));
+        set_child(&high, PointerName::Right, right.as_ref());
+        Some(high)
+    }
+}
+
+impl<K: Ord + Clone + fmt::Debug, V: Clone + fmt::Debug> fmt::Debug for LlrbTree<K, V> {
+    /// Prints the entries as a map, by increasing key, instead of the web of vertex pointers
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use std::sync::{Arc, Mutex};
+
+    // Check the LLRB invariants below a node, returning its black height
+    fn check<K: Ord, V>(node: Option<&Link<K, V>>, parent_red: bool) -> usize {
+        let Some(node) = node else {
+            return 1;
+        };
+
+        let red = is_red(Some(node));
+        let left = child(node, PointerName::Left);
+        let right = child(node, PointerName::Right);
+
+        assert!(!is_red(right.as_ref()), "red right link");
+        assert!(!(red && parent_red), "two red links in a row");
+        if let Some(left) = &left {
+            assert_eq!(
+                compare(&left.borrow().read_data().as_ref().unwrap().key, node),
+                Ordering::Less
+            );
+        }
+        if let Some(right) = &right {
+            assert_eq!(
+                compare(&right.borrow().read_data().as_ref().unwrap().key, node),
+                Ordering::Greater
+            );
+        }
+
+        let left_height = check(left.as_ref(), red);
+        let right_height = check(right.as_ref(), red);
+        assert_eq!(left_height, right_height, "unbalanced black height");
+
+        left_height + usize::from(!red)
+    }
+
+    #[test]
+    fn test_llrb_invariants() {
+        let mut tree = LlrbTree::new();
+        let mut reference = std::collections::BTreeMap::new();
+        let mut rng = Lcg::new(42);
+
+        for _ in 0..2000 {
+            let seed = rng.next_u64();
+            let key = (seed >> 33) % 200;
+
+            if (seed >> 20).is_multiple_of(3) {
+                assert_eq!(tree.remove(&key), reference.remove(&key));
+            } else {
+                assert_eq!(tree.insert(key, seed), reference.insert(key, seed));
+            }
+
+            assert!(!is_red(tree.root.as_ref()));
+            check(tree.root.as_ref(), false);
+            assert_eq!(tree.len(), reference.len());
+        }
+
+        assert!(tree.iter().eq(reference.clone()));
+        for key in reference.keys() {
+            tree.remove(key);
+        }
+        assert!(tree.is_empty());
+        assert!(tree.root.is_none());
+    }
+
+    #[test]
+    fn test_llrb_builder_invariants() {
+        for size in 0..300u32 {
+            // Reversed, with every key twice, the second value winning
+            let entries = (0..size).rev().flat_map(|key| [(key, 0), (key, key)]);
+            let mut tree = LlrbTreeBuilder::new().entries(entries).build();
+
+            assert!(!is_red(tree.root.as_ref()));
+            check(tree.root.as_ref(), false);
+            assert_eq!(tree.len(), size as usize);
+            assert!(tree.iter().eq((0..size).map(|key| (key, key))));
+
+            // The built tree keeps working as a regular one
+            tree.insert(size, size);
+            tree.remove(&0);
+            check(tree.root.as_ref(), false);
+        }
+    }
+
+    #[test]
+    fn test_llrb_hook() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = events.clone();
+
+        let mut tree = LlrbTree::new();
+        tree.set_hook(move |event: Rebalance, key: &u32| log.lock().unwrap().push((event, *key)));
+
+        // Ascending insertions lean right, so each one is fixed with a rotation
+        tree.insert(1, ());
+        tree.insert(2, ());
+        assert_eq!(*events.lock().unwrap(), vec![(Rebalance::RotateLeft, 1)]);
+
+        // Removing the root of a 3-node tree goes through a flip on the way down and back up
+        events.lock().unwrap().clear();
+        tree.insert(3, ());
+        tree.remove(&2);
+        assert!(events.lock().unwrap().contains(&(Rebalance::FlipColors, 2)));
+        assert_eq!(
+            tree.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+
+        tree.clear_hook();
+        events.lock().unwrap().clear();
+        tree.insert(4, ());
+        assert!(events.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_llrb_to_dot() {
+        let tree: LlrbTree<&str, ()> = ["b", "a", "c"].into_iter().map(|key| (key, ())).collect();
+
+        assert_eq!(
+            tree.to_dot(),
+            "digraph LlrbTree {\n    n0 [label=\"\\\"b\\\"\"];\n    n1 [label=\"\\\"a\\\"\"];\n    n0 -> n1;\n    n2 [label=\"\\\"c\\\"\"];\n    n0 -> n2;\n}\n"
+        );
+        assert_eq!(
+            LlrbTree::<u8, ()>::new().to_dot(),
+            "digraph LlrbTree {\n}\n"
+        );
+    }
+}
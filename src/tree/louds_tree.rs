@@ -0,0 +1,336 @@
+//! This module implements a LOUDS (level-order unary degree sequence) tree, a succinct encoding of a static n-ary tree.
+//! The shape of the tree takes about 2 bits per node instead of a vertex with its pointers: the nodes are numbered in
+//! level order, and each one writes its number of children in unary (d ones, then a zero). Parents, children and siblings
+//! are found with rank and select queries on that bit sequence, so very large read-only trees stay compact.
+//!
+//! # Performance
+//! - O(1) for `get`
+//! - O(log n) for `parent`, `first_child`, `next_sibling` and `child_count`, which go through a select query
+//! - 2n + 1 bits for the shape, plus the rank/select index, plus the node data in a `Vec`
+//!
+//! # Implementation Details
+//! - The sequence starts with `10`, a virtual super-root whose only child is the root, so every node owns exactly one
+//!   one-bit: node `i` is the `i`-th one, and its children are described after the `i`-th zero.
+//! - Nodes are identified by their level-order number, the root being 0, and their data is stored in the same order.
+//! - The tree is built once from a `NaryTree` and can't be changed.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::louds_tree::LoudsTree;
+//! use data_structures::tree::nary_tree::NaryTree;
+//!
+//! let mut tree = NaryTree::new("root");
+//! let root = tree.root();
+//! let a = tree.add_child(&root, "a");
+//! tree.add_child(&root, "b");
+//! tree.add_child(&a, "a1");
+//!
+//! let louds = LoudsTree::from_nary(&tree);
+//!
+//! let a = louds.first_child(0).unwrap();
+//! assert_eq!(louds.get(a), Some(&"a"));
+//! assert_eq!(louds.get(louds.next_sibling(a).unwrap()), Some(&"b"));
+//! assert_eq!(louds.get(louds.first_child(a).unwrap()), Some(&"a1"));
+//! assert_eq!(louds.parent(a), Some(0));
+//! ```
+//!
+use std::collections::VecDeque;
+
+use crate::bits::bit_set::BitSet;
+use crate::bits::rank_select::RankSelect;
+use crate::tree::nary_tree::NaryTree;
+
+/// Struct representing a static n-ary tree encoded in level order with unary degrees
+///
+#[derive(Debug, Clone)]
+pub struct LoudsTree<T> {
+    shape: RankSelect,
+    data: Vec<T>,
+}
+
+impl<T> LoudsTree<T> {
+    /// Encode an n-ary tree, copying its data
+    ///
+    /// # Arguments
+    /// * `tree`: The tree to encode
+    ///
+    /// # Returns
+    /// A new LoudsTree instance with the same shape and data
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::louds_tree::LoudsTree;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let louds = LoudsTree::from_nary(&NaryTree::new(1));
+    /// assert_eq!(louds.len(), 1);
+    /// ```
+    pub fn from_nary(tree: &NaryTree<T>) -> Self
+    where
+        T: Clone,
+    {
+        // Super-root with the root as its only child
+        let mut bits: BitSet = [true, false].into_iter().collect();
+        let mut data = Vec::with_capacity(tree.size());
+        let mut queue = VecDeque::from([tree.root()]);
+
+        while let Some(node) = queue.pop_front() {
+            data.push(node.borrow().read_data().clone().unwrap());
+
+            for child in tree.children(&node) {
+                bits.push(true);
+                queue.push_back(child);
+            }
+            bits.push(false);
+        }
+
+        LoudsTree {
+            shape: RankSelect::new(bits),
+            data,
+        }
+    }
+
+    /// Get the number of nodes
+    /// # Returns
+    /// The number of nodes in the tree, at least 1 since the tree has a root
+    /// # Example
+    /// ```
+    /// use data_structures::tree::louds_tree::LoudsTree;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new('r');
+    /// tree.add_child(&tree.root(), 'c');
+    /// assert_eq!(LoudsTree::from_nary(&tree).len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Check if the tree has no nodes, which never happens for a tree built from an n-ary tree
+    /// # Returns
+    /// True if the tree is empty
+    /// # Example
+    /// ```
+    /// use data_structures::tree::louds_tree::LoudsTree;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// assert!(!LoudsTree::from_nary(&NaryTree::new(())).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Get the data of a node
+    /// # Arguments
+    /// * `node`: The level-order number of the node, 0 for the root
+    /// # Returns
+    /// A reference to the data, or None if there is no such node
+    /// # Example
+    /// ```
+    /// use data_structures::tree::louds_tree::LoudsTree;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let louds = LoudsTree::from_nary(&NaryTree::new("root"));
+    /// assert_eq!(louds.get(0), Some(&"root"));
+    /// assert_eq!(louds.get(1), None);
+    /// ```
+    pub fn get(&self, node: usize) -> Option<&T> {
+        self.data.get(node)
+    }
+
+    /// Get the parent of a node
+    /// # Arguments
+    /// * `node`: The level-order number of the node
+    /// # Returns
+    /// The number of the parent, or None for the root and for nodes that don't exist
+    /// # Example
+    /// ```
+    /// use data_structures::tree::louds_tree::LoudsTree;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// tree.add_child(&tree.root(), 1);
+    /// let louds = LoudsTree::from_nary(&tree);
+    ///
+    /// assert_eq!(louds.parent(1), Some(0));
+    /// assert_eq!(louds.parent(0), None);
+    /// ```
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        if node == 0 || node >= self.len() {
+            return None;
+        }
+
+        // The zeros before the bit of the node close the super-root and the nodes before the parent
+        let position = self.shape.select1(node)?;
+        Some(self.shape.rank0(position) - 1)
+    }
+
+    /// Get the first child of a node
+    /// # Arguments
+    /// * `node`: The level-order number of the node
+    /// # Returns
+    /// The number of the first child, or None for leaves and for nodes that don't exist
+    /// # Example
+    /// ```
+    /// use data_structures::tree::louds_tree::LoudsTree;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new("root");
+    /// tree.add_child(&tree.root(), "leaf");
+    /// let louds = LoudsTree::from_nary(&tree);
+    ///
+    /// assert_eq!(louds.first_child(0), Some(1));
+    /// assert_eq!(louds.first_child(1), None);
+    /// ```
+    pub fn first_child(&self, node: usize) -> Option<usize> {
+        if node >= self.len() {
+            return None;
+        }
+
+        let position = self.shape.select0(node)? + 1;
+        if self.shape.bits().get(position)? {
+            Some(self.shape.rank1(position))
+        } else {
+            None
+        }
+    }
+
+    /// Get the next sibling of a node
+    /// # Arguments
+    /// * `node`: The level-order number of the node
+    /// # Returns
+    /// The number of the sibling on the right, or None for last children, the root and nodes that don't exist
+    /// # Example
+    /// ```
+    /// use data_structures::tree::louds_tree::LoudsTree;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new("root");
+    /// tree.add_child(&tree.root(), "a");
+    /// tree.add_child(&tree.root(), "b");
+    /// let louds = LoudsTree::from_nary(&tree);
+    ///
+    /// assert_eq!(louds.next_sibling(1), Some(2));
+    /// assert_eq!(louds.next_sibling(2), None);
+    /// ```
+    pub fn next_sibling(&self, node: usize) -> Option<usize> {
+        if node >= self.len() {
+            return None;
+        }
+
+        // Siblings are consecutive ones, and a zero ends the list
+        let position = self.shape.select1(node)?;
+        if self.shape.bits().get(position + 1)? {
+            Some(node + 1)
+        } else {
+            None
+        }
+    }
+
+    /// Count the children of a node
+    /// # Arguments
+    /// * `node`: The level-order number of the node
+    /// # Returns
+    /// The number of children, 0 for leaves and for nodes that don't exist
+    /// # Example
+    /// ```
+    /// use data_structures::tree::louds_tree::LoudsTree;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// for child in 1..=3 {
+    ///     tree.add_child(&tree.root(), child);
+    /// }
+    /// assert_eq!(LoudsTree::from_nary(&tree).child_count(0), 3);
+    /// ```
+    pub fn child_count(&self, node: usize) -> usize {
+        if node >= self.len() {
+            return 0;
+        }
+
+        match (self.shape.select0(node), self.shape.select0(node + 1)) {
+            (Some(start), Some(end)) => end - start - 1,
+            _ => 0,
+        }
+    }
+
+    /// Get an iterator over the children of a node
+    /// # Arguments
+    /// * `node`: The level-order number of the node
+    /// # Returns
+    /// A lazy iterator yielding the numbers of the children, from the first to the last
+    /// # Example
+    /// ```
+    /// use data_structures::tree::louds_tree::LoudsTree;
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new("root");
+    /// let a = tree.add_child(&tree.root(), "a");
+    /// tree.add_child(&tree.root(), "b");
+    /// tree.add_child(&a, "a1");
+    /// let louds = LoudsTree::from_nary(&tree);
+    ///
+    /// let children: Vec<_> = louds.children(0).filter_map(|child| louds.get(child)).collect();
+    /// assert_eq!(children, vec![&"a", &"b"]);
+    /// ```
+    pub fn children(&self, node: usize) -> impl Iterator<Item = usize> {
+        let count = self.child_count(node);
+        self.first_child(node)
+            .into_iter()
+            .flat_map(move |first| first..first + count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_louds_matches_nary_tree() {
+        // Root with 3 children, the first and the last with children of their own
+        let mut tree = NaryTree::new(0);
+        let root = tree.root();
+        let one = tree.add_child(&root, 1);
+        tree.add_child(&root, 2);
+        let three = tree.add_child(&root, 3);
+        let four = tree.add_child(&one, 4);
+        tree.add_child(&one, 5);
+        tree.add_child(&three, 6);
+        tree.add_child(&four, 7);
+
+        let louds = LoudsTree::from_nary(&tree);
+
+        // Nodes are numbered in level order, which matches the data here
+        assert_eq!(louds.len(), 8);
+        assert_eq!(louds.shape.len(), 2 * 8 + 1);
+        for node in 0..louds.len() {
+            assert_eq!(louds.get(node), Some(&(node as i32)));
+        }
+
+        let parents: Vec<_> = (0..8).map(|node| louds.parent(node)).collect();
+        assert_eq!(
+            parents,
+            vec![
+                None,
+                Some(0),
+                Some(0),
+                Some(0),
+                Some(1),
+                Some(1),
+                Some(3),
+                Some(4)
+            ]
+        );
+
+        let counts: Vec<_> = (0..8).map(|node| louds.child_count(node)).collect();
+        assert_eq!(counts, vec![3, 2, 0, 1, 1, 0, 0, 0]);
+
+        assert_eq!(louds.children(1).collect::<Vec<_>>(), vec![4, 5]);
+        assert_eq!(louds.first_child(2), None);
+        assert_eq!(louds.next_sibling(3), None);
+        assert_eq!(louds.next_sibling(4), Some(5));
+        assert_eq!(louds.next_sibling(5), None);
+        assert_eq!(louds.next_sibling(0), None);
+        assert_eq!(louds.parent(8), None);
+    }
+}
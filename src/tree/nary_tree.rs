@@ -0,0 +1,535 @@
+//! This module implements a generic n-ary tree using linked list vertexes as nodes.
+//! Each node can have any number of ordered children, and keeps a pointer to its parent, so the tree can be walked in both directions.
+//!
+//! # Performance
+//! - O(1) to add a child to a node
+//! - O(c) to access a child or to detach a subtree, where c is the number of children of the parent
+//! - O(d) to get the depth of a node, where d is the depth itself
+//! - O(1) to get the size of the tree
+//!
+//! # Implementation Details
+//! - Nodes are `Vertex` instances handled through `Shared<Vertex<T>>` pointers.
+//! - The children of a node are stored in indexed custom pointers (`child0`, `child1`, ...) and the parent in the `parent` custom pointer.
+//! - Parent and child pointers form reference cycles, so the tree breaks them when it is dropped. Node pointers kept by the user
+//!   after the tree is dropped don't hold data anymore.
+//! - A detached subtree becomes a new tree, which can be attached again to any node of any tree.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::nary_tree::NaryTree;
+//!
+//! let mut tree = NaryTree::new("root");
+//! let root = tree.root();
+//!
+//! let a = tree.add_child(&root, "a");
+//! tree.add_child(&root, "b");
+//! tree.add_child(&a, "a1");
+//!
+//! assert_eq!(tree.size(), 4);
+//! assert_eq!(tree.height(), 2);
+//!
+//! let children: Vec<&str> = tree
+//!     .children(&root)
+//!     .map(|child| child.borrow().read_data().unwrap())
+//!     .collect();
+//! assert_eq!(children, vec!["a", "b"]);
+//! ```
+//!
+
+use crate::error::DataStructureError;
+use crate::linked_list::shared::{Ptr, Shared};
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+// Name of the pointer holding the child at the given index
+fn child_pointer(index: usize) -> PointerName {
+    PointerName::Custom(format!("child{}", index))
+}
+
+fn parent_pointer() -> PointerName {
+    PointerName::Custom("parent".to_string())
+}
+
+/// Struct representing an n-ary tree made of vertexes
+/// The tree always has a root node. Nodes are identified by their vertex pointers, which are returned when they are added to the tree.
+///
+#[derive(Debug)]
+pub struct NaryTree<T> {
+    root: Option<Shared<Vertex<T>>>, // only None while the tree is being attached to another one
+
+    size: usize,
+}
+
+/// Iterator over the children of a node, from the first to the last one
+pub struct Children<T> {
+    parent: Shared<Vertex<T>>,
+    index: usize,
+}
+
+impl<T> Iterator for Children<T> {
+    type Item = Shared<Vertex<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let child = self.parent.borrow().get_pointer(child_pointer(self.index));
+
+        if child.is_some() {
+            self.index += 1;
+        }
+
+        child
+    }
+}
+
+impl<T> NaryTree<T> {
+    /// Create a new tree with a single root node
+    ///
+    /// # Arguments
+    /// * `data`: The data of the root node
+    ///
+    /// # Returns
+    /// A new NaryTree instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let tree = NaryTree::new(1);
+    /// assert_eq!(tree.size(), 1);
+    /// assert_eq!(tree.root().borrow().read_data().unwrap(), 1);
+    /// ```
+    pub fn new(data: T) -> Self {
+        NaryTree {
+            root: Some(Vertex::new(data)),
+            size: 1,
+        }
+    }
+
+    /// Get a pointer to the root node
+    /// # Returns
+    /// The vertex pointer of the root
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let tree = NaryTree::new("root");
+    /// assert_eq!(tree.root().borrow().read_data().unwrap(), "root");
+    /// ```
+    pub fn root(&self) -> Shared<Vertex<T>> {
+        self.root.clone().unwrap()
+    }
+
+    /// Get the number of nodes in the tree
+    /// # Returns
+    /// The number of nodes, including the root
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// tree.add_child(&tree.root(), 1);
+    /// assert_eq!(tree.size(), 2);
+    /// ```
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Add a new child to a node, after its existing children
+    /// # Arguments
+    /// * `parent`: The node that receives the child. It must belong to this tree.
+    /// * `data`: The data of the new node
+    /// # Returns
+    /// The vertex pointer of the new node
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let child = tree.add_child(&tree.root(), 1);
+    ///
+    /// assert_eq!(tree.child_count(&tree.root()), 1);
+    /// assert_eq!(child.borrow().read_data().unwrap(), 1);
+    /// ```
+    pub fn add_child(&mut self, parent: &Shared<Vertex<T>>, data: T) -> Shared<Vertex<T>> {
+        let child = Vertex::new(data);
+        Self::link(parent, &child);
+        self.size += 1;
+
+        child
+    }
+
+    /// Get an iterator over the children of a node
+    /// # Arguments
+    /// * `node`: The node whose children are returned
+    /// # Returns
+    /// An iterator of vertex pointers, from the first to the last child
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let root = tree.root();
+    /// tree.add_child(&root, 1);
+    /// tree.add_child(&root, 2);
+    ///
+    /// let data: Vec<i32> = tree.children(&root).map(|c| c.borrow().read_data().unwrap()).collect();
+    /// assert_eq!(data, vec![1, 2]);
+    /// ```
+    pub fn children(&self, node: &Shared<Vertex<T>>) -> Children<T> {
+        Children {
+            parent: node.clone(),
+            index: 0,
+        }
+    }
+
+    /// Get the number of children of a node
+    /// # Arguments
+    /// * `node`: The node whose children are counted
+    /// # Returns
+    /// The number of children
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let tree = NaryTree::new(0);
+    /// assert_eq!(tree.child_count(&tree.root()), 0);
+    /// ```
+    pub fn child_count(&self, node: &Shared<Vertex<T>>) -> usize {
+        self.children(node).count()
+    }
+
+    /// Get the parent of a node
+    /// # Arguments
+    /// * `node`: The node whose parent is returned
+    /// # Returns
+    /// The vertex pointer of the parent, or None for the root
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    /// use data_structures::linked_list::shared::Ptr;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let root = tree.root();
+    /// let child = tree.add_child(&root, 1);
+    ///
+    /// assert!(Ptr::ptr_eq(&tree.parent(&child).unwrap(), &root));
+    /// assert!(tree.parent(&root).is_none());
+    /// ```
+    pub fn parent(&self, node: &Shared<Vertex<T>>) -> Option<Shared<Vertex<T>>> {
+        node.borrow().get_pointer(parent_pointer())
+    }
+
+    /// Get the depth of a node, which is the number of edges between the node and the root
+    /// # Arguments
+    /// * `node`: The node whose depth is returned
+    /// # Returns
+    /// The depth of the node, 0 for the root
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let child = tree.add_child(&tree.root(), 1);
+    /// let grandchild = tree.add_child(&child, 2);
+    ///
+    /// assert_eq!(tree.depth(&tree.root()), 0);
+    /// assert_eq!(tree.depth(&grandchild), 2);
+    /// ```
+    pub fn depth(&self, node: &Shared<Vertex<T>>) -> usize {
+        let mut depth = 0;
+        let mut current = self.parent(node);
+
+        while let Some(ancestor) = current {
+            depth += 1;
+            current = self.parent(&ancestor);
+        }
+
+        depth
+    }
+
+    /// Get the height of the tree, which is the depth of its deepest node
+    /// # Returns
+    /// The height of the tree, 0 for a tree with only the root
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let child = tree.add_child(&tree.root(), 1);
+    /// tree.add_child(&child, 2);
+    ///
+    /// assert_eq!(tree.height(), 2);
+    /// ```
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut stack = vec![(self.root(), 0)];
+
+        while let Some((node, depth)) = stack.pop() {
+            height = height.max(depth);
+            stack.extend(self.children(&node).map(|child| (child, depth + 1)));
+        }
+
+        height
+    }
+
+    /// Get the number of nodes of the subtree rooted at a node
+    /// # Arguments
+    /// * `node`: The root of the subtree
+    /// # Returns
+    /// The number of nodes in the subtree, including the node itself
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let child = tree.add_child(&tree.root(), 1);
+    /// tree.add_child(&child, 2);
+    /// tree.add_child(&child, 3);
+    ///
+    /// assert_eq!(tree.subtree_size(&child), 3);
+    /// ```
+    pub fn subtree_size(&self, node: &Shared<Vertex<T>>) -> usize {
+        let mut size = 0;
+        let mut stack = vec![node.clone()];
+
+        while let Some(node) = stack.pop() {
+            size += 1;
+            stack.extend(self.children(&node));
+        }
+
+        size
+    }
+
+    /// Check if a node belongs to the tree
+    /// # Arguments
+    /// * `node`: The node to be checked
+    /// # Returns
+    /// True if the root of the tree is an ancestor of the node (or the node itself), false otherwise
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let other = NaryTree::new(1);
+    /// let child = tree.add_child(&tree.root(), 2);
+    ///
+    /// assert!(tree.contains(&child));
+    /// assert!(!tree.contains(&other.root()));
+    /// ```
+    pub fn contains(&self, node: &Shared<Vertex<T>>) -> bool {
+        let mut current = Some(node.clone());
+
+        while let Some(ancestor) = current {
+            if Ptr::ptr_eq(&ancestor, self.root.as_ref().unwrap()) {
+                return true;
+            }
+            current = self.parent(&ancestor);
+        }
+
+        false
+    }
+
+    /// Detach the subtree rooted at a node and return it as a new tree
+    /// The following siblings of the node are shifted to keep the children of the parent contiguous.
+    /// # Arguments
+    /// * `node`: The root of the subtree to be detached
+    /// # Returns
+    /// Result<NaryTree<T>, DataStructureError>
+    /// Ok with the detached subtree, Err if the node is the root or doesn't belong to the tree
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let child = tree.add_child(&tree.root(), 1);
+    /// tree.add_child(&child, 2);
+    ///
+    /// let subtree = tree.detach(&child).unwrap();
+    ///
+    /// assert_eq!(tree.size(), 1);
+    /// assert_eq!(subtree.size(), 2);
+    /// assert!(tree.detach(&tree.root()).is_err());
+    /// ```
+    pub fn detach(&mut self, node: &Shared<Vertex<T>>) -> Result<NaryTree<T>, DataStructureError> {
+        if Ptr::ptr_eq(node, self.root.as_ref().unwrap()) {
+            return Err(DataStructureError::DetachedRoot);
+        }
+        if !self.contains(node) {
+            return Err(DataStructureError::ForeignNode);
+        }
+
+        let parent = self.parent(node).unwrap();
+        let count = self.child_count(&parent);
+        let index = self
+            .children(&parent)
+            .position(|child| Ptr::ptr_eq(&child, node))
+            .unwrap();
+
+        // Shift the following siblings one position to the left
+        for i in index + 1..count {
+            let sibling = parent.borrow().get_pointer(child_pointer(i));
+            parent
+                .borrow_mut()
+                .set_connection(child_pointer(i - 1), sibling.as_ref());
+        }
+        parent
+            .borrow_mut()
+            .set_connection(child_pointer(count - 1), None);
+        node.borrow_mut().set_connection(parent_pointer(), None);
+
+        let subtree = NaryTree {
+            root: Some(node.clone()),
+            size: self.subtree_size(node),
+        };
+        self.size -= subtree.size;
+
+        Ok(subtree)
+    }
+
+    /// Attach a tree as the last child of a node
+    /// # Arguments
+    /// * `parent`: The node that receives the subtree. It must belong to this tree.
+    /// * `subtree`: The tree to be attached
+    /// # Returns
+    /// Result<Shared<Vertex<T>>, DataStructureError>
+    /// Ok with the pointer to the root of the attached subtree, Err if the parent doesn't belong to the tree
+    /// # Example
+    /// ```
+    /// use data_structures::tree::nary_tree::NaryTree;
+    ///
+    /// let mut tree = NaryTree::new(0);
+    /// let mut subtree = NaryTree::new(1);
+    /// subtree.add_child(&subtree.root(), 2);
+    ///
+    /// tree.attach(&tree.root(), subtree).unwrap();
+    ///
+    /// assert_eq!(tree.size(), 3);
+    /// assert_eq!(tree.height(), 2);
+    /// ```
+    pub fn attach(
+        &mut self,
+        parent: &Shared<Vertex<T>>,
+        mut subtree: NaryTree<T>,
+    ) -> Result<Shared<Vertex<T>>, DataStructureError> {
+        if !self.contains(parent) {
+            return Err(DataStructureError::ForeignNode);
+        }
+
+        // The nodes now belong to this tree, so the subtree must not clear them when dropped
+        let subtree_root = subtree.root.take().unwrap();
+        Self::link(parent, &subtree_root);
+        self.size += subtree.size;
+
+        Ok(subtree_root)
+    }
+
+    // Link a node as the last child of the parent
+    fn link(parent: &Shared<Vertex<T>>, child: &Shared<Vertex<T>>) {
+        let index = Children {
+            parent: parent.clone(),
+            index: 0,
+        }
+        .count();
+
+        parent
+            .borrow_mut()
+            .set_connection(child_pointer(index), Some(child));
+        child
+            .borrow_mut()
+            .set_connection(parent_pointer(), Some(parent));
+    }
+}
+
+impl<T> Drop for NaryTree<T> {
+    fn drop(&mut self) {
+        // Break the parent/child reference cycles iteratively, so deep trees don't overflow the stack
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            stack.extend(self.children(&node));
+            node.borrow_mut().clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nary_tree_structure() {
+        let mut tree = NaryTree::new(0);
+        let root = tree.root();
+
+        let nodes: Vec<_> = (1..=3).map(|i| tree.add_child(&root, i)).collect();
+        for (i, node) in nodes.iter().enumerate() {
+            for j in 0..i {
+                tree.add_child(node, 10 * (i + 1) as i32 + j as i32);
+            }
+        }
+
+        assert_eq!(tree.size(), 7);
+        assert_eq!(tree.height(), 2);
+        assert_eq!(tree.child_count(&root), 3);
+        assert_eq!(tree.child_count(&nodes[2]), 2);
+
+        let grandchild = tree.children(&nodes[2]).last().unwrap();
+        assert_eq!(grandchild.borrow().read_data().unwrap(), 31);
+        assert_eq!(tree.depth(&grandchild), 2);
+        assert!(Ptr::ptr_eq(&tree.parent(&grandchild).unwrap(), &nodes[2]));
+    }
+
+    #[test]
+    fn test_nary_tree_detach_and_attach() {
+        let mut tree = NaryTree::new(0);
+        let root = tree.root();
+
+        let a = tree.add_child(&root, 1);
+        let b = tree.add_child(&root, 2);
+        let c = tree.add_child(&root, 3);
+        tree.add_child(&b, 20);
+
+        let subtree = tree.detach(&b).unwrap();
+        assert_eq!(tree.size(), 3);
+        assert_eq!(subtree.size(), 2);
+        assert!(!tree.contains(&b));
+
+        // The siblings are kept in order
+        let data: Vec<i32> = tree
+            .children(&root)
+            .map(|n| n.borrow().read_data().unwrap())
+            .collect();
+        assert_eq!(data, vec![1, 3]);
+
+        tree.attach(&a, subtree).unwrap();
+        assert_eq!(tree.size(), 5);
+        assert_eq!(tree.depth(&b), 2);
+        assert_eq!(tree.height(), 3);
+        assert!(Ptr::ptr_eq(&tree.parent(&b).unwrap(), &a));
+
+        let other = NaryTree::new(100);
+        assert_eq!(
+            tree.attach(&other.root(), NaryTree::new(0)).unwrap_err(),
+            DataStructureError::ForeignNode
+        );
+        assert!(tree.contains(&c));
+    }
+
+    #[test]
+    fn test_nary_tree_memory_leak() {
+        let mut nodes = Vec::new();
+
+        {
+            let mut tree = NaryTree::new(0);
+            let mut parent = tree.root();
+
+            for i in 1..100 {
+                parent = tree.add_child(&parent, i);
+                nodes.push(Ptr::downgrade(&parent));
+            }
+        }
+
+        // All the vertexes are deallocated when the tree is dropped
+        for node in nodes {
+            assert!(node.upgrade().is_none());
+        }
+    }
+}
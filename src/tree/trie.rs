@@ -0,0 +1,916 @@
+//! This module implements a trie (prefix tree) mapping string keys to values.
+//! Keys sharing a prefix share the path of nodes spelling it, so every key starting with a prefix sits below the node
+//! of that prefix. `starts_with` lists those keys in order, the lookup behind autocompletion, and
+//! `longest_prefix_match` finds the longest key that starts some input, as command dispatchers and routers need.
+//! `find_wildcard` matches keys against a glob-style pattern and `find_within_distance` finds the keys close to a
+//! misspelled word, so spell checkers don't need a second structure.
+//! Every key also has a weight, raised with `bump`, and `suggest` returns the heaviest completions of a prefix first,
+//! as search boxes do.
+//!
+//! # Performance
+//! - O(k) for `insert`, `get`, `contains_key`, `remove` and `longest_prefix_match`, where k is the number of characters
+//!   of the key or input, independent of the number of keys
+//! - O(k) to start `starts_with`, then O(c log c) per node visited, c being its number of children
+//! - O(p) per node visited by `find_wildcard` and O(w) by `find_within_distance`, p being the length of the pattern
+//!   and w the length of the word; branches that can no longer match are never visited
+//! - O(k) for `bump` and `weight`
+//! - O(k + n log n) for `suggest`, n being the number of nodes expanded, roughly the limit times the length of the
+//!   completions, as branches lighter than the suggestions found are never expanded
+//!
+//! # Implementation Details
+//! - Each trie node is a `Vertex` holding the value and the weight of the key ending there, if any, its number of
+//!   children, and the largest weight of the keys below it.
+//! - Transitions are stored in custom pointers named after the character, whose names are listed back with
+//!   `Vertex::connection_names` when the keys below a node are enumerated.
+//! - `starts_with` walks the subtree depth first, visiting children in character order, so keys come out sorted.
+//! - `remove` prunes the nodes left without a value and without children, so the trie never keeps dead branches.
+//! - `find_wildcard` runs the pattern as an automaton along the paths, carrying the set of pattern positions reached,
+//!   and drops a branch once that set is empty.
+//! - `find_within_distance` carries one row of the Levenshtein matrix per node, computed from the row of its parent,
+//!   and drops a branch once the smallest entry of its row exceeds the maximum distance.
+//! - `suggest` is a best-first search: a heap holds the nodes by the largest weight below them and the keys by their
+//!   own weight, so a key is only popped once nothing heavier is left. `bump` raises the largest weights along the path
+//!   of the key, and `remove` recomputes them from the children.
+//! - The empty string is a valid key, stored on the root.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::trie::Trie;
+//!
+//! let mut commands = Trie::new();
+//! commands.insert("git", 1);
+//! commands.insert("git commit", 2);
+//! commands.insert("git checkout", 3);
+//! commands.insert("grep", 4);
+//!
+//! let completions: Vec<String> = commands.starts_with("git c").collect();
+//! assert_eq!(completions, vec!["git checkout", "git commit"]);
+//!
+//! assert_eq!(commands.longest_prefix_match("git commit -m wip"), Some(("git commit", 2)));
+//! assert_eq!(commands.longest_prefix_match("gitk"), Some(("git", 1)));
+//!
+//! assert_eq!(commands.find_wildcard("g?t*t"), vec!["git checkout", "git commit"]);
+//! assert_eq!(commands.find_within_distance("grip", 1), vec![("grep".to_string(), 1)]);
+//!
+//! commands.bump("git commit");
+//! assert_eq!(commands.suggest("git", 2), vec![("git commit".to_string(), 1), ("git".to_string(), 0)]);
+//! ```
+//!
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+use crate::linked_list::shared::Shared;
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+// Name of the pointer holding the transition for the given character
+fn transition_pointer(character: char) -> PointerName {
+    PointerName::Custom(character.to_string())
+}
+
+// Children of a trie node with their characters, in character order
+fn children<V>(vertex: &Vertex<Node<V>>) -> Vec<(char, Link<V>)> {
+    let mut characters: Vec<char> = vertex
+        .connection_names()
+        .filter_map(|name| match name {
+            PointerName::Custom(name) => name.chars().next(),
+            _ => None,
+        })
+        .collect();
+    characters.sort_unstable();
+    characters
+        .into_iter()
+        .map(|character| {
+            let child = vertex.get_pointer(transition_pointer(character)).unwrap();
+            (character, child)
+        })
+        .collect()
+}
+
+// Largest weight of the keys below a node, from its own key and the largest weights of its children
+fn heaviest_below<V>(vertex: &Vertex<Node<V>>) -> u64 {
+    let data = vertex.read_data().as_ref().unwrap();
+    let own = if data.value.is_some() { data.weight } else { 0 };
+    children(vertex)
+        .iter()
+        .map(|(_, child)| child.borrow().read_data().as_ref().unwrap().heaviest)
+        .fold(own, u64::max)
+}
+
+// Positions of a wildcard pattern also reached by letting each `*` match the empty sequence
+fn skip_stars(pattern: &[char], mut positions: Vec<bool>) -> Vec<bool> {
+    for (index, &character) in pattern.iter().enumerate() {
+        if positions[index] && character == '*' {
+            positions[index + 1] = true;
+        }
+    }
+    positions
+}
+
+// Positions of a wildcard pattern reached after reading one more character of the key
+fn step_wildcard(pattern: &[char], positions: &[bool], character: char) -> Vec<bool> {
+    let mut next = vec![false; positions.len()];
+    for (index, &expected) in pattern.iter().enumerate() {
+        if !positions[index] {
+            continue;
+        }
+        match expected {
+            '*' => next[index] = true,
+            '?' => next[index + 1] = true,
+            expected if expected == character => next[index + 1] = true,
+            _ => {}
+        }
+    }
+    skip_stars(pattern, next)
+}
+
+/// Data of a node of the trie
+struct Node<V> {
+    value: Option<V>, // value of the key ending on this node
+    weight: u64,      // weight of the key ending on this node, 0 without a key
+    heaviest: u64,    // largest weight of the keys of the subtree, this node included
+    children: usize,
+}
+
+impl<V> Node<V> {
+    // Node without a key nor children
+    fn new() -> Self {
+        Node {
+            value: None,
+            weight: 0,
+            heaviest: 0,
+            children: 0,
+        }
+    }
+}
+
+type Link<V> = Shared<Vertex<Node<V>>>;
+
+/// Struct representing a map from strings to values, stored in a trie
+///
+pub struct Trie<V> {
+    root: Link<V>,
+    len: usize,
+}
+
+impl<V> Trie<V> {
+    /// Create an empty trie
+    ///
+    /// # Returns
+    /// A new Trie instance
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let trie: Trie<u32> = Trie::new();
+    /// assert!(trie.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Trie {
+            root: Vertex::new(Node::new()),
+            len: 0,
+        }
+    }
+
+    /// Get the number of keys
+    /// # Returns
+    /// The number of keys in the trie
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("to", 1);
+    /// trie.insert("tea", 2);
+    /// assert_eq!(trie.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the trie is empty
+    /// # Returns
+    /// True if there are no keys
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("key", ());
+    /// assert!(!trie.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert a key, or replace its value
+    /// # Arguments
+    /// * `key`: The key
+    /// * `value`: The value to store
+    /// # Returns
+    /// The previous value of the key, if it was present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// assert_eq!(trie.insert("key", 1), None);
+    /// assert_eq!(trie.insert("key", 2), Some(1));
+    /// ```
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        let mut current = self.root.clone();
+
+        for character in key.chars() {
+            let next = current.borrow().get_pointer(transition_pointer(character));
+            current = match next {
+                Some(next) => next,
+                None => {
+                    let next = Vertex::new(Node::new());
+                    let mut vertex = current.borrow_mut();
+                    vertex.set_connection(transition_pointer(character), Some(&next));
+                    vertex.data_mut().unwrap().children += 1;
+                    drop(vertex);
+                    next
+                }
+            };
+        }
+
+        let old = current
+            .borrow_mut()
+            .data_mut()
+            .unwrap()
+            .value
+            .replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Check if a key is present
+    /// # Arguments
+    /// * `key`: The key
+    /// # Returns
+    /// True if the key has a value
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("abc", 1);
+    ///
+    /// assert!(trie.contains_key("abc"));
+    /// assert!(!trie.contains_key("ab"));
+    /// ```
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.find(key)
+            .is_some_and(|node| node.borrow().read_data().as_ref().unwrap().value.is_some())
+    }
+
+    /// Get the value of a key
+    /// # Arguments
+    /// * `key`: The key
+    /// # Returns
+    /// A copy of the value, or None if the key isn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("héllo", "unicode");
+    ///
+    /// assert_eq!(trie.get("héllo"), Some("unicode"));
+    /// assert_eq!(trie.get("hé"), None);
+    /// ```
+    pub fn get(&self, key: &str) -> Option<V>
+    where
+        V: Clone,
+    {
+        let node = self.find(key)?;
+        let value = node.borrow().read_data().as_ref().unwrap().value.clone();
+        value
+    }
+
+    /// Remove a key
+    /// # Arguments
+    /// * `key`: The key
+    /// # Returns
+    /// The value of the key, or None if it wasn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("a", 1);
+    /// trie.insert("abc", 2);
+    ///
+    /// assert_eq!(trie.remove("abc"), Some(2));
+    /// assert_eq!(trie.remove("abc"), None);
+    /// assert_eq!(trie.get("a"), Some(1));
+    /// ```
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        // Path from the root to the node of the key
+        let characters: Vec<char> = key.chars().collect();
+        let mut path = vec![self.root.clone()];
+        for &character in &characters {
+            let next = path
+                .last()
+                .unwrap()
+                .borrow()
+                .get_pointer(transition_pointer(character))?;
+            path.push(next);
+        }
+
+        let value = path
+            .last()
+            .unwrap()
+            .borrow_mut()
+            .data_mut()
+            .unwrap()
+            .value
+            .take()?;
+        path.last().unwrap().borrow_mut().data_mut().unwrap().weight = 0;
+        self.len -= 1;
+
+        // Prune the nodes left with no value and no children, from the bottom up
+        for (depth, &character) in characters.iter().enumerate().rev() {
+            let useless = {
+                let node = path[depth + 1].borrow();
+                let node = node.read_data().as_ref().unwrap();
+                node.value.is_none() && node.children == 0
+            };
+            if !useless {
+                break;
+            }
+
+            let mut parent = path[depth].borrow_mut();
+            parent.set_connection(transition_pointer(character), None);
+            parent.data_mut().unwrap().children -= 1;
+        }
+
+        // Recompute the largest weights from the bottom up, until one doesn't change
+        for node in path.iter().rev() {
+            let heaviest = heaviest_below(&node.borrow());
+            let mut vertex = node.borrow_mut();
+            let data = vertex.data_mut().unwrap();
+            if data.heaviest == heaviest {
+                break;
+            }
+            data.heaviest = heaviest;
+        }
+
+        Some(value)
+    }
+
+    /// Get the weight of a key
+    /// # Arguments
+    /// * `key`: The key
+    /// # Returns
+    /// The weight of the key, 0 until it is bumped, or None if the key isn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("rust", ());
+    ///
+    /// assert_eq!(trie.weight("rust"), Some(0));
+    /// assert_eq!(trie.weight("rus"), None);
+    /// ```
+    pub fn weight(&self, key: &str) -> Option<u64> {
+        let node = self.find(key)?;
+        let vertex = node.borrow();
+        let data = vertex.read_data().as_ref().unwrap();
+        data.value.as_ref().map(|_| data.weight)
+    }
+
+    /// Add one to the weight of a key, as when a suggestion is picked
+    /// Replacing the value of a key keeps its weight, removing it resets the weight.
+    /// # Arguments
+    /// * `key`: The key
+    /// # Returns
+    /// The new weight of the key, or None if the key isn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("rust", ());
+    ///
+    /// assert_eq!(trie.bump("rust"), Some(1));
+    /// assert_eq!(trie.bump("rust"), Some(2));
+    /// assert_eq!(trie.bump("ruby"), None);
+    /// ```
+    pub fn bump(&mut self, key: &str) -> Option<u64> {
+        let mut path = vec![self.root.clone()];
+        for character in key.chars() {
+            let next = path
+                .last()
+                .unwrap()
+                .borrow()
+                .get_pointer(transition_pointer(character))?;
+            path.push(next);
+        }
+
+        let weight = {
+            let mut vertex = path.last().unwrap().borrow_mut();
+            let data = vertex.data_mut().unwrap();
+            data.value.as_ref()?;
+            data.weight = data.weight.saturating_add(1);
+            data.weight
+        };
+
+        // Weights only grow here, so the largest ones along the path are raised
+        for node in &path {
+            let mut vertex = node.borrow_mut();
+            let data = vertex.data_mut().unwrap();
+            data.heaviest = data.heaviest.max(weight);
+        }
+        Some(weight)
+    }
+
+    /// Get the heaviest keys starting with a prefix
+    /// # Arguments
+    /// * `prefix`: The prefix of the keys, the empty string matching every key
+    /// * `limit`: The largest number of suggestions
+    /// # Returns
+    /// Up to `limit` matching keys with their weight, from the heaviest to the lightest, keys of equal weight in sorted
+    /// order
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// for word in ["car", "cart", "care", "cat", "dog"] {
+    ///     trie.insert(word, ());
+    /// }
+    /// trie.bump("cat");
+    /// trie.bump("cat");
+    /// trie.bump("cart");
+    /// trie.bump("dog");
+    ///
+    /// let suggestions = trie.suggest("ca", 3);
+    /// assert_eq!(
+    ///     suggestions,
+    ///     vec![("cat".to_string(), 2), ("cart".to_string(), 1), ("car".to_string(), 0)]
+    /// );
+    /// assert!(trie.suggest("x", 3).is_empty());
+    /// ```
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Vec<(String, u64)> {
+        let mut suggestions = Vec::new();
+        let Some(start) = self.find(prefix) else {
+            return suggestions;
+        };
+
+        // The heap holds nodes by the largest weight below them and keys by their own weight. On ties the smallest key
+        // goes first, and a key before the nodes below it, whose keys are longer.
+        let mut nodes = vec![start];
+        let heaviest = nodes[0].borrow().read_data().as_ref().unwrap().heaviest;
+        let mut open = BinaryHeap::new();
+        open.push((heaviest, Reverse(prefix.to_string()), false, 0));
+
+        while suggestions.len() < limit {
+            let Some((weight, Reverse(key), is_key, index)) = open.pop() else {
+                break;
+            };
+            if is_key {
+                suggestions.push((key, weight));
+                continue;
+            }
+
+            let node = nodes[index].clone();
+            let vertex = node.borrow();
+            let data = vertex.read_data().as_ref().unwrap();
+            if data.value.is_some() {
+                open.push((data.weight, Reverse(key.clone()), true, index));
+            }
+            for (character, child) in children(&vertex) {
+                let heaviest = child.borrow().read_data().as_ref().unwrap().heaviest;
+                let mut child_key = key.clone();
+                child_key.push(character);
+                open.push((heaviest, Reverse(child_key), false, nodes.len()));
+                nodes.push(child);
+            }
+        }
+        suggestions
+    }
+
+    /// Get an iterator over the keys starting with a prefix
+    /// # Arguments
+    /// * `prefix`: The prefix of the keys, the empty string matching every key
+    /// # Returns
+    /// An iterator of the matching keys in sorted order, the prefix itself included if it is a key
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// for word in ["car", "cart", "care", "cat", "dog"] {
+    ///     trie.insert(word, ());
+    /// }
+    ///
+    /// assert_eq!(trie.starts_with("car").collect::<Vec<_>>(), vec!["car", "care", "cart"]);
+    /// assert_eq!(trie.starts_with("cow").count(), 0);
+    /// assert_eq!(trie.starts_with("").count(), 5);
+    /// ```
+    pub fn starts_with(&self, prefix: &str) -> Keys<V> {
+        Keys {
+            stack: self
+                .find(prefix)
+                .map(|node| (node, prefix.to_string()))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Find the longest key that is a prefix of some input
+    /// # Arguments
+    /// * `input`: The string to match
+    /// # Returns
+    /// The longest matching key, as a slice of the input, and a copy of its value, or None if no key is a prefix of the
+    /// input
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut routes = Trie::new();
+    /// routes.insert("/", "index");
+    /// routes.insert("/api/", "api");
+    /// routes.insert("/api/users/", "users");
+    ///
+    /// assert_eq!(routes.longest_prefix_match("/api/users/42"), Some(("/api/users/", "users")));
+    /// assert_eq!(routes.longest_prefix_match("/api/orders"), Some(("/api/", "api")));
+    /// assert_eq!(routes.longest_prefix_match("api"), None);
+    /// ```
+    pub fn longest_prefix_match<'a>(&self, input: &'a str) -> Option<(&'a str, V)>
+    where
+        V: Clone,
+    {
+        let mut current = self.root.clone();
+        let mut best = None;
+
+        for (offset, character) in input.char_indices() {
+            if let Some(value) = &current.borrow().read_data().as_ref().unwrap().value {
+                best = Some((&input[..offset], value.clone()));
+            }
+
+            let next = current.borrow().get_pointer(transition_pointer(character));
+            match next {
+                Some(next) => current = next,
+                None => return best,
+            }
+        }
+
+        if let Some(value) = &current.borrow().read_data().as_ref().unwrap().value {
+            best = Some((input, value.clone()));
+        }
+        best
+    }
+
+    /// Find the keys matching a glob-style pattern
+    /// `?` matches any single character and `*` any sequence of characters, the empty one included. Every other
+    /// character matches itself.
+    /// # Arguments
+    /// * `pattern`: The pattern the whole key must match
+    /// # Returns
+    /// The matching keys in sorted order
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// for word in ["foobar", "fooba", "fobar", "foxbaz", "bar"] {
+    ///     trie.insert(word, ());
+    /// }
+    ///
+    /// assert_eq!(trie.find_wildcard("fo?ba*"), vec!["fooba", "foobar", "foxbaz"]);
+    /// assert_eq!(trie.find_wildcard("*bar"), vec!["bar", "fobar", "foobar"]);
+    /// assert!(trie.find_wildcard("?").is_empty());
+    /// ```
+    pub fn find_wildcard(&self, pattern: &str) -> Vec<String> {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let mut start = vec![false; pattern.len() + 1];
+        start[0] = true;
+
+        let mut matches = Vec::new();
+        let mut stack = vec![(
+            self.root.clone(),
+            String::new(),
+            skip_stars(&pattern, start),
+        )];
+        while let Some((node, key, positions)) = stack.pop() {
+            let vertex = node.borrow();
+            if positions[pattern.len()] && vertex.read_data().as_ref().unwrap().value.is_some() {
+                matches.push(key.clone());
+            }
+
+            // Children are pushed from the last character to the first, so keys come out sorted
+            for (character, child) in children(&vertex).into_iter().rev() {
+                let next = step_wildcard(&pattern, &positions, character);
+                if next.contains(&true) {
+                    let mut child_key = key.clone();
+                    child_key.push(character);
+                    stack.push((child, child_key, next));
+                }
+            }
+        }
+        matches
+    }
+
+    /// Find the keys within some edit distance of a word
+    /// The distance is the Levenshtein one, counted in characters, as in `bk_tree::levenshtein`.
+    /// # Arguments
+    /// * `word`: The word to compare the keys with
+    /// * `max_distance`: The largest distance allowed
+    /// # Returns
+    /// The matching keys in sorted order, with their distance to the word
+    /// # Example
+    /// ```
+    /// use data_structures::tree::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// for word in ["book", "books", "cook", "boo", "cake"] {
+    ///     trie.insert(word, ());
+    /// }
+    ///
+    /// let close: Vec<(String, usize)> = trie.find_within_distance("bok", 1);
+    /// assert_eq!(close, vec![("boo".to_string(), 1), ("book".to_string(), 1)]);
+    /// assert_eq!(trie.find_within_distance("cake", 0), vec![("cake".to_string(), 0)]);
+    /// ```
+    pub fn find_within_distance(&self, word: &str, max_distance: usize) -> Vec<(String, usize)> {
+        let word: Vec<char> = word.chars().collect();
+
+        // Each node carries the distances from its key to every prefix of the word
+        let mut matches = Vec::new();
+        let mut stack = vec![(
+            self.root.clone(),
+            String::new(),
+            (0..=word.len()).collect::<Vec<usize>>(),
+        )];
+        while let Some((node, key, row)) = stack.pop() {
+            let vertex = node.borrow();
+            let distance = row[word.len()];
+            if distance <= max_distance && vertex.read_data().as_ref().unwrap().value.is_some() {
+                matches.push((key.clone(), distance));
+            }
+
+            for (character, child) in children(&vertex).into_iter().rev() {
+                let mut next = Vec::with_capacity(row.len());
+                next.push(row[0] + 1);
+                for (j, word_char) in word.iter().enumerate() {
+                    let substitution = row[j] + usize::from(character != *word_char);
+                    next.push(substitution.min(row[j + 1] + 1).min(next[j] + 1));
+                }
+
+                // Distances only grow below a node, so the branch is dropped once all of them are too large
+                if next.iter().min().is_some_and(|&min| min <= max_distance) {
+                    let mut child_key = key.clone();
+                    child_key.push(character);
+                    stack.push((child, child_key, next));
+                }
+            }
+        }
+        matches
+    }
+
+    // Node reached by following a key from the root
+    fn find(&self, key: &str) -> Option<Link<V>> {
+        let mut current = self.root.clone();
+        for character in key.chars() {
+            let next = current
+                .borrow()
+                .get_pointer(transition_pointer(character))?;
+            current = next;
+        }
+        Some(current)
+    }
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> fmt::Debug for Trie<V> {
+    /// Prints the number of keys, the trie itself being a graph of vertexes
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trie").field("len", &self.len).finish()
+    }
+}
+
+/// Iterator over the keys below a node of a trie, in sorted order
+///
+pub struct Keys<V> {
+    stack: Vec<(Link<V>, String)>, // nodes left to visit with their key, the next one last
+}
+
+impl<V> Iterator for Keys<V> {
+    type Item = String;
+
+    /// Returns the next key in sorted order
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, key)) = self.stack.pop() {
+            let vertex = node.borrow();
+
+            // Children are pushed from the last character to the first, so the first is visited next
+            for (character, child) in children(&vertex).into_iter().rev() {
+                let mut child_key = key.clone();
+                child_key.push(character);
+                self.stack.push((child, child_key));
+            }
+
+            if vertex.read_data().as_ref().unwrap().value.is_some() {
+                return Some(key);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+    use crate::tree::bk_tree::levenshtein;
+    use std::collections::BTreeMap;
+
+    // Reference glob matcher, trying every split of the key for each `*`
+    fn glob(pattern: &[char], key: &[char]) -> bool {
+        match pattern.split_first() {
+            None => key.is_empty(),
+            Some(('*', rest)) => (0..=key.len()).any(|skip| glob(rest, &key[skip..])),
+            Some((&expected, rest)) => key.split_first().is_some_and(|(&first, key)| {
+                (expected == '?' || expected == first) && glob(rest, key)
+            }),
+        }
+    }
+
+    #[test]
+    fn test_trie_against_btree_map() {
+        let mut trie = Trie::new();
+        let mut expected = BTreeMap::new();
+        let mut rng = Lcg::new(29);
+        let alphabet = ['a', 'b', 'é', '字'];
+
+        for round in 0..2000u32 {
+            let seed = rng.next_u64();
+            // Short keys over a small alphabet, so they share prefixes
+            let key: String = (0..(seed >> 60) as usize % 5)
+                .map(|i| alphabet[(seed >> (8 * i + 16)) as usize % alphabet.len()])
+                .collect();
+
+            if (seed >> 40).is_multiple_of(3) {
+                assert_eq!(trie.remove(&key), expected.remove(&key));
+            } else {
+                assert_eq!(
+                    trie.insert(&key, round),
+                    expected.insert(key.clone(), round)
+                );
+            }
+            assert_eq!(trie.len(), expected.len());
+            assert_eq!(trie.get(&key), expected.get(&key).copied());
+
+            // Prefixes of the key, from the longest down
+            let prefixes: Vec<usize> = key
+                .char_indices()
+                .map(|(offset, _)| offset)
+                .chain([key.len()])
+                .rev()
+                .collect();
+            let longest = prefixes
+                .iter()
+                .find_map(|&end| expected.get(&key[..end]).map(|&value| (&key[..end], value)));
+            assert_eq!(trie.longest_prefix_match(&key), longest);
+
+            let prefix = &key[..prefixes[prefixes.len() / 2]];
+            let matching: Vec<String> = expected
+                .range(prefix.to_string()..)
+                .map(|(key, _)| key.clone())
+                .take_while(|key| key.starts_with(prefix))
+                .collect();
+            assert_eq!(trie.starts_with(prefix).collect::<Vec<_>>(), matching);
+        }
+    }
+
+    #[test]
+    fn test_remove_prunes_branches() {
+        let mut trie = Trie::new();
+        trie.insert("ab", 1);
+        trie.insert("abcd", 2);
+
+        trie.remove("abcd");
+        // Only the branch of "ab" is left
+        let b = trie.find("ab").unwrap();
+        assert_eq!(b.borrow().read_data().as_ref().unwrap().children, 0);
+        assert_eq!(b.borrow().connection_names().count(), 0);
+
+        trie.remove("ab");
+        assert_eq!(trie.root.borrow().read_data().as_ref().unwrap().children, 0);
+        assert!(trie.is_empty());
+        assert_eq!(trie.starts_with("").count(), 0);
+    }
+
+    #[test]
+    fn test_wildcard_and_fuzzy_search() {
+        let mut trie = Trie::new();
+        let mut rng = Lcg::new(31);
+        let alphabet = ['a', 'b', 'c', 'é'];
+        let mut random_string = |symbols: &[char]| -> String {
+            let seed = rng.next_u64();
+            (0..(seed >> 60) as usize % 6)
+                .map(|i| symbols[(seed >> (6 * i + 8)) as usize % symbols.len()])
+                .collect()
+        };
+
+        let mut keys = Vec::new();
+        for _ in 0..300 {
+            let key = random_string(&alphabet);
+            trie.insert(&key, ());
+            keys.push(key);
+        }
+        keys.sort();
+        keys.dedup();
+
+        for _ in 0..300 {
+            let pattern = random_string(&['a', 'b', 'é', '?', '*']);
+            let pattern_chars: Vec<char> = pattern.chars().collect();
+            let expected: Vec<String> = keys
+                .iter()
+                .filter(|key| glob(&pattern_chars, &key.chars().collect::<Vec<_>>()))
+                .cloned()
+                .collect();
+            assert_eq!(trie.find_wildcard(&pattern), expected, "{}", pattern);
+
+            let word = random_string(&alphabet);
+            for max_distance in 0..3 {
+                let expected: Vec<(String, usize)> = keys
+                    .iter()
+                    .map(|key| (key.clone(), levenshtein(key, &word)))
+                    .filter(|(_, distance)| *distance <= max_distance)
+                    .collect();
+                assert_eq!(trie.find_within_distance(&word, max_distance), expected);
+            }
+        }
+    }
+
+    // Check that every node knows the largest weight of its subtree, returning the one of the given node
+    fn check_heaviest(node: &Link<u32>) -> u64 {
+        let vertex = node.borrow();
+        let data = vertex.read_data().as_ref().unwrap();
+        let own = if data.value.is_some() { data.weight } else { 0 };
+        let heaviest = children(&vertex)
+            .iter()
+            .map(|(_, child)| check_heaviest(child))
+            .fold(own, u64::max);
+        assert_eq!(data.heaviest, heaviest);
+        heaviest
+    }
+
+    #[test]
+    fn test_suggest_against_sorted_model() {
+        let mut trie = Trie::new();
+        let mut expected: BTreeMap<String, u64> = BTreeMap::new();
+        let mut rng = Lcg::new(37);
+        let alphabet = ['a', 'b', 'c', 'é'];
+
+        for round in 0..3000u32 {
+            let seed = rng.next_u64();
+            let key: String = (0..(seed >> 60) as usize % 5)
+                .map(|i| alphabet[(seed >> (6 * i + 8)) as usize % alphabet.len()])
+                .collect();
+
+            match (seed >> 40) % 6 {
+                0 => {
+                    trie.remove(&key);
+                    expected.remove(&key);
+                }
+                1 | 2 => {
+                    trie.insert(&key, round);
+                    expected.entry(key.clone()).or_insert(0);
+                }
+                _ => {
+                    let weight = expected.get_mut(&key).map(|weight| {
+                        *weight += 1;
+                        *weight
+                    });
+                    assert_eq!(trie.bump(&key), weight);
+                }
+            }
+            assert_eq!(trie.weight(&key), expected.get(&key).copied());
+            check_heaviest(&trie.root);
+
+            let prefix: String = key.chars().take((seed >> 20) as usize % 3).collect();
+            let limit = (seed >> 24) as usize % 6;
+            let mut matching: Vec<(String, u64)> = expected
+                .iter()
+                .filter(|(key, _)| key.starts_with(&prefix))
+                .map(|(key, weight)| (key.clone(), *weight))
+                .collect();
+            matching.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            matching.truncate(limit);
+            assert_eq!(trie.suggest(&prefix, limit), matching);
+        }
+    }
+}
@@ -0,0 +1,662 @@
+//! This module implements a weak AVL (WAVL) tree, the rank-balanced tree of Haeupler, Sen and Tarjan, as an ordered map.
+//! Every node has a rank, and the rank difference between a node and each of its children is 1 or 2, with leaves at rank
+//! 0. Built by insertions only, a WAVL tree is an AVL tree; deletions relax the shape but never need more than two
+//! rotations, where an AVL tree can rotate at every level. The tree counts its rotations so the difference can be
+//! measured.
+//!
+//! # Performance
+//! - O(log n) for `insert`, `get` and `remove`, the height is at most 2 log2(n) (1.44 log2(n) without deletions)
+//! - At most 2 rotations per insertion and per deletion, O(1) amortized rank changes
+//!
+//! # Implementation Details
+//! - Nodes are `Vertex` instances handled through `Shared<Vertex<T>>` pointers, using the `Left` and `Right` pointers
+//!   for the children. A missing child has rank -1.
+//! - Insertion and deletion are recursive. On the way back each node checks the rank difference of the child it came
+//!   from: 0 after an insertion and 3 (or a leaf left at rank 1) after a deletion are fixed by promotions, demotions,
+//!   and at most one single or double rotation.
+//! - A node with two children is removed by moving its successor's entry into it and removing the successor instead.
+//!
+//! # Usage
+//! ```
+//! use data_structures::tree::wavl_tree::WavlTree;
+//!
+//! let mut tree: WavlTree<u32, u32> = (0..1000).map(|key| (key, key * 2)).collect();
+//! assert_eq!(tree.get(&21), Some(42));
+//! assert!(tree.height() <= 15);
+//!
+//! tree.reset_counters();
+//! for key in 0..500 {
+//!     tree.remove(&key);
+//! }
+//!
+//! assert_eq!(tree.len(), 500);
+//! assert!(tree.counters().remove <= 2 * 500);
+//! ```
+//!
+use std::cmp::Ordering;
+use std::fmt;
+use std::mem;
+
+use crate::linked_list::shared::Shared;
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+/// Number of rotations done by a tree, by operation
+/// A double rotation counts as two.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RotationCounters {
+    pub insert: u64,
+    pub remove: u64,
+}
+
+// Data of a node
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    rank: i32,
+}
+
+type Link<K, V> = Shared<Vertex<Entry<K, V>>>;
+
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    fn pointer(self) -> PointerName {
+        match self {
+            Side::Left => PointerName::Left,
+            Side::Right => PointerName::Right,
+        }
+    }
+
+    fn opposite(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+/// Struct representing a weak AVL tree mapping ordered keys to values
+///
+pub struct WavlTree<K, V> {
+    root: Option<Link<K, V>>,
+    size: usize,
+
+    counters: RotationCounters,
+}
+
+/// In-order iterator over the entries of a tree
+pub struct Iter<K, V> {
+    stack: Vec<Link<K, V>>,
+    current: Option<Link<K, V>>,
+}
+
+fn child<K, V>(node: &Link<K, V>, side: Side) -> Option<Link<K, V>> {
+    node.borrow().get_pointer(side.pointer())
+}
+
+fn set_child<K, V>(node: &Link<K, V>, side: Side, child: Option<&Link<K, V>>) {
+    node.borrow_mut().set_connection(side.pointer(), child);
+}
+
+fn rank<K, V>(node: Option<&Link<K, V>>) -> i32 {
+    node.map_or(-1, |node| node.borrow().read_data().as_ref().unwrap().rank)
+}
+
+// Add to the rank of a node, negative amounts demote it
+fn promote<K, V>(node: &Link<K, V>, amount: i32) {
+    node.borrow_mut().data_mut().unwrap().rank += amount;
+}
+
+fn is_leaf<K, V>(node: &Link<K, V>) -> bool {
+    child(node, Side::Left).is_none() && child(node, Side::Right).is_none()
+}
+
+fn compare<K: Ord, V>(key: &K, node: &Link<K, V>) -> Ordering {
+    key.cmp(&node.borrow().read_data().as_ref().unwrap().key)
+}
+
+// Lift the child on one side above the node, returning the new root of the subtree
+fn rotate<K, V>(node: &Link<K, V>, side: Side) -> Link<K, V> {
+    let lifted = child(node, side).unwrap();
+    set_child(node, side, child(&lifted, side.opposite()).as_ref());
+    set_child(&lifted, side.opposite(), Some(node));
+    lifted
+}
+
+// Lift the inner grandchild on one side above the node, returning the new root of the subtree
+fn rotate_double<K, V>(node: &Link<K, V>, side: Side) -> Link<K, V> {
+    let lifted_child = rotate(&child(node, side).unwrap(), side.opposite());
+    set_child(node, side, Some(&lifted_child));
+    rotate(node, side)
+}
+
+impl<K: Clone, V: Clone> Iterator for Iter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.current.take() {
+            self.current = child(&node, Side::Left);
+            self.stack.push(node);
+        }
+
+        let node = self.stack.pop()?;
+        self.current = child(&node, Side::Right);
+
+        let vertex = node.borrow();
+        let entry = vertex.read_data().as_ref().unwrap();
+        Some((entry.key.clone(), entry.value.clone()))
+    }
+}
+
+impl<K: Ord, V> WavlTree<K, V> {
+    /// Create a new empty tree
+    ///
+    /// # Returns
+    /// A new WavlTree instance, with its counters at zero
+    ///
+    /// # Example
+    /// ```
+    /// use data_structures::tree::wavl_tree::WavlTree;
+    ///
+    /// let tree: WavlTree<String, u8> = WavlTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        WavlTree {
+            root: None,
+            size: 0,
+            counters: RotationCounters::default(),
+        }
+    }
+
+    /// Get the number of entries in the tree
+    /// # Returns
+    /// The number of keys stored
+    /// # Example
+    /// ```
+    /// use data_structures::tree::wavl_tree::WavlTree;
+    ///
+    /// let tree: WavlTree<u8, ()> = [(1, ()), (2, ()), (1, ())].into_iter().collect();
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the tree is empty
+    /// # Returns
+    /// True if the tree has no entries
+    /// # Example
+    /// ```
+    /// use data_structures::tree::wavl_tree::WavlTree;
+    ///
+    /// let mut tree = WavlTree::default();
+    /// tree.insert(1, 1);
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Get the number of rotations done since the tree was created or the counters were reset
+    /// # Returns
+    /// The rotations done by insertions and by deletions
+    /// # Example
+    /// ```
+    /// use data_structures::tree::wavl_tree::{RotationCounters, WavlTree};
+    ///
+    /// let mut tree = WavlTree::new();
+    /// tree.insert(1, ());
+    /// tree.insert(2, ());
+    /// tree.insert(3, ());
+    ///
+    /// assert_eq!(tree.counters(), RotationCounters { insert: 1, remove: 0 });
+    /// ```
+    pub fn counters(&self) -> RotationCounters {
+        self.counters
+    }
+
+    /// Set the rotation counters back to zero
+    /// # Example
+    /// ```
+    /// use data_structures::tree::wavl_tree::{RotationCounters, WavlTree};
+    ///
+    /// let mut tree: WavlTree<u8, ()> = (0..10).map(|key| (key, ())).collect();
+    /// tree.reset_counters();
+    /// assert_eq!(tree.counters(), RotationCounters::default());
+    /// ```
+    pub fn reset_counters(&mut self) {
+        self.counters = RotationCounters::default();
+    }
+
+    /// Insert a key with its value, replacing the value if the key is already present
+    /// # Arguments
+    /// * `key`: The key of the entry
+    /// * `value`: The value of the entry
+    /// # Returns
+    /// The previous value of the key, or None if it wasn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::wavl_tree::WavlTree;
+    ///
+    /// let mut tree = WavlTree::new();
+    /// assert_eq!(tree.insert('k', 1), None);
+    /// assert_eq!(tree.insert('k', 2), Some(1));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut previous = None;
+        self.root = Some(self.insert_at(self.root.clone(), key, value, &mut previous));
+
+        if previous.is_none() {
+            self.size += 1;
+        }
+        previous
+    }
+
+    /// Check if a key is in the tree
+    /// # Arguments
+    /// * `key`: The key to look for
+    /// # Returns
+    /// True if the key is present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::wavl_tree::WavlTree;
+    ///
+    /// let mut tree = WavlTree::new();
+    /// tree.insert("a", ());
+    /// assert!(tree.contains_key(&"a"));
+    /// assert!(!tree.contains_key(&"b"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Get the value of a key
+    /// # Arguments
+    /// * `key`: The key to look for
+    /// # Returns
+    /// A clone of the value, or None if the key isn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::wavl_tree::WavlTree;
+    ///
+    /// let mut tree = WavlTree::new();
+    /// tree.insert(3, "three");
+    /// assert_eq!(tree.get(&3), Some("three"));
+    /// assert_eq!(tree.get(&4), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.find(key)
+            .map(|node| node.borrow().read_data().as_ref().unwrap().value.clone())
+    }
+
+    /// Remove a key from the tree
+    /// # Arguments
+    /// * `key`: The key to remove
+    /// # Returns
+    /// The value of the key, or None if it wasn't present
+    /// # Example
+    /// ```
+    /// use data_structures::tree::wavl_tree::WavlTree;
+    ///
+    /// let mut tree: WavlTree<i32, i32> = (0..5).map(|key| (key, -key)).collect();
+    ///
+    /// assert_eq!(tree.remove(&2), Some(-2));
+    /// assert_eq!(tree.remove(&2), None);
+    /// assert_eq!(tree.len(), 4);
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let root = self.root.take()?;
+
+        let mut removed = None;
+        self.root = self.remove_at(root, key, &mut removed);
+
+        if removed.is_some() {
+            self.size -= 1;
+        }
+        removed
+    }
+
+    /// Get the height of the tree, which is the number of nodes in the longest path from the root to a leaf
+    /// # Returns
+    /// The height of the tree, 0 for an empty tree
+    /// # Example
+    /// ```
+    /// use data_structures::tree::wavl_tree::WavlTree;
+    ///
+    /// let tree: WavlTree<u32, ()> = (0..7).map(|key| (key, ())).collect();
+    /// assert_eq!(tree.height(), 3);
+    /// ```
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut stack: Vec<_> = self.root.iter().map(|root| (root.clone(), 1)).collect();
+
+        while let Some((node, depth)) = stack.pop() {
+            height = height.max(depth);
+            stack.extend(child(&node, Side::Left).map(|left| (left, depth + 1)));
+            stack.extend(child(&node, Side::Right).map(|right| (right, depth + 1)));
+        }
+
+        height
+    }
+
+    /// Get an in-order iterator over the entries of the tree
+    /// # Returns
+    /// A lazy iterator yielding clones of the keys and values, by increasing key
+    /// # Example
+    /// ```
+    /// use data_structures::tree::wavl_tree::WavlTree;
+    ///
+    /// let tree: WavlTree<u8, char> = [(2, 'b'), (1, 'a')].into_iter().collect();
+    /// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![(1, 'a'), (2, 'b')]);
+    /// ```
+    pub fn iter(&self) -> Iter<K, V> {
+        Iter {
+            stack: Vec::new(),
+            current: self.root.clone(),
+        }
+    }
+
+    // Find the node holding a key
+    fn find(&self, key: &K) -> Option<Link<K, V>> {
+        let mut current = self.root.clone();
+
+        while let Some(node) = current {
+            current = match compare(key, &node) {
+                Ordering::Less => child(&node, Side::Left),
+                Ordering::Greater => child(&node, Side::Right),
+                Ordering::Equal => return Some(node),
+            };
+        }
+
+        None
+    }
+
+    // Fix a node whose child on one side may have reached its rank, returning the new root of the subtree
+    fn rebalance_insert(&mut self, node: Link<K, V>, side: Side) -> Link<K, V> {
+        let grown = child(&node, side).unwrap();
+        let node_rank = rank(Some(&node));
+
+        if rank(Some(&grown)) != node_rank {
+            return node;
+        }
+
+        if node_rank - rank(child(&node, side.opposite()).as_ref()) == 1 {
+            promote(&node, 1);
+            return node;
+        }
+
+        // The sibling is a 2-child, one or two rotations restore the ranks and stop the fix-up
+        if node_rank - rank(child(&grown, side.opposite()).as_ref()) == 2 {
+            let root = rotate(&node, side);
+            promote(&node, -1);
+            self.counters.insert += 1;
+            root
+        } else {
+            let root = rotate_double(&node, side);
+            promote(&root, 1);
+            promote(&grown, -1);
+            promote(&node, -1);
+            self.counters.insert += 2;
+            root
+        }
+    }
+
+    // Fix a node whose child on one side may have lost a rank, returning the new root of the subtree
+    fn rebalance_remove(&mut self, node: Link<K, V>, side: Side) -> Link<K, V> {
+        let node_rank = rank(Some(&node));
+
+        if is_leaf(&node) {
+            // A leaf must have rank 0
+            if node_rank == 1 {
+                promote(&node, -1);
+            }
+            return node;
+        }
+
+        if node_rank - rank(child(&node, side).as_ref()) != 3 {
+            return node;
+        }
+
+        let sibling = child(&node, side.opposite()).unwrap();
+        let sibling_rank = rank(Some(&sibling));
+        if node_rank - sibling_rank == 2 {
+            promote(&node, -1);
+            return node;
+        }
+
+        let inner_rank = rank(child(&sibling, side).as_ref());
+        let outer_rank = rank(child(&sibling, side.opposite()).as_ref());
+        if sibling_rank - inner_rank == 2 && sibling_rank - outer_rank == 2 {
+            promote(&node, -1);
+            promote(&sibling, -1);
+            return node;
+        }
+
+        // The sibling has a 1-child, one or two rotations restore the ranks and stop the fix-up
+        if sibling_rank - outer_rank == 1 {
+            let root = rotate(&node, side.opposite());
+            promote(&root, 1);
+            promote(&node, if is_leaf(&node) { -2 } else { -1 });
+            self.counters.remove += 1;
+            root
+        } else {
+            let root = rotate_double(&node, side.opposite());
+            promote(&root, 2);
+            promote(&node, -2);
+            promote(&sibling, -1);
+            self.counters.remove += 2;
+            root
+        }
+    }
+
+    fn insert_at(
+        &mut self,
+        node: Option<Link<K, V>>,
+        key: K,
+        value: V,
+        previous: &mut Option<V>,
+    ) -> Link<K, V> {
+        let Some(node) = node else {
+            return Vertex::new(Entry {
+                key,
+                value,
+                rank: 0,
+            });
+        };
+
+        let side = match compare(&key, &node) {
+            Ordering::Less => Side::Left,
+            Ordering::Greater => Side::Right,
+            Ordering::Equal => {
+                *previous = Some(mem::replace(
+                    &mut node.borrow_mut().data_mut().unwrap().value,
+                    value,
+                ));
+                return node;
+            }
+        };
+
+        let subtree = self.insert_at(child(&node, side), key, value, previous);
+        set_child(&node, side, Some(&subtree));
+
+        self.rebalance_insert(node, side)
+    }
+
+    // Remove the smallest entry of a subtree, returning the new root of the subtree
+    fn remove_min_at(
+        &mut self,
+        node: Link<K, V>,
+        removed: &mut Option<(K, V)>,
+    ) -> Option<Link<K, V>> {
+        let Some(left) = child(&node, Side::Left) else {
+            let right = child(&node, Side::Right);
+            let entry = node.borrow_mut().clear().unwrap();
+            *removed = Some((entry.key, entry.value));
+            return right;
+        };
+
+        let subtree = self.remove_min_at(left, removed);
+        set_child(&node, Side::Left, subtree.as_ref());
+
+        Some(self.rebalance_remove(node, Side::Left))
+    }
+
+    fn remove_at(
+        &mut self,
+        node: Link<K, V>,
+        key: &K,
+        removed: &mut Option<V>,
+    ) -> Option<Link<K, V>> {
+        let side = match compare(key, &node) {
+            Ordering::Less => Side::Left,
+            Ordering::Greater => Side::Right,
+            Ordering::Equal => return self.remove_node(node, removed),
+        };
+
+        let Some(next) = child(&node, side) else {
+            return Some(node);
+        };
+
+        let subtree = self.remove_at(next, key, removed);
+        set_child(&node, side, subtree.as_ref());
+
+        Some(self.rebalance_remove(node, side))
+    }
+
+    // Remove the entry of a node, returning the new root of its subtree
+    fn remove_node(&mut self, node: Link<K, V>, removed: &mut Option<V>) -> Option<Link<K, V>> {
+        let left = child(&node, Side::Left);
+        let right = child(&node, Side::Right);
+
+        let Some(right) = right.filter(|_| left.is_some()) else {
+            // A node with at most one child is replaced by it
+            let replacement = left.or(child(&node, Side::Right));
+            *removed = Some(node.borrow_mut().clear().unwrap().value);
+            return replacement;
+        };
+
+        let mut successor = None;
+        let subtree = self.remove_min_at(right, &mut successor);
+        set_child(&node, Side::Right, subtree.as_ref());
+
+        let (successor_key, successor_value) = successor.unwrap();
+        {
+            let mut vertex = node.borrow_mut();
+            let entry = vertex.data_mut().unwrap();
+            entry.key = successor_key;
+            *removed = Some(mem::replace(&mut entry.value, successor_value));
+        }
+
+        Some(self.rebalance_remove(node, Side::Right))
+    }
+}
+
+impl<K: Ord, V> Default for WavlTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for WavlTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = WavlTree::new();
+        for (key, value) in iter {
+            tree.insert(key, value);
+        }
+        tree
+    }
+}
+
+impl<K: Ord + Clone + fmt::Debug, V: Clone + fmt::Debug> fmt::Debug for WavlTree<K, V> {
+    /// Prints the entries as a map, by increasing key, instead of the web of vertex pointers
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Lcg;
+
+    // Check the rank rule and the key order below a node
+    fn check<K: Ord, V>(node: &Link<K, V>) {
+        let node_rank = rank(Some(node));
+
+        if is_leaf(node) {
+            assert_eq!(node_rank, 0, "leaf with a non-zero rank");
+        }
+
+        for side in [Side::Left, Side::Right] {
+            let child = child(node, side);
+            let difference = node_rank - rank(child.as_ref());
+            assert!(
+                difference == 1 || difference == 2,
+                "rank difference {}",
+                difference
+            );
+
+            if let Some(child) = child {
+                let order = compare(&child.borrow().read_data().as_ref().unwrap().key, node);
+                let expected = match side {
+                    Side::Left => Ordering::Less,
+                    Side::Right => Ordering::Greater,
+                };
+                assert_eq!(order, expected);
+                check(&child);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wavl_invariants() {
+        let mut tree = WavlTree::new();
+        let mut reference = std::collections::BTreeMap::new();
+        let mut rng = Lcg::new(3);
+
+        for _ in 0..3000 {
+            let seed = rng.next_u64();
+            let key = (seed >> 33) % 300;
+
+            if (seed >> 20).is_multiple_of(3) {
+                let before = tree.counters().remove;
+                assert_eq!(tree.remove(&key), reference.remove(&key));
+                assert!(tree.counters().remove - before <= 2);
+            } else {
+                let before = tree.counters().insert;
+                assert_eq!(tree.insert(key, seed), reference.insert(key, seed));
+                assert!(tree.counters().insert - before <= 2);
+            }
+
+            if let Some(root) = &tree.root {
+                check(root);
+            }
+            assert_eq!(tree.len(), reference.len());
+        }
+
+        assert!(tree.iter().eq(reference.clone()));
+        for key in reference.keys() {
+            tree.remove(key);
+        }
+        assert!(tree.is_empty());
+        assert!(tree.root.is_none());
+    }
+
+    #[test]
+    fn test_wavl_avl_height_without_deletions() {
+        // Without deletions the tree is an AVL tree: height below 1.44 log2(n + 2)
+        let tree: WavlTree<u32, ()> = (0..4096).map(|key| (key, ())).collect();
+        assert!(tree.height() as f64 <= 1.44 * (4098f64).log2());
+
+        let root = tree.root.as_ref().unwrap();
+        check(root);
+    }
+}
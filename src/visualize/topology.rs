@@ -0,0 +1,431 @@
+//! This module exports the topology of vertex based structures as Graphviz DOT or Mermaid text, for debugging.
+//! A `Topology` is a snapshot of the vertexes reachable from some roots: each vertex with its data printed by `Debug`,
+//! and each named pointer as an edge labelled with its name. Pointers whose inverse is missing, like a `Right` link
+//! not answered by a `Left` link, and vertexes whose data was cleared are highlighted, so broken links stand out.
+//! `CircularQueue::topology`, `LinkedList::topology` and `Graph::topology` build the snapshot of a whole structure.
+//!
+//! # Performance
+//! - O(V + E) to build a topology, V and E being the reachable vertexes and pointers
+//! - O(V + E) to render it, in either format
+//!
+//! # Implementation Details
+//! - Vertexes are walked breadth first from the roots in order, and identified by the address of their allocation, so
+//!   cycles and shared vertexes are visited once. They are numbered `n0`, `n1`, ... in the order they are reached.
+//! - The pointers of a vertex are listed with `Vertex::connection_names` and sorted by name, so the output is stable.
+//! - `Left`/`Right`, `Previous`/`Next` and `From`/`To` are inverses: a pointer is broken when its target doesn't point
+//!   back with the inverse. Other pointers, such as the `Custom` edges of a graph, have no inverse and are never broken.
+//! - Edge weights set with `Vertex::set_edge` are appended to the label, except `()`.
+//!
+//! # Usage
+//! ```
+//! use data_structures::linked_list::circular_queue::{CircularQueue, Direction};
+//!
+//! let mut queue = CircularQueue::new(0);
+//! queue.insert(1, Direction::Right).unwrap();
+//! queue.insert(2, Direction::Right).unwrap();
+//!
+//! let topology = queue.topology();
+//! assert_eq!(topology.node_count(), 2);
+//! assert!(topology.broken_links().is_empty());
+//!
+//! let dot = topology.to_dot();
+//! assert!(dot.starts_with("digraph {"));
+//! assert!(dot.contains("n0 -> n1 [label=\"Right\"];"));
+//!
+//! let mermaid = topology.to_mermaid();
+//! assert!(mermaid.contains("n1 -->|Left| n0"));
+//! ```
+//!
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Write};
+
+use crate::linked_list::shared::{Ptr, Shared};
+use crate::linked_list::vertex::{PointerName, Vertex};
+
+/// Data of a vertex of a topology
+#[derive(Debug, Clone)]
+struct Node {
+    label: String,
+    cleared: bool, // the vertex holds no data anymore
+}
+
+/// Data of a pointer of a topology
+#[derive(Debug, Clone)]
+struct Edge {
+    from: usize,
+    to: usize,
+    label: String,
+    broken: bool, // the target doesn't point back with the inverse pointer
+}
+
+// A pointer read from a vertex: its label, its name and its target
+type Pointer<T, W> = (String, PointerName, Shared<Vertex<T, W>>);
+
+/// Struct representing a snapshot of the vertexes and pointers of a structure
+///
+#[derive(Debug, Clone, Default)]
+pub struct Topology {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+impl Topology {
+    /// Take a snapshot of the vertexes reachable from some roots
+    /// # Arguments
+    /// * `roots`: The vertexes to start from, numbered first in this order
+    /// # Returns
+    /// A new Topology instance
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::vertex::{PointerName, Vertex};
+    /// use data_structures::visualize::topology::Topology;
+    ///
+    /// let a = Vertex::new("a");
+    /// let b = Vertex::new("b");
+    /// a.borrow_mut().set_connection(PointerName::Next, Some(&b));
+    ///
+    /// // b has no Previous pointer back to a
+    /// let topology = Topology::from_vertices([&a]);
+    /// assert_eq!(topology.node_count(), 2);
+    /// assert_eq!(topology.broken_links(), vec![(0, 1, "Next".to_string())]);
+    /// ```
+    pub fn from_vertices<'a, T, W, I>(roots: I) -> Self
+    where
+        T: fmt::Debug + 'a,
+        W: fmt::Debug + 'a,
+        I: IntoIterator<Item = &'a Shared<Vertex<T, W>>>,
+    {
+        let mut topology = Topology::default();
+        let mut ids: HashMap<usize, usize> = HashMap::new();
+        let mut queue: VecDeque<Shared<Vertex<T, W>>> = VecDeque::new();
+
+        for root in roots {
+            topology.visit(root, &mut ids, &mut queue);
+        }
+
+        while let Some(vertex) = queue.pop_front() {
+            let from = ids[&address(&vertex)];
+
+            // Read the pointers first, the targets are borrowed afterwards to check their inverse
+            let mut pointers: Vec<Pointer<T, W>> = {
+                let node = vertex.borrow();
+                node.connection_names()
+                    .map(|name| {
+                        let mut label = pointer_label(name);
+                        if let Some(weight) = node.edge_weight(name) {
+                            let weight = format!("{:?}", weight);
+                            if weight != "()" {
+                                label = format!("{}: {}", label, weight);
+                            }
+                        }
+                        (label, name.clone(), node.get_pointer(name.clone()).unwrap())
+                    })
+                    .collect()
+            };
+            pointers.sort_by(|a, b| a.0.cmp(&b.0));
+
+            for (label, name, target) in pointers {
+                let to = topology.visit(&target, &mut ids, &mut queue);
+                let broken = inverse(&name).is_some_and(|inverse| {
+                    let back = target.borrow().get_pointer(inverse);
+                    !back.is_some_and(|back| Ptr::ptr_eq(&back, &vertex))
+                });
+                topology.edges.push(Edge {
+                    from,
+                    to,
+                    label,
+                    broken,
+                });
+            }
+        }
+        topology
+    }
+
+    /// Get the number of vertexes
+    /// # Returns
+    /// The number of vertexes reachable from the roots
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let list: LinkedList<u32> = (0..5).collect();
+    /// assert_eq!(list.topology().node_count(), 5);
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Get the number of pointers
+    /// # Returns
+    /// The number of pointers between the vertexes
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let list: LinkedList<u32> = (0..5).collect();
+    /// assert_eq!(list.topology().edge_count(), 8);
+    /// ```
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Get the broken pointers
+    /// # Returns
+    /// The source, the target and the label of each pointer whose target doesn't point back with the inverse pointer
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::vertex::{PointerName, Vertex};
+    /// use data_structures::visualize::topology::Topology;
+    ///
+    /// let a = Vertex::new(1);
+    /// let b = Vertex::new(2);
+    /// a.borrow_mut().set_connection(PointerName::Right, Some(&b));
+    /// b.borrow_mut().set_connection(PointerName::Left, Some(&a));
+    /// assert!(Topology::from_vertices([&a]).broken_links().is_empty());
+    ///
+    /// b.borrow_mut().set_connection(PointerName::Left, None);
+    /// assert_eq!(Topology::from_vertices([&a]).broken_links().len(), 1);
+    /// ```
+    pub fn broken_links(&self) -> Vec<(usize, usize, String)> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.broken)
+            .map(|edge| (edge.from, edge.to, edge.label.clone()))
+            .collect()
+    }
+
+    /// Render the topology in the Graphviz DOT language
+    /// # Returns
+    /// A `digraph` with a node per vertex labelled with its data, and an edge per pointer labelled with its name.
+    /// Cleared vertexes are red and broken pointers are red and dashed
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let list: LinkedList<&str> = vec!["a", "b"].into_iter().collect();
+    /// assert_eq!(
+    ///     list.topology().to_dot(),
+    ///     "digraph {\n    n0 [label=\"\\\"a\\\"\"];\n    n1 [label=\"\\\"b\\\"\"];\n    \
+    ///      n0 -> n1 [label=\"Right\"];\n    n1 -> n0 [label=\"Left\"];\n}\n"
+    /// );
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let style = if node.cleared { ", color=red" } else { "" };
+            writeln!(
+                dot,
+                "    n{} [label=\"{}\"{}];",
+                id,
+                escape_dot(&node.label),
+                style
+            )
+            .unwrap();
+        }
+        for edge in &self.edges {
+            let style = if edge.broken {
+                ", color=red, style=dashed"
+            } else {
+                ""
+            };
+            writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{}\"{}];",
+                edge.from,
+                edge.to,
+                escape_dot(&edge.label),
+                style
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Render the topology as a Mermaid flowchart
+    /// # Returns
+    /// A left to right `flowchart` with a node per vertex and a labelled link per pointer. Cleared vertexes get the
+    /// `cleared` class and broken pointers are dotted red links
+    /// # Example
+    /// ```
+    /// use data_structures::linked_list::list::LinkedList;
+    ///
+    /// let list: LinkedList<u8> = vec![1, 2].into_iter().collect();
+    /// assert_eq!(
+    ///     list.topology().to_mermaid(),
+    ///     "flowchart LR\n    n0[\"1\"]\n    n1[\"2\"]\n    n0 -->|Right| n1\n    n1 -->|Left| n0\n"
+    /// );
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let class = if node.cleared { ":::cleared" } else { "" };
+            writeln!(
+                mermaid,
+                "    n{}[\"{}\"]{}",
+                id,
+                escape_mermaid(&node.label),
+                class
+            )
+            .unwrap();
+        }
+        for edge in &self.edges {
+            let arrow = if edge.broken { "-.->" } else { "-->" };
+            writeln!(
+                mermaid,
+                "    n{} {}|{}| n{}",
+                edge.from,
+                arrow,
+                escape_mermaid(&edge.label),
+                edge.to
+            )
+            .unwrap();
+        }
+
+        // Links are styled by their index in the order they were declared
+        for (index, edge) in self.edges.iter().enumerate() {
+            if edge.broken {
+                writeln!(mermaid, "    linkStyle {} stroke:red", index).unwrap();
+            }
+        }
+        if self.nodes.iter().any(|node| node.cleared) {
+            mermaid.push_str("    classDef cleared stroke:red\n");
+        }
+        mermaid
+    }
+
+    // Number a vertex the first time it is reached and queue it, returning its number
+    fn visit<T: fmt::Debug, W>(
+        &mut self,
+        vertex: &Shared<Vertex<T, W>>,
+        ids: &mut HashMap<usize, usize>,
+        queue: &mut VecDeque<Shared<Vertex<T, W>>>,
+    ) -> usize {
+        if let Some(&id) = ids.get(&address(vertex)) {
+            return id;
+        }
+
+        let id = self.nodes.len();
+        ids.insert(address(vertex), id);
+        let node = match vertex.borrow().read_data() {
+            Some(data) => Node {
+                label: format!("{:?}", data),
+                cleared: false,
+            },
+            None => Node {
+                label: "<cleared>".to_string(),
+                cleared: true,
+            },
+        };
+        self.nodes.push(node);
+        queue.push_back(vertex.clone());
+        id
+    }
+}
+
+// Address of the allocation of a vertex, identifying it while it is alive
+fn address<T, W>(vertex: &Shared<Vertex<T, W>>) -> usize {
+    Ptr::as_ptr(vertex) as *const () as usize
+}
+
+// Name printed for a pointer, custom names without their wrapper
+fn pointer_label(name: &PointerName) -> String {
+    match name {
+        PointerName::Custom(name) => name.clone(),
+        other => format!("{:?}", other),
+    }
+}
+
+// Pointer expected back on the target of a pointer, None if the pointer has no inverse
+fn inverse(name: &PointerName) -> Option<PointerName> {
+    match name {
+        PointerName::Left => Some(PointerName::Right),
+        PointerName::Right => Some(PointerName::Left),
+        PointerName::Previous => Some(PointerName::Next),
+        PointerName::Next => Some(PointerName::Previous),
+        PointerName::From => Some(PointerName::To),
+        PointerName::To => Some(PointerName::From),
+        _ => None,
+    }
+}
+
+// Escape a label for a double quoted DOT string
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Escape a label for Mermaid, which uses HTML entity codes
+fn escape_mermaid(label: &str) -> String {
+    label.replace('"', "#quot;").replace('|', "#124;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::vertex_graph::{EdgeKind, Graph};
+    use crate::linked_list::circular_queue::{CircularQueue, Direction};
+
+    #[test]
+    fn test_topology_flags_broken_links_and_cleared_vertexes() {
+        let a = Vertex::new(1);
+        let b = Vertex::new(2);
+        let c = Vertex::new(3);
+        a.borrow_mut().set_connection(PointerName::Next, Some(&b));
+        b.borrow_mut()
+            .set_connection(PointerName::Previous, Some(&a));
+        b.borrow_mut().set_connection(PointerName::Next, Some(&c));
+        c.borrow_mut().clear();
+
+        let topology = Topology::from_vertices([&a]);
+        assert_eq!(topology.node_count(), 3);
+        assert_eq!(topology.edge_count(), 3);
+        assert_eq!(topology.broken_links(), vec![(1, 2, "Next".to_string())]);
+
+        let dot = topology.to_dot();
+        assert!(dot.contains("n2 [label=\"<cleared>\", color=red];"));
+        assert!(dot.contains("n1 -> n2 [label=\"Next\", color=red, style=dashed];"));
+
+        let mermaid = topology.to_mermaid();
+        assert!(mermaid.contains("n2[\"<cleared>\"]:::cleared"));
+        assert!(mermaid.contains("n1 -.->|Next| n2"));
+        assert!(mermaid.contains("linkStyle 1 stroke:red"));
+
+        a.borrow_mut().clear();
+        b.borrow_mut().clear();
+    }
+
+    #[test]
+    fn test_topology_of_structures() {
+        // A ring of 3: each vertex points left and right
+        let queue: CircularQueue<u8> = vec![1, 2, 3].into_iter().collect();
+        let topology = queue.topology();
+        assert_eq!(topology.node_count(), 3);
+        assert_eq!(topology.edge_count(), 6);
+        assert!(topology.broken_links().is_empty());
+
+        // Graph vertexes keep their index, and weights are printed after the pointer name
+        let mut graph = Graph::weighted();
+        let x = graph.add_vertex("x");
+        let y = graph.add_vertex("y");
+        graph
+            .add_weighted_edge(y, x, 2.5, EdgeKind::Directed)
+            .unwrap();
+        let dot = graph.topology().to_dot();
+        assert!(dot.contains("n1 -> n0 [label=\"edge0: 2.5\"];"));
+        assert_eq!(graph.topology().broken_links().len(), 0);
+
+        let mut unweighted = Graph::new();
+        let a = unweighted.add_vertex('a');
+        let b = unweighted.add_vertex('b');
+        unweighted.add_edge(a, b, EdgeKind::Undirected).unwrap();
+        assert!(unweighted
+            .topology()
+            .to_mermaid()
+            .contains("n0 -->|edge1| n1"));
+
+        let mut empty = CircularQueue::<u8>::new(0);
+        empty.insert(1, Direction::Left).unwrap();
+        empty.remove(Direction::Left);
+        assert_eq!(empty.topology().node_count(), 0);
+    }
+}